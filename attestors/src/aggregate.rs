@@ -0,0 +1,215 @@
+//! Plain BLS aggregate signatures over one message by independent signers
+//!
+//! Unlike the threshold path there is no shared key: every signer has its own BLS12-381
+//! key pair (public key in G1, signature in G2) and all of them must sign. Aggregates are
+//! only verified against [`ProvenPublicKey`]s, whose owners proved possession of the secret
+//! key, since otherwise a signer can pick a key that cancels out the others.
+
+use bls12_381::hash_to_curve::{ExpandMsgXmd, HashToCurve};
+use bls12_381::{multi_miller_loop, G1Affine, G1Projective, G2Affine, G2Prepared, G2Projective, Gt, Scalar};
+use crate::error::AttestorError;
+use crate::threshold::{decode_g1, decode_g2};
+
+/// Domain separation tag of the proof-of-possession BLS ciphersuite with signatures in G2
+pub const AGGREGATE_DST: &[u8] = b"BLS_SIG_BLS12381G2_XMD:SHA-256_SSWU_RO_POP_";
+
+/// Domain separation tag of proofs of possession in the same ciphersuite
+pub const POSSESSION_DST: &[u8] = b"BLS_POP_BLS12381G2_XMD:SHA-256_SSWU_RO_POP_";
+
+/// Public key whose owner proved possession of its secret key, and so may be aggregated
+#[derive(Debug, Clone, PartialEq)]
+pub struct ProvenPublicKey {
+    key: G1Affine,
+}
+
+impl ProvenPublicKey {
+    /// Accept a compressed G1 public key only with a valid proof of possession for it
+    pub fn new(public_key: &[u8], proof: &[u8]) -> Result<Self, AttestorError> {
+        if !verify_possession(public_key, proof)? {
+            return Err(AttestorError::InvalidSignature("Invalid proof of possession".to_string()));
+        }
+        Ok(Self { key: decode_g1(public_key)? })
+    }
+
+    /// Compressed G1 public key
+    pub fn to_bytes(&self) -> Vec<u8> {
+        self.key.to_compressed().to_vec()
+    }
+}
+
+/// Sign a message for aggregation with a 32-byte BLS12-381 secret scalar
+pub fn sign_for_aggregate(message: &[u8], secret_key: &[u8]) -> Result<Vec<u8>, AttestorError> {
+    let secret_key = decode_scalar(secret_key)?;
+    let signature = hash_to_g2(message, AGGREGATE_DST) * secret_key;
+    Ok(G2Affine::from(signature).to_compressed().to_vec())
+}
+
+/// Prove possession of a secret key by signing its own public key under [`POSSESSION_DST`]
+pub fn prove_possession(secret_key: &[u8]) -> Result<Vec<u8>, AttestorError> {
+    let public_key = aggregate_public_key(secret_key)?;
+    let proof = hash_to_g2(&public_key, POSSESSION_DST) * decode_scalar(secret_key)?;
+    Ok(G2Affine::from(proof).to_compressed().to_vec())
+}
+
+/// Check a proof of possession for a compressed G1 public key
+pub fn verify_possession(public_key: &[u8], proof: &[u8]) -> Result<bool, AttestorError> {
+    let key = decode_g1(public_key)?;
+    if bool::from(key.is_identity()) {
+        return Ok(false);
+    }
+    let proof = match decode_g2(proof) {
+        Ok(proof) if !bool::from(proof.is_identity()) => proof,
+        _ => return Ok(false),
+    };
+
+    // e(pk, H_pop(pk)) * e(-g1, proof) == 1
+    let key_hash = G2Prepared::from(G2Affine::from(hash_to_g2(public_key, POSSESSION_DST)));
+    let result = multi_miller_loop(&[
+        (&key, &key_hash),
+        (&-G1Affine::generator(), &G2Prepared::from(proof)),
+    ])
+    .final_exponentiation();
+    Ok(result == Gt::identity())
+}
+
+/// Compressed G1 public key for a secret scalar
+pub fn aggregate_public_key(secret_key: &[u8]) -> Result<Vec<u8>, AttestorError> {
+    let public_key = G1Projective::generator() * decode_scalar(secret_key)?;
+    Ok(G1Affine::from(public_key).to_compressed().to_vec())
+}
+
+/// Add individual signatures into one aggregate signature
+pub fn aggregate_signatures(signatures: &[Vec<u8>]) -> Result<Vec<u8>, AttestorError> {
+    if signatures.is_empty() {
+        return Err(AttestorError::InvalidSignature("No signatures to aggregate".to_string()));
+    }
+
+    let mut aggregate = G2Projective::identity();
+    for signature in signatures {
+        let point = decode_g2(signature)?;
+        if bool::from(point.is_identity()) {
+            return Err(AttestorError::InvalidSignature("Identity signature cannot be aggregated".to_string()));
+        }
+        aggregate += point;
+    }
+    Ok(G2Affine::from(aggregate).to_compressed().to_vec())
+}
+
+/// Verify an aggregate signature by distinct signers over one message.
+///
+/// Checks `e(pk_1, H(m)) * ... * e(pk_n, H(m)) * e(-g1, sig) == 1` in a single multi-pairing.
+pub fn verify_aggregate(message: &[u8], aggregate_signature: &[u8], public_keys: &[ProvenPublicKey]) -> Result<bool, AttestorError> {
+    if public_keys.is_empty() {
+        return Err(AttestorError::InvalidSignature("No public keys to verify against".to_string()));
+    }
+    for (index, public_key) in public_keys.iter().enumerate() {
+        if public_keys[..index].contains(public_key) {
+            return Err(AttestorError::InvalidSignature("Aggregate signers must be distinct".to_string()));
+        }
+    }
+
+    let signature = match decode_g2(aggregate_signature) {
+        Ok(signature) if !bool::from(signature.is_identity()) => signature,
+        _ => return Ok(false),
+    };
+
+    let message_hash = G2Prepared::from(G2Affine::from(hash_to_g2(message, AGGREGATE_DST)));
+    let signature = G2Prepared::from(signature);
+    let neg_generator = -G1Affine::generator();
+
+    let mut terms: Vec<(&G1Affine, &G2Prepared)> = public_keys.iter().map(|key| (&key.key, &message_hash)).collect();
+    terms.push((&neg_generator, &signature));
+
+    Ok(multi_miller_loop(&terms).final_exponentiation() == Gt::identity())
+}
+
+/// Hash a message to G2 under a domain separation tag of the ciphersuite
+fn hash_to_g2(message: &[u8], dst: &[u8]) -> G2Projective {
+    <G2Projective as HashToCurve<ExpandMsgXmd<sha2_v09::Sha256>>>::hash_to_curve(message, dst)
+}
+
+/// Decode a little-endian secret scalar, rejecting zero
+fn decode_scalar(bytes: &[u8]) -> Result<Scalar, AttestorError> {
+    let bytes: [u8; 32] = bytes.try_into()
+        .map_err(|_| AttestorError::CryptoError("Invalid secret key length".to_string()))?;
+    Option::<Scalar>::from(Scalar::from_bytes(&bytes))
+        .filter(|scalar| !bool::from(ff::Field::is_zero(scalar)))
+        .ok_or_else(|| AttestorError::CryptoError("Invalid secret key".to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn secret_key(seed: u64) -> Vec<u8> {
+        Scalar::from(seed).to_bytes().to_vec()
+    }
+
+    fn proven(secret_key: &[u8]) -> ProvenPublicKey {
+        ProvenPublicKey::new(&aggregate_public_key(secret_key).unwrap(), &prove_possession(secret_key).unwrap()).unwrap()
+    }
+
+    #[test]
+    fn three_signers_aggregate_and_verify() {
+        let keys: Vec<Vec<u8>> = (1..=3).map(|seed| secret_key(seed * 7919)).collect();
+        let signatures: Vec<Vec<u8>> = keys.iter().map(|key| sign_for_aggregate(b"endorse", key).unwrap()).collect();
+        let public_keys: Vec<ProvenPublicKey> = keys.iter().map(|key| proven(key)).collect();
+
+        let aggregate = aggregate_signatures(&signatures).unwrap();
+
+        assert!(verify_aggregate(b"endorse", &aggregate, &public_keys).unwrap());
+        assert!(!verify_aggregate(b"other", &aggregate, &public_keys).unwrap());
+    }
+
+    #[test]
+    fn wrong_public_key_fails() {
+        let keys: Vec<Vec<u8>> = (1..=3).map(|seed| secret_key(seed * 7919)).collect();
+        let signatures: Vec<Vec<u8>> = keys.iter().map(|key| sign_for_aggregate(b"endorse", key).unwrap()).collect();
+        let aggregate = aggregate_signatures(&signatures).unwrap();
+
+        let public_keys = vec![proven(&keys[0]), proven(&keys[1]), proven(&secret_key(104729))];
+
+        assert!(!verify_aggregate(b"endorse", &aggregate, &public_keys).unwrap());
+    }
+
+    #[test]
+    fn rogue_key_cannot_prove_possession() {
+        let honest = secret_key(7919);
+        let attacker = decode_scalar(&secret_key(104729)).unwrap();
+
+        // pk_rogue = g1 * x - pk_honest, so pk_honest + pk_rogue = g1 * x and H(m) * x alone
+        // would satisfy the aggregate equation for both keys
+        let honest_key = decode_g1(&aggregate_public_key(&honest).unwrap()).unwrap();
+        let rogue_key = G1Affine::from(G1Projective::generator() * attacker - G1Projective::from(honest_key));
+        let rogue_bytes = rogue_key.to_compressed().to_vec();
+        let forged_proof = G2Affine::from(hash_to_g2(&rogue_bytes, POSSESSION_DST) * attacker).to_compressed().to_vec();
+
+        assert!(!verify_possession(&rogue_bytes, &forged_proof).unwrap());
+        assert!(ProvenPublicKey::new(&rogue_bytes, &forged_proof).is_err());
+
+        // Only the possession check stops the forgery: bypassing it, the equation holds
+        let forged = G2Affine::from(hash_to_g2(b"endorse", AGGREGATE_DST) * attacker).to_compressed().to_vec();
+        let unchecked = [ProvenPublicKey { key: honest_key }, ProvenPublicKey { key: rogue_key }];
+        assert!(verify_aggregate(b"endorse", &forged, &unchecked).unwrap());
+    }
+
+    #[test]
+    fn proofs_are_bound_to_their_key_and_domain() {
+        let first = secret_key(7919);
+        let second = secret_key(104729);
+        let first_key = aggregate_public_key(&first).unwrap();
+
+        assert!(verify_possession(&first_key, &prove_possession(&first).unwrap()).unwrap());
+        assert!(!verify_possession(&first_key, &prove_possession(&second).unwrap()).unwrap());
+        // A message signature over the key bytes is not a proof of possession
+        assert!(!verify_possession(&first_key, &sign_for_aggregate(&first_key, &first).unwrap()).unwrap());
+    }
+
+    #[test]
+    fn duplicate_signers_are_rejected() {
+        let key = secret_key(7919);
+        let aggregate = aggregate_signatures(&[sign_for_aggregate(b"endorse", &key).unwrap()]).unwrap();
+
+        assert!(verify_aggregate(b"endorse", &aggregate, &[proven(&key), proven(&key)]).is_err());
+    }
+}
@@ -2,11 +2,18 @@
 
 use serde::{Deserialize, Serialize};
 use chrono::{DateTime, Utc};
-use std::collections::HashMap;
-use identity_core::VerifiableCredential;
+use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
+use identity_core::{hash_data, system_clock, ClaimAttestation, Clock, DidDocument, DidResolver, DomainEvent, EventBus, VerifiableCredential};
+use identity_core::utils::generate_nonce;
 use crate::threshold::{ThresholdScheme, KeyShare, PartialSignature, ThresholdSignature, ThresholdPublicKey};
 use crate::verifier::Verifier;
+use crate::certificate::QuorumCertificate;
+use crate::challenge::{AttestorChallenge, ChallengeResponse};
+use crate::escalation::EscalationPolicy;
+use crate::signature_cache::PartialSignatureCache;
 use crate::error::AttestorError;
+use crate::webhook::WebhookNotifier;
 
 /// Attestation request for a credential
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -17,6 +24,9 @@ pub struct AttestationRequest {
     pub threshold: usize,
     pub created_at: DateTime<Utc>,
     pub expires_at: Option<DateTime<Utc>>,
+    /// DID or identifier of the party that submitted the request
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub requester: Option<String>,
 }
 
 /// Individual attestation from a verifier
@@ -62,6 +72,9 @@ pub enum AttestationResultStatus {
     Completed,
     Failed,
     Expired,
+    Cancelled,
+    /// Approved by the escalation authority rather than a threshold signature
+    Escalated,
 }
 
 /// Attestation manager for coordinating multiparty attestations
@@ -72,6 +85,21 @@ pub struct AttestationManager {
     pub threshold_public_key: ThresholdPublicKey,
     pub pending_requests: HashMap<String, AttestationRequest>,
     pub attestations: HashMap<String, Vec<Attestation>>,
+    pub cancelled_requests: HashMap<String, AttestationResult>,
+    pub in_flight: HashMap<String, HashSet<String>>, // verifier_id -> assigned request ids awaiting a response
+    pub event_bus: Option<EventBus>,
+    pub webhook_notifier: Option<WebhookNotifier>,
+    pub clock: Arc<dyn Clock>,
+    pub assignment_counts: HashMap<String, usize>,   // verifier_id -> total assignments
+    pub challenges: HashMap<String, HashMap<String, AttestorChallenge>>, // request_id -> attestor_id -> open challenge
+    pub proven_attestors: HashMap<String, HashSet<String>>, // request_id -> attestors that proved DID control
+    /// Whether attestors must answer a DID control challenge before attesting; off by default
+    pub require_did_control: bool,
+    pub escalation_policy: Option<EscalationPolicy>,
+    pub escalated_requests: HashMap<String, DateTime<Utc>>, // request_id -> when it was escalated
+    /// Grace period after a request first meets its threshold to collect further signatures
+    pub batching_window: chrono::Duration,
+    pub threshold_reached: HashMap<String, DateTime<Utc>>, // request_id -> when approvals first met the threshold
 }
 
 impl AttestationRequest {
@@ -81,40 +109,67 @@ impl AttestationRequest {
         required_attestors: Vec<String>,
         threshold: usize,
     ) -> Self {
+        Self::new_with_clock(credential, required_attestors, threshold, &identity_core::SystemClock)
+    }
+
+    /// Create a new attestation request timestamped by the given clock
+    pub fn new_with_clock(
+        credential: VerifiableCredential,
+        required_attestors: Vec<String>,
+        threshold: usize,
+        clock: &dyn Clock,
+    ) -> Self {
+        let now = clock.now();
         Self {
             id: uuid::Uuid::new_v4().to_string(),
             credential,
             required_attestors,
             threshold,
-            created_at: Utc::now(),
-            expires_at: Some(Utc::now() + chrono::Duration::hours(24)), // 24 hour expiry
+            created_at: now,
+            expires_at: Some(now + chrono::Duration::hours(24)), // 24 hour expiry
+            requester: None,
         }
     }
 
+    /// Record who submitted the request, allowing them to cancel it later
+    pub fn with_requester(mut self, requester: String) -> Self {
+        self.requester = Some(requester);
+        self
+    }
+
     /// Check if the request has expired
     pub fn is_expired(&self) -> bool {
-        if let Some(expires_at) = self.expires_at {
-            Utc::now() > expires_at
-        } else {
-            false
-        }
+        self.is_expired_at(Utc::now())
+    }
+
+    /// Check if the request has expired at a given time
+    pub fn is_expired_at(&self, now: DateTime<Utc>) -> bool {
+        self.expires_at.is_some_and(|expires_at| now > expires_at)
     }
 
     /// Validate the attestation request
     pub fn validate(&self) -> Result<(), AttestorError> {
+        self.validate_at(Utc::now())
+    }
+
+    /// Validate the attestation request, checking expiry at a given time
+    pub fn validate_at(&self, now: DateTime<Utc>) -> Result<(), AttestorError> {
         if self.threshold == 0 || self.threshold > self.required_attestors.len() {
             return Err(AttestorError::ThresholdNotMet(
                 "Invalid threshold configuration".to_string()
             ));
         }
 
-        if self.is_expired() {
+        if self.is_expired_at(now) {
             return Err(AttestorError::InvalidSignature("Request has expired".to_string()));
         }
 
         // Validate the credential
-        self.credential.validate()
+        self.credential.validate_structure()
             .map_err(|e| AttestorError::InvalidSignature(format!("Invalid credential: {}", e)))?;
+        if self.credential.is_expired_at(now) {
+            return Err(AttestorError::InvalidSignature("Invalid credential: Credential has expired".to_string()));
+        }
 
         Ok(())
     }
@@ -129,7 +184,7 @@ impl Attestation {
         credential_id: String,
     ) -> Self {
         Self {
-            id: uuid::Uuid::new_v4().to_string(),
+            id: attestation_id(&credential_id, &attestor_id, &request_id),
             request_id,
             attestor_id,
             attestor_did,
@@ -155,6 +210,23 @@ impl Attestation {
         self.attestation_data.insert("rejection_reason".to_string(), serde_json::Value::String(reason));
     }
 
+    /// Add a provenance entry to the credential for each verified claim.
+    ///
+    /// The evidence level is taken from the `evidence_level` metadata entry when present.
+    pub fn record_provenance(&self, credential: &mut VerifiableCredential) {
+        let evidence_level = self.attestation_data.get("evidence_level")
+            .and_then(|level| level.as_str())
+            .map(str::to_string);
+
+        for claim in &self.verified_claims {
+            credential.add_claim_provenance(claim, ClaimAttestation {
+                attestor_did: self.attestor_did.clone(),
+                evidence_level: evidence_level.clone(),
+                timestamp: self.created_at,
+            });
+        }
+    }
+
     /// Add metadata to the attestation
     pub fn add_metadata(&mut self, key: String, value: serde_json::Value) {
         self.attestation_data.insert(key, value);
@@ -188,12 +260,239 @@ impl AttestationManager {
             threshold_public_key,
             pending_requests: HashMap::new(),
             attestations: HashMap::new(),
+            cancelled_requests: HashMap::new(),
+            in_flight: HashMap::new(),
+            assignment_counts: HashMap::new(),
+            challenges: HashMap::new(),
+            proven_attestors: HashMap::new(),
+            require_did_control: false,
+            escalation_policy: None,
+            escalated_requests: HashMap::new(),
+            batching_window: chrono::Duration::zero(),
+            threshold_reached: HashMap::new(),
+            event_bus: None,
+            webhook_notifier: None,
+            clock: system_clock(),
         })
     }
 
+    /// Publish `AttestationCompleted` events to a bus
+    pub fn set_event_bus(&mut self, event_bus: EventBus) {
+        self.event_bus = Some(event_bus);
+    }
+
+    /// Use a custom time source for request expiry and result timestamps
+    pub fn set_clock(&mut self, clock: Arc<dyn Clock>) {
+        self.clock = clock;
+    }
+
+    /// POST completed attestation results to registered webhooks
+    pub fn set_webhook_notifier(&mut self, notifier: WebhookNotifier) {
+        self.webhook_notifier = Some(notifier);
+    }
+
+    /// Reuse attestors' partial signatures over identical credential digests across requests
+    pub fn set_signature_cache(&mut self, cache: PartialSignatureCache) {
+        self.threshold_scheme.signature_cache = Some(cache);
+    }
+
+    /// Route requests that stall to a higher-authority verifier, which must be a registered verifier
+    pub fn set_escalation_policy(&mut self, policy: EscalationPolicy) -> Result<(), AttestorError> {
+        if !self.verifiers.contains_key(&policy.authority_id) {
+            return Err(AttestorError::NotFound(format!("Escalation authority {} is not a verifier", policy.authority_id)));
+        }
+        self.escalation_policy = Some(policy);
+        Ok(())
+    }
+
+    /// Wait up to `window` after a request first meets its threshold before completing it, so
+    /// attestations arriving shortly after join the result. Zero completes immediately.
+    pub fn set_batching_window(&mut self, window: chrono::Duration) {
+        self.batching_window = window;
+    }
+
+    /// Require attestors to answer a DID control challenge before their attestations are accepted
+    pub fn set_require_did_control(&mut self, required: bool) {
+        self.require_did_control = required;
+    }
+
+    /// Add a verifier to the attestor set with its key share
+    pub fn add_verifier(&mut self, verifier: Verifier, key_share: KeyShare) -> Result<(), AttestorError> {
+        if self.verifiers.contains_key(&verifier.id) {
+            return Err(AttestorError::ConfigError(format!("Verifier {} already exists", verifier.id)));
+        }
+
+        if key_share.scheme_id != self.threshold_scheme.scheme_id {
+            return Err(AttestorError::ConfigError("Key share scheme ID mismatch".to_string()));
+        }
+
+        if self.key_shares.values().any(|share| share.party_id == key_share.party_id) {
+            return Err(AttestorError::ConfigError(
+                format!("Key share for party {} is already assigned", key_share.party_id)
+            ));
+        }
+
+        self.key_shares.insert(verifier.id.clone(), key_share);
+        self.verifiers.insert(verifier.id.clone(), verifier);
+        Ok(())
+    }
+
+    /// Remove a verifier, ensuring the threshold and in-flight requests remain satisfiable
+    pub fn remove_verifier(&mut self, verifier_id: &str) -> Result<Verifier, AttestorError> {
+        if !self.verifiers.contains_key(verifier_id) {
+            return Err(AttestorError::NotFound(format!("Verifier {} not found", verifier_id)));
+        }
+
+        let remaining_signers = self.key_shares.keys().filter(|id| id.as_str() != verifier_id).count();
+        if remaining_signers < self.threshold_scheme.threshold {
+            return Err(AttestorError::ThresholdNotMet(format!(
+                "Removing {} would leave {} signers for a threshold of {}",
+                verifier_id, remaining_signers, self.threshold_scheme.threshold
+            )));
+        }
+
+        for (request_id, request) in &self.pending_requests {
+            let attestations = self.attestations.get(request_id).map(Vec::as_slice).unwrap_or(&[]);
+
+            if attestations.iter().any(|a| a.attestor_id == verifier_id && a.status == AttestationStatus::Approved) {
+                return Err(AttestorError::ConfigError(format!(
+                    "Verifier {} has an approved attestation counted for request {}",
+                    verifier_id, request_id
+                )));
+            }
+
+            // Approvals already collected plus attestors who can still respond
+            let approved = attestations.iter().filter(|a| a.status == AttestationStatus::Approved).count();
+            let outstanding = request.required_attestors.iter()
+                .filter(|id| id.as_str() != verifier_id)
+                .filter(|id| self.key_shares.contains_key(id.as_str()))
+                .filter(|id| !attestations.iter().any(|a| &a.attestor_id == *id))
+                .count();
+            if approved + outstanding < request.threshold {
+                return Err(AttestorError::ThresholdNotMet(format!(
+                    "Removing {} would make request {} unable to reach its threshold",
+                    verifier_id, request_id
+                )));
+            }
+        }
+
+        for request in self.pending_requests.values_mut() {
+            request.required_attestors.retain(|id| id != verifier_id);
+        }
+        self.key_shares.remove(verifier_id);
+        self.in_flight.remove(verifier_id);
+        self.verifiers.remove(verifier_id)
+            .ok_or_else(|| AttestorError::NotFound(format!("Verifier {} not found", verifier_id)))
+    }
+
+    /// Choose up to `count` attestors for a request, preferring the least-loaded capable verifiers.
+    ///
+    /// Ties on in-flight load are broken by reputation in the capability the credential type
+    /// needs (falling back to global reputation), so reputable verifiers are still favoured
+    /// among equally busy ones without being assigned everything.
+    pub fn assign_attestors(&mut self, request: &AttestationRequest, count: usize) -> Vec<String> {
+        let credential_type = request.credential.credential_type.iter()
+            .find(|t| t.as_str() != "VerifiableCredential");
+
+        let mut candidates: Vec<&Verifier> = self.verifiers.values()
+            .filter(|v| self.key_shares.contains_key(&v.id))
+            .filter(|v| credential_type.is_none_or(|t| v.can_verify(t)))
+            .collect();
+
+        let reputation = |v: &Verifier| match credential_type {
+            Some(t) => v.reputation_for(t),
+            None => v.reputation_score,
+        };
+        candidates.sort_by(|a, b| {
+            self.verifier_load(&a.id).cmp(&self.verifier_load(&b.id))
+                .then(reputation(b).total_cmp(&reputation(a)))
+                .then(a.id.cmp(&b.id))
+        });
+
+        let assigned: Vec<String> = candidates.into_iter()
+            .take(count)
+            .map(|v| v.id.clone())
+            .collect();
+
+        for verifier_id in &assigned {
+            self.in_flight.entry(verifier_id.clone()).or_default().insert(request.id.clone());
+            *self.assignment_counts.entry(verifier_id.clone()).or_default() += 1;
+        }
+
+        assigned
+    }
+
+    /// Number of assigned requests a verifier has not yet responded to
+    pub fn verifier_load(&self, verifier_id: &str) -> usize {
+        self.in_flight.get(verifier_id).map(HashSet::len).unwrap_or(0)
+    }
+
+    /// Clear a finished request from every verifier's in-flight assignments and its challenges
+    pub(crate) fn release_assignments(&mut self, request_id: &str) {
+        for requests in self.in_flight.values_mut() {
+            requests.remove(request_id);
+        }
+        self.challenges.remove(request_id);
+        self.proven_attestors.remove(request_id);
+        self.threshold_reached.remove(request_id);
+    }
+
+    /// Issue a fresh nonce the attestor must sign with a key from its DID document
+    pub fn issue_challenge(&mut self, request_id: &str, attestor_id: &str) -> Result<AttestorChallenge, AttestorError> {
+        self.ensure_open(request_id)?;
+        if !self.pending_requests.contains_key(request_id) {
+            return Err(AttestorError::NotFound(format!("Request {} not found", request_id)));
+        }
+        let verifier = self.verifiers.get(attestor_id)
+            .ok_or_else(|| AttestorError::NotFound(format!("Verifier {} not found", attestor_id)))?;
+
+        let challenge = AttestorChallenge {
+            request_id: request_id.to_string(),
+            attestor_id: attestor_id.to_string(),
+            attestor_did: verifier.did.clone(),
+            nonce: generate_nonce(),
+            issued_at: self.clock.now(),
+        };
+        self.challenges.entry(request_id.to_string()).or_default()
+            .insert(attestor_id.to_string(), challenge.clone());
+        Ok(challenge)
+    }
+
+    /// Accept a challenge response checked against the attestor's DID document; challenges are single use
+    pub fn verify_challenge_response(&mut self, response: &ChallengeResponse, did_document: &DidDocument) -> Result<(), AttestorError> {
+        let challenge = self.challenges.get_mut(&response.request_id)
+            .and_then(|challenges| challenges.remove(&response.attestor_id))
+            .ok_or_else(|| AttestorError::NotFound(format!(
+                "No open challenge for attestor {} on request {}", response.attestor_id, response.request_id
+            )))?;
+
+        challenge.verify_response(response, did_document)?;
+        self.proven_attestors.entry(response.request_id.clone()).or_default()
+            .insert(response.attestor_id.clone());
+        Ok(())
+    }
+
+    /// Accept a challenge response, resolving the attestor's DID document
+    pub async fn verify_challenge_response_with(
+        &mut self,
+        response: &ChallengeResponse,
+        resolver: &dyn DidResolver,
+    ) -> Result<(), AttestorError> {
+        let attestor_did = self.verifiers.get(&response.attestor_id)
+            .map(|verifier| verifier.did.clone())
+            .ok_or_else(|| AttestorError::NotFound(format!("Verifier {} not found", response.attestor_id)))?;
+        let did_document = resolver.resolve(&attestor_did).await?;
+        self.verify_challenge_response(response, &did_document)
+    }
+
+    /// Whether an attestor has proven control of its DID for a request
+    pub fn has_proven_control(&self, request_id: &str, attestor_id: &str) -> bool {
+        self.proven_attestors.get(request_id).is_some_and(|attestors| attestors.contains(attestor_id))
+    }
+
     /// Submit a new attestation request
     pub fn submit_request(&mut self, request: AttestationRequest) -> Result<String, AttestorError> {
-        request.validate()?;
+        request.validate_at(self.clock.now())?;
 
         let request_id = request.id.clone();
         self.pending_requests.insert(request_id.clone(), request);
@@ -202,7 +501,65 @@ impl AttestationManager {
         Ok(request_id)
     }
 
-    /// Process an attestation from a verifier
+    /// Withdraw a pending request, discarding its collected attestations.
+    ///
+    /// Only the original requester may cancel; requests submitted without a
+    /// requester cannot be cancelled.
+    pub fn cancel_request(&mut self, request_id: &str, requester: &str) -> Result<(), AttestorError> {
+        let request = self.pending_requests.get(request_id)
+            .ok_or_else(|| AttestorError::NotFound(format!("Request {} not found", request_id)))?;
+
+        if request.requester.as_deref() != Some(requester) {
+            return Err(AttestorError::PermissionDenied(format!(
+                "{} is not the requester of {}", requester, request_id
+            )));
+        }
+
+        let request = self.pending_requests.remove(request_id)
+            .ok_or_else(|| AttestorError::NotFound(format!("Request {} not found", request_id)))?;
+        self.release_assignments(request_id);
+        let attestations = self.attestations.remove(request_id).unwrap_or_default();
+
+        let mut metadata = HashMap::new();
+        metadata.insert("cancelled_by".to_string(), serde_json::Value::String(requester.to_string()));
+        metadata.insert("total_attestations".to_string(), serde_json::Value::Number(attestations.len().into()));
+
+        self.cancelled_requests.insert(request_id.to_string(), AttestationResult {
+            request_id: request_id.to_string(),
+            credential_id: request.credential.id.clone(),
+            threshold_signature: None,
+            participating_attestors: attestations.iter()
+                .filter(|a| a.status == AttestationStatus::Approved)
+                .map(|a| a.attestor_id.clone())
+                .collect(),
+            status: AttestationResultStatus::Cancelled,
+            created_at: self.clock.now(),
+            metadata,
+        });
+
+        Ok(())
+    }
+
+    /// Get the result recorded for a cancelled request
+    pub fn get_cancelled_result(&self, request_id: &str) -> Option<&AttestationResult> {
+        self.cancelled_requests.get(request_id)
+    }
+
+    /// Reject operations on a request that has been cancelled or handed to the escalation authority
+    fn ensure_open(&self, request_id: &str) -> Result<(), AttestorError> {
+        if self.cancelled_requests.contains_key(request_id) {
+            return Err(AttestorError::InvalidRequest(format!("Request {} was cancelled", request_id)));
+        }
+        if self.escalated_requests.contains_key(request_id) {
+            return Err(AttestorError::InvalidRequest(format!("Request {} was escalated", request_id)));
+        }
+        Ok(())
+    }
+
+    /// Process an attestation from a verifier.
+    ///
+    /// Returns `false` without recording anything when the attestor already attested to the
+    /// request, so retried submissions are not counted twice.
     pub fn process_attestation(
         &mut self,
         request_id: &str,
@@ -210,17 +567,30 @@ impl AttestationManager {
         approved: bool,
         verified_claims: Vec<String>,
         metadata: HashMap<String, serde_json::Value>,
-    ) -> Result<(), AttestorError> {
+    ) -> Result<bool, AttestorError> {
+        self.ensure_open(request_id)?;
+
         let request = self.pending_requests.get(request_id)
             .ok_or_else(|| AttestorError::InvalidSignature("Request not found".to_string()))?;
 
-        if request.is_expired() {
+        let id = attestation_id(&request.credential.id, attestor_id, request_id);
+        if self.attestations.get(request_id).is_some_and(|attestations| attestations.iter().any(|a| a.id == id)) {
+            return Ok(false);
+        }
+
+        if request.is_expired_at(self.clock.now()) {
             return Err(AttestorError::InvalidSignature("Request has expired".to_string()));
         }
 
         let verifier = self.verifiers.get(attestor_id)
             .ok_or_else(|| AttestorError::InvalidSignature("Verifier not found".to_string()))?;
 
+        if self.require_did_control && !self.has_proven_control(request_id, attestor_id) {
+            return Err(AttestorError::PermissionDenied(format!(
+                "Attestor {} has not proven control of {}", attestor_id, verifier.did
+            )));
+        }
+
         let key_share = self.key_shares.get(attestor_id)
             .ok_or_else(|| AttestorError::InvalidSignature("Key share not found".to_string()))?;
 
@@ -237,24 +607,44 @@ impl AttestationManager {
         }
 
         if approved {
-            // Create partial signature
-            let credential_bytes = serde_json::to_vec(&request.credential)
-                .map_err(|e| AttestorError::InvalidSignature(format!("Serialization error: {}", e)))?;
+            // Create partial signature over the digest of the canonical payload
+            let credential_hash = attestation_digest(&request.credential)?;
 
-            let partial_signature = self.threshold_scheme.partial_sign(&credential_bytes, key_share)?;
+            let partial_signature = self.threshold_scheme.partial_sign(&credential_hash, key_share)?;
+            if !self.threshold_scheme.verify_partial(&credential_hash, &partial_signature, &key_share.public_share)? {
+                return Err(AttestorError::InvalidSignature(format!(
+                    "Partial signature from {} does not match its public share", attestor_id
+                )));
+            }
             attestation.approve(partial_signature, verified_claims);
         } else {
             attestation.reject("Attestor rejected the credential".to_string());
         }
 
+        // Record which claims this attestor vouched for on the pending credential
+        if attestation.status == AttestationStatus::Approved {
+            if let Some(request) = self.pending_requests.get_mut(request_id) {
+                attestation.record_provenance(&mut request.credential);
+            }
+        }
+
         // Add attestation to the list
         self.attestations.get_mut(request_id).unwrap().push(attestation);
+        if let Some(requests) = self.in_flight.get_mut(attestor_id) {
+            requests.remove(request_id);
+        }
 
-        Ok(())
+        Ok(true)
     }
 
-    /// Check if threshold is met and combine signatures
+    /// Check if threshold is met and combine signatures.
+    ///
+    /// With a batching window, the first call to see the threshold met starts the window and
+    /// returns `None`; the request completes once the window has passed or every required
+    /// attestor has approved.
     pub fn try_complete_attestation(&mut self, request_id: &str) -> Result<Option<AttestationResult>, AttestorError> {
+        self.ensure_open(request_id)?;
+
         let request = self.pending_requests.get(request_id)
             .ok_or_else(|| AttestorError::InvalidSignature("Request not found".to_string()))?;
 
@@ -265,6 +655,13 @@ impl AttestationManager {
             .collect();
 
         if approved_attestations.len() >= request.threshold {
+            let now = self.clock.now();
+            let reached_at = *self.threshold_reached.entry(request_id.to_string()).or_insert(now);
+            let everyone_approved = approved_attestations.len() >= request.required_attestors.len();
+            if now - reached_at < self.batching_window && !everyone_approved {
+                return Ok(None);
+            }
+
             // Collect partial signatures
             let partial_signatures: Vec<_> = approved_attestations.iter()
                 .filter_map(|a| a.partial_signature.as_ref())
@@ -274,13 +671,19 @@ impl AttestationManager {
             // Combine signatures
             let threshold_signature = self.threshold_scheme.combine_signatures(&partial_signatures)?;
 
+            // Name exactly the attestors whose shares went into the signature
             let participating_attestors: Vec<String> = approved_attestations.iter()
+                .filter(|a| a.partial_signature.as_ref()
+                    .is_some_and(|sig| threshold_signature.signers.contains(&sig.party_id)))
                 .map(|a| a.attestor_id.clone())
                 .collect();
 
             let mut metadata = HashMap::new();
             metadata.insert("threshold_met".to_string(), serde_json::Value::Bool(true));
             metadata.insert("total_attestations".to_string(), serde_json::Value::Number(attestations.len().into()));
+            if !self.batching_window.is_zero() {
+                metadata.insert("threshold_reached_at".to_string(), serde_json::Value::String(reached_at.to_rfc3339()));
+            }
 
             let result = AttestationResult {
                 request_id: request_id.to_string(),
@@ -288,12 +691,14 @@ impl AttestationManager {
                 threshold_signature: Some(threshold_signature),
                 participating_attestors,
                 status: AttestationResultStatus::Completed,
-                created_at: Utc::now(),
+                created_at: now,
                 metadata,
             };
 
             // Remove completed request
             self.pending_requests.remove(request_id);
+            self.release_assignments(request_id);
+            self.publish_completion(&result);
 
             Ok(Some(result))
         } else {
@@ -301,6 +706,37 @@ impl AttestationManager {
         }
     }
 
+    /// Announce a completed result on the event bus and to webhooks
+    pub(crate) fn publish_completion(&self, result: &AttestationResult) {
+        if let Some(event_bus) = &self.event_bus {
+            event_bus.publish(DomainEvent::AttestationCompleted {
+                request_id: result.request_id.clone(),
+                credential_id: result.credential_id.clone(),
+                participating_attestors: result.participating_attestors.clone(),
+            });
+        }
+
+        if let Some(notifier) = &self.webhook_notifier {
+            notifier.dispatch(result.clone());
+        }
+    }
+
+    /// Attach claim provenance from every approved attestation of a request to a credential
+    pub fn annotate_provenance(&self, request_id: &str, credential: &mut VerifiableCredential) -> Result<(), AttestorError> {
+        let attestations = self.attestations.get(request_id)
+            .ok_or_else(|| AttestorError::NotFound(format!("No attestations for request {}", request_id)))?;
+
+        for attestation in attestations.iter().filter(|a| a.status == AttestationStatus::Approved) {
+            attestation.record_provenance(credential);
+        }
+        Ok(())
+    }
+
+    /// When a request that has met its threshold will complete, if a batching window is open for it
+    pub fn batching_deadline(&self, request_id: &str) -> Option<DateTime<Utc>> {
+        self.threshold_reached.get(request_id).map(|reached_at| *reached_at + self.batching_window)
+    }
+
     /// Get attestation status
     pub fn get_attestation_status(&self, request_id: &str) -> Option<(usize, usize)> {
         self.attestations.get(request_id).map(|attestations| {
@@ -311,18 +747,42 @@ impl AttestationManager {
         })
     }
 
+    /// Issue a self-contained quorum certificate for a completed attestation of a credential
+    pub fn quorum_certificate(
+        &self,
+        result: &AttestationResult,
+        credential: &VerifiableCredential,
+    ) -> Result<QuorumCertificate, AttestorError> {
+        if result.credential_id != credential.id {
+            return Err(AttestorError::InvalidRequest("Result is for a different credential".to_string()));
+        }
+        QuorumCertificate::from_result(result, &attestation_digest(credential)?, &self.threshold_public_key)
+    }
+
     /// Verify a completed attestation result
     pub fn verify_attestation_result(
         &self,
         result: &AttestationResult,
         credential: &VerifiableCredential,
     ) -> Result<bool, AttestorError> {
-        if let Some(signature) = &result.threshold_signature {
-            let credential_bytes = serde_json::to_vec(credential)
-                .map_err(|e| AttestorError::InvalidSignature(format!("Serialization error: {}", e)))?;
+        let payload = attestation_payload(credential)?;
+        self.verify_attestation_result_for(result, &payload)
+    }
 
+    /// Verify a completed attestation result against already-canonicalized payload bytes
+    pub fn verify_attestation_result_for(
+        &self,
+        result: &AttestationResult,
+        payload: &[u8],
+    ) -> Result<bool, AttestorError> {
+        if result.status == AttestationResultStatus::Escalated {
+            return Err(AttestorError::AttestationError(format!(
+                "Attestation {} was decided by escalation and carries no threshold signature", result.request_id
+            )));
+        }
+        if let Some(signature) = &result.threshold_signature {
             self.threshold_scheme.verify_signature(
-                &credential_bytes,
+                &hash_data(payload),
                 signature,
                 &self.threshold_public_key,
             )
@@ -331,3 +791,552 @@ impl AttestationManager {
         }
     }
 }
+
+/// Id of an attestor's attestation to a request, derived so resubmissions get the same id
+pub fn attestation_id(credential_id: &str, attestor_id: &str, request_id: &str) -> String {
+    // Length-prefix each part so different splits of the same bytes cannot collide
+    let mut input = Vec::new();
+    for part in [credential_id, attestor_id, request_id] {
+        input.extend_from_slice(&(part.len() as u64).to_be_bytes());
+        input.extend_from_slice(part.as_bytes());
+    }
+    hex::encode(hash_data(&input))
+}
+
+/// Canonical bytes attestors sign for a credential
+pub fn attestation_payload(credential: &VerifiableCredential) -> Result<Vec<u8>, AttestorError> {
+    credential.signing_payload()
+        .map_err(|e| AttestorError::InvalidSignature(format!("Serialization error: {}", e)))
+}
+
+/// SHA-256 of the canonical payload; this is the message the threshold signature covers
+pub fn attestation_digest(credential: &VerifiableCredential) -> Result<Vec<u8>, AttestorError> {
+    Ok(hash_data(&attestation_payload(credential)?))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::BTreeMap;
+    use identity_core::{CredentialType, CryptoKeyPair, InMemorySigner, KeyType, MockClock};
+    use crate::verifier::VerificationCapability;
+    use identity_core::utils::create_basic_did_document;
+
+    fn credential() -> VerifiableCredential {
+        let mut claims = BTreeMap::new();
+        claims.insert("name".to_string(), serde_json::json!("Alice"));
+        VerifiableCredential::new("did:example:issuer".to_string(), Some("did:example:alice".to_string()), claims)
+    }
+
+    /// Manager with three verifiers whose DIDs are backed by fresh Ed25519 documents
+    fn manager(threshold: usize) -> (AttestationManager, Vec<(DidDocument, CryptoKeyPair)>) {
+        let identities: Vec<_> = (0..3)
+            .map(|_| create_basic_did_document("example", KeyType::Ed25519).unwrap())
+            .collect();
+        let verifiers = identities.iter().enumerate()
+            .map(|(i, (document, _))| Verifier::new(format!("v{}", i + 1), document.id.clone(), format!("Verifier {}", i + 1), Vec::new()))
+            .collect();
+        (AttestationManager::new(threshold, 3, verifiers).unwrap(), identities)
+    }
+
+    fn submit(manager: &mut AttestationManager, threshold: usize) -> String {
+        submit_credential(manager, credential(), threshold)
+    }
+
+    fn submit_credential(manager: &mut AttestationManager, credential: VerifiableCredential, threshold: usize) -> String {
+        let request = AttestationRequest::new(credential, vec!["v1".to_string(), "v2".to_string(), "v3".to_string()], threshold);
+        manager.submit_request(request).unwrap()
+    }
+
+    #[test]
+    fn did_control_is_not_required_by_default() {
+        let (mut manager, _) = manager(2);
+        let request_id = submit(&mut manager, 2);
+
+        assert!(!manager.require_did_control);
+        assert!(manager.process_attestation(&request_id, "v1", true, Vec::new(), HashMap::new()).unwrap());
+    }
+
+    #[tokio::test]
+    async fn proven_did_control_admits_attestation() {
+        let (mut manager, identities) = manager(2);
+        manager.set_require_did_control(true);
+        let request_id = submit(&mut manager, 2);
+        let (document, keypair) = &identities[0];
+
+        let challenge = manager.issue_challenge(&request_id, "v1").unwrap();
+        let response = challenge.respond(&InMemorySigner::new(keypair.clone()), format!("{}#key-1", document.id)).await.unwrap();
+        manager.verify_challenge_response(&response, document).unwrap();
+
+        assert!(manager.has_proven_control(&request_id, "v1"));
+        assert!(manager.process_attestation(&request_id, "v1", true, Vec::new(), HashMap::new()).unwrap());
+    }
+
+    #[tokio::test]
+    async fn wrong_key_is_rejected_when_did_control_is_required() {
+        let (mut manager, identities) = manager(2);
+        manager.set_require_did_control(true);
+        let request_id = submit(&mut manager, 2);
+        let (document, _) = &identities[0];
+        let (_, other_keypair) = &identities[1];
+
+        let challenge = manager.issue_challenge(&request_id, "v1").unwrap();
+        let response = challenge.respond(&InMemorySigner::new(other_keypair.clone()), format!("{}#key-1", document.id)).await.unwrap();
+
+        assert!(matches!(manager.verify_challenge_response(&response, document), Err(AttestorError::PermissionDenied(_))));
+        assert!(matches!(
+            manager.process_attestation(&request_id, "v1", true, Vec::new(), HashMap::new()),
+            Err(AttestorError::PermissionDenied(_))
+        ));
+    }
+
+    fn approve(manager: &mut AttestationManager, request_id: &str, attestor_id: &str) {
+        assert!(manager.process_attestation(request_id, attestor_id, true, Vec::new(), HashMap::new()).unwrap());
+    }
+
+    #[test]
+    fn signature_arriving_in_grace_window_is_combined() {
+        let (mut manager, _) = manager(2);
+        let clock = MockClock::default();
+        manager.set_clock(Arc::new(clock.clone()));
+        manager.set_batching_window(chrono::Duration::seconds(30));
+        let credential = credential();
+        let request_id = submit_credential(&mut manager, credential.clone(), 2);
+
+        approve(&mut manager, &request_id, "v1");
+        approve(&mut manager, &request_id, "v2");
+        assert!(manager.try_complete_attestation(&request_id).unwrap().is_none());
+
+        clock.advance(chrono::Duration::seconds(5));
+        approve(&mut manager, &request_id, "v3");
+        let result = manager.try_complete_attestation(&request_id).unwrap().unwrap();
+
+        assert_eq!(result.participating_attestors, vec!["v1", "v2", "v3"]);
+        assert_eq!(result.threshold_signature.as_ref().unwrap().signers.len(), 3);
+        assert!(manager.verify_attestation_result(&result, &credential).unwrap());
+        assert!(manager.quorum_certificate(&result, &credential).unwrap().verify());
+    }
+
+    #[test]
+    fn grace_window_closes_with_the_signatures_collected() {
+        let (mut manager, _) = manager(2);
+        let clock = MockClock::default();
+        manager.set_clock(Arc::new(clock.clone()));
+        manager.set_batching_window(chrono::Duration::seconds(30));
+        let credential = credential();
+        let request_id = submit_credential(&mut manager, credential.clone(), 2);
+
+        approve(&mut manager, &request_id, "v1");
+        approve(&mut manager, &request_id, "v2");
+        assert!(manager.try_complete_attestation(&request_id).unwrap().is_none());
+
+        clock.advance(chrono::Duration::seconds(30));
+        let result = manager.try_complete_attestation(&request_id).unwrap().unwrap();
+
+        assert_eq!(result.participating_attestors, vec!["v1", "v2"]);
+        assert!(manager.verify_attestation_result(&result, &credential).unwrap());
+    }
+
+    #[test]
+    fn without_a_window_the_request_completes_at_threshold() {
+        let (mut manager, _) = manager(2);
+        let request_id = submit(&mut manager, 2);
+
+        approve(&mut manager, &request_id, "v1");
+        approve(&mut manager, &request_id, "v2");
+        let result = manager.try_complete_attestation(&request_id).unwrap().unwrap();
+
+        assert_eq!(result.participating_attestors, vec!["v1", "v2"]);
+        assert_eq!(result.threshold_signature.unwrap().signers.len(), 2);
+    }
+
+    #[test]
+    fn completed_attestation_verifies_against_the_canonical_payload() {
+        let (mut manager, _) = manager(2);
+        let credential = credential();
+        let request_id = submit_credential(&mut manager, credential.clone(), 2);
+        approve(&mut manager, &request_id, "v1");
+        approve(&mut manager, &request_id, "v3");
+
+        let result = manager.try_complete_attestation(&request_id).unwrap().unwrap();
+
+        assert!(manager.verify_attestation_result(&result, &credential).unwrap());
+        let payload = attestation_payload(&credential).unwrap();
+        assert!(manager.verify_attestation_result_for(&result, &payload).unwrap());
+        // Plain serde bytes are not what the attestors signed
+        assert!(!manager.verify_attestation_result_for(&result, &serde_json::to_vec(&credential).unwrap()).unwrap());
+        let mut tampered = credential.clone();
+        tampered.credential_subject.claims.insert("name".to_string(), serde_json::json!("Mallory"));
+        assert!(!manager.verify_attestation_result(&result, &tampered).unwrap());
+    }
+
+    #[test]
+    fn rotated_verifier_signs_with_the_handed_over_share() {
+        let (mut manager, _) = manager(2);
+        let share = manager.key_shares["v3"].clone();
+
+        manager.remove_verifier("v3").unwrap();
+        manager.add_verifier(Verifier::new("v4".to_string(), "did:example:v4".to_string(), "Verifier 4".to_string(), Vec::new()), share).unwrap();
+
+        let credential = credential();
+        let request = AttestationRequest::new(credential.clone(), vec!["v1".to_string(), "v4".to_string()], 2);
+        let request_id = manager.submit_request(request).unwrap();
+        approve(&mut manager, &request_id, "v1");
+        approve(&mut manager, &request_id, "v4");
+        let result = manager.try_complete_attestation(&request_id).unwrap().unwrap();
+
+        assert_eq!(result.participating_attestors, vec!["v1", "v4"]);
+        assert!(manager.verify_attestation_result(&result, &credential).unwrap());
+    }
+
+    #[test]
+    fn add_verifier_rejects_duplicates_and_foreign_shares() {
+        let (mut manager, _) = manager(2);
+        let verifier = |id: &str| Verifier::new(id.to_string(), format!("did:example:{}", id), id.to_string(), Vec::new());
+        let share = manager.key_shares["v1"].clone();
+
+        assert!(matches!(manager.add_verifier(verifier("v1"), share.clone()), Err(AttestorError::ConfigError(_))));
+        assert!(matches!(manager.add_verifier(verifier("v4"), share), Err(AttestorError::ConfigError(_))));
+
+        let foreign = ThresholdScheme::new(2, 3).unwrap().generate_key_shares().unwrap().0.remove(0);
+        assert!(matches!(manager.add_verifier(verifier("v4"), foreign), Err(AttestorError::ConfigError(_))));
+        assert_eq!(manager.verifiers.len(), 3);
+    }
+
+    #[test]
+    fn remove_verifier_keeps_the_threshold_reachable() {
+        let (mut manager, _) = manager(2);
+
+        manager.remove_verifier("v3").unwrap();
+        assert!(matches!(manager.remove_verifier("v2"), Err(AttestorError::ThresholdNotMet(_))));
+        assert!(matches!(manager.remove_verifier("v3"), Err(AttestorError::NotFound(_))));
+        assert_eq!(manager.key_shares.len(), 2);
+    }
+
+    #[test]
+    fn remove_verifier_accounts_for_in_flight_requests() {
+        let (mut manager, _) = manager(2);
+        let request_id = submit(&mut manager, 2);
+        approve(&mut manager, &request_id, "v1");
+        assert!(manager.process_attestation(&request_id, "v2", false, Vec::new(), HashMap::new()).unwrap());
+
+        // v1's approval is already counted, and without v3 nobody could supply the second one
+        assert!(matches!(manager.remove_verifier("v1"), Err(AttestorError::ConfigError(_))));
+        assert!(matches!(manager.remove_verifier("v3"), Err(AttestorError::ThresholdNotMet(_))));
+        // v2 has already responded, so dropping it leaves the request reachable
+        manager.remove_verifier("v2").unwrap();
+        assert_eq!(manager.pending_requests[&request_id].required_attestors, vec!["v1", "v3"]);
+    }
+
+    fn submit_as(manager: &mut AttestationManager, requester: Option<&str>) -> String {
+        let mut request = AttestationRequest::new(credential(), vec!["v1".to_string(), "v2".to_string(), "v3".to_string()], 2);
+        if let Some(requester) = requester {
+            request = request.with_requester(requester.to_string());
+        }
+        manager.assign_attestors(&request, 3);
+        manager.submit_request(request).unwrap()
+    }
+
+    #[test]
+    fn cancelled_request_cannot_be_completed() {
+        let (mut manager, _) = manager(2);
+        let request_id = submit_as(&mut manager, Some("did:example:alice"));
+        approve(&mut manager, &request_id, "v1");
+
+        manager.cancel_request(&request_id, "did:example:alice").unwrap();
+
+        let result = manager.get_cancelled_result(&request_id).unwrap();
+        assert_eq!(result.status, AttestationResultStatus::Cancelled);
+        assert_eq!(result.participating_attestors, vec!["v1"]);
+        assert!(result.threshold_signature.is_none());
+        assert_eq!(manager.verifier_load("v2"), 0);
+        assert!(matches!(
+            manager.process_attestation(&request_id, "v2", true, Vec::new(), HashMap::new()),
+            Err(AttestorError::InvalidRequest(_))
+        ));
+        assert!(matches!(manager.try_complete_attestation(&request_id), Err(AttestorError::InvalidRequest(_))));
+        assert!(manager.get_attestation_status(&request_id).is_none());
+    }
+
+    #[test]
+    fn only_the_requester_may_cancel() {
+        let (mut manager, _) = manager(2);
+        let request_id = submit_as(&mut manager, Some("did:example:alice"));
+        let anonymous = submit_as(&mut manager, None);
+
+        assert!(matches!(manager.cancel_request(&request_id, "did:example:mallory"), Err(AttestorError::PermissionDenied(_))));
+        assert!(matches!(manager.cancel_request(&anonymous, "did:example:alice"), Err(AttestorError::PermissionDenied(_))));
+        assert!(matches!(manager.cancel_request("missing", "did:example:alice"), Err(AttestorError::NotFound(_))));
+
+        approve(&mut manager, &request_id, "v1");
+        approve(&mut manager, &request_id, "v2");
+        assert!(manager.try_complete_attestation(&request_id).unwrap().is_some());
+    }
+
+    fn request_for(credential: VerifiableCredential) -> AttestationRequest {
+        AttestationRequest::new(credential, vec!["v1".to_string(), "v2".to_string(), "v3".to_string()], 1)
+    }
+
+    #[test]
+    fn assignments_spread_across_verifiers() {
+        let (mut manager, _) = manager(1);
+        manager.verifiers.get_mut("v1").unwrap().reputation_score = 0.9;
+
+        let mut assigned = Vec::new();
+        for _ in 0..6 {
+            assigned.extend(manager.assign_attestors(&request_for(credential()), 1));
+        }
+
+        // The most reputable verifier wins ties but isn't given everything
+        assert_eq!(assigned[0], "v1");
+        for verifier_id in ["v1", "v2", "v3"] {
+            assert_eq!(manager.verifier_load(verifier_id), 2);
+            assert_eq!(manager.assignment_counts[verifier_id], 2);
+        }
+    }
+
+    #[test]
+    fn assignment_respects_capabilities_and_responses() {
+        let (mut manager, _) = manager(1);
+        for verifier_id in ["v2", "v3"] {
+            manager.verifiers.get_mut(verifier_id).unwrap().add_capability(VerificationCapability::EducationVerification);
+        }
+        let mut degree = credential();
+        degree.add_type(CredentialType::UniversityDegreeCredential);
+
+        let first = request_for(degree.clone());
+        let first_assigned = manager.assign_attestors(&first, 1);
+        let request_id = manager.submit_request(first).unwrap();
+        let second_assigned = manager.assign_attestors(&request_for(degree.clone()), 1);
+
+        assert_ne!(first_assigned, second_assigned);
+        assert!(!first_assigned.contains(&"v1".to_string()) && !second_assigned.contains(&"v1".to_string()));
+        assert_eq!(manager.assign_attestors(&request_for(degree), 3).len(), 2);
+
+        // Responding frees the verifier for new work
+        let responder = &first_assigned[0];
+        let load = manager.verifier_load(responder);
+        approve(&mut manager, &request_id, responder);
+        assert_eq!(manager.verifier_load(responder), load - 1);
+        assert_eq!(manager.assignment_counts[responder.as_str()], load);
+    }
+
+    #[test]
+    fn provenance_records_which_verifier_attested_which_claim() {
+        let (mut manager, identities) = manager(2);
+        let mut credential = credential();
+        credential.credential_subject.claims.insert("degree".to_string(), serde_json::json!("BSc"));
+        let request_id = submit_credential(&mut manager, credential.clone(), 2);
+
+        let level = HashMap::from([("evidence_level".to_string(), serde_json::json!("document"))]);
+        assert!(manager.process_attestation(&request_id, "v1", true, vec!["name".to_string()], level).unwrap());
+        assert!(manager.process_attestation(&request_id, "v2", true, vec!["degree".to_string()], HashMap::new()).unwrap());
+        assert!(manager.process_attestation(&request_id, "v3", false, vec!["name".to_string()], HashMap::new()).unwrap());
+
+        // Provenance is kept on the pending credential without changing what the attestors sign
+        let pending = &manager.pending_requests[&request_id].credential;
+        assert_eq!(attestation_digest(pending).unwrap(), attestation_digest(&credential).unwrap());
+        assert!(manager.try_complete_attestation(&request_id).unwrap().is_some());
+
+        manager.annotate_provenance(&request_id, &mut credential).unwrap();
+        let name = credential.provenance_for("name");
+        assert_eq!(name.len(), 1);
+        assert_eq!(name[0].attestor_did, identities[0].0.id);
+        assert_eq!(name[0].evidence_level.as_deref(), Some("document"));
+        let degree = credential.provenance_for("degree");
+        assert_eq!(degree.len(), 1);
+        assert_eq!(degree[0].attestor_did, identities[1].0.id);
+        assert_eq!(degree[0].evidence_level, None);
+
+        assert!(manager.annotate_provenance("unknown", &mut credential).is_err());
+    }
+
+    /// Subscriber keeping every completed attestation it hears about
+    #[derive(Default)]
+    struct CompletionLog(Vec<(String, Vec<String>)>);
+
+    impl identity_core::EventHandler for CompletionLog {
+        fn handle_event(&mut self, event: &DomainEvent) {
+            if let DomainEvent::AttestationCompleted { request_id, participating_attestors, .. } = event {
+                self.0.push((request_id.clone(), participating_attestors.clone()));
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn completed_attestation_is_delivered_to_subscribers() {
+        let (mut manager, _) = manager(2);
+        let bus = EventBus::default();
+        let log = Arc::new(std::sync::Mutex::new(CompletionLog::default()));
+        let subscriber = bus.attach(log.clone());
+        manager.set_event_bus(bus);
+
+        let request_id = submit(&mut manager, 2);
+        approve(&mut manager, &request_id, "v1");
+        assert!(manager.try_complete_attestation(&request_id).unwrap().is_none());
+        approve(&mut manager, &request_id, "v3");
+        let result = manager.try_complete_attestation(&request_id).unwrap().unwrap();
+
+        // Dropping the manager drops the last bus handle, letting the subscriber drain and stop
+        drop(manager);
+        subscriber.await.unwrap();
+
+        let log = log.lock().unwrap();
+        assert_eq!(log.0, vec![(request_id, result.participating_attestors)]);
+    }
+
+    #[test]
+    fn request_expires_when_the_clock_passes_its_deadline() {
+        let (mut manager, _) = manager(2);
+        let clock = MockClock::default();
+        manager.set_clock(Arc::new(clock.clone()));
+        let request = AttestationRequest::new_with_clock(credential(), vec!["v1".to_string(), "v2".to_string(), "v3".to_string()], 2, &clock);
+        assert_eq!(request.created_at, clock.now());
+        let request_id = manager.submit_request(request).unwrap();
+
+        clock.advance(chrono::Duration::hours(23));
+        approve(&mut manager, &request_id, "v1");
+
+        clock.advance(chrono::Duration::hours(2));
+        assert!(manager.pending_requests[&request_id].is_expired_at(clock.now()));
+        assert!(manager.process_attestation(&request_id, "v2", true, Vec::new(), HashMap::new()).is_err());
+    }
+
+    #[test]
+    fn expired_request_cannot_be_submitted() {
+        let (mut manager, _) = manager(2);
+        let clock = MockClock::default();
+        manager.set_clock(Arc::new(clock.clone()));
+        let request = AttestationRequest::new_with_clock(credential(), vec!["v1".to_string(), "v2".to_string(), "v3".to_string()], 2, &clock);
+
+        clock.advance(chrono::Duration::days(2));
+
+        assert!(request.validate_at(clock.now()).is_err());
+        assert!(manager.submit_request(request).is_err());
+    }
+
+    fn completed(manager: &mut AttestationManager, credential: &VerifiableCredential) -> AttestationResult {
+        let request_id = submit_credential(manager, credential.clone(), 2);
+        approve(manager, &request_id, "v1");
+        approve(manager, &request_id, "v2");
+        manager.try_complete_attestation(&request_id).unwrap().unwrap()
+    }
+
+    #[test]
+    fn quorum_certificate_verifies_without_the_manager() {
+        let (mut manager, _) = manager(2);
+        let credential = credential();
+        let result = completed(&mut manager, &credential);
+
+        let certificate = manager.quorum_certificate(&result, &credential).unwrap();
+        let json = serde_json::to_string(&certificate).unwrap();
+        drop(manager);
+
+        let certificate: QuorumCertificate = serde_json::from_str(&json).unwrap();
+        assert!(certificate.verify());
+        assert_eq!(certificate.credential_hash, hex::encode(attestation_digest(&credential).unwrap()));
+        assert_eq!(certificate.signers, vec![1, 2]);
+    }
+
+    #[test]
+    fn quorum_certificate_with_a_swapped_credential_hash_fails() {
+        let (mut manager, _) = manager(2);
+        let credential = credential();
+        let result = completed(&mut manager, &credential);
+        let mut certificate = manager.quorum_certificate(&result, &credential).unwrap();
+
+        certificate.credential_hash = hex::encode(attestation_digest(&self::credential()).unwrap());
+        assert!(!certificate.verify());
+
+        let mut short_quorum = manager.quorum_certificate(&result, &credential).unwrap();
+        short_quorum.signers.pop();
+        short_quorum.threshold_signature.signers.pop();
+        assert!(!short_quorum.verify());
+    }
+
+    #[test]
+    fn quorum_certificate_requires_a_completed_result_for_the_credential() {
+        let (mut manager, _) = manager(2);
+        let credential = credential();
+        let mut result = completed(&mut manager, &credential);
+
+        assert!(matches!(manager.quorum_certificate(&result, &self::credential()), Err(AttestorError::InvalidRequest(_))));
+        result.status = AttestationResultStatus::Failed;
+        assert!(matches!(manager.quorum_certificate(&result, &credential), Err(AttestorError::AttestationError(_))));
+    }
+
+    /// Manager whose verifiers all verify degrees and KYC; v1 has the best global reputation
+    /// but scores poorly in education, and v3 is the education specialist
+    fn capability_manager() -> AttestationManager {
+        let (mut manager, _) = manager(1);
+        for (verifier_id, global, education) in [("v1", 95.0, Some(20.0)), ("v2", 60.0, None), ("v3", 50.0, Some(80.0))] {
+            let verifier = manager.verifiers.get_mut(verifier_id).unwrap();
+            verifier.add_capability(VerificationCapability::EducationVerification);
+            verifier.add_capability(VerificationCapability::KycVerification);
+            verifier.update_reputation(global);
+            if let Some(score) = education {
+                verifier.set_capability_reputation(VerificationCapability::EducationVerification, score);
+            }
+        }
+        manager
+    }
+
+    #[test]
+    fn assignment_ranks_by_reputation_in_the_needed_capability() {
+        let mut degree = credential();
+        degree.add_type(CredentialType::UniversityDegreeCredential);
+        let mut kyc = credential();
+        kyc.add_type(CredentialType::KycCredential);
+
+        assert_eq!(capability_manager().assign_attestors(&request_for(degree.clone()), 1), vec!["v3"]);
+        assert_eq!(capability_manager().assign_attestors(&request_for(degree), 3), vec!["v3", "v2", "v1"]);
+        // Without capability-specific scores the global reputation decides
+        assert_eq!(capability_manager().assign_attestors(&request_for(kyc), 1), vec!["v1"]);
+    }
+
+    #[test]
+    fn identical_inputs_derive_the_same_attestation_id() {
+        let first = Attestation::new("req-1".to_string(), "v1".to_string(), "did:example:v1".to_string(), "urn:uuid:cred".to_string());
+        let retry = Attestation::new("req-1".to_string(), "v1".to_string(), "did:example:v1".to_string(), "urn:uuid:cred".to_string());
+
+        assert_eq!(first.id, retry.id);
+        assert_eq!(first.id, attestation_id("urn:uuid:cred", "v1", "req-1"));
+        assert_ne!(first.id, attestation_id("urn:uuid:cred", "v2", "req-1"));
+        assert_ne!(first.id, attestation_id("urn:uuid:cred", "v1", "req-2"));
+        // Moving bytes between parts changes the id
+        assert_ne!(attestation_id("ab", "c", "d"), attestation_id("a", "bc", "d"));
+    }
+
+    #[test]
+    fn resubmitted_attestation_is_not_double_counted() {
+        let (mut manager, _) = manager(2);
+        let request_id = submit(&mut manager, 2);
+
+        approve(&mut manager, &request_id, "v1");
+        let resubmitted = manager.process_attestation(&request_id, "v1", true, Vec::new(), HashMap::new()).unwrap();
+
+        assert!(!resubmitted);
+        assert_eq!(manager.attestations[&request_id].len(), 1);
+        assert!(manager.try_complete_attestation(&request_id).unwrap().is_none());
+
+        approve(&mut manager, &request_id, "v2");
+        assert!(manager.try_complete_attestation(&request_id).unwrap().is_some());
+    }
+
+    #[test]
+    fn attestation_with_an_invalid_partial_signature_is_rejected() {
+        let (mut manager, _) = manager(2);
+        let request_id = submit(&mut manager, 2);
+        // v1's private share no longer matches the public share it registered
+        let other = manager.key_shares["v2"].private_share.clone();
+        manager.key_shares.get_mut("v1").unwrap().private_share = other;
+
+        let result = manager.process_attestation(&request_id, "v1", true, Vec::new(), HashMap::new());
+
+        assert!(matches!(result, Err(AttestorError::InvalidSignature(message)) if message.contains("v1")));
+        assert!(manager.attestations.get(&request_id).is_none_or(|attestations| attestations.is_empty()));
+        approve(&mut manager, &request_id, "v2");
+        approve(&mut manager, &request_id, "v3");
+        assert!(manager.try_complete_attestation(&request_id).unwrap().is_some());
+    }
+}
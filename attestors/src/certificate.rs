@@ -0,0 +1,75 @@
+//! Quorum certificates that prove a completed attestation without the manager
+
+use std::collections::HashSet;
+use serde::{Deserialize, Serialize};
+use crate::attestation::{AttestationResult, AttestationResultStatus};
+use crate::error::AttestorError;
+use crate::threshold::{ThresholdPublicKey, ThresholdScheme, ThresholdSignature};
+
+/// Compact proof that a threshold of attestors signed a credential
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct QuorumCertificate {
+    /// Hex SHA-256 of the credential's canonical attestation payload
+    pub credential_hash: String,
+    pub threshold: usize,
+    pub signers: Vec<usize>,
+    pub threshold_signature: ThresholdSignature,
+    pub public_key: ThresholdPublicKey,
+}
+
+impl QuorumCertificate {
+    /// Build a certificate from a completed attestation result and the digest its signature covers
+    pub fn from_result(
+        result: &AttestationResult,
+        credential_hash: &[u8],
+        public_key: &ThresholdPublicKey,
+    ) -> Result<Self, AttestorError> {
+        if result.status != AttestationResultStatus::Completed {
+            return Err(AttestorError::AttestationError(format!(
+                "Attestation {} is not completed", result.request_id
+            )));
+        }
+        let threshold_signature = result.threshold_signature.clone()
+            .ok_or_else(|| AttestorError::AttestationError("Result has no threshold signature".to_string()))?;
+
+        Ok(Self {
+            credential_hash: hex::encode(credential_hash),
+            threshold: public_key.threshold,
+            signers: threshold_signature.signers.clone(),
+            threshold_signature,
+            public_key: public_key.clone(),
+        })
+    }
+
+    /// Check the quorum and the pairing equation over the credential hash using only the certificate
+    pub fn verify(&self) -> bool {
+        let credential_hash = match hex::decode(&self.credential_hash) {
+            Ok(hash) if hash.len() == 32 => hash,
+            _ => return false,
+        };
+
+        if self.threshold == 0
+            || self.threshold != self.public_key.threshold
+            || self.signers != self.threshold_signature.signers
+            || self.signers.len() < self.threshold
+        {
+            return false;
+        }
+
+        let mut seen = HashSet::new();
+        let signers_valid = self.signers.iter()
+            .all(|&party| party >= 1 && party <= self.public_key.total_parties && seen.insert(party));
+        if !signers_valid {
+            return false;
+        }
+
+        let scheme = ThresholdScheme {
+            threshold: self.threshold,
+            total_parties: self.public_key.total_parties,
+            scheme_id: self.public_key.scheme_id.clone(),
+            signature_cache: None,
+        };
+        scheme.verify_pairing(&credential_hash, &self.threshold_signature, &self.public_key)
+            .unwrap_or(false)
+    }
+}
@@ -0,0 +1,72 @@
+//! Challenges proving an attestor controls the DID it attests under
+
+use serde::{Deserialize, Serialize};
+use chrono::{DateTime, Utc};
+use identity_core::{verify_data, DidDocument, RelationshipType, Signer};
+use identity_core::crypto::encoding::{decode_base64url, encode_base64url};
+use crate::error::AttestorError;
+
+/// Per-request nonce an attestor signs with a key from its DID document
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct AttestorChallenge {
+    pub request_id: String,
+    pub attestor_id: String,
+    pub attestor_did: String,
+    pub nonce: String,
+    pub issued_at: DateTime<Utc>,
+}
+
+/// Attestor's signature over a challenge
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct ChallengeResponse {
+    pub request_id: String,
+    pub attestor_id: String,
+    /// Authentication method in the attestor's DID document that made the signature
+    pub verification_method: String,
+    /// Base64url signature over the challenge's signing payload
+    pub signature: String,
+}
+
+impl AttestorChallenge {
+    /// Bytes the attestor signs
+    pub fn signing_payload(&self) -> Result<Vec<u8>, AttestorError> {
+        Ok(serde_json::to_vec(&serde_json::to_value(self)?)?)
+    }
+
+    /// Sign the challenge with an authentication key of the attestor's DID
+    pub async fn respond(&self, signer: &dyn Signer, verification_method: String) -> Result<ChallengeResponse, AttestorError> {
+        let signature = signer.sign(&self.signing_payload()?).await?;
+        Ok(ChallengeResponse {
+            request_id: self.request_id.clone(),
+            attestor_id: self.attestor_id.clone(),
+            verification_method,
+            signature: encode_base64url(&signature),
+        })
+    }
+
+    /// Check a response against the attestor's DID document
+    pub fn verify_response(&self, response: &ChallengeResponse, did_document: &DidDocument) -> Result<(), AttestorError> {
+        if response.request_id != self.request_id || response.attestor_id != self.attestor_id {
+            return Err(AttestorError::InvalidRequest("Response is for a different challenge".to_string()));
+        }
+        if did_document.id != self.attestor_did {
+            return Err(AttestorError::PermissionDenied(format!(
+                "DID document {} does not belong to attestor DID {}", did_document.id, self.attestor_did
+            )));
+        }
+
+        let method = did_document.authorized_method(RelationshipType::Authentication, &response.verification_method)
+            .ok_or_else(|| AttestorError::PermissionDenied(format!(
+                "{} is not an authentication method of {}", response.verification_method, self.attestor_did
+            )))?;
+        let (key_type, public_key) = method.key_material()?;
+
+        let signature = decode_base64url(&response.signature)?;
+        if !verify_data(&self.signing_payload()?, &signature, &public_key, &key_type)? {
+            return Err(AttestorError::PermissionDenied(format!(
+                "Challenge signature does not verify for {}", self.attestor_did
+            )));
+        }
+        Ok(())
+    }
+}
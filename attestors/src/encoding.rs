@@ -0,0 +1,162 @@
+//! Point encodings for exchanging threshold artifacts with other BLS12-381 libraries
+
+use bls12_381::{G1Affine, G2Affine};
+use serde::{Deserialize, Serialize};
+use crate::error::AttestorError;
+
+/// Size of an encoded base field element
+const FIELD_ELEMENT_SIZE: usize = 48;
+
+/// Wire format for BLS12-381 points.
+///
+/// Big-endian encodings are the ZCash format used internally. Little-endian encodings reverse
+/// the bytes of each 48-byte field element, so the flag bits end up in the last byte of the
+/// first element.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum PointEncoding {
+    #[default]
+    CompressedBigEndian,
+    UncompressedBigEndian,
+    CompressedLittleEndian,
+    UncompressedLittleEndian,
+}
+
+impl PointEncoding {
+    /// Whether points are stored as the x-coordinate only
+    pub fn is_compressed(&self) -> bool {
+        matches!(self, PointEncoding::CompressedBigEndian | PointEncoding::CompressedLittleEndian)
+    }
+
+    /// Whether field elements are little-endian
+    pub fn is_little_endian(&self) -> bool {
+        matches!(self, PointEncoding::CompressedLittleEndian | PointEncoding::UncompressedLittleEndian)
+    }
+
+    /// Encoded length of a G1 point
+    pub fn g1_len(&self) -> usize {
+        if self.is_compressed() { 48 } else { 96 }
+    }
+
+    /// Encoded length of a G2 point
+    pub fn g2_len(&self) -> usize {
+        if self.is_compressed() { 96 } else { 192 }
+    }
+
+    /// Encode a G1 point
+    pub fn encode_g1(&self, point: &G1Affine) -> Vec<u8> {
+        let bytes = if self.is_compressed() {
+            point.to_compressed().to_vec()
+        } else {
+            point.to_uncompressed().to_vec()
+        };
+        self.apply_endianness(bytes)
+    }
+
+    /// Encode a G2 point
+    pub fn encode_g2(&self, point: &G2Affine) -> Vec<u8> {
+        let bytes = if self.is_compressed() {
+            point.to_compressed().to_vec()
+        } else {
+            point.to_uncompressed().to_vec()
+        };
+        self.apply_endianness(bytes)
+    }
+
+    /// Decode a G1 point, checking it is on the curve and in the subgroup
+    pub fn decode_g1(&self, bytes: &[u8]) -> Result<G1Affine, AttestorError> {
+        if bytes.len() != self.g1_len() {
+            return Err(AttestorError::CryptoError(format!(
+                "Invalid G1 point length {} for {:?}", bytes.len(), self
+            )));
+        }
+        let bytes = self.apply_endianness(bytes.to_vec());
+
+        let point = if self.is_compressed() {
+            let bytes: [u8; 48] = bytes.try_into().expect("length checked above");
+            Option::from(G1Affine::from_compressed(&bytes))
+        } else {
+            let bytes: [u8; 96] = bytes.try_into().expect("length checked above");
+            Option::from(G1Affine::from_uncompressed(&bytes))
+        };
+        point.ok_or_else(|| AttestorError::CryptoError("Invalid G1 point encoding".to_string()))
+    }
+
+    /// Decode a G2 point, checking it is on the curve and in the subgroup
+    pub fn decode_g2(&self, bytes: &[u8]) -> Result<G2Affine, AttestorError> {
+        if bytes.len() != self.g2_len() {
+            return Err(AttestorError::CryptoError(format!(
+                "Invalid G2 point length {} for {:?}", bytes.len(), self
+            )));
+        }
+        let bytes = self.apply_endianness(bytes.to_vec());
+
+        let point = if self.is_compressed() {
+            let bytes: [u8; 96] = bytes.try_into().expect("length checked above");
+            Option::from(G2Affine::from_compressed(&bytes))
+        } else {
+            let bytes: [u8; 192] = bytes.try_into().expect("length checked above");
+            Option::from(G2Affine::from_uncompressed(&bytes))
+        };
+        point.ok_or_else(|| AttestorError::CryptoError("Invalid G2 point encoding".to_string()))
+    }
+
+    /// Convert between big- and little-endian field elements; the conversion is its own inverse
+    fn apply_endianness(&self, mut bytes: Vec<u8>) -> Vec<u8> {
+        if self.is_little_endian() {
+            for element in bytes.chunks_mut(FIELD_ELEMENT_SIZE) {
+                element.reverse();
+            }
+        }
+        bytes
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bls12_381::{G1Projective, G2Projective, Scalar};
+
+    const ENCODINGS: [PointEncoding; 4] = [
+        PointEncoding::CompressedBigEndian,
+        PointEncoding::UncompressedBigEndian,
+        PointEncoding::CompressedLittleEndian,
+        PointEncoding::UncompressedLittleEndian,
+    ];
+
+    fn g1() -> G1Affine {
+        (G1Projective::generator() * Scalar::from(7u64)).into()
+    }
+
+    fn g2() -> G2Affine {
+        (G2Projective::generator() * Scalar::from(11u64)).into()
+    }
+
+    #[test]
+    fn every_encoding_decodes_to_the_same_points() {
+        for encoding in ENCODINGS {
+            assert_eq!(encoding.decode_g1(&encoding.encode_g1(&g1())).unwrap(), g1());
+            assert_eq!(encoding.decode_g2(&encoding.encode_g2(&g2())).unwrap(), g2());
+        }
+    }
+
+    #[test]
+    fn little_endian_reverses_each_field_element() {
+        let big = PointEncoding::UncompressedBigEndian.encode_g2(&g2());
+        let little = PointEncoding::UncompressedLittleEndian.encode_g2(&g2());
+
+        for (big, little) in big.chunks(FIELD_ELEMENT_SIZE).zip(little.chunks(FIELD_ELEMENT_SIZE)) {
+            assert_eq!(big.iter().rev().copied().collect::<Vec<u8>>(), little);
+        }
+        // The flag bits live in the first element's most significant byte
+        assert_eq!(little[FIELD_ELEMENT_SIZE - 1], big[0]);
+    }
+
+    #[test]
+    fn decoding_with_the_wrong_encoding_fails() {
+        let compressed = PointEncoding::CompressedBigEndian.encode_g1(&g1());
+
+        assert!(PointEncoding::UncompressedBigEndian.decode_g1(&compressed).is_err());
+        assert!(PointEncoding::CompressedLittleEndian.decode_g1(&compressed).is_err());
+        assert!(PointEncoding::CompressedBigEndian.decode_g2(&compressed).is_err());
+    }
+}
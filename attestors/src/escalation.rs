@@ -0,0 +1,232 @@
+//! Escalation of stalled attestation requests to a higher-authority verifier
+
+use std::collections::HashMap;
+use chrono::Duration;
+use serde::{Deserialize, Serialize};
+use crate::attestation::{AttestationManager, AttestationResult, AttestationResultStatus, AttestationStatus};
+use crate::error::AttestorError;
+
+/// Who decides a request that neither meets nor fails its threshold, and when
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct EscalationPolicy {
+    /// Id of the verifier whose decision on an escalated request is final
+    pub authority_id: String,
+    /// Time after submission before an incomplete request counts as stalled
+    pub stall_timeout: Duration,
+}
+
+impl EscalationPolicy {
+    /// Escalate requests still incomplete `stall_timeout` after submission to `authority_id`
+    pub fn new(authority_id: String, stall_timeout: Duration) -> Self {
+        Self { authority_id, stall_timeout }
+    }
+}
+
+impl AttestationManager {
+    /// Hand every stalled request to the escalation authority, returning their ids.
+    ///
+    /// A request is stalled once `stall_timeout` has passed and its votes are split: it has
+    /// both approvals and rejections, too few approvals to meet the threshold and too few
+    /// rejections to rule it out. Escalated requests accept no further attestations until
+    /// the authority decides them.
+    pub fn escalate_stalled(&mut self) -> Vec<String> {
+        let Some(policy) = &self.escalation_policy else {
+            return Vec::new();
+        };
+
+        let now = self.clock.now();
+        let stalled: Vec<String> = self.pending_requests.values()
+            .filter(|request| !self.escalated_requests.contains_key(&request.id))
+            .filter(|request| now - request.created_at >= policy.stall_timeout)
+            .filter(|request| {
+                let attestations = self.attestations.get(&request.id).map(Vec::as_slice).unwrap_or_default();
+                let count = |status: AttestationStatus| attestations.iter().filter(|a| a.status == status).count();
+                let (approvals, rejections) = (count(AttestationStatus::Approved), count(AttestationStatus::Rejected));
+                // More rejections than this leave too few attestors to ever meet the threshold
+                let tolerated_rejections = request.required_attestors.len().saturating_sub(request.threshold);
+                approvals > 0 && rejections > 0 && approvals < request.threshold && rejections <= tolerated_rejections
+            })
+            .map(|request| request.id.clone())
+            .collect();
+
+        for request_id in &stalled {
+            self.escalated_requests.insert(request_id.clone(), now);
+        }
+        stalled
+    }
+
+    /// Record the escalation authority's final decision on an escalated request.
+    ///
+    /// Approval marks the request `Escalated` and rejection fails it. The result carries no
+    /// threshold signature; its metadata records the escalation, the authority and the collected votes.
+    pub fn resolve_escalation(
+        &mut self,
+        request_id: &str,
+        authority_id: &str,
+        approved: bool,
+    ) -> Result<AttestationResult, AttestorError> {
+        if self.escalation_policy.as_ref().map(|policy| policy.authority_id.as_str()) != Some(authority_id) {
+            return Err(AttestorError::PermissionDenied(format!(
+                "{} is not the escalation authority", authority_id
+            )));
+        }
+        if !self.verifiers.contains_key(authority_id) {
+            return Err(AttestorError::PermissionDenied(format!(
+                "Escalation authority {} is no longer a verifier", authority_id
+            )));
+        }
+        let escalated_at = *self.escalated_requests.get(request_id)
+            .ok_or_else(|| AttestorError::InvalidRequest(format!("Request {} has not been escalated", request_id)))?;
+        let request = self.pending_requests.remove(request_id)
+            .ok_or_else(|| AttestorError::NotFound(format!("Request {} not found", request_id)))?;
+        self.escalated_requests.remove(request_id);
+        self.release_assignments(request_id);
+
+        let attestations = self.attestations.get(request_id).cloned().unwrap_or_default();
+        let count = |status: AttestationStatus| attestations.iter().filter(|a| a.status == status).count();
+        let mut participating_attestors: Vec<String> = attestations.iter()
+            .filter(|a| a.status == AttestationStatus::Approved)
+            .map(|a| a.attestor_id.clone())
+            .collect();
+        if approved {
+            participating_attestors.push(authority_id.to_string());
+        }
+
+        let mut metadata = HashMap::new();
+        metadata.insert("escalated".to_string(), serde_json::Value::Bool(true));
+        metadata.insert("escalated_at".to_string(), serde_json::Value::String(escalated_at.to_rfc3339()));
+        metadata.insert("escalation_authority".to_string(), serde_json::Value::String(authority_id.to_string()));
+        metadata.insert("escalation_decision".to_string(), serde_json::Value::String(
+            if approved { "approved" } else { "rejected" }.to_string()
+        ));
+        metadata.insert("approvals".to_string(), serde_json::Value::Number(count(AttestationStatus::Approved).into()));
+        metadata.insert("rejections".to_string(), serde_json::Value::Number(count(AttestationStatus::Rejected).into()));
+
+        let result = AttestationResult {
+            request_id: request_id.to_string(),
+            credential_id: request.credential.id.clone(),
+            threshold_signature: None,
+            participating_attestors,
+            status: if approved { AttestationResultStatus::Escalated } else { AttestationResultStatus::Failed },
+            created_at: self.clock.now(),
+            metadata,
+        };
+
+        if approved {
+            self.publish_completion(&result);
+        }
+        Ok(result)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::BTreeMap;
+    use std::sync::Arc;
+    use identity_core::{MockClock, VerifiableCredential};
+    use crate::attestation::AttestationRequest;
+    use crate::verifier::Verifier;
+
+    /// Manager with attestors v1..v3 and an `authority` verifier, a 2-of-3 request and a one-hour stall timeout
+    fn stalled_setup() -> (AttestationManager, MockClock, String) {
+        let verifiers = ["v1", "v2", "v3", "authority"].iter()
+            .map(|id| Verifier::new(id.to_string(), format!("did:example:{}", id), id.to_string(), Vec::new()))
+            .collect();
+        let mut manager = AttestationManager::new(2, 4, verifiers).unwrap();
+        let clock = MockClock::default();
+        manager.set_clock(Arc::new(clock.clone()));
+        manager.set_escalation_policy(EscalationPolicy::new("authority".to_string(), Duration::hours(1))).unwrap();
+
+        let mut claims = BTreeMap::new();
+        claims.insert("name".to_string(), serde_json::json!("Alice"));
+        let credential = VerifiableCredential::new("did:example:issuer".to_string(), Some("did:example:alice".to_string()), claims);
+        let request = AttestationRequest::new_with_clock(credential, vec!["v1".to_string(), "v2".to_string(), "v3".to_string()], 2, &clock);
+        let request_id = manager.submit_request(request).unwrap();
+        (manager, clock, request_id)
+    }
+
+    fn vote(manager: &mut AttestationManager, request_id: &str, attestor_id: &str, approved: bool) {
+        manager.process_attestation(request_id, attestor_id, approved, Vec::new(), HashMap::new()).unwrap();
+    }
+
+    #[test]
+    fn split_request_escalates_and_approval_marks_it_escalated() {
+        let (mut manager, clock, request_id) = stalled_setup();
+        vote(&mut manager, &request_id, "v1", true);
+        vote(&mut manager, &request_id, "v2", false);
+
+        assert!(manager.escalate_stalled().is_empty());
+        clock.advance(Duration::hours(1));
+        assert_eq!(manager.escalate_stalled(), vec![request_id.clone()]);
+        assert!(manager.process_attestation(&request_id, "v3", true, Vec::new(), HashMap::new()).is_err());
+
+        let result = manager.resolve_escalation(&request_id, "authority", true).unwrap();
+
+        assert_eq!(result.status, AttestationResultStatus::Escalated);
+        assert!(result.threshold_signature.is_none());
+        assert_eq!(result.participating_attestors, vec!["v1", "authority"]);
+        assert_eq!(result.metadata["escalation_decision"], "approved");
+        assert_eq!(result.metadata["approvals"], 1);
+        assert_eq!(result.metadata["rejections"], 1);
+        assert!(manager.verify_attestation_result_for(&result, b"payload").is_err());
+    }
+
+    #[test]
+    fn authority_rejection_fails_split_request() {
+        let (mut manager, clock, request_id) = stalled_setup();
+        vote(&mut manager, &request_id, "v1", true);
+        vote(&mut manager, &request_id, "v2", false);
+        clock.advance(Duration::hours(1));
+        manager.escalate_stalled();
+
+        let result = manager.resolve_escalation(&request_id, "authority", false).unwrap();
+
+        assert_eq!(result.status, AttestationResultStatus::Failed);
+        assert_eq!(result.participating_attestors, vec!["v1"]);
+        assert_eq!(result.metadata["escalation_decision"], "rejected");
+    }
+
+    #[test]
+    fn timeout_alone_does_not_escalate() {
+        let (mut manager, clock, request_id) = stalled_setup();
+        clock.advance(Duration::hours(1));
+        assert!(manager.escalate_stalled().is_empty());
+
+        vote(&mut manager, &request_id, "v1", true);
+        assert!(manager.escalate_stalled().is_empty());
+    }
+
+    #[test]
+    fn decided_rejection_is_not_escalated() {
+        let (mut manager, clock, request_id) = stalled_setup();
+        vote(&mut manager, &request_id, "v1", true);
+        vote(&mut manager, &request_id, "v2", false);
+        vote(&mut manager, &request_id, "v3", false);
+        clock.advance(Duration::hours(1));
+
+        assert!(manager.escalate_stalled().is_empty());
+    }
+
+    #[test]
+    fn only_the_authority_can_resolve() {
+        let (mut manager, clock, request_id) = stalled_setup();
+        vote(&mut manager, &request_id, "v1", true);
+        vote(&mut manager, &request_id, "v2", false);
+        clock.advance(Duration::hours(1));
+        assert_eq!(manager.escalate_stalled(), vec![request_id.clone()]);
+
+        assert!(matches!(manager.resolve_escalation(&request_id, "v3", true), Err(AttestorError::PermissionDenied(_))));
+
+        manager.remove_verifier("authority").unwrap();
+        assert!(matches!(manager.resolve_escalation(&request_id, "authority", true), Err(AttestorError::PermissionDenied(_))));
+    }
+
+    #[test]
+    fn authority_must_be_a_verifier() {
+        let (mut manager, _, _) = stalled_setup();
+        let policy = EscalationPolicy::new("stranger".to_string(), Duration::hours(1));
+
+        assert!(matches!(manager.set_escalation_policy(policy), Err(AttestorError::NotFound(_))));
+    }
+}
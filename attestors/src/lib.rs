@@ -4,11 +4,27 @@
 //! Implements BLS12-381 threshold signatures for k-of-n credential attestation.
 
 pub mod threshold;
+pub mod aggregate;
+pub mod signature_cache;
 pub mod attestation;
 pub mod verifier;
+pub mod receipt;
+pub mod certificate;
+pub mod challenge;
+pub mod escalation;
+pub mod webhook;
+pub mod encoding;
 pub mod error;
 
 pub use threshold::*;
+pub use aggregate::*;
+pub use signature_cache::*;
 pub use attestation::*;
 pub use verifier::*;
+pub use receipt::*;
+pub use certificate::*;
+pub use challenge::*;
+pub use escalation::*;
+pub use webhook::*;
+pub use encoding::*;
 pub use error::*;
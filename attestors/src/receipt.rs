@@ -0,0 +1,124 @@
+//! Verifier-signed attestation receipts the subject can present independently
+
+use serde::{Deserialize, Serialize};
+use chrono::{DateTime, Utc};
+use identity_core::{KeyType, Signer, verify_data};
+use identity_core::crypto::encoding::{encode_base64url, decode_base64url};
+use crate::attestation::{Attestation, AttestationStatus};
+use crate::error::AttestorError;
+
+/// Signed statement that an attestor verified claims of a credential
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct AttestationReceipt {
+    pub attestation_id: String,
+    pub attestor_did: String,
+    pub credential_id: String,
+    pub timestamp: DateTime<Utc>,
+    pub verified_claims: Vec<String>,
+    pub key_type: KeyType,
+    pub signature: String,
+}
+
+impl AttestationReceipt {
+    /// Bytes covered by the receipt signature (the receipt without its signature)
+    pub fn signing_payload(&self) -> Result<Vec<u8>, AttestorError> {
+        let mut value = serde_json::to_value(self)?;
+        if let Some(object) = value.as_object_mut() {
+            object.remove("signature");
+        }
+        Ok(serde_json::to_vec(&value)?)
+    }
+}
+
+impl Attestation {
+    /// Produce a receipt for this approved attestation signed by the attestor
+    pub async fn to_receipt(&self, signer: &dyn Signer) -> Result<AttestationReceipt, AttestorError> {
+        if self.status != AttestationStatus::Approved {
+            return Err(AttestorError::AttestationError(
+                "Receipts can only be issued for approved attestations".to_string()
+            ));
+        }
+
+        let mut receipt = AttestationReceipt {
+            attestation_id: self.id.clone(),
+            attestor_did: self.attestor_did.clone(),
+            credential_id: self.credential_id.clone(),
+            timestamp: Utc::now(),
+            verified_claims: self.verified_claims.clone(),
+            key_type: signer.key_type(),
+            signature: String::new(),
+        };
+
+        let signature = signer.sign(&receipt.signing_payload()?).await?;
+        receipt.signature = encode_base64url(&signature);
+        Ok(receipt)
+    }
+}
+
+/// Verify a receipt's signature against the attestor's public key
+pub fn verify_receipt(receipt: &AttestationReceipt, public_key: &[u8]) -> Result<bool, AttestorError> {
+    let signature = decode_base64url(&receipt.signature)?;
+    Ok(verify_data(&receipt.signing_payload()?, &signature, public_key, &receipt.key_type)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use identity_core::{InMemorySigner, generate_ed25519_keypair};
+    use crate::threshold::ThresholdScheme;
+
+    fn approved_attestation() -> Attestation {
+        let scheme = ThresholdScheme::new(1, 1).unwrap();
+        let (shares, _) = scheme.generate_key_shares().unwrap();
+        let partial_signature = scheme.partial_sign(b"credential digest", &shares[0]).unwrap();
+
+        let mut attestation = Attestation::new(
+            "request-1".to_string(),
+            "v1".to_string(),
+            "did:example:v1".to_string(),
+            "urn:uuid:credential-1".to_string(),
+        );
+        attestation.approve(partial_signature, vec!["name".to_string(), "address.country".to_string()]);
+        attestation
+    }
+
+    #[tokio::test]
+    async fn receipt_verifies_against_the_attestor_key() {
+        let keypair = generate_ed25519_keypair().unwrap();
+        let attestation = approved_attestation();
+
+        let receipt = attestation.to_receipt(&InMemorySigner::new(keypair.clone())).await.unwrap();
+
+        assert_eq!(receipt.attestor_did, "did:example:v1");
+        assert_eq!(receipt.credential_id, "urn:uuid:credential-1");
+        assert_eq!(receipt.verified_claims, attestation.verified_claims);
+        assert!(verify_receipt(&receipt, &keypair.public_key).unwrap());
+        assert!(!verify_receipt(&receipt, &generate_ed25519_keypair().unwrap().public_key).unwrap());
+    }
+
+    #[tokio::test]
+    async fn tampered_verified_claims_fail_verification() {
+        let keypair = generate_ed25519_keypair().unwrap();
+        let mut receipt = approved_attestation().to_receipt(&InMemorySigner::new(keypair.clone())).await.unwrap();
+
+        receipt.verified_claims.push("dateOfBirth".to_string());
+        assert!(!verify_receipt(&receipt, &keypair.public_key).unwrap());
+
+        receipt.verified_claims = vec!["address.country".to_string(), "name".to_string()];
+        assert!(!verify_receipt(&receipt, &keypair.public_key).unwrap());
+    }
+
+    #[tokio::test]
+    async fn rejected_attestations_have_no_receipt() {
+        let mut attestation = Attestation::new(
+            "request-1".to_string(),
+            "v1".to_string(),
+            "did:example:v1".to_string(),
+            "urn:uuid:credential-1".to_string(),
+        );
+        attestation.reject("Document mismatch".to_string());
+
+        let signer = InMemorySigner::new(generate_ed25519_keypair().unwrap());
+        assert!(matches!(attestation.to_receipt(&signer).await, Err(AttestorError::AttestationError(_))));
+    }
+}
@@ -0,0 +1,200 @@
+//! Reuse of partial signatures over identical messages
+
+use std::collections::{HashMap, VecDeque};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use identity_core::hash_data;
+use crate::threshold::{KeyShare, PartialSignature};
+
+/// Default number of partial signatures kept
+pub const DEFAULT_SIGNATURE_CACHE_CAPACITY: usize = 4096;
+
+/// Bounded cache of partial signatures; clones share the same entries.
+///
+/// Entries are keyed by party and message hash, plus the scheme and the share's public key,
+/// so a refreshed or different share never receives another share's signature.
+#[derive(Debug, Clone)]
+pub struct PartialSignatureCache {
+    inner: Arc<CacheInner>,
+}
+
+#[derive(Debug)]
+struct CacheInner {
+    capacity: usize,
+    entries: Mutex<CacheEntries>,
+    hits: AtomicU64,
+    misses: AtomicU64,
+}
+
+#[derive(Debug, Default)]
+struct CacheEntries {
+    signatures: HashMap<SignatureKey, PartialSignature>,
+    order: VecDeque<SignatureKey>, // insertion order, oldest first
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+struct SignatureKey {
+    party_id: usize,
+    message_hash: Vec<u8>,
+    scheme_id: String,
+    share_fingerprint: Vec<u8>,
+}
+
+/// Hit and miss counts of a signature cache
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct SignatureCacheStats {
+    pub hits: u64,
+    pub misses: u64,
+    pub entries: usize,
+}
+
+impl PartialSignatureCache {
+    /// Create a cache holding up to `capacity` signatures, evicting the oldest first
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            inner: Arc::new(CacheInner {
+                capacity,
+                entries: Mutex::new(CacheEntries::default()),
+                hits: AtomicU64::new(0),
+                misses: AtomicU64::new(0),
+            }),
+        }
+    }
+
+    /// Cached signature by a share over a message, if any
+    pub(crate) fn get(&self, message: &[u8], key_share: &KeyShare) -> Option<PartialSignature> {
+        let key = SignatureKey::new(message, key_share);
+        let cached = self.lock().signatures.get(&key).cloned();
+        let counter = if cached.is_some() { &self.inner.hits } else { &self.inner.misses };
+        counter.fetch_add(1, Ordering::Relaxed);
+        cached
+    }
+
+    /// Remember a share's signature over a message
+    pub(crate) fn insert(&self, message: &[u8], key_share: &KeyShare, signature: PartialSignature) {
+        if self.inner.capacity == 0 {
+            return;
+        }
+
+        let key = SignatureKey::new(message, key_share);
+        let mut entries = self.lock();
+        if entries.signatures.insert(key.clone(), signature).is_none() {
+            entries.order.push_back(key);
+        }
+        while entries.order.len() > self.inner.capacity {
+            if let Some(oldest) = entries.order.pop_front() {
+                entries.signatures.remove(&oldest);
+            }
+        }
+    }
+
+    /// Drop every cached signature
+    pub fn clear(&self) {
+        let mut entries = self.lock();
+        entries.signatures.clear();
+        entries.order.clear();
+    }
+
+    /// Current hit, miss and entry counts
+    pub fn stats(&self) -> SignatureCacheStats {
+        SignatureCacheStats {
+            hits: self.inner.hits.load(Ordering::Relaxed),
+            misses: self.inner.misses.load(Ordering::Relaxed),
+            entries: self.lock().signatures.len(),
+        }
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, CacheEntries> {
+        self.inner.entries.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+impl Default for PartialSignatureCache {
+    fn default() -> Self {
+        Self::new(DEFAULT_SIGNATURE_CACHE_CAPACITY)
+    }
+}
+
+impl SignatureKey {
+    fn new(message: &[u8], key_share: &KeyShare) -> Self {
+        Self {
+            party_id: key_share.party_id,
+            message_hash: hash_data(message),
+            scheme_id: key_share.scheme_id.clone(),
+            share_fingerprint: hash_data(&key_share.public_share),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::threshold::ThresholdScheme;
+
+    fn cached_scheme(capacity: usize) -> (ThresholdScheme, Vec<KeyShare>, PartialSignatureCache) {
+        let cache = PartialSignatureCache::new(capacity);
+        let scheme = ThresholdScheme::new(2, 3).unwrap().with_signature_cache(cache.clone());
+        let (shares, _) = scheme.generate_key_shares().unwrap();
+        (scheme, shares, cache)
+    }
+
+    #[test]
+    fn identical_message_is_served_from_the_cache() {
+        let (scheme, shares, cache) = cached_scheme(16);
+
+        let first = scheme.partial_sign(b"credential digest", &shares[0]).unwrap();
+        let second = scheme.partial_sign(b"credential digest", &shares[0]).unwrap();
+
+        assert_eq!(second.signature, first.signature);
+        assert_eq!(second.party_id, first.party_id);
+        assert_eq!(cache.stats(), SignatureCacheStats { hits: 1, misses: 1, entries: 1 });
+    }
+
+    #[test]
+    fn message_differing_by_one_byte_is_recomputed() {
+        let (scheme, shares, cache) = cached_scheme(16);
+
+        let original = scheme.partial_sign(b"credential digest", &shares[0]).unwrap();
+        let altered = scheme.partial_sign(b"credential digesT", &shares[0]).unwrap();
+        let other_party = scheme.partial_sign(b"credential digest", &shares[1]).unwrap();
+
+        assert_ne!(altered.signature, original.signature);
+        assert_ne!(other_party.signature, original.signature);
+        assert_eq!(cache.stats(), SignatureCacheStats { hits: 0, misses: 3, entries: 3 });
+    }
+
+    #[test]
+    fn cached_partials_still_combine_into_a_valid_signature() {
+        let scheme = ThresholdScheme::new(2, 3).unwrap().with_signature_cache(PartialSignatureCache::new(16));
+        let (shares, public_key) = scheme.generate_key_shares().unwrap();
+        let message = b"credential digest";
+        let sign = || -> Vec<PartialSignature> {
+            shares[..2].iter().map(|share| scheme.partial_sign(message, share).unwrap()).collect()
+        };
+
+        let computed = scheme.combine_signatures(&sign()).unwrap();
+        let cached = scheme.combine_signatures(&sign()).unwrap();
+
+        assert_eq!(cached.signature, computed.signature);
+        assert!(scheme.verify_signature(message, &cached, &public_key).unwrap());
+        assert_eq!(scheme.signature_cache.as_ref().unwrap().stats().hits, 2);
+    }
+
+    #[test]
+    fn oldest_entries_are_evicted_and_zero_capacity_caches_nothing() {
+        let (scheme, shares, cache) = cached_scheme(2);
+        for message in [b"one".as_slice(), b"two", b"three"] {
+            scheme.partial_sign(message, &shares[0]).unwrap();
+        }
+        assert_eq!(cache.stats().entries, 2);
+        scheme.partial_sign(b"one", &shares[0]).unwrap();
+        assert_eq!(cache.stats().hits, 0);
+        cache.clear();
+        assert_eq!(cache.stats().entries, 0);
+
+        let (scheme, shares, cache) = cached_scheme(0);
+        scheme.partial_sign(b"one", &shares[0]).unwrap();
+        scheme.partial_sign(b"one", &shares[0]).unwrap();
+        assert_eq!(cache.stats(), SignatureCacheStats { hits: 0, misses: 2, entries: 0 });
+    }
+}
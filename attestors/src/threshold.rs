@@ -1,11 +1,18 @@
 //! Threshold signature implementation using BLS12-381
 
-use bls12_381::{G1Projective, G2Projective, Scalar};
+use std::collections::BTreeMap;
+use bls12_381::hash_to_curve::{ExpandMsgXmd, HashToCurve};
+use bls12_381::{multi_miller_loop, G1Affine, G1Projective, G2Affine, G2Prepared, G2Projective, Gt, Scalar};
 use ff::Field;
 use group::GroupEncoding;
 use rand::rngs::OsRng;
 use serde::{Deserialize, Serialize};
+use crate::encoding::PointEncoding;
 use crate::error::AttestorError;
+use crate::signature_cache::PartialSignatureCache;
+
+/// Prefix of the hash-to-curve domain separation tag; the scheme id completes it
+pub const THRESHOLD_DST_PREFIX: &[u8] = b"DIMS_THRESHOLD_BLS12381G2_XMD:SHA-256_SSWU_RO_";
 
 /// Threshold signature scheme configuration
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -13,6 +20,9 @@ pub struct ThresholdScheme {
     pub threshold: usize,
     pub total_parties: usize,
     pub scheme_id: String,
+    /// Reuses partial signatures over identical messages when set
+    #[serde(skip)]
+    pub signature_cache: Option<PartialSignatureCache>,
 }
 
 /// Individual party's key share
@@ -49,6 +59,17 @@ pub struct ThresholdSignature {
     pub signers: Vec<usize>,
 }
 
+/// Verifier for one threshold public key, decoded and checked once.
+///
+/// bls12_381 only prepares the G2 side of a pairing, and both G2 inputs change with every
+/// signature, so `verify` costs the same as the one-shot path; `verify_batch` is what
+/// amortizes the pairing work across many signatures.
+#[derive(Debug, Clone)]
+pub struct PreparedVerifier {
+    scheme: ThresholdScheme,
+    public_key: G1Affine,
+}
+
 impl ThresholdScheme {
     /// Create a new threshold scheme
     pub fn new(threshold: usize, total_parties: usize) -> Result<Self, AttestorError> {
@@ -62,10 +83,19 @@ impl ThresholdScheme {
             threshold,
             total_parties,
             scheme_id: uuid::Uuid::new_v4().to_string(),
+            signature_cache: None,
         })
     }
 
-    /// Generate distributed key shares using Shamir's Secret Sharing
+    /// Return cached partial signatures for messages a share has already signed
+    pub fn with_signature_cache(mut self, cache: PartialSignatureCache) -> Self {
+        self.signature_cache = Some(cache);
+        self
+    }
+
+    /// Generate distributed key shares using Shamir's Secret Sharing.
+    ///
+    /// The caller briefly holds the master secret; use `DkgSession` or `DkgParticipant` to avoid a trusted dealer.
     pub fn generate_key_shares(&self) -> Result<(Vec<KeyShare>, ThresholdPublicKey), AttestorError> {
         // Generate master secret key
         let master_secret = Scalar::random(&mut OsRng);
@@ -110,6 +140,41 @@ impl ThresholdScheme {
         Ok((key_shares, threshold_public_key))
     }
 
+    /// Rerandomize key shares without changing the threshold public key.
+    ///
+    /// Each share gains the evaluation of a fresh polynomial whose constant term is zero, so the
+    /// shared secret is unchanged but shares from before the refresh no longer interpolate with
+    /// shares from after it. Parties should discard their old shares.
+    pub fn refresh_shares(&self, old_shares: &[KeyShare]) -> Result<Vec<KeyShare>, AttestorError> {
+        let mut party_ids = Vec::with_capacity(old_shares.len());
+        for share in old_shares {
+            if share.scheme_id != self.scheme_id {
+                return Err(AttestorError::KeyGenerationError("Key share scheme ID mismatch".to_string()));
+            }
+            if share.party_id == 0 || share.party_id > self.total_parties || party_ids.contains(&share.party_id) {
+                return Err(AttestorError::KeyGenerationError(format!("Invalid or duplicate party id {}", share.party_id)));
+            }
+            party_ids.push(share.party_id);
+        }
+
+        // delta(x) = c_1 x + ... + c_{t-1} x^{t-1}, so delta(0) = 0
+        let coefficients: Vec<Scalar> = (1..self.threshold).map(|_| Scalar::random(&mut OsRng)).collect();
+        old_shares.iter()
+            .map(|share| {
+                let x = Scalar::from(share.party_id as u64);
+                let delta = coefficients.iter().rev().fold(Scalar::zero(), |acc, coefficient| (acc + coefficient) * x);
+                let private_share = decode_scalar(&share.private_share)? + delta;
+
+                Ok(KeyShare {
+                    party_id: share.party_id,
+                    private_share: private_share.to_bytes().to_vec(),
+                    public_share: (G1Projective::generator() * private_share).to_bytes().as_ref().to_vec(),
+                    scheme_id: self.scheme_id.clone(),
+                })
+            })
+            .collect()
+    }
+
     /// Create a partial signature with a key share
     pub fn partial_sign(
         &self,
@@ -120,22 +185,60 @@ impl ThresholdScheme {
             return Err(AttestorError::InvalidSignature("Key share scheme ID mismatch".to_string()));
         }
 
+        if let Some(cached) = self.signature_cache.as_ref().and_then(|cache| cache.get(message, key_share)) {
+            return Ok(cached);
+        }
+
         // Convert private share back to Scalar
         let private_bytes: [u8; 32] = key_share.private_share.clone().try_into()
             .map_err(|_| AttestorError::InvalidSignature("Invalid private share format".to_string()))?;
-        let private_scalar = Scalar::from_bytes(&private_bytes).unwrap();
+        let private_scalar: Scalar = Option::from(Scalar::from_bytes(&private_bytes))
+            .ok_or_else(|| AttestorError::InvalidSignature("Private share is not a canonical scalar".to_string()))?;
 
-        // Hash message to G2 (simplified - in practice use proper hash-to-curve)
         let message_hash = self.hash_to_g2(message);
 
         // Create partial signature
         let partial_sig = message_hash * private_scalar;
 
-        Ok(PartialSignature {
+        let partial_signature = PartialSignature {
             party_id: key_share.party_id,
             signature: partial_sig.to_bytes().as_ref().to_vec(),
             scheme_id: self.scheme_id.clone(),
-        })
+        };
+        if let Some(cache) = &self.signature_cache {
+            cache.insert(message, key_share, partial_signature.clone());
+        }
+        Ok(partial_signature)
+    }
+
+    /// Verify one party's partial signature against its public share: e(pk_i, H(m)) == e(g1, sig_i)
+    pub fn verify_partial(
+        &self,
+        message: &[u8],
+        partial: &PartialSignature,
+        key_share_public: &[u8],
+    ) -> Result<bool, AttestorError> {
+        if partial.scheme_id != self.scheme_id {
+            return Err(AttestorError::InvalidSignature("Signature scheme ID mismatch".to_string()));
+        }
+
+        let public_share = decode_g1(key_share_public)?;
+        let sig = match decode_g2(&partial.signature) {
+            Ok(sig) => sig,
+            Err(_) => return Ok(false),
+        };
+        if bool::from(public_share.is_identity()) || bool::from(sig.is_identity()) {
+            return Ok(false);
+        }
+        let message_hash = G2Affine::from(self.hash_to_g2(message));
+
+        let result = multi_miller_loop(&[
+            (&public_share, &G2Prepared::from(message_hash)),
+            (&-G1Affine::generator(), &G2Prepared::from(sig)),
+        ])
+        .final_exponentiation();
+
+        Ok(result == Gt::identity())
     }
 
     /// Combine partial signatures into a threshold signature
@@ -156,16 +259,30 @@ impl ThresholdScheme {
             }
         }
 
-        // Simplified combination for now - in production this would use proper Lagrange interpolation
-        let signers: Vec<usize> = partial_signatures.iter().take(self.threshold).map(|s| s.party_id).collect();
+        // Interpolate at zero over every distinct party: sig = sum(lambda_i * sig_i). Any threshold of
+        // valid shares yields the same signature, so extra shares only widen the recorded quorum
+        let mut shares: Vec<&PartialSignature> = Vec::with_capacity(partial_signatures.len());
+        for sig in partial_signatures {
+            if sig.party_id == 0 || sig.party_id > self.total_parties {
+                return Err(AttestorError::InvalidSignature(format!("Unknown party id {}", sig.party_id)));
+            }
+            if !shares.iter().any(|share| share.party_id == sig.party_id) {
+                shares.push(sig);
+            }
+        }
+        if shares.len() < self.threshold {
+            return Err(AttestorError::ThresholdNotMet(
+                format!("Need signatures from {} distinct parties, got {}", self.threshold, shares.len())
+            ));
+        }
 
-        // For now, just use the first signature as a placeholder
-        // TODO: Implement proper BLS signature aggregation
-        let combined_signature = if let Some(first_sig) = partial_signatures.first() {
-            first_sig.signature.clone()
-        } else {
-            return Err(AttestorError::InvalidSignature("No signatures to combine".to_string()));
-        };
+        let signers: Vec<usize> = shares.iter().map(|share| share.party_id).collect();
+        let mut combined = G2Projective::identity();
+        for share in &shares {
+            let point = decode_g2(&share.signature)?;
+            combined += G2Projective::from(point) * self.lagrange_coefficient(share.party_id, &signers);
+        }
+        let combined_signature = G2Affine::from(combined).to_compressed().to_vec();
 
         Ok(ThresholdSignature {
             signature: combined_signature,
@@ -174,7 +291,7 @@ impl ThresholdScheme {
         })
     }
 
-    /// Verify a threshold signature
+    /// Verify a threshold signature against the master public key
     pub fn verify_signature(
         &self,
         message: &[u8],
@@ -185,21 +302,22 @@ impl ThresholdScheme {
             return Err(AttestorError::InvalidSignature("Scheme ID mismatch".to_string()));
         }
 
-        // Simplified verification for now
-        // TODO: Implement proper BLS signature verification with pairings
-
-        // Basic checks
-        if signature.signature.is_empty() || public_key.public_key.is_empty() {
-            return Ok(false);
-        }
+        self.verify_pairing(message, signature, public_key)
+    }
 
-        if signature.signers.len() < self.threshold {
-            return Ok(false);
-        }
+    /// Decode a threshold public key once for verifying many signatures
+    pub fn prepare_verifier(&self, public_key: &ThresholdPublicKey) -> Result<PreparedVerifier, AttestorError> {
+        PreparedVerifier::new(self, public_key)
+    }
 
-        // For now, just return true if basic checks pass
-        // In production, this would do proper pairing-based verification
-        Ok(true)
+    /// Verify a threshold signature with the pairing equation e(pk, H(m)) == e(g1, sig)
+    pub fn verify_pairing(
+        &self,
+        message: &[u8],
+        signature: &ThresholdSignature,
+        public_key: &ThresholdPublicKey,
+    ) -> Result<bool, AttestorError> {
+        self.prepare_verifier(public_key)?.verify(message, signature)
     }
 
     /// Calculate Lagrange coefficient for interpolation
@@ -217,16 +335,636 @@ impl ThresholdScheme {
         coeff
     }
 
-    /// Simplified hash-to-G2 function (in practice, use proper hash-to-curve)
+    /// Hash a message to G2 under a domain separation tag bound to this scheme
     fn hash_to_g2(&self, message: &[u8]) -> G2Projective {
-        use sha2::{Sha256, Digest};
-        let mut hasher = Sha256::new();
-        hasher.update(message);
-        hasher.update(&self.scheme_id.as_bytes());
-        let hash = hasher.finalize();
-
-        // This is a simplified approach - in production use proper hash-to-curve
-        let scalar = Scalar::from_bytes_wide(&[hash.as_slice(), hash.as_slice()].concat().try_into().unwrap());
-        G2Projective::generator() * scalar
+        let dst = [THRESHOLD_DST_PREFIX, self.scheme_id.as_bytes()].concat();
+        <G2Projective as HashToCurve<ExpandMsgXmd<sha2_v09::Sha256>>>::hash_to_curve(message, &dst)
+    }
+}
+
+impl ThresholdPublicKey {
+    /// Encode the public key point in a counterpart library's format
+    pub fn to_encoding(&self, encoding: PointEncoding) -> Result<Vec<u8>, AttestorError> {
+        Ok(encoding.encode_g1(&decode_g1(&self.public_key)?))
+    }
+
+    /// Build a public key from a point in the given format
+    pub fn from_encoding(
+        bytes: &[u8],
+        encoding: PointEncoding,
+        scheme_id: String,
+        threshold: usize,
+        total_parties: usize,
+    ) -> Result<Self, AttestorError> {
+        Ok(Self {
+            public_key: encoding.decode_g1(bytes)?.to_compressed().to_vec(),
+            scheme_id,
+            threshold,
+            total_parties,
+        })
+    }
+}
+
+impl ThresholdSignature {
+    /// Encode the signature point in a counterpart library's format
+    pub fn to_encoding(&self, encoding: PointEncoding) -> Result<Vec<u8>, AttestorError> {
+        Ok(encoding.encode_g2(&decode_g2(&self.signature)?))
+    }
+
+    /// Build a signature from a point in the given format
+    pub fn from_encoding(
+        bytes: &[u8],
+        encoding: PointEncoding,
+        scheme_id: String,
+        signers: Vec<usize>,
+    ) -> Result<Self, AttestorError> {
+        Ok(Self {
+            signature: encoding.decode_g2(bytes)?.to_compressed().to_vec(),
+            scheme_id,
+            signers,
+        })
+    }
+}
+
+impl PreparedVerifier {
+    /// Decode and prepare the public key for a scheme
+    pub fn new(scheme: &ThresholdScheme, public_key: &ThresholdPublicKey) -> Result<Self, AttestorError> {
+        if public_key.scheme_id != scheme.scheme_id {
+            return Err(AttestorError::InvalidSignature("Scheme ID mismatch".to_string()));
+        }
+
+        Ok(Self {
+            scheme: scheme.clone(),
+            public_key: decode_g1(&public_key.public_key)?,
+        })
+    }
+
+    /// Verify a threshold signature over a message
+    pub fn verify(&self, message: &[u8], signature: &ThresholdSignature) -> Result<bool, AttestorError> {
+        let Some(sig) = self.checked_signature(signature)? else {
+            return Ok(false);
+        };
+        let message_hash = self.scheme.hash_to_g2(message);
+        Ok(self.pairing_holds(message_hash, sig.into()))
+    }
+
+    /// Verify many signatures with a single two-term multi-pairing.
+    ///
+    /// Each signature is weighted by a fresh random scalar, so
+    /// `e(pk, sum r_i H(m_i)) * e(-g1, sum r_i sig_i) == 1` holds only if every signature is
+    /// valid, except with negligible probability. Returns `false` if any fails; `verify`
+    /// tells which.
+    pub fn verify_batch(&self, items: &[(&[u8], &ThresholdSignature)]) -> Result<bool, AttestorError> {
+        if items.is_empty() {
+            return Err(AttestorError::InvalidSignature("No signatures to verify".to_string()));
+        }
+
+        let mut message_hashes = G2Projective::identity();
+        let mut signatures = G2Projective::identity();
+        for (message, signature) in items {
+            let Some(sig) = self.checked_signature(signature)? else {
+                return Ok(false);
+            };
+            let weight = Scalar::random(&mut OsRng);
+            message_hashes += self.scheme.hash_to_g2(message) * weight;
+            signatures += G2Projective::from(sig) * weight;
+        }
+        Ok(self.pairing_holds(message_hashes, signatures))
+    }
+
+    /// Decode a signature that can be checked, or `None` if it fails without a pairing
+    fn checked_signature(&self, signature: &ThresholdSignature) -> Result<Option<G2Affine>, AttestorError> {
+        if signature.scheme_id != self.scheme.scheme_id {
+            return Err(AttestorError::InvalidSignature("Scheme ID mismatch".to_string()));
+        }
+
+        if signature.signers.len() < self.scheme.threshold {
+            return Ok(None);
+        }
+
+        // The identity key and signature satisfy the pairing equation for every message
+        if bool::from(self.public_key.is_identity()) {
+            return Ok(None);
+        }
+        Ok(decode_g2(&signature.signature).ok().filter(|sig| !bool::from(sig.is_identity())))
+    }
+
+    /// Check e(pk, message_hash) * e(-g1, signature) == 1
+    fn pairing_holds(&self, message_hash: G2Projective, signature: G2Projective) -> bool {
+        let result = multi_miller_loop(&[
+            (&self.public_key, &G2Prepared::from(G2Affine::from(message_hash))),
+            (&-G1Affine::generator(), &G2Prepared::from(G2Affine::from(signature))),
+        ])
+        .final_exponentiation();
+
+        result == Gt::identity()
+    }
+}
+
+/// Broadcast of a dealer's Feldman commitments to its polynomial coefficients, constant term first
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DkgCommitment {
+    pub dealer_id: usize,
+    pub commitments: Vec<Vec<u8>>,
+    pub scheme_id: String,
+}
+
+/// Share of a dealer's polynomial evaluated for one recipient; must travel over a private channel
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DkgShare {
+    pub dealer_id: usize,
+    pub recipient_id: usize,
+    pub share: Vec<u8>,
+    pub scheme_id: String,
+}
+
+/// One party's side of a Pedersen distributed key generation.
+///
+/// Every party deals a random polynomial, so the master secret is the sum of all dealers'
+/// constant terms and is never held by anyone.
+#[derive(Debug, Clone)]
+pub struct DkgParticipant {
+    scheme: ThresholdScheme,
+    party_id: usize,
+    coefficients: Vec<Scalar>,
+    commitments: BTreeMap<usize, Vec<G1Projective>>, // dealer -> coefficient commitments
+    shares: BTreeMap<usize, Scalar>,                  // dealer -> verified share for this party
+}
+
+/// In-process run of a DKG among all of a scheme's parties, producing the same output as the trusted dealer
+#[derive(Debug, Clone)]
+pub struct DkgSession {
+    participants: Vec<DkgParticipant>,
+}
+
+impl DkgParticipant {
+    /// Join a key generation for a scheme as `party_id` (1-based), dealing a fresh polynomial
+    pub fn new(scheme: &ThresholdScheme, party_id: usize) -> Result<Self, AttestorError> {
+        if party_id == 0 || party_id > scheme.total_parties {
+            return Err(AttestorError::KeyGenerationError(format!("Unknown party id {}", party_id)));
+        }
+
+        let coefficients: Vec<Scalar> = (0..scheme.threshold).map(|_| Scalar::random(&mut OsRng)).collect();
+        let mut participant = Self {
+            scheme: scheme.clone(),
+            party_id,
+            coefficients,
+            commitments: BTreeMap::new(),
+            shares: BTreeMap::new(),
+        };
+        participant.receive_commitment(&participant.commitment())?;
+        participant.receive_share(&participant.share_for(party_id)?)?;
+        Ok(participant)
+    }
+
+    /// Party id of this participant
+    pub fn party_id(&self) -> usize {
+        self.party_id
+    }
+
+    /// Round 1: commitments to broadcast to every party
+    pub fn commitment(&self) -> DkgCommitment {
+        DkgCommitment {
+            dealer_id: self.party_id,
+            commitments: self.coefficients.iter()
+                .map(|coefficient| (G1Projective::generator() * coefficient).to_bytes().as_ref().to_vec())
+                .collect(),
+            scheme_id: self.scheme.scheme_id.clone(),
+        }
+    }
+
+    /// Round 2: this party's polynomial evaluated at a recipient's id
+    pub fn share_for(&self, recipient_id: usize) -> Result<DkgShare, AttestorError> {
+        if recipient_id == 0 || recipient_id > self.scheme.total_parties {
+            return Err(AttestorError::KeyGenerationError(format!("Unknown party id {}", recipient_id)));
+        }
+
+        let x = Scalar::from(recipient_id as u64);
+        let share = self.coefficients.iter().rev().fold(Scalar::zero(), |acc, coefficient| acc * x + coefficient);
+        Ok(DkgShare {
+            dealer_id: self.party_id,
+            recipient_id,
+            share: share.to_bytes().to_vec(),
+            scheme_id: self.scheme.scheme_id.clone(),
+        })
+    }
+
+    /// Record a dealer's broadcast commitments
+    pub fn receive_commitment(&mut self, commitment: &DkgCommitment) -> Result<(), AttestorError> {
+        self.check_dealer(commitment.dealer_id, &commitment.scheme_id)?;
+        if commitment.commitments.len() != self.scheme.threshold {
+            return Err(AttestorError::KeyGenerationError(format!(
+                "Party {} committed to {} coefficients, expected {}",
+                commitment.dealer_id, commitment.commitments.len(), self.scheme.threshold
+            )));
+        }
+        if self.commitments.contains_key(&commitment.dealer_id) {
+            return Err(AttestorError::KeyGenerationError(format!(
+                "Party {} already sent its commitments", commitment.dealer_id
+            )));
+        }
+
+        let points = commitment.commitments.iter()
+            .map(|bytes| decode_g1(bytes).map(G1Projective::from))
+            .collect::<Result<Vec<_>, _>>()?;
+        self.commitments.insert(commitment.dealer_id, points);
+        Ok(())
+    }
+
+    /// Round 3: accept a share addressed to this party after checking it against the dealer's commitments
+    pub fn receive_share(&mut self, share: &DkgShare) -> Result<(), AttestorError> {
+        self.check_dealer(share.dealer_id, &share.scheme_id)?;
+        if share.recipient_id != self.party_id {
+            return Err(AttestorError::KeyGenerationError(format!(
+                "Share for party {} delivered to party {}", share.recipient_id, self.party_id
+            )));
+        }
+        let commitments = self.commitments.get(&share.dealer_id).ok_or_else(|| {
+            AttestorError::KeyGenerationError(format!("No commitments from party {} yet", share.dealer_id))
+        })?;
+
+        let value = decode_scalar(&share.share)?;
+        // g1 * share == sum_k C_k * id^k
+        let x = Scalar::from(self.party_id as u64);
+        let expected = commitments.iter().rev().fold(G1Projective::identity(), |acc, commitment| acc * x + commitment);
+        if G1Projective::generator() * value != expected {
+            return Err(AttestorError::VerificationError(format!(
+                "Share from party {} does not match its commitments", share.dealer_id
+            )));
+        }
+
+        self.shares.insert(share.dealer_id, value);
+        Ok(())
+    }
+
+    /// Combine the verified shares of every dealer into this party's key share and the joint public key
+    pub fn finalize(&self) -> Result<(KeyShare, ThresholdPublicKey), AttestorError> {
+        let missing: Vec<usize> = (1..=self.scheme.total_parties)
+            .filter(|dealer| !self.shares.contains_key(dealer))
+            .collect();
+        if !missing.is_empty() {
+            return Err(AttestorError::ThresholdNotMet(format!("Missing verified shares from parties {:?}", missing)));
+        }
+
+        let private_share: Scalar = self.shares.values().sum();
+        let master_public: G1Projective = self.commitments.values().map(|commitments| commitments[0]).sum();
+
+        let key_share = KeyShare {
+            party_id: self.party_id,
+            private_share: private_share.to_bytes().to_vec(),
+            public_share: (G1Projective::generator() * private_share).to_bytes().as_ref().to_vec(),
+            scheme_id: self.scheme.scheme_id.clone(),
+        };
+        let public_key = ThresholdPublicKey {
+            public_key: master_public.to_bytes().as_ref().to_vec(),
+            scheme_id: self.scheme.scheme_id.clone(),
+            threshold: self.scheme.threshold,
+            total_parties: self.scheme.total_parties,
+        };
+        Ok((key_share, public_key))
+    }
+
+    fn check_dealer(&self, dealer_id: usize, scheme_id: &str) -> Result<(), AttestorError> {
+        if scheme_id != self.scheme.scheme_id {
+            return Err(AttestorError::KeyGenerationError("DKG message scheme ID mismatch".to_string()));
+        }
+        if dealer_id == 0 || dealer_id > self.scheme.total_parties {
+            return Err(AttestorError::KeyGenerationError(format!("Unknown party id {}", dealer_id)));
+        }
+        Ok(())
+    }
+}
+
+impl DkgSession {
+    /// Start a session with one participant per party of the scheme
+    pub fn new(scheme: &ThresholdScheme) -> Result<Self, AttestorError> {
+        let participants = (1..=scheme.total_parties)
+            .map(|party_id| DkgParticipant::new(scheme, party_id))
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(Self { participants })
+    }
+
+    /// Exchange commitments and shares between every pair of parties, then finalize each.
+    ///
+    /// Fails if any share does not match its dealer's commitments or the parties disagree on the public key.
+    pub fn run(mut self) -> Result<(Vec<KeyShare>, ThresholdPublicKey), AttestorError> {
+        let commitments: Vec<DkgCommitment> = self.participants.iter().map(DkgParticipant::commitment).collect();
+        let mut shares = Vec::new();
+        for dealer in &self.participants {
+            for recipient in &self.participants {
+                if recipient.party_id != dealer.party_id {
+                    shares.push(dealer.share_for(recipient.party_id)?);
+                }
+            }
+        }
+
+        for participant in &mut self.participants {
+            let party_id = participant.party_id;
+            for commitment in commitments.iter().filter(|c| c.dealer_id != party_id) {
+                participant.receive_commitment(commitment)?;
+            }
+            for share in shares.iter().filter(|share| share.recipient_id == party_id) {
+                participant.receive_share(share)?;
+            }
+        }
+
+        let mut key_shares = Vec::with_capacity(self.participants.len());
+        let mut public_key: Option<ThresholdPublicKey> = None;
+        for participant in &self.participants {
+            let (key_share, participant_key) = participant.finalize()?;
+            match &public_key {
+                Some(agreed) if agreed.public_key != participant_key.public_key => {
+                    return Err(AttestorError::KeyGenerationError("Parties derived different public keys".to_string()));
+                }
+                Some(_) => {}
+                None => public_key = Some(participant_key),
+            }
+            key_shares.push(key_share);
+        }
+
+        let public_key = public_key
+            .ok_or_else(|| AttestorError::KeyGenerationError("DKG session has no parties".to_string()))?;
+        Ok((key_shares, public_key))
+    }
+}
+
+/// Decode a canonical scalar
+fn decode_scalar(bytes: &[u8]) -> Result<Scalar, AttestorError> {
+    let bytes: [u8; 32] = bytes.try_into()
+        .map_err(|_| AttestorError::CryptoError("Invalid scalar length".to_string()))?;
+    Option::from(Scalar::from_bytes(&bytes))
+        .ok_or_else(|| AttestorError::CryptoError("Invalid scalar encoding".to_string()))
+}
+
+/// Decode a compressed G1 point
+pub(crate) fn decode_g1(bytes: &[u8]) -> Result<G1Affine, AttestorError> {
+    let bytes: [u8; 48] = bytes.try_into()
+        .map_err(|_| AttestorError::CryptoError("Invalid G1 point length".to_string()))?;
+    Option::from(G1Affine::from_compressed(&bytes))
+        .ok_or_else(|| AttestorError::CryptoError("Invalid G1 point encoding".to_string()))
+}
+
+/// Decode a compressed G2 point
+pub(crate) fn decode_g2(bytes: &[u8]) -> Result<G2Affine, AttestorError> {
+    let bytes: [u8; 96] = bytes.try_into()
+        .map_err(|_| AttestorError::CryptoError("Invalid G2 point length".to_string()))?;
+    Option::from(G2Affine::from_compressed(&bytes))
+        .ok_or_else(|| AttestorError::CryptoError("Invalid G2 point encoding".to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Threshold signatures by parties 1 and 2 of a 2-of-3 scheme over each message
+    fn signed(scheme: &ThresholdScheme, shares: &[KeyShare], messages: &[Vec<u8>]) -> Vec<ThresholdSignature> {
+        messages.iter()
+            .map(|message| {
+                let partials: Vec<PartialSignature> = shares[..2].iter()
+                    .map(|share| scheme.partial_sign(message, share).unwrap())
+                    .collect();
+                scheme.combine_signatures(&partials).unwrap()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn prepared_verifier_matches_one_shot_across_many_messages() {
+        let scheme = ThresholdScheme::new(2, 3).unwrap();
+        let (shares, public_key) = scheme.generate_key_shares().unwrap();
+        let messages: Vec<Vec<u8>> = (0..16).map(|i| format!("message {}", i).into_bytes()).collect();
+        let signatures = signed(&scheme, &shares, &messages);
+        let verifier = scheme.prepare_verifier(&public_key).unwrap();
+
+        // Each message against its own signature and its neighbour's
+        for (i, message) in messages.iter().enumerate() {
+            for j in [i, (i + 1) % messages.len()] {
+                let one_shot = scheme.verify_signature(message, &signatures[j], &public_key).unwrap();
+                assert_eq!(verifier.verify(message, &signatures[j]).unwrap(), one_shot);
+                assert_eq!(one_shot, i == j);
+            }
+        }
+    }
+
+    #[test]
+    fn batch_verification_fails_if_any_signature_is_wrong() {
+        let scheme = ThresholdScheme::new(2, 3).unwrap();
+        let (shares, public_key) = scheme.generate_key_shares().unwrap();
+        let messages: Vec<Vec<u8>> = (0..16).map(|i| format!("message {}", i).into_bytes()).collect();
+        let signatures = signed(&scheme, &shares, &messages);
+        let verifier = scheme.prepare_verifier(&public_key).unwrap();
+
+        let mut items: Vec<(&[u8], &ThresholdSignature)> = messages.iter()
+            .map(Vec::as_slice)
+            .zip(signatures.iter())
+            .collect();
+        assert!(verifier.verify_batch(&items).unwrap());
+
+        items[3].1 = &signatures[4];
+        assert!(!verifier.verify_batch(&items).unwrap());
+        assert!(verifier.verify_batch(&[]).is_err());
+    }
+
+    #[test]
+    fn identity_and_non_canonical_signatures_do_not_verify() {
+        let scheme = ThresholdScheme::new(2, 3).unwrap();
+        let (shares, public_key) = scheme.generate_key_shares().unwrap();
+        let valid = signed(&scheme, &shares, &[b"message".to_vec()]).remove(0);
+        let identity = ThresholdSignature { signature: G2Affine::identity().to_compressed().to_vec(), ..valid.clone() };
+
+        assert!(scheme.verify_signature(b"message", &valid, &public_key).unwrap());
+        assert!(!scheme.verify_signature(b"message", &identity, &public_key).unwrap());
+
+        // The identity key with the identity signature satisfies the pairing for any message
+        let identity_key = ThresholdPublicKey { public_key: G1Affine::identity().to_compressed().to_vec(), ..public_key.clone() };
+        assert!(!scheme.verify_signature(b"message", &identity, &identity_key).unwrap());
+
+        // Clearing the compression flag makes the encoding non-canonical
+        let mut uncompressed_flag = valid.clone();
+        uncompressed_flag.signature[0] &= 0x7f;
+        assert!(!scheme.verify_signature(b"message", &uncompressed_flag, &public_key).unwrap());
+    }
+
+    #[test]
+    fn identity_partial_signature_does_not_verify() {
+        let scheme = ThresholdScheme::new(2, 3).unwrap();
+        let (shares, _) = scheme.generate_key_shares().unwrap();
+        let partial = scheme.partial_sign(b"message", &shares[0]).unwrap();
+        let identity = PartialSignature { signature: G2Affine::identity().to_compressed().to_vec(), ..partial.clone() };
+
+        assert!(scheme.verify_partial(b"message", &partial, &shares[0].public_share).unwrap());
+        assert!(!scheme.verify_partial(b"message", &identity, &shares[0].public_share).unwrap());
+        assert!(!scheme.verify_partial(b"message", &partial, &G1Affine::identity().to_compressed()).unwrap());
+    }
+
+    #[test]
+    fn signature_round_trips_through_every_point_encoding() {
+        let scheme = ThresholdScheme::new(2, 3).unwrap();
+        let (shares, public_key) = scheme.generate_key_shares().unwrap();
+        let message = b"interop".to_vec();
+        let signature = signed(&scheme, &shares, std::slice::from_ref(&message)).remove(0);
+
+        for encoding in [
+            PointEncoding::CompressedBigEndian,
+            PointEncoding::UncompressedBigEndian,
+            PointEncoding::CompressedLittleEndian,
+            PointEncoding::UncompressedLittleEndian,
+        ] {
+            let encoded_signature = signature.to_encoding(encoding).unwrap();
+            let encoded_key = public_key.to_encoding(encoding).unwrap();
+            assert_eq!(encoded_signature.len(), encoding.g2_len());
+            assert_eq!(encoded_key.len(), encoding.g1_len());
+
+            let decoded_signature = ThresholdSignature::from_encoding(
+                &encoded_signature, encoding, signature.scheme_id.clone(), signature.signers.clone(),
+            ).unwrap();
+            let decoded_key = ThresholdPublicKey::from_encoding(
+                &encoded_key, encoding, public_key.scheme_id.clone(), public_key.threshold, public_key.total_parties,
+            ).unwrap();
+            assert_eq!(decoded_signature.signature, signature.signature);
+            assert_eq!(decoded_key.public_key, public_key.public_key);
+            assert!(scheme.verify_signature(&message, &decoded_signature, &decoded_key).unwrap());
+        }
+    }
+
+    #[test]
+    fn any_threshold_of_parties_combines_to_the_same_valid_signature() {
+        let scheme = ThresholdScheme::new(3, 5).unwrap();
+        let (shares, public_key) = scheme.generate_key_shares().unwrap();
+        let partials: Vec<PartialSignature> = shares.iter()
+            .map(|share| scheme.partial_sign(b"credential", share).unwrap())
+            .collect();
+
+        let first = scheme.combine_signatures(&partials[..3]).unwrap();
+        assert_eq!(first.signers, vec![1, 2, 3]);
+        assert!(scheme.verify_signature(b"credential", &first, &public_key).unwrap());
+        assert!(!scheme.verify_signature(b"other credential", &first, &public_key).unwrap());
+
+        for subset in [[0, 2, 4], [4, 3, 1], [1, 2, 3]] {
+            let chosen: Vec<PartialSignature> = subset.iter().map(|&i| partials[i].clone()).collect();
+            let combined = scheme.combine_signatures(&chosen).unwrap();
+            assert_eq!(combined.signature, first.signature, "{:?}", subset);
+        }
+        // The signature is not just one party's share
+        assert!(partials.iter().all(|partial| partial.signature != first.signature));
+    }
+
+    #[test]
+    fn fewer_than_threshold_parties_cannot_combine() {
+        let scheme = ThresholdScheme::new(3, 5).unwrap();
+        let (shares, public_key) = scheme.generate_key_shares().unwrap();
+        let partials: Vec<PartialSignature> = shares[..2].iter()
+            .map(|share| scheme.partial_sign(b"credential", share).unwrap())
+            .collect();
+
+        assert!(matches!(scheme.combine_signatures(&partials), Err(AttestorError::ThresholdNotMet(_))));
+        // A repeated share does not count as another party
+        let repeated = vec![partials[0].clone(), partials[1].clone(), partials[1].clone()];
+        assert!(matches!(scheme.combine_signatures(&repeated), Err(AttestorError::ThresholdNotMet(_))));
+
+        // Interpolating below the threshold yields a signature the master key rejects
+        let two_of_five = ThresholdScheme { threshold: 2, ..scheme.clone() };
+        let short = two_of_five.combine_signatures(&partials).unwrap();
+        assert!(!scheme.verify_signature(b"credential", &short, &public_key).unwrap());
+    }
+
+    #[test]
+    fn non_canonical_private_share_is_rejected() {
+        let scheme = ThresholdScheme::new(2, 3).unwrap();
+        let (mut shares, _) = scheme.generate_key_shares().unwrap();
+        shares[0].private_share = vec![0xff; 32];
+
+        assert!(matches!(scheme.partial_sign(b"credential", &shares[0]), Err(AttestorError::InvalidSignature(_))));
+    }
+
+    #[test]
+    fn dkg_shares_reconstruct_the_joint_public_key() {
+        let scheme = ThresholdScheme::new(3, 5).unwrap();
+        let (shares, public_key) = DkgSession::new(&scheme).unwrap().run().unwrap();
+
+        assert_eq!(shares.iter().map(|share| share.party_id).collect::<Vec<_>>(), vec![1, 2, 3, 4, 5]);
+        // Interpolating any threshold of public shares at zero yields the master public key
+        for signers in [vec![1, 2, 3], vec![2, 4, 5]] {
+            let reconstructed: G1Projective = signers.iter()
+                .map(|&id| G1Projective::from(decode_g1(&shares[id - 1].public_share).unwrap()) * scheme.lagrange_coefficient(id, &signers))
+                .sum();
+            assert_eq!(reconstructed.to_bytes().as_ref(), public_key.public_key.as_slice());
+        }
+
+        let partials: Vec<PartialSignature> = shares[2..].iter()
+            .map(|share| scheme.partial_sign(b"credential", share).unwrap())
+            .collect();
+        let signature = scheme.combine_signatures(&partials).unwrap();
+        assert!(scheme.verify_signature(b"credential", &signature, &public_key).unwrap());
+
+        // Every run deals fresh polynomials
+        let (_, other_key) = DkgSession::new(&scheme).unwrap().run().unwrap();
+        assert_ne!(other_key.public_key, public_key.public_key);
+    }
+
+    #[test]
+    fn dkg_rejects_shares_that_do_not_match_commitments() {
+        let scheme = ThresholdScheme::new(2, 3).unwrap();
+        let dealer = DkgParticipant::new(&scheme, 1).unwrap();
+        let mut recipient = DkgParticipant::new(&scheme, 2).unwrap();
+
+        let share = dealer.share_for(2).unwrap();
+        assert!(recipient.receive_share(&share).is_err(), "shares need the dealer's commitments first");
+        recipient.receive_commitment(&dealer.commitment()).unwrap();
+        assert!(recipient.receive_commitment(&dealer.commitment()).is_err());
+
+        let mut tampered = share.clone();
+        tampered.share = (decode_scalar(&share.share).unwrap() + Scalar::one()).to_bytes().to_vec();
+        assert!(matches!(recipient.receive_share(&tampered), Err(AttestorError::VerificationError(_))));
+        assert!(recipient.receive_share(&dealer.share_for(3).unwrap()).is_err());
+
+        recipient.receive_share(&share).unwrap();
+        // Party 3 has not dealt yet
+        assert!(matches!(recipient.finalize(), Err(AttestorError::ThresholdNotMet(_))));
+    }
+
+    #[test]
+    fn refreshed_shares_sign_but_do_not_mix_with_old_shares() {
+        let scheme = ThresholdScheme::new(2, 3).unwrap();
+        let (old_shares, public_key) = scheme.generate_key_shares().unwrap();
+        let new_shares = scheme.refresh_shares(&old_shares).unwrap();
+        let sign = |shares: [&KeyShare; 2]| {
+            let partials: Vec<PartialSignature> = shares.iter()
+                .map(|share| scheme.partial_sign(b"credential", share).unwrap())
+                .collect();
+            scheme.verify_signature(b"credential", &scheme.combine_signatures(&partials).unwrap(), &public_key).unwrap()
+        };
+
+        assert!(new_shares.iter().zip(&old_shares).all(|(new, old)| new.private_share != old.private_share));
+        assert!(sign([&new_shares[0], &new_shares[2]]));
+        assert!(!sign([&old_shares[0], &new_shares[2]]));
+        assert!(!sign([&new_shares[1], &old_shares[2]]));
+
+        // A second refresh keeps the same public key
+        let newer_shares = scheme.refresh_shares(&new_shares).unwrap();
+        assert!(sign([&newer_shares[1], &newer_shares[2]]));
+        assert!(!sign([&new_shares[1], &newer_shares[2]]));
+    }
+
+    #[test]
+    fn refresh_rejects_foreign_or_duplicate_shares() {
+        let scheme = ThresholdScheme::new(2, 3).unwrap();
+        let (shares, _) = scheme.generate_key_shares().unwrap();
+        let (foreign, _) = ThresholdScheme::new(2, 3).unwrap().generate_key_shares().unwrap();
+
+        assert!(scheme.refresh_shares(&[shares[0].clone(), shares[0].clone()]).is_err());
+        assert!(scheme.refresh_shares(&[shares[0].clone(), foreign[1].clone()]).is_err());
+    }
+
+    #[test]
+    fn partial_signature_verifies_only_against_its_own_share_and_message() {
+        let scheme = ThresholdScheme::new(2, 3).unwrap();
+        let (shares, _) = scheme.generate_key_shares().unwrap();
+        let partial = scheme.partial_sign(b"message", &shares[0]).unwrap();
+
+        assert!(scheme.verify_partial(b"message", &partial, &shares[0].public_share).unwrap());
+        assert!(!scheme.verify_partial(b"message", &partial, &shares[1].public_share).unwrap());
+        assert!(!scheme.verify_partial(b"other message", &partial, &shares[0].public_share).unwrap());
+        assert!(scheme.verify_partial(b"message", &partial, &[0u8; 10]).is_err());
     }
 }
@@ -3,7 +3,7 @@
 use serde::{Deserialize, Serialize};
 use chrono::{DateTime, Utc};
 use std::collections::HashMap;
-use identity_core::{VerifiableCredential, DidDocument};
+use identity_core::{VerifiableCredential, DidDocument, FreshnessPolicy, ReportCheckV1, ReportV1, ReportVerifierV1, freshness_score};
 use crate::error::AttestorError;
 
 /// Verifier entity that can participate in attestations
@@ -16,14 +16,35 @@ pub struct Verifier {
     pub public_key: Vec<u8>,
     pub verification_methods: Vec<String>,
     pub capabilities: Vec<VerificationCapability>,
+    /// Global reputation, used for capabilities without a specific score
     pub reputation_score: f64,
+    /// Reputation earned in specific capabilities
+    #[serde(default, with = "capability_scores")]
+    pub capability_reputation: HashMap<VerificationCapability, f64>,
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
     pub metadata: HashMap<String, serde_json::Value>,
+    #[serde(skip)]
+    pub freshness_policy: Option<FreshnessPolicy>,
+    #[serde(default)]
+    pub scoring_weights: ScoringWeights,
 }
 
-/// Types of verification capabilities a verifier can have
+/// Weights used to compute a verification confidence score
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct ScoringWeights {
+    /// Multiplier applied to the verifier's reputation score
+    pub reputation: f64,
+    /// Points awarded for full coverage of the required fields
+    pub coverage: f64,
+    /// Multiplier applied to the freshness bucket score (5-20 points)
+    pub freshness: f64,
+    /// Flat points for the issuer
+    pub issuer: f64,
+}
+
+/// Types of verification capabilities a verifier can have
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, Hash)]
 pub enum VerificationCapability {
     KycVerification,
     AgeVerification,
@@ -83,9 +104,12 @@ impl Verifier {
             verification_methods: Vec::new(),
             capabilities: Vec::new(),
             reputation_score: 0.0,
+            capability_reputation: HashMap::new(),
             created_at: Utc::now(),
             updated_at: Utc::now(),
             metadata: HashMap::new(),
+            freshness_policy: None,
+            scoring_weights: ScoringWeights::default(),
         }
     }
 
@@ -97,6 +121,13 @@ impl Verifier {
         }
     }
 
+    /// Check if the verifier has the capability required for a credential type
+    pub fn can_verify(&self, credential_type: &str) -> bool {
+        self.get_capability_for_credential_type(credential_type)
+            .map(|capability| self.has_capability(&capability))
+            .unwrap_or(false)
+    }
+
     /// Check if verifier has a specific capability
     pub fn has_capability(&self, capability: &VerificationCapability) -> bool {
         self.capabilities.contains(capability)
@@ -108,12 +139,42 @@ impl Verifier {
         self.updated_at = Utc::now();
     }
 
+    /// Reject credentials older than the policy allows
+    pub fn set_freshness_policy(&mut self, policy: FreshnessPolicy) {
+        self.freshness_policy = Some(policy);
+        self.updated_at = Utc::now();
+    }
+
+    /// Set the weights used for confidence scoring
+    pub fn set_scoring_weights(&mut self, weights: ScoringWeights) {
+        self.scoring_weights = weights;
+        self.updated_at = Utc::now();
+    }
+
     /// Update reputation score
     pub fn update_reputation(&mut self, score: f64) {
         self.reputation_score = score.clamp(0.0, 100.0);
         self.updated_at = Utc::now();
     }
 
+    /// Set the reputation score for one capability
+    pub fn set_capability_reputation(&mut self, capability: VerificationCapability, score: f64) {
+        self.capability_reputation.insert(capability, score.clamp(0.0, 100.0));
+        self.updated_at = Utc::now();
+    }
+
+    /// Reputation in a capability, falling back to the global score
+    pub fn capability_score(&self, capability: &VerificationCapability) -> f64 {
+        self.capability_reputation.get(capability).copied().unwrap_or(self.reputation_score)
+    }
+
+    /// Reputation relevant to verifying a credential type
+    pub fn reputation_for(&self, credential_type: &str) -> f64 {
+        self.get_capability_for_credential_type(credential_type)
+            .map(|capability| self.capability_score(&capability))
+            .unwrap_or(self.reputation_score)
+    }
+
     /// Add metadata
     pub fn add_metadata(&mut self, key: String, value: serde_json::Value) {
         self.metadata.insert(key, value);
@@ -138,10 +199,22 @@ impl Verifier {
         credential.validate()
             .map_err(|e| AttestorError::InvalidSignature(format!("Invalid credential: {}", e)))?;
 
-        // Check required fields
+        if let Some(policy) = &self.freshness_policy {
+            policy.check(credential)
+                .map_err(|e| AttestorError::VerificationError(e.to_string()))?;
+        }
+
+        // Check required fields (dotted paths into the claim tree), applying any validation rule
         let mut verified_claims = Vec::new();
         for field in &criteria.required_fields {
-            if credential.credential_subject.claims.contains_key(field) {
+            let value = match credential.credential_subject.get_path(field) {
+                Some(value) => value,
+                None => continue,
+            };
+            let satisfies_rule = criteria.validation_rules.get(field)
+                .map(|rule| identity_core::schema::schema_errors(value, rule).is_empty())
+                .unwrap_or(true);
+            if satisfies_rule {
                 verified_claims.push(field.clone());
             }
         }
@@ -182,31 +255,29 @@ impl Verifier {
         criteria: &VerificationCriteria,
         verified_claims: &[String],
     ) -> f64 {
+        let weights = &self.scoring_weights;
         let mut score = 0.0;
 
-        // Base score from reputation
-        score += self.reputation_score * 0.3;
+        // Base score from reputation in the capability being exercised
+        score += self.reputation_for(&criteria.credential_type) * weights.reputation;
 
         // Score from verified claims coverage
-        let coverage = verified_claims.len() as f64 / criteria.required_fields.len() as f64;
-        score += coverage * 40.0;
-
-        // Score from credential freshness
-        let age_days = (Utc::now() - credential.issuance_date).num_days();
-        let freshness_score = if age_days <= 30 {
-            20.0
-        } else if age_days <= 90 {
-            15.0
-        } else if age_days <= 365 {
-            10.0
+        let coverage = if criteria.required_fields.is_empty() {
+            1.0
         } else {
-            5.0
+            verified_claims.len() as f64 / criteria.required_fields.len() as f64
         };
-        score += freshness_score;
+        score += coverage * weights.coverage;
+
+        // Score from credential freshness
+        score += freshness_score(credential.age()) * weights.freshness;
 
         // Score from issuer reputation (simplified)
-        score += 10.0; // Would be based on actual issuer reputation
+        score += weights.issuer; // Would be based on actual issuer reputation
 
+        if score.is_nan() {
+            return 0.0;
+        }
         score.clamp(0.0, 100.0)
     }
 
@@ -223,6 +294,56 @@ impl Verifier {
     }
 }
 
+/// Capability scores serialized as pairs, since custom capabilities cannot be JSON object keys
+mod capability_scores {
+    use std::collections::HashMap;
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+    use super::VerificationCapability;
+
+    pub fn serialize<S: Serializer>(scores: &HashMap<VerificationCapability, f64>, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut pairs: Vec<(&VerificationCapability, &f64)> = scores.iter().collect();
+        pairs.sort_by_key(|(capability, _)| capability.to_string());
+        pairs.serialize(serializer)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<HashMap<VerificationCapability, f64>, D::Error> {
+        Ok(Vec::<(VerificationCapability, f64)>::deserialize(deserializer)?.into_iter().collect())
+    }
+}
+
+impl VerificationResult {
+    /// Convert to the version 1 report format, with one passed `claim.<path>` check per verified claim
+    pub fn report_v1(&self) -> ReportV1 {
+        let checks = self.verified_claims.iter()
+            .map(|claim| ReportCheckV1::new(&format!("claim.{}", claim), true, None))
+            .collect();
+        ReportV1::new(self.credential_id.clone(), checks, self.timestamp)
+            .with_verifier(ReportVerifierV1 {
+                evidence_level: Some(self.evidence_level.to_string()),
+                confidence: Some(self.confidence_score),
+                ..ReportVerifierV1::new(&self.verifier_id)
+            })
+    }
+}
+
+impl ScoringWeights {
+    /// Create scoring weights
+    pub fn new(reputation: f64, coverage: f64, freshness: f64, issuer: f64) -> Self {
+        Self {
+            reputation,
+            coverage,
+            freshness,
+            issuer,
+        }
+    }
+}
+
+impl Default for ScoringWeights {
+    fn default() -> Self {
+        Self::new(0.3, 40.0, 1.0, 10.0)
+    }
+}
+
 impl VerificationCapability {
     /// Get human-readable description of the capability
     pub fn description(&self) -> &str {
@@ -266,3 +387,107 @@ impl std::fmt::Display for EvidenceLevel {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::BTreeMap;
+
+    fn verifier(weights: ScoringWeights) -> Verifier {
+        let mut verifier = Verifier::new("v1".to_string(), "did:example:v1".to_string(), "Verifier 1".to_string(), Vec::new());
+        verifier.add_capability(VerificationCapability::EducationVerification);
+        verifier.update_reputation(50.0);
+        verifier.set_scoring_weights(weights);
+        verifier
+    }
+
+    fn criteria() -> VerificationCriteria {
+        VerificationCriteria {
+            credential_type: "UniversityDegreeCredential".to_string(),
+            required_fields: vec!["degree".to_string(), "gpa".to_string()],
+            validation_rules: HashMap::new(),
+            minimum_evidence_level: EvidenceLevel::Medium,
+        }
+    }
+
+    /// Fresh credential carrying one of the two required fields
+    fn credential() -> VerifiableCredential {
+        let mut claims = BTreeMap::new();
+        claims.insert("degree".to_string(), serde_json::json!("BSc"));
+        VerifiableCredential::new("did:example:university".to_string(), Some("did:example:alice".to_string()), claims)
+    }
+
+    fn score(weights: ScoringWeights) -> f64 {
+        verifier(weights).verify_credential(&credential(), &criteria()).unwrap().confidence_score
+    }
+
+    #[test]
+    fn default_weights_keep_the_original_scoring() {
+        // 50 * 0.3 reputation + 0.5 * 40 coverage + 20 freshness + 10 issuer
+        assert_eq!(score(ScoringWeights::default()), 65.0);
+    }
+
+    #[test]
+    fn custom_weights_change_the_score_predictably() {
+        assert_eq!(score(ScoringWeights::new(0.0, 40.0, 1.0, 10.0)), 50.0);
+        assert_eq!(score(ScoringWeights::new(0.3, 80.0, 1.0, 10.0)), 85.0);
+        assert_eq!(score(ScoringWeights::new(0.3, 40.0, 0.5, 0.0)), 45.0);
+        assert_eq!(score(ScoringWeights::new(1.0, 0.0, 0.0, 0.0)), 50.0);
+    }
+
+    #[test]
+    fn scores_are_clamped_to_the_valid_range() {
+        assert_eq!(score(ScoringWeights::new(1.0, 100.0, 2.0, 50.0)), 100.0);
+        assert_eq!(score(ScoringWeights::new(-1.0, -40.0, 0.0, 0.0)), 0.0);
+        assert_eq!(score(ScoringWeights::new(f64::NAN, 40.0, 1.0, 10.0)), 0.0);
+    }
+
+    #[test]
+    fn weights_default_when_missing_from_stored_verifiers() {
+        let mut stored = serde_json::to_value(verifier(ScoringWeights::new(1.0, 0.0, 0.0, 0.0))).unwrap();
+        stored.as_object_mut().unwrap().remove("scoring_weights");
+
+        let restored: Verifier = serde_json::from_value(stored).unwrap();
+        assert_eq!(restored.scoring_weights, ScoringWeights::default());
+    }
+
+    #[test]
+    fn capability_reputation_falls_back_to_the_global_score() {
+        let mut verifier = verifier(ScoringWeights::default());
+        verifier.set_capability_reputation(VerificationCapability::EducationVerification, 90.0);
+        verifier.set_capability_reputation(VerificationCapability::KycVerification, 250.0);
+
+        assert_eq!(verifier.reputation_for("UniversityDegreeCredential"), 90.0);
+        assert_eq!(verifier.capability_score(&VerificationCapability::KycVerification), 100.0);
+        assert_eq!(verifier.reputation_for("EmploymentCredential"), 50.0);
+        // 90 * 0.3 reputation in education + 0.5 * 40 coverage + 20 freshness + 10 issuer
+        assert_eq!(verifier.verify_credential(&credential(), &criteria()).unwrap().confidence_score, 77.0);
+    }
+
+    #[test]
+    fn capability_reputation_round_trips_including_custom_capabilities() {
+        let mut verifier = verifier(ScoringWeights::default());
+        verifier.set_capability_reputation(VerificationCapability::Custom("MembershipCredential".to_string()), 70.0);
+        verifier.set_capability_reputation(VerificationCapability::AgeVerification, 30.0);
+
+        let restored: Verifier = serde_json::from_str(&serde_json::to_string(&verifier).unwrap()).unwrap();
+        assert_eq!(restored.capability_reputation, verifier.capability_reputation);
+        assert_eq!(restored.reputation_for("MembershipCredential"), 70.0);
+    }
+
+    #[test]
+    fn verification_result_report_names_the_verifier_and_claims() {
+        let result = verifier(ScoringWeights::default()).verify_credential(&credential(), &criteria()).unwrap();
+
+        let report = ReportV1::from_json(&result.report_v1().to_json().unwrap()).unwrap();
+        assert_eq!(report.credential_id, result.credential_id);
+        assert!(report.verified);
+        assert_eq!(report.verified_at, result.timestamp);
+        let names: Vec<String> = report.checks.iter().map(|check| check.name.clone()).collect();
+        assert_eq!(names, result.verified_claims.iter().map(|claim| format!("claim.{}", claim)).collect::<Vec<_>>());
+        assert_eq!(report.verifiers.len(), 1);
+        assert_eq!(report.verifiers[0].id, "v1");
+        assert_eq!(report.verifiers[0].confidence, Some(65.0));
+        assert_eq!(report.verifiers[0].evidence_level.as_deref(), Some(result.evidence_level.to_string().as_str()));
+    }
+}
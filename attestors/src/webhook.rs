@@ -0,0 +1,364 @@
+//! Webhook notifications for completed attestations
+
+use std::collections::HashMap;
+use std::time::Duration;
+use hmac::{Hmac, Mac};
+use serde::{Deserialize, Serialize};
+use sha2::Sha256;
+use crate::attestation::AttestationResult;
+use crate::error::AttestorError;
+
+/// Header carrying the hex HMAC-SHA256 of the request body, prefixed with `sha256=`
+pub const WEBHOOK_SIGNATURE_HEADER: &str = "X-Attestation-Signature";
+
+/// Registered webhook target
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct WebhookEndpoint {
+    pub url: String,
+    /// Shared secret used to HMAC-sign deliveries
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub secret: Option<String>,
+}
+
+/// Outcome of delivering a result to one endpoint
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WebhookDelivery {
+    pub url: String,
+    pub attempts: u32,
+    pub status: Option<u16>,
+    pub error: Option<String>,
+}
+
+/// POSTs attestation results to global and per-credential webhooks, retrying failures
+#[derive(Debug, Clone)]
+pub struct WebhookNotifier {
+    client: reqwest::Client,
+    global: Vec<WebhookEndpoint>,
+    per_credential: HashMap<String, Vec<WebhookEndpoint>>,
+    max_attempts: u32,
+    backoff: Duration,
+}
+
+impl WebhookEndpoint {
+    /// Create an unsigned endpoint
+    pub fn new(url: String) -> Self {
+        Self { url, secret: None }
+    }
+
+    /// Sign deliveries to this endpoint with a shared secret
+    pub fn with_secret(mut self, secret: String) -> Self {
+        self.secret = Some(secret);
+        self
+    }
+}
+
+impl WebhookDelivery {
+    /// Whether the endpoint accepted the delivery
+    pub fn is_delivered(&self) -> bool {
+        self.error.is_none()
+    }
+}
+
+impl WebhookNotifier {
+    /// Create a notifier making up to 3 attempts per endpoint
+    pub fn new() -> Self {
+        Self {
+            client: reqwest::Client::new(),
+            global: Vec::new(),
+            per_credential: HashMap::new(),
+            max_attempts: 3,
+            backoff: Duration::from_millis(200),
+        }
+    }
+
+    /// Set the attempt limit and initial backoff, doubled after each failed attempt
+    pub fn with_retry(mut self, max_attempts: u32, backoff: Duration) -> Self {
+        self.max_attempts = max_attempts.max(1);
+        self.backoff = backoff;
+        self
+    }
+
+    /// Notify an endpoint of every completed attestation
+    pub fn register_global(&mut self, endpoint: WebhookEndpoint) {
+        self.global.push(endpoint);
+    }
+
+    /// Notify an endpoint of completed attestations for one credential
+    pub fn register_for_credential(&mut self, credential_id: String, endpoint: WebhookEndpoint) {
+        self.per_credential.entry(credential_id).or_default().push(endpoint);
+    }
+
+    /// Remove an endpoint from every registration
+    pub fn unregister(&mut self, url: &str) {
+        self.global.retain(|endpoint| endpoint.url != url);
+        for endpoints in self.per_credential.values_mut() {
+            endpoints.retain(|endpoint| endpoint.url != url);
+        }
+        self.per_credential.retain(|_, endpoints| !endpoints.is_empty());
+    }
+
+    /// Endpoints that should receive a result
+    pub fn endpoints_for(&self, credential_id: &str) -> Vec<&WebhookEndpoint> {
+        self.global.iter()
+            .chain(self.per_credential.get(credential_id).into_iter().flatten())
+            .collect()
+    }
+
+    /// Deliver a result to every matching endpoint
+    pub async fn notify(&self, result: &AttestationResult) -> Result<Vec<WebhookDelivery>, AttestorError> {
+        let body = serde_json::to_vec(result)?;
+
+        let mut deliveries = Vec::new();
+        for endpoint in self.endpoints_for(&result.credential_id) {
+            deliveries.push(self.deliver(endpoint, &body).await);
+        }
+        Ok(deliveries)
+    }
+
+    /// Deliver a result in the background on the current tokio runtime, if any
+    pub fn dispatch(&self, result: AttestationResult) {
+        if self.endpoints_for(&result.credential_id).is_empty() {
+            return;
+        }
+        if let Ok(handle) = tokio::runtime::Handle::try_current() {
+            let notifier = self.clone();
+            handle.spawn(async move {
+                let _ = notifier.notify(&result).await;
+            });
+        }
+    }
+
+    /// POST a body to one endpoint, retrying connection failures and server errors
+    async fn deliver(&self, endpoint: &WebhookEndpoint, body: &[u8]) -> WebhookDelivery {
+        let mut delivery = WebhookDelivery {
+            url: endpoint.url.clone(),
+            attempts: 0,
+            status: None,
+            error: None,
+        };
+        let mut backoff = self.backoff;
+
+        while delivery.attempts < self.max_attempts {
+            if delivery.attempts > 0 {
+                tokio::time::sleep(backoff).await;
+                backoff *= 2;
+            }
+            delivery.attempts += 1;
+
+            let mut request = self.client.post(&endpoint.url)
+                .header(reqwest::header::CONTENT_TYPE, "application/json")
+                .body(body.to_vec());
+            if let Some(secret) = &endpoint.secret {
+                request = request.header(WEBHOOK_SIGNATURE_HEADER, format!("sha256={}", sign_webhook_body(secret, body)));
+            }
+
+            match request.send().await {
+                Ok(response) => {
+                    let status = response.status();
+                    delivery.status = Some(status.as_u16());
+                    if status.is_success() {
+                        delivery.error = None;
+                        return delivery;
+                    }
+                    delivery.error = Some(format!("Webhook returned {}", status));
+                    if status.is_client_error() {
+                        return delivery;
+                    }
+                }
+                Err(e) => delivery.error = Some(e.to_string()),
+            }
+        }
+
+        delivery
+    }
+}
+
+impl Default for WebhookNotifier {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Hex HMAC-SHA256 of a webhook body under a shared secret
+pub fn sign_webhook_body(secret: &str, body: &[u8]) -> String {
+    let mut mac = Hmac::<Sha256>::new_from_slice(secret.as_bytes())
+        .expect("HMAC accepts keys of any length");
+    mac.update(body);
+    hex::encode(mac.finalize().into_bytes())
+}
+
+/// Check a webhook signature header value against the body, in constant time
+pub fn verify_webhook_signature(secret: &str, body: &[u8], signature: &str) -> bool {
+    let expected = match signature.strip_prefix("sha256=").and_then(|hex_sig| hex::decode(hex_sig).ok()) {
+        Some(expected) => expected,
+        None => return false,
+    };
+    let mut mac = Hmac::<Sha256>::new_from_slice(secret.as_bytes())
+        .expect("HMAC accepts keys of any length");
+    mac.update(body);
+    mac.verify_slice(&expected).is_ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::BTreeMap;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use tokio::net::{TcpListener, TcpStream};
+    use tokio::sync::mpsc;
+    use identity_core::utils::create_basic_did_document;
+    use identity_core::{KeyType, VerifiableCredential};
+    use crate::attestation::{AttestationManager, AttestationRequest};
+    use crate::verifier::Verifier;
+
+    /// Request received by the mock endpoint
+    struct Received {
+        signature: Option<String>,
+        body: Vec<u8>,
+    }
+
+    /// Read one request, returning its lowercased header block and body
+    async fn read_request(socket: &mut TcpStream) -> (String, Vec<u8>) {
+        let mut request = Vec::new();
+        let mut buffer = [0u8; 4096];
+        loop {
+            let read = socket.read(&mut buffer).await.unwrap();
+            request.extend_from_slice(&buffer[..read]);
+            let text = String::from_utf8_lossy(&request).to_string();
+            if let Some(end) = text.find("\r\n\r\n") {
+                let headers = text[..end].to_lowercase();
+                let length = headers.lines()
+                    .find_map(|line| line.strip_prefix("content-length:"))
+                    .and_then(|length| length.trim().parse::<usize>().ok())
+                    .unwrap_or(0);
+                if read == 0 || request.len() >= end + 4 + length {
+                    return (headers, request[end + 4..].to_vec());
+                }
+            }
+        }
+    }
+
+    /// Endpoint answering with `statuses` in order and reporting each request it receives
+    async fn mock_endpoint(statuses: Vec<u16>) -> (String, mpsc::UnboundedReceiver<Received>) {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let address = listener.local_addr().unwrap();
+        let (sender, receiver) = mpsc::unbounded_channel();
+        tokio::spawn(async move {
+            for status in statuses {
+                let (mut socket, _) = listener.accept().await.unwrap();
+                let (headers, body) = read_request(&mut socket).await;
+                let signature = headers.lines()
+                    .find_map(|line| line.strip_prefix(&format!("{}:", WEBHOOK_SIGNATURE_HEADER.to_lowercase())))
+                    .map(|value| value.trim().to_string());
+                let _ = sender.send(Received { signature, body });
+                let response = format!("HTTP/1.1 {} Status\r\nContent-Length: 0\r\nConnection: close\r\n\r\n", status);
+                let _ = socket.write_all(response.as_bytes()).await;
+            }
+        });
+        (format!("http://{}/hook", address), receiver)
+    }
+
+    fn credential() -> VerifiableCredential {
+        let mut claims = BTreeMap::new();
+        claims.insert("name".to_string(), serde_json::json!("Alice"));
+        VerifiableCredential::new("did:example:issuer".to_string(), Some("did:example:alice".to_string()), claims)
+    }
+
+    fn manager() -> AttestationManager {
+        let verifiers = (1..=3)
+            .map(|i| {
+                let did = create_basic_did_document("example", KeyType::Ed25519).unwrap().0.id;
+                Verifier::new(format!("v{}", i), did, format!("Verifier {}", i), Vec::new())
+            })
+            .collect();
+        AttestationManager::new(2, 3, verifiers).unwrap()
+    }
+
+    fn fast(notifier: WebhookNotifier) -> WebhookNotifier {
+        notifier.with_retry(3, Duration::from_millis(1))
+    }
+
+    /// Completed result from a manager without webhooks
+    async fn manager_result() -> AttestationResult {
+        let mut manager = manager();
+        let request = AttestationRequest::new(credential(), vec!["v1".to_string(), "v2".to_string(), "v3".to_string()], 2);
+        let request_id = manager.submit_request(request).unwrap();
+        for attestor in ["v1", "v3"] {
+            manager.process_attestation(&request_id, attestor, true, Vec::new(), HashMap::new()).unwrap();
+        }
+        manager.try_complete_attestation(&request_id).unwrap().unwrap()
+    }
+
+    #[tokio::test]
+    async fn completion_posts_the_signed_result() {
+        let (url, mut received) = mock_endpoint(vec![200]).await;
+        let mut notifier = fast(WebhookNotifier::new());
+        notifier.register_global(WebhookEndpoint::new(url).with_secret("s3cret".to_string()));
+        let mut manager = manager();
+        manager.set_webhook_notifier(notifier);
+
+        let request = AttestationRequest::new(credential(), vec!["v1".to_string(), "v2".to_string(), "v3".to_string()], 2);
+        let request_id = manager.submit_request(request).unwrap();
+        for attestor in ["v1", "v2"] {
+            manager.process_attestation(&request_id, attestor, true, Vec::new(), HashMap::new()).unwrap();
+        }
+        let result = manager.try_complete_attestation(&request_id).unwrap().unwrap();
+
+        let delivered = tokio::time::timeout(Duration::from_secs(5), received.recv()).await.unwrap().unwrap();
+        let posted: AttestationResult = serde_json::from_slice(&delivered.body).unwrap();
+        assert_eq!(posted.request_id, result.request_id);
+        assert_eq!(posted.participating_attestors, result.participating_attestors);
+        assert_eq!(delivered.body, serde_json::to_vec(&result).unwrap());
+
+        let signature = delivered.signature.unwrap();
+        assert!(verify_webhook_signature("s3cret", &delivered.body, &signature));
+        assert!(!verify_webhook_signature("wrong", &delivered.body, &signature));
+    }
+
+    #[tokio::test]
+    async fn server_errors_are_retried_and_client_errors_are_not() {
+        let result = manager_result().await;
+
+        let (flaky, mut flaky_received) = mock_endpoint(vec![503, 200]).await;
+        let mut notifier = fast(WebhookNotifier::new());
+        notifier.register_for_credential(result.credential_id.clone(), WebhookEndpoint::new(flaky));
+        let deliveries = notifier.notify(&result).await.unwrap();
+        assert_eq!((deliveries[0].attempts, deliveries[0].status), (2, Some(200)));
+        assert!(deliveries[0].is_delivered());
+        assert!(flaky_received.recv().await.unwrap().signature.is_none());
+
+        let (rejecting, _) = mock_endpoint(vec![400]).await;
+        let mut notifier = fast(WebhookNotifier::new());
+        notifier.register_global(WebhookEndpoint::new(rejecting));
+        let deliveries = notifier.notify(&result).await.unwrap();
+        assert_eq!((deliveries[0].attempts, deliveries[0].status), (1, Some(400)));
+        assert!(!deliveries[0].is_delivered());
+    }
+
+    #[test]
+    fn endpoints_are_routed_globally_or_per_credential() {
+        let mut notifier = WebhookNotifier::new();
+        notifier.register_global(WebhookEndpoint::new("http://global.example/hook".to_string()));
+        notifier.register_for_credential("urn:uuid:1".to_string(), WebhookEndpoint::new("http://one.example/hook".to_string()));
+
+        let urls = |credential_id: &str| -> Vec<String> {
+            notifier.endpoints_for(credential_id).iter().map(|endpoint| endpoint.url.clone()).collect()
+        };
+        assert_eq!(urls("urn:uuid:1"), vec!["http://global.example/hook", "http://one.example/hook"]);
+        assert_eq!(urls("urn:uuid:2"), vec!["http://global.example/hook"]);
+
+        notifier.unregister("http://one.example/hook");
+        assert_eq!(notifier.endpoints_for("urn:uuid:1").len(), 1);
+        assert!(notifier.per_credential.is_empty());
+    }
+
+    #[test]
+    fn malformed_signature_headers_are_rejected() {
+        let signature = format!("sha256={}", sign_webhook_body("s3cret", b"body"));
+
+        assert!(verify_webhook_signature("s3cret", b"body", &signature));
+        assert!(!verify_webhook_signature("s3cret", b"tampered", &signature));
+        assert!(!verify_webhook_signature("s3cret", b"body", signature.trim_start_matches("sha256=")));
+        assert!(!verify_webhook_signature("s3cret", b"body", "sha256=zz"));
+    }
+}
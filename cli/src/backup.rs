@@ -0,0 +1,292 @@
+//! Passphrase-encrypted backups of the local CLI state directory
+
+use std::collections::BTreeMap;
+use std::path::{Component, Path, PathBuf};
+use anyhow::{anyhow, bail, Context, Result};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use identity_core::crypto::encoding::{decode_base64url, encode_base64url};
+use identity_core::{hash_data, EncryptedKeystore, DEFAULT_KDF_ITERATIONS};
+
+/// Version of the backup bundle format
+pub const BACKUP_VERSION: u32 = 1;
+
+/// Every file under the state directory, with a digest checked on restore
+#[derive(Debug, Serialize, Deserialize)]
+struct BackupBundle {
+    version: u32,
+    created_at: DateTime<Utc>,
+    /// Relative path, `/`-separated -> base64url file contents
+    files: BTreeMap<String, String>,
+    /// Hex SHA-256 over the serialized `files` map
+    digest: String,
+}
+
+/// Bundle the state directory (keystores, DID and credential state, config) into an encrypted archive
+pub fn create_backup(state_dir: &Path, passphrase: &str) -> Result<EncryptedKeystore> {
+    create_backup_with_iterations(state_dir, passphrase, DEFAULT_KDF_ITERATIONS)
+}
+
+/// Bundle the state directory into an archive whose key is derived with a custom iteration count
+pub fn create_backup_with_iterations(state_dir: &Path, passphrase: &str, iterations: u32) -> Result<EncryptedKeystore> {
+    if !state_dir.is_dir() {
+        bail!("State directory {} does not exist", state_dir.display());
+    }
+
+    let mut files = BTreeMap::new();
+    collect_files(state_dir, state_dir, &mut files)?;
+
+    let bundle = BackupBundle {
+        version: BACKUP_VERSION,
+        created_at: Utc::now(),
+        digest: files_digest(&files)?,
+        files,
+    };
+    Ok(EncryptedKeystore::encrypt_with_iterations(&serde_json::to_vec(&bundle)?, passphrase, iterations)?)
+}
+
+/// Decrypt an archive, verify it and write its files into the state directory, returning the file count.
+///
+/// A non-empty state directory is only replaced when `force` is set.
+pub fn restore_backup(archive: &EncryptedKeystore, state_dir: &Path, passphrase: &str, force: bool) -> Result<usize> {
+    let plaintext = archive.decrypt(passphrase)
+        .map_err(|_| anyhow!("Failed to decrypt backup: wrong passphrase or corrupted archive"))?;
+    let bundle: BackupBundle = serde_json::from_slice(&plaintext).context("Backup contents are malformed")?;
+
+    if bundle.version != BACKUP_VERSION {
+        bail!("Unsupported backup version {}", bundle.version);
+    }
+    if files_digest(&bundle.files)? != bundle.digest {
+        bail!("Backup integrity check failed: digest mismatch");
+    }
+
+    // Decode and check everything before touching the existing state
+    let mut decoded = Vec::new();
+    for (relative, contents) in &bundle.files {
+        decoded.push((safe_relative_path(relative)?, decode_base64url(contents)?));
+    }
+
+    let has_state = state_dir.is_dir() && std::fs::read_dir(state_dir)?.next().is_some();
+    if has_state && !force {
+        bail!("State directory {} is not empty; pass --force to overwrite it", state_dir.display());
+    }
+
+    // Write the restored state beside the old one so a failure part way leaves the old state intact
+    let staging = sibling_dir(state_dir, "restore")?;
+    if let Err(e) = write_files(&staging, &decoded) {
+        let _ = std::fs::remove_dir_all(&staging);
+        return Err(e);
+    }
+    if let Err(e) = replace_dir(&staging, state_dir) {
+        let _ = std::fs::remove_dir_all(&staging);
+        return Err(e);
+    }
+
+    Ok(decoded.len())
+}
+
+/// Fresh path next to `dir`, on the same filesystem so it can be renamed over it
+fn sibling_dir(dir: &Path, label: &str) -> Result<PathBuf> {
+    let name = dir.file_name()
+        .ok_or_else(|| anyhow!("Invalid state directory {}", dir.display()))?
+        .to_string_lossy();
+    let sibling = dir.with_file_name(format!(".{}.{}-{}", name, label, uuid::Uuid::new_v4()));
+    if let Some(parent) = sibling.parent().filter(|parent| !parent.as_os_str().is_empty()) {
+        std::fs::create_dir_all(parent)?;
+    }
+    Ok(sibling)
+}
+
+fn write_files(dir: &Path, files: &[(PathBuf, Vec<u8>)]) -> Result<()> {
+    std::fs::create_dir_all(dir).with_context(|| format!("Failed to create {}", dir.display()))?;
+    for (relative, contents) in files {
+        let path = dir.join(relative);
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        std::fs::write(&path, contents).with_context(|| format!("Failed to write {}", path.display()))?;
+    }
+    Ok(())
+}
+
+/// Move `staging` into place at `target`, putting the old `target` back if that fails
+fn replace_dir(staging: &Path, target: &Path) -> Result<()> {
+    if !target.exists() {
+        return std::fs::rename(staging, target)
+            .with_context(|| format!("Failed to move restored state into {}", target.display()));
+    }
+
+    let previous = sibling_dir(target, "previous")?;
+    std::fs::rename(target, &previous)
+        .with_context(|| format!("Failed to move aside {}", target.display()))?;
+    if let Err(e) = std::fs::rename(staging, target) {
+        let _ = std::fs::rename(&previous, target);
+        return Err(e).with_context(|| format!("Failed to move restored state into {}", target.display()));
+    }
+
+    std::fs::remove_dir_all(&previous)
+        .with_context(|| format!("Restored {}, but failed to remove the old state at {}", target.display(), previous.display()))
+}
+
+fn collect_files(root: &Path, dir: &Path, files: &mut BTreeMap<String, String>) -> Result<()> {
+    for entry in std::fs::read_dir(dir).with_context(|| format!("Failed to read {}", dir.display()))? {
+        let path = entry?.path();
+        if path.is_dir() {
+            collect_files(root, &path, files)?;
+        } else {
+            let relative = path.strip_prefix(root)?
+                .components()
+                .map(|component| component.as_os_str().to_string_lossy().into_owned())
+                .collect::<Vec<_>>()
+                .join("/");
+            let contents = std::fs::read(&path).with_context(|| format!("Failed to read {}", path.display()))?;
+            files.insert(relative, encode_base64url(&contents));
+        }
+    }
+    Ok(())
+}
+
+fn files_digest(files: &BTreeMap<String, String>) -> Result<String> {
+    Ok(hex::encode(hash_data(&serde_json::to_vec(files)?)))
+}
+
+/// Reject archive paths that would escape the state directory
+fn safe_relative_path(relative: &str) -> Result<PathBuf> {
+    let path = PathBuf::from(relative);
+    if relative.is_empty() || !path.components().all(|component| matches!(component, Component::Normal(_))) {
+        bail!("Backup contains an unsafe path: {}", relative);
+    }
+    Ok(path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use identity_core::{utils::generate_did_with_id, Wallet};
+    use crate::commands::{handle_did_command, tests::seed_wallet, DidCommands};
+    use crate::state::{LocalState, WALLET_FILE_NAME};
+
+    fn temp_dir(label: &str) -> PathBuf {
+        std::env::temp_dir().join(format!("{}-{}", label, uuid::Uuid::new_v4()))
+    }
+
+    /// State directory holding a DID made by `did create` with its wallet, an issued credential and a config file
+    async fn populated_state() -> (PathBuf, String) {
+        let dir = temp_dir("backup-state");
+        seed_wallet(&dir.join(WALLET_FILE_NAME));
+        handle_did_command(DidCommands::Create {
+            method: "example".to_string(),
+            controller: None,
+            key_type: "ed25519".to_string(),
+            domain: None,
+            path: None,
+            web_root: dir.join("web"),
+            output: "text".to_string(),
+            id: Some("alice".to_string()),
+            state: dir.join("state.json"),
+        }).await.unwrap();
+        std::fs::create_dir_all(dir.join("credentials")).unwrap();
+        std::fs::write(dir.join("credentials/urn-uuid-1.json"), r#"{"id":"urn:uuid:1"}"#).unwrap();
+        std::fs::write(dir.join("config.toml"), "network = \"local\"\n").unwrap();
+        (dir, generate_did_with_id("example", "alice"))
+    }
+
+    /// Check the restored state still holds the DID and the private key matching its document
+    fn assert_did_and_key_restored(state: &Path, did: &str) {
+        let document = LocalState::load(&state.join("state.json")).unwrap().get(did).unwrap().clone();
+        let method = &document.verification_method.as_ref().unwrap()[0];
+        let wallet = Wallet::load(&state.join(WALLET_FILE_NAME), "passphrase").unwrap();
+        let keypair = wallet.key(&method.id).unwrap();
+        assert_eq!(keypair.public_key, method.public_key_bytes().unwrap());
+        assert!(!keypair.private_key.is_empty());
+    }
+
+    /// Sibling directories a restore left behind next to `state`
+    fn leftover_siblings(state: &Path) -> Vec<PathBuf> {
+        let prefix = format!(".{}.", state.file_name().unwrap().to_string_lossy());
+        std::fs::read_dir(state.parent().unwrap()).unwrap()
+            .map(|entry| entry.unwrap().path())
+            .filter(|path| path.file_name().unwrap().to_string_lossy().starts_with(&prefix))
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn backup_restores_state_after_it_is_wiped() {
+        let (state, did) = populated_state().await;
+        let archive = create_backup_with_iterations(&state, "passphrase", 1_000).unwrap();
+        std::fs::remove_dir_all(&state).unwrap();
+
+        assert_eq!(restore_backup(&archive, &state, "passphrase", false).unwrap(), 4);
+        assert_did_and_key_restored(&state, &did);
+        assert_eq!(std::fs::read_to_string(state.join("credentials/urn-uuid-1.json")).unwrap(), r#"{"id":"urn:uuid:1"}"#);
+        assert!(state.join("config.toml").is_file());
+        assert!(leftover_siblings(&state).is_empty());
+
+        std::fs::remove_dir_all(&state).unwrap();
+    }
+
+    #[tokio::test]
+    async fn restore_refuses_to_overwrite_existing_state_without_force() {
+        let (state, did) = populated_state().await;
+        let archive = create_backup_with_iterations(&state, "passphrase", 1_000).unwrap();
+        let original = std::fs::read_to_string(state.join("state.json")).unwrap();
+        std::fs::write(state.join("state.json"), r#"{"dids":{}}"#).unwrap();
+        std::fs::write(state.join("extra.json"), "{}").unwrap();
+
+        let error = restore_backup(&archive, &state, "passphrase", false).unwrap_err();
+        assert!(error.to_string().contains("--force"), "{}", error);
+        assert!(state.join("extra.json").exists());
+
+        restore_backup(&archive, &state, "passphrase", true).unwrap();
+        assert_eq!(std::fs::read_to_string(state.join("state.json")).unwrap(), original);
+        assert_did_and_key_restored(&state, &did);
+        assert!(!state.join("extra.json").exists());
+        assert!(leftover_siblings(&state).is_empty());
+
+        std::fs::remove_dir_all(&state).unwrap();
+    }
+
+    #[tokio::test]
+    async fn failed_forced_restore_leaves_the_existing_state_intact() {
+        let (state, did) = populated_state().await;
+
+        // `config` as both a file and a directory cannot be written out
+        let mut files = BTreeMap::new();
+        files.insert("config".to_string(), encode_base64url(b"network"));
+        files.insert("config/network.toml".to_string(), encode_base64url(b"local"));
+        let bundle = BackupBundle { version: BACKUP_VERSION, created_at: Utc::now(), digest: files_digest(&files).unwrap(), files };
+        let archive = EncryptedKeystore::encrypt_with_iterations(&serde_json::to_vec(&bundle).unwrap(), "passphrase", 1_000).unwrap();
+
+        assert!(restore_backup(&archive, &state, "passphrase", true).is_err());
+        assert_did_and_key_restored(&state, &did);
+        assert!(state.join("config.toml").is_file());
+        assert!(leftover_siblings(&state).is_empty());
+
+        std::fs::remove_dir_all(&state).unwrap();
+    }
+
+    #[tokio::test]
+    async fn wrong_passphrase_and_tampered_bundles_are_rejected() {
+        let (state, _) = populated_state().await;
+        let archive = create_backup_with_iterations(&state, "passphrase", 1_000).unwrap();
+        let target = temp_dir("backup-target");
+
+        assert!(restore_backup(&archive, &target, "wrong", false).unwrap_err().to_string().contains("wrong passphrase"));
+
+        let mut bundle: BackupBundle = serde_json::from_slice(&archive.decrypt("passphrase").unwrap()).unwrap();
+        bundle.files.insert("state.json".to_string(), encode_base64url(b"{}"));
+        let tampered = EncryptedKeystore::encrypt_with_iterations(&serde_json::to_vec(&bundle).unwrap(), "passphrase", 1_000).unwrap();
+        assert!(restore_backup(&tampered, &target, "passphrase", false).unwrap_err().to_string().contains("digest mismatch"));
+        assert!(!target.exists());
+
+        std::fs::remove_dir_all(&state).unwrap();
+    }
+
+    #[test]
+    fn paths_escaping_the_state_directory_are_rejected() {
+        assert!(safe_relative_path("credentials/a.json").is_ok());
+        assert!(safe_relative_path("../outside").is_err());
+        assert!(safe_relative_path("/etc/passwd").is_err());
+        assert!(safe_relative_path("").is_err());
+    }
+}
@@ -2,10 +2,14 @@
 
 use clap::Subcommand;
 use anyhow::Result;
-use std::collections::HashMap;
-use identity_core::{DidDocument, VerifiableCredential, KeyType, generate_keypair, utils::*};
-use attestors::{ThresholdScheme, Verifier, AttestationManager, VerificationCapability};
-use ipfs_client::{IpfsClient, StorageManager};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use identity_core::{CryptoKeyPair, DidDocument, EncryptedKeystore, VerifiableCredential, KeyType, Wallet, DEFAULT_KDF_ITERATIONS, did_web_from_domain, DidResolver, ResolverRegistry, utils::*};
+use attestors::{ThresholdScheme, Verifier};
+use ipfs_client::IpfsClient;
+use crate::backup::{create_backup, restore_backup};
+use crate::state::{LocalState, DEFAULT_STATE_PATH};
+use crate::utils::{parse_key_type, set_document_controller};
 
 #[derive(Subcommand)]
 pub enum DidCommands {
@@ -14,8 +18,27 @@ pub enum DidCommands {
         method: String,
         #[arg(long)]
         controller: Option<String>,
+        /// Key type: ed25519, secp256k1, bls12381g1 or bls12381g2
+        #[arg(long, default_value = "ed25519")]
+        key_type: String,
+        /// Domain hosting the document (did:web only)
         #[arg(long)]
-        key_type: Option<String>,
+        domain: Option<String>,
+        /// Path under the domain, e.g. `users/alice` (did:web only)
+        #[arg(long)]
+        path: Option<String>,
+        /// Web root the did:web document is written under
+        #[arg(long, default_value = ".")]
+        web_root: PathBuf,
+        /// Output format: text or json
+        #[arg(long, default_value = "text")]
+        output: String,
+        /// Method-specific id; an existing DID with this id is resolved instead of recreated
+        #[arg(long)]
+        id: Option<String>,
+        /// Local state file recording created DIDs; their keys go to the wallet next to it
+        #[arg(long, default_value = DEFAULT_STATE_PATH)]
+        state: PathBuf,
     },
     Resolve {
         #[arg(long)]
@@ -75,24 +98,70 @@ pub enum DemoCommands {
 
 pub async fn handle_did_command(action: DidCommands) -> Result<()> {
     match action {
-        DidCommands::Create { method, controller, key_type } => {
-            println!("🔑 Creating new DID...");
+        DidCommands::Create { method, controller, key_type, domain, path, web_root, output, id, state } => {
+            let key_type = parse_key_type(&key_type)?;
+
+            if method == "web" {
+                let domain = domain.ok_or_else(|| anyhow::anyhow!("--domain is required for did:web"))?;
+                let did = did_web_from_domain(&domain, path.as_deref())?;
+                let (mut did_doc, keypair) = create_did_document_with_id(did, key_type)?;
+                set_document_controller(&mut did_doc, controller.as_deref())?;
+                let wallet = store_key(&LocalState::load(&state)?.wallet_path(), &did_doc, keypair)?;
+                let file = did_doc.to_did_web_files(&web_root)?;
+
+                if output == "json" {
+                    let result = serde_json::json!({
+                        "did": did_doc.id,
+                        "path": file,
+                        "wallet": wallet,
+                        "document": did_doc,
+                    });
+                    println!("{}", serde_json::to_string_pretty(&result)?);
+                } else {
+                    println!("✅ DID created successfully!");
+                    println!("📋 DID: {}", did_doc.id);
+                    println!("📁 Written to: {}", file.display());
+                    println!("🔐 Key stored in: {}", wallet.display());
+                    println!("{}", serde_json::to_string_pretty(&did_doc)?);
+                }
+                return Ok(());
+            }
 
-            let key_type = match key_type.as_deref() {
-                Some("ed25519") => KeyType::Ed25519,
-                Some("bls12381") => KeyType::Bls12381G1,
-                _ => KeyType::Ed25519,
+            let mut state = LocalState::load(&state)?;
+            let (mut did_doc, keypair) = match id {
+                Some(id) => {
+                    if id.is_empty() {
+                        return Err(anyhow::anyhow!("--id must not be empty"));
+                    }
+                    let did = generate_did_with_id(&method, &id);
+                    if let Some(existing) = state.get(&did) {
+                        println!("♻️  DID already exists, resolved from local state");
+                        println!("📋 DID: {}", existing.id);
+                        println!("{}", serde_json::to_string_pretty(existing)?);
+                        return Ok(());
+                    }
+                    println!("🔑 Creating new DID...");
+                    create_did_document_with_id(did, key_type)?
+                }
+                None => {
+                    println!("🔑 Creating new DID...");
+                    create_basic_did_document(&method, key_type)?
+                }
             };
-
-            let (did_doc, keypair) = create_basic_did_document(&method, key_type)?;
+            set_document_controller(&mut did_doc, controller.as_deref())?;
 
             println!("✅ DID created successfully!");
             println!("📋 DID: {}", did_doc.id);
             println!("🔐 Key Type: {}", keypair.key_type);
 
+            let wallet = store_key(&state.wallet_path(), &did_doc, keypair)?;
+            println!("🔐 Key stored in: {}", wallet.display());
+            state.insert(did_doc.clone());
+            state.save()?;
+
             // Store to IPFS
-            if let Ok(ipfs_client) = IpfsClient::new_local() {
-                match ipfs_client.store_did_document(&did_doc).await {
+            if let Ok(ipfs_client) = IpfsClient::shared_local() {
+                match ipfs_client.store_did_document(&did_doc, false).await {
                     Ok(result) => {
                         println!("📦 Stored on IPFS: {}", result.hash);
                     }
@@ -104,8 +173,9 @@ pub async fn handle_did_command(action: DidCommands) -> Result<()> {
         }
         DidCommands::Resolve { did } => {
             println!("🔍 Resolving DID: {}", did);
-            // TODO: Implement DID resolution
-            println!("⚠️  DID resolution not fully implemented yet");
+            let did_doc = ResolverRegistry::with_defaults().resolve(&did).await?;
+            println!("✅ DID resolved successfully!");
+            println!("{}", serde_json::to_string_pretty(&did_doc)?);
         }
         DidCommands::List => {
             println!("📋 Listing DIDs...");
@@ -121,7 +191,7 @@ pub async fn handle_vc_command(action: VcCommands) -> Result<()> {
         VcCommands::Issue { issuer, subject, claims, credential_type } => {
             println!("📜 Issuing new Verifiable Credential...");
 
-            let claims_map: HashMap<String, serde_json::Value> =
+            let claims_map: BTreeMap<String, serde_json::Value> =
                 serde_json::from_str(&claims)?;
 
             let mut credential = VerifiableCredential::new(issuer, subject, claims_map);
@@ -135,7 +205,7 @@ pub async fn handle_vc_command(action: VcCommands) -> Result<()> {
             println!("👤 Issuer: {}", credential.get_issuer_did());
 
             // Store to IPFS
-            if let Ok(ipfs_client) = IpfsClient::new_local() {
+            if let Ok(ipfs_client) = IpfsClient::shared_local() {
                 match ipfs_client.store_credential(&credential).await {
                     Ok(result) => {
                         println!("📦 Stored on IPFS: {}", result.hash);
@@ -170,7 +240,7 @@ pub async fn handle_attest_command(action: AttestCommands) -> Result<()> {
             println!("🎯 Threshold: {}/{}", threshold, total_parties);
 
             let scheme = ThresholdScheme::new(threshold, total_parties)?;
-            let (key_shares, public_key) = scheme.generate_key_shares()?;
+            let (key_shares, _public_key) = scheme.generate_key_shares()?;
 
             println!("✅ Threshold scheme setup complete!");
             println!("🔑 Generated {} key shares", key_shares.len());
@@ -203,21 +273,21 @@ pub async fn handle_demo_command(scenario: DemoCommands) -> Result<()> {
             println!("🚀 Setting up demo environment...");
 
             // Create demo verifiers (banks)
-            let bank1 = Verifier::new(
+            let _bank1 = Verifier::new(
                 "bank1".to_string(),
                 "did:example:bank1".to_string(),
                 "First National Bank".to_string(),
                 vec![1, 2, 3], // dummy public key
             );
 
-            let bank2 = Verifier::new(
+            let _bank2 = Verifier::new(
                 "bank2".to_string(),
                 "did:example:bank2".to_string(),
                 "Second Trust Bank".to_string(),
                 vec![4, 5, 6], // dummy public key
             );
 
-            let bank3 = Verifier::new(
+            let _bank3 = Verifier::new(
                 "bank3".to_string(),
                 "did:example:bank3".to_string(),
                 "Third Community Bank".to_string(),
@@ -238,7 +308,7 @@ pub async fn handle_demo_command(scenario: DemoCommands) -> Result<()> {
             println!("📋 Alice's DID: {}", alice_did.id);
 
             // Create KYC credential
-            let mut kyc_claims = HashMap::new();
+            let mut kyc_claims = BTreeMap::new();
             kyc_claims.insert("name".to_string(), serde_json::Value::String("Alice Smith".to_string()));
             kyc_claims.insert("age".to_string(), serde_json::Value::Number(25.into()));
             kyc_claims.insert("country".to_string(), serde_json::Value::String("USA".to_string()));
@@ -273,3 +343,165 @@ pub async fn handle_demo_command(scenario: DemoCommands) -> Result<()> {
     }
     Ok(())
 }
+
+/// Environment variable supplying the wallet and backup passphrase non-interactively
+const PASSPHRASE_ENV: &str = "IDENTITY_CLI_PASSPHRASE";
+
+/// Read a passphrase from the environment or prompt for it
+fn read_passphrase(label: &str, confirm: bool) -> Result<String> {
+    if let Ok(passphrase) = std::env::var(PASSPHRASE_ENV) {
+        return Ok(passphrase);
+    }
+
+    let mut prompt = dialoguer::Password::new().with_prompt(label);
+    if confirm {
+        prompt = prompt.with_confirmation("Confirm passphrase", "Passphrases do not match");
+    }
+    Ok(prompt.interact()?)
+}
+
+/// Add a created DID's key pair to the encrypted wallet, under its first verification method id.
+///
+/// An existing wallet keeps its KDF iteration count; a new one uses the default.
+fn store_key(wallet_path: &Path, did_doc: &DidDocument, keypair: CryptoKeyPair) -> Result<PathBuf> {
+    let method = did_doc.verification_method.as_ref()
+        .and_then(|methods| methods.first())
+        .ok_or_else(|| anyhow::anyhow!("DID document {} has no verification method", did_doc.id))?;
+
+    let existing: Option<EncryptedKeystore> = match std::fs::read(wallet_path) {
+        Ok(bytes) => Some(serde_json::from_slice(&bytes)
+            .map_err(|e| anyhow::anyhow!("{} is not a wallet: {}", wallet_path.display(), e))?),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => None,
+        Err(e) => return Err(anyhow::anyhow!("Failed to read wallet {}: {}", wallet_path.display(), e)),
+    };
+    let passphrase = read_passphrase("Wallet passphrase", existing.is_none())?;
+    let (mut wallet, iterations) = match &existing {
+        Some(keystore) => (Wallet::from_keystore(keystore, &passphrase)?, keystore.iterations),
+        None => (Wallet::new(None), DEFAULT_KDF_ITERATIONS),
+    };
+    wallet.add_key(method.id.clone(), keypair);
+
+    if let Some(parent) = wallet_path.parent().filter(|parent| !parent.as_os_str().is_empty()) {
+        std::fs::create_dir_all(parent)?;
+    }
+    std::fs::write(wallet_path, serde_json::to_vec_pretty(&wallet.to_keystore_with_iterations(&passphrase, iterations)?)?)
+        .map_err(|e| anyhow::anyhow!("Failed to write wallet {}: {}", wallet_path.display(), e))?;
+    Ok(wallet_path.to_path_buf())
+}
+
+pub fn handle_backup_command(output: PathBuf, state_dir: PathBuf) -> Result<()> {
+    let passphrase = read_passphrase("Backup passphrase", true)?;
+    let archive = create_backup(&state_dir, &passphrase)?;
+    std::fs::write(&output, serde_json::to_vec_pretty(&archive)?)?;
+
+    println!("✅ Backup written to {}", output.display());
+    Ok(())
+}
+
+pub fn handle_restore_command(input: PathBuf, state_dir: PathBuf, force: bool) -> Result<()> {
+    let archive = serde_json::from_slice(&std::fs::read(&input)?)
+        .map_err(|e| anyhow::anyhow!("{} is not a backup archive: {}", input.display(), e))?;
+    let passphrase = read_passphrase("Backup passphrase", false)?;
+    let restored = restore_backup(&archive, &state_dir, &passphrase, force)?;
+
+    println!("✅ Restored {} file(s) into {}", restored, state_dir.display());
+    Ok(())
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+    use identity_core::resolve_did_web_from_dir;
+
+    /// Start an empty wallet with a cheap KDF so `did create` does not spend seconds per test
+    pub(crate) fn seed_wallet(wallet_path: &Path) {
+        std::env::set_var(PASSPHRASE_ENV, "passphrase");
+        std::fs::create_dir_all(wallet_path.parent().unwrap()).unwrap();
+        let keystore = Wallet::new(None).to_keystore_with_iterations("passphrase", 1_000).unwrap();
+        std::fs::write(wallet_path, serde_json::to_vec(&keystore).unwrap()).unwrap();
+    }
+
+    #[tokio::test]
+    async fn did_create_web_publishes_a_resolvable_document() {
+        let web_root = std::env::temp_dir().join(format!("did-web-{}", uuid::Uuid::new_v4()));
+        seed_wallet(&web_root.join(crate::state::WALLET_FILE_NAME));
+
+        handle_did_command(DidCommands::Create {
+            method: "web".to_string(),
+            controller: None,
+            key_type: "ed25519".to_string(),
+            domain: Some("example.com".to_string()),
+            path: Some("users/alice".to_string()),
+            web_root: web_root.clone(),
+            output: "json".to_string(),
+            id: None,
+            state: web_root.join("state.json"),
+        }).await.unwrap();
+
+        assert!(web_root.join("users/alice/did.json").is_file());
+        let document = resolve_did_web_from_dir("did:web:example.com:users:alice", &web_root).unwrap();
+        assert_eq!(document.id, "did:web:example.com:users:alice");
+        assert!(!web_root.join("state.json").exists());
+        let wallet = Wallet::load(&web_root.join(crate::state::WALLET_FILE_NAME), "passphrase").unwrap();
+        assert!(wallet.key("did:web:example.com:users:alice#key-1").is_some());
+
+        std::fs::remove_dir_all(&web_root).unwrap();
+    }
+
+    #[tokio::test]
+    async fn did_create_rejects_a_mistyped_key_type() {
+        let web_root = std::env::temp_dir().join(format!("did-web-{}", uuid::Uuid::new_v4()));
+
+        let error = handle_did_command(DidCommands::Create {
+            method: "web".to_string(),
+            controller: None,
+            key_type: "ed2559".to_string(),
+            domain: Some("example.com".to_string()),
+            path: None,
+            web_root: web_root.clone(),
+            output: "json".to_string(),
+            id: None,
+            state: web_root.join("state.json"),
+        }).await.unwrap_err();
+
+        assert!(error.to_string().contains("Unsupported key type"), "{}", error);
+        assert!(!web_root.exists());
+    }
+
+    #[tokio::test]
+    async fn did_create_with_an_id_resolves_the_existing_did_on_repeat() {
+        let dir = std::env::temp_dir().join(format!("did-state-{}", uuid::Uuid::new_v4()));
+        let state_path = dir.join("state.json");
+        seed_wallet(&dir.join(crate::state::WALLET_FILE_NAME));
+        let create = || DidCommands::Create {
+            method: "example".to_string(),
+            controller: None,
+            key_type: "ed25519".to_string(),
+            domain: None,
+            path: None,
+            web_root: dir.join("web"),
+            output: "text".to_string(),
+            id: Some("alice".to_string()),
+            state: state_path.clone(),
+        };
+
+        handle_did_command(create()).await.unwrap();
+        let created = LocalState::load(&state_path).unwrap();
+        let did = generate_did_with_id("example", "alice");
+        let document = created.get(&did).unwrap().clone();
+        assert_eq!(created.dids.len(), 1);
+
+        // The created key is kept, encrypted, and matches the published verification method
+        let method = &document.verification_method.as_ref().unwrap()[0];
+        let wallet = Wallet::load(&created.wallet_path(), "passphrase").unwrap();
+        assert_eq!(wallet.key(&method.id).unwrap().public_key, method.public_key_bytes().unwrap());
+        assert!(Wallet::load(&created.wallet_path(), "wrong").is_err());
+
+        handle_did_command(create()).await.unwrap();
+        let resolved = LocalState::load(&state_path).unwrap();
+        assert_eq!(resolved.dids.len(), 1);
+        assert_eq!(resolved.get(&did).unwrap().verification_method, document.verification_method);
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -1,5 +1,6 @@
 //! CLI configuration
 
+#[allow(dead_code)]
 pub struct Config {
     pub ipfs_endpoint: String,
     pub substrate_endpoint: String,
@@ -5,9 +5,12 @@
 
 use clap::{Parser, Subcommand};
 use anyhow::Result;
+use std::path::PathBuf;
 
+mod backup;
 mod commands;
 mod config;
+mod state;
 mod utils;
 
 use commands::*;
@@ -43,6 +46,27 @@ enum Commands {
         #[command(subcommand)]
         scenario: DemoCommands,
     },
+    /// Write a passphrase-encrypted backup of the local state
+    Backup {
+        /// Archive file to write
+        #[arg(long)]
+        output: PathBuf,
+        /// Local state directory
+        #[arg(long, default_value = state::DEFAULT_STATE_DIR)]
+        state_dir: PathBuf,
+    },
+    /// Restore the local state from an encrypted backup
+    Restore {
+        /// Archive file to read
+        #[arg(long)]
+        input: PathBuf,
+        /// Local state directory
+        #[arg(long, default_value = state::DEFAULT_STATE_DIR)]
+        state_dir: PathBuf,
+        /// Replace existing local state
+        #[arg(long)]
+        force: bool,
+    },
 }
 
 #[tokio::main]
@@ -54,5 +78,7 @@ async fn main() -> Result<()> {
         Commands::Vc { action } => handle_vc_command(action).await,
         Commands::Attest { action } => handle_attest_command(action).await,
         Commands::Demo { scenario } => handle_demo_command(scenario).await,
+        Commands::Backup { output, state_dir } => handle_backup_command(output, state_dir),
+        Commands::Restore { input, state_dir, force } => handle_restore_command(input, state_dir, force),
     }
 }
@@ -0,0 +1,89 @@
+//! Local record of DIDs created by the CLI
+
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use identity_core::DidDocument;
+
+/// Default directory holding all local CLI state
+pub const DEFAULT_STATE_DIR: &str = ".identity-cli";
+
+/// Default location of the local state file
+pub const DEFAULT_STATE_PATH: &str = ".identity-cli/state.json";
+
+/// File next to the state file holding the encrypted keys of created DIDs
+pub const WALLET_FILE_NAME: &str = "wallet.json";
+
+/// DID documents created on this machine, keyed by DID
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct LocalState {
+    #[serde(default)]
+    pub dids: BTreeMap<String, DidDocument>,
+    #[serde(skip)]
+    path: PathBuf,
+}
+
+impl LocalState {
+    /// Load the state file, starting empty if it does not exist yet
+    pub fn load(path: &Path) -> Result<Self> {
+        let mut state: Self = if path.exists() {
+            let content = std::fs::read_to_string(path)
+                .with_context(|| format!("Failed to read state file {}", path.display()))?;
+            serde_json::from_str(&content)
+                .with_context(|| format!("Invalid state file {}", path.display()))?
+        } else {
+            Self::default()
+        };
+        state.path = path.to_path_buf();
+        Ok(state)
+    }
+
+    /// Write the state back to the file it was loaded from
+    pub fn save(&self) -> Result<()> {
+        if let Some(parent) = self.path.parent().filter(|parent| !parent.as_os_str().is_empty()) {
+            std::fs::create_dir_all(parent)?;
+        }
+        std::fs::write(&self.path, serde_json::to_string_pretty(self)?)
+            .with_context(|| format!("Failed to write state file {}", self.path.display()))
+    }
+
+    /// Look up a previously created DID
+    pub fn get(&self, did: &str) -> Option<&DidDocument> {
+        self.dids.get(did)
+    }
+
+    /// Location of the key wallet belonging to this state
+    pub fn wallet_path(&self) -> PathBuf {
+        self.path.with_file_name(WALLET_FILE_NAME)
+    }
+
+    /// Record a created DID
+    pub fn insert(&mut self, did_doc: DidDocument) {
+        self.dids.insert(did_doc.id.clone(), did_doc);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use identity_core::{utils::create_basic_did_document, KeyType};
+
+    #[test]
+    fn state_starts_empty_and_round_trips_created_dids() {
+        let dir = std::env::temp_dir().join(format!("did-state-{}", uuid::Uuid::new_v4()));
+        let path = dir.join("nested/state.json");
+
+        let mut state = LocalState::load(&path).unwrap();
+        assert!(state.dids.is_empty());
+        let (did_doc, _) = create_basic_did_document("example", KeyType::Ed25519).unwrap();
+        state.insert(did_doc.clone());
+        state.save().unwrap();
+
+        let loaded = LocalState::load(&path).unwrap();
+        assert_eq!(loaded.get(&did_doc.id).unwrap().id, did_doc.id);
+        assert!(loaded.get("did:example:unknown").is_none());
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -1,5 +1,82 @@
 //! CLI utility functions
 
+#[allow(dead_code)]
 pub fn format_output(data: &str) -> String {
     format!("✅ {}", data)
 }
+
+/// Key types accepted by `--key-type`
+pub const SUPPORTED_KEY_TYPES: &[&str] = &["ed25519", "secp256k1", "bls12381g1", "bls12381g2"];
+
+/// Parse a `--key-type` value, rejecting anything unsupported
+pub fn parse_key_type(value: &str) -> anyhow::Result<identity_core::KeyType> {
+    use identity_core::KeyType;
+
+    match value.to_ascii_lowercase().as_str() {
+        "ed25519" => Ok(KeyType::Ed25519),
+        "secp256k1" => Ok(KeyType::Secp256k1),
+        // `bls12381` is kept as an alias for the G1 key type
+        "bls12381g1" | "bls12381" => Ok(KeyType::Bls12381G1),
+        "bls12381g2" => Ok(KeyType::Bls12381G2),
+        other => Err(anyhow::anyhow!(
+            "Unsupported key type '{}'; expected one of: {}",
+            other,
+            SUPPORTED_KEY_TYPES.join(", ")
+        )),
+    }
+}
+
+/// Record a `--controller` DID as the document's controller, rejecting malformed DIDs
+pub fn set_document_controller(did_doc: &mut identity_core::DidDocument, controller: Option<&str>) -> anyhow::Result<()> {
+    if let Some(controller) = controller {
+        identity_core::utils::parse_did(controller)?;
+        did_doc.extra.insert("controller".to_string(), serde_json::Value::String(controller.to_string()));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use identity_core::DidDocument;
+
+    #[test]
+    fn controller_is_recorded_on_the_document() {
+        let mut did_doc = DidDocument::new("did:example:alice".to_string());
+
+        set_document_controller(&mut did_doc, Some("did:example:guardian")).unwrap();
+
+        assert_eq!(serde_json::to_value(&did_doc).unwrap()["controller"], "did:example:guardian");
+    }
+
+    #[test]
+    fn malformed_controller_is_rejected() {
+        let mut did_doc = DidDocument::new("did:example:alice".to_string());
+
+        assert!(set_document_controller(&mut did_doc, Some("guardian")).is_err());
+        assert!(!did_doc.extra.contains_key("controller"));
+    }
+
+    #[test]
+    fn each_supported_key_type_selects_its_key_type() {
+        use identity_core::KeyType;
+
+        let expected = [KeyType::Ed25519, KeyType::Secp256k1, KeyType::Bls12381G1, KeyType::Bls12381G2];
+        for (value, key_type) in SUPPORTED_KEY_TYPES.iter().zip(expected) {
+            assert_eq!(parse_key_type(value).unwrap(), key_type);
+        }
+        assert_eq!(parse_key_type("Ed25519").unwrap(), KeyType::Ed25519);
+        assert_eq!(parse_key_type("bls12381").unwrap(), KeyType::Bls12381G1);
+    }
+
+    #[test]
+    fn key_type_typo_is_rejected_with_the_supported_options() {
+        let error = parse_key_type("ed2559").unwrap_err().to_string();
+
+        assert!(error.contains("'ed2559'"), "{}", error);
+        for key_type in SUPPORTED_KEY_TYPES {
+            assert!(error.contains(key_type), "{}", error);
+        }
+        assert!(parse_key_type("").is_err());
+    }
+}
@@ -0,0 +1,277 @@
+//! Anonymous credential presentations using BBS+ derived proofs
+
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use crate::crypto::{CryptoKeyPair, KeyType};
+use crate::crypto::bbs::{bbs_derive_proof, bbs_sign, bbs_verify, bbs_verify_proof};
+use crate::crypto::encoding::{encode_base64url, decode_base64url};
+use crate::error::IdentityError;
+use crate::vc::{Proof, VerifiableCredential};
+
+/// Proof type of a proof derived from a BBS+ signature
+pub const BBS_DERIVED_PROOF_TYPE: &str = "BbsBlsSignatureProof2020";
+
+/// Claim disclosed by a derived proof
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct RevealedClaim {
+    pub index: usize,
+    pub name: String,
+    pub value: serde_json::Value,
+}
+
+/// Credential disclosing a subset of claims with a zero-knowledge proof of the issuer's signature
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct DerivedCredential {
+    pub issuer: String,
+    #[serde(rename = "type")]
+    pub credential_type: Vec<String>,
+    #[serde(rename = "claimCount")]
+    pub claim_count: usize,
+    #[serde(rename = "revealedClaims")]
+    pub revealed_claims: Vec<RevealedClaim>,
+    #[serde(rename = "verificationMethod")]
+    pub verification_method: String,
+    #[serde(rename = "proofType")]
+    pub proof_type: String,
+    #[serde(rename = "proofValue")]
+    pub proof_value: String,
+}
+
+impl VerifiableCredential {
+    /// Claims in the order they are signed under BBS+
+    pub fn bbs_claims(&self) -> Vec<(&String, &serde_json::Value)> {
+        self.credential_subject.claims.iter().collect()
+    }
+
+    /// Messages signed under BBS+: a header binding issuer and types, then one per claim
+    pub fn bbs_messages(&self) -> Result<Vec<Vec<u8>>, IdentityError> {
+        let mut messages = vec![bbs_header(self.get_issuer_did(), &self.credential_type)?];
+        for (name, value) in self.bbs_claims() {
+            messages.push(claim_message(name, value)?);
+        }
+        Ok(messages)
+    }
+
+    /// Sign the credential's claims with a BLS12-381 G2 key so holders can disclose them selectively
+    pub fn sign_bbs(&mut self, keypair: &CryptoKeyPair, verification_method: String) -> Result<(), IdentityError> {
+        if keypair.key_type != KeyType::Bls12381G2 {
+            return Err(IdentityError::SignatureError("BBS+ signing requires a Bls12381G2 key".to_string()));
+        }
+
+        let signature = bbs_sign(&self.bbs_messages()?, &keypair.private_key)?;
+        self.add_proof(Proof::new(&keypair.key_type, verification_method, "assertionMethod", &signature));
+        Ok(())
+    }
+
+    /// Verify the credential's BBS+ signature over all claims
+    pub fn verify_bbs_signature(&self, public_key: &[u8]) -> Result<bool, IdentityError> {
+        let proof = self.bbs_proof()?;
+        let signature = decode_base64url(&proof.proof_value)?;
+        bbs_verify(&self.bbs_messages()?, &signature, public_key)
+    }
+
+    /// Derive an unlinkable proof revealing only the claims at `reveal_indices` (in claim name order)
+    pub fn derive_proof(&self, reveal_indices: &[usize], nonce: &[u8]) -> Result<DerivedCredential, IdentityError> {
+        let proof = self.bbs_proof()?;
+        let signature = decode_base64url(&proof.proof_value)?;
+        let claims = self.bbs_claims();
+
+        let mut reveal = vec![0];
+        let mut revealed_claims = Vec::new();
+        for &index in reveal_indices {
+            let (name, value) = claims.get(index)
+                .ok_or_else(|| IdentityError::InvalidCredential(format!("No claim at index {}", index)))?;
+            reveal.push(index + 1);
+            revealed_claims.push(RevealedClaim {
+                index,
+                name: name.to_string(),
+                value: (*value).clone(),
+            });
+        }
+
+        let derived = bbs_derive_proof(&self.bbs_messages()?, &signature, &reveal, nonce)?;
+        revealed_claims.sort_by_key(|claim| claim.index);
+
+        Ok(DerivedCredential {
+            issuer: self.get_issuer_did().to_string(),
+            credential_type: self.credential_type.clone(),
+            claim_count: claims.len(),
+            revealed_claims,
+            verification_method: proof.verification_method.clone(),
+            proof_type: BBS_DERIVED_PROOF_TYPE.to_string(),
+            proof_value: encode_base64url(&derived),
+        })
+    }
+
+    /// Find the credential's BBS+ signature proof
+    fn bbs_proof(&self) -> Result<&Proof, IdentityError> {
+        let suite = KeyType::Bls12381G2.signature_suite();
+        self.proof.as_ref()
+            .and_then(|proofs| proofs.iter().find(|proof| proof.proof_type == suite))
+            .ok_or_else(|| IdentityError::VerificationError("Credential has no BBS+ signature".to_string()))
+    }
+}
+
+impl DerivedCredential {
+    /// Embed as a credential carrying only the revealed claims, with the derived proof attached
+    pub fn to_credential(&self) -> VerifiableCredential {
+        let claims = self.revealed_claims.iter()
+            .map(|claim| (claim.name.clone(), claim.value.clone()))
+            .collect();
+        let mut credential = VerifiableCredential::new(self.issuer.clone(), None, claims);
+        credential.credential_type = self.credential_type.clone();
+
+        let mut proof = Proof::new(&KeyType::Bls12381G2, self.verification_method.clone(), "assertionMethod", &[]);
+        proof.proof_type = self.proof_type.clone();
+        proof.proof_value = self.proof_value.clone();
+        proof.additional_properties.insert("claimCount".to_string(), self.claim_count.into());
+        proof.additional_properties.insert(
+            "revealedIndices".to_string(),
+            self.revealed_claims.iter().map(|claim| claim.index).collect::<Vec<_>>().into(),
+        );
+        credential.add_proof(proof);
+        credential
+    }
+
+    /// Recover a derived credential embedded with `to_credential`
+    pub fn from_credential(credential: &VerifiableCredential) -> Result<Self, IdentityError> {
+        let proof = credential.proof.as_ref()
+            .and_then(|proofs| proofs.iter().find(|proof| proof.proof_type == BBS_DERIVED_PROOF_TYPE))
+            .ok_or_else(|| IdentityError::VerificationError(format!(
+                "Credential {} has no derived BBS+ proof", credential.id
+            )))?;
+
+        let claim_count = proof.additional_properties.get("claimCount")
+            .and_then(serde_json::Value::as_u64)
+            .ok_or_else(|| IdentityError::InvalidCredential("Derived proof has no claimCount".to_string()))?;
+        let indices: Vec<usize> = proof.additional_properties.get("revealedIndices")
+            .map(|indices| serde_json::from_value(indices.clone()))
+            .transpose()?
+            .ok_or_else(|| IdentityError::InvalidCredential("Derived proof has no revealedIndices".to_string()))?;
+
+        // Claims are held in name order, which is also the order of their indices
+        let claims = &credential.credential_subject.claims;
+        if indices.len() != claims.len() || indices.windows(2).any(|pair| pair[0] >= pair[1]) {
+            return Err(IdentityError::InvalidCredential(
+                "Derived proof indices do not match the disclosed claims".to_string()
+            ));
+        }
+        let revealed_claims = indices.into_iter()
+            .zip(claims)
+            .map(|(index, (name, value))| RevealedClaim { index, name: name.clone(), value: value.clone() })
+            .collect();
+
+        Ok(Self {
+            issuer: credential.get_issuer_did().to_string(),
+            credential_type: credential.credential_type.clone(),
+            claim_count: claim_count as usize,
+            revealed_claims,
+            verification_method: proof.verification_method.clone(),
+            proof_type: proof.proof_type.clone(),
+            proof_value: proof.proof_value.clone(),
+        })
+    }
+}
+
+/// Verify a derived credential against the issuer's BLS12-381 G2 public key and the verifier's nonce
+pub fn verify_bbs_proof(derived: &DerivedCredential, public_key: &[u8], nonce: &[u8]) -> Result<bool, IdentityError> {
+    if derived.proof_type != BBS_DERIVED_PROOF_TYPE {
+        return Err(IdentityError::VerificationError(format!("Unsupported proof type {}", derived.proof_type)));
+    }
+
+    let mut revealed = BTreeMap::new();
+    revealed.insert(0, bbs_header(&derived.issuer, &derived.credential_type)?);
+    for claim in &derived.revealed_claims {
+        if revealed.insert(claim.index + 1, claim_message(&claim.name, &claim.value)?).is_some() {
+            return Ok(false);
+        }
+    }
+
+    let proof = decode_base64url(&derived.proof_value)?;
+    bbs_verify_proof(public_key, &proof, derived.claim_count + 1, &revealed, nonce)
+}
+
+fn bbs_header(issuer: &str, credential_type: &[String]) -> Result<Vec<u8>, IdentityError> {
+    Ok(serde_json::to_vec(&serde_json::json!({
+        "issuer": issuer,
+        "type": credential_type,
+    }))?)
+}
+
+fn claim_message(name: &str, value: &serde_json::Value) -> Result<Vec<u8>, IdentityError> {
+    Ok(serde_json::to_vec(&serde_json::json!([name, value]))?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::crypto::{generate_bls12381_g2_keypair, generate_ed25519_keypair};
+
+    /// Credential whose claims sort as birthdate (0), name (1), nationality (2)
+    fn signed_credential() -> (VerifiableCredential, CryptoKeyPair) {
+        let keypair = generate_bls12381_g2_keypair().unwrap();
+        let mut claims = BTreeMap::new();
+        claims.insert("name".to_string(), serde_json::json!("Alice"));
+        claims.insert("birthdate".to_string(), serde_json::json!("1990-01-01"));
+        claims.insert("nationality".to_string(), serde_json::json!("NZ"));
+        let mut credential = VerifiableCredential::new("did:example:issuer".to_string(), Some("did:example:alice".to_string()), claims);
+        credential.sign_bbs(&keypair, "did:example:issuer#bbs-1".to_string()).unwrap();
+        (credential, keypair)
+    }
+
+    #[test]
+    fn derived_proof_reveals_only_chosen_claims() {
+        let (credential, keypair) = signed_credential();
+        assert!(credential.verify_bbs_signature(&keypair.public_key).unwrap());
+
+        let derived = credential.derive_proof(&[2], b"verifier-nonce").unwrap();
+
+        assert_eq!(derived.claim_count, 3);
+        assert_eq!(derived.revealed_claims.len(), 1);
+        assert_eq!(derived.revealed_claims[0].name, "nationality");
+        assert!(verify_bbs_proof(&derived, &keypair.public_key, b"verifier-nonce").unwrap());
+        assert!(!verify_bbs_proof(&derived, &keypair.public_key, b"replayed-nonce").unwrap());
+
+        // Proofs are randomized so presentations cannot be linked
+        assert_ne!(credential.derive_proof(&[2], b"verifier-nonce").unwrap().proof_value, derived.proof_value);
+    }
+
+    #[test]
+    fn tampered_disclosure_fails_verification() {
+        let (credential, keypair) = signed_credential();
+        let derived = credential.derive_proof(&[1, 2], b"nonce").unwrap();
+
+        let mut altered_value = derived.clone();
+        altered_value.revealed_claims[0].value = serde_json::json!("Mallory");
+        assert!(!verify_bbs_proof(&altered_value, &keypair.public_key, b"nonce").unwrap());
+
+        let mut altered_issuer = derived.clone();
+        altered_issuer.issuer = "did:example:other".to_string();
+        assert!(!verify_bbs_proof(&altered_issuer, &keypair.public_key, b"nonce").unwrap());
+
+        let other_key = generate_bls12381_g2_keypair().unwrap();
+        assert!(!verify_bbs_proof(&derived, &other_key.public_key, b"nonce").unwrap());
+    }
+
+    #[test]
+    fn derived_credential_round_trips_through_a_credential() {
+        let (credential, keypair) = signed_credential();
+        let derived = credential.derive_proof(&[0, 2], b"nonce").unwrap();
+
+        let embedded = derived.to_credential();
+        assert!(!embedded.credential_subject.claims.contains_key("name"));
+        let recovered = DerivedCredential::from_credential(&embedded).unwrap();
+
+        assert_eq!(recovered, derived);
+        assert!(verify_bbs_proof(&recovered, &keypair.public_key, b"nonce").unwrap());
+    }
+
+    #[test]
+    fn bbs_signing_requires_a_g2_key() {
+        let (mut credential, _) = signed_credential();
+        credential.proof = None;
+
+        assert!(credential.sign_bbs(&generate_ed25519_keypair().unwrap(), "did:example:issuer#key-1".to_string()).is_err());
+        assert!(credential.derive_proof(&[0], b"nonce").is_err());
+    }
+}
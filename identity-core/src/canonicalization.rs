@@ -0,0 +1,180 @@
+//! Canonical serializations that signatures are computed over
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use crate::error::IdentityError;
+
+/// Canonicalization algorithm a signature suite signs documents with
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum Canonicalization {
+    /// JSON Canonicalization Scheme (RFC 8785)
+    Jcs,
+}
+
+impl Canonicalization {
+    /// Algorithm a Data Integrity cryptosuite names, e.g. `eddsa-jcs-2022`.
+    ///
+    /// RDF canonicalization suites such as `eddsa-rdfc-2022` are not supported.
+    pub fn for_cryptosuite(cryptosuite: &str) -> Option<Self> {
+        if cryptosuite.contains("-jcs-") {
+            Some(Canonicalization::Jcs)
+        } else {
+            None
+        }
+    }
+
+    /// Canonical bytes of a JSON document
+    pub fn canonicalize(&self, value: &Value) -> Result<Vec<u8>, IdentityError> {
+        match self {
+            Canonicalization::Jcs => Ok(canonicalize_jcs(value).into_bytes()),
+        }
+    }
+}
+
+/// Serialize a JSON value per RFC 8785
+pub fn canonicalize_jcs(value: &Value) -> String {
+    let mut out = String::new();
+    write_jcs(value, &mut out);
+    out
+}
+
+fn write_jcs(value: &Value, out: &mut String) {
+    match value {
+        Value::Null => out.push_str("null"),
+        Value::Bool(b) => out.push_str(if *b { "true" } else { "false" }),
+        Value::Number(number) => out.push_str(&jcs_number(number)),
+        Value::String(s) => write_jcs_string(s, out),
+        Value::Array(items) => {
+            out.push('[');
+            for (i, item) in items.iter().enumerate() {
+                if i > 0 {
+                    out.push(',');
+                }
+                write_jcs(item, out);
+            }
+            out.push(']');
+        }
+        Value::Object(object) => {
+            // Members are ordered by the UTF-16 code units of their names
+            let mut members: Vec<_> = object.iter().collect();
+            members.sort_by(|(a, _), (b, _)| a.encode_utf16().cmp(b.encode_utf16()));
+            out.push('{');
+            for (i, (key, member)) in members.into_iter().enumerate() {
+                if i > 0 {
+                    out.push(',');
+                }
+                write_jcs_string(key, out);
+                out.push(':');
+                write_jcs(member, out);
+            }
+            out.push('}');
+        }
+    }
+}
+
+fn write_jcs_string(s: &str, out: &mut String) {
+    out.push('"');
+    for c in s.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\u{08}' => out.push_str("\\b"),
+            '\t' => out.push_str("\\t"),
+            '\n' => out.push_str("\\n"),
+            '\u{0C}' => out.push_str("\\f"),
+            '\r' => out.push_str("\\r"),
+            c if c < ' ' => out.push_str(&format!("\\u{:04x}", c as u32)),
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+}
+
+/// Numbers are IEEE 754 doubles written as ECMAScript's `Number.prototype.toString` does
+fn jcs_number(number: &serde_json::Number) -> String {
+    const MAX_SAFE_INTEGER: u64 = 1 << 53;
+    if let Some(n) = number.as_u64().filter(|n| *n <= MAX_SAFE_INTEGER) {
+        return n.to_string();
+    }
+    if let Some(n) = number.as_i64().filter(|n| n.unsigned_abs() <= MAX_SAFE_INTEGER) {
+        return n.to_string();
+    }
+    es_number(number.as_f64().unwrap_or_default())
+}
+
+fn es_number(n: f64) -> String {
+    if n == 0.0 {
+        return "0".to_string();
+    }
+    if n < 0.0 {
+        return format!("-{}", es_number(-n));
+    }
+
+    // Shortest round-trip digits and decimal exponent, e.g. "1.2345e-7"
+    let scientific = format!("{:e}", n);
+    let (mantissa, exponent) = scientific.split_once('e').unwrap_or((&scientific, "0"));
+    let digits: String = mantissa.chars().filter(|c| *c != '.').collect();
+    let k = digits.len() as i32;
+    let point = exponent.parse::<i32>().unwrap_or_default() + 1;
+
+    if k <= point && point <= 21 {
+        format!("{}{}", digits, "0".repeat((point - k) as usize))
+    } else if 0 < point && point <= 21 {
+        format!("{}.{}", &digits[..point as usize], &digits[point as usize..])
+    } else if -6 < point && point <= 0 {
+        format!("0.{}{}", "0".repeat(-point as usize), digits)
+    } else {
+        let sign = if point - 1 < 0 { '-' } else { '+' };
+        let (first, rest) = digits.split_at(1);
+        if rest.is_empty() {
+            format!("{}e{}{}", first, sign, (point - 1).abs())
+        } else {
+            format!("{}.{}e{}{}", first, rest, sign, (point - 1).abs())
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn jcs(json: &str) -> String {
+        canonicalize_jcs(&serde_json::from_str(json).unwrap())
+    }
+
+    #[test]
+    fn jcs_matches_the_rfc_8785_vectors() {
+        // RFC 8785 section 3.2.2
+        let input = r#"{
+            "numbers": [333333333.33333329, 1E30, 4.50, 2e-3, 0.000000000000000000000000001],
+            "string": "\u20ac$\u000F\u000aA'\u0042\u0022\u005c\\\"\/",
+            "literals": [null, true, false]
+        }"#;
+        assert_eq!(
+            jcs(input),
+            r#"{"literals":[null,true,false],"numbers":[333333333.3333333,1e+30,4.5,0.002,1e-27],"string":"€$\u000f\nA'B\"\\\\\"/"}"#
+        );
+
+        // RFC 8785 section 3.2.3: names sort by UTF-16 code units, so the emoji's surrogates precede U+FB33
+        let sorted = jcs(r#"{"\u20ac":1,"\r":2,"\ufb33":3,"1":4,"\ud83d\ude00":5,"\u0080":6,"\u00f6":7}"#);
+        assert_eq!(sorted, "{\"\\r\":2,\"1\":4,\"\u{80}\":6,\"ö\":7,\"€\":1,\"😀\":5,\"\u{fb33}\":3}");
+    }
+
+    #[test]
+    fn jcs_numbers_follow_ecmascript_formatting() {
+        for (input, expected) in [
+            ("0", "0"), ("-0.0", "0"), ("1e21", "1e+21"), ("1e20", "100000000000000000000"),
+            ("0.000001", "0.000001"), ("1e-7", "1e-7"), ("-1.5", "-1.5"), ("5e-324", "5e-324"),
+            ("9007199254740992", "9007199254740992"), ("123456789012345680000", "123456789012345680000"),
+        ] {
+            assert_eq!(jcs(input), expected, "{}", input);
+        }
+    }
+
+    #[test]
+    fn cryptosuite_selects_its_canonicalization() {
+        assert_eq!(Canonicalization::for_cryptosuite("eddsa-jcs-2022"), Some(Canonicalization::Jcs));
+        assert_eq!(Canonicalization::for_cryptosuite("eddsa-rdfc-2022"), None);
+        assert_eq!(Canonicalization::for_cryptosuite("bbs-2023"), None);
+    }
+}
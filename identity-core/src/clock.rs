@@ -0,0 +1,91 @@
+//! Pluggable time source for time-dependent logic
+
+use std::fmt::Debug;
+use std::sync::{Arc, Mutex};
+use chrono::{DateTime, Duration, Utc};
+
+/// Source of the current time
+pub trait Clock: Debug + Send + Sync {
+    /// Current time
+    fn now(&self) -> DateTime<Utc>;
+}
+
+/// Clock reading the system time
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SystemClock;
+
+/// Manually controlled clock; clones share the same time
+#[derive(Debug, Clone)]
+pub struct MockClock {
+    now: Arc<Mutex<DateTime<Utc>>>,
+}
+
+impl Clock for SystemClock {
+    fn now(&self) -> DateTime<Utc> {
+        Utc::now()
+    }
+}
+
+impl MockClock {
+    /// Create a clock stopped at `start`
+    pub fn new(start: DateTime<Utc>) -> Self {
+        Self { now: Arc::new(Mutex::new(start)) }
+    }
+
+    /// Move the clock forward
+    pub fn advance(&self, duration: Duration) {
+        *self.lock() += duration;
+    }
+
+    /// Set the clock to a specific time
+    pub fn set(&self, now: DateTime<Utc>) {
+        *self.lock() = now;
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, DateTime<Utc>> {
+        // A poisoned clock still holds a valid time
+        self.now.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+impl Default for MockClock {
+    fn default() -> Self {
+        Self::new(Utc::now())
+    }
+}
+
+impl Clock for MockClock {
+    fn now(&self) -> DateTime<Utc> {
+        *self.lock()
+    }
+}
+
+/// Shared handle to the system clock
+pub fn system_clock() -> Arc<dyn Clock> {
+    Arc::new(SystemClock)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn mock_clock_clones_share_their_time() {
+        let start = Utc::now();
+        let clock = MockClock::new(start);
+        let shared = clock.clone();
+
+        shared.advance(Duration::hours(2));
+        assert_eq!(clock.now(), start + Duration::hours(2));
+
+        clock.set(start - Duration::days(1));
+        assert_eq!(shared.now(), start - Duration::days(1));
+    }
+
+    #[test]
+    fn system_clock_reads_the_current_time() {
+        let before = Utc::now();
+        let now = system_clock().now();
+        assert!(before <= now && now <= Utc::now());
+    }
+}
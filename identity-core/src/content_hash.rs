@@ -0,0 +1,185 @@
+//! Multihash-aware content hashing, compatible with IPFS CIDs
+
+use std::fmt;
+use std::str::FromStr;
+use cid::Cid;
+use multihash::Multihash;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256, Sha512};
+use sha3::{Keccak256, Sha3_256};
+use crate::error::IdentityError;
+
+/// Largest digest supported, in bytes
+pub const MAX_DIGEST_SIZE: usize = 64;
+
+/// Multihash function codes supported for content addressing
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize, Default)]
+pub enum HashCode {
+    #[default]
+    Sha2_256,
+    Sha2_512,
+    Sha3_256,
+    Keccak256,
+    Blake3_256,
+}
+
+impl HashCode {
+    /// Multicodec code of the hash function
+    pub fn code(&self) -> u64 {
+        match self {
+            HashCode::Sha2_256 => 0x12,
+            HashCode::Sha2_512 => 0x13,
+            HashCode::Sha3_256 => 0x16,
+            HashCode::Keccak256 => 0x1b,
+            HashCode::Blake3_256 => 0x1e,
+        }
+    }
+
+    /// Look up a hash function by its multicodec code
+    pub fn from_code(code: u64) -> Result<Self, IdentityError> {
+        match code {
+            0x12 => Ok(HashCode::Sha2_256),
+            0x13 => Ok(HashCode::Sha2_512),
+            0x16 => Ok(HashCode::Sha3_256),
+            0x1b => Ok(HashCode::Keccak256),
+            0x1e => Ok(HashCode::Blake3_256),
+            code => Err(IdentityError::CryptoError(format!("Unsupported multihash code: {:#x}", code))),
+        }
+    }
+
+    /// Multicodec table name of the hash function
+    pub fn name(&self) -> &'static str {
+        match self {
+            HashCode::Sha2_256 => "sha2-256",
+            HashCode::Sha2_512 => "sha2-512",
+            HashCode::Sha3_256 => "sha3-256",
+            HashCode::Keccak256 => "keccak-256",
+            HashCode::Blake3_256 => "blake3",
+        }
+    }
+
+    /// Raw digest of data
+    pub fn digest(&self, data: &[u8]) -> Vec<u8> {
+        match self {
+            HashCode::Sha2_256 => Sha256::digest(data).to_vec(),
+            HashCode::Sha2_512 => Sha512::digest(data).to_vec(),
+            HashCode::Sha3_256 => Sha3_256::digest(data).to_vec(),
+            HashCode::Keccak256 => Keccak256::digest(data).to_vec(),
+            HashCode::Blake3_256 => blake3::hash(data).as_bytes().to_vec(),
+        }
+    }
+}
+
+impl fmt::Display for HashCode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+impl FromStr for HashCode {
+    type Err = IdentityError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        [HashCode::Sha2_256, HashCode::Sha2_512, HashCode::Sha3_256, HashCode::Keccak256, HashCode::Blake3_256]
+            .into_iter()
+            .find(|code| code.name() == s)
+            .ok_or_else(|| IdentityError::CryptoError(format!("Unsupported hash function: {}", s)))
+    }
+}
+
+/// Multihash of data: varint code, varint digest length, then the digest
+pub fn hash_multihash(data: &[u8], code: HashCode) -> Result<Vec<u8>, IdentityError> {
+    let multihash = Multihash::<MAX_DIGEST_SIZE>::wrap(code.code(), &code.digest(data))
+        .map_err(|e| IdentityError::CryptoError(format!("Failed to build multihash: {}", e)))?;
+    Ok(multihash.to_bytes())
+}
+
+/// Split a multihash into its hash function and digest
+pub fn decode_multihash(bytes: &[u8]) -> Result<(HashCode, Vec<u8>), IdentityError> {
+    let multihash = Multihash::<MAX_DIGEST_SIZE>::from_bytes(bytes)
+        .map_err(|e| IdentityError::EncodingError(format!("Invalid multihash: {}", e)))?;
+    Ok((HashCode::from_code(multihash.code())?, multihash.digest().to_vec()))
+}
+
+/// Check data against a multihash using the hash function the multihash names
+pub fn verify_multihash(data: &[u8], multihash: &[u8]) -> Result<bool, IdentityError> {
+    let (code, digest) = decode_multihash(multihash)?;
+    Ok(code.digest(data) == digest)
+}
+
+/// Multihash a CID commits to
+pub fn cid_multihash(cid: &str) -> Result<Vec<u8>, IdentityError> {
+    let cid = Cid::try_from(cid)
+        .map_err(|e| IdentityError::EncodingError(format!("Invalid CID {}: {}", cid, e)))?;
+    Ok(cid.hash().to_bytes())
+}
+
+/// Hash function a content hash was computed with: the CID's for CIDs, SHA-256 otherwise
+pub fn hash_code_of(content_hash: &str) -> HashCode {
+    cid_multihash(content_hash)
+        .and_then(|multihash| decode_multihash(&multihash))
+        .map(|(code, _)| code)
+        .unwrap_or_default()
+}
+
+/// Check that raw data hashes to the digest a CID commits to
+pub fn matches_cid(data: &[u8], cid: &str) -> Result<bool, IdentityError> {
+    verify_multihash(data, &cid_multihash(cid)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// CIDv1 (raw codec) of `hello world` as produced by `ipfs add --raw-leaves --cid-version 1`
+    const SHA256_CID: &str = "bafkreifzjut3te2nhyekklss27nh3k72ysco7y32koao5eei66wof36n5e";
+    /// Same content added with `--hash blake3`
+    const BLAKE3_CID: &str = "bafkr4igxjga67jykbseaxdmmdgc5a5o3zp3htom2l6mrjznk7fvyggu6eq";
+
+    #[test]
+    fn sha256_multihash_matches_the_cid() {
+        let multihash = hash_multihash(b"hello world", HashCode::Sha2_256).unwrap();
+
+        assert_eq!(multihash[..2], [0x12, 0x20]);
+        assert_eq!(hex::encode(&multihash[2..]), "b94d27b9934d3e08a52e52d7da7dabfac484efe37a5380ee9088f7ace2efcde9");
+        assert_eq!(multihash, cid_multihash(SHA256_CID).unwrap());
+        assert!(matches_cid(b"hello world", SHA256_CID).unwrap());
+        assert!(!matches_cid(b"hello world!", SHA256_CID).unwrap());
+        assert_eq!(hash_code_of(SHA256_CID), HashCode::Sha2_256);
+    }
+
+    #[test]
+    fn blake3_multihash_matches_the_cid() {
+        let multihash = hash_multihash(b"hello world", HashCode::Blake3_256).unwrap();
+
+        assert_eq!(multihash[..2], [0x1e, 0x20]);
+        assert_eq!(multihash, cid_multihash(BLAKE3_CID).unwrap());
+        assert!(matches_cid(b"hello world", BLAKE3_CID).unwrap());
+        assert_eq!(hash_code_of(BLAKE3_CID), HashCode::Blake3_256);
+        assert_ne!(cid_multihash(SHA256_CID).unwrap(), multihash);
+    }
+
+    #[test]
+    fn every_code_round_trips_through_its_multihash() {
+        for code in [HashCode::Sha2_256, HashCode::Sha2_512, HashCode::Sha3_256, HashCode::Keccak256, HashCode::Blake3_256] {
+            let multihash = hash_multihash(b"payload", code).unwrap();
+            let (decoded, digest) = decode_multihash(&multihash).unwrap();
+
+            assert_eq!(decoded, code);
+            assert_eq!(digest, code.digest(b"payload"));
+            assert!(verify_multihash(b"payload", &multihash).unwrap());
+            assert_eq!(HashCode::from_code(code.code()).unwrap(), code);
+            assert_eq!(code.name().parse::<HashCode>().unwrap(), code);
+        }
+    }
+
+    #[test]
+    fn unknown_codes_and_plain_hashes() {
+        assert!(HashCode::from_code(0x11).is_err());
+        assert!("md5".parse::<HashCode>().is_err());
+        assert!(decode_multihash(&[0x12, 0x20, 0x00]).is_err());
+        assert!(cid_multihash("not-a-cid").is_err());
+        // Legacy registry hashes are plain SHA-256 hex digests
+        assert_eq!(hash_code_of(&hex::encode(HashCode::Sha2_256.digest(b"doc"))), HashCode::Sha2_256);
+    }
+}
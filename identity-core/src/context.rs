@@ -0,0 +1,293 @@
+//! Loading of JSON-LD `@context` documents, bundled or fetched and cached
+
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use chrono::{DateTime, Duration, Utc};
+use crate::clock::{system_clock, Clock};
+use crate::error::IdentityError;
+use crate::vc::VerifiableCredential;
+
+/// W3C Verifiable Credentials 1.1 context
+pub const CREDENTIALS_V1_CONTEXT: &str = "https://www.w3.org/2018/credentials/v1";
+
+/// W3C Verifiable Credentials 2.0 context
+pub const CREDENTIALS_V2_CONTEXT: &str = "https://www.w3.org/ns/credentials/v2";
+
+/// W3C DID Core context
+pub const DID_V1_CONTEXT: &str = "https://www.w3.org/ns/did/v1";
+
+/// Largest context document fetched unless configured otherwise
+pub const DEFAULT_MAX_CONTEXT_SIZE: usize = 256 * 1024;
+
+/// Contexts shipped with the crate, so standard credentials resolve offline
+const BUNDLED_CONTEXTS: [(&str, &str); 3] = [
+    (CREDENTIALS_V1_CONTEXT, include_str!("../contexts/credentials-v1.jsonld")),
+    (CREDENTIALS_V2_CONTEXT, include_str!("../contexts/credentials-v2.jsonld")),
+    (DID_V1_CONTEXT, include_str!("../contexts/did-v1.jsonld")),
+];
+
+/// Bundled context document for a URL, if one ships with the crate
+pub fn bundled_context(url: &str) -> Option<serde_json::Value> {
+    BUNDLED_CONTEXTS.iter()
+        .find(|(bundled, _)| *bundled == url)
+        .map(|(_, document)| serde_json::from_str(document).expect("bundled context is valid JSON"))
+}
+
+/// Where a loaded context came from
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ContextSource {
+    Bundled,
+    Cache,
+    Remote,
+}
+
+/// Loads `@context` documents, serving bundled contexts offline and caching fetched ones for a TTL
+#[derive(Debug)]
+pub struct ContextLoader {
+    client: reqwest::Client,
+    clock: Arc<dyn Clock>,
+    ttl: Duration,
+    max_size: usize,
+    allow_http: bool,
+    cache: Mutex<HashMap<String, CachedContext>>,
+}
+
+/// Fetched context with the time it stops being served from cache
+#[derive(Debug, Clone)]
+struct CachedContext {
+    document: serde_json::Value,
+    expires_at: DateTime<Utc>,
+}
+
+impl ContextLoader {
+    /// Create a loader caching fetched contexts for a day
+    pub fn new() -> Self {
+        Self {
+            client: reqwest::Client::new(),
+            clock: system_clock(),
+            ttl: Duration::days(1),
+            max_size: DEFAULT_MAX_CONTEXT_SIZE,
+            allow_http: false,
+            cache: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Cache fetched contexts for `ttl`
+    pub fn with_ttl(mut self, ttl: Duration) -> Self {
+        self.ttl = ttl;
+        self
+    }
+
+    /// Reject fetched contexts larger than `max_size` bytes
+    pub fn with_max_size(mut self, max_size: usize) -> Self {
+        self.max_size = max_size;
+        self
+    }
+
+    /// Measure cache expiry with the given clock
+    pub fn with_clock(mut self, clock: Arc<dyn Clock>) -> Self {
+        self.clock = clock;
+        self
+    }
+
+    /// Also fetch `http://` contexts, e.g. from a local development server
+    pub fn with_insecure_http(mut self, allow_http: bool) -> Self {
+        self.allow_http = allow_http;
+        self
+    }
+
+    /// Load a context document
+    pub async fn load(&self, url: &str) -> Result<serde_json::Value, IdentityError> {
+        self.load_with_source(url).await.map(|(document, _)| document)
+    }
+
+    /// Load a context document, reporting whether it was bundled, cached or fetched
+    pub async fn load_with_source(&self, url: &str) -> Result<(serde_json::Value, ContextSource), IdentityError> {
+        if let Some(document) = bundled_context(url) {
+            return Ok((document, ContextSource::Bundled));
+        }
+        if let Some(document) = self.cached(url) {
+            return Ok((document, ContextSource::Cache));
+        }
+
+        let document = self.fetch(url).await?;
+        let expires_at = self.clock.now() + self.ttl;
+        self.lock_cache().insert(url.to_string(), CachedContext { document: document.clone(), expires_at });
+        Ok((document, ContextSource::Remote))
+    }
+
+    /// Load every context a credential references
+    pub async fn load_credential_contexts(&self, credential: &VerifiableCredential) -> Result<Vec<serde_json::Value>, IdentityError> {
+        let mut documents = Vec::with_capacity(credential.context.len());
+        for url in &credential.context {
+            documents.push(self.load(url).await?);
+        }
+        Ok(documents)
+    }
+
+    /// Number of fetched contexts currently cached, including expired ones not yet refetched
+    pub fn cached_count(&self) -> usize {
+        self.lock_cache().len()
+    }
+
+    /// Drop every cached context
+    pub fn clear_cache(&self) {
+        self.lock_cache().clear();
+    }
+
+    fn cached(&self, url: &str) -> Option<serde_json::Value> {
+        let now = self.clock.now();
+        self.lock_cache().get(url)
+            .filter(|cached| now < cached.expires_at)
+            .map(|cached| cached.document.clone())
+    }
+
+    fn lock_cache(&self) -> std::sync::MutexGuard<'_, HashMap<String, CachedContext>> {
+        self.cache.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+
+    /// Fetch a context, enforcing the scheme and size limit
+    async fn fetch(&self, url: &str) -> Result<serde_json::Value, IdentityError> {
+        let parsed = url::Url::parse(url)
+            .map_err(|e| IdentityError::InvalidCredential(format!("Invalid context URL {}: {}", url, e)))?;
+        match parsed.scheme() {
+            "https" => {}
+            "http" if self.allow_http => {}
+            scheme => {
+                return Err(IdentityError::PermissionDenied(format!(
+                    "Refusing to fetch context over {}: {}", scheme, url
+                )));
+            }
+        }
+
+        let mut response = self.client.get(parsed)
+            .header(reqwest::header::ACCEPT, "application/ld+json, application/json")
+            .send()
+            .await
+            .map_err(|e| IdentityError::NetworkError(format!("Failed to fetch context {}: {}", url, e)))?;
+
+        if !response.status().is_success() {
+            return Err(IdentityError::NetworkError(format!(
+                "Fetching context {} returned {}", url, response.status()
+            )));
+        }
+        if response.content_length().is_some_and(|length| length > self.max_size as u64) {
+            return Err(self.too_large(url));
+        }
+
+        // The declared length may be missing or wrong, so count the body as it arrives
+        let mut body = Vec::new();
+        while let Some(chunk) = response.chunk().await
+            .map_err(|e| IdentityError::NetworkError(format!("Failed to read context {}: {}", url, e)))?
+        {
+            if body.len() + chunk.len() > self.max_size {
+                return Err(self.too_large(url));
+            }
+            body.extend_from_slice(&chunk);
+        }
+
+        let document: serde_json::Value = serde_json::from_slice(&body)?;
+        if document.get("@context").is_none() {
+            return Err(IdentityError::InvalidCredential(format!("{} is not a JSON-LD context document", url)));
+        }
+        Ok(document)
+    }
+
+    fn too_large(&self, url: &str) -> IdentityError {
+        IdentityError::InvalidCredential(format!("Context {} exceeds {} bytes", url, self.max_size))
+    }
+}
+
+impl Default for ContextLoader {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use tokio::net::TcpListener;
+    use crate::clock::MockClock;
+
+    /// Serve `body` to every request, counting the requests served
+    async fn mock_server(body: serde_json::Value) -> (String, Arc<AtomicUsize>) {
+        let body = serde_json::to_vec(&body).unwrap();
+        let hits = Arc::new(AtomicUsize::new(0));
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let address = listener.local_addr().unwrap();
+
+        let served = hits.clone();
+        tokio::spawn(async move {
+            while let Ok((mut socket, _)) = listener.accept().await {
+                let mut buffer = [0u8; 4096];
+                let _ = socket.read(&mut buffer).await;
+                served.fetch_add(1, Ordering::SeqCst);
+                let header = format!(
+                    "HTTP/1.1 200 OK\r\nContent-Type: application/ld+json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n",
+                    body.len()
+                );
+                let _ = socket.write_all(header.as_bytes()).await;
+                let _ = socket.write_all(&body).await;
+            }
+        });
+        (format!("http://{}/contexts/example/v1", address), hits)
+    }
+
+    fn example_context() -> serde_json::Value {
+        serde_json::json!({ "@context": { "degree": "https://example.com/vocab#degree" } })
+    }
+
+    #[tokio::test]
+    async fn bundled_contexts_resolve_offline() {
+        let loader = ContextLoader::new();
+
+        let (document, source) = loader.load_with_source(CREDENTIALS_V1_CONTEXT).await.unwrap();
+        assert_eq!(source, ContextSource::Bundled);
+        assert!(document.get("@context").is_some());
+
+        let credential = VerifiableCredential::new("did:example:issuer".to_string(), None, Default::default());
+        assert_eq!(loader.load_credential_contexts(&credential).await.unwrap().len(), credential.context.len());
+        assert_eq!(loader.cached_count(), 0);
+    }
+
+    #[tokio::test]
+    async fn remote_context_is_fetched_once_and_refetched_after_the_ttl() {
+        let (url, hits) = mock_server(example_context()).await;
+        let clock = MockClock::default();
+        let loader = ContextLoader::new()
+            .with_insecure_http(true)
+            .with_ttl(Duration::minutes(10))
+            .with_clock(Arc::new(clock.clone()));
+
+        assert_eq!(loader.load_with_source(&url).await.unwrap(), (example_context(), ContextSource::Remote));
+        assert_eq!(loader.load_with_source(&url).await.unwrap().1, ContextSource::Cache);
+        assert_eq!(hits.load(Ordering::SeqCst), 1);
+
+        clock.advance(Duration::minutes(11));
+        assert_eq!(loader.load_with_source(&url).await.unwrap().1, ContextSource::Remote);
+        assert_eq!(hits.load(Ordering::SeqCst), 2);
+    }
+
+    #[tokio::test]
+    async fn oversized_and_non_context_documents_are_rejected() {
+        let (url, _) = mock_server(example_context()).await;
+        let loader = ContextLoader::new().with_insecure_http(true).with_max_size(16);
+        assert!(loader.load(&url).await.unwrap_err().to_string().contains("exceeds 16 bytes"));
+
+        let (url, _) = mock_server(serde_json::json!({ "degree": "BSc" })).await;
+        let loader = ContextLoader::new().with_insecure_http(true);
+        assert!(matches!(loader.load(&url).await, Err(IdentityError::InvalidCredential(_))));
+        assert_eq!(loader.cached_count(), 0);
+    }
+
+    #[tokio::test]
+    async fn plain_http_is_refused_by_default() {
+        let (url, hits) = mock_server(example_context()).await;
+
+        assert!(matches!(ContextLoader::new().load(&url).await, Err(IdentityError::PermissionDenied(_))));
+        assert_eq!(hits.load(Ordering::SeqCst), 0);
+    }
+}
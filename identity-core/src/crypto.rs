@@ -1,18 +1,21 @@
 //! Cryptographic utilities for identity management
 
+pub mod encoding;
+pub mod siwe;
+pub mod bbs;
+
 use anyhow::Result;
 use sha2::{Sha256, Digest};
 use rand::rngs::OsRng;
-use schnorrkel::{Keypair, PublicKey, SecretKey, Signature};
+use ed25519_dalek::{Signature, Signer, SigningKey, VerifyingKey, SIGNATURE_LENGTH};
 use bls12_381::{G1Projective, G2Projective, Scalar};
 use ff::Field;
 use group::GroupEncoding;
-use base64::{Engine as _, engine::general_purpose::URL_SAFE_NO_PAD};
 use crate::error::IdentityError;
-use std::collections::HashMap;
+use std::collections::BTreeMap;
 
 /// Key types supported by the system
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
 pub enum KeyType {
     Ed25519,
     Secp256k1,
@@ -31,6 +34,48 @@ impl std::fmt::Display for KeyType {
     }
 }
 
+impl KeyType {
+    /// Get the proof type used when signing with this key type
+    pub fn signature_suite(&self) -> &str {
+        match self {
+            KeyType::Ed25519 => "Ed25519Signature2020",
+            KeyType::Secp256k1 => "EcdsaSecp256k1Signature2019",
+            KeyType::Bls12381G1 => "BbsBlsSignature2020",
+            KeyType::Bls12381G2 => "BbsBlsSignature2020",
+        }
+    }
+
+    /// Data Integrity cryptosuite this crate signs with for the key type, if one is defined.
+    ///
+    /// Only the JCS suites are supported; no standard suite covers secp256k1.
+    pub fn cryptosuite(&self) -> Option<&str> {
+        match self {
+            KeyType::Ed25519 => Some("eddsa-jcs-2022"),
+            KeyType::Secp256k1 | KeyType::Bls12381G1 | KeyType::Bls12381G2 => None,
+        }
+    }
+
+    /// Key type a supported Data Integrity cryptosuite signs with.
+    ///
+    /// `eddsa-rdfc-2022` needs RDF dataset canonicalization, which this crate does not implement.
+    pub fn from_cryptosuite(cryptosuite: &str) -> Option<KeyType> {
+        match cryptosuite {
+            "eddsa-jcs-2022" => Some(KeyType::Ed25519),
+            _ => None,
+        }
+    }
+
+    /// Length in bytes of a public key of this type as it is encoded in multikeys
+    pub fn public_key_length(&self) -> usize {
+        match self {
+            KeyType::Ed25519 => 32,
+            KeyType::Secp256k1 => 33,
+            KeyType::Bls12381G1 => 48,
+            KeyType::Bls12381G2 => 96,
+        }
+    }
+}
+
 /// Cryptographic key pair
 #[derive(Debug, Clone)]
 pub struct CryptoKeyPair {
@@ -41,9 +86,51 @@ pub struct CryptoKeyPair {
 
 /// Generate a cryptographic hash of data
 pub fn hash_data(data: &[u8]) -> Vec<u8> {
-    let mut hasher = Sha256::new();
+    let mut hasher = StreamingHasher::new();
     hasher.update(data);
-    hasher.finalize().to_vec()
+    hasher.finalize()
+}
+
+/// Incremental SHA-256 matching `hash_data`, for content too large to hold in memory
+#[derive(Debug, Clone, Default)]
+pub struct StreamingHasher {
+    hasher: Sha256,
+    bytes_hashed: u64,
+}
+
+impl StreamingHasher {
+    /// Start an empty hash
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Hash the next chunk of the stream
+    pub fn update(&mut self, chunk: &[u8]) {
+        self.hasher.update(chunk);
+        self.bytes_hashed += chunk.len() as u64;
+    }
+
+    /// Total bytes hashed so far
+    pub fn bytes_hashed(&self) -> u64 {
+        self.bytes_hashed
+    }
+
+    /// Finish the hash, returning the 32-byte digest
+    pub fn finalize(self) -> Vec<u8> {
+        self.hasher.finalize().to_vec()
+    }
+
+    /// Hash everything a reader yields
+    pub fn hash_reader<R: std::io::Read>(mut reader: R) -> std::io::Result<Vec<u8>> {
+        let mut hasher = Self::new();
+        let mut buffer = [0u8; 64 * 1024];
+        loop {
+            match reader.read(&mut buffer)? {
+                0 => return Ok(hasher.finalize()),
+                read => hasher.update(&buffer[..read]),
+            }
+        }
+    }
 }
 
 /// Generate a hash of JSON-serializable data
@@ -52,14 +139,26 @@ pub fn hash_json<T: serde::Serialize>(data: &T) -> Result<Vec<u8>, IdentityError
     Ok(hash_data(&json))
 }
 
-/// Generate an Ed25519 keypair using Schnorrkel
+/// Generate an Ed25519 keypair with a 32-byte seed as its private key
 pub fn generate_ed25519_keypair() -> Result<CryptoKeyPair, IdentityError> {
-    let keypair = Keypair::generate_with(&mut OsRng);
+    let signing_key = SigningKey::generate(&mut OsRng);
 
     Ok(CryptoKeyPair {
         key_type: KeyType::Ed25519,
-        private_key: keypair.secret.to_bytes().to_vec(),
-        public_key: keypair.public.to_bytes().to_vec(),
+        private_key: signing_key.to_bytes().to_vec(),
+        public_key: signing_key.verifying_key().to_bytes().to_vec(),
+    })
+}
+
+/// Generate a secp256k1 keypair with a compressed 33-byte public key
+pub fn generate_secp256k1_keypair() -> Result<CryptoKeyPair, IdentityError> {
+    let private_key = k256::SecretKey::random(&mut OsRng);
+    let public_key = private_key.public_key().to_sec1_bytes();
+
+    Ok(CryptoKeyPair {
+        key_type: KeyType::Secp256k1,
+        private_key: private_key.to_bytes().to_vec(),
+        public_key: public_key.to_vec(),
     })
 }
 
@@ -93,64 +192,299 @@ pub fn generate_keypair(key_type: KeyType) -> Result<CryptoKeyPair, IdentityErro
         KeyType::Ed25519 => generate_ed25519_keypair(),
         KeyType::Bls12381G1 => generate_bls12381_g1_keypair(),
         KeyType::Bls12381G2 => generate_bls12381_g2_keypair(),
-        KeyType::Secp256k1 => Err(IdentityError::CryptoError("Secp256k1 not implemented yet".to_string())),
+        KeyType::Secp256k1 => generate_secp256k1_keypair(),
     }
 }
 
-/// Sign data with Ed25519 key
+/// Sign data with an Ed25519 seed (RFC 8032)
 pub fn sign_ed25519(data: &[u8], private_key: &[u8]) -> Result<Vec<u8>, IdentityError> {
-    let secret = SecretKey::from_bytes(private_key)
-        .map_err(|e| IdentityError::CryptoError(format!("Invalid private key: {}", e)))?;
+    let seed: [u8; 32] = private_key.try_into()
+        .map_err(|_| IdentityError::CryptoError("Invalid private key: expected a 32-byte Ed25519 seed".to_string()))?;
 
-    let keypair = Keypair::from(secret);
-    let signature = keypair.sign_simple(b"", data);
+    let signature = SigningKey::from_bytes(&seed).sign(data);
 
     Ok(signature.to_bytes().to_vec())
 }
 
 /// Verify Ed25519 signature
+///
+/// Small-order keys and commitments, and non-canonical signature encodings, verify as `false`.
 pub fn verify_ed25519(data: &[u8], signature: &[u8], public_key: &[u8]) -> Result<bool, IdentityError> {
-    let public = PublicKey::from_bytes(public_key)
+    let public = public_key.try_into().ok()
+        .and_then(|bytes: [u8; 32]| VerifyingKey::from_bytes(&bytes).ok())
+        .ok_or_else(|| IdentityError::CryptoError("Invalid public key: not an Ed25519 point".to_string()))?;
+
+    let sig = match <[u8; SIGNATURE_LENGTH]>::try_from(signature) {
+        Ok(bytes) => Signature::from_bytes(&bytes),
+        Err(_) => return Ok(false),
+    };
+
+    // Strict verification rejects weak keys and scalars >= the group order
+    Ok(public.verify_strict(data, &sig).is_ok())
+}
+
+/// Sign the SHA-256 digest of data with a secp256k1 key, as a 64-byte compact `r || s` signature
+pub fn sign_secp256k1(data: &[u8], private_key: &[u8]) -> Result<Vec<u8>, IdentityError> {
+    use k256::ecdsa::signature::Signer as _;
+
+    let signing_key = k256::ecdsa::SigningKey::from_slice(private_key)
+        .map_err(|e| IdentityError::CryptoError(format!("Invalid private key: {}", e)))?;
+    let signature: k256::ecdsa::Signature = signing_key.sign(data);
+
+    Ok(signature.to_bytes().to_vec())
+}
+
+/// Verify a compact secp256k1 ECDSA signature over the SHA-256 digest of data
+///
+/// Malformed and high-S signatures verify as `false`.
+pub fn verify_secp256k1(data: &[u8], signature: &[u8], public_key: &[u8]) -> Result<bool, IdentityError> {
+    use k256::ecdsa::signature::Verifier as _;
+
+    let verifying_key = k256::ecdsa::VerifyingKey::from_sec1_bytes(public_key)
         .map_err(|e| IdentityError::CryptoError(format!("Invalid public key: {}", e)))?;
+    let sig = match k256::ecdsa::Signature::from_slice(signature) {
+        Ok(sig) => sig,
+        Err(_) => return Ok(false),
+    };
 
-    let sig = Signature::from_bytes(signature)
-        .map_err(|e| IdentityError::CryptoError(format!("Invalid signature: {}", e)))?;
+    Ok(verifying_key.verify(data, &sig).is_ok())
+}
 
-    Ok(public.verify_simple(b"", data, &sig).is_ok())
+/// Sign data with a private key of the given type
+pub fn sign_data(data: &[u8], private_key: &[u8], key_type: &KeyType) -> Result<Vec<u8>, IdentityError> {
+    match key_type {
+        KeyType::Ed25519 => sign_ed25519(data, private_key),
+        KeyType::Secp256k1 => sign_secp256k1(data, private_key),
+        _ => Err(IdentityError::SignatureError(format!("Signing not supported for {}", key_type))),
+    }
 }
 
-/// Convert public key to multibase format
-pub fn public_key_to_multibase(public_key: &[u8], key_type: &KeyType) -> String {
-    // This is a simplified implementation
-    // In practice, you'd use proper multibase encoding with the correct prefixes
+/// Verify a signature with a public key of the given type
+pub fn verify_data(data: &[u8], signature: &[u8], public_key: &[u8], key_type: &KeyType) -> Result<bool, IdentityError> {
     match key_type {
-        KeyType::Ed25519 => format!("z{}", URL_SAFE_NO_PAD.encode(public_key)),
-        KeyType::Bls12381G1 => format!("z{}", URL_SAFE_NO_PAD.encode(public_key)),
-        KeyType::Bls12381G2 => format!("z{}", URL_SAFE_NO_PAD.encode(public_key)),
-        KeyType::Secp256k1 => format!("z{}", URL_SAFE_NO_PAD.encode(public_key)),
+        KeyType::Ed25519 => verify_ed25519(data, signature, public_key),
+        KeyType::Secp256k1 => verify_secp256k1(data, signature, public_key),
+        _ => Err(IdentityError::VerificationError(format!("Verification not supported for {}", key_type))),
     }
 }
 
+/// Convert public key to multibase format (base58btc with multicodec prefix)
+pub fn public_key_to_multibase(public_key: &[u8], key_type: &KeyType) -> String {
+    encoding::encode_multikey(public_key, key_type)
+}
+
+/// Decode a multibase public key into its key type and raw bytes
+pub fn public_key_from_multibase(multibase: &str) -> Result<(KeyType, Vec<u8>), IdentityError> {
+    encoding::decode_multikey(multibase)
+}
+
 /// Create a JWK (JSON Web Key) representation
-pub fn public_key_to_jwk(public_key: &[u8], key_type: &KeyType) -> HashMap<String, serde_json::Value> {
-    let mut jwk = HashMap::new();
+pub fn public_key_to_jwk(public_key: &[u8], key_type: &KeyType) -> BTreeMap<String, serde_json::Value> {
+    let mut jwk = BTreeMap::new();
 
     match key_type {
-        KeyType::Ed25519 => {
-            jwk.insert("kty".to_string(), serde_json::Value::String("OKP".to_string()));
-            jwk.insert("crv".to_string(), serde_json::Value::String("Ed25519".to_string()));
-            jwk.insert("x".to_string(), serde_json::Value::String(
-                URL_SAFE_NO_PAD.encode(public_key)
-            ));
+        KeyType::Secp256k1 => {
+            jwk.insert("kty".to_string(), serde_json::Value::String("EC".to_string()));
+            jwk.insert("crv".to_string(), serde_json::Value::String("secp256k1".to_string()));
+            match k256::PublicKey::from_sec1_bytes(public_key) {
+                Ok(key) => {
+                    use k256::elliptic_curve::sec1::ToEncodedPoint;
+                    let point = key.to_encoded_point(false);
+                    jwk.insert("x".to_string(), serde_json::Value::String(
+                        encoding::encode_base64url(point.x().map(|x| x.as_slice()).unwrap_or_default())
+                    ));
+                    jwk.insert("y".to_string(), serde_json::Value::String(
+                        encoding::encode_base64url(point.y().map(|y| y.as_slice()).unwrap_or_default())
+                    ));
+                }
+                Err(_) => {
+                    jwk.insert("x".to_string(), serde_json::Value::String(
+                        encoding::encode_base64url(public_key)
+                    ));
+                }
+            }
         }
         _ => {
-            // Simplified for other key types
-            jwk.insert("kty".to_string(), serde_json::Value::String("EC".to_string()));
+            jwk.insert("kty".to_string(), serde_json::Value::String("OKP".to_string()));
+            jwk.insert("crv".to_string(), serde_json::Value::String(jwk_curve(key_type).to_string()));
             jwk.insert("x".to_string(), serde_json::Value::String(
-                URL_SAFE_NO_PAD.encode(public_key)
+                encoding::encode_base64url(public_key)
             ));
         }
     }
 
     jwk
 }
+
+/// Decode a public JWK into its key type and raw key bytes (SEC1-compressed for secp256k1)
+pub fn public_key_from_jwk(jwk: &BTreeMap<String, serde_json::Value>) -> Result<(KeyType, Vec<u8>), IdentityError> {
+    let member = |name: &str| {
+        jwk.get(name)
+            .and_then(|value| value.as_str())
+            .ok_or_else(|| IdentityError::EncodingError(format!("JWK is missing \"{}\"", name)))
+    };
+
+    let key_type = match (member("kty")?, member("crv")?) {
+        ("OKP", "Ed25519") => KeyType::Ed25519,
+        ("OKP", "Bls12381G1") => KeyType::Bls12381G1,
+        ("OKP", "Bls12381G2") => KeyType::Bls12381G2,
+        ("EC", "secp256k1") => KeyType::Secp256k1,
+        (kty, crv) => return Err(IdentityError::EncodingError(format!("Unsupported JWK key: {} {}", kty, crv))),
+    };
+
+    let x = encoding::decode_base64url(member("x")?)?;
+    if key_type != KeyType::Secp256k1 {
+        return Ok((key_type, x));
+    }
+
+    let y = encoding::decode_base64url(member("y")?)?;
+    if x.len() != 32 || y.len() != 32 {
+        return Err(IdentityError::EncodingError("Invalid secp256k1 JWK coordinates".to_string()));
+    }
+    let uncompressed = [&[0x04u8][..], &x, &y].concat();
+    let key = k256::PublicKey::from_sec1_bytes(&uncompressed)
+        .map_err(|e| IdentityError::EncodingError(format!("Invalid secp256k1 JWK: {}", e)))?;
+
+    use k256::elliptic_curve::sec1::ToEncodedPoint;
+    Ok((key_type, key.to_encoded_point(true).as_bytes().to_vec()))
+}
+
+/// JWK `crv` name for an octet key pair type
+fn jwk_curve(key_type: &KeyType) -> &'static str {
+    match key_type {
+        KeyType::Ed25519 => "Ed25519",
+        KeyType::Bls12381G1 => "Bls12381G1",
+        KeyType::Bls12381G2 => "Bls12381G2",
+        KeyType::Secp256k1 => "secp256k1",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn ed25519_rejects_zero_and_non_canonical_signatures() {
+        let keypair = generate_ed25519_keypair().unwrap();
+        let signature = sign_ed25519(b"message", &keypair.private_key).unwrap();
+        assert!(verify_ed25519(b"message", &signature, &keypair.public_key).unwrap());
+
+        assert!(!verify_ed25519(b"message", &[0u8; SIGNATURE_LENGTH], &keypair.public_key).unwrap());
+        assert!(!verify_ed25519(b"message", &signature, &[0u8; 32]).unwrap());
+        assert!(!verify_ed25519(b"message", &signature[..63], &keypair.public_key).unwrap());
+
+        // A scalar above the group order
+        let mut unreduced = signature.clone();
+        unreduced[32..].fill(0xff);
+        assert!(!verify_ed25519(b"message", &unreduced, &keypair.public_key).unwrap());
+    }
+
+    #[test]
+    fn ed25519_matches_rfc8032_test_vector() {
+        // RFC 8032 section 7.1, TEST 2
+        let seed = hex::decode("4ccd089b28ff96da9db6c346ec114e0f5b8a319f35aba624da8cf6ed4fb8a6fb").unwrap();
+        let public_key = hex::decode("3d4017c3e843895a92b70aa74d1b7ebc9c982ccf2ec4968cc0cd55f12af4660c").unwrap();
+        let expected = "92a009a9f0d4cab8720e820b5f642540a2b27b5416503f8fb3762223ebdb69da085ac1e43e15996e458f3613d0f11d8c387b2eaeb4302aeeb00d291612bb0c00";
+
+        let signature = sign_ed25519(&[0x72], &seed).unwrap();
+
+        assert_eq!(hex::encode(&signature), expected);
+        assert!(verify_ed25519(&[0x72], &signature, &public_key).unwrap());
+        assert!(sign_ed25519(&[0x72], &[0u8; 64]).is_err());
+    }
+
+    #[test]
+    fn secp256k1_rejects_zero_and_high_s_signatures() {
+        let keypair = generate_secp256k1_keypair().unwrap();
+        let signature = sign_secp256k1(b"message", &keypair.private_key).unwrap();
+        assert!(verify_secp256k1(b"message", &signature, &keypair.public_key).unwrap());
+
+        assert!(!verify_secp256k1(b"message", &[0u8; 64], &keypair.public_key).unwrap());
+        assert!(!verify_secp256k1(b"message", &signature[..63], &keypair.public_key).unwrap());
+
+        // (r, n - s) is the malleated twin of a valid signature
+        let parsed = k256::ecdsa::Signature::from_slice(&signature).unwrap();
+        let high_s = k256::ecdsa::Signature::from_scalars(parsed.r().to_bytes(), (-*parsed.s().as_ref()).to_bytes()).unwrap();
+        assert!(!verify_secp256k1(b"message", &high_s.to_bytes(), &keypair.public_key).unwrap());
+    }
+
+    #[test]
+    fn secp256k1_signature_round_trips_and_rejects_tampered_messages() {
+        let keypair = generate_secp256k1_keypair().unwrap();
+        let signature = sign_data(b"credential payload", &keypair.private_key, &KeyType::Secp256k1).unwrap();
+
+        assert_eq!(signature.len(), 64);
+        assert!(verify_data(b"credential payload", &signature, &keypair.public_key, &KeyType::Secp256k1).unwrap());
+        assert!(!verify_secp256k1(b"credential payloaD", &signature, &keypair.public_key).unwrap());
+        let other = generate_secp256k1_keypair().unwrap();
+        assert!(!verify_secp256k1(b"credential payload", &signature, &other.public_key).unwrap());
+    }
+
+    #[test]
+    fn secp256k1_keypair_has_compressed_keys() {
+        let keypair = generate_keypair(KeyType::Secp256k1).unwrap();
+
+        assert_eq!(keypair.key_type, KeyType::Secp256k1);
+        assert_eq!(keypair.public_key.len(), 33);
+        assert!(matches!(keypair.public_key[0], 0x02 | 0x03));
+        assert_eq!(keypair.private_key.len(), 32);
+        let derived = k256::ecdsa::SigningKey::from_slice(&keypair.private_key).unwrap();
+        assert_eq!(derived.verifying_key().to_encoded_point(true).as_bytes(), keypair.public_key.as_slice());
+    }
+
+    #[test]
+    fn secp256k1_public_key_round_trips_through_multibase() {
+        let keypair = generate_secp256k1_keypair().unwrap();
+
+        let multibase = public_key_to_multibase(&keypair.public_key, &KeyType::Secp256k1);
+
+        assert!(multibase.starts_with("zQ3s"), "{}", multibase);
+        assert_eq!(public_key_from_multibase(&multibase).unwrap(), (KeyType::Secp256k1, keypair.public_key));
+    }
+
+    #[test]
+    fn ed25519_jwk_matches_rfc8037() {
+        // RFC 8037 appendix A.2
+        let public_key = hex::decode("d75a980182b10ab7d54bfed3c964073a0ee172f3daa62325af021a68f707511a").unwrap();
+
+        let jwk = public_key_to_jwk(&public_key, &KeyType::Ed25519);
+
+        assert_eq!(jwk["kty"], "OKP");
+        assert_eq!(jwk["crv"], "Ed25519");
+        assert_eq!(jwk["x"], "11qYAYKxCrfVS_7TyWQHOg7hcvPapiMlrwIaaPcHURo");
+        assert_eq!(public_key_from_jwk(&jwk).unwrap(), (KeyType::Ed25519, public_key));
+    }
+
+    /// Deterministic pseudo-random bytes
+    fn synthetic_stream(len: usize) -> Vec<u8> {
+        let mut state = 0x2545_f491_4f6c_dd1du64;
+        (0..len)
+            .map(|_| {
+                state ^= state << 13;
+                state ^= state >> 7;
+                state ^= state << 17;
+                state as u8
+            })
+            .collect()
+    }
+
+    #[test]
+    fn streaming_hash_of_uneven_chunks_matches_one_shot() {
+        let data = synthetic_stream(4 * 1024 * 1024 + 17);
+        let mut hasher = StreamingHasher::new();
+        let mut offset = 0;
+        for size in [1, 63, 64, 65, 4096, 65_537].iter().cycle() {
+            if offset == data.len() {
+                break;
+            }
+            let end = (offset + size).min(data.len());
+            hasher.update(&data[offset..end]);
+            offset = end;
+        }
+
+        assert_eq!(hasher.bytes_hashed(), data.len() as u64);
+        assert_eq!(hasher.finalize(), hash_data(&data));
+        assert_eq!(StreamingHasher::hash_reader(data.as_slice()).unwrap(), hash_data(&data));
+        assert_eq!(StreamingHasher::new().finalize(), hash_data(&[]));
+    }
+}
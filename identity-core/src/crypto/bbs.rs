@@ -0,0 +1,410 @@
+//! BBS+ signatures over BLS12-381 with zero-knowledge selective disclosure proofs
+//!
+//! Signatures are made with a `Bls12381G2` key pair over an ordered list of messages.
+//! A holder can derive a proof revealing only chosen messages; the proof is
+//! randomized, so two proofs from the same signature cannot be linked.
+
+use std::collections::BTreeMap;
+use bls12_381::{pairing, G1Affine, G1Projective, G2Affine, G2Projective, Scalar};
+use ff::Field;
+use group::Curve;
+use rand::rngs::OsRng;
+use sha2::{Digest, Sha512};
+use crate::error::IdentityError;
+
+/// Domain separation tag for message generators
+const GENERATOR_DST: &[u8] = b"BBS_BLS12381G1_XMD:SHA-512_GENERATORS_";
+
+/// Domain separation tag for the proof challenge
+const CHALLENGE_DST: &[u8] = b"BBS_BLS12381G1_XMD:SHA-512_CHALLENGE_";
+
+const G1_LENGTH: usize = 48;
+const G2_LENGTH: usize = 96;
+const SCALAR_LENGTH: usize = 32;
+
+/// Encoded signature length: A, e, s
+pub const BBS_SIGNATURE_LENGTH: usize = G1_LENGTH + 2 * SCALAR_LENGTH;
+
+/// Public generators for signing a fixed number of messages
+#[derive(Debug, Clone)]
+pub struct BbsGenerators {
+    pub h0: G1Projective,
+    pub h: Vec<G1Projective>,
+}
+
+/// BBS+ signature `(A, e, s)` with `A = (g1 · h0^s · Π hᵢ^mᵢ)^(1/(x+e))`
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct BbsSignature {
+    pub a: G1Affine,
+    pub e: Scalar,
+    pub s: Scalar,
+}
+
+impl BbsGenerators {
+    /// Derive generators for `count` messages; nobody knows their discrete logs
+    pub fn new(count: usize) -> Self {
+        Self {
+            h0: hash_to_g1(0),
+            h: (1..=count as u32).map(hash_to_g1).collect(),
+        }
+    }
+
+    /// Commitment `g1 · h0^s · Π hᵢ^mᵢ` to the messages
+    fn commit(&self, messages: &[Scalar], s: &Scalar) -> G1Projective {
+        self.h.iter()
+            .zip(messages)
+            .fold(G1Projective::generator() + self.h0 * s, |acc, (h, m)| acc + h * m)
+    }
+}
+
+impl BbsSignature {
+    /// Encode as `A || e || s`
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = Vec::with_capacity(BBS_SIGNATURE_LENGTH);
+        bytes.extend_from_slice(&self.a.to_compressed());
+        bytes.extend_from_slice(&self.e.to_bytes());
+        bytes.extend_from_slice(&self.s.to_bytes());
+        bytes
+    }
+
+    /// Decode from `A || e || s`
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, IdentityError> {
+        if bytes.len() != BBS_SIGNATURE_LENGTH {
+            return Err(IdentityError::SignatureError(format!(
+                "BBS+ signature must be {} bytes", BBS_SIGNATURE_LENGTH
+            )));
+        }
+
+        let mut reader = Reader::new(bytes);
+        Ok(Self {
+            a: reader.g1()?,
+            e: reader.scalar()?,
+            s: reader.scalar()?,
+        })
+    }
+}
+
+/// Map a message to a scalar
+pub fn message_to_scalar(message: &[u8]) -> Scalar {
+    hash_to_scalar(&[b"BBS_MESSAGE_", message])
+}
+
+/// Sign an ordered list of messages with a BLS12-381 G2 private key
+pub fn bbs_sign(messages: &[Vec<u8>], private_key: &[u8]) -> Result<Vec<u8>, IdentityError> {
+    let x = decode_private_key(private_key)?;
+    let generators = BbsGenerators::new(messages.len());
+    let scalars: Vec<Scalar> = messages.iter().map(|m| message_to_scalar(m)).collect();
+
+    loop {
+        let e = Scalar::random(&mut OsRng);
+        let s = Scalar::random(&mut OsRng);
+        let inverse = Option::<Scalar>::from((x + e).invert());
+        if let Some(inverse) = inverse {
+            let a = (generators.commit(&scalars, &s) * inverse).to_affine();
+            return Ok(BbsSignature { a, e, s }.to_bytes());
+        }
+    }
+}
+
+/// Verify a signature over all messages: `e(A, w · g2^e) == e(B, g2)`
+pub fn bbs_verify(messages: &[Vec<u8>], signature: &[u8], public_key: &[u8]) -> Result<bool, IdentityError> {
+    let signature = BbsSignature::from_bytes(signature)?;
+    let w = decode_public_key(public_key)?;
+    let generators = BbsGenerators::new(messages.len());
+    let scalars: Vec<Scalar> = messages.iter().map(|m| message_to_scalar(m)).collect();
+
+    if bool::from(signature.a.is_identity()) {
+        return Ok(false);
+    }
+
+    let b = generators.commit(&scalars, &signature.s).to_affine();
+    let w_e = (G2Projective::from(w) + G2Projective::generator() * signature.e).to_affine();
+
+    Ok(pairing(&signature.a, &w_e) == pairing(&b, &G2Affine::generator()))
+}
+
+/// Derive a zero-knowledge proof of a signature revealing only the messages at `reveal`
+pub fn bbs_derive_proof(
+    messages: &[Vec<u8>],
+    signature: &[u8],
+    reveal: &[usize],
+    nonce: &[u8],
+) -> Result<Vec<u8>, IdentityError> {
+    let signature = BbsSignature::from_bytes(signature)?;
+    let generators = BbsGenerators::new(messages.len());
+    let scalars: Vec<Scalar> = messages.iter().map(|m| message_to_scalar(m)).collect();
+
+    let revealed = revealed_set(reveal, messages.len())?;
+    let hidden: Vec<usize> = (0..messages.len()).filter(|i| !revealed.contains_key(i)).collect();
+
+    let b = generators.commit(&scalars, &signature.s);
+    let r1 = random_nonzero_scalar();
+    let r2 = Scalar::random(&mut OsRng);
+    let r3 = Option::<Scalar>::from(r1.invert())
+        .ok_or_else(|| IdentityError::CryptoError("Degenerate proof randomness".to_string()))?;
+
+    // Randomized signature: A' = A^r1, Ā = A'^-e · B^r1 (= A'^x), d = B^r1 · h0^-r2
+    let a_prime = G1Projective::from(signature.a) * r1;
+    let a_bar = a_prime * (-signature.e) + b * r1;
+    let d = b * r1 - generators.h0 * r2;
+    let s_prime = signature.s - r2 * r3;
+
+    // Schnorr commitments for Ā/d = A'^-e · h0^r2 and g1 · Π_R hᵢ^mᵢ = d^r3 · h0^-s' · Π_H hᵢ^-mᵢ
+    let e_blind = Scalar::random(&mut OsRng);
+    let r2_blind = Scalar::random(&mut OsRng);
+    let r3_blind = Scalar::random(&mut OsRng);
+    let s_blind = Scalar::random(&mut OsRng);
+    let m_blinds: Vec<Scalar> = hidden.iter().map(|_| Scalar::random(&mut OsRng)).collect();
+
+    let t1 = a_prime * (-e_blind) + generators.h0 * r2_blind;
+    let t2 = hidden.iter()
+        .zip(&m_blinds)
+        .fold(d * r3_blind - generators.h0 * s_blind, |acc, (&i, blind)| acc - generators.h[i] * blind);
+
+    let revealed_scalars: BTreeMap<usize, Scalar> = revealed.keys().map(|&i| (i, scalars[i])).collect();
+    let points = [a_prime, a_bar, d, t1, t2].map(|p| p.to_affine());
+    let c = challenge(&points, &revealed_scalars, nonce);
+
+    let mut proof = Vec::new();
+    for point in &points[..3] {
+        proof.extend_from_slice(&point.to_compressed());
+    }
+    proof.extend_from_slice(&c.to_bytes());
+    proof.extend_from_slice(&(e_blind + c * signature.e).to_bytes());
+    proof.extend_from_slice(&(r2_blind + c * r2).to_bytes());
+    proof.extend_from_slice(&(r3_blind + c * r3).to_bytes());
+    proof.extend_from_slice(&(s_blind + c * s_prime).to_bytes());
+    for (&i, blind) in hidden.iter().zip(&m_blinds) {
+        proof.extend_from_slice(&(blind + c * scalars[i]).to_bytes());
+    }
+
+    Ok(proof)
+}
+
+/// Verify a derived proof given the revealed messages by index and the total message count
+pub fn bbs_verify_proof(
+    public_key: &[u8],
+    proof: &[u8],
+    message_count: usize,
+    revealed: &BTreeMap<usize, Vec<u8>>,
+    nonce: &[u8],
+) -> Result<bool, IdentityError> {
+    let w = decode_public_key(public_key)?;
+    if revealed.keys().any(|&i| i >= message_count) {
+        return Err(IdentityError::VerificationError("Revealed message index out of range".to_string()));
+    }
+
+    let hidden: Vec<usize> = (0..message_count).filter(|i| !revealed.contains_key(i)).collect();
+    let expected_length = 3 * G1_LENGTH + (5 + hidden.len()) * SCALAR_LENGTH;
+    if proof.len() != expected_length {
+        return Ok(false);
+    }
+
+    let mut reader = Reader::new(proof);
+    let a_prime = reader.g1()?;
+    let a_bar = reader.g1()?;
+    let d = reader.g1()?;
+    let c = reader.scalar()?;
+    let e_hat = reader.scalar()?;
+    let r2_hat = reader.scalar()?;
+    let r3_hat = reader.scalar()?;
+    let s_hat = reader.scalar()?;
+    let m_hats = hidden.iter().map(|_| reader.scalar()).collect::<Result<Vec<_>, _>>()?;
+
+    if bool::from(a_prime.is_identity()) {
+        return Ok(false);
+    }
+
+    // Ā must equal A'^x
+    if pairing(&a_prime, &w) != pairing(&a_bar, &G2Affine::generator()) {
+        return Ok(false);
+    }
+
+    let generators = BbsGenerators::new(message_count);
+    let revealed_scalars: BTreeMap<usize, Scalar> = revealed.iter()
+        .map(|(&i, m)| (i, message_to_scalar(m)))
+        .collect();
+
+    let a_prime_p = G1Projective::from(a_prime);
+    let d_p = G1Projective::from(d);
+
+    let t1 = a_prime_p * (-e_hat) + generators.h0 * r2_hat - (G1Projective::from(a_bar) - d_p) * c;
+
+    let public_part = revealed_scalars.iter()
+        .fold(G1Projective::generator(), |acc, (&i, m)| acc + generators.h[i] * m);
+    let t2 = hidden.iter()
+        .zip(&m_hats)
+        .fold(d_p * r3_hat - generators.h0 * s_hat, |acc, (&i, m_hat)| acc - generators.h[i] * m_hat)
+        - public_part * c;
+
+    let points = [a_prime, a_bar, d, t1.to_affine(), t2.to_affine()];
+    Ok(challenge(&points, &revealed_scalars, nonce) == c)
+}
+
+/// Fiat-Shamir challenge over the proof commitments, revealed messages and nonce
+fn challenge(points: &[G1Affine; 5], revealed: &BTreeMap<usize, Scalar>, nonce: &[u8]) -> Scalar {
+    let mut hasher = Sha512::new();
+    hasher.update(CHALLENGE_DST);
+    for point in points {
+        hasher.update(point.to_compressed());
+    }
+    hasher.update((revealed.len() as u64).to_be_bytes());
+    for (index, message) in revealed {
+        hasher.update((*index as u64).to_be_bytes());
+        hasher.update(message.to_bytes());
+    }
+    hasher.update((nonce.len() as u64).to_be_bytes());
+    hasher.update(nonce);
+    Scalar::from_bytes_wide(&hasher.finalize().into())
+}
+
+/// Index the revealed messages, rejecting out-of-range or duplicate indices
+fn revealed_set(reveal: &[usize], count: usize) -> Result<BTreeMap<usize, ()>, IdentityError> {
+    let mut revealed = BTreeMap::new();
+    for &index in reveal {
+        if index >= count {
+            return Err(IdentityError::CryptoError(format!("Reveal index {} out of range", index)));
+        }
+        if revealed.insert(index, ()).is_some() {
+            return Err(IdentityError::CryptoError(format!("Reveal index {} repeated", index)));
+        }
+    }
+    Ok(revealed)
+}
+
+/// Hash to a G1 point of unknown discrete logarithm by try-and-increment
+fn hash_to_g1(index: u32) -> G1Projective {
+    let mut counter = 0u32;
+    loop {
+        let digest = Sha512::new()
+            .chain_update(GENERATOR_DST)
+            .chain_update(index.to_be_bytes())
+            .chain_update(counter.to_be_bytes())
+            .finalize();
+
+        let mut x = [0u8; G1_LENGTH];
+        x.copy_from_slice(&digest[..G1_LENGTH]);
+        x[0] = (x[0] & 0x1f) | 0x80 | if digest[G1_LENGTH] & 1 == 1 { 0x20 } else { 0 };
+
+        if let Some(point) = Option::<G1Affine>::from(G1Affine::from_compressed_unchecked(&x)) {
+            let point = G1Projective::from(point).clear_cofactor();
+            if !bool::from(point.is_identity()) {
+                return point;
+            }
+        }
+        counter += 1;
+    }
+}
+
+fn hash_to_scalar(parts: &[&[u8]]) -> Scalar {
+    let mut hasher = Sha512::new();
+    for part in parts {
+        hasher.update(part);
+    }
+    Scalar::from_bytes_wide(&hasher.finalize().into())
+}
+
+fn random_nonzero_scalar() -> Scalar {
+    loop {
+        let scalar = Scalar::random(&mut OsRng);
+        if !bool::from(scalar.is_zero()) {
+            return scalar;
+        }
+    }
+}
+
+fn decode_private_key(bytes: &[u8]) -> Result<Scalar, IdentityError> {
+    let bytes: [u8; SCALAR_LENGTH] = bytes.try_into()
+        .map_err(|_| IdentityError::CryptoError("BBS+ private key must be 32 bytes".to_string()))?;
+    let scalar = Option::<Scalar>::from(Scalar::from_bytes(&bytes))
+        .ok_or_else(|| IdentityError::CryptoError("Invalid BBS+ private key".to_string()))?;
+    if bool::from(scalar.is_zero()) {
+        return Err(IdentityError::CryptoError("Invalid BBS+ private key".to_string()));
+    }
+    Ok(scalar)
+}
+
+fn decode_public_key(bytes: &[u8]) -> Result<G2Affine, IdentityError> {
+    let bytes: [u8; G2_LENGTH] = bytes.try_into()
+        .map_err(|_| IdentityError::CryptoError("BBS+ public key must be a compressed G2 point".to_string()))?;
+    let point = Option::<G2Affine>::from(G2Affine::from_compressed(&bytes))
+        .ok_or_else(|| IdentityError::CryptoError("Invalid BBS+ public key".to_string()))?;
+    if bool::from(point.is_identity()) {
+        return Err(IdentityError::CryptoError("Invalid BBS+ public key".to_string()));
+    }
+    Ok(point)
+}
+
+/// Sequential decoder for fixed-width proof and signature fields
+struct Reader<'a> {
+    bytes: &'a [u8],
+}
+
+impl<'a> Reader<'a> {
+    fn new(bytes: &'a [u8]) -> Self {
+        Self { bytes }
+    }
+
+    fn take<const N: usize>(&mut self) -> Result<[u8; N], IdentityError> {
+        if self.bytes.len() < N {
+            return Err(IdentityError::EncodingError("Truncated BBS+ data".to_string()));
+        }
+        let (head, rest) = self.bytes.split_at(N);
+        self.bytes = rest;
+        Ok(head.try_into().expect("length checked"))
+    }
+
+    fn g1(&mut self) -> Result<G1Affine, IdentityError> {
+        let bytes = self.take::<G1_LENGTH>()?;
+        Option::from(G1Affine::from_compressed(&bytes))
+            .ok_or_else(|| IdentityError::EncodingError("Invalid G1 point".to_string()))
+    }
+
+    fn scalar(&mut self) -> Result<Scalar, IdentityError> {
+        let bytes = self.take::<SCALAR_LENGTH>()?;
+        Option::from(Scalar::from_bytes(&bytes))
+            .ok_or_else(|| IdentityError::EncodingError("Invalid scalar".to_string()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::crypto::generate_bls12381_g2_keypair;
+
+    fn messages() -> Vec<Vec<u8>> {
+        ["header", "name", "birthdate", "nationality"].iter().map(|m| m.as_bytes().to_vec()).collect()
+    }
+
+    #[test]
+    fn signature_verifies_only_over_the_signed_messages() {
+        let keypair = generate_bls12381_g2_keypair().unwrap();
+        let signature = bbs_sign(&messages(), &keypair.private_key).unwrap();
+        assert_eq!(signature.len(), BBS_SIGNATURE_LENGTH);
+
+        assert!(bbs_verify(&messages(), &signature, &keypair.public_key).unwrap());
+        let mut altered = messages();
+        altered[2] = b"other birthdate".to_vec();
+        assert!(!bbs_verify(&altered, &signature, &keypair.public_key).unwrap());
+    }
+
+    #[test]
+    fn derived_proof_verifies_and_rejects_tampering() {
+        let keypair = generate_bls12381_g2_keypair().unwrap();
+        let messages = messages();
+        let signature = bbs_sign(&messages, &keypair.private_key).unwrap();
+        let proof = bbs_derive_proof(&messages, &signature, &[0, 3], b"nonce").unwrap();
+        let revealed: BTreeMap<usize, Vec<u8>> = [0, 3].into_iter().map(|i| (i, messages[i].clone())).collect();
+
+        assert!(bbs_verify_proof(&keypair.public_key, &proof, messages.len(), &revealed, b"nonce").unwrap());
+        assert!(!bbs_verify_proof(&keypair.public_key, &proof, messages.len(), &revealed, b"other nonce").unwrap());
+
+        let mut tampered = proof.clone();
+        let last = tampered.len() - 1;
+        tampered[last] ^= 1;
+        assert!(!matches!(bbs_verify_proof(&keypair.public_key, &tampered, messages.len(), &revealed, b"nonce"), Ok(true)));
+
+        assert!(bbs_derive_proof(&messages, &signature, &[4], b"nonce").is_err());
+        assert!(bbs_derive_proof(&messages, &signature, &[1, 1], b"nonce").is_err());
+    }
+}
@@ -0,0 +1,139 @@
+//! Multibase and multicodec encoding for public keys
+
+use base64::{Engine as _, engine::general_purpose::URL_SAFE_NO_PAD};
+use crate::crypto::KeyType;
+use crate::error::IdentityError;
+
+/// Multibase prefix for base58btc
+pub const MULTIBASE_BASE58BTC: char = 'z';
+
+/// Multibase prefix for base64url without padding
+pub const MULTIBASE_BASE64URL: char = 'u';
+
+/// Get the varint-encoded multicodec prefix for a key type
+pub fn multicodec_prefix(key_type: &KeyType) -> [u8; 2] {
+    match key_type {
+        KeyType::Ed25519 => [0xed, 0x01],
+        KeyType::Secp256k1 => [0xe7, 0x01],
+        KeyType::Bls12381G1 => [0xea, 0x01],
+        KeyType::Bls12381G2 => [0xeb, 0x01],
+    }
+}
+
+/// Determine the key type from a multicodec-prefixed key
+pub fn key_type_from_multicodec(bytes: &[u8]) -> Result<(KeyType, &[u8]), IdentityError> {
+    if bytes.len() < 2 {
+        return Err(IdentityError::EncodingError("Multicodec value too short".to_string()));
+    }
+
+    let key_type = match [bytes[0], bytes[1]] {
+        [0xed, 0x01] => KeyType::Ed25519,
+        [0xe7, 0x01] => KeyType::Secp256k1,
+        [0xea, 0x01] => KeyType::Bls12381G1,
+        [0xeb, 0x01] => KeyType::Bls12381G2,
+        prefix => {
+            return Err(IdentityError::EncodingError(format!(
+                "Unsupported multicodec prefix: 0x{}",
+                hex::encode(prefix)
+            )))
+        }
+    };
+
+    Ok((key_type, &bytes[2..]))
+}
+
+/// Encode bytes as a base58btc multibase string
+pub fn encode_multibase(data: &[u8]) -> String {
+    format!("{}{}", MULTIBASE_BASE58BTC, bs58::encode(data).into_string())
+}
+
+/// Decode a multibase string (base58btc or base64url)
+pub fn decode_multibase(encoded: &str) -> Result<Vec<u8>, IdentityError> {
+    let mut chars = encoded.chars();
+    let prefix = chars.next()
+        .ok_or_else(|| IdentityError::EncodingError("Empty multibase string".to_string()))?;
+    let body = chars.as_str();
+
+    match prefix {
+        MULTIBASE_BASE58BTC => bs58::decode(body).into_vec()
+            .map_err(|e| IdentityError::EncodingError(format!("Invalid base58btc: {}", e))),
+        MULTIBASE_BASE64URL => decode_base64url(body),
+        other => Err(IdentityError::EncodingError(format!("Unsupported multibase prefix: {}", other))),
+    }
+}
+
+/// Encode a public key as a multicodec-prefixed multibase string
+pub fn encode_multikey(public_key: &[u8], key_type: &KeyType) -> String {
+    let mut bytes = multicodec_prefix(key_type).to_vec();
+    bytes.extend_from_slice(public_key);
+    encode_multibase(&bytes)
+}
+
+/// Decode a multicodec-prefixed multibase public key
+pub fn decode_multikey(encoded: &str) -> Result<(KeyType, Vec<u8>), IdentityError> {
+    let bytes = decode_multibase(encoded)?;
+    let (key_type, key) = key_type_from_multicodec(&bytes)?;
+    Ok((key_type, key.to_vec()))
+}
+
+/// Encode bytes as unpadded base64url
+pub fn encode_base64url(data: &[u8]) -> String {
+    URL_SAFE_NO_PAD.encode(data)
+}
+
+/// Decode unpadded base64url
+pub fn decode_base64url(encoded: &str) -> Result<Vec<u8>, IdentityError> {
+    URL_SAFE_NO_PAD.decode(encoded)
+        .map_err(|e| IdentityError::EncodingError(format!("Invalid base64url: {}", e)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const KEY_TYPES: [KeyType; 4] = [
+        KeyType::Ed25519,
+        KeyType::Secp256k1,
+        KeyType::Bls12381G1,
+        KeyType::Bls12381G2,
+    ];
+
+    #[test]
+    fn test_multicodec_prefixes() {
+        assert_eq!(multicodec_prefix(&KeyType::Ed25519), [0xed, 0x01]);
+        assert_eq!(multicodec_prefix(&KeyType::Secp256k1), [0xe7, 0x01]);
+        assert_eq!(multicodec_prefix(&KeyType::Bls12381G1), [0xea, 0x01]);
+        assert_eq!(multicodec_prefix(&KeyType::Bls12381G2), [0xeb, 0x01]);
+    }
+
+    #[test]
+    fn test_multikey_round_trip_for_every_key_type() {
+        let public_key: Vec<u8> = (0u8..48).collect();
+
+        for key_type in KEY_TYPES {
+            let encoded = encode_multikey(&public_key, &key_type);
+            assert!(encoded.starts_with(MULTIBASE_BASE58BTC));
+
+            let (decoded_type, decoded_key) = decode_multikey(&encoded).unwrap();
+            assert_eq!(decoded_type, key_type);
+            assert_eq!(decoded_key, public_key);
+        }
+    }
+
+    #[test]
+    fn test_multibase_accepts_base64url() {
+        let data = b"multibase payload";
+        let encoded = format!("{}{}", MULTIBASE_BASE64URL, encode_base64url(data));
+
+        assert_eq!(decode_multibase(&encoded).unwrap(), data);
+        assert_eq!(decode_multibase(&encode_multibase(data)).unwrap(), data);
+    }
+
+    #[test]
+    fn test_rejects_unknown_prefixes() {
+        assert!(decode_multibase("").is_err());
+        assert!(decode_multibase("fdeadbeef").is_err());
+        assert!(key_type_from_multicodec(&[0xed]).is_err());
+        assert!(key_type_from_multicodec(&[0x12, 0x20, 0x00]).is_err());
+    }
+}
@@ -0,0 +1,431 @@
+//! Sign-In With Ethereum (EIP-4361) authentication messages
+
+use chrono::{DateTime, SecondsFormat, Utc};
+use k256::ecdsa::{RecoveryId, Signature, VerifyingKey};
+use sha3::{Digest, Keccak256};
+use crate::error::IdentityError;
+use crate::utils::parse_did;
+
+const PREAMBLE_SUFFIX: &str = " wants you to sign in with your Ethereum account:";
+
+/// Minimum nonce length required by EIP-4361
+const MIN_NONCE_LENGTH: usize = 8;
+
+/// EIP-4361 sign-in message bound to an Ethereum account
+#[derive(Debug, Clone, PartialEq)]
+pub struct SiweMessage {
+    pub domain: String,
+    pub address: String,
+    pub statement: Option<String>,
+    pub uri: String,
+    pub version: String,
+    pub chain_id: u64,
+    pub nonce: String,
+    pub issued_at: DateTime<Utc>,
+    pub expiration_time: Option<DateTime<Utc>>,
+    pub not_before: Option<DateTime<Utc>>,
+    pub request_id: Option<String>,
+    pub resources: Vec<String>,
+}
+
+impl SiweMessage {
+    /// Create a message for the account behind a did:pkh (eip155) or did:ethr DID
+    pub fn new(did: &str, domain: String, uri: String, nonce: String) -> Result<Self, IdentityError> {
+        let (chain_id, address) = account_from_did(did)?;
+        validate_nonce(&nonce)?;
+
+        Ok(Self {
+            domain,
+            address,
+            statement: None,
+            uri,
+            version: "1".to_string(),
+            chain_id,
+            nonce,
+            issued_at: Utc::now(),
+            expiration_time: None,
+            not_before: None,
+            request_id: None,
+            resources: Vec::new(),
+        })
+    }
+
+    /// Set the human-readable statement
+    pub fn with_statement(mut self, statement: String) -> Self {
+        self.statement = Some(statement);
+        self
+    }
+
+    /// Set when the message stops being valid
+    pub fn with_expiration(mut self, expiration_time: DateTime<Utc>) -> Self {
+        self.expiration_time = Some(expiration_time);
+        self
+    }
+
+    /// Set when the message becomes valid
+    pub fn with_not_before(mut self, not_before: DateTime<Utc>) -> Self {
+        self.not_before = Some(not_before);
+        self
+    }
+
+    /// Add a resource the user is authorizing
+    pub fn with_resource(mut self, resource: String) -> Self {
+        self.resources.push(resource);
+        self
+    }
+
+    /// The did:pkh DID of the signing account
+    pub fn did(&self) -> String {
+        format!("did:pkh:eip155:{}:{}", self.chain_id, self.address)
+    }
+
+    /// Parse a message from its EIP-4361 text form
+    pub fn parse(message: &str) -> Result<Self, IdentityError> {
+        let malformed = |reason: &str| IdentityError::InvalidPresentation(format!("Malformed SIWE message: {}", reason));
+        let mut lines = message.split('\n').peekable();
+
+        let domain = lines.next()
+            .and_then(|line| line.strip_suffix(PREAMBLE_SUFFIX))
+            .ok_or_else(|| malformed("missing preamble"))?
+            .to_string();
+        let address = lines.next().ok_or_else(|| malformed("missing address"))?.to_string();
+        address_bytes(&address)?;
+
+        if lines.next() != Some("") {
+            return Err(malformed("expected blank line after address"));
+        }
+
+        let mut statement = None;
+        if let Some(line) = lines.peek() {
+            if !line.starts_with("URI: ") {
+                statement = Some(line.to_string());
+                lines.next();
+                if lines.next() != Some("") {
+                    return Err(malformed("expected blank line after statement"));
+                }
+            }
+        }
+
+        let mut field = |name: &str| -> Result<String, IdentityError> {
+            lines.next()
+                .and_then(|line| line.strip_prefix(name))
+                .and_then(|line| line.strip_prefix(": "))
+                .map(str::to_string)
+                .ok_or_else(|| malformed(&format!("missing {}", name)))
+        };
+
+        let uri = field("URI")?;
+        let version = field("Version")?;
+        if version != "1" {
+            return Err(malformed("unsupported version"));
+        }
+        let chain_id = field("Chain ID")?.parse::<u64>().map_err(|_| malformed("invalid chain ID"))?;
+        let nonce = field("Nonce")?;
+        validate_nonce(&nonce)?;
+        let issued_at = parse_time(&field("Issued At")?)?;
+
+        let mut siwe = Self {
+            domain,
+            address,
+            statement,
+            uri,
+            version,
+            chain_id,
+            nonce,
+            issued_at,
+            expiration_time: None,
+            not_before: None,
+            request_id: None,
+            resources: Vec::new(),
+        };
+
+        while let Some(line) = lines.next() {
+            if let Some(value) = line.strip_prefix("Expiration Time: ") {
+                siwe.expiration_time = Some(parse_time(value)?);
+            } else if let Some(value) = line.strip_prefix("Not Before: ") {
+                siwe.not_before = Some(parse_time(value)?);
+            } else if let Some(value) = line.strip_prefix("Request ID: ") {
+                siwe.request_id = Some(value.to_string());
+            } else if line == "Resources:" {
+                for resource in lines.by_ref() {
+                    let resource = resource.strip_prefix("- ").ok_or_else(|| malformed("invalid resource"))?;
+                    siwe.resources.push(resource.to_string());
+                }
+            } else {
+                return Err(malformed(&format!("unexpected line '{}'", line)));
+            }
+        }
+
+        Ok(siwe)
+    }
+
+    /// Check that a nonce matches the one issued for this sign-in
+    pub fn check_nonce(&self, expected: &str) -> Result<(), IdentityError> {
+        if self.nonce != expected {
+            return Err(IdentityError::VerificationError("SIWE nonce mismatch".to_string()));
+        }
+        Ok(())
+    }
+
+    /// Verify a 65-byte `r || s || v` personal_sign signature over the message.
+    ///
+    /// Fails if the message is outside its validity window; returns whether the
+    /// recovered signer matches the message address.
+    pub fn verify(&self, signature: &[u8]) -> Result<bool, IdentityError> {
+        let now = Utc::now();
+        if self.expiration_time.is_some_and(|expiration| now >= expiration) {
+            return Err(IdentityError::VerificationError("SIWE message has expired".to_string()));
+        }
+        if self.not_before.is_some_and(|not_before| now < not_before) {
+            return Err(IdentityError::VerificationError("SIWE message is not yet valid".to_string()));
+        }
+
+        if signature.len() != 65 {
+            return Err(IdentityError::SignatureError("SIWE signature must be 65 bytes".to_string()));
+        }
+        let recovery_byte = match signature[64] {
+            v @ 0..=1 => v,
+            v @ 27..=28 => v - 27,
+            _ => return Err(IdentityError::SignatureError("Invalid recovery id".to_string())),
+        };
+        let recovery_id = RecoveryId::from_byte(recovery_byte)
+            .ok_or_else(|| IdentityError::SignatureError("Invalid recovery id".to_string()))?;
+        // Zero scalars and high-S (malleated) signatures never verify
+        let signature = match Signature::from_slice(&signature[..64]) {
+            Ok(signature) if signature.normalize_s().is_none() => signature,
+            _ => return Ok(false),
+        };
+
+        let digest = personal_message_hash(self.to_string().as_bytes());
+        let recovered = match VerifyingKey::recover_from_prehash(&digest, &signature, recovery_id) {
+            Ok(key) => key,
+            Err(_) => return Ok(false),
+        };
+
+        Ok(ethereum_address(&recovered) == address_bytes(&self.address)?)
+    }
+}
+
+impl std::fmt::Display for SiweMessage {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "{}{}", self.domain, PREAMBLE_SUFFIX)?;
+        writeln!(f, "{}", self.address)?;
+        writeln!(f)?;
+        if let Some(statement) = &self.statement {
+            writeln!(f, "{}", statement)?;
+            writeln!(f)?;
+        }
+        writeln!(f, "URI: {}", self.uri)?;
+        writeln!(f, "Version: {}", self.version)?;
+        writeln!(f, "Chain ID: {}", self.chain_id)?;
+        writeln!(f, "Nonce: {}", self.nonce)?;
+        write!(f, "Issued At: {}", format_time(&self.issued_at))?;
+        if let Some(expiration) = &self.expiration_time {
+            write!(f, "\nExpiration Time: {}", format_time(expiration))?;
+        }
+        if let Some(not_before) = &self.not_before {
+            write!(f, "\nNot Before: {}", format_time(not_before))?;
+        }
+        if let Some(request_id) = &self.request_id {
+            write!(f, "\nRequest ID: {}", request_id)?;
+        }
+        if !self.resources.is_empty() {
+            write!(f, "\nResources:")?;
+            for resource in &self.resources {
+                write!(f, "\n- {}", resource)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Extract the chain ID and address from a did:pkh (eip155) or did:ethr DID
+fn account_from_did(did: &str) -> Result<(u64, String), IdentityError> {
+    let (_, method, id) = parse_did(did)?;
+    let parts: Vec<&str> = id.split(':').collect();
+
+    let (chain_id, address) = match (method.as_str(), parts.as_slice()) {
+        ("pkh", ["eip155", chain_id, address]) => {
+            let chain_id = chain_id.parse::<u64>()
+                .map_err(|_| IdentityError::InvalidDid(format!("Invalid chain ID in {}", did)))?;
+            (chain_id, *address)
+        }
+        ("ethr", [address]) => (1, *address),
+        ("ethr", [network, address]) => (ethr_chain_id(network)?, *address),
+        _ => return Err(IdentityError::InvalidDid(format!("Not an Ethereum account DID: {}", did))),
+    };
+
+    address_bytes(address)?;
+    Ok((chain_id, address.to_string()))
+}
+
+/// Map a did:ethr network name or hex chain ID to a chain ID
+fn ethr_chain_id(network: &str) -> Result<u64, IdentityError> {
+    match network {
+        "mainnet" => Ok(1),
+        "sepolia" => Ok(11155111),
+        "goerli" => Ok(5),
+        hex if hex.starts_with("0x") => u64::from_str_radix(&hex[2..], 16)
+            .map_err(|_| IdentityError::InvalidDid(format!("Invalid did:ethr network: {}", network))),
+        _ => Err(IdentityError::InvalidDid(format!("Unknown did:ethr network: {}", network))),
+    }
+}
+
+fn validate_nonce(nonce: &str) -> Result<(), IdentityError> {
+    if nonce.len() < MIN_NONCE_LENGTH || !nonce.chars().all(|c| c.is_ascii_alphanumeric()) {
+        return Err(IdentityError::InvalidPresentation(format!(
+            "SIWE nonce must be at least {} alphanumeric characters", MIN_NONCE_LENGTH
+        )));
+    }
+    Ok(())
+}
+
+/// Decode a 0x-prefixed 20-byte address (case-insensitive)
+fn address_bytes(address: &str) -> Result<[u8; 20], IdentityError> {
+    let hex = address.strip_prefix("0x")
+        .ok_or_else(|| IdentityError::InvalidDid(format!("Address must start with 0x: {}", address)))?;
+    let bytes = hex::decode(hex)
+        .map_err(|e| IdentityError::InvalidDid(format!("Invalid address {}: {}", address, e)))?;
+    bytes.try_into()
+        .map_err(|_| IdentityError::InvalidDid(format!("Address must be 20 bytes: {}", address)))
+}
+
+/// Ethereum address of a secp256k1 public key
+fn ethereum_address(key: &VerifyingKey) -> [u8; 20] {
+    let point = key.to_encoded_point(false);
+    let hash = Keccak256::digest(&point.as_bytes()[1..]);
+    let mut address = [0u8; 20];
+    address.copy_from_slice(&hash[12..]);
+    address
+}
+
+/// EIP-191 personal message hash
+fn personal_message_hash(message: &[u8]) -> [u8; 32] {
+    let mut hasher = Keccak256::new();
+    hasher.update(format!("\x19Ethereum Signed Message:\n{}", message.len()).as_bytes());
+    hasher.update(message);
+    hasher.finalize().into()
+}
+
+fn format_time(time: &DateTime<Utc>) -> String {
+    time.to_rfc3339_opts(SecondsFormat::AutoSi, true)
+}
+
+fn parse_time(value: &str) -> Result<DateTime<Utc>, IdentityError> {
+    DateTime::parse_from_rfc3339(value)
+        .map(|time| time.with_timezone(&Utc))
+        .map_err(|e| IdentityError::InvalidPresentation(format!("Invalid SIWE timestamp '{}': {}", value, e)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use k256::ecdsa::SigningKey;
+
+    fn account(seed: u8) -> (SigningKey, String) {
+        let key = SigningKey::from_slice(&[seed; 32]).unwrap();
+        let address = format!("0x{}", hex::encode(ethereum_address(key.verifying_key())));
+        (key, address)
+    }
+
+    /// personal_sign the message, returning `r || s || v` with v in {27, 28}
+    fn sign(key: &SigningKey, message: &SiweMessage) -> Vec<u8> {
+        let digest = personal_message_hash(message.to_string().as_bytes());
+        let (signature, recovery_id) = key.sign_prehash_recoverable(&digest).unwrap();
+        let mut bytes = signature.to_bytes().to_vec();
+        bytes.push(recovery_id.to_byte() + 27);
+        bytes
+    }
+
+    fn message(address: &str) -> SiweMessage {
+        SiweMessage::new(
+            &format!("did:pkh:eip155:1:{}", address),
+            "example.com".to_string(),
+            "https://example.com/login".to_string(),
+            "32891756abcdef".to_string(),
+        ).unwrap()
+    }
+
+    #[test]
+    fn message_round_trips_through_text() {
+        let (_, address) = account(7);
+        let mut message = message(&address)
+            .with_statement("Sign in to Example".to_string())
+            .with_expiration(Utc::now() + chrono::Duration::minutes(10))
+            .with_not_before(Utc::now() - chrono::Duration::minutes(1))
+            .with_resource("ipfs://QmCredential".to_string())
+            .with_resource("https://example.com/profile".to_string());
+        message.request_id = Some("request-1".to_string());
+
+        let text = message.to_string();
+        assert!(text.starts_with("example.com wants you to sign in with your Ethereum account:\n"));
+        assert_eq!(SiweMessage::parse(&text).unwrap(), message);
+
+        let minimal = self::message(&address);
+        assert_eq!(SiweMessage::parse(&minimal.to_string()).unwrap(), minimal);
+        assert_eq!(minimal.did(), format!("did:pkh:eip155:1:{}", address));
+    }
+
+    #[test]
+    fn verifies_correct_signature_and_rejects_others() {
+        let (key, address) = account(7);
+        let (other_key, _) = account(9);
+        let message = message(&address).with_expiration(Utc::now() + chrono::Duration::minutes(10));
+
+        let signature = sign(&key, &message);
+        assert!(message.verify(&signature).unwrap());
+        let mut raw_v = signature.clone();
+        raw_v[64] -= 27;
+        assert!(message.verify(&raw_v).unwrap());
+
+        assert!(!message.verify(&sign(&other_key, &message)).unwrap());
+        let tampered = message.clone().with_resource("https://evil.example".to_string());
+        assert!(!tampered.verify(&signature).unwrap());
+        assert!(message.verify(&signature[..64]).is_err());
+    }
+
+    #[test]
+    fn enforces_nonce_and_validity_window() {
+        let (key, address) = account(7);
+
+        let expired = message(&address).with_expiration(Utc::now() - chrono::Duration::seconds(1));
+        assert!(matches!(expired.verify(&sign(&key, &expired)), Err(IdentityError::VerificationError(_))));
+        let early = message(&address).with_not_before(Utc::now() + chrono::Duration::minutes(5));
+        assert!(matches!(early.verify(&sign(&key, &early)), Err(IdentityError::VerificationError(_))));
+
+        let did = format!("did:pkh:eip155:1:{}", address);
+        assert!(SiweMessage::new(&did, "example.com".to_string(), "https://example.com".to_string(), "short".to_string()).is_err());
+        assert!(message(&address).check_nonce("32891756abcdef").is_ok());
+        assert!(message(&address).check_nonce("0000000000000").is_err());
+    }
+
+    #[test]
+    fn accepts_ethereum_account_dids_only() {
+        let (_, address) = account(7);
+
+        assert_eq!(account_from_did(&format!("did:ethr:{}", address)).unwrap(), (1, address.clone()));
+        assert_eq!(account_from_did(&format!("did:ethr:sepolia:{}", address)).unwrap().0, 11155111);
+        assert_eq!(account_from_did(&format!("did:ethr:0x89:{}", address)).unwrap().0, 137);
+        assert!(account_from_did("did:pkh:eip155:1:0x1234").is_err());
+        assert!(account_from_did(&format!("did:pkh:solana:1:{}", address)).is_err());
+        assert!(account_from_did("did:key:z6MkhaXgBZDvotDkL5257faiztiGiC2QtKLGpbnnEGta2doK").is_err());
+    }
+
+    #[test]
+    fn zero_and_high_s_signatures_do_not_verify() {
+        let (key, address) = account(7);
+        let message = message(&address);
+        let signature = sign(&key, &message);
+
+        let mut zero = vec![0u8; 64];
+        zero.push(27);
+        assert!(!message.verify(&zero).unwrap());
+
+        // (r, n - s) with the flipped recovery id recovers the same key but is malleated
+        let parsed = Signature::from_slice(&signature[..64]).unwrap();
+        let high_s = Signature::from_scalars(parsed.r().to_bytes(), (-*parsed.s().as_ref()).to_bytes()).unwrap();
+        let mut malleated = high_s.to_bytes().to_vec();
+        malleated.push(((signature[64] - 27) ^ 1) + 27);
+        assert!(!message.verify(&malleated).unwrap());
+    }
+}
@@ -0,0 +1,224 @@
+//! Derived credentials carrying predicates computed from another credential's claims
+
+use std::collections::BTreeMap;
+use chrono::{DateTime, NaiveDate, Utc};
+use serde::{Deserialize, Serialize};
+use crate::error::IdentityError;
+use crate::vc::{CredentialType, VerifiableCredential};
+
+/// Type added to credentials produced by derivation
+pub const DERIVED_CREDENTIAL_TYPE: &str = "DerivedCredential";
+
+/// Computes one derived claim from a source claim without disclosing the source value
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(tag = "type", rename_all = "camelCase")]
+pub enum ClaimDerivation {
+    /// `true` when the `YYYY-MM-DD` date in `source` is at least `years` ago
+    AgeOver { source: String, target: String, years: u32 },
+    /// `true` when the country code in `source` is one of `countries` (case-insensitive)
+    CountryInRegion { source: String, target: String, countries: Vec<String> },
+    /// Label of the bucket containing the number in `source`; `bounds` are ascending lower bounds
+    Bucket { source: String, target: String, bounds: Vec<f64> },
+}
+
+/// Reference from a derived credential to the credential it was computed from
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct DerivationSource {
+    pub id: String,
+    pub issuer: String,
+}
+
+impl ClaimDerivation {
+    /// Derive `ageOver{years}` from a date of birth claim
+    pub fn age_over(source: &str, years: u32) -> Self {
+        ClaimDerivation::AgeOver { source: source.to_string(), target: format!("ageOver{}", years), years }
+    }
+
+    /// Derive `countryIn{region}` from a country code claim
+    pub fn country_in_region(source: &str, region: &str, countries: &[&str]) -> Self {
+        ClaimDerivation::CountryInRegion {
+            source: source.to_string(),
+            target: format!("countryIn{}", region),
+            countries: countries.iter().map(|country| country.to_string()).collect(),
+        }
+    }
+
+    /// Derive `{source}Range` as the bucket label, e.g. `"18-25"` or `"65+"`
+    pub fn bucket(source: &str, bounds: Vec<f64>) -> Self {
+        ClaimDerivation::Bucket { source: source.to_string(), target: format!("{}Range", source), bounds }
+    }
+
+    /// Name of the claim this derivation reads
+    pub fn source(&self) -> &str {
+        match self {
+            ClaimDerivation::AgeOver { source, .. }
+            | ClaimDerivation::CountryInRegion { source, .. }
+            | ClaimDerivation::Bucket { source, .. } => source,
+        }
+    }
+
+    /// Name of the claim this derivation produces
+    pub fn target(&self) -> &str {
+        match self {
+            ClaimDerivation::AgeOver { target, .. }
+            | ClaimDerivation::CountryInRegion { target, .. }
+            | ClaimDerivation::Bucket { target, .. } => target,
+        }
+    }
+
+    /// Compute the derived value from a credential's claims as of `now`
+    pub fn derive(&self, claims: &BTreeMap<String, serde_json::Value>, now: DateTime<Utc>) -> Result<serde_json::Value, IdentityError> {
+        let value = claims.get(self.source())
+            .ok_or_else(|| IdentityError::InvalidCredential(format!("Missing source claim: {}", self.source())))?;
+
+        match self {
+            ClaimDerivation::AgeOver { source, years, .. } => {
+                let born = value.as_str()
+                    .and_then(|date| NaiveDate::parse_from_str(date, "%Y-%m-%d").ok())
+                    .ok_or_else(|| IdentityError::InvalidCredential(format!("{} is not a YYYY-MM-DD date", source)))?;
+                let age = now.date_naive().years_since(born).unwrap_or(0);
+                Ok(serde_json::Value::Bool(age >= *years))
+            }
+            ClaimDerivation::CountryInRegion { source, countries, .. } => {
+                let country = value.as_str()
+                    .ok_or_else(|| IdentityError::InvalidCredential(format!("{} is not a country code", source)))?;
+                Ok(serde_json::Value::Bool(countries.iter().any(|c| c.eq_ignore_ascii_case(country))))
+            }
+            ClaimDerivation::Bucket { source, bounds, .. } => {
+                let number = value.as_f64()
+                    .ok_or_else(|| IdentityError::InvalidCredential(format!("{} is not a number", source)))?;
+                bucket_label(number, bounds)
+                    .map(serde_json::Value::String)
+                    .ok_or_else(|| IdentityError::InvalidCredential(format!("{} is below the lowest bucket", source)))
+            }
+        }
+    }
+}
+
+fn bucket_label(number: f64, bounds: &[f64]) -> Option<String> {
+    let index = bounds.iter().rposition(|lower| number >= *lower)?;
+    Some(match bounds.get(index + 1) {
+        Some(upper) => format!("{}-{}", bounds[index], upper),
+        None => format!("{}+", bounds[index]),
+    })
+}
+
+/// Build an unsigned credential from `deriver_did` holding only the derived claims of a verified source.
+///
+/// The result keeps the source's subject and expiration and records the source in `derivedFrom`;
+/// sign it with the holder's or derivation service's key.
+pub fn derive_credential(
+    source: &VerifiableCredential,
+    derivations: &[ClaimDerivation],
+    deriver_did: String,
+) -> Result<VerifiableCredential, IdentityError> {
+    derive_credential_at(source, derivations, deriver_did, Utc::now())
+}
+
+/// Build a derived credential evaluating time-dependent derivations as of `now`
+pub fn derive_credential_at(
+    source: &VerifiableCredential,
+    derivations: &[ClaimDerivation],
+    deriver_did: String,
+    now: DateTime<Utc>,
+) -> Result<VerifiableCredential, IdentityError> {
+    if derivations.is_empty() {
+        return Err(IdentityError::InvalidCredential("No claim derivations given".to_string()));
+    }
+    if source.is_expired_at(now) {
+        return Err(IdentityError::InvalidCredential("Source credential has expired".to_string()));
+    }
+
+    let mut claims = BTreeMap::new();
+    for derivation in derivations {
+        let value = derivation.derive(&source.credential_subject.claims, now)?;
+        if claims.insert(derivation.target().to_string(), value).is_some() {
+            return Err(IdentityError::InvalidCredential(format!("Duplicate derived claim: {}", derivation.target())));
+        }
+    }
+
+    let mut credential = VerifiableCredential::new(deriver_did, source.credential_subject.id.clone(), claims);
+    credential.add_type(CredentialType::Custom(DERIVED_CREDENTIAL_TYPE.to_string()));
+    credential.expiration_date = source.expiration_date;
+    credential.extra.insert("derivedFrom".to_string(), serde_json::to_value(DerivationSource {
+        id: source.id.clone(),
+        issuer: source.get_issuer_did().to_string(),
+    })?);
+    Ok(credential)
+}
+
+impl VerifiableCredential {
+    /// The credential a derived credential was computed from, if any
+    pub fn derived_from(&self) -> Option<DerivationSource> {
+        self.extra.get("derivedFrom").and_then(|value| serde_json::from_value(value.clone()).ok())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+    use crate::crypto::{generate_ed25519_keypair, KeyType};
+
+    fn now() -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(2026, 6, 15, 12, 0, 0).unwrap()
+    }
+
+    fn identity_credential(date_of_birth: &str) -> VerifiableCredential {
+        let mut claims = BTreeMap::new();
+        claims.insert("dateOfBirth".to_string(), serde_json::json!(date_of_birth));
+        claims.insert("country".to_string(), serde_json::json!("de"));
+        claims.insert("income".to_string(), serde_json::json!(42_000));
+        VerifiableCredential::new("did:example:government".to_string(), Some("did:example:alice".to_string()), claims)
+    }
+
+    #[test]
+    fn age_over_18_is_derived_and_verifies_without_the_date_of_birth() {
+        let source = identity_credential("2000-01-31");
+        let service = generate_ed25519_keypair().unwrap();
+
+        let mut derived = derive_credential_at(&source, &[ClaimDerivation::age_over("dateOfBirth", 18)], "did:example:deriver".to_string(), now()).unwrap();
+        derived.sign(&service, "did:example:deriver#key-1".to_string()).unwrap();
+
+        assert!(derived.verify_proof(&service.public_key, &KeyType::Ed25519).unwrap());
+        assert_eq!(derived.credential_subject.claims.get("ageOver18"), Some(&serde_json::json!(true)));
+        assert!(!derived.credential_subject.claims.contains_key("dateOfBirth"));
+        assert_eq!(derived.credential_subject.id.as_deref(), Some("did:example:alice"));
+        assert!(derived.credential_type.contains(&DERIVED_CREDENTIAL_TYPE.to_string()));
+        assert_eq!(derived.derived_from(), Some(DerivationSource { id: source.id.clone(), issuer: "did:example:government".to_string() }));
+    }
+
+    #[test]
+    fn age_threshold_flips_on_the_birthday() {
+        let derivation = ClaimDerivation::age_over("dateOfBirth", 18);
+
+        let turns_18_tomorrow = identity_credential("2008-06-16");
+        assert_eq!(derivation.derive(&turns_18_tomorrow.credential_subject.claims, now()).unwrap(), serde_json::json!(false));
+        let turns_18_today = identity_credential("2008-06-15");
+        assert_eq!(derivation.derive(&turns_18_today.credential_subject.claims, now()).unwrap(), serde_json::json!(true));
+        let malformed = identity_credential("15/06/2008");
+        assert!(derivation.derive(&malformed.credential_subject.claims, now()).is_err());
+    }
+
+    #[test]
+    fn region_and_bucket_derivations() {
+        let claims = identity_credential("2000-01-31").credential_subject.claims;
+
+        assert_eq!(ClaimDerivation::country_in_region("country", "EU", &["DE", "FR"]).derive(&claims, now()).unwrap(), serde_json::json!(true));
+        assert_eq!(ClaimDerivation::country_in_region("country", "NA", &["US", "CA"]).derive(&claims, now()).unwrap(), serde_json::json!(false));
+        assert_eq!(ClaimDerivation::bucket("income", vec![0.0, 30_000.0, 60_000.0]).derive(&claims, now()).unwrap(), serde_json::json!("30000-60000"));
+        assert_eq!(ClaimDerivation::bucket("income", vec![0.0, 30_000.0]).derive(&claims, now()).unwrap(), serde_json::json!("30000+"));
+        assert!(ClaimDerivation::bucket("income", vec![50_000.0]).derive(&claims, now()).is_err());
+    }
+
+    #[test]
+    fn derivation_rejects_expired_sources_and_duplicate_targets() {
+        let mut source = identity_credential("2000-01-31");
+        let age = ClaimDerivation::age_over("dateOfBirth", 18);
+
+        assert!(derive_credential_at(&source, &[age.clone(), age.clone()], "did:example:deriver".to_string(), now()).is_err());
+        assert!(derive_credential_at(&source, &[], "did:example:deriver".to_string(), now()).is_err());
+        source.set_expiration(now() - chrono::Duration::days(1));
+        assert!(derive_credential_at(&source, &[age], "did:example:deriver".to_string(), now()).is_err());
+    }
+}
@@ -1,8 +1,11 @@
 //! DID (Decentralized Identifier) implementation following W3C DID Core specification
 
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::BTreeMap;
 use chrono::{DateTime, Utc};
+use crate::canonicalization::Canonicalization;
+use crate::crypto::encoding::decode_multikey;
+use crate::crypto::{public_key_from_jwk, KeyType};
 use crate::error::IdentityError;
 
 /// DID Document as per W3C DID Core specification
@@ -29,6 +32,9 @@ pub struct DidDocument {
     pub created: Option<DateTime<Utc>>,
     #[serde(rename = "updated", skip_serializing_if = "Option::is_none")]
     pub updated: Option<DateTime<Utc>>,
+    /// Properties not modeled above, preserved across round trips
+    #[serde(flatten)]
+    pub extra: BTreeMap<String, serde_json::Value>,
 }
 
 /// Verification Method for DID Document
@@ -56,7 +62,7 @@ pub enum VerificationRelationship {
 pub enum PublicKeyFormat {
     Jwk {
         #[serde(rename = "publicKeyJwk")]
-        public_key_jwk: HashMap<String, serde_json::Value>
+        public_key_jwk: BTreeMap<String, serde_json::Value>
     },
     Multibase {
         #[serde(rename = "publicKeyMultibase")]
@@ -91,24 +97,68 @@ pub enum ServiceType {
 #[serde(untagged)]
 pub enum ServiceEndpoint {
     Uri(String),
-    Map(HashMap<String, serde_json::Value>),
+    Map(BTreeMap<String, serde_json::Value>),
+}
+
+/// Verification relationship kinds in a DID document
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum RelationshipType {
+    Authentication,
+    AssertionMethod,
+    KeyAgreement,
+    CapabilityInvocation,
+    CapabilityDelegation,
+}
+
+/// Single operation within a DID document patch
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(tag = "op", rename_all = "camelCase")]
+pub enum DidPatchOperation {
+    AddVerificationMethod { method: VerificationMethod },
+    RemoveVerificationMethod { id: String },
+    AddService { service: Service },
+    RemoveService { id: String },
+    AddRelationship { relationship: RelationshipType, entry: VerificationRelationship },
+    RemoveRelationship { relationship: RelationshipType, id: String },
+}
+
+/// Set of changes to apply to a DID document without resending it
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Default)]
+pub struct DidDocumentPatch {
+    pub operations: Vec<DidPatchOperation>,
 }
 
 /// DID Method types
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub enum DidMethod {
     Web,
     Key,
+    Jwk,
     Ethr,
     Ion,
     Custom(String),
 }
 
+impl From<&str> for DidMethod {
+    fn from(method: &str) -> Self {
+        match method {
+            "web" => DidMethod::Web,
+            "key" => DidMethod::Key,
+            "jwk" => DidMethod::Jwk,
+            "ethr" => DidMethod::Ethr,
+            "ion" => DidMethod::Ion,
+            other => DidMethod::Custom(other.to_string()),
+        }
+    }
+}
+
 impl std::fmt::Display for DidMethod {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
             DidMethod::Web => write!(f, "web"),
             DidMethod::Key => write!(f, "key"),
+            DidMethod::Jwk => write!(f, "jwk"),
             DidMethod::Ethr => write!(f, "ethr"),
             DidMethod::Ion => write!(f, "ion"),
             DidMethod::Custom(method) => write!(f, "{}", method),
@@ -116,6 +166,72 @@ impl std::fmt::Display for DidMethod {
     }
 }
 
+impl VerificationMethod {
+    /// Raw public key bytes, whatever format the key is published in
+    pub fn public_key_bytes(&self) -> Result<Vec<u8>, IdentityError> {
+        Ok(self.key_material()?.1)
+    }
+
+    /// Key type and raw public key bytes
+    pub fn key_material(&self) -> Result<(KeyType, Vec<u8>), IdentityError> {
+        match &self.public_key {
+            PublicKeyFormat::Jwk { public_key_jwk } => public_key_from_jwk(public_key_jwk),
+            PublicKeyFormat::Multibase { public_key_multibase } => decode_multikey(public_key_multibase),
+            // Base58 keys carry no codec, so the key type comes from the method type
+            PublicKeyFormat::Base58 { public_key_base58 } if self.method_type.starts_with("Ed25519") => {
+                let key = bs58::decode(public_key_base58).into_vec()
+                    .map_err(|e| IdentityError::EncodingError(format!("Invalid base58 public key: {}", e)))?;
+                Ok((KeyType::Ed25519, key))
+            }
+            PublicKeyFormat::Base58 { .. } => Err(IdentityError::EncodingError(format!(
+                "Cannot determine key type of base58 key with method type {}", self.method_type
+            ))),
+        }
+    }
+}
+
+impl VerificationRelationship {
+    /// Get the verification method ID this relationship refers to
+    pub fn id(&self) -> &str {
+        match self {
+            VerificationRelationship::Reference(id) => id,
+            VerificationRelationship::Embedded(method) => &method.id,
+        }
+    }
+}
+
+impl RelationshipType {
+    /// Parse a proof purpose such as `assertionMethod`
+    pub fn from_purpose(purpose: &str) -> Result<Self, IdentityError> {
+        serde_json::from_value(serde_json::Value::String(purpose.to_string()))
+            .map_err(|_| IdentityError::VerificationError(format!("Unknown proof purpose: {}", purpose)))
+    }
+
+    /// All relationship kinds
+    pub fn all() -> [RelationshipType; 5] {
+        [
+            RelationshipType::Authentication,
+            RelationshipType::AssertionMethod,
+            RelationshipType::KeyAgreement,
+            RelationshipType::CapabilityInvocation,
+            RelationshipType::CapabilityDelegation,
+        ]
+    }
+}
+
+impl DidDocumentPatch {
+    /// Create an empty patch
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add an operation to the patch
+    pub fn with_operation(mut self, operation: DidPatchOperation) -> Self {
+        self.operations.push(operation);
+        self
+    }
+}
+
 impl DidDocument {
     /// Create a new DID Document
     pub fn new(id: String) -> Self {
@@ -134,6 +250,7 @@ impl DidDocument {
             service: None,
             created: Some(Utc::now()),
             updated: None,
+            extra: BTreeMap::new(),
         }
     }
 
@@ -164,6 +281,220 @@ impl DidDocument {
         self.updated = Some(Utc::now());
     }
 
+    /// Apply a patch, committing the changes only if the patched document validates
+    pub fn apply_patch(&mut self, patch: DidDocumentPatch) -> Result<(), IdentityError> {
+        let mut patched = self.clone();
+
+        for operation in patch.operations {
+            match operation {
+                DidPatchOperation::AddVerificationMethod { method } => {
+                    let methods = patched.verification_method.get_or_insert_with(Vec::new);
+                    if methods.iter().any(|m| m.id == method.id) {
+                        return Err(IdentityError::AlreadyExists(format!("Verification method {}", method.id)));
+                    }
+                    methods.push(method);
+                }
+                DidPatchOperation::RemoveVerificationMethod { id } => {
+                    let methods = patched.verification_method.get_or_insert_with(Vec::new);
+                    let before = methods.len();
+                    methods.retain(|m| m.id != id);
+                    if methods.len() == before {
+                        return Err(IdentityError::NotFound(format!("Verification method {}", id)));
+                    }
+                    if methods.is_empty() {
+                        patched.verification_method = None;
+                    }
+
+                    // Drop relationships that referenced the removed method
+                    for relationship in RelationshipType::all() {
+                        if let Some(entries) = patched.relationship_field(relationship) {
+                            entries.retain(|entry| entry.id() != id);
+                        }
+                        patched.clear_empty_relationship(relationship);
+                    }
+                }
+                DidPatchOperation::AddService { service } => {
+                    let services = patched.service.get_or_insert_with(Vec::new);
+                    if services.iter().any(|s| s.id == service.id) {
+                        return Err(IdentityError::AlreadyExists(format!("Service {}", service.id)));
+                    }
+                    services.push(service);
+                }
+                DidPatchOperation::RemoveService { id } => {
+                    let services = patched.service.get_or_insert_with(Vec::new);
+                    let before = services.len();
+                    services.retain(|s| s.id != id);
+                    if services.len() == before {
+                        return Err(IdentityError::NotFound(format!("Service {}", id)));
+                    }
+                    if services.is_empty() {
+                        patched.service = None;
+                    }
+                }
+                DidPatchOperation::AddRelationship { relationship, entry } => {
+                    let entries = patched.relationship_entry(relationship);
+                    if entries.iter().any(|e| e.id() == entry.id()) {
+                        return Err(IdentityError::AlreadyExists(format!("Relationship entry {}", entry.id())));
+                    }
+                    entries.push(entry);
+                }
+                DidPatchOperation::RemoveRelationship { relationship, id } => {
+                    let entries = patched.relationship_entry(relationship);
+                    let before = entries.len();
+                    entries.retain(|e| e.id() != id);
+                    if entries.len() == before {
+                        return Err(IdentityError::NotFound(format!("Relationship entry {}", id)));
+                    }
+                    patched.clear_empty_relationship(relationship);
+                }
+            }
+        }
+
+        patched.validate()?;
+        patched.updated = Some(Utc::now());
+        *self = patched;
+        Ok(())
+    }
+
+    /// Dedupe and sort verification methods, services, and relationships, dropping dangling references.
+    ///
+    /// The first entry with a given id wins. A reference is dangling when it points into this
+    /// document but no verification method has that id; references to other DIDs are kept.
+    pub fn normalize(&mut self) {
+        let did = self.id.clone();
+        let fragment_prefix = format!("{}#", did);
+        let absolute = |id: &str| if id.starts_with('#') { format!("{}{}", did, id) } else { id.to_string() };
+
+        if let Some(methods) = self.verification_method.take() {
+            let methods = dedupe_sorted(methods, |method| absolute(&method.id));
+            self.verification_method = (!methods.is_empty()).then_some(methods);
+        }
+
+        if let Some(services) = self.service.take() {
+            let services = dedupe_sorted(services, |service| absolute(&service.id));
+            self.service = (!services.is_empty()).then_some(services);
+        }
+
+        let defined: Vec<String> = self.verification_method.iter()
+            .flatten()
+            .map(|method| absolute(&method.id))
+            .collect();
+
+        for relationship in RelationshipType::all() {
+            if let Some(entries) = self.relationship_field(relationship).take() {
+                let entries: Vec<_> = entries.into_iter()
+                    .filter(|entry| match entry {
+                        VerificationRelationship::Reference(id) => {
+                            let id = absolute(id);
+                            !id.starts_with(&fragment_prefix) || defined.contains(&id)
+                        }
+                        VerificationRelationship::Embedded(_) => true,
+                    })
+                    .collect();
+                *self.relationship_field(relationship) = Some(dedupe_sorted(entries, |entry| absolute(entry.id())));
+            }
+            self.clear_empty_relationship(relationship);
+        }
+    }
+
+    /// Deterministic serialization of the normalized document
+    pub fn canonical_bytes(&self) -> Result<Vec<u8>, IdentityError> {
+        let mut normalized = self.clone();
+        normalized.normalize();
+        let value = serde_json::to_value(&normalized)?;
+        Canonicalization::Jcs.canonicalize(&value)
+    }
+
+    /// Check a resolved document really belongs to `did`.
+    ///
+    /// The document id must equal `did`, and every verification method, listed or embedded in
+    /// a relationship, must live under `did` and be controlled by `did` or one of `controllers`.
+    pub fn check_integrity(&self, did: &str, controllers: &[String]) -> Result<(), IdentityError> {
+        if self.id != did {
+            return Err(IdentityError::VerificationError(format!(
+                "Resolved document id {} does not match requested DID {}", self.id, did
+            )));
+        }
+
+        let embedded = [
+            &self.authentication,
+            &self.assertion_method,
+            &self.key_agreement,
+            &self.capability_invocation,
+            &self.capability_delegation,
+        ]
+        .into_iter()
+        .flatten()
+        .flatten()
+        .filter_map(|entry| match entry {
+            VerificationRelationship::Embedded(method) => Some(method),
+            VerificationRelationship::Reference(_) => None,
+        });
+
+        for method in self.verification_method.iter().flatten().chain(embedded) {
+            if !method.id.starts_with('#') && !method.id.starts_with(&format!("{}#", did)) {
+                return Err(IdentityError::VerificationError(format!(
+                    "Verification method {} does not belong to {}", method.id, did
+                )));
+            }
+            if method.controller != did && !controllers.contains(&method.controller) {
+                return Err(IdentityError::VerificationError(format!(
+                    "Verification method {} has foreign controller {}", method.id, method.controller
+                )));
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Get a relationship list
+    pub fn relationship(&self, relationship: RelationshipType) -> Option<&Vec<VerificationRelationship>> {
+        match relationship {
+            RelationshipType::Authentication => self.authentication.as_ref(),
+            RelationshipType::AssertionMethod => self.assertion_method.as_ref(),
+            RelationshipType::KeyAgreement => self.key_agreement.as_ref(),
+            RelationshipType::CapabilityInvocation => self.capability_invocation.as_ref(),
+            RelationshipType::CapabilityDelegation => self.capability_delegation.as_ref(),
+        }
+    }
+
+    /// Verification method `method_id` if the document authorizes it for a relationship
+    pub fn authorized_method(&self, relationship: RelationshipType, method_id: &str) -> Option<&VerificationMethod> {
+        let absolute = |id: &str| if id.starts_with('#') { format!("{}{}", self.id, id) } else { id.to_string() };
+        let method_id = absolute(method_id);
+
+        match self.relationship(relationship)?.iter().find(|entry| absolute(entry.id()) == method_id)? {
+            VerificationRelationship::Embedded(method) => Some(method),
+            VerificationRelationship::Reference(_) => self.verification_method.iter()
+                .flatten()
+                .find(|method| absolute(&method.id) == method_id),
+        }
+    }
+
+    /// Get the field holding a relationship list
+    fn relationship_field(&mut self, relationship: RelationshipType) -> &mut Option<Vec<VerificationRelationship>> {
+        match relationship {
+            RelationshipType::Authentication => &mut self.authentication,
+            RelationshipType::AssertionMethod => &mut self.assertion_method,
+            RelationshipType::KeyAgreement => &mut self.key_agreement,
+            RelationshipType::CapabilityInvocation => &mut self.capability_invocation,
+            RelationshipType::CapabilityDelegation => &mut self.capability_delegation,
+        }
+    }
+
+    /// Get a relationship list, creating it if absent
+    fn relationship_entry(&mut self, relationship: RelationshipType) -> &mut Vec<VerificationRelationship> {
+        self.relationship_field(relationship).get_or_insert_with(Vec::new)
+    }
+
+    /// Reset a relationship list to `None` when it has no entries
+    fn clear_empty_relationship(&mut self, relationship: RelationshipType) {
+        let field = self.relationship_field(relationship);
+        if field.as_ref().is_some_and(|entries| entries.is_empty()) {
+            *field = None;
+        }
+    }
+
     /// Validate the DID document structure
     pub fn validate(&self) -> Result<(), IdentityError> {
         // Check if ID is a valid DID
@@ -199,9 +530,202 @@ impl DidDocument {
         match parts[1] {
             "web" => Ok(DidMethod::Web),
             "key" => Ok(DidMethod::Key),
+            "jwk" => Ok(DidMethod::Jwk),
             "ethr" => Ok(DidMethod::Ethr),
             "ion" => Ok(DidMethod::Ion),
             method => Ok(DidMethod::Custom(method.to_string())),
         }
     }
 }
+
+/// Keep the first item for each key and sort by key
+fn dedupe_sorted<T>(items: Vec<T>, key: impl Fn(&T) -> String) -> Vec<T> {
+    let mut unique: BTreeMap<String, T> = BTreeMap::new();
+    for item in items {
+        unique.entry(key(&item)).or_insert(item);
+    }
+    unique.into_values().collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::crypto::KeyType;
+    use crate::utils::create_did_document_with_id;
+
+    fn document() -> DidDocument {
+        create_did_document_with_id("did:example:alice".to_string(), KeyType::Ed25519).unwrap().0
+    }
+
+    fn service(id: &str) -> Service {
+        Service {
+            id: id.to_string(),
+            service_type: ServiceType::Single("LinkedDomains".to_string()),
+            service_endpoint: ServiceEndpoint::Uri("https://alice.example".to_string()),
+        }
+    }
+
+    #[test]
+    fn add_service_patch_produces_a_valid_document() {
+        let mut document = document();
+        let patch = DidDocumentPatch::new()
+            .with_operation(DidPatchOperation::AddService { service: service("did:example:alice#web") });
+
+        document.apply_patch(patch).unwrap();
+
+        assert_eq!(document.service.as_ref().unwrap()[0].id, "did:example:alice#web");
+        assert!(document.updated.is_some());
+        document.validate().unwrap();
+    }
+
+    #[test]
+    fn remove_key_patch_drops_the_key_and_its_relationships() {
+        let mut document = document();
+        let patch = DidDocumentPatch::new()
+            .with_operation(DidPatchOperation::RemoveVerificationMethod { id: "did:example:alice#key-1".to_string() });
+
+        document.apply_patch(patch).unwrap();
+
+        assert!(document.verification_method.is_none());
+        assert!(document.authentication.is_none());
+        document.validate().unwrap();
+    }
+
+    #[test]
+    fn failing_patch_leaves_the_document_unchanged() {
+        let mut document = document();
+        let original = document.clone();
+        let patch = DidDocumentPatch::new()
+            .with_operation(DidPatchOperation::AddService { service: service("did:example:alice#web") })
+            .with_operation(DidPatchOperation::RemoveService { id: "did:example:alice#missing".to_string() });
+
+        assert!(matches!(document.apply_patch(patch), Err(IdentityError::NotFound(_))));
+        assert_eq!(document, original);
+    }
+
+    #[test]
+    fn unknown_fields_survive_a_round_trip() {
+        let mut value = serde_json::to_value(document()).unwrap();
+        value["alsoKnownAs"] = serde_json::json!(["https://alice.example"]);
+
+        let parsed: DidDocument = serde_json::from_value(value.clone()).unwrap();
+        assert_eq!(parsed.extra["alsoKnownAs"], serde_json::json!(["https://alice.example"]));
+        assert_eq!(serde_json::to_value(&parsed).unwrap(), value);
+
+        let canonical = String::from_utf8(parsed.canonical_bytes().unwrap()).unwrap();
+        assert!(canonical.contains("\"alsoKnownAs\":[\"https://alice.example\"]"));
+    }
+
+    #[test]
+    fn documents_with_map_fields_serialize_stably() {
+        let mut document = document();
+        let mut endpoint = BTreeMap::new();
+        for key in ["uri", "accept", "routingKeys"] {
+            endpoint.insert(key.to_string(), serde_json::json!(key));
+        }
+        document.add_service(Service {
+            id: "did:example:alice#didcomm".to_string(),
+            service_type: ServiceType::Single("DIDCommMessaging".to_string()),
+            service_endpoint: ServiceEndpoint::Map(endpoint),
+        });
+        let mut jwk = BTreeMap::new();
+        for (key, value) in [("y", "Y"), ("x", "X"), ("kty", "EC"), ("crv", "P-256")] {
+            jwk.insert(key.to_string(), serde_json::json!(value));
+        }
+        document.add_verification_method(VerificationMethod {
+            id: "did:example:alice#jwk-1".to_string(),
+            method_type: "JsonWebKey2020".to_string(),
+            controller: "did:example:alice".to_string(),
+            public_key: PublicKeyFormat::Jwk { public_key_jwk: jwk },
+        });
+
+        let bytes = serde_json::to_vec(&document).unwrap();
+        let reparsed: DidDocument = serde_json::from_slice(&bytes).unwrap();
+        assert_eq!(serde_json::to_vec(&reparsed).unwrap(), bytes);
+        let text = String::from_utf8(bytes).unwrap();
+        assert!(text.contains(r#"{"crv":"P-256","kty":"EC","x":"X","y":"Y"}"#));
+        assert!(text.contains(r#"{"accept":"accept","routingKeys":"routingKeys","uri":"uri"}"#));
+    }
+
+    fn method(id: &str) -> VerificationMethod {
+        let mut method = document().verification_method.unwrap().remove(0);
+        method.id = id.to_string();
+        method
+    }
+
+    fn reference(id: &str) -> VerificationRelationship {
+        VerificationRelationship::Reference(id.to_string())
+    }
+
+    #[test]
+    fn normalize_dedupes_methods_by_absolute_id() {
+        let mut document = document();
+        let original = document.verification_method.as_ref().unwrap()[0].clone();
+        document.add_verification_method(original.clone());
+        document.add_verification_method(method("#key-1"));
+        document.add_verification_method(method("did:example:alice#key-0"));
+        document.add_authentication(reference("#key-1"));
+
+        document.normalize();
+
+        let ids: Vec<&str> = document.verification_method.iter().flatten().map(|m| m.id.as_str()).collect();
+        assert_eq!(ids, vec!["did:example:alice#key-0", "did:example:alice#key-1"]);
+        assert_eq!(document.verification_method.as_ref().unwrap()[1], original);
+        assert_eq!(document.authentication, Some(vec![reference("did:example:alice#key-1")]));
+    }
+
+    #[test]
+    fn normalize_drops_dangling_references_only() {
+        let mut document = document();
+        let embedded = VerificationRelationship::Embedded(method("did:example:alice#embedded"));
+        document.add_authentication(reference("did:example:alice#missing"));
+        document.add_authentication(reference("#gone"));
+        document.add_authentication(reference("did:example:bob#key-1"));
+        document.add_authentication(embedded.clone());
+        document.assertion_method = Some(vec![reference("did:example:alice#missing")]);
+
+        document.normalize();
+
+        let authentication = document.authentication.unwrap();
+        assert_eq!(authentication.len(), 3);
+        assert!(authentication.contains(&reference("did:example:alice#key-1")));
+        assert!(authentication.contains(&reference("did:example:bob#key-1")));
+        assert!(authentication.contains(&embedded));
+        assert_eq!(document.assertion_method, None);
+    }
+
+    #[test]
+    fn canonical_bytes_ignore_insertion_order_and_duplicates() {
+        let mut forward = document();
+        forward.add_verification_method(method("did:example:alice#key-2"));
+        forward.add_service(service("did:example:alice#a"));
+        forward.add_service(service("did:example:alice#b"));
+
+        let mut backward = forward.clone();
+        backward.verification_method.as_mut().unwrap().reverse();
+        backward.service.as_mut().unwrap().reverse();
+        backward.service.as_mut().unwrap().push(service("did:example:alice#a"));
+
+        assert_ne!(serde_json::to_vec(&forward).unwrap(), serde_json::to_vec(&backward).unwrap());
+        assert_eq!(forward.canonical_bytes().unwrap(), backward.canonical_bytes().unwrap());
+    }
+
+    #[test]
+    fn integrity_covers_embedded_methods_and_listed_controllers() {
+        let mut document = document();
+        assert!(document.check_integrity("did:example:alice", &[]).is_ok());
+        assert!(document.check_integrity("did:example:bob", &[]).is_err());
+
+        let mut delegated = method("did:example:alice#delegated");
+        delegated.controller = "did:example:guardian".to_string();
+        document.add_authentication(VerificationRelationship::Embedded(delegated));
+        assert!(document.check_integrity("did:example:alice", &[]).is_err());
+        assert!(document.check_integrity("did:example:alice", &["did:example:guardian".to_string()]).is_ok());
+
+        document.add_authentication(VerificationRelationship::Embedded(method("did:example:mallory#key-1")));
+        assert!(matches!(
+            document.check_integrity("did:example:alice", &["did:example:guardian".to_string()]),
+            Err(IdentityError::VerificationError(_))
+        ));
+    }
+}
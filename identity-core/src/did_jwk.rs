@@ -0,0 +1,125 @@
+//! did:jwk identifiers embedding a public JWK, resolved without network access
+
+use std::collections::BTreeMap;
+use crate::crypto::encoding::{decode_base64url, encode_base64url};
+use crate::crypto::{public_key_from_jwk, public_key_to_jwk, KeyType};
+use crate::did::{DidDocument, PublicKeyFormat, VerificationMethod, VerificationRelationship};
+use crate::error::IdentityError;
+use crate::utils::parse_did;
+
+/// Verification method type used in resolved did:jwk documents
+pub const DID_JWK_METHOD_TYPE: &str = "JsonWebKey2020";
+
+/// Build a did:jwk DID by base64url-encoding the public key's JWK
+pub fn did_jwk_from_public_key(public_key: &[u8], key_type: &KeyType) -> String {
+    let jwk = serde_json::to_vec(&public_key_to_jwk(public_key, key_type)).unwrap_or_default();
+    format!("did:jwk:{}", encode_base64url(&jwk))
+}
+
+/// Decode the JWK embedded in a did:jwk DID
+pub fn did_jwk_to_jwk(did: &str) -> Result<BTreeMap<String, serde_json::Value>, IdentityError> {
+    let (_, method, method_specific_id) = parse_did(did)?;
+    if method != "jwk" {
+        return Err(IdentityError::InvalidDid(format!("Not a did:jwk DID: {}", did)));
+    }
+
+    let bytes = decode_base64url(&method_specific_id)?;
+    let jwk: BTreeMap<String, serde_json::Value> = serde_json::from_slice(&bytes)
+        .map_err(|e| IdentityError::InvalidDid(format!("did:jwk does not embed a JWK: {}", e)))?;
+
+    if jwk.contains_key("d") {
+        return Err(IdentityError::InvalidDid("did:jwk must not embed a private key".to_string()));
+    }
+    public_key_from_jwk(&jwk)?;
+
+    Ok(jwk)
+}
+
+/// Resolve a did:jwk DID into a document with a single verification method `#0`
+pub fn resolve_did_jwk(did: &str) -> Result<DidDocument, IdentityError> {
+    let jwk = did_jwk_to_jwk(did)?;
+
+    let mut did_doc = DidDocument::new(did.to_string());
+    did_doc.context = vec![
+        "https://www.w3.org/ns/did/v1".to_string(),
+        "https://w3id.org/security/suites/jws-2020/v1".to_string(),
+    ];
+
+    let vm_id = format!("{}#0", did);
+    did_doc.add_verification_method(VerificationMethod {
+        id: vm_id.clone(),
+        method_type: DID_JWK_METHOD_TYPE.to_string(),
+        controller: did.to_string(),
+        public_key: PublicKeyFormat::Jwk { public_key_jwk: jwk },
+    });
+
+    let reference = || Some(vec![VerificationRelationship::Reference(vm_id.clone())]);
+    did_doc.authentication = reference();
+    did_doc.assertion_method = reference();
+    did_doc.capability_invocation = reference();
+    did_doc.capability_delegation = reference();
+
+    // The document is derived from the DID alone, so it carries no timestamps
+    did_doc.created = None;
+    did_doc.updated = None;
+
+    Ok(did_doc)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::crypto::{generate_ed25519_keypair, generate_secp256k1_keypair, sign_data, verify_data};
+    use crate::did::DidMethod;
+
+    fn round_trip(public_key: &[u8], key_type: KeyType) {
+        let did = did_jwk_from_public_key(public_key, &key_type);
+        assert!(did.starts_with("did:jwk:"));
+
+        let did_doc = resolve_did_jwk(&did).unwrap();
+        assert_eq!(did_doc.id, did);
+        assert_eq!(did_doc.get_method().unwrap(), DidMethod::Jwk);
+        let methods = did_doc.verification_method.as_ref().unwrap();
+        assert_eq!(methods.len(), 1);
+
+        let method = &methods[0];
+        assert_eq!(method.id, format!("{}#0", did));
+        assert_eq!(method.controller, did);
+        assert_eq!(method.key_material().unwrap(), (key_type, public_key.to_vec()));
+    }
+
+    #[test]
+    fn ed25519_did_jwk_round_trips() {
+        let keypair = generate_ed25519_keypair().unwrap();
+        round_trip(&keypair.public_key, KeyType::Ed25519);
+    }
+
+    #[test]
+    fn secp256k1_did_jwk_round_trips() {
+        let keypair = generate_secp256k1_keypair().unwrap();
+        round_trip(&keypair.public_key, KeyType::Secp256k1);
+
+        // The resolved key verifies signatures from the original keypair
+        let did_doc = resolve_did_jwk(&did_jwk_from_public_key(&keypair.public_key, &keypair.key_type)).unwrap();
+        let (key_type, public_key) = did_doc.verification_method.unwrap()[0].key_material().unwrap();
+        let signature = sign_data(b"payload", &keypair.private_key, &keypair.key_type).unwrap();
+        assert!(verify_data(b"payload", &signature, &public_key, &key_type).unwrap());
+    }
+
+    #[test]
+    fn rejects_foreign_methods_and_malformed_payloads() {
+        assert!(resolve_did_jwk("did:key:z6MkhaXgBZDvotDkL5257faiztiGiC2QtKLGpbnnEGta2doK").is_err());
+        assert!(resolve_did_jwk("did:jwk:not-base64!").is_err());
+        assert!(resolve_did_jwk(&format!("did:jwk:{}", encode_base64url(b"{\"kty\":\"unknown\"}"))).is_err());
+    }
+
+    #[test]
+    fn rejects_embedded_private_keys() {
+        let keypair = generate_ed25519_keypair().unwrap();
+        let mut jwk = public_key_to_jwk(&keypair.public_key, &keypair.key_type);
+        jwk.insert("d".to_string(), serde_json::json!(encode_base64url(&keypair.private_key)));
+        let did = format!("did:jwk:{}", encode_base64url(&serde_json::to_vec(&jwk).unwrap()));
+
+        assert!(resolve_did_jwk(&did).is_err());
+    }
+}
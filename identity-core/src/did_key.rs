@@ -0,0 +1,245 @@
+//! did:key identifiers embedding a multikey, resolved without network access
+
+use curve25519_dalek::edwards::CompressedEdwardsY;
+use crate::crypto::encoding::{decode_multikey, encode_multibase, encode_multikey, MULTIBASE_BASE58BTC};
+use crate::crypto::KeyType;
+use crate::did::{DidDocument, PublicKeyFormat, VerificationMethod, VerificationRelationship};
+use crate::error::IdentityError;
+use crate::utils::parse_did;
+
+/// Verification method type of the derived key agreement key
+pub const X25519_KEY_AGREEMENT_TYPE: &str = "X25519KeyAgreementKey2020";
+
+/// Varint-encoded multicodec prefix for X25519 public keys
+pub const X25519_MULTICODEC: [u8; 2] = [0xec, 0x01];
+
+/// Build a did:key DID from a public key
+pub fn did_key_from_public_key(public_key: &[u8], key_type: &KeyType) -> String {
+    format!("did:key:{}", encode_multikey(public_key, key_type))
+}
+
+/// Build the did:key document for a public key, including the derived key agreement key for Ed25519
+pub fn did_key_document(public_key: &[u8], key_type: &KeyType) -> Result<DidDocument, IdentityError> {
+    resolve_did_key(&did_key_from_public_key(public_key, key_type))
+}
+
+/// Convert an Ed25519 public key to the X25519 key with the same secret, as the did:key spec prescribes
+pub fn ed25519_to_x25519(public_key: &[u8]) -> Result<[u8; 32], IdentityError> {
+    let point = CompressedEdwardsY::from_slice(public_key)
+        .ok()
+        .and_then(|compressed| compressed.decompress())
+        .ok_or_else(|| IdentityError::InvalidDid("Public key is not a valid Ed25519 point".to_string()))?;
+
+    if point.is_small_order() {
+        return Err(IdentityError::InvalidDid("Ed25519 public key has small order".to_string()));
+    }
+    Ok(point.to_montgomery().to_bytes())
+}
+
+/// Derive the X25519 private key matching `ed25519_to_x25519` from an Ed25519 seed
+pub fn ed25519_private_to_x25519(private_key: &[u8]) -> Result<[u8; 32], IdentityError> {
+    let seed: [u8; 32] = private_key.try_into()
+        .map_err(|_| IdentityError::CryptoError("Invalid private key: expected a 32-byte Ed25519 seed".to_string()))?;
+    Ok(ed25519_dalek::SigningKey::from_bytes(&seed).to_scalar_bytes())
+}
+
+/// Resolve a did:key DID into a document whose signing method is `#<multibase>`.
+///
+/// Ed25519 keys also get an X25519 `keyAgreement` method derived from the signing key. Malformed multibase,
+/// unsupported multicodec prefixes, keys of the wrong length and Ed25519 keys that are not curve points are
+/// `InvalidDid` errors.
+pub fn resolve_did_key(did: &str) -> Result<DidDocument, IdentityError> {
+    let (_, method, multibase) = parse_did(did)?;
+    if method != "key" {
+        return Err(IdentityError::InvalidDid(format!("Not a did:key DID: {}", did)));
+    }
+    if !multibase.starts_with(MULTIBASE_BASE58BTC) {
+        return Err(IdentityError::InvalidDid("did:key must use base58btc multibase".to_string()));
+    }
+    let (key_type, public_key) = decode_multikey(&multibase)
+        .map_err(|e| IdentityError::InvalidDid(format!("Invalid did:key public key: {}", e)))?;
+    if public_key.len() != key_type.public_key_length() {
+        return Err(IdentityError::InvalidDid(format!(
+            "did:key {} public key is {} bytes, expected {}", key_type, public_key.len(), key_type.public_key_length()
+        )));
+    }
+
+    let mut did_doc = DidDocument::new(did.to_string());
+    did_doc.context = vec!["https://www.w3.org/ns/did/v1".to_string()];
+
+    let vm_id = format!("{}#{}", did, multibase);
+    did_doc.add_verification_method(VerificationMethod {
+        id: vm_id.clone(),
+        method_type: key_type.to_string(),
+        controller: did.to_string(),
+        public_key: PublicKeyFormat::Multibase { public_key_multibase: multibase.clone() },
+    });
+
+    let reference = || Some(vec![VerificationRelationship::Reference(vm_id.clone())]);
+    did_doc.authentication = reference();
+    did_doc.assertion_method = reference();
+    did_doc.capability_invocation = reference();
+    did_doc.capability_delegation = reference();
+
+    if key_type == KeyType::Ed25519 {
+        did_doc.context.push("https://w3id.org/security/suites/ed25519-2020/v1".to_string());
+    }
+
+    if key_type == KeyType::Ed25519 {
+        did_doc.context.push("https://w3id.org/security/suites/x25519-2020/v1".to_string());
+
+        let mut x25519 = X25519_MULTICODEC.to_vec();
+        x25519.extend_from_slice(&ed25519_to_x25519(&public_key)?);
+        let x25519_multibase = encode_multibase(&x25519);
+
+        let ka_id = format!("{}#{}", did, x25519_multibase);
+        did_doc.add_verification_method(VerificationMethod {
+            id: ka_id.clone(),
+            method_type: X25519_KEY_AGREEMENT_TYPE.to_string(),
+            controller: did.to_string(),
+            public_key: PublicKeyFormat::Multibase { public_key_multibase: x25519_multibase },
+        });
+        did_doc.key_agreement = Some(vec![VerificationRelationship::Reference(ka_id)]);
+    }
+
+    // The document is derived from the DID alone, so it carries no timestamps
+    did_doc.created = None;
+    did_doc.updated = None;
+
+    Ok(did_doc)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::crypto::generate_ed25519_keypair;
+    use crate::did::RelationshipType;
+
+    #[test]
+    fn ed25519_did_key_has_signing_and_key_agreement_methods() {
+        let public_key = generate_ed25519_keypair().unwrap().public_key;
+        let did = did_key_from_public_key(&public_key, &KeyType::Ed25519);
+        let document = resolve_did_key(&did).unwrap();
+
+        let methods = document.verification_method.as_ref().unwrap();
+        assert_eq!(methods.len(), 2);
+        let signing = &methods[0];
+        let agreement = &methods[1];
+        assert_eq!(signing.key_material().unwrap(), (KeyType::Ed25519, public_key.clone()));
+        assert_eq!(agreement.method_type, X25519_KEY_AGREEMENT_TYPE);
+
+        for relationship in [RelationshipType::Authentication, RelationshipType::AssertionMethod] {
+            assert!(document.authorized_method(relationship, &signing.id).is_some());
+            assert!(document.authorized_method(relationship, &agreement.id).is_none());
+        }
+        assert!(document.authorized_method(RelationshipType::KeyAgreement, &agreement.id).is_some());
+        assert!(document.authorized_method(RelationshipType::KeyAgreement, &signing.id).is_none());
+
+        let PublicKeyFormat::Multibase { public_key_multibase } = &agreement.public_key else {
+            panic!("key agreement key is not multibase");
+        };
+        let mut expected = X25519_MULTICODEC.to_vec();
+        expected.extend_from_slice(&ed25519_to_x25519(&public_key).unwrap());
+        assert_eq!(public_key_multibase, &encode_multibase(&expected));
+    }
+
+    #[test]
+    fn every_generated_ed25519_key_has_a_usable_key_agreement_key() {
+        for _ in 0..32 {
+            let keypair = generate_ed25519_keypair().unwrap();
+            let document = did_key_document(&keypair.public_key, &keypair.key_type).unwrap();
+            assert!(document.authentication.is_some());
+            assert!(document.key_agreement.is_some());
+
+            // The holder derives the matching X25519 secret from the Ed25519 seed
+            let secret = ed25519_private_to_x25519(&keypair.private_key).unwrap();
+            assert_eq!(
+                curve25519_dalek::MontgomeryPoint::mul_base_clamped(secret).to_bytes(),
+                ed25519_to_x25519(&keypair.public_key).unwrap()
+            );
+        }
+    }
+
+    #[test]
+    fn ed25519_key_that_is_not_a_curve_point_is_invalid() {
+        // y = 2 has no x on the curve
+        let mut not_a_point = [0u8; 32];
+        not_a_point[0] = 2;
+
+        assert!(CompressedEdwardsY(not_a_point).decompress().is_none());
+        assert!(matches!(did_key_document(&not_a_point, &KeyType::Ed25519), Err(IdentityError::InvalidDid(_))));
+    }
+
+    #[test]
+    fn secp256k1_did_key_has_no_key_agreement() {
+        let keypair = crate::crypto::generate_secp256k1_keypair().unwrap();
+        let document = did_key_document(&keypair.public_key, &keypair.key_type).unwrap();
+
+        assert_eq!(document.verification_method.as_ref().unwrap().len(), 1);
+        assert!(document.key_agreement.is_none());
+    }
+
+    /// Example from the did:key specification
+    const FIXTURE_DID: &str = "did:key:z6MkhaXgBZDvotDkL5257faiztiGiC2QtKLGpbnnEGta2doK";
+
+    #[test]
+    fn known_ed25519_fixture_resolves() {
+        let document = resolve_did_key(FIXTURE_DID).unwrap();
+
+        assert_eq!(document.id, FIXTURE_DID);
+        let methods = document.verification_method.as_ref().unwrap();
+        assert_eq!(methods[0].id, format!("{}#z6MkhaXgBZDvotDkL5257faiztiGiC2QtKLGpbnnEGta2doK", FIXTURE_DID));
+        assert_eq!(methods[0].controller, FIXTURE_DID);
+        assert_eq!(
+            hex::encode(methods[0].key_material().unwrap().1),
+            "2e6fcce36701dc791488e0d0b1745cc1e33a4c1c9fcc41c63bd343dbbe0970e6"
+        );
+        assert_eq!(methods[1].id, format!("{}#z6LSj72tK8brWgZja8NLRwPigth2T9QRiG1uH9oKZuKjdh9p", FIXTURE_DID));
+        assert!(document.authorized_method(RelationshipType::AssertionMethod, &methods[0].id).is_some());
+        assert!(document.created.is_none());
+    }
+
+    #[test]
+    fn fixture_key_is_a_genuine_ed25519_key() {
+        let document = resolve_did_key(FIXTURE_DID).unwrap();
+        let (key_type, public_key) = document.verification_method.as_ref().unwrap()[0].key_material().unwrap();
+
+        assert_eq!(key_type, KeyType::Ed25519);
+        assert!(ed25519_dalek::VerifyingKey::from_bytes(&public_key.clone().try_into().unwrap()).is_ok());
+        // A forged signature is rejected rather than failing to parse the key
+        assert!(!crate::crypto::verify_data(b"message", &[1u8; 64], &public_key, &key_type).unwrap());
+    }
+
+    #[test]
+    fn rfc8032_signature_verifies_against_the_resolved_did_key() {
+        // RFC 8032 section 7.1, TEST 1: the empty message
+        let public_key = hex::decode("d75a980182b10ab7d54bfed3c964073a0ee172f3daa62325af021a68f707511a").unwrap();
+        let signature = hex::decode(
+            "e5564300c360ac729086e2cc806e828a84877f1eb8e5d974d873e065224901555fb8821590a33bacc61e39701cf9b46bd25bf5f0595bbe24655141438e7a100b"
+        ).unwrap();
+
+        let did = did_key_from_public_key(&public_key, &KeyType::Ed25519);
+        assert!(did.starts_with("did:key:z6Mk"), "{}", did);
+        let document = resolve_did_key(&did).unwrap();
+        let method = &document.verification_method.as_ref().unwrap()[0];
+        let (key_type, resolved) = method.key_material().unwrap();
+
+        assert!(crate::crypto::verify_data(b"", &signature, &resolved, &key_type).unwrap());
+        assert!(!crate::crypto::verify_data(b"x", &signature, &resolved, &key_type).unwrap());
+    }
+
+    #[test]
+    fn malformed_or_unsupported_did_keys_are_invalid() {
+        let unsupported = format!("did:key:{}", encode_multibase(&[0x12, 0x00, 1, 2, 3]));
+        let truncated = &FIXTURE_DID[..FIXTURE_DID.len() - 4];
+        for did in [
+            "did:key:z0OIl",
+            "did:key:mAQID",
+            unsupported.as_str(),
+            truncated,
+        ] {
+            assert!(matches!(resolve_did_key(did), Err(IdentityError::InvalidDid(_))), "{}", did);
+        }
+        assert!(resolve_did_key("did:web:example.com").is_err());
+    }
+}
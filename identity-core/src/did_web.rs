@@ -0,0 +1,299 @@
+//! did:web identifiers and their web-hosted document layout
+
+use std::path::{Path, PathBuf};
+use percent_encoding::{percent_decode_str, utf8_percent_encode, AsciiSet, CONTROLS};
+use crate::did::DidDocument;
+use crate::error::IdentityError;
+use crate::utils::parse_did;
+
+/// File name of a did:web document
+pub const DID_WEB_FILE_NAME: &str = "did.json";
+
+/// Directory holding the document for a did:web DID without a path
+pub const DID_WEB_WELL_KNOWN: &str = ".well-known";
+
+/// Build a did:web DID from a domain (optionally with port) and an optional path like `sub/path`
+pub fn did_web_from_domain(domain: &str, path: Option<&str>) -> Result<String, IdentityError> {
+    if domain.is_empty() || domain.contains('/') {
+        return Err(IdentityError::InvalidDid(format!("Invalid did:web domain: {}", domain)));
+    }
+
+    let mut did = format!("did:web:{}", domain.replace(':', "%3A"));
+
+    if let Some(path) = path {
+        for segment in path.trim_matches('/').split('/') {
+            if segment.is_empty() || segment.contains(':') {
+                return Err(IdentityError::InvalidDid(format!("Invalid did:web path: {}", path)));
+            }
+            did.push(':');
+            did.extend(utf8_percent_encode(segment, URL_PATH_SEGMENT));
+        }
+    }
+
+    Ok(did)
+}
+
+/// Characters escaped when a decoded path segment is put back into a URL path
+const URL_PATH_SEGMENT: &AsciiSet = &CONTROLS.add(b' ').add(b'"').add(b'#').add(b'%').add(b'/').add(b'<').add(b'>').add(b'?').add(b'`').add(b'{').add(b'}');
+
+/// Percent-decode one component of a did:web method-specific id
+fn percent_decode_component(component: &str, did: &str) -> Result<String, IdentityError> {
+    percent_decode_str(component)
+        .decode_utf8()
+        .map(|decoded| decoded.into_owned())
+        .map_err(|_| IdentityError::InvalidDid(format!("Invalid percent-encoding in did:web DID: {}", did)))
+}
+
+/// Split a did:web DID into its decoded host and path segments
+fn did_web_parts(did: &str) -> Result<(String, Vec<String>), IdentityError> {
+    let (_, method, method_specific_id) = parse_did(did)?;
+    if method != "web" {
+        return Err(IdentityError::InvalidDid(format!("Not a did:web DID: {}", did)));
+    }
+
+    let mut segments = method_specific_id.split(':');
+    let host = segments.next()
+        .filter(|host| !host.is_empty())
+        .ok_or_else(|| IdentityError::InvalidDid(format!("did:web DID has no domain: {}", did)))?;
+    // Checked after decoding so an escaped separator cannot smuggle in a path or user info
+    let host = percent_decode_component(host, did)?;
+    if host.chars().any(|c| c.is_whitespace() || c.is_control() || "/\\@?#".contains(c)) {
+        return Err(IdentityError::InvalidDid(format!("Invalid did:web domain: {}", did)));
+    }
+
+    let path = segments
+        .map(|segment| percent_decode_component(segment, did))
+        .collect::<Result<Vec<_>, _>>()?;
+    if path.iter().any(|segment| {
+        segment.is_empty() || segment == "." || segment == ".." || segment.contains(['/', '\\', '\0'])
+    }) {
+        return Err(IdentityError::InvalidDid(format!("Invalid did:web path: {}", did)));
+    }
+
+    Ok((host, path))
+}
+
+/// Path of a did:web document relative to the web root
+pub fn did_web_document_path(did: &str) -> Result<PathBuf, IdentityError> {
+    let (_, segments) = did_web_parts(did)?;
+
+    let mut path = PathBuf::new();
+    if segments.is_empty() {
+        path.push(DID_WEB_WELL_KNOWN);
+    } else {
+        path.extend(segments);
+    }
+    path.push(DID_WEB_FILE_NAME);
+
+    Ok(path)
+}
+
+/// HTTPS URL at which a did:web document is published
+pub fn did_web_url(did: &str) -> Result<String, IdentityError> {
+    let (host, _) = did_web_parts(did)?;
+    let path = did_web_document_path(did)?;
+    let path: Vec<_> = path.iter()
+        .map(|segment| utf8_percent_encode(&segment.to_string_lossy(), URL_PATH_SEGMENT).to_string())
+        .collect();
+    Ok(format!("https://{}/{}", host, path.join("/")))
+}
+
+/// Resolve a did:web DID against documents published under a local web root
+pub fn resolve_did_web_from_dir(did: &str, web_root: &Path) -> Result<DidDocument, IdentityError> {
+    let file = web_root.join(did_web_document_path(did)?);
+    let content = std::fs::read(&file)
+        .map_err(|e| IdentityError::NotFound(format!("{}: {}", file.display(), e)))?;
+
+    let document: DidDocument = serde_json::from_slice(&content)?;
+    if document.id != did {
+        return Err(IdentityError::InvalidDid(format!(
+            "Document at {} is for {}, not {}",
+            file.display(),
+            document.id,
+            did
+        )));
+    }
+
+    document.validate()?;
+    Ok(document)
+}
+
+/// Resolve a did:web DID by fetching its document over HTTPS
+pub async fn resolve_did_web(did: &str) -> Result<DidDocument, IdentityError> {
+    fetch_did_web_document(did, &did_web_url(did)?).await
+}
+
+/// Fetch the document for a did:web DID from `url`, checking it is the DID's own document
+async fn fetch_did_web_document(did: &str, url: &str) -> Result<DidDocument, IdentityError> {
+    let response = reqwest::Client::new()
+        .get(url)
+        .header(reqwest::header::ACCEPT, "application/did+json, application/json")
+        .send()
+        .await
+        .map_err(|e| IdentityError::NetworkError(format!("Failed to fetch {}: {}", url, e)))?;
+
+    let status = response.status();
+    if status == reqwest::StatusCode::NOT_FOUND {
+        return Err(IdentityError::NotFound(format!("No DID document at {}", url)));
+    }
+    if !status.is_success() {
+        return Err(IdentityError::NetworkError(format!("Fetching {} returned {}", url, status)));
+    }
+
+    let content = response.bytes()
+        .await
+        .map_err(|e| IdentityError::NetworkError(format!("Failed to read {}: {}", url, e)))?;
+    let document: DidDocument = serde_json::from_slice(&content)?;
+    if document.id != did {
+        return Err(IdentityError::InvalidDid(format!(
+            "Document at {} is for {}, not {}",
+            url,
+            document.id,
+            did
+        )));
+    }
+
+    document.validate()?;
+    Ok(document)
+}
+
+impl DidDocument {
+    /// Write this did:web document into the directory layout expected under a web root,
+    /// returning the path of the written file
+    pub fn to_did_web_files(&self, web_root: &Path) -> Result<PathBuf, IdentityError> {
+        let file = web_root.join(did_web_document_path(&self.id)?);
+
+        if let Some(dir) = file.parent() {
+            std::fs::create_dir_all(dir)
+                .map_err(|e| IdentityError::StorageError(format!("{}: {}", dir.display(), e)))?;
+        }
+
+        let mut normalized = self.clone();
+        normalized.normalize();
+        let content = serde_json::to_vec_pretty(&normalized)?;
+        std::fs::write(&file, content)
+            .map_err(|e| IdentityError::StorageError(format!("{}: {}", file.display(), e)))?;
+
+        Ok(file)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::crypto::KeyType;
+    use crate::utils::create_did_document_with_id;
+
+    fn web_root() -> PathBuf {
+        std::env::temp_dir().join(format!("did-web-{}", uuid::Uuid::new_v4()))
+    }
+
+    #[test]
+    fn builds_dids_and_document_paths() {
+        assert_eq!(did_web_from_domain("example.com", None).unwrap(), "did:web:example.com");
+        assert_eq!(did_web_from_domain("localhost:8443", Some("/users/alice/")).unwrap(), "did:web:localhost%3A8443:users:alice");
+        assert!(did_web_from_domain("example.com/users", None).is_err());
+        assert!(did_web_from_domain("example.com", Some("users//alice")).is_err());
+
+        assert_eq!(did_web_document_path("did:web:example.com").unwrap(), Path::new(".well-known/did.json"));
+        assert_eq!(did_web_document_path("did:web:example.com:users:alice").unwrap(), Path::new("users/alice/did.json"));
+        assert_eq!(did_web_url("did:web:localhost%3A8443:users:alice").unwrap(), "https://localhost:8443/users/alice/did.json");
+        assert!(did_web_document_path("did:web:example.com:..:etc").is_err());
+        assert!(did_web_document_path("did:key:z6Mk").is_err());
+    }
+
+    #[test]
+    fn host_and_path_segments_are_percent_decoded() {
+        assert_eq!(did_web_url("did:web:localhost%3a8443").unwrap(), "https://localhost:8443/.well-known/did.json");
+        assert_eq!(did_web_document_path("did:web:example.com:user%20a").unwrap(), Path::new("user a/did.json"));
+        assert_eq!(did_web_url("did:web:example.com:user%20a").unwrap(), "https://example.com/user%20a/did.json");
+        assert_eq!(did_web_from_domain("example.com", Some("user a")).unwrap(), "did:web:example.com:user%20a");
+
+        // Traversal and separators are checked on the decoded values
+        assert!(did_web_document_path("did:web:example.com:%2e%2e:etc").is_err());
+        assert!(did_web_document_path("did:web:example.com:users%2F..%2F..").is_err());
+        assert!(did_web_url("did:web:example.com%2Fevil").is_err());
+        assert!(did_web_url("did:web:user%40example.com").is_err());
+        assert!(did_web_document_path("did:web:example.com:%ff").is_err());
+    }
+
+    #[test]
+    fn published_files_resolve_from_the_web_root() {
+        let root = web_root();
+
+        for path in [None, Some("users/alice")] {
+            let did = did_web_from_domain("example.com", path).unwrap();
+            let (document, _) = create_did_document_with_id(did.clone(), KeyType::Ed25519).unwrap();
+
+            let file = document.to_did_web_files(&root).unwrap();
+            assert_eq!(file, root.join(did_web_document_path(&did).unwrap()));
+
+            let resolved = resolve_did_web_from_dir(&did, &root).unwrap();
+            assert_eq!(resolved.id, did);
+            assert_eq!(resolved.verification_method, document.verification_method);
+        }
+
+        std::fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[test]
+    fn resolution_rejects_missing_or_mismatched_documents() {
+        let root = web_root();
+        let (document, _) = create_did_document_with_id("did:web:example.com".to_string(), KeyType::Ed25519).unwrap();
+        document.to_did_web_files(&root).unwrap();
+
+        assert!(matches!(resolve_did_web_from_dir("did:web:example.com:bob", &root), Err(IdentityError::NotFound(_))));
+
+        // A document copied to another DID's location must not resolve as that DID
+        let other = root.join(did_web_document_path("did:web:example.com:bob").unwrap());
+        std::fs::create_dir_all(other.parent().unwrap()).unwrap();
+        std::fs::copy(root.join(did_web_document_path(&document.id).unwrap()), &other).unwrap();
+        assert!(matches!(resolve_did_web_from_dir("did:web:example.com:bob", &root), Err(IdentityError::InvalidDid(_))));
+
+        std::fs::remove_dir_all(&root).unwrap();
+    }
+
+    /// Web server answering every request with `status` and `body`
+    async fn mock_web_server(status: &'static str, body: String) -> String {
+        use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let address = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            while let Ok((mut socket, _)) = listener.accept().await {
+                let mut request = [0u8; 4096];
+                let _ = socket.read(&mut request).await;
+                let response = format!(
+                    "HTTP/1.1 {}\r\nContent-Type: application/did+json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                    status, body.len(), body
+                );
+                let _ = socket.write_all(response.as_bytes()).await;
+            }
+        });
+        format!("http://{}/.well-known/did.json", address)
+    }
+
+    #[tokio::test]
+    async fn fetched_document_must_be_for_the_requested_did() {
+        let (document, _) = create_did_document_with_id("did:web:example.com".to_string(), KeyType::Ed25519).unwrap();
+        let url = mock_web_server("200 OK", serde_json::to_string(&document).unwrap()).await;
+
+        let resolved = fetch_did_web_document("did:web:example.com", &url).await.unwrap();
+        assert_eq!(resolved.verification_method, document.verification_method);
+
+        let error = fetch_did_web_document("did:web:example.org", &url).await.unwrap_err();
+        assert!(matches!(&error, IdentityError::InvalidDid(message) if message.contains("is for did:web:example.com")), "{}", error);
+    }
+
+    #[tokio::test]
+    async fn unsuccessful_responses_are_errors() {
+        let missing = mock_web_server("404 Not Found", String::new()).await;
+        let failing = mock_web_server("500 Internal Server Error", "oops".to_string()).await;
+        let garbage = mock_web_server("200 OK", "<html></html>".to_string()).await;
+
+        assert!(matches!(fetch_did_web_document("did:web:example.com", &missing).await, Err(IdentityError::NotFound(_))));
+        assert!(matches!(fetch_did_web_document("did:web:example.com", &failing).await, Err(IdentityError::NetworkError(_))));
+        assert!(fetch_did_web_document("did:web:example.com", &garbage).await.is_err());
+        assert!(resolve_did_web("did:key:z6MkhaXgBZDvotDkL5257faiztiGiC2QtKLGpbnnEGta2doK").await.is_err());
+    }
+}
@@ -0,0 +1,185 @@
+//! Presentations of selectively disclosed credentials, bound to a verifier's request
+
+use serde::{Deserialize, Serialize};
+use crate::anonymous::{verify_bbs_proof, DerivedCredential};
+use crate::canonicalization::Canonicalization;
+use crate::crypto::KeyType;
+use crate::error::IdentityError;
+use crate::signer::Signer;
+use crate::vc::{Proof, VerifiablePresentation};
+
+/// Verifier's request a presentation is bound to, so it cannot be replayed elsewhere
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct PresentationRequest {
+    /// Fresh nonce chosen by the verifier
+    pub challenge: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub domain: Option<String>,
+    /// DID of the verifier
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub audience: Option<String>,
+}
+
+impl PresentationRequest {
+    /// Create a request for a challenge
+    pub fn new(challenge: String) -> Self {
+        Self { challenge, domain: None, audience: None }
+    }
+
+    /// Bind presentations to a domain
+    pub fn with_domain(mut self, domain: String) -> Self {
+        self.domain = Some(domain);
+        self
+    }
+
+    /// Bind presentations to a verifier DID
+    pub fn with_audience(mut self, audience: String) -> Self {
+        self.audience = Some(audience);
+        self
+    }
+
+    /// Whether a proof carries exactly this request's challenge, domain and audience
+    fn bound_by(&self, proof: &Proof) -> bool {
+        let property = |name: &str| proof.additional_properties.get(name).and_then(serde_json::Value::as_str);
+        property("challenge") == Some(self.challenge.as_str())
+            && property("domain") == self.domain.as_deref()
+            && proof.audience == self.audience
+    }
+}
+
+impl VerifiablePresentation {
+    /// Get the bytes covered by a proof bound to `request`: the unsigned presentation plus the request
+    pub fn request_signing_payload(&self, request: &PresentationRequest) -> Result<Vec<u8>, IdentityError> {
+        let mut unsigned = self.clone();
+        unsigned.proof = None;
+        let mut value = serde_json::to_value(&unsigned)?;
+        if let serde_json::Value::Object(object) = &mut value {
+            object.insert("request".to_string(), serde_json::to_value(request)?);
+        }
+        Canonicalization::Jcs.canonicalize(&value)
+    }
+
+    /// Sign the presentation in answer to a request, recording the challenge, domain and audience in the proof
+    pub async fn sign_for_request(&mut self, signer: &dyn Signer, verification_method: String, request: &PresentationRequest) -> Result<(), IdentityError> {
+        let payload = self.request_signing_payload(request)?;
+        let signature = signer.sign(&payload).await?;
+
+        let mut proof = Proof::new(&signer.key_type(), verification_method, "authentication", &signature);
+        proof.additional_properties.insert("challenge".to_string(), request.challenge.clone().into());
+        if let Some(domain) = &request.domain {
+            proof.additional_properties.insert("domain".to_string(), domain.clone().into());
+        }
+        proof.audience = request.audience.clone();
+        self.add_proof(proof);
+        Ok(())
+    }
+
+    /// Verify that a proof bound to `request` is a valid signature by the holder's key
+    pub fn verify_for_request(&self, request: &PresentationRequest, public_key: &[u8], key_type: &KeyType) -> Result<bool, IdentityError> {
+        let proofs = match &self.proof {
+            Some(proofs) if !proofs.is_empty() => proofs,
+            _ => return Err(IdentityError::VerificationError("Presentation has no proof".to_string())),
+        };
+
+        let payload = self.request_signing_payload(request)?;
+        Ok(proofs.iter()
+            .filter(|proof| request.bound_by(proof))
+            .filter(|proof| self.holder.as_deref().is_none_or(|holder| proof.verification_method.split('#').next() == Some(holder)))
+            .any(|proof| proof.verify(&payload, public_key, key_type)))
+    }
+}
+
+/// Verify a presentation of selectively disclosed credentials built for `request`.
+///
+/// The holder's proof must be bound to the request, and every credential must carry a derived
+/// BBS+ proof made for the request's challenge that verifies against the issuer's key.
+pub fn verify_disclosed_presentation(
+    presentation: &VerifiablePresentation,
+    request: &PresentationRequest,
+    holder_public_key: &[u8],
+    holder_key_type: &KeyType,
+    issuer_public_key: &[u8],
+) -> Result<bool, IdentityError> {
+    if presentation.verifiable_credential.is_empty() {
+        return Err(IdentityError::InvalidPresentation("Presentation discloses no credentials".to_string()));
+    }
+    if !presentation.verify_for_request(request, holder_public_key, holder_key_type)? {
+        return Ok(false);
+    }
+
+    for credential in &presentation.verifiable_credential {
+        let derived = DerivedCredential::from_credential(credential)?;
+        if !verify_bbs_proof(&derived, issuer_public_key, request.challenge.as_bytes())? {
+            return Ok(false);
+        }
+    }
+    Ok(true)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::BTreeMap;
+    use crate::crypto::{generate_bls12381_g2_keypair, generate_ed25519_keypair, CryptoKeyPair};
+    use crate::vc::VerifiableCredential;
+    use crate::wallet::Wallet;
+
+    const HOLDER_KEY: &str = "did:example:alice#key-1";
+
+    /// Wallet holding a BBS+ signed credential, with the issuer and holder keys
+    fn wallet() -> (Wallet, String, CryptoKeyPair, CryptoKeyPair) {
+        let issuer = generate_bls12381_g2_keypair().unwrap();
+        let holder = generate_ed25519_keypair().unwrap();
+        let mut claims = BTreeMap::new();
+        claims.insert("name".to_string(), serde_json::json!("Alice"));
+        claims.insert("birthdate".to_string(), serde_json::json!("1990-01-01"));
+        claims.insert("nationality".to_string(), serde_json::json!("NZ"));
+        let mut credential = VerifiableCredential::new("did:example:issuer".to_string(), Some("did:example:alice".to_string()), claims);
+        credential.sign_bbs(&issuer, "did:example:issuer#bbs-1".to_string()).unwrap();
+
+        let mut wallet = Wallet::new(Some("did:example:alice".to_string()));
+        wallet.add_key(HOLDER_KEY.to_string(), holder.clone());
+        wallet.add_credential(credential.clone()).unwrap();
+        (wallet, credential.id, issuer, holder)
+    }
+
+    fn request() -> PresentationRequest {
+        PresentationRequest::new("challenge-1".to_string())
+            .with_domain("verifier.example".to_string())
+            .with_audience("did:example:verifier".to_string())
+    }
+
+    #[tokio::test]
+    async fn disclosed_presentation_verifies_end_to_end() {
+        let (wallet, credential_id, issuer, holder) = wallet();
+
+        let presentation = wallet.build_disclosed_presentation(&credential_id, &["nationality", "name"], &request(), HOLDER_KEY).await.unwrap();
+
+        let disclosed = &presentation.verifiable_credential[0].credential_subject.claims;
+        assert_eq!(disclosed.keys().collect::<Vec<_>>(), vec!["name", "nationality"]);
+        assert_eq!(presentation.holder.as_deref(), Some("did:example:alice"));
+        assert!(verify_disclosed_presentation(&presentation, &request(), &holder.public_key, &KeyType::Ed25519, &issuer.public_key).unwrap());
+
+        let round_tripped: VerifiablePresentation = serde_json::from_str(&serde_json::to_string(&presentation).unwrap()).unwrap();
+        assert!(verify_disclosed_presentation(&round_tripped, &request(), &holder.public_key, &KeyType::Ed25519, &issuer.public_key).unwrap());
+    }
+
+    #[tokio::test]
+    async fn presentation_fails_for_another_request_or_altered_claims() {
+        let (wallet, credential_id, issuer, holder) = wallet();
+        let presentation = wallet.build_disclosed_presentation(&credential_id, &["name"], &request(), HOLDER_KEY).await.unwrap();
+        let verify = |presentation: &VerifiablePresentation, request: &PresentationRequest| {
+            verify_disclosed_presentation(presentation, request, &holder.public_key, &KeyType::Ed25519, &issuer.public_key).unwrap()
+        };
+
+        assert!(!verify(&presentation, &PresentationRequest { challenge: "challenge-2".to_string(), ..request() }));
+        assert!(!verify(&presentation, &request().with_audience("did:example:other".to_string())));
+        assert!(!verify(&presentation, &PresentationRequest { domain: None, ..request() }));
+
+        let mut altered = presentation.clone();
+        altered.verifiable_credential[0].credential_subject.claims.insert("name".to_string(), serde_json::json!("Mallory"));
+        assert!(!verify(&altered, &request()));
+
+        assert!(wallet.build_disclosed_presentation(&credential_id, &["salary"], &request(), HOLDER_KEY).await.is_err());
+    }
+}
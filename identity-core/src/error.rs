@@ -48,4 +48,10 @@ pub enum IdentityError {
 
     #[error("Configuration error: {0}")]
     ConfigError(String),
+
+    #[error("Unsupported credential status type: {0}")]
+    UnsupportedStatusType(String),
+
+    #[error("Missing prerequisite credential: {0}")]
+    MissingPrerequisite(String),
 }
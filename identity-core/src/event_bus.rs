@@ -0,0 +1,179 @@
+//! In-process event bus for coordinating identity components
+
+use std::sync::{Arc, Mutex};
+use tokio::sync::broadcast;
+use tokio::task::JoinHandle;
+use crate::vc::VerifiableCredential;
+
+/// Default number of events buffered per subscriber before the oldest are dropped
+pub const DEFAULT_EVENT_BUS_CAPACITY: usize = 256;
+
+/// Domain event published by identity components
+#[derive(Debug, Clone)]
+pub enum DomainEvent {
+    CredentialIssued {
+        credential: Box<VerifiableCredential>,
+    },
+    AttestationCompleted {
+        request_id: String,
+        credential_id: String,
+        participating_attestors: Vec<String>,
+    },
+    CredentialRevoked {
+        credential_id: String,
+        reason: String,
+    },
+    CredentialReinstated {
+        credential_id: String,
+    },
+    DidRegistered {
+        did: String,
+        document_hash: String,
+    },
+}
+
+/// Component that reacts to domain events, such as a cache invalidated by revocations
+pub trait EventHandler: Send {
+    /// Apply one event
+    fn handle_event(&mut self, event: &DomainEvent);
+
+    /// Called when the handler fell behind and events were dropped before it saw them
+    fn handle_lagged(&mut self, _missed: u64) {}
+}
+
+/// Broadcast bus; clones share the same channel
+#[derive(Debug, Clone)]
+pub struct EventBus {
+    sender: broadcast::Sender<DomainEvent>,
+}
+
+impl EventBus {
+    /// Create a bus buffering up to `capacity` events per subscriber
+    pub fn new(capacity: usize) -> Self {
+        let (sender, _) = broadcast::channel(capacity.max(1));
+        Self { sender }
+    }
+
+    /// Publish an event, returning the number of subscribers it was delivered to
+    pub fn publish(&self, event: DomainEvent) -> usize {
+        // Publishing with no subscribers is not an error
+        self.sender.send(event).unwrap_or(0)
+    }
+
+    /// Subscribe to events published from now on
+    pub fn subscribe(&self) -> broadcast::Receiver<DomainEvent> {
+        self.sender.subscribe()
+    }
+
+    /// Feed every event published from now on to a handler, on a task of the current tokio runtime.
+    ///
+    /// The task ends when every clone of the bus has been dropped.
+    pub fn attach<H: EventHandler + 'static>(&self, handler: Arc<Mutex<H>>) -> JoinHandle<()> {
+        let mut receiver = self.subscribe();
+        tokio::spawn(async move {
+            loop {
+                let result = receiver.recv().await;
+                let mut handler = handler.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+                match result {
+                    Ok(event) => handler.handle_event(&event),
+                    Err(broadcast::error::RecvError::Lagged(missed)) => handler.handle_lagged(missed),
+                    Err(broadcast::error::RecvError::Closed) => break,
+                }
+            }
+        })
+    }
+
+    /// Number of active subscribers
+    pub fn subscriber_count(&self) -> usize {
+        self.sender.receiver_count()
+    }
+}
+
+impl Default for EventBus {
+    fn default() -> Self {
+        Self::new(DEFAULT_EVENT_BUS_CAPACITY)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Handler keeping the ids of credentials it was told about
+    #[derive(Default)]
+    struct Recorder {
+        revoked: Vec<String>,
+        missed: u64,
+    }
+
+    impl EventHandler for Recorder {
+        fn handle_event(&mut self, event: &DomainEvent) {
+            if let DomainEvent::CredentialRevoked { credential_id, .. } = event {
+                self.revoked.push(credential_id.clone());
+            }
+        }
+
+        fn handle_lagged(&mut self, missed: u64) {
+            self.missed += missed;
+        }
+    }
+
+    fn revoked(credential_id: &str) -> DomainEvent {
+        DomainEvent::CredentialRevoked {
+            credential_id: credential_id.to_string(),
+            reason: "compromised".to_string(),
+        }
+    }
+
+    #[tokio::test]
+    async fn every_subscriber_receives_published_events() {
+        let bus = EventBus::default();
+        assert_eq!(bus.publish(revoked("urn:uuid:lost")), 0);
+
+        let mut first = bus.subscribe();
+        let mut second = bus.clone().subscribe();
+        assert_eq!(bus.subscriber_count(), 2);
+        assert_eq!(bus.publish(revoked("urn:uuid:1")), 2);
+
+        for receiver in [&mut first, &mut second] {
+            match receiver.recv().await.unwrap() {
+                DomainEvent::CredentialRevoked { credential_id, .. } => assert_eq!(credential_id, "urn:uuid:1"),
+                other => panic!("unexpected event {:?}", other),
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn attached_handler_runs_until_the_bus_is_dropped() {
+        let bus = EventBus::default();
+        let recorder = Arc::new(Mutex::new(Recorder::default()));
+        let task = bus.attach(recorder.clone());
+
+        bus.publish(revoked("urn:uuid:1"));
+        bus.publish(DomainEvent::CredentialReinstated { credential_id: "urn:uuid:1".to_string() });
+        bus.publish(revoked("urn:uuid:2"));
+        drop(bus);
+        task.await.unwrap();
+
+        assert_eq!(recorder.lock().unwrap().revoked, vec!["urn:uuid:1", "urn:uuid:2"]);
+    }
+
+    #[tokio::test]
+    async fn lagging_handler_is_told_how_many_events_it_missed() {
+        let bus = EventBus::new(2);
+        let recorder = Arc::new(Mutex::new(Recorder::default()));
+
+        // The single-threaded test runtime only runs the handler task once it is awaited,
+        // by which time its buffer has overflowed
+        let task = bus.attach(recorder.clone());
+        for i in 0..5 {
+            bus.publish(revoked(&format!("urn:uuid:{}", i)));
+        }
+        drop(bus);
+        task.await.unwrap();
+
+        let recorder = recorder.lock().unwrap();
+        assert_eq!(recorder.missed, 3);
+        assert_eq!(recorder.revoked, vec!["urn:uuid:3", "urn:uuid:4"]);
+    }
+}
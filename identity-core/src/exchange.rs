@@ -0,0 +1,252 @@
+//! DIF Presentation Exchange definitions and credential selection
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use crate::error::IdentityError;
+use crate::schema::schema_errors;
+use crate::utils::generate_id;
+use crate::vc::VerifiableCredential;
+
+/// Verifier's description of the credentials it requires
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct PresentationDefinition {
+    pub id: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub purpose: Option<String>,
+    pub input_descriptors: Vec<InputDescriptor>,
+}
+
+/// Requirement for a single credential
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct InputDescriptor {
+    pub id: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub purpose: Option<String>,
+    #[serde(default)]
+    pub constraints: Constraints,
+}
+
+/// Constraints a credential must satisfy
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Default)]
+pub struct Constraints {
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub fields: Vec<FieldConstraint>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub limit_disclosure: Option<String>,
+}
+
+/// Field located by JSONPath and optionally checked against a JSON Schema filter
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct FieldConstraint {
+    pub path: Vec<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub id: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub purpose: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub filter: Option<Value>,
+    #[serde(default)]
+    pub optional: bool,
+}
+
+/// Holder's mapping of input descriptors to submitted credentials
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct PresentationSubmission {
+    pub id: String,
+    pub definition_id: String,
+    pub descriptor_map: Vec<DescriptorMapEntry>,
+}
+
+/// Location of the credential satisfying an input descriptor
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct DescriptorMapEntry {
+    pub id: String,
+    pub format: String,
+    pub path: String,
+}
+
+impl PresentationDefinition {
+    /// Select credentials satisfying every input descriptor and map them in a submission.
+    ///
+    /// Descriptor paths index into `credentials`, which should be presented in the same order.
+    pub fn evaluate(&self, credentials: &[VerifiableCredential]) -> Result<PresentationSubmission, IdentityError> {
+        let values = credentials.iter()
+            .map(serde_json::to_value)
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let mut descriptor_map = Vec::new();
+        for descriptor in &self.input_descriptors {
+            let index = values.iter()
+                .position(|credential| descriptor.matches(credential))
+                .ok_or_else(|| IdentityError::InvalidPresentation(format!(
+                    "No credential satisfies input descriptor {}", descriptor.id
+                )))?;
+
+            descriptor_map.push(DescriptorMapEntry {
+                id: descriptor.id.clone(),
+                format: "ldp_vc".to_string(),
+                path: format!("$.verifiableCredential[{}]", index),
+            });
+        }
+
+        Ok(PresentationSubmission {
+            id: generate_id(),
+            definition_id: self.id.clone(),
+            descriptor_map,
+        })
+    }
+}
+
+impl InputDescriptor {
+    /// Check whether a serialized credential satisfies every required field
+    pub fn matches(&self, credential: &Value) -> bool {
+        self.constraints.fields.iter().all(|field| field.optional || field.matches(credential))
+    }
+}
+
+impl FieldConstraint {
+    /// Check whether any value at the first resolvable path passes the filter
+    pub fn matches(&self, credential: &Value) -> bool {
+        let values = self.path.iter()
+            .map(|path| json_path(credential, path))
+            .find(|values| !values.is_empty())
+            .unwrap_or_default();
+
+        match &self.filter {
+            Some(filter) => values.iter().any(|value| schema_errors(value, filter).is_empty()),
+            None => !values.is_empty(),
+        }
+    }
+}
+
+/// Evaluate a JSONPath expression supporting `$`, `.key`, `['key']`, `[n]` and `[*]`
+pub fn json_path<'a>(root: &'a Value, path: &str) -> Vec<&'a Value> {
+    let mut rest = match path.strip_prefix('$') {
+        Some(rest) => rest,
+        None => return Vec::new(),
+    };
+    let mut current = vec![root];
+
+    while !rest.is_empty() {
+        if let Some(after) = rest.strip_prefix(".*").or_else(|| rest.strip_prefix("[*]")) {
+            current = current.into_iter().flat_map(children).collect();
+            rest = after;
+        } else if let Some(after) = rest.strip_prefix('.') {
+            let end = after.find(['.', '[']).unwrap_or(after.len());
+            let key = &after[..end];
+            current = current.into_iter().filter_map(|value| value.get(key)).collect();
+            rest = &after[end..];
+        } else if let Some(after) = rest.strip_prefix('[') {
+            let end = match after.find(']') {
+                Some(end) => end,
+                None => return Vec::new(),
+            };
+            let selector = &after[..end];
+            let quoted = selector.strip_prefix('\'').and_then(|s| s.strip_suffix('\''))
+                .or_else(|| selector.strip_prefix('"').and_then(|s| s.strip_suffix('"')));
+
+            current = match (quoted, selector.parse::<usize>()) {
+                (Some(key), _) => current.into_iter().filter_map(|value| value.get(key)).collect(),
+                (None, Ok(index)) => current.into_iter().filter_map(|value| value.get(index)).collect(),
+                _ => return Vec::new(),
+            };
+            rest = &after[end + 1..];
+        } else {
+            return Vec::new();
+        }
+    }
+
+    current
+}
+
+fn children(value: &Value) -> Vec<&Value> {
+    match value {
+        Value::Array(items) => items.iter().collect(),
+        Value::Object(object) => object.values().collect(),
+        _ => Vec::new(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::BTreeMap;
+    use crate::vc::CredentialType;
+
+    fn credential(credential_type: Option<CredentialType>, degree: &str) -> VerifiableCredential {
+        let mut claims = BTreeMap::new();
+        claims.insert("degree".to_string(), serde_json::json!({ "type": degree, "name": "Computer Science" }));
+        let mut credential = VerifiableCredential::new("did:example:university".to_string(), Some("did:example:alice".to_string()), claims);
+        if let Some(credential_type) = credential_type {
+            credential.add_type(credential_type);
+        }
+        credential
+    }
+
+    fn degree_definition() -> PresentationDefinition {
+        serde_json::from_value(serde_json::json!({
+            "id": "degree-check",
+            "input_descriptors": [{
+                "id": "bachelor_degree",
+                "constraints": {
+                    "fields": [
+                        {
+                            "path": ["$.type"],
+                            "filter": { "type": "array", "contains": { "const": "UniversityDegreeCredential" } }
+                        },
+                        {
+                            "path": ["$.credentialSubject.degree.type", "$.vc.credentialSubject.degree.type"],
+                            "filter": { "type": "string", "const": "BachelorDegree" }
+                        },
+                        { "path": ["$.credentialSubject.gpa"], "optional": true }
+                    ]
+                }
+            }]
+        })).unwrap()
+    }
+
+    #[test]
+    fn selects_the_credential_matching_type_and_claim() {
+        let credentials = vec![
+            credential(None, "BachelorDegree"),
+            credential(Some(CredentialType::UniversityDegreeCredential), "MasterDegree"),
+            credential(Some(CredentialType::UniversityDegreeCredential), "BachelorDegree"),
+        ];
+
+        let submission = degree_definition().evaluate(&credentials).unwrap();
+
+        assert_eq!(submission.definition_id, "degree-check");
+        assert_eq!(submission.descriptor_map, vec![DescriptorMapEntry {
+            id: "bachelor_degree".to_string(),
+            format: "ldp_vc".to_string(),
+            path: "$.verifiableCredential[2]".to_string(),
+        }]);
+    }
+
+    #[test]
+    fn unsatisfied_descriptor_is_an_error() {
+        let credentials = vec![credential(Some(CredentialType::UniversityDegreeCredential), "MasterDegree")];
+
+        assert!(matches!(degree_definition().evaluate(&credentials), Err(IdentityError::InvalidPresentation(_))));
+    }
+
+    #[test]
+    fn json_path_supports_keys_indices_and_wildcards() {
+        let value = serde_json::json!({
+            "type": ["VerifiableCredential", "UniversityDegreeCredential"],
+            "credentialSubject": { "degree": { "type": "BachelorDegree" }, "first-name": "Alice" },
+        });
+
+        assert_eq!(json_path(&value, "$.type[1]"), vec!["UniversityDegreeCredential"]);
+        assert_eq!(json_path(&value, "$.type[*]").len(), 2);
+        assert_eq!(json_path(&value, "$.credentialSubject['first-name']"), vec!["Alice"]);
+        assert_eq!(json_path(&value, "$.credentialSubject.*.type"), vec!["BachelorDegree"]);
+        assert!(json_path(&value, "$.credentialSubject.missing").is_empty());
+        assert!(json_path(&value, "credentialSubject").is_empty());
+        assert!(json_path(&value, "$.type[1").is_empty());
+    }
+}
@@ -0,0 +1,220 @@
+//! Credential binding to hardware-attested keys (TPM, secure enclave)
+
+use serde::{Deserialize, Serialize};
+use crate::crypto::encoding::{decode_base64url, decode_multikey, encode_base64url};
+use crate::crypto::{sign_data, verify_data, CryptoKeyPair};
+use crate::error::IdentityError;
+use crate::vc::VerifiableCredential;
+
+/// Certificate in a hardware attestation chain, signed by the next key up the chain
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct AttestationCertificate {
+    /// Certified key, multibase multikey encoded
+    #[serde(rename = "publicKey")]
+    pub public_key: String,
+    /// Issuer signature over the certified key, base64url encoded
+    pub signature: String,
+}
+
+/// Attestation statement proving the subject key lives in attested hardware
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct HardwareAttestation {
+    /// Attestation format, e.g. `tpm` or `apple-secure-enclave`
+    pub format: String,
+    /// Hardware-backed subject key, multibase multikey encoded
+    #[serde(rename = "subjectKey")]
+    pub subject_key: String,
+    /// Signature by the leaf attestation key over the statement, base64url encoded
+    #[serde(rename = "statementSignature")]
+    pub statement_signature: String,
+    /// Certificate chain, leaf attestation key first, last one signed by a trusted root
+    #[serde(rename = "certificateChain")]
+    pub certificate_chain: Vec<AttestationCertificate>,
+}
+
+impl AttestationCertificate {
+    /// Certify a multikey with an issuer keypair
+    pub fn issue(public_key: String, issuer: &CryptoKeyPair) -> Result<Self, IdentityError> {
+        let signature = sign_data(public_key.as_bytes(), &issuer.private_key, &issuer.key_type)?;
+        Ok(Self {
+            public_key,
+            signature: encode_base64url(&signature),
+        })
+    }
+
+    /// Check the certificate was signed by the given multikey
+    fn signed_by(&self, issuer_key: &str) -> bool {
+        verify_multikey_signature(self.public_key.as_bytes(), &self.signature, issuer_key)
+    }
+}
+
+impl HardwareAttestation {
+    /// Create an attestation for a subject key, signing the statement with the leaf attestation key
+    pub fn new(
+        format: String,
+        subject_key: String,
+        attestation_key: &CryptoKeyPair,
+        certificate_chain: Vec<AttestationCertificate>,
+    ) -> Result<Self, IdentityError> {
+        let mut attestation = Self {
+            format,
+            subject_key,
+            statement_signature: String::new(),
+            certificate_chain,
+        };
+
+        let signature = sign_data(
+            &attestation.statement_payload()?,
+            &attestation_key.private_key,
+            &attestation_key.key_type,
+        )?;
+        attestation.statement_signature = encode_base64url(&signature);
+        Ok(attestation)
+    }
+
+    /// Bytes covered by the statement signature
+    pub fn statement_payload(&self) -> Result<Vec<u8>, IdentityError> {
+        let statement = serde_json::json!({
+            "format": self.format,
+            "subjectKey": self.subject_key,
+        });
+        Ok(serde_json::to_vec(&statement)?)
+    }
+
+    /// Validate the statement and certificate chain up to one of the trusted root multikeys
+    pub fn verify(&self, trusted_roots: &[String]) -> Result<bool, IdentityError> {
+        decode_multikey(&self.subject_key)?;
+
+        let leaf = match self.certificate_chain.first() {
+            Some(leaf) => leaf,
+            None => return Ok(false),
+        };
+        if !verify_multikey_signature(&self.statement_payload()?, &self.statement_signature, &leaf.public_key) {
+            return Ok(false);
+        }
+
+        for pair in self.certificate_chain.windows(2) {
+            if !pair[0].signed_by(&pair[1].public_key) {
+                return Ok(false);
+            }
+        }
+
+        let top = &self.certificate_chain[self.certificate_chain.len() - 1];
+        Ok(trusted_roots.iter().any(|root| top.signed_by(root)))
+    }
+}
+
+impl VerifiableCredential {
+    /// Bind the credential to a hardware-attested subject key
+    pub fn set_hardware_attestation(&mut self, attestation: HardwareAttestation) {
+        self.hardware_attestation = Some(attestation);
+    }
+
+    /// Validate the hardware attestation chain against trusted root multikeys.
+    ///
+    /// The attestation is part of the signing payload, so issuer proofs also cover the binding.
+    pub fn verify_hardware_binding(&self, trusted_roots: &[String]) -> Result<bool, IdentityError> {
+        match &self.hardware_attestation {
+            Some(attestation) => attestation.verify(trusted_roots),
+            None => Err(IdentityError::VerificationError("Credential has no hardware attestation".to_string())),
+        }
+    }
+}
+
+/// Verify a base64url signature against a multikey, treating malformed input as invalid
+fn verify_multikey_signature(payload: &[u8], signature: &str, multikey: &str) -> bool {
+    let (key_type, public_key) = match decode_multikey(multikey) {
+        Ok(decoded) => decoded,
+        Err(_) => return false,
+    };
+    match decode_base64url(signature) {
+        Ok(signature) => verify_data(payload, &signature, &public_key, &key_type).unwrap_or(false),
+        Err(_) => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::BTreeMap;
+    use crate::crypto::encoding::encode_multikey;
+    use crate::crypto::{generate_ed25519_keypair, generate_secp256k1_keypair};
+
+    fn multikey(keypair: &CryptoKeyPair) -> String {
+        encode_multikey(&keypair.public_key, &keypair.key_type)
+    }
+
+    /// Subject key attested by a leaf key, certified by an intermediate, certified by the root
+    fn attestation(root: &CryptoKeyPair) -> HardwareAttestation {
+        let subject = generate_secp256k1_keypair().unwrap();
+        let leaf = generate_ed25519_keypair().unwrap();
+        let intermediate = generate_ed25519_keypair().unwrap();
+
+        let chain = vec![
+            AttestationCertificate::issue(multikey(&leaf), &intermediate).unwrap(),
+            AttestationCertificate::issue(multikey(&intermediate), root).unwrap(),
+        ];
+        HardwareAttestation::new("tpm".to_string(), multikey(&subject), &leaf, chain).unwrap()
+    }
+
+    fn credential() -> VerifiableCredential {
+        let mut claims = BTreeMap::new();
+        claims.insert("role".to_string(), serde_json::json!("operator"));
+        VerifiableCredential::new("did:example:issuer".to_string(), Some("did:example:alice".to_string()), claims)
+    }
+
+    #[test]
+    fn valid_attestation_chain_verifies_against_trusted_root() {
+        let root = generate_ed25519_keypair().unwrap();
+        let mut credential = credential();
+        credential.set_hardware_attestation(attestation(&root));
+
+        assert!(credential.verify_hardware_binding(&[multikey(&root)]).unwrap());
+    }
+
+    #[test]
+    fn untrusted_root_is_rejected() {
+        let root = generate_ed25519_keypair().unwrap();
+        let other = generate_ed25519_keypair().unwrap();
+        let mut credential = credential();
+        credential.set_hardware_attestation(attestation(&root));
+
+        assert!(!credential.verify_hardware_binding(&[multikey(&other)]).unwrap());
+        assert!(!credential.verify_hardware_binding(&[]).unwrap());
+    }
+
+    #[test]
+    fn tampered_statement_or_chain_is_rejected() {
+        let root = generate_ed25519_keypair().unwrap();
+        let roots = [multikey(&root)];
+
+        let mut swapped_subject = attestation(&root);
+        swapped_subject.subject_key = multikey(&generate_secp256k1_keypair().unwrap());
+        assert!(!swapped_subject.verify(&roots).unwrap());
+
+        let mut broken_chain = attestation(&root);
+        broken_chain.certificate_chain[1].public_key = multikey(&generate_ed25519_keypair().unwrap());
+        assert!(!broken_chain.verify(&roots).unwrap());
+
+        let mut empty_chain = attestation(&root);
+        empty_chain.certificate_chain.clear();
+        assert!(!empty_chain.verify(&roots).unwrap());
+    }
+
+    #[test]
+    fn binding_is_optional_and_signed_with_the_credential() {
+        let root = generate_ed25519_keypair().unwrap();
+        let issuer = generate_ed25519_keypair().unwrap();
+
+        let mut credential = credential();
+        assert!(credential.hardware_attestation.is_none());
+        assert!(credential.verify_hardware_binding(&[multikey(&root)]).is_err());
+
+        credential.set_hardware_attestation(attestation(&root));
+        credential.sign(&issuer, "did:example:issuer#key-1".to_string()).unwrap();
+        assert!(credential.verify_proof(&issuer.public_key, &issuer.key_type).unwrap());
+
+        credential.set_hardware_attestation(attestation(&root));
+        assert!(!credential.verify_proof(&issuer.public_key, &issuer.key_type).unwrap());
+    }
+}
@@ -0,0 +1,132 @@
+//! Service endpoint health probing for DID documents
+
+use serde::{Deserialize, Serialize};
+use std::time::{Duration, Instant};
+use crate::did::{DidDocument, ServiceEndpoint};
+
+/// Reachability of a single DID service endpoint
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct ServiceHealth {
+    pub service_id: String,
+    pub endpoint: String,
+    pub reachable: bool,
+    pub status_code: Option<u16>,
+    pub latency_ms: Option<u64>,
+    pub error: Option<String>,
+}
+
+impl DidDocument {
+    /// Probe every HTTP(S) service endpoint, reporting reachability within the timeout.
+    /// Map-valued and non-HTTP endpoints are skipped.
+    pub async fn probe_services(&self, timeout: Duration) -> Vec<ServiceHealth> {
+        let services = match &self.service {
+            Some(services) => services,
+            None => return Vec::new(),
+        };
+
+        let client = reqwest::Client::new();
+
+        let mut results = Vec::new();
+        for service in services {
+            let uri = match probeable_uri(&service.service_endpoint) {
+                Some(uri) => uri,
+                None => continue,
+            };
+
+            let started = Instant::now();
+            let health = match client.get(uri).timeout(timeout).send().await {
+                Ok(response) => ServiceHealth {
+                    service_id: service.id.clone(),
+                    endpoint: uri.to_string(),
+                    reachable: true,
+                    status_code: Some(response.status().as_u16()),
+                    latency_ms: Some(started.elapsed().as_millis() as u64),
+                    error: None,
+                },
+                Err(e) => ServiceHealth {
+                    service_id: service.id.clone(),
+                    endpoint: uri.to_string(),
+                    reachable: false,
+                    status_code: None,
+                    latency_ms: None,
+                    error: Some(if e.is_timeout() {
+                        format!("Timed out after {} ms", timeout.as_millis())
+                    } else {
+                        e.to_string()
+                    }),
+                },
+            };
+            results.push(health);
+        }
+
+        results
+    }
+}
+
+/// Get the endpoint URI if it can be probed over HTTP
+fn probeable_uri(endpoint: &ServiceEndpoint) -> Option<&str> {
+    match endpoint {
+        ServiceEndpoint::Uri(uri) if uri.starts_with("http://") || uri.starts_with("https://") => Some(uri),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::crypto::KeyType;
+    use crate::did::{Service, ServiceType};
+    use crate::utils::create_did_document_with_id;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use tokio::net::TcpListener;
+
+    fn service(id: &str, endpoint: ServiceEndpoint) -> Service {
+        Service {
+            id: format!("did:example:alice#{}", id),
+            service_type: ServiceType::Single("LinkedDomains".to_string()),
+            service_endpoint: endpoint,
+        }
+    }
+
+    /// Serve a single empty 204 response
+    async fn mock_endpoint() -> String {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let address = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            let (mut socket, _) = listener.accept().await.unwrap();
+            let mut buffer = [0u8; 1024];
+            let _ = socket.read(&mut buffer).await;
+            let _ = socket.write_all(b"HTTP/1.1 204 No Content\r\nConnection: close\r\n\r\n").await;
+        });
+        format!("http://{}", address)
+    }
+
+    /// Address nothing is listening on
+    async fn closed_endpoint() -> String {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        format!("http://{}", listener.local_addr().unwrap())
+    }
+
+    #[tokio::test]
+    async fn probe_reports_reachable_and_unreachable_endpoints() {
+        let mut document = create_did_document_with_id("did:example:alice".to_string(), KeyType::Ed25519).unwrap().0;
+        let live = mock_endpoint().await;
+        let dead = closed_endpoint().await;
+        document.service = Some(vec![
+            service("live", ServiceEndpoint::Uri(live.clone())),
+            service("dead", ServiceEndpoint::Uri(dead.clone())),
+            service("didcomm", ServiceEndpoint::Uri("did:example:mediator".to_string())),
+        ]);
+
+        let health = document.probe_services(Duration::from_secs(5)).await;
+
+        assert_eq!(health.len(), 2);
+        assert_eq!(health[0].endpoint, live);
+        assert!(health[0].reachable);
+        assert_eq!(health[0].status_code, Some(204));
+        assert!(health[0].latency_ms.is_some());
+        assert_eq!(health[1].endpoint, dead);
+        assert!(!health[1].reachable);
+        assert!(health[1].error.is_some());
+    }
+}
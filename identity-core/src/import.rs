@@ -0,0 +1,126 @@
+//! Bulk credential import with triage of malformed documents
+
+use serde::Serialize;
+use crate::vc::VerifiableCredential;
+
+/// Credential that deserialized but failed validation, held for review
+#[derive(Debug, Clone, Serialize)]
+pub struct QuarantinedCredential {
+    pub index: usize,
+    pub credential: VerifiableCredential,
+    pub reason: String,
+}
+
+/// Input that could not be deserialized as a credential
+#[derive(Debug, Clone, Serialize)]
+pub struct RejectedImport {
+    pub index: usize,
+    pub value: serde_json::Value,
+    pub reason: String,
+}
+
+/// Outcome of a bulk import, partitioned by how far each input got
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct ImportReport {
+    pub accepted: Vec<VerifiableCredential>,
+    pub quarantined: Vec<QuarantinedCredential>,
+    pub rejected: Vec<RejectedImport>,
+}
+
+impl ImportReport {
+    /// Total number of inputs processed
+    pub fn total(&self) -> usize {
+        self.accepted.len() + self.quarantined.len() + self.rejected.len()
+    }
+
+    /// Whether every input was accepted
+    pub fn is_clean(&self) -> bool {
+        self.quarantined.is_empty() && self.rejected.is_empty()
+    }
+}
+
+/// Deserialize and validate each value without letting one bad input abort the import.
+///
+/// Indices in the report refer to positions in `values`.
+pub fn import_credentials(values: Vec<serde_json::Value>) -> ImportReport {
+    let mut report = ImportReport::default();
+
+    for (index, value) in values.into_iter().enumerate() {
+        let credential: VerifiableCredential = match serde_json::from_value(value.clone()) {
+            Ok(credential) => credential,
+            Err(e) => {
+                report.rejected.push(RejectedImport {
+                    index,
+                    value,
+                    reason: e.to_string(),
+                });
+                continue;
+            }
+        };
+
+        match credential.validate() {
+            Ok(()) => report.accepted.push(credential),
+            Err(e) => report.quarantined.push(QuarantinedCredential {
+                index,
+                credential,
+                reason: e.to_string(),
+            }),
+        }
+    }
+
+    report
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::BTreeMap;
+
+    fn credential() -> VerifiableCredential {
+        let mut claims = BTreeMap::new();
+        claims.insert("degree".to_string(), serde_json::json!("BSc"));
+        VerifiableCredential::new("did:example:issuer".to_string(), Some("did:example:alice".to_string()), claims)
+    }
+
+    #[test]
+    fn inputs_are_partitioned_by_how_far_they_got() {
+        let good = credential();
+        let mut expired = credential();
+        expired.expiration_date = Some(chrono::Utc::now() - chrono::Duration::days(1));
+        let mut no_issuer = serde_json::to_value(credential()).unwrap();
+        no_issuer.as_object_mut().unwrap().remove("issuer");
+
+        let report = import_credentials(vec![
+            serde_json::to_value(&good).unwrap(),
+            serde_json::json!("not a credential"),
+            serde_json::to_value(&expired).unwrap(),
+            no_issuer,
+            serde_json::json!({ "id": 42 }),
+        ]);
+
+        assert_eq!(report.total(), 5);
+        assert!(!report.is_clean());
+        assert_eq!(report.accepted, vec![good]);
+
+        assert_eq!(report.quarantined.len(), 1);
+        assert_eq!(report.quarantined[0].index, 2);
+        assert_eq!(report.quarantined[0].credential, expired);
+        assert!(report.quarantined[0].reason.contains("expired"), "{}", report.quarantined[0].reason);
+
+        let rejected: Vec<usize> = report.rejected.iter().map(|r| r.index).collect();
+        assert_eq!(rejected, vec![1, 3, 4]);
+        assert_eq!(report.rejected[0].value, serde_json::json!("not a credential"));
+        assert!(report.rejected.iter().all(|r| !r.reason.is_empty()));
+    }
+
+    #[test]
+    fn clean_and_empty_imports() {
+        let report = import_credentials(vec![serde_json::to_value(credential()).unwrap()]);
+        assert!(report.is_clean());
+        assert_eq!(report.accepted.len(), 1);
+
+        let empty = import_credentials(Vec::new());
+        assert!(empty.is_clean());
+        assert_eq!(empty.total(), 0);
+    }
+}
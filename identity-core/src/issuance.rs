@@ -0,0 +1,198 @@
+//! Issuance controls for credential issuers
+
+use chrono::{DateTime, Duration, Utc};
+use std::collections::{HashMap, VecDeque};
+use crate::error::IdentityError;
+use crate::vc::VerifiableCredential;
+use crate::event_bus::{DomainEvent, EventBus};
+
+/// Maximum number of issuances allowed within a sliding window
+#[derive(Debug, Clone, PartialEq)]
+pub struct IssuanceLimit {
+    pub max_issuances: usize,
+    pub window: Duration,
+}
+
+/// Per-issuer issuance quota enforced with a sliding window
+#[derive(Debug, Clone)]
+pub struct IssuanceGuard {
+    default_limit: Option<IssuanceLimit>,
+    limits: HashMap<String, IssuanceLimit>,
+    history: HashMap<String, VecDeque<DateTime<Utc>>>,
+    event_bus: Option<EventBus>,
+}
+
+impl IssuanceLimit {
+    /// Create a new issuance limit
+    pub fn new(max_issuances: usize, window: Duration) -> Self {
+        Self {
+            max_issuances,
+            window,
+        }
+    }
+}
+
+impl IssuanceGuard {
+    /// Create a new guard; issuers without a configured limit fall back to `default_limit`
+    pub fn new(default_limit: Option<IssuanceLimit>) -> Self {
+        Self {
+            default_limit,
+            limits: HashMap::new(),
+            history: HashMap::new(),
+            event_bus: None,
+        }
+    }
+
+    /// Configure the limit for a specific issuer DID
+    pub fn set_limit(&mut self, issuer_did: String, limit: IssuanceLimit) {
+        self.limits.insert(issuer_did, limit);
+    }
+
+    /// Remove the issuer-specific limit, reverting to the default
+    pub fn remove_limit(&mut self, issuer_did: &str) {
+        self.limits.remove(issuer_did);
+    }
+
+    /// Get the limit that applies to an issuer
+    pub fn limit_for(&self, issuer_did: &str) -> Option<&IssuanceLimit> {
+        self.limits.get(issuer_did).or(self.default_limit.as_ref())
+    }
+
+    /// Publish `CredentialIssued` events to a bus for credentials that pass the guard
+    pub fn set_event_bus(&mut self, event_bus: EventBus) {
+        self.event_bus = Some(event_bus);
+    }
+
+    /// Record an issuance by the credential's issuer, rejecting it if the quota is exhausted
+    pub fn check_credential(&mut self, credential: &VerifiableCredential) -> Result<(), IdentityError> {
+        self.record_issuance(credential.get_issuer_did())?;
+
+        if let Some(event_bus) = &self.event_bus {
+            event_bus.publish(DomainEvent::CredentialIssued {
+                credential: Box::new(credential.clone()),
+            });
+        }
+        Ok(())
+    }
+
+    /// Record an issuance for an issuer, rejecting it if the quota is exhausted
+    pub fn record_issuance(&mut self, issuer_did: &str) -> Result<(), IdentityError> {
+        self.record_issuance_at(issuer_did, Utc::now())
+    }
+
+    /// Record an issuance at a specific time, rejecting it if the quota is exhausted
+    pub fn record_issuance_at(&mut self, issuer_did: &str, at: DateTime<Utc>) -> Result<(), IdentityError> {
+        let limit = match self.limit_for(issuer_did) {
+            Some(limit) => limit.clone(),
+            None => return Ok(()),
+        };
+
+        let history = self.history.entry(issuer_did.to_string()).or_default();
+        while let Some(oldest) = history.front() {
+            if at - *oldest >= limit.window {
+                history.pop_front();
+            } else {
+                break;
+            }
+        }
+
+        if history.len() >= limit.max_issuances {
+            return Err(IdentityError::PermissionDenied(format!(
+                "Issuer {} exceeded quota of {} credentials per {} seconds",
+                issuer_did,
+                limit.max_issuances,
+                limit.window.num_seconds()
+            )));
+        }
+
+        history.push_back(at);
+        Ok(())
+    }
+
+    /// Number of issuances still allowed for an issuer in the current window
+    pub fn remaining(&self, issuer_did: &str) -> Option<usize> {
+        let limit = self.limit_for(issuer_did)?;
+        let now = Utc::now();
+        let used = self.history.get(issuer_did)
+            .map(|h| h.iter().filter(|t| now - **t < limit.window).count())
+            .unwrap_or(0);
+        Some(limit.max_issuances.saturating_sub(used))
+    }
+}
+
+impl Default for IssuanceGuard {
+    fn default() -> Self {
+        Self::new(None)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn guard() -> IssuanceGuard {
+        let mut guard = IssuanceGuard::new(None);
+        guard.set_limit("did:example:issuer".to_string(), IssuanceLimit::new(3, Duration::minutes(1)));
+        guard
+    }
+
+    #[test]
+    fn issuances_up_to_the_quota_are_allowed() {
+        let mut guard = guard();
+        let start = Utc::now();
+
+        for i in 0..3 {
+            guard.record_issuance_at("did:example:issuer", start + Duration::seconds(i)).unwrap();
+        }
+        let rejected = guard.record_issuance_at("did:example:issuer", start + Duration::seconds(3));
+
+        assert!(matches!(rejected, Err(IdentityError::PermissionDenied(_))));
+    }
+
+    #[test]
+    fn the_window_slides_over_time() {
+        let mut guard = guard();
+        let start = Utc::now();
+        for i in 0..3 {
+            guard.record_issuance_at("did:example:issuer", start + Duration::seconds(i * 20)).unwrap();
+        }
+
+        assert!(guard.record_issuance_at("did:example:issuer", start + Duration::seconds(59)).is_err());
+        // The first issuance leaves the window, freeing exactly one slot
+        guard.record_issuance_at("did:example:issuer", start + Duration::seconds(60)).unwrap();
+        assert!(guard.record_issuance_at("did:example:issuer", start + Duration::seconds(61)).is_err());
+    }
+
+    #[test]
+    fn limits_are_per_issuer() {
+        let mut guard = IssuanceGuard::new(Some(IssuanceLimit::new(1, Duration::minutes(1))));
+        guard.set_limit("did:example:busy".to_string(), IssuanceLimit::new(2, Duration::minutes(1)));
+        let now = Utc::now();
+
+        guard.record_issuance_at("did:example:busy", now).unwrap();
+        guard.record_issuance_at("did:example:busy", now).unwrap();
+        guard.record_issuance_at("did:example:quiet", now).unwrap();
+
+        assert!(guard.record_issuance_at("did:example:quiet", now).is_err());
+        assert!(guard.record_issuance_at("did:example:busy", now).is_err());
+        assert_eq!(IssuanceGuard::default().record_issuance("did:example:anyone").ok(), Some(()));
+    }
+
+    #[tokio::test]
+    async fn credentials_within_quota_are_announced() {
+        let bus = EventBus::default();
+        let mut events = bus.subscribe();
+        let mut guard = IssuanceGuard::new(Some(IssuanceLimit::new(1, Duration::minutes(1))));
+        guard.set_event_bus(bus);
+
+        let credential = VerifiableCredential::new("did:example:issuer".to_string(), None, Default::default());
+        guard.check_credential(&credential).unwrap();
+        assert!(guard.check_credential(&credential).is_err());
+
+        match events.recv().await.unwrap() {
+            DomainEvent::CredentialIssued { credential: issued } => assert_eq!(issued.id, credential.id),
+            other => panic!("unexpected event {:?}", other),
+        }
+        assert!(events.try_recv().is_err());
+    }
+}
@@ -0,0 +1,179 @@
+//! Credentials issued jointly by several organizations, each adding its own proof
+
+use std::collections::HashSet;
+use serde::{Deserialize, Serialize};
+use crate::did::RelationshipType;
+use crate::error::IdentityError;
+use crate::resolver::DidResolver;
+use crate::vc::{Issuer, VerifiableCredential};
+
+/// Issuer field of a jointly issued credential
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct JointIssuer {
+    /// DIDs of the co-issuers; the first is the lead issuer
+    pub issuers: Vec<String>,
+}
+
+impl JointIssuer {
+    /// Create a joint issuer set
+    pub fn new(issuers: Vec<String>) -> Self {
+        Self { issuers }
+    }
+
+    /// Check the set is non-empty and holds distinct DIDs
+    pub fn validate(&self) -> Result<(), IdentityError> {
+        if self.issuers.is_empty() {
+            return Err(IdentityError::InvalidCredential("Joint issuer set is empty".to_string()));
+        }
+        if let Some(issuer) = self.issuers.iter().find(|issuer| !issuer.starts_with("did:")) {
+            return Err(IdentityError::InvalidCredential(format!("Joint issuer {} is not a valid DID", issuer)));
+        }
+        let mut seen = HashSet::new();
+        if let Some(issuer) = self.issuers.iter().find(|issuer| !seen.insert(issuer.as_str())) {
+            return Err(IdentityError::InvalidCredential(format!("Joint issuer {} is listed twice", issuer)));
+        }
+        Ok(())
+    }
+}
+
+impl From<JointIssuer> for Issuer {
+    fn from(joint: JointIssuer) -> Self {
+        Issuer::Joint(joint)
+    }
+}
+
+impl VerifiableCredential {
+    /// Verify that at least `min_issuers` of the credential's issuers signed it.
+    ///
+    /// An issuer counts once it has a valid `assertionMethod` proof by a key its resolved DID
+    /// document authorizes for assertions; an issuer whose DID fails to resolve counts as not having
+    /// signed. Pass the number of issuers to require all of them.
+    pub async fn verify_joint_issuance(&self, resolver: &dyn DidResolver, min_issuers: usize) -> Result<(), IdentityError> {
+        self.validate()?;
+
+        let issuers = self.issuer_dids();
+        if min_issuers == 0 || min_issuers > issuers.len() {
+            return Err(IdentityError::InvalidCredential(format!(
+                "Required issuer count {} must be between 1 and {}", min_issuers, issuers.len()
+            )));
+        }
+
+        let payload = self.signing_payload()?;
+        let proofs = self.proof.as_deref().unwrap_or_default();
+        let mut unsigned = Vec::new();
+        for issuer in &issuers {
+            let own_proofs: Vec<_> = proofs.iter()
+                .filter(|proof| proof.proof_purpose == "assertionMethod")
+                .filter(|proof| proof.verification_method.split('#').next() == Some(*issuer))
+                .collect();
+            if own_proofs.is_empty() {
+                unsigned.push(*issuer);
+                continue;
+            }
+
+            let Ok(document) = resolver.resolve(issuer).await else {
+                unsigned.push(*issuer);
+                continue;
+            };
+            let signed = own_proofs.iter().any(|proof| {
+                document.authorized_method(RelationshipType::AssertionMethod, &proof.verification_method)
+                    .and_then(|method| method.key_material().ok())
+                    .is_some_and(|(key_type, public_key)| proof.verify(&payload, &public_key, &key_type))
+            });
+            if !signed {
+                unsigned.push(*issuer);
+            }
+        }
+
+        let signed = issuers.len() - unsigned.len();
+        if signed < min_issuers {
+            return Err(IdentityError::VerificationError(format!(
+                "Only {} of {} required issuers signed credential {}; missing valid proofs from {}",
+                signed, min_issuers, self.id, unsigned.join(", ")
+            )));
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::BTreeMap;
+    use crate::crypto::{generate_secp256k1_keypair, CryptoKeyPair};
+    use crate::resolver::KeyResolver;
+
+    /// A secp256k1 did:key issuer and its verification method id
+    fn issuer() -> (CryptoKeyPair, String, String) {
+        let keypair = generate_secp256k1_keypair().unwrap();
+        let did = crate::did_key::did_key_from_public_key(&keypair.public_key, &keypair.key_type);
+        let method = format!("{}#{}", did, did.trim_start_matches("did:key:"));
+        (keypair, did, method)
+    }
+
+    fn jointly_issued(issuers: &[&(CryptoKeyPair, String, String)]) -> VerifiableCredential {
+        let mut claims = BTreeMap::new();
+        claims.insert("programme".to_string(), serde_json::json!("Joint MSc"));
+        let mut credential = VerifiableCredential::new(String::new(), Some("did:example:alice".to_string()), claims);
+        credential.issuer = JointIssuer::new(issuers.iter().map(|(_, did, _)| did.clone()).collect()).into();
+        credential
+    }
+
+    #[tokio::test]
+    async fn credential_signed_by_both_issuers_is_accepted() {
+        let (first, second) = (issuer(), issuer());
+        let mut credential = jointly_issued(&[&first, &second]);
+        credential.sign(&first.0, first.2.clone()).unwrap();
+        credential.sign(&second.0, second.2.clone()).unwrap();
+
+        credential.verify_joint_issuance(&KeyResolver, 2).await.unwrap();
+        assert_eq!(credential.get_issuer_did(), first.1);
+
+        let round_tripped: VerifiableCredential = serde_json::from_str(&serde_json::to_string(&credential).unwrap()).unwrap();
+        assert_eq!(round_tripped.issuer_dids(), vec![first.1.as_str(), second.1.as_str()]);
+        round_tripped.verify_joint_issuance(&KeyResolver, 2).await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn missing_or_invalid_second_signature_is_rejected() {
+        let (first, second) = (issuer(), issuer());
+        let mut credential = jointly_issued(&[&first, &second]);
+        credential.sign(&first.0, first.2.clone()).unwrap();
+
+        let error = credential.verify_joint_issuance(&KeyResolver, 2).await.unwrap_err();
+        assert!(error.to_string().contains(&format!("missing valid proofs from {}", second.1)), "{}", error);
+        credential.verify_joint_issuance(&KeyResolver, 1).await.unwrap();
+
+        // Signed under the second issuer's method id, but with someone else's key
+        let impostor = generate_secp256k1_keypair().unwrap();
+        credential.sign(&impostor, second.2.clone()).unwrap();
+        assert!(credential.verify_joint_issuance(&KeyResolver, 2).await.is_err());
+        assert!(credential.verify_joint_issuance(&KeyResolver, 3).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn unresolvable_issuer_does_not_block_the_threshold() {
+        let (first, second) = (issuer(), issuer());
+        let mut credential = jointly_issued(&[&first, &second]);
+        if let Issuer::Joint(joint) = &mut credential.issuer {
+            joint.issuers.push("did:example:retired".to_string());
+        }
+        credential.sign(&first.0, first.2.clone()).unwrap();
+        credential.sign(&second.0, second.2.clone()).unwrap();
+        credential.sign(&generate_secp256k1_keypair().unwrap(), "did:example:retired#key-1".to_string()).unwrap();
+
+        credential.verify_joint_issuance(&KeyResolver, 2).await.unwrap();
+        let error = credential.verify_joint_issuance(&KeyResolver, 3).await.unwrap_err();
+        assert!(error.to_string().contains("missing valid proofs from did:example:retired"), "{}", error);
+    }
+
+    #[test]
+    fn duplicate_or_empty_issuer_sets_are_invalid() {
+        let (_, did, _) = issuer();
+
+        assert!(JointIssuer::new(vec![did.clone(), "did:example:other".to_string()]).validate().is_ok());
+        assert!(JointIssuer::new(vec![did.clone(), did]).validate().is_err());
+        assert!(JointIssuer::new(Vec::new()).validate().is_err());
+        assert!(JointIssuer::new(vec!["example:other".to_string()]).validate().is_err());
+    }
+}
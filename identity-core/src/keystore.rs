@@ -0,0 +1,127 @@
+//! Password-encrypted keystore format (PBKDF2-SHA256 + AES-256-GCM)
+
+use aes_gcm::aead::{Aead, KeyInit};
+use aes_gcm::{Aes256Gcm, Nonce};
+use rand::RngCore;
+use rand::rngs::OsRng;
+use serde::{Deserialize, Serialize};
+use sha2::Sha256;
+use crate::crypto::encoding::{decode_base64url, encode_base64url};
+use crate::error::IdentityError;
+
+/// Current keystore format version
+pub const KEYSTORE_VERSION: u32 = 1;
+
+/// Default PBKDF2 iteration count for new keystores
+pub const DEFAULT_KDF_ITERATIONS: u32 = 600_000;
+
+const KDF_NAME: &str = "pbkdf2-sha256";
+const CIPHER_NAME: &str = "aes-256-gcm";
+const SALT_LENGTH: usize = 16;
+const NONCE_LENGTH: usize = 12;
+
+/// Encrypted payload with the parameters needed to decrypt it
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct EncryptedKeystore {
+    pub version: u32,
+    pub kdf: String,
+    pub iterations: u32,
+    pub salt: String,
+    pub cipher: String,
+    pub nonce: String,
+    pub ciphertext: String,
+}
+
+impl EncryptedKeystore {
+    /// Encrypt a payload under a password with the default iteration count
+    pub fn encrypt(plaintext: &[u8], password: &str) -> Result<Self, IdentityError> {
+        Self::encrypt_with_iterations(plaintext, password, DEFAULT_KDF_ITERATIONS)
+    }
+
+    /// Encrypt a payload under a password with a specific PBKDF2 iteration count
+    pub fn encrypt_with_iterations(plaintext: &[u8], password: &str, iterations: u32) -> Result<Self, IdentityError> {
+        if iterations == 0 {
+            return Err(IdentityError::CryptoError("KDF iterations must be positive".to_string()));
+        }
+
+        let mut salt = [0u8; SALT_LENGTH];
+        let mut nonce = [0u8; NONCE_LENGTH];
+        OsRng.fill_bytes(&mut salt);
+        OsRng.fill_bytes(&mut nonce);
+
+        let cipher = Aes256Gcm::new(&derive_key(password, &salt, iterations).into());
+        let ciphertext = cipher.encrypt(Nonce::from_slice(&nonce), plaintext)
+            .map_err(|_| IdentityError::CryptoError("Keystore encryption failed".to_string()))?;
+
+        Ok(Self {
+            version: KEYSTORE_VERSION,
+            kdf: KDF_NAME.to_string(),
+            iterations,
+            salt: encode_base64url(&salt),
+            cipher: CIPHER_NAME.to_string(),
+            nonce: encode_base64url(&nonce),
+            ciphertext: encode_base64url(&ciphertext),
+        })
+    }
+
+    /// Decrypt the payload, failing on a wrong password or tampered data
+    pub fn decrypt(&self, password: &str) -> Result<Vec<u8>, IdentityError> {
+        if self.version != KEYSTORE_VERSION || self.kdf != KDF_NAME || self.cipher != CIPHER_NAME {
+            return Err(IdentityError::CryptoError(format!(
+                "Unsupported keystore: v{} {} {}", self.version, self.kdf, self.cipher
+            )));
+        }
+
+        let salt = decode_base64url(&self.salt)?;
+        let nonce = decode_base64url(&self.nonce)?;
+        if nonce.len() != NONCE_LENGTH {
+            return Err(IdentityError::CryptoError("Invalid keystore nonce".to_string()));
+        }
+
+        let cipher = Aes256Gcm::new(&derive_key(password, &salt, self.iterations).into());
+        cipher.decrypt(Nonce::from_slice(&nonce), decode_base64url(&self.ciphertext)?.as_slice())
+            .map_err(|_| IdentityError::CryptoError("Keystore decryption failed: wrong password or corrupted data".to_string()))
+    }
+}
+
+/// Derive a 256-bit key from a password
+fn derive_key(password: &str, salt: &[u8], iterations: u32) -> [u8; 32] {
+    let mut key = [0u8; 32];
+    pbkdf2::pbkdf2_hmac::<Sha256>(password.as_bytes(), salt, iterations, &mut key);
+    key
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn payload_round_trips_under_the_right_password() {
+        let keystore = EncryptedKeystore::encrypt_with_iterations(b"secret key material", "correct horse", 1_000).unwrap();
+
+        assert_eq!(keystore.iterations, 1_000);
+        assert_eq!(keystore.decrypt("correct horse").unwrap(), b"secret key material");
+        assert!(keystore.decrypt("battery staple").is_err());
+    }
+
+    #[test]
+    fn tampered_or_unsupported_keystores_are_rejected() {
+        let keystore = EncryptedKeystore::encrypt_with_iterations(b"secret", "password", 1_000).unwrap();
+
+        let mut tampered = keystore.clone();
+        let mut ciphertext = decode_base64url(&tampered.ciphertext).unwrap();
+        ciphertext[0] ^= 1;
+        tampered.ciphertext = encode_base64url(&ciphertext);
+        assert!(tampered.decrypt("password").is_err());
+
+        let mut future = keystore.clone();
+        future.version = KEYSTORE_VERSION + 1;
+        assert!(future.decrypt("password").is_err());
+
+        let mut short_nonce = keystore;
+        short_nonce.nonce = encode_base64url(&[0u8; 8]);
+        assert!(short_nonce.decrypt("password").is_err());
+
+        assert!(EncryptedKeystore::encrypt_with_iterations(b"secret", "password", 0).is_err());
+    }
+}
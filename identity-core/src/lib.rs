@@ -4,12 +4,83 @@
 //! This crate implements W3C DID Core and Verifiable Credentials specifications.
 
 pub mod did;
+pub mod did_web;
+pub mod did_jwk;
+pub mod did_key;
+pub mod context;
 pub mod vc;
+pub mod canonicalization;
 pub mod crypto;
+pub mod verification;
+pub mod verification_cache;
+pub mod revocation;
+pub mod report;
+pub mod vc_api;
+pub mod status;
+pub mod issuance;
+pub mod template;
+pub mod schema;
+pub mod timestamp;
+pub mod clock;
+pub mod merkle;
+pub mod content_hash;
+pub mod health;
+pub mod signer;
+pub mod anonymous;
+pub mod disclosure;
+pub mod derivation;
+pub mod prerequisites;
+pub mod rotation;
+pub mod joint_issuance;
+pub mod exchange;
+pub mod resolver;
+pub mod hardware;
+pub mod event_bus;
+pub mod keystore;
+pub mod wallet;
+pub mod import;
+pub mod ndjson;
+#[cfg(feature = "metrics")]
+pub mod metrics;
 pub mod error;
 pub mod utils;
 
 pub use did::*;
+pub use did_web::*;
+pub use did_jwk::*;
+pub use did_key::*;
+pub use context::*;
 pub use vc::*;
+pub use canonicalization::*;
 pub use crypto::*;
+pub use verification::*;
+pub use verification_cache::*;
+pub use revocation::*;
+pub use report::*;
+pub use vc_api::*;
+pub use status::*;
+pub use issuance::*;
+pub use template::*;
+pub use timestamp::*;
+pub use clock::*;
+pub use merkle::*;
+pub use content_hash::*;
+pub use health::*;
+pub use signer::*;
+pub use anonymous::*;
+pub use disclosure::*;
+pub use derivation::*;
+pub use prerequisites::*;
+pub use rotation::*;
+pub use joint_issuance::*;
+pub use exchange::*;
+pub use resolver::*;
+pub use hardware::*;
+pub use event_bus::*;
+pub use keystore::*;
+pub use wallet::*;
+pub use import::*;
+pub use ndjson::*;
+#[cfg(feature = "metrics")]
+pub use metrics::*;
 pub use error::*;
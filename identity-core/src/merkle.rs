@@ -0,0 +1,178 @@
+//! SHA-256 Merkle trees with inclusion proofs
+
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+const LEAF_PREFIX: u8 = 0x00;
+const NODE_PREFIX: u8 = 0x01;
+
+/// Merkle tree over a list of items; leaves and nodes are domain-separated
+#[derive(Debug, Clone)]
+pub struct MerkleTree {
+    levels: Vec<Vec<[u8; 32]>>, // levels[0] holds the leaf hashes, the last level the root
+}
+
+/// Sibling hashes from a leaf up to the root
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct MerkleProof {
+    pub index: usize,
+    pub siblings: Vec<MerkleSibling>,
+}
+
+/// Sibling hash at one level of a proof
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct MerkleSibling {
+    /// Hex-encoded sibling hash
+    pub hash: String,
+    /// Whether the sibling is on the left of the path
+    pub left: bool,
+}
+
+impl MerkleTree {
+    /// Build a tree over the items in order
+    pub fn new<T: AsRef<[u8]>>(items: &[T]) -> Self {
+        let mut levels = vec![items.iter().map(|item| merkle_leaf(item.as_ref())).collect::<Vec<_>>()];
+
+        while levels[levels.len() - 1].len() > 1 {
+            let next = levels[levels.len() - 1]
+                .chunks(2)
+                .map(|pair| match pair {
+                    [left, right] => merkle_node(left, right),
+                    // An odd node is promoted unchanged rather than paired with itself
+                    [single] => *single,
+                    _ => unreachable!(),
+                })
+                .collect();
+            levels.push(next);
+        }
+
+        Self { levels }
+    }
+
+    /// Root hash; the hash of an empty input for an empty tree
+    pub fn root(&self) -> [u8; 32] {
+        self.levels.last()
+            .and_then(|level| level.first().copied())
+            .unwrap_or_else(|| Sha256::digest([]).into())
+    }
+
+    /// Hex-encoded root hash
+    pub fn root_hex(&self) -> String {
+        hex::encode(self.root())
+    }
+
+    /// Number of leaves
+    pub fn len(&self) -> usize {
+        self.levels[0].len()
+    }
+
+    /// Whether the tree has no leaves
+    pub fn is_empty(&self) -> bool {
+        self.levels[0].is_empty()
+    }
+
+    /// Inclusion proof for the leaf at `index`
+    pub fn proof(&self, index: usize) -> Option<MerkleProof> {
+        if index >= self.len() {
+            return None;
+        }
+
+        let mut siblings = Vec::new();
+        let mut position = index;
+        for level in &self.levels[..self.levels.len() - 1] {
+            let sibling = position ^ 1;
+            if sibling < level.len() {
+                siblings.push(MerkleSibling {
+                    hash: hex::encode(level[sibling]),
+                    left: sibling < position,
+                });
+            }
+            position /= 2;
+        }
+
+        Some(MerkleProof { index, siblings })
+    }
+}
+
+/// Check that an item is included under a root
+pub fn verify_merkle_proof(root: &[u8; 32], item: &[u8], proof: &MerkleProof) -> bool {
+    let mut hash = merkle_leaf(item);
+    for sibling in &proof.siblings {
+        let sibling_hash: [u8; 32] = match hex::decode(&sibling.hash).ok().and_then(|bytes| bytes.try_into().ok()) {
+            Some(bytes) => bytes,
+            None => return false,
+        };
+        hash = if sibling.left { merkle_node(&sibling_hash, &hash) } else { merkle_node(&hash, &sibling_hash) };
+    }
+    &hash == root
+}
+
+/// Hash of a leaf item
+pub fn merkle_leaf(item: &[u8]) -> [u8; 32] {
+    let mut hasher = Sha256::new();
+    hasher.update([LEAF_PREFIX]);
+    hasher.update(item);
+    hasher.finalize().into()
+}
+
+/// Hash of an interior node
+fn merkle_node(left: &[u8; 32], right: &[u8; 32]) -> [u8; 32] {
+    let mut hasher = Sha256::new();
+    hasher.update([NODE_PREFIX]);
+    hasher.update(left);
+    hasher.update(right);
+    hasher.finalize().into()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn items(count: usize) -> Vec<String> {
+        (0..count).map(|i| format!("item-{}", i)).collect()
+    }
+
+    #[test]
+    fn every_leaf_proves_inclusion_for_odd_and_even_sizes() {
+        for count in 1..=9 {
+            let items = items(count);
+            let tree = MerkleTree::new(&items);
+            assert_eq!(tree.len(), count);
+
+            for (index, item) in items.iter().enumerate() {
+                let proof = tree.proof(index).unwrap();
+                assert!(verify_merkle_proof(&tree.root(), item.as_bytes(), &proof), "{} of {}", index, count);
+                assert!(!verify_merkle_proof(&tree.root(), b"outsider", &proof));
+            }
+            assert!(tree.proof(count).is_none());
+        }
+    }
+
+    #[test]
+    fn leaves_and_nodes_are_domain_separated() {
+        let pair = MerkleTree::new(&["a", "b"]);
+        assert_eq!(MerkleTree::new(&["a"]).root(), merkle_leaf(b"a"));
+
+        // A leaf whose bytes equal an interior node's preimage does not collide with it
+        let mut forged = vec![NODE_PREFIX];
+        forged.extend_from_slice(&merkle_leaf(b"a"));
+        forged.extend_from_slice(&merkle_leaf(b"b"));
+        assert_ne!(MerkleTree::new(&[forged]).root(), pair.root());
+    }
+
+    #[test]
+    fn tampered_proofs_and_empty_trees() {
+        let items = items(4);
+        let tree = MerkleTree::new(&items);
+        let mut proof = tree.proof(1).unwrap();
+        proof.siblings[0].left = !proof.siblings[0].left;
+        assert!(!verify_merkle_proof(&tree.root(), items[1].as_bytes(), &proof));
+        proof.siblings[0].hash = "not hex".to_string();
+        assert!(!verify_merkle_proof(&tree.root(), items[1].as_bytes(), &proof));
+
+        let empty = MerkleTree::new::<String>(&[]);
+        assert!(empty.is_empty());
+        assert!(empty.proof(0).is_none());
+        assert_eq!(empty.root_hex(), hex::encode(Sha256::digest([])));
+    }
+}
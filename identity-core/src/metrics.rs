@@ -0,0 +1,179 @@
+//! Issuance and verification counters per credential type and issuer
+
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{OnceLock, RwLock};
+use serde::{Deserialize, Serialize};
+use crate::vc::VerifiableCredential;
+
+/// Counters shared by the whole process, updated by signing and full verification
+pub fn credential_metrics() -> &'static CredentialMetrics {
+    static METRICS: OnceLock<CredentialMetrics> = OnceLock::new();
+    METRICS.get_or_init(CredentialMetrics::new)
+}
+
+/// Credential counters keyed by type, then issuer.
+///
+/// Counting an already seen type and issuer takes a read lock and an atomic add; only the
+/// first credential of a new pair allocates.
+#[derive(Debug, Default)]
+pub struct CredentialMetrics {
+    counters: RwLock<HashMap<String, HashMap<String, Counters>>>,
+}
+
+#[derive(Debug, Default)]
+struct Counters {
+    issued: AtomicU64,
+    verified: AtomicU64,
+    verification_failed: AtomicU64,
+}
+
+/// Tally for one credential type and issuer
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CredentialCount {
+    pub credential_type: String,
+    pub issuer: String,
+    pub issued: u64,
+    pub verified: u64,
+    pub verification_failed: u64,
+}
+
+impl CredentialMetrics {
+    /// Create an empty set of counters
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Count a credential being issued
+    pub fn record_issued(&self, credential: &VerifiableCredential) {
+        self.increment(credential, |counters| &counters.issued);
+    }
+
+    /// Count a verification of a credential and whether it passed
+    pub fn record_verified(&self, credential: &VerifiableCredential, passed: bool) {
+        if passed {
+            self.increment(credential, |counters| &counters.verified);
+        } else {
+            self.increment(credential, |counters| &counters.verification_failed);
+        }
+    }
+
+    /// Current tallies, sorted by type then issuer
+    pub fn snapshot(&self) -> Vec<CredentialCount> {
+        let counters = self.counters.read().unwrap_or_else(|poisoned| poisoned.into_inner());
+        let mut counts: Vec<CredentialCount> = counters.iter()
+            .flat_map(|(credential_type, issuers)| issuers.iter().map(move |(issuer, counters)| CredentialCount {
+                credential_type: credential_type.clone(),
+                issuer: issuer.clone(),
+                issued: counters.issued.load(Ordering::Relaxed),
+                verified: counters.verified.load(Ordering::Relaxed),
+                verification_failed: counters.verification_failed.load(Ordering::Relaxed),
+            }))
+            .collect();
+        counts.sort_by(|a, b| (&a.credential_type, &a.issuer).cmp(&(&b.credential_type, &b.issuer)));
+        counts
+    }
+
+    /// Reset every counter
+    pub fn reset(&self) {
+        self.counters.write().unwrap_or_else(|poisoned| poisoned.into_inner()).clear();
+    }
+
+    fn increment(&self, credential: &VerifiableCredential, counter: impl Fn(&Counters) -> &AtomicU64) {
+        let credential_type = primary_type(credential);
+        let issuer = credential.get_issuer_did();
+
+        {
+            let counters = self.counters.read().unwrap_or_else(|poisoned| poisoned.into_inner());
+            if let Some(counters) = counters.get(credential_type).and_then(|issuers| issuers.get(issuer)) {
+                counter(counters).fetch_add(1, Ordering::Relaxed);
+                return;
+            }
+        }
+
+        let mut counters = self.counters.write().unwrap_or_else(|poisoned| poisoned.into_inner());
+        let counters = counters.entry(credential_type.to_string())
+            .or_default()
+            .entry(issuer.to_string())
+            .or_default();
+        counter(counters).fetch_add(1, Ordering::Relaxed);
+    }
+}
+
+/// Most specific type of a credential: its last listed type
+fn primary_type(credential: &VerifiableCredential) -> &str {
+    credential.credential_type.last().map(String::as_str).unwrap_or("VerifiableCredential")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::BTreeMap;
+    use crate::crypto::{generate_secp256k1_keypair, KeyType};
+    use crate::verification::{verify_credential_full, VerificationOptions};
+    use crate::vc::CredentialType;
+
+    fn credential(issuer: &str, credential_type: CredentialType) -> VerifiableCredential {
+        let mut credential = VerifiableCredential::new(issuer.to_string(), Some("did:example:alice".to_string()), BTreeMap::new());
+        credential.add_type(credential_type);
+        credential
+    }
+
+    #[test]
+    fn counters_are_kept_per_type_and_issuer() {
+        let metrics = CredentialMetrics::new();
+        let degree = credential("did:example:university", CredentialType::UniversityDegreeCredential);
+        let kyc = credential("did:example:bank", CredentialType::KycCredential);
+
+        metrics.record_issued(&degree);
+        metrics.record_issued(&degree);
+        metrics.record_issued(&kyc);
+        metrics.record_verified(&degree, true);
+        metrics.record_verified(&kyc, false);
+
+        assert_eq!(metrics.snapshot(), vec![
+            CredentialCount {
+                credential_type: "KycCredential".to_string(),
+                issuer: "did:example:bank".to_string(),
+                issued: 1,
+                verified: 0,
+                verification_failed: 1,
+            },
+            CredentialCount {
+                credential_type: "UniversityDegreeCredential".to_string(),
+                issuer: "did:example:university".to_string(),
+                issued: 2,
+                verified: 1,
+                verification_failed: 0,
+            },
+        ]);
+
+        metrics.reset();
+        assert!(metrics.snapshot().is_empty());
+    }
+
+    #[test]
+    fn issuing_and_verifying_updates_the_global_counters() {
+        // Tests share the process-wide counters, so only this test's issuer is inspected
+        let issuer = format!("did:example:{}", uuid::Uuid::new_v4());
+        let keypair = generate_secp256k1_keypair().unwrap();
+        let options = VerificationOptions::default();
+        for credential_type in [CredentialType::UniversityDegreeCredential, CredentialType::UniversityDegreeCredential, CredentialType::KycCredential] {
+            let mut credential = credential(&issuer, credential_type);
+            credential.sign(&keypair, format!("{}#key-1", issuer)).unwrap();
+            assert!(verify_credential_full(&credential, &keypair.public_key, &KeyType::Secp256k1, &options).verified);
+        }
+        let unsigned = credential(&issuer, CredentialType::KycCredential);
+        assert!(!verify_credential_full(&unsigned, &keypair.public_key, &KeyType::Secp256k1, &options).verified);
+
+        let counts: Vec<(String, u64, u64, u64)> = credential_metrics().snapshot().into_iter()
+            .filter(|count| count.issuer == issuer)
+            .map(|count| (count.credential_type, count.issued, count.verified, count.verification_failed))
+            .collect();
+        assert_eq!(counts, vec![
+            ("KycCredential".to_string(), 1, 1, 1),
+            ("UniversityDegreeCredential".to_string(), 2, 2, 0),
+        ]);
+    }
+}
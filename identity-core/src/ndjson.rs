@@ -0,0 +1,220 @@
+//! Streaming verification of newline-delimited JSON credential files
+
+use serde::{Deserialize, Serialize};
+use tokio::io::{AsyncBufRead, AsyncBufReadExt, AsyncWrite, AsyncWriteExt};
+use crate::did::RelationshipType;
+use crate::error::IdentityError;
+use crate::resolver::DidResolver;
+use crate::vc::VerifiableCredential;
+use crate::verification::{verify_credential_full, VerificationOptions, VerifyMode};
+
+/// Outcome of verifying one line
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LineStatus {
+    /// The credential verified
+    Valid,
+    /// The credential was checked and failed verification
+    Invalid,
+    /// The line could not be checked, e.g. malformed JSON or an unresolvable issuer
+    Error,
+}
+
+/// Result record written for each non-blank input line
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LineResult {
+    /// 1-based line number in the input
+    pub line: usize,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub credential_id: Option<String>,
+    pub status: LineStatus,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub errors: Vec<String>,
+}
+
+/// Running counts over a stream
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct StreamSummary {
+    pub total: usize,
+    pub valid: usize,
+    pub invalid: usize,
+    pub errors: usize,
+}
+
+impl StreamSummary {
+    fn record(&mut self, status: LineStatus) {
+        self.total += 1;
+        match status {
+            LineStatus::Valid => self.valid += 1,
+            LineStatus::Invalid => self.invalid += 1,
+            LineStatus::Error => self.errors += 1,
+        }
+    }
+}
+
+impl LineResult {
+    fn new(line: usize, credential_id: Option<String>, status: LineStatus, errors: Vec<String>) -> Self {
+        Self { line, credential_id, status, errors }
+    }
+}
+
+/// Verify credentials read one per line, writing a JSON result record per line.
+///
+/// Each credential is checked against the key its `assertionMethod` proof names in the
+/// issuer's resolved DID document. Blank lines are skipped; only one line is held at a time.
+pub async fn verify_ndjson_stream<R, W>(
+    reader: R,
+    resolver: &dyn DidResolver,
+    mut writer: W,
+) -> Result<StreamSummary, IdentityError>
+where
+    R: AsyncBufRead + Unpin,
+    W: AsyncWrite + Unpin,
+{
+    let options = VerificationOptions::new(VerifyMode::Collect);
+    let mut summary = StreamSummary::default();
+    let mut lines = reader.lines();
+    let mut line_number = 0;
+
+    while let Some(line) = lines.next_line().await
+        .map_err(|e| IdentityError::StorageError(format!("Failed to read NDJSON input: {}", e)))?
+    {
+        line_number += 1;
+        if line.trim().is_empty() {
+            continue;
+        }
+
+        let result = verify_line(line_number, &line, resolver, &options).await;
+        summary.record(result.status);
+
+        let mut record = serde_json::to_vec(&result)?;
+        record.push(b'\n');
+        writer.write_all(&record).await
+            .map_err(|e| IdentityError::StorageError(format!("Failed to write result record: {}", e)))?;
+    }
+
+    writer.flush().await
+        .map_err(|e| IdentityError::StorageError(format!("Failed to flush result records: {}", e)))?;
+    Ok(summary)
+}
+
+async fn verify_line(
+    line_number: usize,
+    line: &str,
+    resolver: &dyn DidResolver,
+    options: &VerificationOptions,
+) -> LineResult {
+    let credential: VerifiableCredential = match serde_json::from_str(line) {
+        Ok(credential) => credential,
+        Err(e) => return LineResult::new(line_number, None, LineStatus::Error, vec![format!("Invalid credential JSON: {}", e)]),
+    };
+    let id = Some(credential.id.clone());
+
+    let proof = match credential.proof.iter().flatten().find(|proof| proof.proof_purpose == "assertionMethod") {
+        Some(proof) => proof,
+        None => return LineResult::new(line_number, id, LineStatus::Invalid, vec!["Credential has no assertionMethod proof".to_string()]),
+    };
+
+    let issuer = credential.get_issuer_did();
+    if proof.verification_method.split('#').next() != Some(issuer) {
+        return LineResult::new(line_number, id, LineStatus::Invalid, vec![format!(
+            "Verification method {} is not controlled by issuer {}", proof.verification_method, issuer
+        )]);
+    }
+
+    let document = match resolver.resolve(issuer).await {
+        Ok(document) => document,
+        Err(e) => return LineResult::new(line_number, id, LineStatus::Error, vec![format!("Failed to resolve issuer: {}", e)]),
+    };
+
+    let key = document.authorized_method(RelationshipType::AssertionMethod, &proof.verification_method)
+        .ok_or_else(|| format!("{} is not an assertion method of {}", proof.verification_method, issuer))
+        .and_then(|method| method.key_material().map_err(|e| e.to_string()));
+    let (key_type, public_key) = match key {
+        Ok(key) => key,
+        Err(e) => return LineResult::new(line_number, id, LineStatus::Invalid, vec![e]),
+    };
+
+    let report = verify_credential_full(&credential, &public_key, &key_type, options);
+    if report.verified {
+        LineResult::new(line_number, id, LineStatus::Valid, Vec::new())
+    } else {
+        let errors = report.failures().iter()
+            .map(|failure| failure.message.clone().unwrap_or_else(|| format!("{:?} check failed", failure.check)))
+            .collect();
+        LineResult::new(line_number, id, LineStatus::Invalid, errors)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::BTreeMap;
+    use crate::crypto::{generate_secp256k1_keypair, CryptoKeyPair};
+    use crate::did_key::did_key_from_public_key;
+    use crate::resolver::KeyResolver;
+
+    fn issued(keypair: &CryptoKeyPair, degree: &str) -> VerifiableCredential {
+        let did = did_key_from_public_key(&keypair.public_key, &keypair.key_type);
+        let mut claims = BTreeMap::new();
+        claims.insert("degree".to_string(), serde_json::json!(degree));
+        let mut credential = VerifiableCredential::new(did.clone(), Some("did:example:alice".to_string()), claims);
+        let method = format!("{}#{}", did, did.trim_start_matches("did:key:"));
+        credential.sign(keypair, method).unwrap();
+        credential
+    }
+
+    #[tokio::test]
+    async fn mixed_stream_yields_per_line_results_and_a_summary() {
+        let keypair = generate_secp256k1_keypair().unwrap();
+        let valid = issued(&keypair, "BSc");
+        let mut tampered = issued(&keypair, "BSc");
+        tampered.credential_subject.claims.insert("degree".to_string(), serde_json::json!("PhD"));
+        let mut unresolvable = issued(&keypair, "MSc");
+        unresolvable.issuer = crate::vc::Issuer::Did("did:example:issuer".to_string());
+        unresolvable.proof.as_mut().unwrap()[0].verification_method = "did:example:issuer#key-1".to_string();
+
+        let input = format!(
+            "{}\n{}\n\n{{not json\n{}\n",
+            serde_json::to_string(&valid).unwrap(),
+            serde_json::to_string(&tampered).unwrap(),
+            serde_json::to_string(&unresolvable).unwrap(),
+        );
+        let mut output = Vec::new();
+        let summary = verify_ndjson_stream(input.as_bytes(), &KeyResolver, &mut output).await.unwrap();
+
+        assert_eq!(summary, StreamSummary { total: 4, valid: 1, invalid: 1, errors: 2 });
+
+        let results: Vec<LineResult> = String::from_utf8(output).unwrap()
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect();
+        let lines: Vec<(usize, LineStatus)> = results.iter().map(|result| (result.line, result.status)).collect();
+        assert_eq!(lines, vec![
+            (1, LineStatus::Valid),
+            (2, LineStatus::Invalid),
+            (4, LineStatus::Error),
+            (5, LineStatus::Error),
+        ]);
+        assert_eq!(results[0].credential_id.as_deref(), Some(valid.id.as_str()));
+        assert!(results[0].errors.is_empty());
+        assert!(!results[1].errors.is_empty());
+        assert_eq!(results[2].credential_id, None);
+        assert!(results[3].errors[0].starts_with("Failed to resolve issuer"));
+    }
+
+    #[tokio::test]
+    async fn credential_signed_by_another_did_is_invalid() {
+        let keypair = generate_secp256k1_keypair().unwrap();
+        let mut credential = issued(&keypair, "BSc");
+        credential.proof.as_mut().unwrap()[0].verification_method = "did:example:mallory#key-1".to_string();
+
+        let mut output = Vec::new();
+        let input = serde_json::to_string(&credential).unwrap();
+        let summary = verify_ndjson_stream(input.as_bytes(), &KeyResolver, &mut output).await.unwrap();
+
+        assert_eq!(summary, StreamSummary { total: 1, valid: 0, invalid: 1, errors: 0 });
+        let result: LineResult = serde_json::from_slice(&output).unwrap();
+        assert!(result.errors[0].contains("is not controlled by issuer"));
+    }
+}
@@ -0,0 +1,188 @@
+//! Prerequisite credentials a subject must hold for a credential to be valid
+
+use serde::{Deserialize, Serialize};
+use crate::did::RelationshipType;
+use crate::error::IdentityError;
+use crate::resolver::DidResolver;
+use crate::vc::VerifiableCredential;
+
+/// Supporting credential required alongside another, e.g. a degree for a professional license
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct CredentialRequirement {
+    /// Type the supporting credential must carry
+    pub credential_type: String,
+    /// Issuers accepted for the supporting credential; empty accepts any issuer
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub trusted_issuers: Vec<String>,
+}
+
+impl CredentialRequirement {
+    /// Require a credential of a type from any issuer
+    pub fn new(credential_type: &str) -> Self {
+        Self { credential_type: credential_type.to_string(), trusted_issuers: Vec::new() }
+    }
+
+    /// Only accept the supporting credential from these issuers
+    pub fn with_trusted_issuers(mut self, issuers: Vec<String>) -> Self {
+        self.trusted_issuers = issuers;
+        self
+    }
+
+    /// Check type, subject and issuer, ignoring validity
+    fn matches(&self, candidate: &VerifiableCredential, subject: Option<&str>) -> bool {
+        candidate.credential_type.contains(&self.credential_type)
+            && candidate.credential_subject.id.as_deref() == subject
+            && (self.trusted_issuers.is_empty()
+                || self.trusted_issuers.iter().any(|issuer| issuer == candidate.get_issuer_did()))
+    }
+}
+
+impl VerifiableCredential {
+    /// Prerequisites recorded on the credential
+    pub fn prerequisites(&self) -> Vec<CredentialRequirement> {
+        self.extra.get("prerequisites")
+            .and_then(|value| serde_json::from_value(value.clone()).ok())
+            .unwrap_or_default()
+    }
+
+    /// Record prerequisites on the credential; set them before signing so proofs cover them
+    pub fn set_prerequisites(&mut self, prerequisites: Vec<CredentialRequirement>) -> Result<(), IdentityError> {
+        if prerequisites.is_empty() {
+            self.extra.remove("prerequisites");
+        } else {
+            self.extra.insert("prerequisites".to_string(), serde_json::to_value(prerequisites)?);
+        }
+        Ok(())
+    }
+
+    /// Verify the credential and that the subject holds a valid credential for every prerequisite.
+    ///
+    /// Every credential is checked for structure, expiry and an `assertionMethod` proof by a key
+    /// its issuer's resolved DID document publishes. Fails with `MissingPrerequisite` naming the
+    /// first requirement no held credential satisfies.
+    pub async fn verify_with_prerequisites(
+        &self,
+        held: &[VerifiableCredential],
+        resolver: &dyn DidResolver,
+    ) -> Result<(), IdentityError> {
+        self.verify_issuer_proof(resolver).await?;
+
+        let subject = self.credential_subject.id.as_deref();
+        for requirement in self.prerequisites() {
+            let mut satisfied = false;
+            for candidate in held.iter().filter(|candidate| requirement.matches(candidate, subject)) {
+                if candidate.verify_issuer_proof(resolver).await.is_ok() {
+                    satisfied = true;
+                    break;
+                }
+            }
+
+            if !satisfied {
+                return Err(IdentityError::MissingPrerequisite(format!(
+                    "No valid {} credential held by {}",
+                    requirement.credential_type,
+                    subject.unwrap_or("the subject")
+                )));
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Check validity and an assertion proof by a key published in the issuer's DID document
+    async fn verify_issuer_proof(&self, resolver: &dyn DidResolver) -> Result<(), IdentityError> {
+        self.validate()?;
+
+        let issuer = resolver.resolve(self.get_issuer_did()).await?;
+        let payload = self.signing_payload()?;
+        let verified = self.proof.as_deref().unwrap_or_default().iter()
+            .filter(|proof| proof.proof_purpose == "assertionMethod")
+            .any(|proof| {
+                issuer.authorized_method(RelationshipType::AssertionMethod, &proof.verification_method)
+                    .and_then(|method| method.key_material().ok())
+                    .is_some_and(|(key_type, public_key)| proof.verify(&payload, &public_key, &key_type))
+            });
+
+        if !verified {
+            return Err(IdentityError::VerificationError(format!(
+                "Credential {} has no valid proof by its issuer {}", self.id, self.get_issuer_did()
+            )));
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::BTreeMap;
+    use crate::crypto::{generate_ed25519_keypair, CryptoKeyPair};
+    use crate::did_key::did_key_from_public_key;
+    use crate::resolver::KeyResolver;
+    use crate::vc::CredentialType;
+
+    struct Issuer {
+        did: String,
+        keypair: CryptoKeyPair,
+    }
+
+    impl Issuer {
+        fn new() -> Self {
+            let keypair = generate_ed25519_keypair().unwrap();
+            Self { did: did_key_from_public_key(&keypair.public_key, &keypair.key_type), keypair }
+        }
+
+        fn issue(&self, credential_type: CredentialType, subject: &str, prerequisites: Vec<CredentialRequirement>) -> VerifiableCredential {
+            let mut credential = VerifiableCredential::new(self.did.clone(), Some(subject.to_string()), BTreeMap::new());
+            credential.add_type(credential_type);
+            credential.set_prerequisites(prerequisites).unwrap();
+            let method = format!("{}#{}", self.did, self.did.trim_start_matches("did:key:"));
+            credential.sign(&self.keypair, method).unwrap();
+            credential
+        }
+    }
+
+    /// A professional license requiring a degree from `university`
+    fn license(board: &Issuer, university: &Issuer) -> VerifiableCredential {
+        let requirement = CredentialRequirement::new("UniversityDegreeCredential").with_trusted_issuers(vec![university.did.clone()]);
+        board.issue(CredentialType::Custom("ProfessionalLicense".to_string()), "did:example:alice", vec![requirement])
+    }
+
+    #[tokio::test]
+    async fn credential_with_its_prerequisite_held_verifies() {
+        let (board, university) = (Issuer::new(), Issuer::new());
+        let license = license(&board, &university);
+        let degree = university.issue(CredentialType::UniversityDegreeCredential, "did:example:alice", Vec::new());
+
+        assert_eq!(license.prerequisites().len(), 1);
+        license.verify_with_prerequisites(&[degree], &KeyResolver).await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn missing_prerequisite_is_a_specific_error() {
+        let (board, university) = (Issuer::new(), Issuer::new());
+        let license = license(&board, &university);
+
+        match license.verify_with_prerequisites(&[], &KeyResolver).await {
+            Err(IdentityError::MissingPrerequisite(message)) => {
+                assert!(message.contains("UniversityDegreeCredential") && message.contains("did:example:alice"));
+            }
+            other => panic!("expected MissingPrerequisite, got {:?}", other),
+        }
+    }
+
+    #[tokio::test]
+    async fn prerequisites_for_another_subject_or_issuer_or_tampered_do_not_count() {
+        let (board, university, diploma_mill) = (Issuer::new(), Issuer::new(), Issuer::new());
+        let license = license(&board, &university);
+
+        let someone_elses = university.issue(CredentialType::UniversityDegreeCredential, "did:example:bob", Vec::new());
+        let untrusted = diploma_mill.issue(CredentialType::UniversityDegreeCredential, "did:example:alice", Vec::new());
+        let mut tampered = university.issue(CredentialType::UniversityDegreeCredential, "did:example:alice", Vec::new());
+        tampered.credential_subject.claims.insert("degree".to_string(), serde_json::json!("PhD"));
+
+        let result = license.verify_with_prerequisites(&[someone_elses, untrusted, tampered], &KeyResolver).await;
+        assert!(matches!(result, Err(IdentityError::MissingPrerequisite(_))));
+    }
+}
@@ -0,0 +1,187 @@
+//! Versioned, schema-stable JSON verification reports for logging and audit
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use crate::error::IdentityError;
+use crate::verification::{VerificationCheck, VerificationReport};
+
+/// Version written by `report_v1` and accepted by `ReportV1::from_json`
+pub const REPORT_VERSION_V1: u32 = 1;
+
+/// Version 1 of the verification report format.
+///
+/// Field names and check names are part of the format; changing them requires a new version.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct ReportV1 {
+    pub version: u32,
+    /// Credential the report is about
+    pub credential_id: String,
+    /// Overall result: true only if every check passed
+    pub verified: bool,
+    pub checks: Vec<ReportCheckV1>,
+    pub verified_at: DateTime<Utc>,
+    /// Parties that performed the verification
+    #[serde(default)]
+    pub verifiers: Vec<ReportVerifierV1>,
+}
+
+/// Result of a single named check
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct ReportCheckV1 {
+    pub name: String,
+    pub passed: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub message: Option<String>,
+}
+
+/// Identity of a verifier, with its confidence when it reports one
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct ReportVerifierV1 {
+    pub id: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub did: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub evidence_level: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub confidence: Option<f64>,
+}
+
+impl ReportV1 {
+    /// Start a report, deriving the overall result from the checks
+    pub fn new(credential_id: String, checks: Vec<ReportCheckV1>, verified_at: DateTime<Utc>) -> Self {
+        Self {
+            version: REPORT_VERSION_V1,
+            credential_id,
+            verified: checks.iter().all(|check| check.passed),
+            checks,
+            verified_at,
+            verifiers: Vec::new(),
+        }
+    }
+
+    /// Record a verifier that took part
+    pub fn with_verifier(mut self, verifier: ReportVerifierV1) -> Self {
+        self.verifiers.push(verifier);
+        self
+    }
+
+    /// Serialize the report
+    pub fn to_json(&self) -> Result<String, IdentityError> {
+        Ok(serde_json::to_string(self)?)
+    }
+
+    /// Parse a report, rejecting any version other than 1
+    pub fn from_json(json: &str) -> Result<Self, IdentityError> {
+        let value: serde_json::Value = serde_json::from_str(json)?;
+        match value.get("version").and_then(serde_json::Value::as_u64) {
+            Some(version) if version == REPORT_VERSION_V1 as u64 => Ok(serde_json::from_value(value)?),
+            Some(version) => Err(IdentityError::EncodingError(format!("Unsupported report version: {}", version))),
+            None => Err(IdentityError::EncodingError("Report has no version".to_string())),
+        }
+    }
+}
+
+impl ReportCheckV1 {
+    /// Create a check result
+    pub fn new(name: &str, passed: bool, message: Option<String>) -> Self {
+        Self { name: name.to_string(), passed, message }
+    }
+}
+
+impl ReportVerifierV1 {
+    /// Identify a verifier by id
+    pub fn new(id: &str) -> Self {
+        Self { id: id.to_string(), did: None, evidence_level: None, confidence: None }
+    }
+
+    /// Add the verifier's DID
+    pub fn with_did(mut self, did: &str) -> Self {
+        self.did = Some(did.to_string());
+        self
+    }
+}
+
+impl VerificationCheck {
+    /// Name of the check in versioned reports
+    pub fn report_name(&self) -> &'static str {
+        match self {
+            VerificationCheck::Structure => "structure",
+            VerificationCheck::Expiration => "expiration",
+            VerificationCheck::Signature => "signature",
+            VerificationCheck::TrustedIssuer => "trustedIssuer",
+            VerificationCheck::Freshness => "freshness",
+            VerificationCheck::Status => "status",
+        }
+    }
+}
+
+impl VerificationReport {
+    /// Convert to the version 1 report format
+    pub fn report_v1(&self) -> ReportV1 {
+        let checks = self.checks.iter()
+            .map(|check| ReportCheckV1::new(check.check.report_name(), check.passed, check.message.clone()))
+            .collect();
+        ReportV1 {
+            verified: self.verified,
+            ..ReportV1::new(self.credential_id.clone(), checks, self.verified_at)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::BTreeMap;
+    use crate::crypto::{generate_ed25519_keypair, KeyType};
+    use crate::vc::VerifiableCredential;
+    use crate::verification::{verify_credential_full, VerificationOptions, VerifyMode};
+
+    /// Report for an expired credential checked against the right key
+    fn report() -> VerificationReport {
+        let keypair = generate_ed25519_keypair().unwrap();
+        let mut claims = BTreeMap::new();
+        claims.insert("degree".to_string(), serde_json::json!("BSc"));
+        let mut credential = VerifiableCredential::new("did:example:issuer".to_string(), Some("did:example:alice".to_string()), claims);
+        credential.set_expiration(Utc::now() - chrono::Duration::days(1));
+        credential.sign(&keypair, "did:example:issuer#key-1".to_string()).unwrap();
+        verify_credential_full(&credential, &keypair.public_key, &KeyType::Ed25519, &VerificationOptions::new(VerifyMode::Collect))
+    }
+
+    #[test]
+    fn report_round_trips_with_a_stable_shape() {
+        let report = report();
+        let v1 = report.report_v1().with_verifier(ReportVerifierV1::new("v1").with_did("did:example:verifier"));
+
+        let json = v1.to_json().unwrap();
+        assert_eq!(ReportV1::from_json(&json).unwrap(), v1);
+
+        let value: serde_json::Value = serde_json::from_str(&json).unwrap();
+        assert_eq!(value["version"], 1);
+        assert_eq!(value["credentialId"], report.credential_id.as_str());
+        assert_eq!(value["verified"], false);
+        assert!(value["verifiedAt"].is_string());
+        assert_eq!(value["verifiers"][0], serde_json::json!({ "id": "v1", "did": "did:example:verifier" }));
+
+        let names: Vec<&str> = v1.checks.iter().map(|check| check.name.as_str()).collect();
+        assert_eq!(names, vec!["structure", "expiration", "signature"]);
+        let expiration = &value["checks"][1];
+        assert_eq!(expiration["passed"], false);
+        assert!(expiration["message"].is_string());
+        assert!(value["checks"][0].get("message").is_none());
+    }
+
+    #[test]
+    fn future_and_unversioned_reports_are_rejected() {
+        let mut value = serde_json::to_value(report().report_v1()).unwrap();
+
+        value["version"] = serde_json::json!(2);
+        let error = ReportV1::from_json(&value.to_string()).unwrap_err();
+        assert!(error.to_string().contains("Unsupported report version: 2"), "{}", error);
+
+        value.as_object_mut().unwrap().remove("version");
+        assert!(matches!(ReportV1::from_json(&value.to_string()), Err(IdentityError::EncodingError(_))));
+    }
+}
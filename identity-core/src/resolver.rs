@@ -0,0 +1,400 @@
+//! DID resolution with retry and fallback policies
+
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use crate::did::{DidDocument, DidMethod};
+use crate::did_key::resolve_did_key;
+use crate::did_web::resolve_did_web;
+use crate::error::IdentityError;
+use crate::utils::parse_did;
+
+/// Resolves DIDs of one or more methods into DID documents
+#[async_trait]
+pub trait DidResolver: Send + Sync {
+    /// Resolve a DID into its document
+    async fn resolve(&self, did: &str) -> Result<DidDocument, IdentityError>;
+}
+
+/// Resolver for did:key, deriving documents from the key itself
+#[derive(Debug, Clone, Copy, Default)]
+pub struct KeyResolver;
+
+/// Resolver for did:web, fetching documents over HTTPS
+#[derive(Debug, Clone, Copy, Default)]
+pub struct WebResolver;
+
+/// Resolver dispatching each DID to the resolver registered for its method
+#[derive(Default)]
+pub struct ResolverRegistry {
+    resolvers: HashMap<DidMethod, Box<dyn DidResolver>>,
+}
+
+/// Where a resolved document came from
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum ResolutionSource {
+    /// The primary resolver
+    Primary,
+    /// A previously resolved copy
+    Cache,
+    /// The mirror resolver at this position
+    Mirror(usize),
+}
+
+/// Metadata describing how a document was resolved
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ResolutionMetadata {
+    pub source: ResolutionSource,
+    pub attempts: u32,
+    pub retrieved_at: DateTime<Utc>,
+    pub primary_error: Option<String>,
+}
+
+/// Resolved document with its resolution metadata
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ResolutionResult {
+    pub document: DidDocument,
+    pub metadata: ResolutionMetadata,
+}
+
+/// Fallbacks tried, in order, after the primary resolver gives up
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FallbackMethod {
+    /// Serve the last document the primary resolver returned
+    Cache,
+    /// Try each configured mirror resolver
+    Mirror,
+}
+
+/// Retry and fallback behaviour for resolution
+#[derive(Debug, Clone)]
+pub struct ResolutionPolicy {
+    /// Additional attempts after the first transient failure
+    pub retries: u32,
+    /// Delay before the first retry, doubled for each further retry
+    pub backoff: Duration,
+    pub fallback_methods: Vec<FallbackMethod>,
+}
+
+/// Resolver applying a `ResolutionPolicy` around a primary resolver
+pub struct PolicyResolver {
+    primary: Arc<dyn DidResolver>,
+    mirrors: Vec<Arc<dyn DidResolver>>,
+    policy: ResolutionPolicy,
+    cache: Mutex<HashMap<String, DidDocument>>,
+}
+
+#[async_trait]
+impl DidResolver for KeyResolver {
+    async fn resolve(&self, did: &str) -> Result<DidDocument, IdentityError> {
+        resolve_did_key(did)
+    }
+}
+
+#[async_trait]
+impl DidResolver for WebResolver {
+    async fn resolve(&self, did: &str) -> Result<DidDocument, IdentityError> {
+        resolve_did_web(did).await
+    }
+}
+
+impl ResolverRegistry {
+    /// Create a registry with no resolvers
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Create a registry with the built-in did:key and did:web resolvers
+    pub fn with_defaults() -> Self {
+        Self::new()
+            .with_resolver(DidMethod::Key, KeyResolver)
+            .with_resolver(DidMethod::Web, WebResolver)
+    }
+
+    /// Register the resolver for a method, replacing any existing one
+    pub fn with_resolver(mut self, method: DidMethod, resolver: impl DidResolver + 'static) -> Self {
+        self.register(method, Box::new(resolver));
+        self
+    }
+
+    /// Register the resolver for a method, replacing any existing one
+    pub fn register(&mut self, method: DidMethod, resolver: Box<dyn DidResolver>) {
+        self.resolvers.insert(method, resolver);
+    }
+
+    /// Whether a resolver is registered for the method
+    pub fn supports(&self, method: &DidMethod) -> bool {
+        self.resolvers.contains_key(method)
+    }
+}
+
+#[async_trait]
+impl DidResolver for ResolverRegistry {
+    async fn resolve(&self, did: &str) -> Result<DidDocument, IdentityError> {
+        let (_, method, _) = parse_did(did)?;
+        let method = DidMethod::from(method.as_str());
+        let resolver = self.resolvers.get(&method)
+            .ok_or_else(|| IdentityError::NotFound(format!("No resolver registered for did:{}", method)))?;
+        resolver.resolve(did).await
+    }
+}
+
+impl ResolutionPolicy {
+    /// Create a policy with the given retries and initial backoff and no fallbacks
+    pub fn new(retries: u32, backoff: Duration) -> Self {
+        Self {
+            retries,
+            backoff,
+            fallback_methods: Vec::new(),
+        }
+    }
+
+    /// Add a fallback tried after the primary resolver fails
+    pub fn with_fallback(mut self, method: FallbackMethod) -> Self {
+        self.fallback_methods.push(method);
+        self
+    }
+}
+
+impl Default for ResolutionPolicy {
+    fn default() -> Self {
+        Self::new(2, Duration::from_millis(200))
+    }
+}
+
+impl PolicyResolver {
+    /// Wrap a primary resolver with a policy
+    pub fn new(primary: Arc<dyn DidResolver>, policy: ResolutionPolicy) -> Self {
+        Self {
+            primary,
+            mirrors: Vec::new(),
+            policy,
+            cache: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Add a mirror consulted by the `Mirror` fallback
+    pub fn with_mirror(mut self, mirror: Arc<dyn DidResolver>) -> Self {
+        self.mirrors.push(mirror);
+        self
+    }
+
+    /// Resolve a DID, reporting where the document came from
+    pub async fn resolve_with_metadata(&self, did: &str) -> Result<ResolutionResult, IdentityError> {
+        let mut attempts = 0;
+        let primary_error = loop {
+            attempts += 1;
+            match self.primary.resolve(did).await {
+                Ok(document) => {
+                    self.cache.lock().unwrap().insert(did.to_string(), document.clone());
+                    return Ok(ResolutionResult {
+                        document,
+                        metadata: metadata(ResolutionSource::Primary, attempts, None),
+                    });
+                }
+                Err(e) if is_transient(&e) && attempts <= self.policy.retries => {
+                    tokio::time::sleep(self.policy.backoff * 2u32.saturating_pow(attempts - 1)).await;
+                }
+                Err(e) => break e,
+            }
+        };
+
+        for method in &self.policy.fallback_methods {
+            match method {
+                FallbackMethod::Cache => {
+                    let cached = self.cache.lock().unwrap().get(did).cloned();
+                    if let Some(document) = cached {
+                        return Ok(ResolutionResult {
+                            document,
+                            metadata: metadata(ResolutionSource::Cache, attempts, Some(&primary_error)),
+                        });
+                    }
+                }
+                FallbackMethod::Mirror => {
+                    for (index, mirror) in self.mirrors.iter().enumerate() {
+                        if let Ok(document) = mirror.resolve(did).await {
+                            return Ok(ResolutionResult {
+                                document,
+                                metadata: metadata(ResolutionSource::Mirror(index), attempts, Some(&primary_error)),
+                            });
+                        }
+                    }
+                }
+            }
+        }
+
+        Err(primary_error)
+    }
+}
+
+#[async_trait]
+impl DidResolver for PolicyResolver {
+    async fn resolve(&self, did: &str) -> Result<DidDocument, IdentityError> {
+        self.resolve_with_metadata(did).await.map(|result| result.document)
+    }
+}
+
+/// Failures worth retrying; malformed DIDs and missing documents are not
+fn is_transient(error: &IdentityError) -> bool {
+    matches!(error, IdentityError::NetworkError(_) | IdentityError::StorageError(_))
+}
+
+fn metadata(source: ResolutionSource, attempts: u32, primary_error: Option<&IdentityError>) -> ResolutionMetadata {
+    ResolutionMetadata {
+        source,
+        attempts,
+        retrieved_at: Utc::now(),
+        primary_error: primary_error.map(|e| e.to_string()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicU32, Ordering};
+    use crate::crypto::KeyType;
+    use crate::utils::create_did_document_with_id;
+
+    const DID: &str = "did:web:example.com";
+
+    /// Resolver failing with `error` on the calls (counted from 0) picked out by `fails_on`
+    struct FlakyResolver {
+        fails_on: fn(u32) -> bool,
+        error: fn(String) -> IdentityError,
+        calls: AtomicU32,
+        document: DidDocument,
+    }
+
+    impl FlakyResolver {
+        fn new(fails_on: fn(u32) -> bool, error: fn(String) -> IdentityError) -> Arc<Self> {
+            Arc::new(Self {
+                fails_on,
+                error,
+                calls: AtomicU32::new(0),
+                document: create_did_document_with_id(DID.to_string(), KeyType::Ed25519).unwrap().0,
+            })
+        }
+
+        fn up() -> Arc<Self> {
+            Self::new(|_| false, IdentityError::NetworkError)
+        }
+
+        fn down() -> Arc<Self> {
+            Self::new(|_| true, IdentityError::NetworkError)
+        }
+    }
+
+    #[async_trait]
+    impl DidResolver for FlakyResolver {
+        async fn resolve(&self, _did: &str) -> Result<DidDocument, IdentityError> {
+            if (self.fails_on)(self.calls.fetch_add(1, Ordering::SeqCst)) {
+                return Err((self.error)("connection reset".to_string()));
+            }
+            Ok(self.document.clone())
+        }
+    }
+
+    fn policy(retries: u32) -> ResolutionPolicy {
+        ResolutionPolicy::new(retries, Duration::from_millis(1))
+    }
+
+    #[tokio::test]
+    async fn transient_failure_is_retried() {
+        let primary = FlakyResolver::new(|call| call == 0, IdentityError::NetworkError);
+        let resolver = PolicyResolver::new(primary.clone(), policy(2));
+
+        let result = resolver.resolve_with_metadata(DID).await.unwrap();
+
+        assert_eq!(result.document, primary.document);
+        assert_eq!(result.metadata.source, ResolutionSource::Primary);
+        assert_eq!(result.metadata.attempts, 2);
+        assert!(result.metadata.primary_error.is_none());
+    }
+
+    #[tokio::test]
+    async fn mirror_serves_after_primary_failure() {
+        let primary = FlakyResolver::down();
+        let mirror = FlakyResolver::up();
+        let resolver = PolicyResolver::new(primary.clone(), policy(1).with_fallback(FallbackMethod::Mirror))
+            .with_mirror(FlakyResolver::down())
+            .with_mirror(mirror.clone());
+
+        let result = resolver.resolve_with_metadata(DID).await.unwrap();
+
+        assert_eq!(result.document, mirror.document);
+        assert_eq!(result.metadata.source, ResolutionSource::Mirror(1));
+        assert_eq!(result.metadata.attempts, 2);
+        assert!(result.metadata.primary_error.unwrap().contains("connection reset"));
+        assert_eq!(primary.calls.load(Ordering::SeqCst), 2);
+    }
+
+    #[tokio::test]
+    async fn cache_serves_the_last_resolved_document() {
+        let primary = FlakyResolver::new(|call| call > 0, IdentityError::NetworkError);
+        let resolver = PolicyResolver::new(primary.clone(), policy(0).with_fallback(FallbackMethod::Cache));
+        resolver.resolve(DID).await.unwrap();
+
+        let result = resolver.resolve_with_metadata(DID).await.unwrap();
+
+        assert_eq!(result.document, primary.document);
+        assert_eq!(result.metadata.source, ResolutionSource::Cache);
+        assert!(resolver.resolve_with_metadata("did:web:other.example").await.is_err());
+    }
+
+    #[tokio::test]
+    async fn permanent_failures_are_not_retried() {
+        let primary = FlakyResolver::new(|_| true, IdentityError::NotFound);
+        let resolver = PolicyResolver::new(primary.clone(), policy(3));
+
+        assert!(matches!(resolver.resolve(DID).await, Err(IdentityError::NotFound(_))));
+        assert_eq!(primary.calls.load(Ordering::SeqCst), 1);
+    }
+
+    #[tokio::test]
+    async fn registry_dispatches_by_method() {
+        let registry = ResolverRegistry::new()
+            .with_resolver(DidMethod::Web, PolicyResolver::new(FlakyResolver::up(), policy(0)));
+
+        assert!(registry.supports(&DidMethod::Web));
+        assert_eq!(registry.resolve(DID).await.unwrap().id, DID);
+        assert!(matches!(registry.resolve("did:key:z6Mk").await, Err(IdentityError::NotFound(_))));
+    }
+
+    /// Resolver recording the DIDs it was asked for
+    #[derive(Default)]
+    struct RecordingResolver {
+        requested: Mutex<Vec<String>>,
+    }
+
+    #[async_trait]
+    impl DidResolver for Arc<RecordingResolver> {
+        async fn resolve(&self, did: &str) -> Result<DidDocument, IdentityError> {
+            self.requested.lock().unwrap().push(did.to_string());
+            Ok(DidDocument::new(did.to_string()))
+        }
+    }
+
+    #[tokio::test]
+    async fn each_method_prefix_reaches_its_own_resolver() {
+        let web = Arc::new(RecordingResolver::default());
+        let example = Arc::new(RecordingResolver::default());
+        let registry = ResolverRegistry::with_defaults()
+            .with_resolver(DidMethod::Web, web.clone())
+            .with_resolver(DidMethod::Custom("example".to_string()), example.clone());
+
+        registry.resolve("did:web:example.com:alice").await.unwrap();
+        registry.resolve("did:example:123").await.unwrap();
+        registry.resolve("did:web:example.org").await.unwrap();
+        let key = registry.resolve("did:key:z6MkhaXgBZDvotDkL5257faiztiGiC2QtKLGpbnnEGta2doK").await.unwrap();
+
+        assert_eq!(*web.requested.lock().unwrap(), vec!["did:web:example.com:alice", "did:web:example.org"]);
+        assert_eq!(*example.requested.lock().unwrap(), vec!["did:example:123"]);
+        assert!(key.verification_method.is_some());
+        assert!(!registry.supports(&DidMethod::Ethr));
+        assert!(matches!(registry.resolve("did:ethr:0x1234").await, Err(IdentityError::NotFound(_))));
+        assert!(matches!(registry.resolve("not-a-did").await, Err(IdentityError::InvalidDid(_))));
+    }
+}
@@ -0,0 +1,142 @@
+//! Cache of credential revocation status, kept current by revocation events
+
+use std::collections::HashMap;
+use chrono::{DateTime, Duration, Utc};
+use crate::event_bus::{DomainEvent, EventHandler};
+
+/// Revocation status last seen for a credential
+#[derive(Debug, Clone, PartialEq)]
+pub struct RevocationStatus {
+    pub revoked: bool,
+    pub reason: Option<String>,
+    pub checked_at: DateTime<Utc>,
+}
+
+/// Revocation statuses keyed by credential id.
+///
+/// Statuses looked up from a registry expire after a TTL; revocations received as events are
+/// kept until cleared, since a revoked credential only becomes valid again through a reinstatement event.
+#[derive(Debug, Clone)]
+pub struct RevocationCache {
+    ttl: Duration,
+    entries: HashMap<String, RevocationStatus>,
+}
+
+impl RevocationCache {
+    /// Create a cache holding looked-up statuses for `ttl`
+    pub fn new(ttl: Duration) -> Self {
+        Self { ttl, entries: HashMap::new() }
+    }
+
+    /// Record a status looked up from the registry
+    pub fn insert(&mut self, credential_id: String, revoked: bool, reason: Option<String>) {
+        self.entries.insert(credential_id, RevocationStatus { revoked, reason, checked_at: Utc::now() });
+    }
+
+    /// Live status for a credential, if cached
+    pub fn get(&self, credential_id: &str) -> Option<&RevocationStatus> {
+        self.entries.get(credential_id)
+            .filter(|status| status.revoked || status.checked_at + self.ttl > Utc::now())
+    }
+
+    /// Whether the credential is known to be revoked
+    pub fn is_revoked(&self, credential_id: &str) -> bool {
+        self.get(credential_id).is_some_and(|status| status.revoked)
+    }
+
+    /// Forget the status of a credential, e.g. after it is reinstated
+    pub fn invalidate(&mut self, credential_id: &str) {
+        self.entries.remove(credential_id);
+    }
+
+    /// Remove expired entries
+    pub fn purge_expired(&mut self) {
+        let now = Utc::now();
+        let ttl = self.ttl;
+        self.entries.retain(|_, status| status.revoked || status.checked_at + ttl > now);
+    }
+
+    /// Number of cached statuses
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Whether the cache is empty
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Clear all cached statuses
+    pub fn clear(&mut self) {
+        self.entries.clear();
+    }
+}
+
+impl EventHandler for RevocationCache {
+    /// Record revocations as they happen and forget reinstated credentials
+    fn handle_event(&mut self, event: &DomainEvent) {
+        match event {
+            DomainEvent::CredentialRevoked { credential_id, reason } => {
+                self.insert(credential_id.clone(), true, Some(reason.clone()));
+            }
+            DomainEvent::CredentialReinstated { credential_id } => self.invalidate(credential_id),
+            _ => {}
+        }
+    }
+
+    /// A missed event may have been a revocation, so cached "not revoked" statuses are dropped
+    fn handle_lagged(&mut self, _missed: u64) {
+        self.entries.retain(|_, status| status.revoked);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn revoked(credential_id: &str) -> DomainEvent {
+        DomainEvent::CredentialRevoked {
+            credential_id: credential_id.to_string(),
+            reason: "compromised".to_string(),
+        }
+    }
+
+    #[test]
+    fn revocation_event_is_recorded_until_reinstatement() {
+        let mut cache = RevocationCache::new(Duration::minutes(5));
+        cache.insert("urn:uuid:1".to_string(), false, None);
+        assert!(!cache.is_revoked("urn:uuid:1"));
+
+        cache.handle_event(&revoked("urn:uuid:1"));
+        let status = cache.get("urn:uuid:1").unwrap();
+        assert!(status.revoked);
+        assert_eq!(status.reason.as_deref(), Some("compromised"));
+
+        cache.handle_event(&DomainEvent::CredentialReinstated { credential_id: "urn:uuid:1".to_string() });
+        assert!(cache.get("urn:uuid:1").is_none());
+    }
+
+    #[test]
+    fn looked_up_statuses_expire_but_revocations_do_not() {
+        let mut cache = RevocationCache::new(Duration::zero());
+        cache.insert("urn:uuid:valid".to_string(), false, None);
+        cache.handle_event(&revoked("urn:uuid:revoked"));
+
+        assert!(cache.get("urn:uuid:valid").is_none());
+        assert!(cache.is_revoked("urn:uuid:revoked"));
+        cache.purge_expired();
+        assert_eq!(cache.len(), 1);
+    }
+
+    #[test]
+    fn missed_events_drop_unrevoked_statuses() {
+        let mut cache = RevocationCache::new(Duration::minutes(5));
+        cache.insert("urn:uuid:valid".to_string(), false, None);
+        cache.handle_event(&revoked("urn:uuid:revoked"));
+
+        cache.handle_lagged(3);
+
+        assert!(cache.get("urn:uuid:valid").is_none());
+        assert!(cache.is_revoked("urn:uuid:revoked"));
+    }
+}
@@ -0,0 +1,222 @@
+//! Key rotation announcements, signed by both the retiring and the replacement key
+
+use std::collections::BTreeMap;
+use serde::{Deserialize, Serialize};
+use crate::crypto::encoding::{decode_multikey, encode_multikey};
+use crate::crypto::{hash_data, KeyType};
+use crate::did::DidDocument;
+use crate::error::IdentityError;
+use crate::signer::Signer;
+use crate::vc::{Proof, VerifiableCredential};
+
+/// Credential type of rotation announcements
+pub const KEY_ROTATION_CREDENTIAL_TYPE: &str = "KeyRotationCredential";
+
+/// Rotation asserted by a `KeyRotationCredential`, carried as its subject's claims
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct KeyRotation {
+    /// Verification method retired by the rotation
+    pub previous_verification_method: String,
+    /// Multikey encoding of the retired public key
+    pub previous_public_key_multibase: String,
+    /// Verification method that replaces it
+    pub new_verification_method: String,
+    /// Multikey encoding of the replacement public key
+    pub new_public_key_multibase: String,
+    /// Hex SHA-256 of the canonical DID document published with the rotation
+    pub did_document_hash: String,
+}
+
+/// Issue a rotation announcement for `updated_document`, the DID document after the rotation.
+///
+/// The credential is issued by the DID to itself and carries one assertion proof from each key,
+/// so relying parties holding credentials signed by the old key can follow it to the new one.
+pub async fn issue_key_rotation_credential(
+    updated_document: &DidDocument,
+    previous_method: &str,
+    previous_signer: &dyn Signer,
+    new_method: &str,
+    new_signer: &dyn Signer,
+) -> Result<VerifiableCredential, IdentityError> {
+    let rotation = KeyRotation {
+        previous_verification_method: previous_method.to_string(),
+        previous_public_key_multibase: encode_multikey(previous_signer.public_key(), &previous_signer.key_type()),
+        new_verification_method: new_method.to_string(),
+        new_public_key_multibase: encode_multikey(new_signer.public_key(), &new_signer.key_type()),
+        did_document_hash: document_hash(updated_document)?,
+    };
+    // Check the document before signing anything, so a bad rotation is never announced
+    rotation.check_document(updated_document)?;
+
+    let claims: BTreeMap<String, serde_json::Value> = serde_json::from_value(serde_json::to_value(&rotation)?)?;
+    let mut credential = VerifiableCredential::new(updated_document.id.clone(), Some(updated_document.id.clone()), claims);
+    credential.credential_type.push(KEY_ROTATION_CREDENTIAL_TYPE.to_string());
+
+    credential.sign_with(previous_signer, previous_method.to_string()).await?;
+    credential.sign_with(new_signer, new_method.to_string()).await?;
+    Ok(credential)
+}
+
+impl VerifiableCredential {
+    /// Rotation this credential announces, if it is a `KeyRotationCredential`
+    pub fn key_rotation(&self) -> Option<KeyRotation> {
+        if !self.credential_type.iter().any(|t| t == KEY_ROTATION_CREDENTIAL_TYPE) {
+            return None;
+        }
+        serde_json::to_value(&self.credential_subject.claims).ok()
+            .and_then(|claims| serde_json::from_value(claims).ok())
+    }
+
+    /// Verify a rotation announcement against the DID document published with the rotation.
+    ///
+    /// Both the retiring and the replacement key must have signed it, and the document must
+    /// hash to the value the announcement commits to and publish the new key in place of the old.
+    pub fn verify_key_rotation(&self, updated_document: &DidDocument) -> Result<KeyRotation, IdentityError> {
+        self.validate()?;
+        let rotation = self.key_rotation().ok_or_else(|| {
+            IdentityError::InvalidCredential(format!("Credential {} is not a key rotation credential", self.id))
+        })?;
+
+        let did = self.get_issuer_did();
+        if self.credential_subject.id.as_deref() != Some(did) || updated_document.id != did {
+            return Err(IdentityError::VerificationError(format!(
+                "Key rotation for {} must be issued by and about that DID", updated_document.id
+            )));
+        }
+
+        let payload = self.signing_payload()?;
+        let proofs = self.proof.as_deref().unwrap_or_default();
+        for (method, key, role) in [
+            (&rotation.previous_verification_method, &rotation.previous_public_key_multibase, "previous"),
+            (&rotation.new_verification_method, &rotation.new_public_key_multibase, "new"),
+        ] {
+            let (key_type, public_key) = decode_multikey(key)?;
+            if !signed_by(proofs, &payload, method, &public_key, &key_type) {
+                return Err(IdentityError::VerificationError(format!(
+                    "Key rotation is missing a valid signature by the {} key {}", role, method
+                )));
+            }
+        }
+
+        if document_hash(updated_document)? != rotation.did_document_hash {
+            return Err(IdentityError::VerificationError(
+                "DID document does not match the one the rotation was announced with".to_string()
+            ));
+        }
+        rotation.check_document(updated_document)?;
+        Ok(rotation)
+    }
+}
+
+impl KeyRotation {
+    /// Check the document publishes the new key and no longer publishes the old one
+    fn check_document(&self, document: &DidDocument) -> Result<(), IdentityError> {
+        let published = |method_id: &str| document.verification_method.as_deref().unwrap_or_default()
+            .iter()
+            .find(|method| method.id == method_id)
+            .and_then(|method| method.key_material().ok());
+
+        if published(&self.new_verification_method) != Some(decode_multikey(&self.new_public_key_multibase)?) {
+            return Err(IdentityError::VerificationError(format!(
+                "DID document does not publish the new key {}", self.new_verification_method
+            )));
+        }
+        if published(&self.previous_verification_method) == Some(decode_multikey(&self.previous_public_key_multibase)?) {
+            return Err(IdentityError::VerificationError(format!(
+                "DID document still publishes the previous key {}", self.previous_verification_method
+            )));
+        }
+        Ok(())
+    }
+}
+
+/// Hex SHA-256 of a document's canonical form
+fn document_hash(document: &DidDocument) -> Result<String, IdentityError> {
+    Ok(hex::encode(hash_data(&document.canonical_bytes()?)))
+}
+
+/// Whether a proof by `method` carries a valid signature by the given key
+fn signed_by(proofs: &[Proof], payload: &[u8], method: &str, public_key: &[u8], key_type: &KeyType) -> bool {
+    proofs.iter()
+        .filter(|proof| proof.verification_method == method)
+        .any(|proof| proof.verify(payload, public_key, key_type))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::crypto::generate_ed25519_keypair;
+    use crate::did::{PublicKeyFormat, VerificationMethod};
+    use crate::signer::InMemorySigner;
+
+    const DID: &str = "did:example:alice";
+
+    fn method(id: &str, signer: &InMemorySigner) -> VerificationMethod {
+        VerificationMethod {
+            id: format!("{}#{}", DID, id),
+            method_type: signer.key_type().to_string(),
+            controller: DID.to_string(),
+            public_key: PublicKeyFormat::Multibase {
+                public_key_multibase: encode_multikey(signer.public_key(), &signer.key_type()),
+            },
+        }
+    }
+
+    /// Document after rotating from `key-1` to `key-2`, publishing only the new key
+    fn rotated_document(new_signer: &InMemorySigner) -> DidDocument {
+        let mut document = DidDocument::new(DID.to_string());
+        document.add_verification_method(method("key-2", new_signer));
+        document
+    }
+
+    fn signers() -> (InMemorySigner, InMemorySigner) {
+        (InMemorySigner::new(generate_ed25519_keypair().unwrap()), InMemorySigner::new(generate_ed25519_keypair().unwrap()))
+    }
+
+    async fn rotation_credential(document: &DidDocument, old: &InMemorySigner, new: &InMemorySigner) -> VerifiableCredential {
+        let (old_id, new_id) = (format!("{}#key-1", DID), format!("{}#key-2", DID));
+        issue_key_rotation_credential(document, &old_id, old, &new_id, new).await.unwrap()
+    }
+
+    #[tokio::test]
+    async fn rotation_signed_by_old_and_new_keys_verifies() {
+        let (old, new) = signers();
+        let document = rotated_document(&new);
+        let credential = rotation_credential(&document, &old, &new).await;
+
+        assert_eq!(credential.proof.as_ref().unwrap().len(), 2);
+        let rotation = credential.verify_key_rotation(&document).unwrap();
+        assert_eq!(rotation.previous_verification_method, format!("{}#key-1", DID));
+        assert_eq!(rotation.new_public_key_multibase, encode_multikey(new.public_key(), &new.key_type()));
+        assert_eq!(credential.key_rotation(), Some(rotation));
+    }
+
+    #[tokio::test]
+    async fn rotation_missing_the_old_key_signature_is_rejected() {
+        let (old, new) = signers();
+        let document = rotated_document(&new);
+        let mut credential = rotation_credential(&document, &old, &new).await;
+        credential.proof.as_mut().unwrap().remove(0);
+
+        let error = credential.verify_key_rotation(&document).unwrap_err();
+        assert!(error.to_string().contains("by the previous key"), "{}", error);
+    }
+
+    #[tokio::test]
+    async fn rotation_is_linked_to_the_document_it_was_announced_with() {
+        let (old, new) = signers();
+        let document = rotated_document(&new);
+        let credential = rotation_credential(&document, &old, &new).await;
+
+        let mut later = document.clone();
+        later.add_verification_method(method("key-3", &signers().0));
+        assert!(credential.verify_key_rotation(&later).unwrap_err().to_string().contains("does not match"));
+
+        // A document still publishing the old key cannot announce a rotation
+        let mut unrotated = rotated_document(&new);
+        unrotated.add_verification_method(method("key-1", &old));
+        let (old_id, new_id) = (format!("{}#key-1", DID), format!("{}#key-2", DID));
+        assert!(issue_key_rotation_credential(&unrotated, &old_id, &old, &new_id, &new).await.is_err());
+    }
+}
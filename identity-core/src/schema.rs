@@ -0,0 +1,169 @@
+//! Lightweight JSON Schema validation for credential claims
+//!
+//! Supports the commonly used subset of JSON Schema: `type`, `enum`, `const`,
+//! `required`, `properties`, `additionalProperties`, `items`, numeric bounds,
+//! string length bounds, `pattern`, array length bounds, and `contains`.
+
+use serde_json::Value;
+use crate::error::IdentityError;
+
+/// Validate a JSON value against a schema, reporting every violation
+pub fn validate_json_schema(instance: &Value, schema: &Value) -> Result<(), IdentityError> {
+    let errors = schema_errors(instance, schema);
+    if errors.is_empty() {
+        Ok(())
+    } else {
+        Err(IdentityError::InvalidCredential(format!("Schema validation failed: {}", errors.join("; "))))
+    }
+}
+
+/// Collect all schema violations for a JSON value
+pub fn schema_errors(instance: &Value, schema: &Value) -> Vec<String> {
+    let mut errors = Vec::new();
+    check(instance, schema, "$", &mut errors);
+    errors
+}
+
+fn check(instance: &Value, schema: &Value, path: &str, errors: &mut Vec<String>) {
+    let schema = match schema {
+        Value::Object(schema) => schema,
+        Value::Bool(false) => {
+            errors.push(format!("{}: no value is allowed", path));
+            return;
+        }
+        _ => return,
+    };
+
+    if let Some(expected) = schema.get("type") {
+        let matches = match expected {
+            Value::String(t) => type_matches(instance, t),
+            Value::Array(types) => types.iter().filter_map(|t| t.as_str()).any(|t| type_matches(instance, t)),
+            _ => true,
+        };
+        if !matches {
+            errors.push(format!("{}: expected type {}", path, expected));
+            return;
+        }
+    }
+
+    if let Some(Value::Array(allowed)) = schema.get("enum") {
+        if !allowed.contains(instance) {
+            errors.push(format!("{}: value is not one of the allowed values", path));
+        }
+    }
+
+    if let Some(expected) = schema.get("const") {
+        if expected != instance {
+            errors.push(format!("{}: value must equal {}", path, expected));
+        }
+    }
+
+    match instance {
+        Value::Object(object) => {
+            if let Some(Value::Array(required)) = schema.get("required") {
+                for field in required.iter().filter_map(|f| f.as_str()) {
+                    if !object.contains_key(field) {
+                        errors.push(format!("{}: missing required property '{}'", path, field));
+                    }
+                }
+            }
+
+            let properties = schema.get("properties").and_then(|p| p.as_object());
+            for (key, value) in object {
+                let child_path = format!("{}.{}", path, key);
+                match properties.and_then(|p| p.get(key)) {
+                    Some(property_schema) => check(value, property_schema, &child_path, errors),
+                    None => match schema.get("additionalProperties") {
+                        Some(Value::Bool(false)) => {
+                            errors.push(format!("{}: additional property is not allowed", child_path));
+                        }
+                        Some(additional @ Value::Object(_)) => check(value, additional, &child_path, errors),
+                        _ => {}
+                    },
+                }
+            }
+        }
+        Value::Array(items) => {
+            if let Some(min) = schema.get("minItems").and_then(|v| v.as_u64()) {
+                if (items.len() as u64) < min {
+                    errors.push(format!("{}: expected at least {} items", path, min));
+                }
+            }
+            if let Some(max) = schema.get("maxItems").and_then(|v| v.as_u64()) {
+                if items.len() as u64 > max {
+                    errors.push(format!("{}: expected at most {} items", path, max));
+                }
+            }
+            if let Some(contains) = schema.get("contains") {
+                if !items.iter().any(|item| schema_errors(item, contains).is_empty()) {
+                    errors.push(format!("{}: no item matches the 'contains' schema", path));
+                }
+            }
+            if let Some(item_schema) = schema.get("items") {
+                for (index, item) in items.iter().enumerate() {
+                    check(item, item_schema, &format!("{}[{}]", path, index), errors);
+                }
+            }
+        }
+        Value::String(string) => {
+            let length = string.chars().count() as u64;
+            if let Some(min) = schema.get("minLength").and_then(|v| v.as_u64()) {
+                if length < min {
+                    errors.push(format!("{}: expected at least {} characters", path, min));
+                }
+            }
+            if let Some(max) = schema.get("maxLength").and_then(|v| v.as_u64()) {
+                if length > max {
+                    errors.push(format!("{}: expected at most {} characters", path, max));
+                }
+            }
+            if let Some(pattern) = schema.get("pattern").and_then(|v| v.as_str()) {
+                match regex::Regex::new(pattern) {
+                    Ok(re) if !re.is_match(string) => {
+                        errors.push(format!("{}: does not match pattern '{}'", path, pattern));
+                    }
+                    Err(e) => errors.push(format!("{}: invalid pattern '{}': {}", path, pattern, e)),
+                    _ => {}
+                }
+            }
+        }
+        Value::Number(number) => {
+            if let Some(value) = number.as_f64() {
+                if let Some(min) = schema.get("minimum").and_then(|v| v.as_f64()) {
+                    if value < min {
+                        errors.push(format!("{}: must be >= {}", path, min));
+                    }
+                }
+                if let Some(max) = schema.get("maximum").and_then(|v| v.as_f64()) {
+                    if value > max {
+                        errors.push(format!("{}: must be <= {}", path, max));
+                    }
+                }
+                if let Some(min) = schema.get("exclusiveMinimum").and_then(|v| v.as_f64()) {
+                    if value <= min {
+                        errors.push(format!("{}: must be > {}", path, min));
+                    }
+                }
+                if let Some(max) = schema.get("exclusiveMaximum").and_then(|v| v.as_f64()) {
+                    if value >= max {
+                        errors.push(format!("{}: must be < {}", path, max));
+                    }
+                }
+            }
+        }
+        _ => {}
+    }
+}
+
+fn type_matches(instance: &Value, expected: &str) -> bool {
+    match expected {
+        "object" => instance.is_object(),
+        "array" => instance.is_array(),
+        "string" => instance.is_string(),
+        "number" => instance.is_number(),
+        "integer" => instance.is_i64() || instance.is_u64(),
+        "boolean" => instance.is_boolean(),
+        "null" => instance.is_null(),
+        _ => false,
+    }
+}
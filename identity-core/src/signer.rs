@@ -0,0 +1,125 @@
+//! Signing key abstraction so private keys can live outside process memory
+
+use async_trait::async_trait;
+use crate::crypto::{CryptoKeyPair, KeyType, sign_data};
+use crate::error::IdentityError;
+
+/// A signing key that may be held in memory, an HSM, or a remote KMS
+#[async_trait]
+pub trait Signer: Send + Sync {
+    /// Sign data, returning the raw signature bytes
+    async fn sign(&self, data: &[u8]) -> Result<Vec<u8>, IdentityError>;
+
+    /// Public key matching the signing key
+    fn public_key(&self) -> &[u8];
+
+    /// Type of the signing key
+    fn key_type(&self) -> KeyType;
+}
+
+/// Signer backed by a key pair held in memory
+#[derive(Debug, Clone)]
+pub struct InMemorySigner {
+    keypair: CryptoKeyPair,
+}
+
+impl InMemorySigner {
+    /// Wrap a key pair
+    pub fn new(keypair: CryptoKeyPair) -> Self {
+        Self { keypair }
+    }
+
+    /// Get the wrapped key pair
+    pub fn keypair(&self) -> &CryptoKeyPair {
+        &self.keypair
+    }
+}
+
+#[async_trait]
+impl Signer for InMemorySigner {
+    async fn sign(&self, data: &[u8]) -> Result<Vec<u8>, IdentityError> {
+        sign_data(data, &self.keypair.private_key, &self.keypair.key_type)
+    }
+
+    fn public_key(&self) -> &[u8] {
+        &self.keypair.public_key
+    }
+
+    fn key_type(&self) -> KeyType {
+        self.keypair.key_type.clone()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::BTreeMap;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use crate::crypto::{generate_ed25519_keypair, generate_secp256k1_keypair};
+    use crate::vc::{VerifiableCredential, VerifiablePresentation};
+
+    /// Stands in for a KMS: the private key only exists inside a spawned task
+    struct RemoteSigner {
+        public_key: Vec<u8>,
+        key_type: KeyType,
+        keypair: CryptoKeyPair,
+        requests: AtomicUsize,
+    }
+
+    #[async_trait]
+    impl Signer for RemoteSigner {
+        async fn sign(&self, data: &[u8]) -> Result<Vec<u8>, IdentityError> {
+            self.requests.fetch_add(1, Ordering::SeqCst);
+            let (data, keypair) = (data.to_vec(), self.keypair.clone());
+            tokio::spawn(async move { sign_data(&data, &keypair.private_key, &keypair.key_type) })
+                .await
+                .map_err(|e| IdentityError::CryptoError(e.to_string()))?
+        }
+
+        fn public_key(&self) -> &[u8] {
+            &self.public_key
+        }
+
+        fn key_type(&self) -> KeyType {
+            self.key_type.clone()
+        }
+    }
+
+    fn credential() -> VerifiableCredential {
+        let mut claims = BTreeMap::new();
+        claims.insert("degree".to_string(), serde_json::json!("BSc"));
+        VerifiableCredential::new("did:example:issuer".to_string(), Some("did:example:alice".to_string()), claims)
+    }
+
+    #[tokio::test]
+    async fn in_memory_signer_proofs_verify() {
+        for keypair in [generate_ed25519_keypair().unwrap(), generate_secp256k1_keypair().unwrap()] {
+            let signer = InMemorySigner::new(keypair.clone());
+            let mut credential = credential();
+            credential.sign_with(&signer, "did:example:issuer#key-1".to_string()).await.unwrap();
+
+            assert!(credential.verify_proof(signer.public_key(), &signer.key_type()).unwrap());
+            assert_eq!(signer.keypair().public_key, keypair.public_key);
+        }
+    }
+
+    #[tokio::test]
+    async fn remote_signer_proofs_verify() {
+        let keypair = generate_ed25519_keypair().unwrap();
+        let signer = RemoteSigner {
+            public_key: keypair.public_key.clone(),
+            key_type: KeyType::Ed25519,
+            keypair,
+            requests: AtomicUsize::new(0),
+        };
+
+        let mut credential = credential();
+        credential.sign_with(&signer, "did:example:issuer#key-1".to_string()).await.unwrap();
+        let mut presentation = VerifiablePresentation::new(vec![credential.clone()], Some("did:example:alice".to_string()));
+        presentation.sign_with(&signer, "did:example:alice#key-1".to_string()).await.unwrap();
+
+        assert_eq!(signer.requests.load(Ordering::SeqCst), 2);
+        assert!(credential.verify_proof(signer.public_key(), &KeyType::Ed25519).unwrap());
+        assert!(presentation.verify_proof(signer.public_key(), &KeyType::Ed25519).unwrap());
+    }
+}
@@ -0,0 +1,312 @@
+//! Credential status checking dispatched on `credentialStatus.type`
+
+use std::collections::HashMap;
+use std::io::{Read, Write};
+use std::sync::{Arc, RwLock};
+use flate2::read::GzDecoder;
+use flate2::write::GzEncoder;
+use flate2::Compression;
+use serde::{Deserialize, Serialize};
+use crate::error::IdentityError;
+use crate::crypto::encoding::{decode_base64url, encode_base64url};
+use crate::vc::{CredentialStatus, VerifiableCredential};
+
+/// Status of a credential as reported by its status list
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum StatusOutcome {
+    Valid,
+    Revoked,
+    Suspended,
+}
+
+/// Checks credential status entries of one `credentialStatus.type`
+pub trait StatusChecker: Send + Sync {
+    /// Resolve the status an entry points to
+    fn check(&self, status: &CredentialStatus) -> Result<StatusOutcome, IdentityError>;
+}
+
+/// Bitstring where bit `i` is the most significant bit of byte `i / 8` first
+#[derive(Debug, Clone, PartialEq)]
+pub struct StatusList {
+    bits: Vec<u8>,
+}
+
+/// Status lists keyed by the URL of the status list credential; clones share the same lists
+#[derive(Debug, Clone, Default)]
+pub struct StatusListStore {
+    lists: Arc<RwLock<HashMap<String, StatusList>>>,
+}
+
+/// Checker for bitstring status lists such as `StatusList2021Entry` and `RevocationList2020Status`
+pub struct BitstringStatusChecker {
+    store: StatusListStore,
+    list_property: &'static str,
+    index_property: &'static str,
+    /// Purpose used when the entry has no `statusPurpose`
+    default_purpose: StatusOutcome,
+}
+
+/// Status checkers keyed by `credentialStatus.type`
+#[derive(Clone, Default)]
+pub struct StatusCheckerRegistry {
+    checkers: HashMap<String, Arc<dyn StatusChecker>>,
+}
+
+impl StatusList {
+    /// Create a list of `size` bits, all unset
+    pub fn new(size: usize) -> Self {
+        Self { bits: vec![0; size.div_ceil(8)] }
+    }
+
+    /// Number of bits in the list
+    pub fn len(&self) -> usize {
+        self.bits.len() * 8
+    }
+
+    /// Whether the list has no bits
+    pub fn is_empty(&self) -> bool {
+        self.bits.is_empty()
+    }
+
+    /// Get the bit at an index
+    pub fn get(&self, index: usize) -> Result<bool, IdentityError> {
+        let byte = self.bits.get(index / 8)
+            .ok_or_else(|| IdentityError::InvalidCredential(format!("Status list index {} out of range", index)))?;
+        Ok(byte & (0x80 >> (index % 8)) != 0)
+    }
+
+    /// Set the bit at an index
+    pub fn set(&mut self, index: usize, value: bool) -> Result<(), IdentityError> {
+        let byte = self.bits.get_mut(index / 8)
+            .ok_or_else(|| IdentityError::InvalidCredential(format!("Status list index {} out of range", index)))?;
+        if value {
+            *byte |= 0x80 >> (index % 8);
+        } else {
+            *byte &= !(0x80 >> (index % 8));
+        }
+        Ok(())
+    }
+
+    /// Encode as `encodedList`: base64url of the GZIP-compressed bitstring
+    pub fn encode(&self) -> Result<String, IdentityError> {
+        let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+        encoder.write_all(&self.bits)
+            .and_then(|_| encoder.finish())
+            .map(|compressed| encode_base64url(&compressed))
+            .map_err(|e| IdentityError::EncodingError(format!("Failed to compress status list: {}", e)))
+    }
+
+    /// Decode an `encodedList`
+    pub fn decode(encoded: &str) -> Result<Self, IdentityError> {
+        let compressed = decode_base64url(encoded)?;
+        let mut bits = Vec::new();
+        GzDecoder::new(compressed.as_slice()).read_to_end(&mut bits)
+            .map_err(|e| IdentityError::EncodingError(format!("Failed to decompress status list: {}", e)))?;
+        Ok(Self { bits })
+    }
+}
+
+impl StatusListStore {
+    /// Create an empty store
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add or replace the list published by a status list credential
+    pub fn insert(&self, list_credential: String, list: StatusList) {
+        self.lists.write().unwrap_or_else(|poisoned| poisoned.into_inner()).insert(list_credential, list);
+    }
+
+    /// Add the list carried by a status list credential's `encodedList` claim
+    pub fn insert_credential(&self, credential: &VerifiableCredential) -> Result<(), IdentityError> {
+        let encoded = credential.credential_subject.claims.get("encodedList")
+            .and_then(|value| value.as_str())
+            .ok_or_else(|| IdentityError::InvalidCredential("Status list credential has no encodedList".to_string()))?;
+        self.insert(credential.id.clone(), StatusList::decode(encoded)?);
+        Ok(())
+    }
+
+    /// Get a copy of a list
+    pub fn get(&self, list_credential: &str) -> Option<StatusList> {
+        self.lists.read().unwrap_or_else(|poisoned| poisoned.into_inner()).get(list_credential).cloned()
+    }
+}
+
+impl BitstringStatusChecker {
+    /// Checker for `StatusList2021Entry` entries
+    pub fn status_list_2021(store: StatusListStore) -> Self {
+        Self {
+            store,
+            list_property: "statusListCredential",
+            index_property: "statusListIndex",
+            default_purpose: StatusOutcome::Revoked,
+        }
+    }
+
+    /// Checker for `RevocationList2020Status` entries
+    pub fn revocation_list_2020(store: StatusListStore) -> Self {
+        Self {
+            store,
+            list_property: "revocationListCredential",
+            index_property: "revocationListIndex",
+            default_purpose: StatusOutcome::Revoked,
+        }
+    }
+}
+
+impl StatusChecker for BitstringStatusChecker {
+    fn check(&self, status: &CredentialStatus) -> Result<StatusOutcome, IdentityError> {
+        let list_credential = status.properties.get(self.list_property)
+            .and_then(|value| value.as_str())
+            .ok_or_else(|| IdentityError::InvalidCredential(format!("Status entry has no {}", self.list_property)))?;
+
+        // Indexes are strings in the specs, but accept numbers too
+        let index = match status.properties.get(self.index_property) {
+            Some(serde_json::Value::String(index)) => index.parse::<usize>().ok(),
+            Some(serde_json::Value::Number(index)) => index.as_u64().map(|index| index as usize),
+            _ => None,
+        }
+        .ok_or_else(|| IdentityError::InvalidCredential(format!("Status entry has no valid {}", self.index_property)))?;
+
+        let purpose = match status.properties.get("statusPurpose").and_then(|value| value.as_str()) {
+            None => self.default_purpose,
+            Some("revocation") => StatusOutcome::Revoked,
+            Some("suspension") => StatusOutcome::Suspended,
+            Some(purpose) => {
+                return Err(IdentityError::InvalidCredential(format!("Unsupported status purpose: {}", purpose)));
+            }
+        };
+
+        let list = self.store.get(list_credential)
+            .ok_or_else(|| IdentityError::NotFound(format!("Status list not loaded: {}", list_credential)))?;
+        Ok(if list.get(index)? { purpose } else { StatusOutcome::Valid })
+    }
+}
+
+impl StatusCheckerRegistry {
+    /// Create a registry with no checkers
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Create a registry with the built-in bitstring checkers reading from a store
+    pub fn with_defaults(store: StatusListStore) -> Self {
+        let mut registry = Self::new();
+        registry.register("StatusList2021Entry", BitstringStatusChecker::status_list_2021(store.clone()));
+        registry.register("BitstringStatusListEntry", BitstringStatusChecker::status_list_2021(store.clone()));
+        registry.register("RevocationList2020Status", BitstringStatusChecker::revocation_list_2020(store));
+        registry
+    }
+
+    /// Register or replace the checker for a status type
+    pub fn register(&mut self, status_type: &str, checker: impl StatusChecker + 'static) {
+        self.checkers.insert(status_type.to_string(), Arc::new(checker));
+    }
+
+    /// Status types with a registered checker
+    pub fn status_types(&self) -> Vec<&str> {
+        let mut types: Vec<&str> = self.checkers.keys().map(String::as_str).collect();
+        types.sort();
+        types
+    }
+
+    /// Check a status entry with the checker for its type
+    pub fn check_status(&self, status: &CredentialStatus) -> Result<StatusOutcome, IdentityError> {
+        self.checkers.get(&status.status_type)
+            .ok_or_else(|| IdentityError::UnsupportedStatusType(status.status_type.clone()))?
+            .check(status)
+    }
+
+    /// Check a credential's status; credentials without a status entry are valid
+    pub fn check(&self, credential: &VerifiableCredential) -> Result<StatusOutcome, IdentityError> {
+        match &credential.credential_status {
+            Some(status) => self.check_status(status),
+            None => Ok(StatusOutcome::Valid),
+        }
+    }
+}
+
+impl std::fmt::Debug for StatusCheckerRegistry {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("StatusCheckerRegistry")
+            .field("status_types", &self.status_types())
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::BTreeMap;
+
+    const LIST: &str = "https://example.com/status/1";
+
+    fn entry(status_type: &str, purpose: Option<&str>, index: serde_json::Value) -> CredentialStatus {
+        let mut properties = BTreeMap::new();
+        properties.insert("statusListCredential".to_string(), serde_json::json!(LIST));
+        properties.insert("statusListIndex".to_string(), index);
+        if let Some(purpose) = purpose {
+            properties.insert("statusPurpose".to_string(), serde_json::json!(purpose));
+        }
+        CredentialStatus { id: format!("{}#94567", LIST), status_type: status_type.to_string(), properties }
+    }
+
+    /// Registry whose list has bit 94567 set
+    fn registry() -> StatusCheckerRegistry {
+        let mut list = StatusList::new(131_072);
+        list.set(94567, true).unwrap();
+        let store = StatusListStore::new();
+        store.insert(LIST.to_string(), StatusList::decode(&list.encode().unwrap()).unwrap());
+        StatusCheckerRegistry::with_defaults(store)
+    }
+
+    #[test]
+    fn status_list_round_trips_through_its_encoding() {
+        let mut list = StatusList::new(16);
+        list.set(0, true).unwrap();
+        list.set(9, true).unwrap();
+
+        let decoded = StatusList::decode(&list.encode().unwrap()).unwrap();
+        assert_eq!(decoded, list);
+        assert!(decoded.get(0).unwrap());
+        assert!(!decoded.get(1).unwrap());
+        assert!(decoded.get(9).unwrap());
+        assert!(decoded.get(16).is_err());
+    }
+
+    #[test]
+    fn status_list_2021_entries_are_checked_against_their_list() {
+        let registry = registry();
+
+        let unset = entry("StatusList2021Entry", Some("revocation"), serde_json::json!("94566"));
+        assert_eq!(registry.check_status(&unset).unwrap(), StatusOutcome::Valid);
+        let revoked = entry("StatusList2021Entry", Some("revocation"), serde_json::json!("94567"));
+        assert_eq!(registry.check_status(&revoked).unwrap(), StatusOutcome::Revoked);
+        let suspended = entry("StatusList2021Entry", Some("suspension"), serde_json::json!(94567));
+        assert_eq!(registry.check_status(&suspended).unwrap(), StatusOutcome::Suspended);
+    }
+
+    #[test]
+    fn unknown_status_type_is_a_specific_error() {
+        let status = entry("SuspensionList2099", None, serde_json::json!("1"));
+
+        match registry().check_status(&status) {
+            Err(IdentityError::UnsupportedStatusType(status_type)) => assert_eq!(status_type, "SuspensionList2099"),
+            other => panic!("expected UnsupportedStatusType, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn malformed_entries_and_missing_lists_are_rejected() {
+        let registry = registry();
+
+        let bad_purpose = entry("StatusList2021Entry", Some("refresh"), serde_json::json!("1"));
+        assert!(matches!(registry.check_status(&bad_purpose), Err(IdentityError::InvalidCredential(_))));
+        let bad_index = entry("StatusList2021Entry", None, serde_json::json!("first"));
+        assert!(matches!(registry.check_status(&bad_index), Err(IdentityError::InvalidCredential(_))));
+
+        let mut unloaded = entry("StatusList2021Entry", None, serde_json::json!("1"));
+        unloaded.properties.insert("statusListCredential".to_string(), serde_json::json!("https://example.com/status/2"));
+        assert!(matches!(registry.check_status(&unloaded), Err(IdentityError::NotFound(_))));
+    }
+}
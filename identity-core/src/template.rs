@@ -0,0 +1,191 @@
+//! Reusable templates for credentials an issuer issues repeatedly
+
+use std::collections::BTreeMap;
+use chrono::Duration;
+use crate::error::IdentityError;
+use crate::signer::Signer;
+use crate::vc::{CredentialBuilder, CredentialSchema, CredentialType, VerifiableCredential};
+
+/// Fixed structure of a credential kind: everything but the subject and its per-subject claims
+#[derive(Debug, Clone)]
+pub struct CredentialTemplate {
+    issuer_did: String,
+    verification_method: String,
+    credential_types: Vec<CredentialType>,
+    contexts: Vec<String>,
+    required_claims: Vec<String>,
+    fixed_claims: BTreeMap<String, serde_json::Value>,
+    schema: Option<(CredentialSchema, serde_json::Value)>,
+    validity: Option<Duration>,
+}
+
+impl CredentialTemplate {
+    /// Start a template for credentials of a type, signed with the issuer's verification method
+    pub fn new(issuer_did: String, verification_method: String, credential_type: CredentialType) -> Self {
+        Self {
+            issuer_did,
+            verification_method,
+            credential_types: vec![credential_type],
+            contexts: Vec::new(),
+            required_claims: Vec::new(),
+            fixed_claims: BTreeMap::new(),
+            schema: None,
+            validity: None,
+        }
+    }
+
+    /// Add another credential type
+    pub fn with_type(mut self, credential_type: CredentialType) -> Self {
+        self.credential_types.push(credential_type);
+        self
+    }
+
+    /// Add a JSON-LD context after the base credentials context
+    pub fn with_context(mut self, context: &str) -> Self {
+        self.contexts.push(context.to_string());
+        self
+    }
+
+    /// Require a claim, addressed by dotted path, in every issued credential
+    pub fn with_required_claim(mut self, path: &str) -> Self {
+        self.required_claims.push(path.to_string());
+        self
+    }
+
+    /// Include a claim with the same value in every issued credential
+    pub fn with_fixed_claim(mut self, key: &str, value: serde_json::Value) -> Self {
+        self.fixed_claims.insert(key.to_string(), value);
+        self
+    }
+
+    /// Validate the claims of every issued credential against a JSON Schema
+    pub fn with_schema(mut self, reference: CredentialSchema, definition: serde_json::Value) -> Self {
+        self.schema = Some((reference, definition));
+        self
+    }
+
+    /// Expire issued credentials this long after issuance
+    pub fn with_validity(mut self, validity: Duration) -> Self {
+        self.validity = Some(validity);
+        self
+    }
+
+    /// Issuer of credentials built from this template
+    pub fn issuer_did(&self) -> &str {
+        &self.issuer_did
+    }
+
+    /// Build an unsigned credential for a subject, checking its claims against the template
+    pub fn build(&self, subject: String, claims: BTreeMap<String, serde_json::Value>) -> Result<VerifiableCredential, IdentityError> {
+        if let Some(key) = claims.keys().find(|key| self.fixed_claims.contains_key(*key)) {
+            return Err(IdentityError::InvalidCredential(format!("Claim {} is fixed by the template", key)));
+        }
+
+        let mut builder = CredentialBuilder::new(self.issuer_did.clone())
+            .subject(subject)
+            .claims(self.fixed_claims.clone())
+            .claims(claims);
+        for credential_type in &self.credential_types {
+            builder = builder.credential_type(credential_type.clone());
+        }
+        if let Some((reference, definition)) = &self.schema {
+            builder = builder.schema(reference.clone(), definition.clone());
+        }
+        let mut credential = builder.build()?;
+
+        if let Some(path) = self.required_claims.iter().find(|path| credential.credential_subject.get_path(path).is_none()) {
+            return Err(IdentityError::InvalidCredential(format!("Missing required claim: {}", path)));
+        }
+
+        for context in &self.contexts {
+            if !credential.context.contains(context) {
+                credential.context.push(context.clone());
+            }
+        }
+        if let Some(validity) = self.validity {
+            credential.expiration_date = Some(credential.issuance_date + validity);
+        }
+        Ok(credential)
+    }
+
+    /// Build and sign a credential for a subject
+    pub async fn issue(
+        &self,
+        subject: String,
+        claims: BTreeMap<String, serde_json::Value>,
+        signer: &dyn Signer,
+    ) -> Result<VerifiableCredential, IdentityError> {
+        let mut credential = self.build(subject, claims)?;
+        credential.sign_with(signer, self.verification_method.clone()).await?;
+        Ok(credential)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::crypto::{generate_ed25519_keypair, KeyType};
+    use crate::signer::InMemorySigner;
+
+    fn kyc_template() -> CredentialTemplate {
+        CredentialTemplate::new("did:example:bank".to_string(), "did:example:bank#key-1".to_string(), CredentialType::KycCredential)
+            .with_context("https://example.com/kyc/v1")
+            .with_required_claim("name")
+            .with_required_claim("address.country")
+            .with_fixed_claim("kycLevel", serde_json::json!("enhanced"))
+            .with_schema(
+                CredentialSchema { id: "https://example.com/kyc.json".to_string(), schema_type: "JsonSchema".to_string() },
+                serde_json::json!({
+                    "type": "object",
+                    "properties": { "name": { "type": "string" } },
+                }),
+            )
+            .with_validity(Duration::days(365))
+    }
+
+    fn claims(name: serde_json::Value, country: &str) -> BTreeMap<String, serde_json::Value> {
+        let mut claims = BTreeMap::new();
+        claims.insert("name".to_string(), name);
+        claims.insert("address".to_string(), serde_json::json!({ "country": country }));
+        claims
+    }
+
+    #[tokio::test]
+    async fn kyc_template_issues_signed_credentials_for_different_subjects() {
+        let keypair = generate_ed25519_keypair().unwrap();
+        let signer = InMemorySigner::new(keypair.clone());
+        let template = kyc_template();
+
+        let alice = template.issue("did:example:alice".to_string(), claims(serde_json::json!("Alice"), "DE"), &signer).await.unwrap();
+        let bob = template.issue("did:example:bob".to_string(), claims(serde_json::json!("Bob"), "FR"), &signer).await.unwrap();
+
+        assert_ne!(alice.id, bob.id);
+        assert_eq!(alice.credential_subject.id.as_deref(), Some("did:example:alice"));
+        assert_eq!(bob.credential_subject.id.as_deref(), Some("did:example:bob"));
+        for credential in [&alice, &bob] {
+            assert!(credential.verify_proof(&keypair.public_key, &KeyType::Ed25519).unwrap());
+            assert_eq!(credential.get_issuer_did(), "did:example:bank");
+            assert!(credential.credential_type.contains(&"KycCredential".to_string()));
+            assert!(credential.context.contains(&"https://example.com/kyc/v1".to_string()));
+            assert_eq!(credential.credential_subject.claims["kycLevel"], serde_json::json!("enhanced"));
+            assert_eq!(credential.credential_schema.as_ref().unwrap()[0].id, "https://example.com/kyc.json");
+            assert_eq!(credential.expiration_date, Some(credential.issuance_date + Duration::days(365)));
+        }
+    }
+
+    #[test]
+    fn claims_are_checked_against_the_template() {
+        let template = kyc_template();
+        let subject = || "did:example:alice".to_string();
+
+        let mut missing = claims(serde_json::json!("Alice"), "DE");
+        missing.remove("address");
+        assert!(template.build(subject(), missing).unwrap_err().to_string().contains("address.country"));
+
+        let mut overriding = claims(serde_json::json!("Alice"), "DE");
+        overriding.insert("kycLevel".to_string(), serde_json::json!("basic"));
+        assert!(template.build(subject(), overriding).unwrap_err().to_string().contains("fixed by the template"));
+
+        assert!(template.build(subject(), claims(serde_json::json!(42), "DE")).is_err());
+    }
+}
@@ -0,0 +1,166 @@
+//! Proof-of-existence timestamps for credentials
+
+use serde::{Deserialize, Serialize};
+use chrono::{DateTime, Utc};
+use crate::crypto::{CryptoKeyPair, KeyType, sign_data, verify_data};
+use crate::crypto::encoding::{encode_base64url, decode_base64url};
+use crate::error::IdentityError;
+use crate::vc::VerifiableCredential;
+
+/// Key under which a timestamp proof is stored in a credential proof's additional properties
+pub const TIMESTAMP_PROOF_PROPERTY: &str = "timestampProof";
+
+/// How the timestamp was obtained
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub enum TimestampMethod {
+    /// Timestamp recorded locally, optionally signed by a timestamping key
+    Local,
+    /// Timestamp anchored through OpenTimestamps
+    OpenTimestamps,
+}
+
+/// Proof that a credential existed at a point in time
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct TimestampProof {
+    #[serde(rename = "credentialHash")]
+    pub credential_hash: String,
+    pub timestamp: DateTime<Utc>,
+    pub method: TimestampMethod,
+    #[serde(rename = "verificationMethod", skip_serializing_if = "Option::is_none")]
+    pub verification_method: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub signature: Option<String>,
+}
+
+impl TimestampProof {
+    /// Create a local timestamp over a credential hash
+    pub fn new(credential_hash: String) -> Self {
+        Self {
+            credential_hash,
+            timestamp: Utc::now(),
+            method: TimestampMethod::Local,
+            verification_method: None,
+            signature: None,
+        }
+    }
+
+    /// Bytes covered by the timestamp signature
+    pub fn signing_payload(&self) -> Vec<u8> {
+        format!("{}|{}", self.credential_hash, self.timestamp.to_rfc3339()).into_bytes()
+    }
+
+    /// Sign the timestamp with a timestamping key
+    pub fn sign(&mut self, keypair: &CryptoKeyPair, verification_method: String) -> Result<(), IdentityError> {
+        let signature = sign_data(&self.signing_payload(), &keypair.private_key, &keypair.key_type)?;
+        self.signature = Some(encode_base64url(&signature));
+        self.verification_method = Some(verification_method);
+        Ok(())
+    }
+
+    /// Verify the timestamp signature
+    pub fn verify_signature(&self, public_key: &[u8], key_type: &KeyType) -> Result<bool, IdentityError> {
+        let signature = self.signature.as_ref()
+            .ok_or_else(|| IdentityError::VerificationError("Timestamp proof is not signed".to_string()))?;
+        let signature = decode_base64url(signature)?;
+        verify_data(&self.signing_payload(), &signature, public_key, key_type)
+    }
+}
+
+impl VerifiableCredential {
+    /// Create a proof that this credential exists now
+    pub fn timestamp_proof(&self) -> Result<TimestampProof, IdentityError> {
+        Ok(TimestampProof::new(self.canonical_hash()?))
+    }
+
+    /// Check that a timestamp proof matches this credential and isn't dated in the future
+    pub fn verify_timestamp(&self, proof: &TimestampProof) -> Result<bool, IdentityError> {
+        if proof.timestamp > Utc::now() {
+            return Ok(false);
+        }
+        Ok(proof.credential_hash == self.canonical_hash()?)
+    }
+
+    /// Store a timestamp proof in the additional properties of the credential's first proof
+    pub fn attach_timestamp_proof(&mut self, timestamp: &TimestampProof) -> Result<(), IdentityError> {
+        let value = serde_json::to_value(timestamp)?;
+        let proof = self.proof.as_mut()
+            .and_then(|proofs| proofs.first_mut())
+            .ok_or_else(|| IdentityError::InvalidCredential("Credential has no proof to attach a timestamp to".to_string()))?;
+
+        proof.additional_properties.insert(TIMESTAMP_PROOF_PROPERTY.to_string(), value);
+        Ok(())
+    }
+
+    /// Get a timestamp proof previously attached to the credential
+    pub fn attached_timestamp_proof(&self) -> Option<TimestampProof> {
+        self.proof.as_ref()?
+            .iter()
+            .filter_map(|proof| proof.additional_properties.get(TIMESTAMP_PROOF_PROPERTY))
+            .find_map(|value| serde_json::from_value(value.clone()).ok())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::BTreeMap;
+    use crate::crypto::generate_ed25519_keypair;
+
+    fn credential() -> VerifiableCredential {
+        let mut claims = BTreeMap::new();
+        claims.insert("degree".to_string(), serde_json::json!("BSc"));
+        VerifiableCredential::new("did:example:issuer".to_string(), Some("did:example:alice".to_string()), claims)
+    }
+
+    #[test]
+    fn signed_timestamp_survives_attachment() {
+        let issuer = generate_ed25519_keypair().unwrap();
+        let timestamper = generate_ed25519_keypair().unwrap();
+        let mut credential = credential();
+        credential.sign(&issuer, "did:example:issuer#key-1".to_string()).unwrap();
+
+        let mut proof = credential.timestamp_proof().unwrap();
+        proof.sign(&timestamper, "did:example:tsa#key-1".to_string()).unwrap();
+        credential.attach_timestamp_proof(&proof).unwrap();
+
+        let attached = credential.attached_timestamp_proof().unwrap();
+        assert_eq!(attached, proof);
+        assert!(credential.verify_timestamp(&attached).unwrap());
+        assert!(attached.verify_signature(&timestamper.public_key, &KeyType::Ed25519).unwrap());
+        assert!(credential.verify_proof(&issuer.public_key, &KeyType::Ed25519).unwrap());
+    }
+
+    #[test]
+    fn timestamp_does_not_match_a_modified_credential() {
+        let mut credential = credential();
+        let proof = credential.timestamp_proof().unwrap();
+
+        credential.credential_subject.claims.insert("degree".to_string(), serde_json::json!("PhD"));
+        assert!(!credential.verify_timestamp(&proof).unwrap());
+    }
+
+    #[test]
+    fn rejects_future_dated_or_tampered_timestamps() {
+        let timestamper = generate_ed25519_keypair().unwrap();
+        let credential = credential();
+
+        let mut future = credential.timestamp_proof().unwrap();
+        future.timestamp = Utc::now() + chrono::Duration::hours(1);
+        assert!(!credential.verify_timestamp(&future).unwrap());
+
+        let mut backdated = credential.timestamp_proof().unwrap();
+        backdated.sign(&timestamper, "did:example:tsa#key-1".to_string()).unwrap();
+        backdated.timestamp -= chrono::Duration::days(365);
+        assert!(!backdated.verify_signature(&timestamper.public_key, &KeyType::Ed25519).unwrap());
+        assert!(credential.timestamp_proof().unwrap().verify_signature(&timestamper.public_key, &KeyType::Ed25519).is_err());
+    }
+
+    #[test]
+    fn attaching_requires_an_existing_proof() {
+        let mut credential = credential();
+        let proof = credential.timestamp_proof().unwrap();
+
+        assert!(credential.attach_timestamp_proof(&proof).is_err());
+        assert!(credential.attached_timestamp_proof().is_none());
+    }
+}
@@ -21,30 +21,75 @@ pub fn generate_did_with_id(method: &str, id: &str) -> String {
     format!("did:{}:{}", method, id)
 }
 
-/// Parse a DID into its components
+/// Longest DID `parse_did` accepts, in bytes
+pub const MAX_DID_LENGTH: usize = 2048;
+
+/// Parse a DID into its components.
+///
+/// Enforces the DID Core syntax: a lowercase alphanumeric method and a method-specific id
+/// of `ALPHA / DIGIT / "." / "-" / "_" / pct-encoded` characters in `:`-separated segments,
+/// the last of them non-empty. DIDs longer than `MAX_DID_LENGTH` are rejected.
 pub fn parse_did(did: &str) -> Result<(String, String, String), IdentityError> {
-    let parts: Vec<&str> = did.split(':').collect();
-    if parts.len() < 3 {
-        return Err(IdentityError::InvalidDid("DID must have at least 3 parts".to_string()));
+    if did.len() > MAX_DID_LENGTH {
+        return Err(IdentityError::InvalidDid(format!(
+            "DID is {} bytes, longer than the {} byte limit", did.len(), MAX_DID_LENGTH
+        )));
     }
 
-    if parts[0] != "did" {
-        return Err(IdentityError::InvalidDid("DID must start with 'did:'".to_string()));
+    let rest = did.strip_prefix("did:")
+        .ok_or_else(|| IdentityError::InvalidDid("DID must start with 'did:'".to_string()))?;
+    let (method, method_specific_id) = rest.split_once(':')
+        .ok_or_else(|| IdentityError::InvalidDid("DID must have at least 3 parts".to_string()))?;
+
+    if method.is_empty() {
+        return Err(IdentityError::InvalidDid("DID method is empty".to_string()));
+    }
+    if let Some(c) = method.chars().find(|c| !(c.is_ascii_lowercase() || c.is_ascii_digit())) {
+        return Err(IdentityError::InvalidDid(format!("Invalid character {:?} in DID method", c)));
     }
 
-    let method = parts[1].to_string();
-    let method_specific_id = parts[2..].join(":");
+    if method_specific_id.is_empty() || method_specific_id.ends_with(':') {
+        return Err(IdentityError::InvalidDid("DID method-specific id must not be empty or end with ':'".to_string()));
+    }
+    let bytes = method_specific_id.as_bytes();
+    let mut i = 0;
+    while i < bytes.len() {
+        match bytes[i] {
+            b'%' => {
+                if !bytes.get(i + 1..i + 3).is_some_and(|hex| hex.iter().all(u8::is_ascii_hexdigit)) {
+                    return Err(IdentityError::InvalidDid(format!(
+                        "Invalid percent-encoding at byte {} of the method-specific id", i
+                    )));
+                }
+                i += 3;
+            }
+            b if b.is_ascii_alphanumeric() || matches!(b, b'.' | b'-' | b'_' | b':') => i += 1,
+            _ => {
+                let c = method_specific_id[i..].chars().next().unwrap_or_default();
+                return Err(IdentityError::InvalidDid(format!(
+                    "Invalid character {:?} in DID method-specific id", c
+                )));
+            }
+        }
+    }
 
-    Ok(("did".to_string(), method, method_specific_id))
+    Ok(("did".to_string(), method.to_string(), method_specific_id.to_string()))
 }
 
 /// Create a basic DID document with a single verification method
 pub fn create_basic_did_document(
     method: &str,
     key_type: KeyType,
+) -> Result<(DidDocument, CryptoKeyPair), IdentityError> {
+    create_did_document_with_id(generate_did(method), key_type)
+}
+
+/// Create a DID document for a specific DID with a single verification method
+pub fn create_did_document_with_id(
+    did: String,
+    key_type: KeyType,
 ) -> Result<(DidDocument, CryptoKeyPair), IdentityError> {
     let keypair = generate_keypair(key_type.clone())?;
-    let did = generate_did(method);
     let mut did_doc = DidDocument::new(did.clone());
 
     // Create verification method
@@ -107,10 +152,86 @@ pub fn validate_url(url: &str) -> Result<(), IdentityError> {
     Ok(())
 }
 
-/// Generate a random nonce
+/// Number of random bytes in a default nonce
+pub const DEFAULT_NONCE_BYTES: usize = 16;
+
+/// Generate a random 128-bit nonce
 pub fn generate_nonce() -> String {
-    use rand::Rng;
-    let mut rng = rand::thread_rng();
-    let nonce: u64 = rng.gen();
-    format!("{:x}", nonce)
+    generate_nonce_with(DEFAULT_NONCE_BYTES)
+}
+
+/// Generate a base64url nonce from the given number of random bytes
+pub fn generate_nonce_with(bytes: usize) -> String {
+    use rand::RngCore;
+    let mut nonce = vec![0u8; bytes];
+    rand::rngs::OsRng.fill_bytes(&mut nonce);
+    crate::crypto::encoding::encode_base64url(&nonce)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::crypto::encoding::decode_base64url;
+
+    #[test]
+    fn nonce_encodes_the_requested_number_of_bytes() {
+        for bytes in [0, 1, 16, 32, 33] {
+            let nonce = generate_nonce_with(bytes);
+            assert_eq!(decode_base64url(&nonce).unwrap().len(), bytes);
+            assert!(nonce.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_'));
+        }
+        assert_eq!(decode_base64url(&generate_nonce()).unwrap().len(), DEFAULT_NONCE_BYTES);
+    }
+
+    #[test]
+    fn successive_nonces_differ() {
+        let nonces: std::collections::HashSet<String> = (0..100).map(|_| generate_nonce()).collect();
+        assert_eq!(nonces.len(), 100);
+        assert_ne!(generate_nonce_with(32), generate_nonce_with(32));
+    }
+
+    fn invalid_did_message(did: &str) -> String {
+        match parse_did(did) {
+            Err(IdentityError::InvalidDid(message)) => message,
+            other => panic!("{} parsed as {:?}", did, other),
+        }
+    }
+
+    #[test]
+    fn valid_dids_are_parsed() {
+        assert_eq!(parse_did("did:example:123456789abcdefghi").unwrap(), (
+            "did".to_string(), "example".to_string(), "123456789abcdefghi".to_string()
+        ));
+        assert_eq!(parse_did("did:web:example.com:user:alice").unwrap().2, "example.com:user:alice");
+        assert_eq!(parse_did("did:web:localhost%3A8443").unwrap().2, "localhost%3A8443");
+        assert!(parse_did("did:key:z6MkhaXgBZDvotDkL5257faiztiGiC2QtKLGpbnnEGta2doK").is_ok());
+        assert!(parse_did("did:ethr:0x1:0xb9c5714089478a327f09197987f16f9e5d936e8a").is_ok());
+        assert!(parse_did(&format!("did:example:{}", "a".repeat(MAX_DID_LENGTH - 12))).is_ok());
+    }
+
+    #[test]
+    fn empty_or_malformed_methods_are_rejected() {
+        assert_eq!(invalid_did_message("did::123"), "DID method is empty");
+        assert!(invalid_did_message("did:Example:123").contains("'E'"));
+        assert!(invalid_did_message("did:ex-ample:123").contains("DID method"));
+        assert_eq!(invalid_did_message("doc:example:123"), "DID must start with 'did:'");
+        assert_eq!(invalid_did_message("did:example"), "DID must have at least 3 parts");
+    }
+
+    #[test]
+    fn illegal_method_specific_ids_are_rejected() {
+        assert!(invalid_did_message("did:example:").contains("must not be empty"));
+        assert!(invalid_did_message("did:example:abc:").contains("end with ':'"));
+        assert!(invalid_did_message("did:example:abc def").contains("' '"));
+        assert!(invalid_did_message("did:example:abc\u{7}").contains("\\u{7}"));
+        assert!(invalid_did_message("did:example:caf\u{e9}").contains("'\u{e9}'"));
+        assert!(invalid_did_message("did:example:abc%2").contains("percent-encoding at byte 3"));
+        assert!(invalid_did_message("did:example:abc%zz").contains("percent-encoding"));
+    }
+
+    #[test]
+    fn overlong_dids_are_rejected() {
+        let did = format!("did:example:{}", "a".repeat(MAX_DID_LENGTH));
+        assert!(invalid_did_message(&did).contains("longer than the 2048 byte limit"));
+    }
 }
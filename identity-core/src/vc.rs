@@ -2,10 +2,22 @@
 
 use serde::{Deserialize, Serialize};
 use chrono::{DateTime, Utc};
-use std::collections::HashMap;
+use std::collections::BTreeMap;
+use crate::canonicalization::Canonicalization;
 use crate::error::IdentityError;
+use crate::crypto::{CryptoKeyPair, KeyType, hash_data, sign_data, verify_data};
+use crate::signer::Signer;
+use crate::did::RelationshipType;
+use crate::resolver::DidResolver;
+use crate::hardware::HardwareAttestation;
+use crate::joint_issuance::JointIssuer;
+use crate::crypto::encoding::{encode_base64url, decode_base64url, encode_multibase, decode_multibase};
+use crate::schema::validate_json_schema;
 use crate::utils::generate_id;
 
+/// Proof type of the W3C Data Integrity suites, which name their algorithm in `cryptosuite`
+pub const DATA_INTEGRITY_PROOF_TYPE: &str = "DataIntegrityProof";
+
 /// Verifiable Credential as per W3C VC Data Model
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub struct VerifiableCredential {
@@ -25,19 +37,28 @@ pub struct VerifiableCredential {
     pub credential_status: Option<CredentialStatus>,
     #[serde(rename = "credentialSchema", skip_serializing_if = "Option::is_none")]
     pub credential_schema: Option<Vec<CredentialSchema>>,
+    #[serde(rename = "hardwareAttestation", default, skip_serializing_if = "Option::is_none")]
+    pub hardware_attestation: Option<HardwareAttestation>,
+    /// Claim key -> attestors that vouched for it; recorded after issuance, so not covered by proofs
+    #[serde(rename = "claimProvenance", default, skip_serializing_if = "Option::is_none")]
+    pub claim_provenance: Option<BTreeMap<String, Vec<ClaimAttestation>>>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub proof: Option<Vec<Proof>>,
+    /// Properties not modeled above, preserved across round trips and covered by proofs
+    #[serde(flatten)]
+    pub extra: BTreeMap<String, serde_json::Value>,
 }
 
-/// Issuer can be a string (DID) or an object with additional properties
+/// Issuer can be a string (DID), a set of joint issuers, or an object with additional properties
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 #[serde(untagged)]
 pub enum Issuer {
     Did(String),
+    Joint(JointIssuer),
     Object {
         id: String,
         #[serde(flatten)]
-        properties: HashMap<String, serde_json::Value>,
+        properties: BTreeMap<String, serde_json::Value>,
     },
 }
 
@@ -47,7 +68,7 @@ pub struct CredentialSubject {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub id: Option<String>,
     #[serde(flatten)]
-    pub claims: HashMap<String, serde_json::Value>,
+    pub claims: BTreeMap<String, serde_json::Value>,
 }
 
 /// Credential Status for revocation checking
@@ -57,7 +78,7 @@ pub struct CredentialStatus {
     #[serde(rename = "type")]
     pub status_type: String,
     #[serde(flatten)]
-    pub properties: HashMap<String, serde_json::Value>,
+    pub properties: BTreeMap<String, serde_json::Value>,
 }
 
 /// Credential Schema for validation
@@ -73,6 +94,9 @@ pub struct CredentialSchema {
 pub struct Proof {
     #[serde(rename = "type")]
     pub proof_type: String,
+    /// Algorithm of a `DataIntegrityProof`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cryptosuite: Option<String>,
     pub created: DateTime<Utc>,
     #[serde(rename = "verificationMethod")]
     pub verification_method: String,
@@ -80,8 +104,29 @@ pub struct Proof {
     pub proof_purpose: String,
     #[serde(rename = "proofValue")]
     pub proof_value: String,
+    /// DID of the verifier a presentation proof is bound to
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub audience: Option<String>,
     #[serde(flatten)]
-    pub additional_properties: HashMap<String, serde_json::Value>,
+    pub additional_properties: BTreeMap<String, serde_json::Value>,
+}
+
+/// Proof a verifier requires: a proof with this purpose made by this key
+#[derive(Debug, Clone, PartialEq)]
+pub struct ProofRequirement {
+    pub purpose: String,
+    pub public_key: Vec<u8>,
+    pub key_type: KeyType,
+}
+
+/// Record of an attestor vouching for a single claim
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct ClaimAttestation {
+    #[serde(rename = "attestorDid")]
+    pub attestor_did: String,
+    #[serde(rename = "evidenceLevel", skip_serializing_if = "Option::is_none")]
+    pub evidence_level: Option<String>,
+    pub timestamp: DateTime<Utc>,
 }
 
 /// Verifiable Presentation containing one or more credentials
@@ -101,6 +146,18 @@ pub struct VerifiablePresentation {
     pub proof: Option<Vec<Proof>>,
 }
 
+/// Builder for verifiable credentials with optional schema validation
+#[derive(Debug, Clone)]
+pub struct CredentialBuilder {
+    issuer_did: String,
+    subject_id: Option<String>,
+    claims: BTreeMap<String, serde_json::Value>,
+    credential_types: Vec<CredentialType>,
+    expiration_date: Option<DateTime<Utc>>,
+    credential_status: Option<CredentialStatus>,
+    schema: Option<(CredentialSchema, serde_json::Value)>,
+}
+
 /// Credential types commonly used
 #[derive(Debug, Clone, PartialEq)]
 pub enum CredentialType {
@@ -130,7 +187,7 @@ impl VerifiableCredential {
     pub fn new(
         issuer_did: String,
         subject_id: Option<String>,
-        claims: HashMap<String, serde_json::Value>,
+        claims: BTreeMap<String, serde_json::Value>,
     ) -> Self {
         Self {
             context: vec![
@@ -147,7 +204,10 @@ impl VerifiableCredential {
             },
             credential_status: None,
             credential_schema: None,
+            hardware_attestation: None,
+            claim_provenance: None,
             proof: None,
+            extra: BTreeMap::new(),
         }
     }
 
@@ -177,8 +237,20 @@ impl VerifiableCredential {
         self.proof.as_mut().unwrap().push(proof);
     }
 
-    /// Validate the credential structure
+    /// Validate the credential structure and expiration
     pub fn validate(&self) -> Result<(), IdentityError> {
+        self.validate_structure()?;
+
+        // Check expiration
+        if self.is_expired() {
+            return Err(IdentityError::InvalidCredential("Credential has expired".to_string()));
+        }
+
+        Ok(())
+    }
+
+    /// Validate the credential structure without checking expiration
+    pub fn validate_structure(&self) -> Result<(), IdentityError> {
         // Check required fields
         if self.credential_type.is_empty() {
             return Err(IdentityError::InvalidCredential("Credential must have at least one type".to_string()));
@@ -195,6 +267,7 @@ impl VerifiableCredential {
                     return Err(IdentityError::InvalidCredential("Issuer must be a valid DID".to_string()));
                 }
             }
+            Issuer::Joint(joint) => joint.validate()?,
             Issuer::Object { id, .. } => {
                 if !id.starts_with("did:") {
                     return Err(IdentityError::InvalidCredential("Issuer ID must be a valid DID".to_string()));
@@ -202,32 +275,403 @@ impl VerifiableCredential {
             }
         }
 
-        // Check expiration
-        if let Some(exp) = self.expiration_date {
-            if exp <= Utc::now() {
-                return Err(IdentityError::InvalidCredential("Credential has expired".to_string()));
-            }
-        }
-
         Ok(())
     }
 
     /// Check if the credential is expired
     pub fn is_expired(&self) -> bool {
-        if let Some(exp) = self.expiration_date {
-            exp <= Utc::now()
-        } else {
-            false
-        }
+        self.is_expired_at(Utc::now())
     }
 
-    /// Get the issuer DID
+    /// Check if the credential is expired at a given time
+    pub fn is_expired_at(&self, now: DateTime<Utc>) -> bool {
+        self.expiration_date.is_some_and(|exp| exp <= now)
+    }
+
+    /// Get the issuer DID; for joint issuance, the first (lead) issuer
     pub fn get_issuer_did(&self) -> &str {
         match &self.issuer {
             Issuer::Did(did) => did,
+            Issuer::Joint(joint) => joint.issuers.first().map(String::as_str).unwrap_or_default(),
             Issuer::Object { id, .. } => id,
         }
     }
+
+    /// Every issuer DID of the credential
+    pub fn issuer_dids(&self) -> Vec<&str> {
+        match &self.issuer {
+            Issuer::Joint(joint) => joint.issuers.iter().map(String::as_str).collect(),
+            _ => vec![self.get_issuer_did()],
+        }
+    }
+
+    /// Get the bytes covered by the credential's proofs (the credential without its proofs)
+    pub fn signing_payload(&self) -> Result<Vec<u8>, IdentityError> {
+        let mut unsigned = self.clone();
+        unsigned.proof = None;
+        unsigned.claim_provenance = None;
+        let value = serde_json::to_value(&unsigned)?;
+        Canonicalization::Jcs.canonicalize(&value)
+    }
+
+    /// Hex-encoded SHA-256 hash of the canonical credential, excluding proofs
+    pub fn canonical_hash(&self) -> Result<String, IdentityError> {
+        Ok(hex::encode(hash_data(&self.signing_payload()?)))
+    }
+
+    /// Time elapsed since the credential was issued
+    pub fn age(&self) -> chrono::Duration {
+        self.age_at(Utc::now())
+    }
+
+    /// Time elapsed between issuance and a given time
+    pub fn age_at(&self, now: DateTime<Utc>) -> chrono::Duration {
+        now - self.issuance_date
+    }
+
+    /// Record that an attestor vouched for a claim
+    pub fn add_claim_provenance(&mut self, claim: &str, attestation: ClaimAttestation) {
+        self.claim_provenance
+            .get_or_insert_with(BTreeMap::new)
+            .entry(claim.to_string())
+            .or_default()
+            .push(attestation);
+    }
+
+    /// Attestors that vouched for a claim
+    pub fn provenance_for(&self, claim: &str) -> &[ClaimAttestation] {
+        self.claim_provenance.as_ref()
+            .and_then(|provenance| provenance.get(claim))
+            .map(Vec::as_slice)
+            .unwrap_or_default()
+    }
+
+    /// Sign the credential and attach an assertion proof
+    pub fn sign(&mut self, keypair: &CryptoKeyPair, verification_method: String) -> Result<(), IdentityError> {
+        let payload = self.signing_payload()?;
+        let signature = sign_data(&payload, &keypair.private_key, &keypair.key_type)?;
+
+        self.add_proof(Proof::new(&keypair.key_type, verification_method, "assertionMethod", &signature));
+        #[cfg(feature = "metrics")]
+        crate::metrics::credential_metrics().record_issued(self);
+        Ok(())
+    }
+
+    /// Sign the credential with any signer and attach an assertion proof
+    pub async fn sign_with(&mut self, signer: &dyn Signer, verification_method: String) -> Result<(), IdentityError> {
+        let payload = self.signing_payload()?;
+        let signature = signer.sign(&payload).await?;
+
+        self.add_proof(Proof::new(&signer.key_type(), verification_method, "assertionMethod", &signature));
+        #[cfg(feature = "metrics")]
+        crate::metrics::credential_metrics().record_issued(self);
+        Ok(())
+    }
+
+    /// Sign the credential with any signer and attach an assertion `DataIntegrityProof`.
+    ///
+    /// Ed25519 signers produce `eddsa-jcs-2022` proofs; other key types have no supported suite.
+    pub async fn sign_data_integrity(&mut self, signer: &dyn Signer, verification_method: String) -> Result<(), IdentityError> {
+        let mut proof = Proof::data_integrity(&signer.key_type(), verification_method, "assertionMethod")?;
+        let signature = signer.sign(&proof.signing_input(&self.signing_payload()?)?).await?;
+        proof.proof_value = encode_multibase(&signature);
+
+        self.add_proof(proof);
+        #[cfg(feature = "metrics")]
+        crate::metrics::credential_metrics().record_issued(self);
+        Ok(())
+    }
+
+    /// Verify that at least one attached proof is a valid signature by the given key
+    pub fn verify_proof(&self, public_key: &[u8], key_type: &KeyType) -> Result<bool, IdentityError> {
+        let proofs = match &self.proof {
+            Some(proofs) if !proofs.is_empty() => proofs,
+            _ => return Err(IdentityError::VerificationError("Credential has no proof".to_string())),
+        };
+
+        Ok(any_proof_valid(proofs, &self.signing_payload()?, public_key, key_type))
+    }
+
+    /// Check that every required purpose has a valid proof by the expected key.
+    ///
+    /// The proof's verification method must also be authorized for that purpose in its
+    /// controller's resolved DID document and publish the expected key.
+    pub async fn verify_proof_set(
+        &self,
+        requirements: &[ProofRequirement],
+        resolver: &dyn DidResolver,
+    ) -> Result<(), IdentityError> {
+        let proofs = self.proof.as_deref().unwrap_or_default();
+        let payload = self.signing_payload()?;
+
+        for requirement in requirements {
+            let relationship = RelationshipType::from_purpose(&requirement.purpose)?;
+            let candidates: Vec<&Proof> = proofs.iter()
+                .filter(|proof| proof.proof_purpose == requirement.purpose)
+                .collect();
+            if candidates.is_empty() {
+                return Err(IdentityError::VerificationError(format!(
+                    "Missing required {} proof", requirement.purpose
+                )));
+            }
+
+            let mut satisfied = false;
+            for proof in candidates {
+                if !proof.verify(&payload, &requirement.public_key, &requirement.key_type) {
+                    continue;
+                }
+
+                let did = proof.verification_method.split('#').next().unwrap_or_default();
+                let document = resolver.resolve(did).await?;
+                let published = document.authorized_method(relationship, &proof.verification_method)
+                    .and_then(|method| method.public_key_bytes().ok());
+                if published.as_deref() == Some(requirement.public_key.as_slice()) {
+                    satisfied = true;
+                    break;
+                }
+            }
+
+            if !satisfied {
+                return Err(IdentityError::VerificationError(format!(
+                    "No valid {} proof by the required key", requirement.purpose
+                )));
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl CredentialSubject {
+    /// Look up a nested claim by dotted path, e.g. `address.country` or `degrees[0].name`.
+    ///
+    /// Array elements may be addressed with `[n]` or as a numeric segment (`degrees.0`).
+    pub fn get_path(&self, path: &str) -> Option<&serde_json::Value> {
+        let mut segments = path_segments(path)?.into_iter();
+        let mut current = self.claims.get(segments.next()?)?;
+
+        for segment in segments {
+            current = match current {
+                serde_json::Value::Object(object) => object.get(segment)?,
+                serde_json::Value::Array(items) => items.get(segment.parse::<usize>().ok()?)?,
+                _ => return None,
+            };
+        }
+
+        Some(current)
+    }
+}
+
+/// Split a claim path into object keys and array indices
+fn path_segments(path: &str) -> Option<Vec<&str>> {
+    let mut segments = Vec::new();
+    for part in path.split('.') {
+        let (key, mut rest) = match part.find('[') {
+            Some(start) => part.split_at(start),
+            None => (part, ""),
+        };
+        if !key.is_empty() {
+            segments.push(key);
+        }
+        while !rest.is_empty() {
+            let end = rest.find(']')?;
+            let index = &rest[1..end];
+            if index.is_empty() {
+                return None;
+            }
+            segments.push(index);
+            rest = &rest[end + 1..];
+            if !rest.is_empty() && !rest.starts_with('[') {
+                return None;
+            }
+        }
+    }
+
+    if segments.is_empty() {
+        None
+    } else {
+        Some(segments)
+    }
+}
+
+impl Proof {
+    /// Create a proof carrying a signature made with a key of the given type
+    pub fn new(key_type: &KeyType, verification_method: String, proof_purpose: &str, signature: &[u8]) -> Self {
+        Self {
+            proof_type: key_type.signature_suite().to_string(),
+            cryptosuite: None,
+            created: Utc::now(),
+            verification_method,
+            proof_purpose: proof_purpose.to_string(),
+            proof_value: encode_base64url(signature),
+            audience: None,
+            additional_properties: BTreeMap::new(),
+        }
+    }
+
+    /// Create an unsigned `DataIntegrityProof` using the key type's cryptosuite
+    pub fn data_integrity(key_type: &KeyType, verification_method: String, proof_purpose: &str) -> Result<Self, IdentityError> {
+        let cryptosuite = key_type.cryptosuite().ok_or_else(|| {
+            IdentityError::CryptoError(format!("No Data Integrity cryptosuite for {}", key_type))
+        })?;
+
+        Ok(Self {
+            proof_type: DATA_INTEGRITY_PROOF_TYPE.to_string(),
+            cryptosuite: Some(cryptosuite.to_string()),
+            ..Self::new(key_type, verification_method, proof_purpose, &[])
+        })
+    }
+
+    /// Whether this is a `DataIntegrityProof` rather than a 2020-era suite proof
+    pub fn is_data_integrity(&self) -> bool {
+        self.proof_type == DATA_INTEGRITY_PROOF_TYPE
+    }
+
+    /// Canonicalization the proof's suite signs with.
+    ///
+    /// Data Integrity proofs take it from their cryptosuite; this crate signs 2020 suite proofs
+    /// over the JCS form.
+    pub fn canonicalization(&self) -> Result<Canonicalization, IdentityError> {
+        if !self.is_data_integrity() {
+            return Ok(Canonicalization::Jcs);
+        }
+        self.cryptosuite.as_deref()
+            .and_then(Canonicalization::for_cryptosuite)
+            .ok_or_else(|| IdentityError::CryptoError(format!(
+                "Unsupported cryptosuite: {}", self.cryptosuite.as_deref().unwrap_or("none")
+            )))
+    }
+
+    /// Bytes signed for a document payload, the document's JCS form.
+    ///
+    /// 2020 suites sign the payload itself. Data Integrity proofs sign the hash of the proof
+    /// configuration followed by the hash of the document, both in the suite's canonical form.
+    /// As the JCS suites specify, the configuration carries the document's `@context`.
+    /// Proofs whose cryptosuite names an unsupported canonicalization are an error.
+    pub fn signing_input(&self, payload: &[u8]) -> Result<Vec<u8>, IdentityError> {
+        if !self.is_data_integrity() {
+            return Ok(payload.to_vec());
+        }
+
+        let canonicalization = self.canonicalization()?;
+        let document: serde_json::Value = serde_json::from_slice(payload)?;
+        let mut options = serde_json::to_value(self)?;
+        if let Some(options) = options.as_object_mut() {
+            options.remove("proofValue");
+            if let Some(context) = document.get("@context") {
+                options.insert("@context".to_string(), context.clone());
+            }
+        }
+
+        let mut input = hash_data(&canonicalization.canonicalize(&options)?);
+        input.extend(hash_data(payload));
+        Ok(input)
+    }
+
+    /// Verify this proof's signature over a payload.
+    ///
+    /// A `DataIntegrityProof` carries a multibase signature and only verifies with the key
+    /// type its cryptosuite names.
+    pub fn verify(&self, payload: &[u8], public_key: &[u8], key_type: &KeyType) -> bool {
+        if self.is_data_integrity()
+            && self.cryptosuite.as_deref().and_then(KeyType::from_cryptosuite).as_ref() != Some(key_type)
+        {
+            return false;
+        }
+
+        let signature = if self.is_data_integrity() {
+            decode_multibase(&self.proof_value)
+        } else {
+            decode_base64url(&self.proof_value)
+        };
+        match (signature, self.signing_input(payload)) {
+            (Ok(signature), Ok(input)) => verify_data(&input, &signature, public_key, key_type).unwrap_or(false),
+            _ => false,
+        }
+    }
+}
+
+/// Check whether any proof is a valid signature over the payload
+fn any_proof_valid(proofs: &[Proof], payload: &[u8], public_key: &[u8], key_type: &KeyType) -> bool {
+    proofs.iter().any(|proof| proof.verify(payload, public_key, key_type))
+}
+
+impl CredentialBuilder {
+    /// Start building a credential for the given issuer
+    pub fn new(issuer_did: String) -> Self {
+        Self {
+            issuer_did,
+            subject_id: None,
+            claims: BTreeMap::new(),
+            credential_types: Vec::new(),
+            expiration_date: None,
+            credential_status: None,
+            schema: None,
+        }
+    }
+
+    /// Set the credential subject
+    pub fn subject(mut self, subject_id: String) -> Self {
+        self.subject_id = Some(subject_id);
+        self
+    }
+
+    /// Add a single claim
+    pub fn claim(mut self, key: String, value: serde_json::Value) -> Self {
+        self.claims.insert(key, value);
+        self
+    }
+
+    /// Add a set of claims
+    pub fn claims(mut self, claims: BTreeMap<String, serde_json::Value>) -> Self {
+        self.claims.extend(claims);
+        self
+    }
+
+    /// Add a credential type
+    pub fn credential_type(mut self, credential_type: CredentialType) -> Self {
+        self.credential_types.push(credential_type);
+        self
+    }
+
+    /// Set expiration date
+    pub fn expiration(mut self, expiration: DateTime<Utc>) -> Self {
+        self.expiration_date = Some(expiration);
+        self
+    }
+
+    /// Set credential status
+    pub fn status(mut self, status: CredentialStatus) -> Self {
+        self.credential_status = Some(status);
+        self
+    }
+
+    /// Validate claims against a JSON Schema at build time and attach its reference
+    pub fn schema(mut self, reference: CredentialSchema, definition: serde_json::Value) -> Self {
+        self.schema = Some((reference, definition));
+        self
+    }
+
+    /// Build the credential, failing if the claims don't conform to the attached schema
+    pub fn build(self) -> Result<VerifiableCredential, IdentityError> {
+        if let Some((_, definition)) = &self.schema {
+            let claims = serde_json::to_value(&self.claims)?;
+            validate_json_schema(&claims, definition)?;
+        }
+
+        let mut credential = VerifiableCredential::new(self.issuer_did, self.subject_id, self.claims);
+        for credential_type in self.credential_types {
+            credential.add_type(credential_type);
+        }
+        credential.expiration_date = self.expiration_date;
+        credential.credential_status = self.credential_status;
+        if let Some((reference, _)) = self.schema {
+            credential.credential_schema = Some(vec![reference]);
+        }
+
+        credential.validate_structure()?;
+        Ok(credential)
+    }
 }
 
 impl VerifiablePresentation {
@@ -253,6 +697,87 @@ impl VerifiablePresentation {
         self.proof.as_mut().unwrap().push(proof);
     }
 
+    /// Get the bytes covered by the presentation's proofs (the presentation without its proofs)
+    pub fn signing_payload(&self) -> Result<Vec<u8>, IdentityError> {
+        let mut unsigned = self.clone();
+        unsigned.proof = None;
+        let value = serde_json::to_value(&unsigned)?;
+        Canonicalization::Jcs.canonicalize(&value)
+    }
+
+    /// Sign the presentation with any signer and attach an authentication proof
+    pub async fn sign_with(&mut self, signer: &dyn Signer, verification_method: String) -> Result<(), IdentityError> {
+        let payload = self.signing_payload()?;
+        let signature = signer.sign(&payload).await?;
+
+        self.add_proof(Proof::new(&signer.key_type(), verification_method, "authentication", &signature));
+        Ok(())
+    }
+
+    /// Get the bytes covered by a proof bound to `audience`: the unsigned presentation plus the audience
+    pub fn audience_signing_payload(&self, audience: &str) -> Result<Vec<u8>, IdentityError> {
+        let mut unsigned = self.clone();
+        unsigned.proof = None;
+        let mut value = serde_json::to_value(&unsigned)?;
+        if let serde_json::Value::Object(object) = &mut value {
+            object.insert("audience".to_string(), serde_json::Value::String(audience.to_string()));
+        }
+        Canonicalization::Jcs.canonicalize(&value)
+    }
+
+    /// Sign the presentation for a single verifier so it cannot be forwarded to another
+    pub async fn sign_for_audience(&mut self, signer: &dyn Signer, verification_method: String, audience: String) -> Result<(), IdentityError> {
+        let payload = self.audience_signing_payload(&audience)?;
+        let signature = signer.sign(&payload).await?;
+
+        let mut proof = Proof::new(&signer.key_type(), verification_method, "authentication", &signature);
+        proof.audience = Some(audience);
+        self.add_proof(proof);
+        Ok(())
+    }
+
+    /// Verify that at least one attached proof is a valid signature by the given key
+    pub fn verify_proof(&self, public_key: &[u8], key_type: &KeyType) -> Result<bool, IdentityError> {
+        let proofs = match &self.proof {
+            Some(proofs) if !proofs.is_empty() => proofs,
+            _ => return Err(IdentityError::VerificationError("Presentation has no proof".to_string())),
+        };
+
+        Ok(any_proof_valid(proofs, &self.signing_payload()?, public_key, key_type))
+    }
+
+    /// Verify the presentation as the verifier `verifier_did`.
+    ///
+    /// Audience-bound proofs only count when bound to `verifier_did`; a presentation whose
+    /// proofs are all bound to other verifiers is rejected.
+    pub fn verify_presentation(&self, public_key: &[u8], key_type: &KeyType, verifier_did: &str) -> Result<bool, IdentityError> {
+        let proofs = match &self.proof {
+            Some(proofs) if !proofs.is_empty() => proofs,
+            _ => return Err(IdentityError::VerificationError("Presentation has no proof".to_string())),
+        };
+
+        let addressed: Vec<&Proof> = proofs.iter()
+            .filter(|proof| proof.audience.as_deref().is_none_or(|audience| audience == verifier_did))
+            .collect();
+        if addressed.is_empty() {
+            return Err(IdentityError::InvalidPresentation(format!(
+                "Presentation is not addressed to {}", verifier_did
+            )));
+        }
+
+        let unbound_payload = self.signing_payload()?;
+        for proof in addressed {
+            let valid = match &proof.audience {
+                Some(audience) => proof.verify(&self.audience_signing_payload(audience)?, public_key, key_type),
+                None => proof.verify(&unbound_payload, public_key, key_type),
+            };
+            if valid {
+                return Ok(true);
+            }
+        }
+        Ok(false)
+    }
+
     /// Validate the presentation
     pub fn validate(&self) -> Result<(), IdentityError> {
         // Validate all contained credentials
@@ -263,3 +788,356 @@ impl VerifiablePresentation {
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::crypto::{generate_ed25519_keypair, generate_secp256k1_keypair};
+    use crate::signer::InMemorySigner;
+
+    fn credential() -> VerifiableCredential {
+        let mut claims = BTreeMap::new();
+        claims.insert("degree".to_string(), serde_json::json!("BSc"));
+        VerifiableCredential::new("did:example:issuer".to_string(), Some("did:example:alice".to_string()), claims)
+    }
+
+    #[tokio::test]
+    async fn eddsa_jcs_2022_proof_round_trips() {
+        let keypair = generate_ed25519_keypair().unwrap();
+        let mut credential = credential();
+        credential.sign_data_integrity(&InMemorySigner::new(keypair.clone()), "did:example:issuer#key-1".to_string()).await.unwrap();
+
+        let proof = &credential.proof.as_ref().unwrap()[0];
+        assert_eq!(proof.proof_type, DATA_INTEGRITY_PROOF_TYPE);
+        assert_eq!(proof.cryptosuite.as_deref(), Some("eddsa-jcs-2022"));
+        assert!(proof.proof_value.starts_with('z'));
+
+        let parsed: VerifiableCredential = serde_json::from_str(&serde_json::to_string(&credential).unwrap()).unwrap();
+        assert!(parsed.verify_proof(&keypair.public_key, &KeyType::Ed25519).unwrap());
+    }
+
+    #[tokio::test]
+    async fn data_integrity_proof_fails_on_tampered_credential() {
+        let keypair = generate_ed25519_keypair().unwrap();
+        let mut credential = credential();
+        credential.sign_data_integrity(&InMemorySigner::new(keypair.clone()), "did:example:issuer#key-1".to_string()).await.unwrap();
+
+        credential.credential_subject.claims.insert("degree".to_string(), serde_json::json!("PhD"));
+        assert!(!credential.verify_proof(&keypair.public_key, &KeyType::Ed25519).unwrap());
+    }
+
+    #[tokio::test]
+    async fn eddsa_jcs_2022_proof_verifies_with_a_plain_ed25519_verifier() {
+        use crate::canonicalization::canonicalize_jcs;
+        use ed25519_dalek::{Signature, VerifyingKey};
+        use sha2::{Digest, Sha256};
+
+        let keypair = generate_ed25519_keypair().unwrap();
+        let mut credential = credential();
+        credential.sign_data_integrity(&InMemorySigner::new(keypair.clone()), "did:example:issuer#key-1".to_string()).await.unwrap();
+
+        // The eddsa-jcs-2022 verification algorithm, applied to the serialized credential
+        let mut unsecured = serde_json::to_value(&credential).unwrap();
+        let proof = unsecured.as_object_mut().unwrap().remove("proof").unwrap()[0].clone();
+        let mut config = proof.clone();
+        config.as_object_mut().unwrap().remove("proofValue");
+        config["@context"] = unsecured["@context"].clone();
+        let mut input = Sha256::digest(canonicalize_jcs(&config)).to_vec();
+        input.extend(Sha256::digest(canonicalize_jcs(&unsecured)));
+
+        let signature = decode_multibase(proof["proofValue"].as_str().unwrap()).unwrap();
+        let verifying_key = VerifyingKey::from_bytes(&keypair.public_key.clone().try_into().unwrap()).unwrap();
+        assert!(verifying_key.verify_strict(&input, &Signature::from_slice(&signature).unwrap()).is_ok());
+    }
+
+    #[tokio::test]
+    async fn rdfc_labelled_proof_is_not_verified() {
+        let keypair = generate_ed25519_keypair().unwrap();
+        let mut credential = credential();
+        credential.sign_data_integrity(&InMemorySigner::new(keypair.clone()), "did:example:issuer#key-1".to_string()).await.unwrap();
+
+        let payload = credential.signing_payload().unwrap();
+        let proof = &mut credential.proof.as_mut().unwrap()[0];
+        proof.cryptosuite = Some("eddsa-rdfc-2022".to_string());
+        assert!(matches!(proof.canonicalization(), Err(IdentityError::CryptoError(message)) if message.contains("eddsa-rdfc-2022")));
+        assert!(proof.signing_input(&payload).is_err());
+        assert_eq!(KeyType::from_cryptosuite("eddsa-rdfc-2022"), None);
+        assert!(!credential.verify_proof(&keypair.public_key, &KeyType::Ed25519).unwrap());
+    }
+
+    #[tokio::test]
+    async fn secp256k1_has_no_data_integrity_suite() {
+        let keypair = generate_secp256k1_keypair().unwrap();
+        let mut credential = credential();
+
+        assert!(credential.sign_data_integrity(&InMemorySigner::new(keypair), "did:example:issuer#key-1".to_string()).await.is_err());
+    }
+
+    #[test]
+    fn ed25519_signature_2020_proofs_still_verify() {
+        let keypair = generate_ed25519_keypair().unwrap();
+        let mut credential = credential();
+        credential.sign(&keypair, "did:example:issuer#key-1".to_string()).unwrap();
+
+        let proof = &credential.proof.as_ref().unwrap()[0];
+        assert_eq!(proof.proof_type, "Ed25519Signature2020");
+        assert!(proof.cryptosuite.is_none());
+        assert!(credential.verify_proof(&keypair.public_key, &KeyType::Ed25519).unwrap());
+    }
+
+    fn degree_schema() -> (CredentialSchema, serde_json::Value) {
+        let reference = CredentialSchema {
+            id: "https://example.com/schemas/degree.json".to_string(),
+            schema_type: "JsonSchema".to_string(),
+        };
+        let definition = serde_json::json!({
+            "type": "object",
+            "required": ["degree"],
+            "properties": {
+                "degree": { "type": "string", "enum": ["BSc", "MSc", "PhD"] },
+            },
+            "additionalProperties": false,
+        });
+        (reference, definition)
+    }
+
+    #[test]
+    fn builder_attaches_schema_to_conformant_credential() {
+        let (reference, definition) = degree_schema();
+        let credential = CredentialBuilder::new("did:example:issuer".to_string())
+            .subject("did:example:alice".to_string())
+            .claim("degree".to_string(), serde_json::json!("BSc"))
+            .credential_type(CredentialType::UniversityDegreeCredential)
+            .schema(reference.clone(), definition)
+            .build()
+            .unwrap();
+
+        assert_eq!(credential.credential_schema, Some(vec![reference]));
+        assert!(credential.credential_type.contains(&"UniversityDegreeCredential".to_string()));
+    }
+
+    #[test]
+    fn builder_rejects_non_conformant_claims() {
+        let (reference, definition) = degree_schema();
+        let result = CredentialBuilder::new("did:example:issuer".to_string())
+            .subject("did:example:alice".to_string())
+            .claim("degree".to_string(), serde_json::json!("Diploma"))
+            .claim("grade".to_string(), serde_json::json!(1))
+            .schema(reference, definition)
+            .build();
+
+        let message = result.unwrap_err().to_string();
+        assert!(message.contains("$.degree"));
+        assert!(message.contains("$.grade"));
+    }
+
+    #[test]
+    fn unknown_fields_survive_a_round_trip_and_are_signed() {
+        let keypair = generate_ed25519_keypair().unwrap();
+        let mut value = serde_json::to_value(credential()).unwrap();
+        value["evidence"] = serde_json::json!([{ "type": "DocumentVerification" }]);
+
+        let mut parsed: VerifiableCredential = serde_json::from_value(value.clone()).unwrap();
+        assert_eq!(parsed.extra["evidence"], value["evidence"]);
+        assert_eq!(serde_json::to_value(&parsed).unwrap(), value);
+
+        parsed.sign(&keypair, "did:example:issuer#key-1".to_string()).unwrap();
+        let reparsed: VerifiableCredential = serde_json::from_str(&serde_json::to_string(&parsed).unwrap()).unwrap();
+        assert!(reparsed.verify_proof(&keypair.public_key, &KeyType::Ed25519).unwrap());
+
+        parsed.extra.insert("evidence".to_string(), serde_json::json!([]));
+        assert!(!parsed.verify_proof(&keypair.public_key, &KeyType::Ed25519).unwrap());
+    }
+
+    fn subject() -> CredentialSubject {
+        let mut claims = BTreeMap::new();
+        claims.insert("address".to_string(), serde_json::json!({ "country": "NZ", "city": "Wellington" }));
+        claims.insert("degrees".to_string(), serde_json::json!([{ "name": "BSc" }, { "name": "MSc" }]));
+        CredentialSubject { id: Some("did:example:alice".to_string()), claims }
+    }
+
+    #[test]
+    fn get_path_reads_nested_objects_and_array_elements() {
+        let subject = subject();
+
+        assert_eq!(subject.get_path("address.country"), Some(&serde_json::json!("NZ")));
+        assert_eq!(subject.get_path("degrees[1].name"), Some(&serde_json::json!("MSc")));
+        assert_eq!(subject.get_path("degrees.0.name"), Some(&serde_json::json!("BSc")));
+        assert_eq!(subject.get_path("degrees").and_then(|d| d.as_array()).map(Vec::len), Some(2));
+    }
+
+    #[test]
+    fn get_path_returns_none_for_missing_or_malformed_paths() {
+        let subject = subject();
+
+        assert_eq!(subject.get_path("address.postcode"), None);
+        assert_eq!(subject.get_path("degrees[2].name"), None);
+        assert_eq!(subject.get_path("address.country.code"), None);
+        assert_eq!(subject.get_path("degrees[x]"), None);
+        assert_eq!(subject.get_path("degrees[0"), None);
+        assert_eq!(subject.get_path(""), None);
+    }
+
+    #[test]
+    fn serialization_is_byte_identical_regardless_of_insertion_order() {
+        let keypair = generate_ed25519_keypair().unwrap();
+        let names = ["zeta", "alpha", "mu", "beta", "omega", "gamma", "delta", "epsilon"];
+        let mut forward = credential();
+        for name in names {
+            forward.credential_subject.claims.insert(name.to_string(), serde_json::json!({ "b": 2, "a": 1 }));
+        }
+        forward.sign(&keypair, "did:example:issuer#key-1".to_string()).unwrap();
+        let proof = &mut forward.proof.as_mut().unwrap()[0];
+        proof.additional_properties.insert("zKey".to_string(), serde_json::json!(1));
+        proof.additional_properties.insert("aKey".to_string(), serde_json::json!(2));
+
+        let mut reversed = forward.clone();
+        reversed.credential_subject.claims = names.iter().rev()
+            .map(|name| (name.to_string(), serde_json::json!({ "a": 1, "b": 2 })))
+            .chain(std::iter::once(("degree".to_string(), serde_json::json!("BSc"))))
+            .collect();
+
+        let bytes = serde_json::to_vec(&forward).unwrap();
+        for _ in 0..10 {
+            assert_eq!(serde_json::to_vec(&forward).unwrap(), bytes);
+        }
+        assert_eq!(serde_json::to_vec(&reversed).unwrap(), bytes);
+    }
+
+    #[test]
+    fn deserialization_accepts_any_field_order() {
+        let ordered = serde_json::to_string(&credential()).unwrap();
+        let value: serde_json::Value = serde_json::from_str(&ordered).unwrap();
+        let object = value.as_object().unwrap();
+
+        // Rebuild the JSON text with the top-level fields in reverse order
+        let fields: Vec<String> = object.iter().rev()
+            .map(|(key, value)| format!("{}:{}", serde_json::to_string(key).unwrap(), value))
+            .collect();
+        let shuffled = format!("{{{}}}", fields.join(","));
+        assert_ne!(shuffled, ordered);
+
+        let parsed: VerifiableCredential = serde_json::from_str(&shuffled).unwrap();
+        assert_eq!(serde_json::to_string(&parsed).unwrap(), ordered);
+    }
+
+    #[test]
+    fn claim_provenance_is_queryable_and_not_signed() {
+        let keypair = generate_ed25519_keypair().unwrap();
+        let mut credential = credential();
+        credential.sign(&keypair, "did:example:issuer#key-1".to_string()).unwrap();
+        assert!(credential.provenance_for("degree").is_empty());
+
+        let attested = |attestor: &str, level: Option<&str>| ClaimAttestation {
+            attestor_did: attestor.to_string(),
+            evidence_level: level.map(str::to_string),
+            timestamp: Utc::now(),
+        };
+        credential.add_claim_provenance("degree", attested("did:example:university", Some("high")));
+        credential.add_claim_provenance("name", attested("did:example:registry", None));
+        credential.add_claim_provenance("name", attested("did:example:university", None));
+
+        let degree = credential.provenance_for("degree");
+        assert_eq!(degree.len(), 1);
+        assert_eq!(degree[0].attestor_did, "did:example:university");
+        assert_eq!(degree[0].evidence_level.as_deref(), Some("high"));
+        let name: Vec<_> = credential.provenance_for("name").iter().map(|a| a.attestor_did.as_str()).collect();
+        assert_eq!(name, vec!["did:example:registry", "did:example:university"]);
+
+        // Provenance is recorded after issuance, so the issuer proof still verifies
+        assert!(credential.verify_proof(&keypair.public_key, &KeyType::Ed25519).unwrap());
+        let parsed: VerifiableCredential = serde_json::from_str(&serde_json::to_string(&credential).unwrap()).unwrap();
+        assert_eq!(parsed.provenance_for("name"), credential.provenance_for("name"));
+    }
+
+    fn presentation() -> VerifiablePresentation {
+        VerifiablePresentation::new(vec![credential()], Some("did:example:alice".to_string()))
+    }
+
+    #[tokio::test]
+    async fn audience_bound_presentation_is_only_accepted_by_its_verifier() {
+        let keypair = generate_ed25519_keypair().unwrap();
+        let signer = InMemorySigner::new(keypair.clone());
+        let mut presentation = presentation();
+        presentation.sign_for_audience(&signer, "did:example:alice#key-1".to_string(), "did:example:verifier-a".to_string()).await.unwrap();
+
+        assert!(presentation.verify_presentation(&keypair.public_key, &KeyType::Ed25519, "did:example:verifier-a").unwrap());
+        assert!(matches!(
+            presentation.verify_presentation(&keypair.public_key, &KeyType::Ed25519, "did:example:verifier-b"),
+            Err(IdentityError::InvalidPresentation(_))
+        ));
+
+        // Re-addressing the proof breaks its signature
+        presentation.proof.as_mut().unwrap()[0].audience = Some("did:example:verifier-b".to_string());
+        assert!(!presentation.verify_presentation(&keypair.public_key, &KeyType::Ed25519, "did:example:verifier-b").unwrap());
+    }
+
+    #[tokio::test]
+    async fn unbound_presentation_is_accepted_by_any_verifier() {
+        let keypair = generate_ed25519_keypair().unwrap();
+        let mut presentation = presentation();
+        presentation.sign_with(&InMemorySigner::new(keypair.clone()), "did:example:alice#key-1".to_string()).await.unwrap();
+
+        for verifier in ["did:example:verifier-a", "did:example:verifier-b"] {
+            assert!(presentation.verify_presentation(&keypair.public_key, &KeyType::Ed25519, verifier).unwrap());
+        }
+        let unsigned = self::presentation();
+        assert!(unsigned.verify_presentation(&keypair.public_key, &KeyType::Ed25519, "did:example:verifier-a").is_err());
+    }
+
+    /// Method id of a did:key key, which its document authorizes for every purpose
+    fn did_key_method(keypair: &CryptoKeyPair) -> String {
+        let did = crate::did_key::did_key_from_public_key(&keypair.public_key, &keypair.key_type);
+        format!("{}#{}", did, did.trim_start_matches("did:key:"))
+    }
+
+    fn add_proof_for(credential: &mut VerifiableCredential, keypair: &CryptoKeyPair, purpose: &str) {
+        let signature = sign_data(&credential.signing_payload().unwrap(), &keypair.private_key, &keypair.key_type).unwrap();
+        credential.add_proof(Proof::new(&keypair.key_type, did_key_method(keypair), purpose, &signature));
+    }
+
+    fn requirement(purpose: &str, keypair: &CryptoKeyPair) -> ProofRequirement {
+        ProofRequirement { purpose: purpose.to_string(), public_key: keypair.public_key.clone(), key_type: keypair.key_type.clone() }
+    }
+
+    #[tokio::test]
+    async fn proof_set_with_issuer_and_holder_proofs_is_accepted() {
+        let issuer = generate_secp256k1_keypair().unwrap();
+        let holder = generate_secp256k1_keypair().unwrap();
+        let mut credential = credential();
+        add_proof_for(&mut credential, &issuer, "assertionMethod");
+        add_proof_for(&mut credential, &holder, "authentication");
+
+        let requirements = [requirement("assertionMethod", &issuer), requirement("authentication", &holder)];
+        credential.verify_proof_set(&requirements, &crate::resolver::KeyResolver).await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn proof_set_missing_the_holder_proof_is_rejected() {
+        let issuer = generate_secp256k1_keypair().unwrap();
+        let holder = generate_secp256k1_keypair().unwrap();
+        let mut credential = credential();
+        add_proof_for(&mut credential, &issuer, "assertionMethod");
+
+        let requirements = [requirement("assertionMethod", &issuer), requirement("authentication", &holder)];
+        match credential.verify_proof_set(&requirements, &crate::resolver::KeyResolver).await {
+            Err(IdentityError::VerificationError(message)) => assert!(message.contains("Missing required authentication proof")),
+            other => panic!("expected a missing proof error, got {:?}", other),
+        }
+    }
+
+    #[tokio::test]
+    async fn proof_set_rejects_a_proof_by_the_wrong_key() {
+        let issuer = generate_secp256k1_keypair().unwrap();
+        let holder = generate_secp256k1_keypair().unwrap();
+        let mut credential = credential();
+        add_proof_for(&mut credential, &issuer, "assertionMethod");
+        add_proof_for(&mut credential, &issuer, "authentication");
+
+        let requirements = [requirement("assertionMethod", &issuer), requirement("authentication", &holder)];
+        assert!(matches!(
+            credential.verify_proof_set(&requirements, &crate::resolver::KeyResolver).await,
+            Err(IdentityError::VerificationError(_))
+        ));
+    }
+}
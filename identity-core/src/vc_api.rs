@@ -0,0 +1,163 @@
+//! Request and response envelopes of the W3C VC API issuance and verification endpoints
+
+use serde::{Deserialize, Serialize};
+use crate::error::IdentityError;
+use crate::vc::VerifiableCredential;
+use crate::verification::{VerificationCheck, VerificationReport};
+
+/// Body of a VC API `/credentials/issue` response
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct IssueCredentialResponse {
+    pub verifiable_credential: VerifiableCredential,
+}
+
+/// Body of a VC API `/credentials/verify` response
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct VerifyCredentialResponse {
+    pub verified: bool,
+    /// Checks that were performed and passed
+    pub checks: Vec<String>,
+    #[serde(default)]
+    pub warnings: Vec<String>,
+    /// One entry per failed check
+    #[serde(default)]
+    pub errors: Vec<String>,
+}
+
+impl IssueCredentialResponse {
+    /// Wrap an issued credential
+    pub fn new(verifiable_credential: VerifiableCredential) -> Self {
+        Self { verifiable_credential }
+    }
+
+    /// Serialize the envelope
+    pub fn to_json(&self) -> Result<String, IdentityError> {
+        Ok(serde_json::to_string(self)?)
+    }
+
+    /// Parse an envelope returned by a VC API issuer
+    pub fn from_json(json: &str) -> Result<Self, IdentityError> {
+        Ok(serde_json::from_str(json)?)
+    }
+}
+
+impl From<VerifiableCredential> for IssueCredentialResponse {
+    fn from(verifiable_credential: VerifiableCredential) -> Self {
+        Self::new(verifiable_credential)
+    }
+}
+
+impl VerifyCredentialResponse {
+    /// Serialize the envelope
+    pub fn to_json(&self) -> Result<String, IdentityError> {
+        Ok(serde_json::to_string(self)?)
+    }
+
+    /// Parse an envelope returned by a VC API verifier
+    pub fn from_json(json: &str) -> Result<Self, IdentityError> {
+        Ok(serde_json::from_str(json)?)
+    }
+}
+
+impl VerificationCheck {
+    /// Name of the check in VC API verification responses
+    pub fn vc_api_name(&self) -> &'static str {
+        match self {
+            VerificationCheck::Structure => "structure",
+            VerificationCheck::Expiration => "expiration",
+            VerificationCheck::Signature => "proof",
+            VerificationCheck::TrustedIssuer => "issuer",
+            VerificationCheck::Freshness => "freshness",
+            VerificationCheck::Status => "credentialStatus",
+        }
+    }
+}
+
+impl VerificationReport {
+    /// Convert to a VC API verification response
+    pub fn vc_api_response(&self) -> VerifyCredentialResponse {
+        let checks = self.checks.iter()
+            .filter(|check| check.passed)
+            .map(|check| check.check.vc_api_name().to_string())
+            .collect();
+        let errors = self.checks.iter()
+            .filter(|check| !check.passed)
+            .map(|check| match &check.message {
+                Some(message) => format!("{}: {}", check.check.vc_api_name(), message),
+                None => format!("{} check failed", check.check.vc_api_name()),
+            })
+            .collect();
+
+        VerifyCredentialResponse {
+            verified: self.verified,
+            checks,
+            warnings: Vec::new(),
+            errors,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::BTreeMap;
+    use chrono::{Duration, Utc};
+    use crate::crypto::{generate_secp256k1_keypair, CryptoKeyPair, KeyType};
+    use crate::verification::{verify_credential_full, VerificationOptions, VerifyMode};
+
+    fn signed(keypair: &CryptoKeyPair) -> VerifiableCredential {
+        let mut claims = BTreeMap::new();
+        claims.insert("degree".to_string(), serde_json::json!("BSc"));
+        let mut credential = VerifiableCredential::new("did:example:issuer".to_string(), Some("did:example:alice".to_string()), claims);
+        credential.sign(keypair, "did:example:issuer#key-1".to_string()).unwrap();
+        credential
+    }
+
+    #[test]
+    fn valid_credential_produces_a_verified_response() {
+        let keypair = generate_secp256k1_keypair().unwrap();
+        let report = verify_credential_full(&signed(&keypair), &keypair.public_key, &KeyType::Secp256k1, &VerificationOptions::default());
+
+        let json: serde_json::Value = serde_json::from_str(&report.vc_api_response().to_json().unwrap()).unwrap();
+
+        assert_eq!(json, serde_json::json!({
+            "verified": true,
+            "checks": ["structure", "expiration", "proof"],
+            "warnings": [],
+            "errors": [],
+        }));
+    }
+
+    #[test]
+    fn invalid_credential_lists_each_failed_check() {
+        let keypair = generate_secp256k1_keypair().unwrap();
+        let mut credential = signed(&keypair);
+        credential.set_expiration(Utc::now() - Duration::days(1));
+        let options = VerificationOptions::new(VerifyMode::Collect);
+
+        let response = verify_credential_full(&credential, &keypair.public_key, &KeyType::Secp256k1, &options).vc_api_response();
+        let parsed = VerifyCredentialResponse::from_json(&response.to_json().unwrap()).unwrap();
+
+        assert_eq!(parsed, response);
+        assert!(!response.verified);
+        assert_eq!(response.checks, vec!["structure"]);
+        assert_eq!(response.errors.len(), 2);
+        assert!(response.errors[0].starts_with("expiration"), "{:?}", response.errors);
+        assert!(response.errors[1].starts_with("proof"), "{:?}", response.errors);
+        // Verifiers may omit the optional lists
+        assert!(VerifyCredentialResponse::from_json(r#"{"verified":false,"checks":[]}"#).unwrap().errors.is_empty());
+    }
+
+    #[test]
+    fn issuance_envelope_wraps_the_credential() {
+        let credential = signed(&generate_secp256k1_keypair().unwrap());
+
+        let json = IssueCredentialResponse::from(credential.clone()).to_json().unwrap();
+        let value: serde_json::Value = serde_json::from_str(&json).unwrap();
+
+        assert_eq!(value.as_object().unwrap().keys().collect::<Vec<_>>(), vec!["verifiableCredential"]);
+        assert_eq!(value["verifiableCredential"]["id"], serde_json::json!(credential.id));
+        assert_eq!(IssueCredentialResponse::from_json(&json).unwrap().verifiable_credential, credential);
+    }
+}
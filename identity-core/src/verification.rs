@@ -0,0 +1,504 @@
+//! Full credential verification with detailed reporting
+
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use chrono::{DateTime, Duration, Utc};
+use crate::clock::{system_clock, Clock};
+use crate::crypto::KeyType;
+use crate::did::RelationshipType;
+use crate::error::IdentityError;
+use crate::resolver::DidResolver;
+use crate::status::{StatusCheckerRegistry, StatusOutcome};
+use crate::vc::VerifiableCredential;
+
+/// How verification treats failing checks
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum VerifyMode {
+    /// Stop at the first failing check
+    FailFast,
+    /// Run every check and report all failures
+    Collect,
+}
+
+/// Individual checks performed during verification
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum VerificationCheck {
+    Structure,
+    Expiration,
+    Signature,
+    TrustedIssuer,
+    Freshness,
+    Status,
+}
+
+/// Outcome of a single verification check
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CheckResult {
+    pub check: VerificationCheck,
+    pub passed: bool,
+    pub message: Option<String>,
+}
+
+/// Report produced by full credential verification
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct VerificationReport {
+    pub credential_id: String,
+    pub verified: bool,
+    pub checks: Vec<CheckResult>,
+    pub verified_at: DateTime<Utc>,
+}
+
+/// Options for full credential verification
+#[derive(Debug, Clone)]
+pub struct VerificationOptions {
+    pub mode: VerifyMode,
+    pub trusted_issuers: Option<Vec<String>>,
+    pub freshness: Option<FreshnessPolicy>,
+    /// Time source for expiration and freshness checks
+    pub clock: Arc<dyn Clock>,
+    /// Checkers for `credentialStatus` entries; status is not checked if `None`
+    pub status_checkers: Option<Arc<StatusCheckerRegistry>>,
+}
+
+/// Maximum age a credential may have to be accepted
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct FreshnessPolicy {
+    pub max_age: Duration,
+}
+
+impl FreshnessPolicy {
+    /// Create a policy rejecting credentials older than `max_age`
+    pub fn new(max_age: Duration) -> Self {
+        Self { max_age }
+    }
+
+    /// Check whether a credential is young enough
+    pub fn is_fresh(&self, credential: &VerifiableCredential) -> bool {
+        self.is_fresh_at(credential, Utc::now())
+    }
+
+    /// Check whether a credential is young enough at a given time
+    pub fn is_fresh_at(&self, credential: &VerifiableCredential, now: DateTime<Utc>) -> bool {
+        credential.age_at(now) <= self.max_age
+    }
+
+    /// Reject a credential older than the policy allows
+    pub fn check(&self, credential: &VerifiableCredential) -> Result<(), IdentityError> {
+        self.check_at(credential, Utc::now())
+    }
+
+    /// Reject a credential older than the policy allows at a given time
+    pub fn check_at(&self, credential: &VerifiableCredential, now: DateTime<Utc>) -> Result<(), IdentityError> {
+        if self.is_fresh_at(credential, now) {
+            Ok(())
+        } else {
+            Err(IdentityError::VerificationError(format!(
+                "Credential is {} days old, exceeding the maximum of {} days",
+                credential.age_at(now).num_days(),
+                self.max_age.num_days()
+            )))
+        }
+    }
+}
+
+/// Confidence contribution of a credential's age, favouring recently issued credentials
+pub fn freshness_score(age: Duration) -> f64 {
+    let age_days = age.num_days();
+    if age_days <= 30 {
+        20.0
+    } else if age_days <= 90 {
+        15.0
+    } else if age_days <= 365 {
+        10.0
+    } else {
+        5.0
+    }
+}
+
+impl VerificationReport {
+    fn new(credential: &VerifiableCredential, checks: Vec<CheckResult>, verified_at: DateTime<Utc>) -> Self {
+        Self {
+            credential_id: credential.id.clone(),
+            verified: checks.iter().all(|c| c.passed),
+            checks,
+            verified_at,
+        }
+    }
+
+    /// Get all failing checks
+    pub fn failures(&self) -> Vec<&CheckResult> {
+        self.checks.iter().filter(|c| !c.passed).collect()
+    }
+
+    /// Check whether a specific check failed
+    pub fn has_failure(&self, check: &VerificationCheck) -> bool {
+        self.checks.iter().any(|c| !c.passed && &c.check == check)
+    }
+}
+
+impl VerificationOptions {
+    /// Create options with the given mode and no issuer trust list
+    pub fn new(mode: VerifyMode) -> Self {
+        Self {
+            mode,
+            trusted_issuers: None,
+            freshness: None,
+            clock: system_clock(),
+            status_checkers: None,
+        }
+    }
+
+    /// Restrict verification to a set of trusted issuer DIDs
+    pub fn with_trusted_issuers(mut self, issuers: Vec<String>) -> Self {
+        self.trusted_issuers = Some(issuers);
+        self
+    }
+
+    /// Reject credentials older than the policy allows
+    pub fn with_freshness(mut self, policy: FreshnessPolicy) -> Self {
+        self.freshness = Some(policy);
+        self
+    }
+
+    /// Evaluate expiration and freshness against a custom time source
+    pub fn with_clock(mut self, clock: Arc<dyn Clock>) -> Self {
+        self.clock = clock;
+        self
+    }
+
+    /// Check credential status with the checker registered for its status type
+    pub fn with_status_checkers(mut self, checkers: Arc<StatusCheckerRegistry>) -> Self {
+        self.status_checkers = Some(checkers);
+        self
+    }
+}
+
+impl Default for VerificationOptions {
+    fn default() -> Self {
+        Self::new(VerifyMode::FailFast)
+    }
+}
+
+/// Find the issuer key that signed a credential's assertion proof.
+///
+/// Resolves the issuer DID and dereferences the proof's verification method, which must be
+/// one of the issuer's methods authorized for `assertionMethod`.
+pub async fn resolve_issuer_key(
+    credential: &VerifiableCredential,
+    resolver: &dyn DidResolver,
+) -> Result<(KeyType, Vec<u8>), IdentityError> {
+    let issuer = credential.get_issuer_did();
+    let proof = credential.proof.as_deref().unwrap_or_default().iter()
+        .find(|proof| proof.proof_purpose == "assertionMethod")
+        .ok_or_else(|| IdentityError::VerificationError(format!("Credential {} has no assertion proof", credential.id)))?;
+
+    let method_id = &proof.verification_method;
+    if !method_id.starts_with('#') && method_id.split('#').next() != Some(issuer) {
+        return Err(IdentityError::VerificationError(format!(
+            "Verification method {} does not belong to issuer {}", method_id, issuer
+        )));
+    }
+
+    let document = resolver.resolve(issuer).await?;
+    let method = document.authorized_method(RelationshipType::AssertionMethod, method_id)
+        .ok_or_else(|| IdentityError::VerificationError(format!(
+            "Verification method {} is not in the assertionMethod of {}", method_id, issuer
+        )))?;
+    method.key_material()
+}
+
+/// Verify a credential's structure, expiration, signature and issuer trust
+pub fn verify_credential_full(
+    credential: &VerifiableCredential,
+    issuer_public_key: &[u8],
+    key_type: &KeyType,
+    options: &VerificationOptions,
+) -> VerificationReport {
+    let report = run_checks(credential, issuer_public_key, key_type, options);
+    #[cfg(feature = "metrics")]
+    crate::metrics::credential_metrics().record_verified(credential, report.verified);
+    report
+}
+
+fn run_checks(
+    credential: &VerifiableCredential,
+    issuer_public_key: &[u8],
+    key_type: &KeyType,
+    options: &VerificationOptions,
+) -> VerificationReport {
+    let mut checks = Vec::new();
+    let now = options.clock.now();
+
+    let structure = match credential.validate_structure() {
+        Ok(()) => CheckResult::pass(VerificationCheck::Structure),
+        Err(e) => CheckResult::fail(VerificationCheck::Structure, e.to_string()),
+    };
+    if record(&mut checks, structure, options.mode) {
+        return VerificationReport::new(credential, checks, now);
+    }
+
+    let expiration = if credential.is_expired_at(now) {
+        CheckResult::fail(VerificationCheck::Expiration, "Credential has expired".to_string())
+    } else {
+        CheckResult::pass(VerificationCheck::Expiration)
+    };
+    if record(&mut checks, expiration, options.mode) {
+        return VerificationReport::new(credential, checks, now);
+    }
+
+    if let Some(checkers) = &options.status_checkers {
+        let status = match checkers.check(credential) {
+            Ok(StatusOutcome::Valid) => CheckResult::pass(VerificationCheck::Status),
+            Ok(StatusOutcome::Revoked) => CheckResult::fail(VerificationCheck::Status, "Credential has been revoked".to_string()),
+            Ok(StatusOutcome::Suspended) => CheckResult::fail(VerificationCheck::Status, "Credential is suspended".to_string()),
+            Err(e) => CheckResult::fail(VerificationCheck::Status, e.to_string()),
+        };
+        if record(&mut checks, status, options.mode) {
+            return VerificationReport::new(credential, checks, now);
+        }
+    }
+
+    if let Some(policy) = &options.freshness {
+        let freshness = match policy.check_at(credential, now) {
+            Ok(()) => CheckResult::pass(VerificationCheck::Freshness),
+            Err(e) => CheckResult::fail(VerificationCheck::Freshness, e.to_string()),
+        };
+        if record(&mut checks, freshness, options.mode) {
+            return VerificationReport::new(credential, checks, now);
+        }
+    }
+
+    let signature = match credential.verify_proof(issuer_public_key, key_type) {
+        Ok(true) => CheckResult::pass(VerificationCheck::Signature),
+        Ok(false) => CheckResult::fail(VerificationCheck::Signature, "Invalid signature".to_string()),
+        Err(e) => CheckResult::fail(VerificationCheck::Signature, e.to_string()),
+    };
+    if record(&mut checks, signature, options.mode) {
+        return VerificationReport::new(credential, checks, now);
+    }
+
+    if let Some(trusted) = &options.trusted_issuers {
+        let issuer = credential.get_issuer_did();
+        let trust = if trusted.iter().any(|t| t == issuer) {
+            CheckResult::pass(VerificationCheck::TrustedIssuer)
+        } else {
+            CheckResult::fail(VerificationCheck::TrustedIssuer, format!("Issuer {} is not trusted", issuer))
+        };
+        record(&mut checks, trust, options.mode);
+    }
+
+    VerificationReport::new(credential, checks, now)
+}
+
+/// Record a check result, returning true if verification should stop
+fn record(checks: &mut Vec<CheckResult>, result: CheckResult, mode: VerifyMode) -> bool {
+    let stop = !result.passed && mode == VerifyMode::FailFast;
+    checks.push(result);
+    stop
+}
+
+impl CheckResult {
+    fn pass(check: VerificationCheck) -> Self {
+        Self {
+            check,
+            passed: true,
+            message: None,
+        }
+    }
+
+    fn fail(check: VerificationCheck, message: String) -> Self {
+        Self {
+            check,
+            passed: false,
+            message: Some(message),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::BTreeMap;
+    use crate::crypto::generate_ed25519_keypair;
+
+    /// Credential that expired yesterday, signed by a key other than the one verified against
+    fn failing_credential() -> (VerifiableCredential, Vec<u8>) {
+        let mut claims = BTreeMap::new();
+        claims.insert("degree".to_string(), serde_json::json!("BSc"));
+        let mut credential = VerifiableCredential::new("did:example:issuer".to_string(), Some("did:example:alice".to_string()), claims);
+        credential.set_expiration(Utc::now() - Duration::days(1));
+        credential.sign(&generate_ed25519_keypair().unwrap(), "did:example:issuer#key-1".to_string()).unwrap();
+        (credential, generate_ed25519_keypair().unwrap().public_key)
+    }
+
+    #[test]
+    fn collect_mode_reports_every_failure() {
+        let (credential, wrong_key) = failing_credential();
+        let options = VerificationOptions::new(VerifyMode::Collect)
+            .with_trusted_issuers(vec!["did:example:other".to_string()]);
+
+        let report = verify_credential_full(&credential, &wrong_key, &KeyType::Ed25519, &options);
+
+        assert!(!report.verified);
+        assert_eq!(report.failures().len(), 3);
+        assert!(report.has_failure(&VerificationCheck::Expiration));
+        assert!(report.has_failure(&VerificationCheck::Signature));
+        assert!(report.has_failure(&VerificationCheck::TrustedIssuer));
+        assert!(!report.has_failure(&VerificationCheck::Structure));
+    }
+
+    #[test]
+    fn fail_fast_mode_stops_at_the_first_failure() {
+        let (credential, wrong_key) = failing_credential();
+        let options = VerificationOptions::new(VerifyMode::FailFast)
+            .with_trusted_issuers(vec!["did:example:other".to_string()]);
+
+        let report = verify_credential_full(&credential, &wrong_key, &KeyType::Ed25519, &options);
+
+        assert!(!report.verified);
+        assert_eq!(report.checks.last().unwrap().check, VerificationCheck::Expiration);
+        assert_eq!(report.failures().len(), 1);
+    }
+
+    fn issued(age: Duration) -> (VerifiableCredential, Vec<u8>) {
+        let keypair = generate_ed25519_keypair().unwrap();
+        let mut claims = BTreeMap::new();
+        claims.insert("degree".to_string(), serde_json::json!("BSc"));
+        let mut credential = VerifiableCredential::new("did:example:issuer".to_string(), Some("did:example:alice".to_string()), claims);
+        credential.issuance_date = Utc::now() - age;
+        credential.sign(&keypair, "did:example:issuer#key-1".to_string()).unwrap();
+        (credential, keypair.public_key)
+    }
+
+    #[test]
+    fn freshness_policy_accepts_fresh_and_rejects_stale_credentials() {
+        let policy = FreshnessPolicy::new(Duration::days(30));
+        let (fresh, _) = issued(Duration::days(2));
+        let (stale, _) = issued(Duration::days(365));
+
+        assert!(fresh.age() >= Duration::days(2));
+        assert!(policy.check(&fresh).is_ok());
+        assert!(!policy.is_fresh(&stale));
+        assert!(matches!(policy.check(&stale), Err(IdentityError::VerificationError(_))));
+    }
+
+    #[test]
+    fn stale_credential_fails_the_freshness_check() {
+        let options = VerificationOptions::new(VerifyMode::Collect).with_freshness(FreshnessPolicy::new(Duration::days(30)));
+        let (fresh, fresh_key) = issued(Duration::days(2));
+        let (stale, stale_key) = issued(Duration::days(365));
+
+        assert!(verify_credential_full(&fresh, &fresh_key, &KeyType::Ed25519, &options).verified);
+        let report = verify_credential_full(&stale, &stale_key, &KeyType::Ed25519, &options);
+        assert!(!report.verified);
+        assert!(report.has_failure(&VerificationCheck::Freshness));
+        assert_eq!(report.failures().len(), 1);
+    }
+
+    #[test]
+    fn freshness_score_favours_recent_credentials() {
+        assert_eq!(freshness_score(Duration::days(30)), 20.0);
+        assert_eq!(freshness_score(Duration::days(31)), 15.0);
+        assert_eq!(freshness_score(Duration::days(365)), 10.0);
+        assert_eq!(freshness_score(Duration::days(366)), 5.0);
+    }
+
+    #[test]
+    fn expiration_is_checked_against_the_options_clock() {
+        let keypair = generate_ed25519_keypair().unwrap();
+        let mut claims = BTreeMap::new();
+        claims.insert("degree".to_string(), serde_json::json!("BSc"));
+        let mut credential = VerifiableCredential::new("did:example:issuer".to_string(), Some("did:example:alice".to_string()), claims);
+        credential.set_expiration(Utc::now() + Duration::hours(1));
+        credential.sign(&keypair, "did:example:issuer#key-1".to_string()).unwrap();
+
+        let clock = crate::clock::MockClock::default();
+        let options = VerificationOptions::default().with_clock(Arc::new(clock.clone()));
+        assert!(verify_credential_full(&credential, &keypair.public_key, &KeyType::Ed25519, &options).verified);
+
+        clock.advance(Duration::hours(2));
+        let report = verify_credential_full(&credential, &keypair.public_key, &KeyType::Ed25519, &options);
+        assert!(!report.verified);
+        assert!(report.has_failure(&VerificationCheck::Expiration));
+        assert_eq!(report.verified_at, clock.now());
+    }
+
+    #[test]
+    fn status_is_checked_by_the_checker_for_its_type() {
+        let mut list = crate::status::StatusList::new(16);
+        list.set(3, true).unwrap();
+        let store = crate::status::StatusListStore::new();
+        store.insert("https://example.com/status/1".to_string(), list);
+        let options = VerificationOptions::new(VerifyMode::Collect)
+            .with_status_checkers(Arc::new(StatusCheckerRegistry::with_defaults(store)));
+
+        let verify = |status_type: &str, index: &str| {
+            let keypair = generate_ed25519_keypair().unwrap();
+            let mut credential = VerifiableCredential::new("did:example:issuer".to_string(), Some("did:example:alice".to_string()), BTreeMap::new());
+            let mut properties = BTreeMap::new();
+            properties.insert("statusListCredential".to_string(), serde_json::json!("https://example.com/status/1"));
+            properties.insert("statusListIndex".to_string(), serde_json::json!(index));
+            credential.set_status(crate::vc::CredentialStatus {
+                id: format!("https://example.com/status/1#{}", index),
+                status_type: status_type.to_string(),
+                properties,
+            });
+            credential.sign(&keypair, "did:example:issuer#key-1".to_string()).unwrap();
+            verify_credential_full(&credential, &keypair.public_key, &KeyType::Ed25519, &options)
+        };
+
+        assert!(verify("StatusList2021Entry", "2").verified);
+        let revoked = verify("StatusList2021Entry", "3");
+        assert!(revoked.has_failure(&VerificationCheck::Status));
+        assert_eq!(revoked.failures().len(), 1);
+        let unknown = verify("SuspensionList2099", "2");
+        assert!(unknown.has_failure(&VerificationCheck::Status));
+        assert!(unknown.failures()[0].message.as_deref().unwrap_or_default().contains("Unsupported credential status type"));
+    }
+
+    /// Resolver serving one fixed document
+    struct DocumentResolver(crate::did::DidDocument);
+
+    #[async_trait::async_trait]
+    impl DidResolver for DocumentResolver {
+        async fn resolve(&self, _did: &str) -> Result<crate::did::DidDocument, IdentityError> {
+            Ok(self.0.clone())
+        }
+    }
+
+    /// Credential issued by a secp256k1 did:key, with its issuer document and key
+    async fn did_key_credential() -> (VerifiableCredential, crate::did::DidDocument, Vec<u8>) {
+        let keypair = crate::crypto::generate_secp256k1_keypair().unwrap();
+        let did = crate::did_key::did_key_from_public_key(&keypair.public_key, &keypair.key_type);
+        let method = format!("{}#{}", did, did.trim_start_matches("did:key:"));
+        let mut credential = VerifiableCredential::new(did.clone(), Some("did:example:alice".to_string()), BTreeMap::new());
+        credential.sign(&keypair, method).unwrap();
+        let document = crate::resolver::KeyResolver.resolve(&did).await.unwrap();
+        (credential, document, keypair.public_key)
+    }
+
+    #[tokio::test]
+    async fn issuer_key_is_resolved_from_the_assertion_method() {
+        let (credential, document, public_key) = did_key_credential().await;
+
+        let (key_type, key) = resolve_issuer_key(&credential, &DocumentResolver(document)).await.unwrap();
+
+        assert_eq!(key_type, KeyType::Secp256k1);
+        assert_eq!(key, public_key);
+        assert!(verify_credential_full(&credential, &key, &key_type, &VerificationOptions::default()).verified);
+    }
+
+    #[tokio::test]
+    async fn method_outside_assertion_method_is_rejected() {
+        let (credential, mut document, _) = did_key_credential().await;
+        document.assertion_method = None;
+
+        let error = resolve_issuer_key(&credential, &DocumentResolver(document)).await.unwrap_err();
+        assert!(error.to_string().contains("is not in the assertionMethod"), "{}", error);
+
+        let (mut foreign, document, _) = did_key_credential().await;
+        foreign.proof.as_mut().unwrap()[0].verification_method = "did:example:mallory#key-1".to_string();
+        let error = resolve_issuer_key(&foreign, &DocumentResolver(document)).await.unwrap_err();
+        assert!(error.to_string().contains("does not belong to issuer"), "{}", error);
+    }
+}
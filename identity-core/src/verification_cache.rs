@@ -0,0 +1,236 @@
+//! Cache of verification reports for repeat verifications of the same credential
+
+use std::collections::{HashMap, HashSet};
+use chrono::{DateTime, Duration, Utc};
+use crate::crypto::{hash_data, KeyType};
+use crate::error::IdentityError;
+use crate::event_bus::{DomainEvent, EventHandler};
+use crate::vc::VerifiableCredential;
+use crate::verification::{verify_credential_full, VerificationOptions, VerificationReport};
+
+/// Verification reports keyed by credential hash, expiring after a TTL
+#[derive(Debug, Clone)]
+pub struct VerificationCache {
+    ttl: Duration,
+    entries: HashMap<String, CachedReport>,
+    by_credential: HashMap<String, HashSet<String>>, // credential id -> cache keys
+}
+
+/// Cached report with the key it was verified against
+#[derive(Debug, Clone)]
+struct CachedReport {
+    report: VerificationReport,
+    public_key: Vec<u8>,
+    key_type: KeyType,
+    expires_at: DateTime<Utc>,
+}
+
+impl VerificationCache {
+    /// Create a cache holding reports for `ttl`
+    pub fn new(ttl: Duration) -> Self {
+        Self {
+            ttl,
+            entries: HashMap::new(),
+            by_credential: HashMap::new(),
+        }
+    }
+
+    /// Cache key for a credential: SHA-256 of its canonical serialization including proofs
+    pub fn cache_key(credential: &VerifiableCredential) -> Result<String, IdentityError> {
+        let value = serde_json::to_value(credential)?;
+        Ok(hex::encode(hash_data(&serde_json::to_vec(&value)?)))
+    }
+
+    /// Verify a credential, serving a cached report for the same credential and key within the TTL
+    pub fn verify(
+        &mut self,
+        credential: &VerifiableCredential,
+        issuer_public_key: &[u8],
+        key_type: &KeyType,
+        options: &VerificationOptions,
+    ) -> Result<VerificationReport, IdentityError> {
+        let key = Self::cache_key(credential)?;
+        if let Some(report) = self.lookup(&key, issuer_public_key, key_type) {
+            return Ok(report);
+        }
+
+        let report = verify_credential_full(credential, issuer_public_key, key_type, options);
+        self.insert(key, credential, issuer_public_key, key_type, report.clone());
+        Ok(report)
+    }
+
+    /// Get a live cached report for a credential verified against the given key
+    pub fn get(
+        &self,
+        credential: &VerifiableCredential,
+        issuer_public_key: &[u8],
+        key_type: &KeyType,
+    ) -> Result<Option<VerificationReport>, IdentityError> {
+        Ok(self.lookup(&Self::cache_key(credential)?, issuer_public_key, key_type))
+    }
+
+    /// Drop every cached report for a credential id
+    pub fn invalidate(&mut self, credential_id: &str) {
+        for key in self.by_credential.remove(credential_id).unwrap_or_default() {
+            self.entries.remove(&key);
+        }
+    }
+
+    /// Remove expired entries
+    pub fn purge_expired(&mut self) {
+        let now = Utc::now();
+        self.entries.retain(|_, cached| cached.expires_at > now);
+        let entries = &self.entries;
+        self.by_credential.retain(|_, keys| {
+            keys.retain(|key| entries.contains_key(key));
+            !keys.is_empty()
+        });
+    }
+
+    /// Number of cached reports
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Whether the cache is empty
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Clear all cached reports
+    pub fn clear(&mut self) {
+        self.entries.clear();
+        self.by_credential.clear();
+    }
+
+    fn lookup(&self, key: &str, issuer_public_key: &[u8], key_type: &KeyType) -> Option<VerificationReport> {
+        self.entries.get(key)
+            .filter(|cached| cached.expires_at > Utc::now())
+            .filter(|cached| cached.public_key == issuer_public_key && &cached.key_type == key_type)
+            .map(|cached| cached.report.clone())
+    }
+
+    fn insert(
+        &mut self,
+        key: String,
+        credential: &VerifiableCredential,
+        issuer_public_key: &[u8],
+        key_type: &KeyType,
+        report: VerificationReport,
+    ) {
+        // A report must not outlive the credential's own expiration
+        let mut expires_at = Utc::now() + self.ttl;
+        if let Some(expiration) = credential.expiration_date {
+            expires_at = expires_at.min(expiration);
+        }
+
+        self.by_credential.entry(credential.id.clone()).or_default().insert(key.clone());
+        self.entries.insert(key, CachedReport {
+            report,
+            public_key: issuer_public_key.to_vec(),
+            key_type: key_type.clone(),
+            expires_at,
+        });
+    }
+}
+
+impl EventHandler for VerificationCache {
+    /// Drop reports for revoked credentials
+    fn handle_event(&mut self, event: &DomainEvent) {
+        if let DomainEvent::CredentialRevoked { credential_id, .. } = event {
+            self.invalidate(credential_id);
+        }
+    }
+
+    /// A missed event may have been a revocation, so no cached report can be trusted
+    fn handle_lagged(&mut self, _missed: u64) {
+        self.clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::BTreeMap;
+    use std::sync::Arc;
+    use crate::clock::MockClock;
+    use crate::crypto::{generate_ed25519_keypair, CryptoKeyPair};
+    use crate::verification::VerifyMode;
+
+    fn signed(keypair: &CryptoKeyPair) -> VerifiableCredential {
+        let mut claims = BTreeMap::new();
+        claims.insert("degree".to_string(), serde_json::json!("BSc"));
+        let mut credential = VerifiableCredential::new("did:example:issuer".to_string(), Some("did:example:alice".to_string()), claims);
+        credential.sign(keypair, "did:example:issuer#key-1".to_string()).unwrap();
+        credential
+    }
+
+    /// Options stamping reports with a clock the test controls, so recomputation is observable
+    fn options(clock: &MockClock) -> VerificationOptions {
+        VerificationOptions::new(VerifyMode::Collect).with_clock(Arc::new(clock.clone()))
+    }
+
+    #[test]
+    fn repeat_verification_within_ttl_is_served_from_cache() {
+        let keypair = generate_ed25519_keypair().unwrap();
+        let credential = signed(&keypair);
+        let clock = MockClock::default();
+        let mut cache = VerificationCache::new(Duration::minutes(5));
+
+        assert!(cache.get(&credential, &keypair.public_key, &KeyType::Ed25519).unwrap().is_none());
+        let first = cache.verify(&credential, &keypair.public_key, &KeyType::Ed25519, &options(&clock)).unwrap();
+        assert!(first.verified);
+        assert_eq!(cache.len(), 1);
+
+        clock.advance(Duration::seconds(30));
+        let second = cache.verify(&credential, &keypair.public_key, &KeyType::Ed25519, &options(&clock)).unwrap();
+        assert_eq!(second.verified_at, first.verified_at);
+        let cached = cache.get(&credential, &keypair.public_key, &KeyType::Ed25519).unwrap().unwrap();
+        assert_eq!(cached.verified_at, first.verified_at);
+    }
+
+    #[test]
+    fn expired_entries_and_other_keys_are_recomputed() {
+        let keypair = generate_ed25519_keypair().unwrap();
+        let other = generate_ed25519_keypair().unwrap();
+        let credential = signed(&keypair);
+        let clock = MockClock::default();
+
+        let mut cache = VerificationCache::new(Duration::minutes(5));
+        cache.verify(&credential, &keypair.public_key, &KeyType::Ed25519, &options(&clock)).unwrap();
+        let forged = cache.verify(&credential, &other.public_key, &KeyType::Ed25519, &options(&clock)).unwrap();
+        assert!(!forged.verified);
+
+        let mut expired = VerificationCache::new(Duration::zero());
+        let first = expired.verify(&credential, &keypair.public_key, &KeyType::Ed25519, &options(&clock)).unwrap();
+        clock.advance(Duration::seconds(30));
+        let second = expired.verify(&credential, &keypair.public_key, &KeyType::Ed25519, &options(&clock)).unwrap();
+        assert!(second.verified_at > first.verified_at);
+        expired.purge_expired();
+        assert!(expired.is_empty());
+    }
+
+    #[test]
+    fn revocation_event_invalidates_only_that_credential() {
+        let keypair = generate_ed25519_keypair().unwrap();
+        let revoked = signed(&keypair);
+        let kept = signed(&keypair);
+        let clock = MockClock::default();
+        let mut cache = VerificationCache::new(Duration::minutes(5));
+        cache.verify(&revoked, &keypair.public_key, &KeyType::Ed25519, &options(&clock)).unwrap();
+        cache.verify(&kept, &keypair.public_key, &KeyType::Ed25519, &options(&clock)).unwrap();
+
+        cache.handle_event(&DomainEvent::CredentialReinstated { credential_id: revoked.id.clone() });
+        assert_eq!(cache.len(), 2);
+        cache.handle_event(&DomainEvent::CredentialRevoked {
+            credential_id: revoked.id.clone(),
+            reason: "compromised".to_string(),
+        });
+
+        assert!(cache.get(&revoked, &keypair.public_key, &KeyType::Ed25519).unwrap().is_none());
+        assert!(cache.get(&kept, &keypair.public_key, &KeyType::Ed25519).unwrap().is_some());
+
+        cache.handle_lagged(1);
+        assert!(cache.is_empty());
+    }
+}
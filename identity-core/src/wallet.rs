@@ -0,0 +1,332 @@
+//! Holder wallet for owned credentials and DID keys
+
+use std::collections::{BTreeMap, BTreeSet};
+use std::path::Path;
+use serde::{Deserialize, Serialize};
+use crate::crypto::encoding::{decode_base64url, encode_base64url};
+use crate::crypto::{CryptoKeyPair, KeyType};
+use crate::disclosure::PresentationRequest;
+use crate::error::IdentityError;
+use crate::exchange::{PresentationDefinition, PresentationSubmission};
+use crate::keystore::{EncryptedKeystore, DEFAULT_KDF_ITERATIONS};
+use crate::signer::InMemorySigner;
+use crate::vc::{VerifiableCredential, VerifiablePresentation};
+
+/// Holder-side container of credentials and the keys used to present them
+#[derive(Debug, Clone, Default)]
+pub struct Wallet {
+    holder: Option<String>,
+    credentials: BTreeMap<String, VerifiableCredential>,
+    keys: BTreeMap<String, CryptoKeyPair>, // verification method id -> key pair
+}
+
+/// Serialized wallet contents, encrypted at rest
+#[derive(Serialize, Deserialize)]
+struct WalletContents {
+    holder: Option<String>,
+    credentials: Vec<VerifiableCredential>,
+    keys: Vec<StoredKey>,
+}
+
+/// Serialized key pair
+#[derive(Serialize, Deserialize)]
+struct StoredKey {
+    id: String,
+    key_type: KeyType,
+    private_key: String,
+    public_key: String,
+}
+
+impl Wallet {
+    /// Create an empty wallet for a holder DID
+    pub fn new(holder: Option<String>) -> Self {
+        Self {
+            holder,
+            ..Self::default()
+        }
+    }
+
+    /// Holder DID
+    pub fn holder(&self) -> Option<&str> {
+        self.holder.as_deref()
+    }
+
+    /// Add a credential, rejecting duplicates by id
+    pub fn add_credential(&mut self, credential: VerifiableCredential) -> Result<(), IdentityError> {
+        if self.credentials.contains_key(&credential.id) {
+            return Err(IdentityError::AlreadyExists(format!("Credential {} is already in the wallet", credential.id)));
+        }
+        self.credentials.insert(credential.id.clone(), credential);
+        Ok(())
+    }
+
+    /// Remove a credential by id
+    pub fn remove_credential(&mut self, credential_id: &str) -> Option<VerifiableCredential> {
+        self.credentials.remove(credential_id)
+    }
+
+    /// Get a credential by id
+    pub fn get_credential(&self, credential_id: &str) -> Option<&VerifiableCredential> {
+        self.credentials.get(credential_id)
+    }
+
+    /// All credentials, ordered by id
+    pub fn credentials(&self) -> Vec<&VerifiableCredential> {
+        self.credentials.values().collect()
+    }
+
+    /// Credentials declaring the given type
+    pub fn credentials_for_type(&self, credential_type: &str) -> Vec<&VerifiableCredential> {
+        self.credentials.values()
+            .filter(|credential| credential.credential_type.iter().any(|t| t == credential_type))
+            .collect()
+    }
+
+    /// Store a key pair under its verification method id
+    pub fn add_key(&mut self, verification_method: String, keypair: CryptoKeyPair) {
+        self.keys.insert(verification_method, keypair);
+    }
+
+    /// Get a key pair by verification method id
+    pub fn key(&self, verification_method: &str) -> Option<&CryptoKeyPair> {
+        self.keys.get(verification_method)
+    }
+
+    /// Build a presentation satisfying a definition, signed with the holder key.
+    ///
+    /// Only the credentials selected for the definition's input descriptors are disclosed.
+    pub async fn build_presentation(
+        &self,
+        request: &PresentationDefinition,
+        holder_key: &str,
+    ) -> Result<(VerifiablePresentation, PresentationSubmission), IdentityError> {
+        let keypair = self.keys.get(holder_key)
+            .ok_or_else(|| IdentityError::NotFound(format!("Holder key {} not in wallet", holder_key)))?;
+
+        let candidates: Vec<&VerifiableCredential> = self.credentials.values().collect();
+        let values = candidates.iter()
+            .map(serde_json::to_value)
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let mut selected = BTreeSet::new();
+        for descriptor in &request.input_descriptors {
+            let index = values.iter()
+                .position(|credential| descriptor.matches(credential))
+                .ok_or_else(|| IdentityError::InvalidPresentation(format!(
+                    "No credential satisfies input descriptor {}", descriptor.id
+                )))?;
+            selected.insert(index);
+        }
+
+        let disclosed: Vec<VerifiableCredential> = selected.into_iter()
+            .map(|index| candidates[index].clone())
+            .collect();
+        let submission = request.evaluate(&disclosed)?;
+
+        let holder = self.holder.clone()
+            .or_else(|| holder_key.split('#').next().map(str::to_string));
+        let mut presentation = VerifiablePresentation::new(disclosed, holder);
+        presentation.sign_with(&InMemorySigner::new(keypair.clone()), holder_key.to_string()).await?;
+
+        Ok((presentation, submission))
+    }
+
+    /// Build a presentation revealing only `reveal_claims` of a BBS+ signed credential, bound to a request.
+    ///
+    /// The disclosure proof is derived with the request's challenge as its nonce, and the presentation
+    /// is signed with the holder key over the challenge, domain and audience.
+    pub async fn build_disclosed_presentation(
+        &self,
+        credential_id: &str,
+        reveal_claims: &[&str],
+        request: &PresentationRequest,
+        holder_key: &str,
+    ) -> Result<VerifiablePresentation, IdentityError> {
+        let credential = self.credentials.get(credential_id)
+            .ok_or_else(|| IdentityError::NotFound(format!("Credential {} not in wallet", credential_id)))?;
+        let keypair = self.keys.get(holder_key)
+            .ok_or_else(|| IdentityError::NotFound(format!("Holder key {} not in wallet", holder_key)))?;
+
+        let claims = credential.bbs_claims();
+        let reveal_indices = reveal_claims.iter()
+            .map(|name| claims.iter().position(|(claim, _)| claim.as_str() == *name).ok_or_else(|| {
+                IdentityError::InvalidCredential(format!("Credential {} has no claim {}", credential_id, name))
+            }))
+            .collect::<Result<Vec<_>, _>>()?;
+        let derived = credential.derive_proof(&reveal_indices, request.challenge.as_bytes())?;
+
+        let holder = self.holder.clone()
+            .or_else(|| holder_key.split('#').next().map(str::to_string));
+        let mut presentation = VerifiablePresentation::new(vec![derived.to_credential()], holder);
+        presentation.sign_for_request(&InMemorySigner::new(keypair.clone()), holder_key.to_string(), request).await?;
+
+        Ok(presentation)
+    }
+
+    /// Encrypt the wallet contents under a password
+    pub fn to_keystore(&self, password: &str) -> Result<EncryptedKeystore, IdentityError> {
+        self.to_keystore_with_iterations(password, DEFAULT_KDF_ITERATIONS)
+    }
+
+    /// Encrypt the wallet contents under a password with a specific PBKDF2 iteration count
+    pub fn to_keystore_with_iterations(&self, password: &str, iterations: u32) -> Result<EncryptedKeystore, IdentityError> {
+        let contents = WalletContents {
+            holder: self.holder.clone(),
+            credentials: self.credentials.values().cloned().collect(),
+            keys: self.keys.iter()
+                .map(|(id, keypair)| StoredKey {
+                    id: id.clone(),
+                    key_type: keypair.key_type.clone(),
+                    private_key: encode_base64url(&keypair.private_key),
+                    public_key: encode_base64url(&keypair.public_key),
+                })
+                .collect(),
+        };
+
+        EncryptedKeystore::encrypt_with_iterations(&serde_json::to_vec(&contents)?, password, iterations)
+    }
+
+    /// Decrypt a wallet from a keystore
+    pub fn from_keystore(keystore: &EncryptedKeystore, password: &str) -> Result<Self, IdentityError> {
+        let contents: WalletContents = serde_json::from_slice(&keystore.decrypt(password)?)?;
+
+        let mut wallet = Self::new(contents.holder);
+        for credential in contents.credentials {
+            wallet.add_credential(credential)?;
+        }
+        for key in contents.keys {
+            wallet.add_key(key.id, CryptoKeyPair {
+                key_type: key.key_type,
+                private_key: decode_base64url(&key.private_key)?,
+                public_key: decode_base64url(&key.public_key)?,
+            });
+        }
+
+        Ok(wallet)
+    }
+
+    /// Write the encrypted wallet to a file
+    pub fn save(&self, path: &Path, password: &str) -> Result<(), IdentityError> {
+        let keystore = serde_json::to_vec_pretty(&self.to_keystore(password)?)?;
+        std::fs::write(path, keystore)
+            .map_err(|e| IdentityError::StorageError(format!("Failed to write wallet {}: {}", path.display(), e)))
+    }
+
+    /// Load an encrypted wallet from a file
+    pub fn load(path: &Path, password: &str) -> Result<Self, IdentityError> {
+        let bytes = std::fs::read(path)
+            .map_err(|e| IdentityError::StorageError(format!("Failed to read wallet {}: {}", path.display(), e)))?;
+        let keystore: EncryptedKeystore = serde_json::from_slice(&bytes)?;
+        Self::from_keystore(&keystore, password)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::crypto::generate_ed25519_keypair;
+    use crate::vc::CredentialType;
+
+    const HOLDER_KEY: &str = "did:example:alice#key-1";
+
+    fn credential(credential_type: Option<CredentialType>, degree: &str) -> VerifiableCredential {
+        let mut claims = BTreeMap::new();
+        claims.insert("degree".to_string(), serde_json::json!({ "type": degree }));
+        let mut credential = VerifiableCredential::new("did:example:university".to_string(), Some("did:example:alice".to_string()), claims);
+        if let Some(credential_type) = credential_type {
+            credential.add_type(credential_type);
+        }
+        credential
+    }
+
+    fn wallet() -> Wallet {
+        let mut wallet = Wallet::new(Some("did:example:alice".to_string()));
+        wallet.add_key(HOLDER_KEY.to_string(), generate_ed25519_keypair().unwrap());
+        wallet.add_credential(credential(None, "BachelorDegree")).unwrap();
+        wallet.add_credential(credential(Some(CredentialType::UniversityDegreeCredential), "MasterDegree")).unwrap();
+        wallet.add_credential(credential(Some(CredentialType::UniversityDegreeCredential), "BachelorDegree")).unwrap();
+        wallet
+    }
+
+    fn bachelor_request() -> PresentationDefinition {
+        serde_json::from_value(serde_json::json!({
+            "id": "degree-check",
+            "input_descriptors": [{
+                "id": "bachelor_degree",
+                "constraints": {
+                    "fields": [
+                        {
+                            "path": ["$.type"],
+                            "filter": { "type": "array", "contains": { "const": "UniversityDegreeCredential" } }
+                        },
+                        {
+                            "path": ["$.credentialSubject.degree.type"],
+                            "filter": { "type": "string", "const": "BachelorDegree" }
+                        }
+                    ]
+                }
+            }]
+        })).unwrap()
+    }
+
+    #[test]
+    fn credentials_are_indexed_by_id_and_type() {
+        let mut wallet = wallet();
+
+        assert_eq!(wallet.credentials().len(), 3);
+        assert_eq!(wallet.credentials_for_type("UniversityDegreeCredential").len(), 2);
+        assert!(wallet.credentials_for_type("DriverLicenseCredential").is_empty());
+
+        let duplicate = wallet.credentials()[0].clone();
+        assert!(matches!(wallet.add_credential(duplicate.clone()), Err(IdentityError::AlreadyExists(_))));
+        assert_eq!(wallet.remove_credential(&duplicate.id), Some(duplicate));
+        assert_eq!(wallet.credentials().len(), 2);
+    }
+
+    #[tokio::test]
+    async fn presentation_discloses_only_the_matching_credential() {
+        let wallet = wallet();
+        let keypair = wallet.key(HOLDER_KEY).unwrap().clone();
+
+        let (presentation, submission) = wallet.build_presentation(&bachelor_request(), HOLDER_KEY).await.unwrap();
+
+        assert_eq!(presentation.holder.as_deref(), Some("did:example:alice"));
+        assert_eq!(presentation.verifiable_credential.len(), 1);
+        let disclosed = &presentation.verifiable_credential[0];
+        assert_eq!(disclosed.credential_subject.claims["degree"]["type"], "BachelorDegree");
+        assert!(disclosed.credential_type.iter().any(|t| t == "UniversityDegreeCredential"));
+        assert_eq!(submission.descriptor_map[0].path, "$.verifiableCredential[0]");
+        assert!(presentation.verify_proof(&keypair.public_key, &keypair.key_type).unwrap());
+    }
+
+    #[tokio::test]
+    async fn presentation_fails_without_a_match_or_a_key() {
+        let mut wallet = Wallet::new(None);
+        wallet.add_key(HOLDER_KEY.to_string(), generate_ed25519_keypair().unwrap());
+        wallet.add_credential(credential(Some(CredentialType::UniversityDegreeCredential), "MasterDegree")).unwrap();
+
+        let unsatisfied = wallet.build_presentation(&bachelor_request(), HOLDER_KEY).await;
+        assert!(matches!(unsatisfied, Err(IdentityError::InvalidPresentation(_))));
+        let missing_key = wallet.build_presentation(&bachelor_request(), "did:example:alice#key-2").await;
+        assert!(matches!(missing_key, Err(IdentityError::NotFound(_))));
+    }
+
+    #[test]
+    fn encrypted_wallet_survives_a_reload() {
+        let wallet = wallet();
+        let path = std::env::temp_dir().join(format!("wallet-{}.json", uuid::Uuid::new_v4()));
+
+        wallet.save(&path, "hunter2").unwrap();
+        let on_disk = std::fs::read_to_string(&path).unwrap();
+        assert!(!on_disk.contains("BachelorDegree"));
+
+        let reloaded = Wallet::load(&path, "hunter2").unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        assert_eq!(reloaded.holder(), wallet.holder());
+        assert_eq!(reloaded.credentials(), wallet.credentials());
+        let key = reloaded.key(HOLDER_KEY).unwrap();
+        assert_eq!(key.private_key, wallet.key(HOLDER_KEY).unwrap().private_key);
+        assert_eq!(key.public_key, wallet.key(HOLDER_KEY).unwrap().public_key);
+    }
+}
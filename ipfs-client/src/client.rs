@@ -1,15 +1,33 @@
 //! IPFS client implementation for decentralized identity storage
 
-use ipfs_api_backend_hyper::{IpfsApi, IpfsClient as HyperIpfsClient, TryFromUri};
-use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::future::Future;
 use std::io::Cursor;
+use std::sync::OnceLock;
+use futures::stream::{self, Stream, StreamExt, TryStreamExt};
+use hyper::Uri;
+use ipfs_api_backend_hyper::{IpfsApi, TryFromUri};
+use serde::{Deserialize, Serialize};
+use crate::compression::Compression;
 use crate::error::IpfsError;
-use identity_core::{DidDocument, VerifiableCredential, VerifiablePresentation};
+use crate::pool::{PoolConfig, PoolStats, PooledBackend};
+use identity_core::{DidDocument, StreamingHasher, VerifiableCredential, VerifiablePresentation};
+
+/// Default endpoint of a local IPFS node
+pub const DEFAULT_IPFS_ENDPOINT: &str = "http://127.0.0.1:5001";
+
+/// Largest content `get_content` buffers by default, in bytes
+pub const DEFAULT_MAX_CONTENT_SIZE: u64 = 64 * 1024 * 1024;
+
+/// Pin or unpin requests kept in flight at once by `pin_many` and `unpin_many`
+pub const PIN_CONCURRENCY: usize = 8;
 
-/// IPFS client for identity management
+/// IPFS client for identity management; clones share one connection pool
+#[derive(Debug, Clone)]
 pub struct IpfsClient {
-    client: HyperIpfsClient,
+    client: PooledBackend,
     endpoint: String,
+    max_content_size: u64,
 }
 
 /// Metadata for stored content
@@ -21,6 +39,16 @@ pub struct ContentMetadata {
     pub created_at: chrono::DateTime<chrono::Utc>,
     pub tags: Vec<String>,
     pub encryption: Option<EncryptionInfo>,
+    #[serde(default)]
+    pub compression: Compression,
+}
+
+/// SHA-256 digest and size of content, computed while it streams from the node
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct ContentDigest {
+    /// Hex SHA-256 of the content, equal to `hash_data` over the whole content
+    pub sha256: String,
+    pub size: u64,
 }
 
 /// Types of content that can be stored
@@ -51,21 +79,127 @@ pub struct StorageResult {
     pub metadata: ContentMetadata,
 }
 
+/// Outcome of storing one credential of a presentation
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CredentialStoreOutcome {
+    pub index: usize,
+    pub credential_id: String,
+    pub cid: Option<String>,
+    pub error: Option<String>,
+}
+
+/// Per-credential outcomes of storing a presentation's credentials
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PresentationStoreResult {
+    pub outcomes: Vec<CredentialStoreOutcome>,
+}
+
+impl PresentationStoreResult {
+    /// Outcomes of credentials that were stored
+    pub fn stored(&self) -> Vec<&CredentialStoreOutcome> {
+        self.outcomes.iter().filter(|outcome| outcome.cid.is_some()).collect()
+    }
+
+    /// Outcomes of credentials that failed to store
+    pub fn failed(&self) -> Vec<&CredentialStoreOutcome> {
+        self.outcomes.iter().filter(|outcome| outcome.error.is_some()).collect()
+    }
+
+    /// Whether every credential was stored
+    pub fn is_complete(&self) -> bool {
+        self.outcomes.iter().all(|outcome| outcome.error.is_none())
+    }
+}
+
+/// Per-CID outcome of a bulk pin or unpin
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct BatchPinResult {
+    /// CIDs that succeeded, in request order
+    pub succeeded: Vec<String>,
+    pub failed: HashMap<String, String>, // hash -> error message
+}
+
+impl BatchPinResult {
+    /// Whether every CID succeeded
+    pub fn is_complete(&self) -> bool {
+        self.failed.is_empty()
+    }
+}
+
 impl IpfsClient {
-    /// Create a new IPFS client
+    /// Create a new IPFS client with the default pool settings
     pub fn new(endpoint: &str) -> Result<Self, IpfsError> {
-        let client = HyperIpfsClient::from_str(endpoint)
+        Self::with_pool_config(endpoint, PoolConfig::default())
+    }
+
+    /// Create a client and check the node answers a version request within `timeout`.
+    ///
+    /// Fails with `NodeUnavailable` if the node cannot be reached or does not answer in time;
+    /// use `new` to defer connecting until the first operation.
+    pub async fn connect(endpoint: &str, timeout: std::time::Duration) -> Result<Self, IpfsError> {
+        let client = Self::new(endpoint)?;
+        match tokio::time::timeout(timeout, client.client.version()).await {
+            Ok(Ok(_)) => Ok(client),
+            Ok(Err(e)) => Err(IpfsError::NodeUnavailable(format!("IPFS node at {} is unavailable: {}", endpoint, e))),
+            Err(_) => Err(IpfsError::NodeUnavailable(format!(
+                "IPFS node at {} did not respond within {:?}", endpoint, timeout
+            ))),
+        }
+    }
+
+    /// Create a new IPFS client with its own connection pool
+    pub fn with_pool_config(endpoint: &str, config: PoolConfig) -> Result<Self, IpfsError> {
+        let uri: Uri = endpoint.parse()
+            .map_err(|e| IpfsError::ConnectionError(format!("Failed to create IPFS client: {}", e)))?;
+        if uri.scheme_str() != Some("http") || uri.authority().is_none() {
+            return Err(IpfsError::ConnectionError(format!(
+                "Failed to create IPFS client: {} is not an http:// endpoint", endpoint
+            )));
+        }
+        let client = PooledBackend::from_str(endpoint)
             .map_err(|e| IpfsError::ConnectionError(format!("Failed to create IPFS client: {}", e)))?;
 
         Ok(Self {
-            client,
+            client: client.with_config(config),
             endpoint: endpoint.to_string(),
+            max_content_size: DEFAULT_MAX_CONTENT_SIZE,
         })
     }
 
+    /// Abort retrievals that would buffer more than `bytes` of content
+    pub fn with_max_content_size(mut self, bytes: u64) -> Self {
+        self.max_content_size = bytes;
+        self
+    }
+
+    /// Largest content buffered by a retrieval, in bytes
+    pub fn max_content_size(&self) -> u64 {
+        self.max_content_size
+    }
+
+    /// Pool counters shared by this client and its clones
+    pub fn pool_stats(&self) -> PoolStats {
+        self.client.stats()
+    }
+
+    /// Get the endpoint this client was created with
+    pub fn endpoint(&self) -> &str {
+        &self.endpoint
+    }
+
     /// Create a client with default local endpoint
     pub fn new_local() -> Result<Self, IpfsError> {
-        Self::new("http://127.0.0.1:5001")
+        Self::new(DEFAULT_IPFS_ENDPOINT)
+    }
+
+    /// Process-wide client for the local endpoint, so repeated callers reuse connections
+    pub fn shared_local() -> Result<Self, IpfsError> {
+        static SHARED: OnceLock<IpfsClient> = OnceLock::new();
+        if let Some(client) = SHARED.get() {
+            return Ok(client.clone());
+        }
+        let client = Self::new_local()?;
+        Ok(SHARED.get_or_init(|| client).clone())
     }
 
     /// Test connection to IPFS node
@@ -76,11 +210,14 @@ impl IpfsClient {
         }
     }
 
-    /// Store a DID document on IPFS
-    pub async fn store_did_document(&self, did_doc: &DidDocument) -> Result<StorageResult, IpfsError> {
-        let content = serde_json::to_vec(did_doc)
+    /// Store a DID document on IPFS, optionally gzip-compressed
+    pub async fn store_did_document(&self, did_doc: &DidDocument, compress: bool) -> Result<StorageResult, IpfsError> {
+        let json = did_doc.canonical_bytes()
             .map_err(|e| IpfsError::StorageError(format!("Serialization failed: {}", e)))?;
 
+        let compression = if compress { Compression::Gzip } else { Compression::None };
+        let content = compression.compress(&json)?;
+
         let metadata = ContentMetadata {
             content_type: ContentType::DidDocument,
             hash: String::new(), // Will be filled after upload
@@ -88,6 +225,7 @@ impl IpfsClient {
             created_at: chrono::Utc::now(),
             tags: vec!["did".to_string(), "document".to_string()],
             encryption: None,
+            compression,
         };
 
         self.store_content(&content, metadata).await
@@ -105,6 +243,7 @@ impl IpfsClient {
             created_at: chrono::Utc::now(),
             tags: vec!["credential".to_string(), "verifiable".to_string()],
             encryption: None,
+            compression: Compression::None,
         };
 
         self.store_content(&content, metadata).await
@@ -122,11 +261,34 @@ impl IpfsClient {
             created_at: chrono::Utc::now(),
             tags: vec!["presentation".to_string(), "verifiable".to_string()],
             encryption: None,
+            compression: Compression::None,
         };
 
         self.store_content(&content, metadata).await
     }
 
+    /// Store each credential of a presentation, continuing past failures.
+    ///
+    /// Credentials stored before a failure stay stored and pinned; nothing is rolled back.
+    pub async fn store_all(&self, presentation: &VerifiablePresentation) -> PresentationStoreResult {
+        let mut outcomes = Vec::with_capacity(presentation.verifiable_credential.len());
+
+        for (index, credential) in presentation.verifiable_credential.iter().enumerate() {
+            let (cid, error) = match self.store_credential(credential).await {
+                Ok(result) => (Some(result.hash), None),
+                Err(e) => (None, Some(e.to_string())),
+            };
+            outcomes.push(CredentialStoreOutcome {
+                index,
+                credential_id: credential.id.clone(),
+                cid,
+                error,
+            });
+        }
+
+        PresentationStoreResult { outcomes }
+    }
+
     /// Store attestation proof on IPFS
     pub async fn store_attestation_proof(&self, proof: &serde_json::Value) -> Result<StorageResult, IpfsError> {
         let content = serde_json::to_vec(proof)
@@ -139,6 +301,7 @@ impl IpfsClient {
             created_at: chrono::Utc::now(),
             tags: vec!["attestation".to_string(), "proof".to_string()],
             encryption: None,
+            compression: Compression::None,
         };
 
         self.store_content(&content, metadata).await
@@ -146,11 +309,10 @@ impl IpfsClient {
 
     /// Store arbitrary content with metadata
     pub async fn store_content(&self, content: &[u8], mut metadata: ContentMetadata) -> Result<StorageResult, IpfsError> {
-        let content_vec = content.to_vec();
-        let cursor = Cursor::new(content_vec);
+        let cursor = Cursor::new(content.to_vec());
 
         let response = self.client.add(cursor).await
-            .map_err(|e| IpfsError::StorageError(format!("IPFS add failed: {}", e)))?;
+            .map_err(|e| request_error(e, "IPFS add failed", IpfsError::StorageError))?;
 
         let hash = response.hash.clone();
         metadata.hash = response.hash;
@@ -161,26 +323,48 @@ impl IpfsClient {
         })
     }
 
-    /// Retrieve content by hash
+    /// Retrieve content by hash, failing with `QuotaExceeded` once it passes `max_content_size`
     pub async fn get_content(&self, hash: &str) -> Result<Vec<u8>, IpfsError> {
-        use futures::TryStreamExt;
+        let mut content = Vec::new();
+        read_body(self.cat(hash), Some(self.max_content_size), |chunk| content.extend_from_slice(chunk)).await?;
+        Ok(content)
+    }
 
-        let response = self.client.cat(hash);
+    /// Retrieve content, hashing each chunk as it arrives
+    pub async fn get_content_with_digest(&self, hash: &str) -> Result<(Vec<u8>, ContentDigest), IpfsError> {
+        let mut content = Vec::new();
+        let digest = self.stream_content(hash, Some(self.max_content_size), |chunk| content.extend_from_slice(chunk)).await?;
+        Ok((content, digest))
+    }
 
-        let chunks: Vec<bytes::Bytes> = response.try_collect().await
-            .map_err(|e| IpfsError::StorageError(format!("Failed to read content: {}", e)))?;
+    /// Hash content without holding it in memory
+    pub async fn hash_content(&self, hash: &str) -> Result<ContentDigest, IpfsError> {
+        self.stream_content(hash, None, |_| {}).await
+    }
 
-        let mut content = Vec::new();
-        for chunk in chunks {
-            content.extend_from_slice(&chunk);
-        }
+    /// Check streamed content against an expected hex SHA-256 digest
+    pub async fn verify_content_digest(&self, hash: &str, expected_sha256: &str) -> Result<bool, IpfsError> {
+        Ok(self.hash_content(hash).await?.sha256.eq_ignore_ascii_case(expected_sha256))
+    }
 
-        Ok(content)
+    /// Stream content chunk by chunk through a hasher and a consumer
+    async fn stream_content(&self, hash: &str, limit: Option<u64>, mut consume: impl FnMut(&[u8])) -> Result<ContentDigest, IpfsError> {
+        let mut hasher = StreamingHasher::new();
+        read_body(self.cat(hash), limit, |chunk| {
+            hasher.update(chunk);
+            consume(chunk);
+        }).await?;
+
+        Ok(ContentDigest {
+            size: hasher.bytes_hashed(),
+            sha256: hex::encode(hasher.finalize()),
+        })
     }
 
-    /// Retrieve and deserialize a DID document
-    pub async fn get_did_document(&self, hash: &str) -> Result<DidDocument, IpfsError> {
+    /// Retrieve and deserialize a DID document stored with the given compression
+    pub async fn get_did_document(&self, hash: &str, compression: Compression) -> Result<DidDocument, IpfsError> {
         let content = self.get_content(hash).await?;
+        let content = compression.decompress(&content, self.max_content_size)?;
 
         serde_json::from_slice(&content)
             .map_err(|e| IpfsError::StorageError(format!("Failed to deserialize DID document: {}", e)))
@@ -205,7 +389,7 @@ impl IpfsClient {
     /// Pin content to ensure it stays available
     pub async fn pin_content(&self, hash: &str) -> Result<(), IpfsError> {
         self.client.pin_add(hash, false).await
-            .map_err(|e| IpfsError::StorageError(format!("Pin failed: {}", e)))?;
+            .map_err(|e| request_error(e, "Pin failed", IpfsError::StorageError))?;
 
         Ok(())
     }
@@ -213,15 +397,51 @@ impl IpfsClient {
     /// Unpin content
     pub async fn unpin_content(&self, hash: &str) -> Result<(), IpfsError> {
         self.client.pin_rm(hash, false).await
-            .map_err(|e| IpfsError::StorageError(format!("Unpin failed: {}", e)))?;
+            .map_err(|e| request_error(e, "Unpin failed", IpfsError::StorageError))?;
 
         Ok(())
     }
 
+    /// Pin many CIDs concurrently, reporting each failure instead of stopping at the first
+    pub async fn pin_many(&self, hashes: &[String]) -> BatchPinResult {
+        self.pin_batch(hashes, |hash| self.pin_content(hash)).await
+    }
+
+    /// Unpin many CIDs concurrently, reporting each failure instead of stopping at the first
+    pub async fn unpin_many(&self, hashes: &[String]) -> BatchPinResult {
+        self.pin_batch(hashes, |hash| self.unpin_content(hash)).await
+    }
+
+    async fn pin_batch<'a, F, Fut>(&self, hashes: &'a [String], operation: F) -> BatchPinResult
+    where
+        F: Fn(&'a str) -> Fut,
+        Fut: Future<Output = Result<(), IpfsError>>,
+    {
+        let outcomes: Vec<(&String, Result<(), IpfsError>)> = stream::iter(hashes)
+            .map(|hash| {
+                let pinned = operation(hash);
+                async move { (hash, pinned.await) }
+            })
+            .buffered(PIN_CONCURRENCY)
+            .collect()
+            .await;
+
+        let mut result = BatchPinResult::default();
+        for (hash, outcome) in outcomes {
+            match outcome {
+                Ok(()) => result.succeeded.push(hash.clone()),
+                Err(e) => {
+                    result.failed.insert(hash.clone(), e.to_string());
+                }
+            }
+        }
+        result
+    }
+
     /// List pinned content
     pub async fn list_pinned(&self) -> Result<Vec<String>, IpfsError> {
         let response = self.client.pin_ls(None, None).await
-            .map_err(|e| IpfsError::StorageError(format!("Pin list failed: {}", e)))?;
+            .map_err(|e| request_error(e, "Pin list failed", IpfsError::StorageError))?;
 
         Ok(response.keys.into_keys().collect())
     }
@@ -239,6 +459,42 @@ impl IpfsClient {
             "golang": version.golang
         }))
     }
+
+    /// Stream content from the node, classifying its failures
+    fn cat(&self, hash: &str) -> impl Stream<Item = Result<bytes::Bytes, IpfsError>> + Unpin {
+        self.client.cat(hash)
+            .map_err(|e| request_error(e, "Failed to read content", IpfsError::StorageError))
+    }
+}
+
+/// Feed a content stream to a consumer chunk by chunk, aborting once it passes `limit` bytes
+pub async fn read_body<S>(body: S, limit: Option<u64>, mut consume: impl FnMut(&[u8])) -> Result<u64, IpfsError>
+where
+    S: Stream<Item = Result<bytes::Bytes, IpfsError>> + Unpin,
+{
+    let limit = limit.unwrap_or(u64::MAX);
+    let mut body = body;
+    let mut read = 0u64;
+    while let Some(chunk) = body.next().await {
+        let chunk = chunk?;
+        read += chunk.len() as u64;
+        if read > limit {
+            return Err(IpfsError::QuotaExceeded(format!("Content is larger than the {} byte limit", limit)));
+        }
+        consume(&chunk);
+    }
+    Ok(read)
+}
+
+/// Classify a failed node request, reporting an unreachable node as a connection error
+fn request_error(error: ipfs_api_backend_hyper::Error, message: &str, otherwise: fn(String) -> IpfsError) -> IpfsError {
+    let detail = format!("{}: {}", message, error);
+    match &error {
+        ipfs_api_backend_hyper::Error::Client(e) if e.is_connect() || e.is_closed() || e.is_incomplete_message() => {
+            IpfsError::ConnectionError(detail)
+        }
+        _ => otherwise(detail),
+    }
 }
 
 impl ContentType {
@@ -285,3 +541,371 @@ impl std::fmt::Display for ContentType {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::BTreeMap;
+    use std::sync::Arc;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use tokio::net::{TcpListener, TcpStream};
+
+    /// Read one HTTP request, with either a sized or a chunked body
+    async fn read_request(socket: &mut TcpStream) -> Vec<u8> {
+        let mut request = Vec::new();
+        let mut buffer = [0u8; 4096];
+        loop {
+            let read = socket.read(&mut buffer).await.unwrap();
+            if read == 0 {
+                return request;
+            }
+            request.extend_from_slice(&buffer[..read]);
+
+            let text = String::from_utf8_lossy(&request).to_lowercase();
+            if let Some(end) = text.find("\r\n\r\n") {
+                let content_length = text[..end].lines()
+                    .find_map(|line| line.strip_prefix("content-length:"))
+                    .and_then(|length| length.trim().parse::<usize>().ok());
+                let complete = match content_length {
+                    Some(length) => request.len() >= end + 4 + length,
+                    None => !text[..end].contains("chunked") || text.ends_with("0\r\n\r\n"),
+                };
+                if complete {
+                    return request;
+                }
+            }
+        }
+    }
+
+    /// Node answering `add` requests in order: `true` stores the content, `false` fails with a 500
+    async fn mock_node(responses: Vec<bool>) -> String {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let address = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            for (n, succeed) in responses.into_iter().enumerate() {
+                let (mut socket, _) = listener.accept().await.unwrap();
+                read_request(&mut socket).await;
+                let (status, body) = if succeed {
+                    ("200 OK", serde_json::json!({ "Name": "file", "Hash": format!("QmStored{}", n), "Size": "1" }))
+                } else {
+                    ("500 Internal Server Error", serde_json::json!({ "Message": "disk full", "Code": 0, "Type": "error" }))
+                };
+                let body = body.to_string();
+                let response = format!(
+                    "HTTP/1.1 {}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                    status, body.len(), body
+                );
+                let _ = socket.write_all(response.as_bytes()).await;
+            }
+        });
+        format!("http://{}", address)
+    }
+
+    fn presentation(credentials: usize) -> VerifiablePresentation {
+        let credentials = (0..credentials)
+            .map(|i| {
+                let mut claims = BTreeMap::new();
+                claims.insert("index".to_string(), serde_json::json!(i));
+                VerifiableCredential::new("did:example:issuer".to_string(), Some("did:example:alice".to_string()), claims)
+            })
+            .collect();
+        VerifiablePresentation::new(credentials, Some("did:example:alice".to_string()))
+    }
+
+    #[tokio::test]
+    async fn failed_credential_does_not_lose_the_others() {
+        let client = IpfsClient::new(&mock_node(vec![true, false, true]).await).unwrap();
+        let presentation = presentation(3);
+
+        let result = client.store_all(&presentation).await;
+
+        assert!(!result.is_complete());
+        let stored: Vec<(usize, Option<&str>)> = result.stored().iter()
+            .map(|outcome| (outcome.index, outcome.cid.as_deref()))
+            .collect();
+        assert_eq!(stored, vec![(0, Some("QmStored0")), (2, Some("QmStored2"))]);
+
+        let failed = result.failed();
+        assert_eq!(failed.len(), 1);
+        assert_eq!(failed[0].index, 1);
+        assert_eq!(failed[0].credential_id, presentation.verifiable_credential[1].id);
+        assert!(failed[0].cid.is_none());
+        assert!(failed[0].error.as_deref().unwrap().contains("disk full"), "{:?}", failed[0].error);
+    }
+
+    #[tokio::test]
+    async fn complete_when_every_credential_is_stored() {
+        let client = IpfsClient::new(&mock_node(vec![true, true]).await).unwrap();
+
+        let result = client.store_all(&presentation(2)).await;
+
+        assert!(result.is_complete());
+        assert_eq!(result.stored().len(), 2);
+        assert!(client.store_all(&presentation(0)).await.outcomes.is_empty());
+    }
+
+    /// Node answering every `cat` request with `content`, sent chunked in pieces
+    async fn mock_cat(content: Vec<u8>) -> String {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let address = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            while let Ok((mut socket, _)) = listener.accept().await {
+                read_request(&mut socket).await;
+                let header = "HTTP/1.1 200 OK\r\nContent-Type: text/plain\r\nTransfer-Encoding: chunked\r\nConnection: close\r\n\r\n";
+                let _ = socket.write_all(header.as_bytes()).await;
+                for piece in content.chunks(10_000) {
+                    let _ = socket.write_all(format!("{:x}\r\n", piece.len()).as_bytes()).await;
+                    let _ = socket.write_all(piece).await;
+                    let _ = socket.write_all(b"\r\n").await;
+                }
+                let _ = socket.write_all(b"0\r\n\r\n").await;
+            }
+        });
+        format!("http://{}", address)
+    }
+
+    #[tokio::test]
+    async fn streamed_content_digest_matches_hash_data() {
+        let content: Vec<u8> = (0..250_000u32).map(|i| (i % 251) as u8).collect();
+        let client = IpfsClient::new(&mock_cat(content.clone()).await).unwrap();
+        let expected = hex::encode(identity_core::hash_data(&content));
+
+        let (retrieved, digest) = client.get_content_with_digest("QmContent").await.unwrap();
+        assert_eq!(retrieved, content);
+        assert_eq!(digest, ContentDigest { sha256: expected.clone(), size: content.len() as u64 });
+
+        assert_eq!(client.hash_content("QmContent").await.unwrap().sha256, expected);
+        assert!(client.verify_content_digest("QmContent", &expected.to_uppercase()).await.unwrap());
+        assert!(!client.verify_content_digest("QmContent", &hex::encode([0u8; 32])).await.unwrap());
+    }
+
+    /// Node answering `pin/add` and `pin/rm` for any CID except `failing`, which is rejected with a 500
+    async fn mock_pins(failing: &'static str) -> String {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let address = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            while let Ok((mut socket, _)) = listener.accept().await {
+                tokio::spawn(async move {
+                    let request = String::from_utf8_lossy(&read_request(&mut socket).await).to_string();
+                    let request_line = request.lines().next().unwrap_or_default();
+                    let (status, body) = if request_line.contains(&format!("arg={}", failing)) {
+                        ("500 Internal Server Error", serde_json::json!({ "Message": "pin: block not found", "Code": 0, "Type": "error" }))
+                    } else {
+                        ("200 OK", serde_json::json!({ "Pins": [] }))
+                    };
+                    let body = body.to_string();
+                    let response = format!(
+                        "HTTP/1.1 {}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                        status, body.len(), body
+                    );
+                    let _ = socket.write_all(response.as_bytes()).await;
+                });
+            }
+        });
+        format!("http://{}", address)
+    }
+
+    #[tokio::test]
+    async fn failed_pin_does_not_stop_the_batch() {
+        let client = IpfsClient::new(&mock_pins("QmMissing").await).unwrap();
+        let hashes: Vec<String> = ["QmFirst", "QmMissing", "QmSecond", "QmThird"].iter().map(|h| h.to_string()).collect();
+
+        let result = client.pin_many(&hashes).await;
+
+        assert!(!result.is_complete());
+        assert_eq!(result.succeeded, vec!["QmFirst", "QmSecond", "QmThird"]);
+        assert_eq!(result.failed.len(), 1);
+        assert!(result.failed["QmMissing"].contains("block not found"), "{:?}", result.failed);
+    }
+
+    #[tokio::test]
+    async fn unpin_many_reports_each_cid() {
+        let client = IpfsClient::new(&mock_pins("QmMissing").await).unwrap();
+        let hashes: Vec<String> = ["QmFirst", "QmSecond"].iter().map(|h| h.to_string()).collect();
+
+        let result = client.unpin_many(&hashes).await;
+        assert!(result.is_complete());
+        assert_eq!(result.succeeded, hashes);
+
+        assert!(client.pin_many(&[]).await.is_complete());
+    }
+
+    #[tokio::test]
+    async fn oversized_content_is_rejected() {
+        let content: Vec<u8> = (0..250_000u32).map(|i| (i % 251) as u8).collect();
+        let endpoint = mock_cat(content.clone()).await;
+        let limited = IpfsClient::new(&endpoint).unwrap().with_max_content_size(100_000);
+
+        assert_eq!(IpfsClient::new(&endpoint).unwrap().max_content_size(), DEFAULT_MAX_CONTENT_SIZE);
+        assert!(matches!(limited.get_content("QmLarge").await, Err(IpfsError::QuotaExceeded(_))));
+        assert!(matches!(limited.get_content_with_digest("QmLarge").await, Err(IpfsError::QuotaExceeded(_))));
+        // Hashing does not buffer, so it is not limited
+        assert_eq!(limited.hash_content("QmLarge").await.unwrap().size, content.len() as u64);
+
+        let roomy = IpfsClient::new(&endpoint).unwrap().with_max_content_size(content.len() as u64);
+        assert_eq!(roomy.get_content("QmLarge").await.unwrap(), content);
+    }
+
+    #[tokio::test]
+    async fn oversized_stream_is_abandoned_early() {
+        use futures::SinkExt;
+
+        let (mut sender, body) = futures::channel::mpsc::channel::<Result<bytes::Bytes, IpfsError>>(1);
+        let producer = tokio::spawn(async move {
+            let mut sent = 0;
+            while sent < 1_000 && sender.send(Ok(bytes::Bytes::from(vec![0u8; 10_000]))).await.is_ok() {
+                sent += 1;
+            }
+            sent
+        });
+
+        let mut consumed = 0;
+        let result = read_body(body, Some(25_000), |chunk| consumed += chunk.len()).await;
+
+        assert!(matches!(result, Err(IpfsError::QuotaExceeded(_))));
+        assert_eq!(consumed, 20_000);
+        assert!(producer.await.unwrap() < 10, "the producer should stop once the body is dropped");
+    }
+
+    /// Node answering every request with a version report
+    async fn mock_version() -> String {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let address = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            while let Ok((mut socket, _)) = listener.accept().await {
+                read_request(&mut socket).await;
+                let body = serde_json::json!({
+                    "Version": "0.27.0", "Commit": "", "Repo": "15", "System": "amd64/linux", "Golang": "go1.21.7"
+                }).to_string();
+                let response = format!(
+                    "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                    body.len(), body
+                );
+                let _ = socket.write_all(response.as_bytes()).await;
+            }
+        });
+        format!("http://{}", address)
+    }
+
+    #[tokio::test]
+    async fn connect_succeeds_when_the_node_answers() {
+        let endpoint = mock_version().await;
+
+        let client = IpfsClient::connect(&endpoint, std::time::Duration::from_secs(5)).await.unwrap();
+
+        assert_eq!(client.get_node_info().await.unwrap()["version"], "0.27.0");
+    }
+
+    #[tokio::test]
+    async fn connect_times_out_on_a_silent_node() {
+        // Accepts connections but never answers
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let endpoint = format!("http://{}", listener.local_addr().unwrap());
+        tokio::spawn(async move {
+            let mut sockets = Vec::new();
+            while let Ok((socket, _)) = listener.accept().await {
+                sockets.push(socket);
+            }
+        });
+
+        let started = std::time::Instant::now();
+        let result = IpfsClient::connect(&endpoint, std::time::Duration::from_millis(200)).await;
+
+        assert!(matches!(&result, Err(IpfsError::NodeUnavailable(message)) if message.contains("did not respond")));
+        assert!(started.elapsed() < std::time::Duration::from_secs(5));
+    }
+
+    #[tokio::test]
+    async fn connect_fails_when_nothing_listens() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let endpoint = format!("http://{}", listener.local_addr().unwrap());
+        drop(listener);
+
+        let result = IpfsClient::connect(&endpoint, std::time::Duration::from_secs(5)).await;
+
+        assert!(matches!(result, Err(IpfsError::NodeUnavailable(_))));
+        // The lazy constructor does not touch the network
+        assert!(IpfsClient::new(&endpoint).is_ok());
+    }
+
+    /// Node answering version requests over keep-alive connections, counting the connections it accepts
+    async fn mock_keep_alive() -> (String, Arc<AtomicUsize>) {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let address = listener.local_addr().unwrap();
+        let accepted = Arc::new(AtomicUsize::new(0));
+        let counter = accepted.clone();
+        tokio::spawn(async move {
+            while let Ok((mut socket, _)) = listener.accept().await {
+                counter.fetch_add(1, Ordering::SeqCst);
+                tokio::spawn(async move {
+                    while !read_request(&mut socket).await.is_empty() {
+                        let body = serde_json::json!({
+                            "Version": "0.27.0", "Commit": "", "Repo": "15", "System": "amd64/linux", "Golang": "go1.21.7"
+                        }).to_string();
+                        let response = format!(
+                            "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nContent-Length: {}\r\n\r\n{}",
+                            body.len(), body
+                        );
+                        if socket.write_all(response.as_bytes()).await.is_err() {
+                            break;
+                        }
+                    }
+                });
+            }
+        });
+        (format!("http://{}", address), accepted)
+    }
+
+    #[tokio::test]
+    async fn sequential_operations_reuse_pooled_connections() {
+        let (endpoint, accepted) = mock_keep_alive().await;
+        let client = IpfsClient::new(&endpoint).unwrap();
+        let clones = [client.clone(), client.clone()];
+
+        for n in 0..30 {
+            let handle = if n % 3 == 0 { &client } else { &clones[n % 2] };
+            handle.get_node_info().await.unwrap();
+        }
+
+        let stats = client.pool_stats();
+        assert_eq!(stats.requests, 30);
+        assert!(stats.connections_opened < stats.requests, "{:?}", stats);
+        assert_eq!(stats.connections_opened, accepted.load(Ordering::SeqCst) as u64);
+        assert_eq!(stats.reused_connections(), 30 - stats.connections_opened);
+        assert_eq!(clones[0].pool_stats(), stats);
+
+        // A separately created client has its own pool
+        assert_eq!(IpfsClient::new(&endpoint).unwrap().pool_stats(), PoolStats { max_idle_per_host: 8, ..PoolStats::default() });
+    }
+
+    #[tokio::test]
+    async fn pool_without_idle_connections_reconnects_every_request() {
+        let (endpoint, accepted) = mock_keep_alive().await;
+        let client = IpfsClient::with_pool_config(&endpoint, PoolConfig::new(0)).unwrap();
+
+        for _ in 0..5 {
+            client.get_node_info().await.unwrap();
+        }
+
+        let stats = client.pool_stats();
+        assert_eq!(stats, PoolStats { connections_opened: 5, requests: 5, max_idle_per_host: 0 });
+        assert_eq!(accepted.load(Ordering::SeqCst), 5);
+    }
+
+    #[test]
+    fn pool_config_rejects_non_http_endpoints() {
+        assert!(IpfsClient::with_pool_config("https://ipfs.example", PoolConfig::default()).is_err());
+        assert!(IpfsClient::new("not a uri").is_err());
+    }
+
+    #[test]
+    fn shared_local_clients_share_a_pool() {
+        let first = IpfsClient::shared_local().unwrap();
+        let second = IpfsClient::shared_local().unwrap();
+
+        assert_eq!(first.endpoint(), DEFAULT_IPFS_ENDPOINT);
+        assert_eq!(first.pool_stats(), second.pool_stats());
+        assert_eq!(first.pool_stats().max_idle_per_host, PoolConfig::default().max_idle_per_host);
+    }
+}
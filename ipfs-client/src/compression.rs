@@ -0,0 +1,135 @@
+//! Content compression for IPFS storage
+
+use std::io::{Read, Write};
+use flate2::Compression as GzipLevel;
+use flate2::read::GzDecoder;
+use flate2::write::GzEncoder;
+use serde::{Deserialize, Serialize};
+use crate::error::IpfsError;
+
+/// Registry metadata key recording how a DID's stored document is compressed
+pub const COMPRESSION_METADATA_KEY: &str = "compression";
+
+/// Compression applied to stored content
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Default)]
+pub enum Compression {
+    #[default]
+    None,
+    Gzip,
+}
+
+impl Compression {
+    /// Name recorded in metadata outside `ContentMetadata`
+    pub fn name(&self) -> &'static str {
+        match self {
+            Compression::None => "none",
+            Compression::Gzip => "gzip",
+        }
+    }
+
+    /// Parse a name written by `name`, treating a missing name as uncompressed
+    pub fn from_name(name: Option<&str>) -> Result<Self, IpfsError> {
+        match name {
+            None | Some("none") => Ok(Compression::None),
+            Some("gzip") => Ok(Compression::Gzip),
+            Some(other) => Err(IpfsError::InvalidContent(format!("Unknown compression {}", other))),
+        }
+    }
+
+    /// Compress content
+    pub fn compress(&self, content: &[u8]) -> Result<Vec<u8>, IpfsError> {
+        match self {
+            Compression::None => Ok(content.to_vec()),
+            Compression::Gzip => {
+                let mut encoder = GzEncoder::new(Vec::new(), GzipLevel::default());
+                encoder.write_all(content)
+                    .map_err(|e| IpfsError::StorageError(format!("Compression failed: {}", e)))?;
+                encoder.finish()
+                    .map_err(|e| IpfsError::StorageError(format!("Compression failed: {}", e)))
+            }
+        }
+    }
+
+    /// Decompress content, failing with `QuotaExceeded` once the output passes `max_size` bytes
+    pub fn decompress(&self, content: &[u8], max_size: u64) -> Result<Vec<u8>, IpfsError> {
+        let decompressed = match self {
+            Compression::None => content.to_vec(),
+            Compression::Gzip => {
+                // Read one byte past the limit so an oversized stream is detected without inflating all of it
+                let mut decoder = GzDecoder::new(content).take(max_size.saturating_add(1));
+                let mut decompressed = Vec::new();
+                decoder.read_to_end(&mut decompressed)
+                    .map_err(|e| IpfsError::InvalidContent(format!("Decompression failed: {}", e)))?;
+                decompressed
+            }
+        };
+
+        if decompressed.len() as u64 > max_size {
+            return Err(IpfsError::QuotaExceeded(format!(
+                "Decompressed content is larger than the {} byte limit", max_size
+            )));
+        }
+        Ok(decompressed)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use identity_core::{DidDocument, PublicKeyFormat, VerificationMethod};
+
+    fn large_did_document() -> DidDocument {
+        let did = "did:example:large".to_string();
+        let mut document = DidDocument::new(did.clone());
+        for i in 0..200 {
+            document.add_verification_method(VerificationMethod {
+                id: format!("{}#key-{}", did, i),
+                method_type: "Ed25519VerificationKey2020".to_string(),
+                controller: did.clone(),
+                public_key: PublicKeyFormat::Multibase {
+                    public_key_multibase: "z6MkhaXgBZDvotDkL5257faiztiGiC2QtKLGpbnnEGta2doK".to_string(),
+                },
+            });
+        }
+        document
+    }
+
+    #[test]
+    fn gzip_round_trips_a_large_document_in_fewer_bytes() {
+        let document = large_did_document();
+        let original = document.canonical_bytes().unwrap();
+
+        let compressed = Compression::Gzip.compress(&original).unwrap();
+        let restored = Compression::Gzip.decompress(&compressed, original.len() as u64).unwrap();
+
+        assert!(compressed.len() < original.len());
+        assert_eq!(restored, original);
+        assert_eq!(serde_json::from_slice::<DidDocument>(&restored).unwrap().id, document.id);
+    }
+
+    #[test]
+    fn decompression_stops_at_the_size_limit() {
+        let bomb = Compression::Gzip.compress(&vec![0u8; 1024 * 1024]).unwrap();
+
+        assert!(bomb.len() < 16 * 1024);
+        assert!(matches!(Compression::Gzip.decompress(&bomb, 64 * 1024), Err(IpfsError::QuotaExceeded(_))));
+        assert_eq!(Compression::Gzip.decompress(&bomb, 1024 * 1024).unwrap().len(), 1024 * 1024);
+    }
+
+    #[test]
+    fn uncompressed_content_with_gzip_magic_is_returned_unchanged() {
+        let content = [0x1f, 0x8b, b'{', b'}'];
+
+        assert_eq!(Compression::None.decompress(&content, 1024).unwrap(), content);
+        assert!(Compression::Gzip.decompress(&content, 1024).is_err());
+    }
+
+    #[test]
+    fn names_round_trip_and_default_to_uncompressed() {
+        for compression in [Compression::None, Compression::Gzip] {
+            assert_eq!(Compression::from_name(Some(compression.name())).unwrap(), compression);
+        }
+        assert_eq!(Compression::from_name(None).unwrap(), Compression::None);
+        assert!(Compression::from_name(Some("zstd")).is_err());
+    }
+}
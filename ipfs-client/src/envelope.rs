@@ -0,0 +1,71 @@
+//! Typed envelopes recording the media semantics of stored JSON
+
+use serde::{Deserialize, Serialize};
+use crate::client::ContentType;
+use crate::error::IpfsError;
+
+/// Format marker identifying an envelope
+pub const ENVELOPE_FORMAT: &str = "identity-content-envelope/v1";
+
+/// Stored JSON wrapped with its content type and media type
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct ContentEnvelope {
+    pub format: String,
+    #[serde(rename = "contentType")]
+    pub content_type: ContentType,
+    #[serde(rename = "mimeType")]
+    pub mime_type: String,
+    pub data: serde_json::Value,
+}
+
+impl ContentEnvelope {
+    /// Wrap JSON data with its content type
+    pub fn new(content_type: ContentType, data: serde_json::Value) -> Self {
+        Self {
+            format: ENVELOPE_FORMAT.to_string(),
+            mime_type: content_type.mime_type().to_string(),
+            content_type,
+            data,
+        }
+    }
+
+    /// Serialize the envelope for storage
+    pub fn to_bytes(&self) -> Result<Vec<u8>, IpfsError> {
+        Ok(serde_json::to_vec(self)?)
+    }
+
+    /// Parse stored content as an envelope, returning `None` for content stored without one
+    pub fn from_bytes(content: &[u8]) -> Option<Self> {
+        serde_json::from_slice::<Self>(content)
+            .ok()
+            .filter(|envelope| envelope.format == ENVELOPE_FORMAT)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn envelope_records_content_and_media_type() {
+        let schema = serde_json::json!({ "type": "object", "required": ["degree"] });
+        let envelope = ContentEnvelope::new(ContentType::Schema, schema.clone());
+
+        let restored = ContentEnvelope::from_bytes(&envelope.to_bytes().unwrap()).unwrap();
+
+        assert_eq!(restored, envelope);
+        assert_eq!(restored.content_type, ContentType::Schema);
+        assert_eq!(restored.mime_type, "application/schema+json");
+        assert_eq!(restored.data, schema);
+    }
+
+    #[test]
+    fn content_without_an_envelope_is_not_mistaken_for_one() {
+        assert!(ContentEnvelope::from_bytes(br#"{"type": "object"}"#).is_none());
+        assert!(ContentEnvelope::from_bytes(b"not json").is_none());
+
+        let mut foreign = serde_json::to_value(ContentEnvelope::new(ContentType::Metadata, serde_json::json!({}))).unwrap();
+        foreign["format"] = serde_json::json!("other-envelope/v1");
+        assert!(ContentEnvelope::from_bytes(&serde_json::to_vec(&foreign).unwrap()).is_none());
+    }
+}
@@ -43,3 +43,10 @@ pub enum IpfsError {
     #[error("Integrity check failed: {0}")]
     IntegrityError(String),
 }
+
+impl IpfsError {
+    /// Check whether the error means the node could not be reached
+    pub fn is_connection_error(&self) -> bool {
+        matches!(self, IpfsError::ConnectionError(_) | IpfsError::NodeUnavailable(_))
+    }
+}
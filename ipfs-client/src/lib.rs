@@ -4,11 +4,21 @@
 //! DID documents, and credential proofs.
 
 pub mod client;
+pub mod pool;
 pub mod storage;
 pub mod retrieval;
+pub mod compression;
+pub mod envelope;
+pub mod reconnect;
+pub mod snapshot;
 pub mod error;
 
 pub use client::*;
+pub use pool::*;
 pub use storage::*;
 pub use retrieval::*;
+pub use compression::*;
+pub use envelope::*;
+pub use reconnect::*;
+pub use snapshot::*;
 pub use error::*;
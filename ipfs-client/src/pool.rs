@@ -0,0 +1,234 @@
+//! Shared HTTP connection pool for talking to an IPFS node
+//!
+//! `PooledBackend` is the ipfs-api hyper backend over a hyper client whose pool is
+//! configurable, since the stock backend builds its client with idle pooling disabled.
+
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::task::{Context, Poll};
+use std::time::Duration;
+use async_trait::async_trait;
+use base64::Engine;
+use bytes::Bytes;
+use futures::{FutureExt, StreamExt, TryFutureExt, TryStreamExt};
+use http::header::{HeaderName, HeaderValue, AUTHORIZATION};
+use http::StatusCode;
+use hyper::client::HttpConnector;
+use hyper::service::Service;
+use hyper::{Body, Client, Request, Response, Uri};
+use hyper_multipart_rfc7578::client::multipart;
+use ipfs_api_backend_hyper::{Error as BackendError, TryFromUri};
+use ipfs_api_prelude::{ApiRequest, Backend, BoxStream};
+use serde::{Deserialize, Serialize};
+
+/// Connection pool settings
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct PoolConfig {
+    /// Idle connections kept open per host; 0 disables reuse
+    pub max_idle_per_host: usize,
+    /// How long an idle connection is kept before closing it
+    pub idle_timeout: Duration,
+}
+
+/// Counters for a pool, shared by every clone of a client
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq)]
+pub struct PoolStats {
+    /// TCP connections opened to the node
+    pub connections_opened: u64,
+    /// Requests sent to the node
+    pub requests: u64,
+    pub max_idle_per_host: usize,
+}
+
+/// ipfs-api backend whose pooled connections are shared by all clones
+#[derive(Debug, Clone)]
+pub struct PooledBackend {
+    base: Uri,
+    client: Client<CountingConnector>,
+    counters: Arc<PoolCounters>,
+    config: PoolConfig,
+    credentials: Option<(String, String)>,
+}
+
+#[derive(Debug, Default)]
+struct PoolCounters {
+    connections_opened: AtomicU64,
+    requests: AtomicU64,
+}
+
+/// HTTP connector that counts the connections it opens
+#[derive(Debug, Clone)]
+struct CountingConnector {
+    inner: HttpConnector,
+    counters: Arc<PoolCounters>,
+}
+
+impl Default for PoolConfig {
+    fn default() -> Self {
+        Self {
+            max_idle_per_host: 8,
+            idle_timeout: Duration::from_secs(90),
+        }
+    }
+}
+
+impl PoolConfig {
+    /// Keep up to `max_idle_per_host` idle connections
+    pub fn new(max_idle_per_host: usize) -> Self {
+        Self { max_idle_per_host, ..Self::default() }
+    }
+
+    /// Close idle connections after a timeout
+    pub fn with_idle_timeout(mut self, idle_timeout: Duration) -> Self {
+        self.idle_timeout = idle_timeout;
+        self
+    }
+}
+
+impl PoolStats {
+    /// Requests served over an already open connection
+    pub fn reused_connections(&self) -> u64 {
+        self.requests.saturating_sub(self.connections_opened)
+    }
+}
+
+impl PooledBackend {
+    /// Create a backend for an API base URI with an empty pool
+    pub fn new(base: Uri, config: PoolConfig) -> Self {
+        let counters = Arc::new(PoolCounters::default());
+        let connector = CountingConnector {
+            inner: HttpConnector::new(),
+            counters: counters.clone(),
+        };
+        let client = Client::builder()
+            .pool_max_idle_per_host(config.max_idle_per_host)
+            .pool_idle_timeout(config.idle_timeout)
+            .build(connector);
+
+        Self { base, client, counters, config, credentials: None }
+    }
+
+    /// Replace the pool with an empty one using `config`
+    pub fn with_config(self, config: PoolConfig) -> Self {
+        Self { credentials: self.credentials, ..Self::new(self.base, config) }
+    }
+
+    /// Send a request over a pooled connection
+    fn send(&self, request: Request<Body>) -> hyper::client::ResponseFuture {
+        self.counters.requests.fetch_add(1, Ordering::Relaxed);
+        self.client.request(request)
+    }
+
+    /// Snapshot of the pool counters
+    pub fn stats(&self) -> PoolStats {
+        PoolStats {
+            connections_opened: self.counters.connections_opened.load(Ordering::Relaxed),
+            requests: self.counters.requests.load(Ordering::Relaxed),
+            max_idle_per_host: self.config.max_idle_per_host,
+        }
+    }
+}
+
+impl TryFromUri for PooledBackend {
+    fn build_with_base_uri(base: Uri) -> Self {
+        Self::new(base, PoolConfig::default())
+    }
+}
+
+// Mirrors the stock hyper backend, sending through the configured pool
+#[async_trait(?Send)]
+impl Backend for PooledBackend {
+    type HttpRequest = Request<Body>;
+    type HttpResponse = Response<Body>;
+    type Error = BackendError;
+
+    fn build_base_request<Req>(&self, req: Req, form: Option<multipart::Form<'static>>) -> Result<Request<Body>, BackendError>
+    where
+        Req: ApiRequest,
+    {
+        let url = req.absolute_url(&self.base)?;
+        let mut builder = Request::builder().method(Req::METHOD).uri(url);
+        if let Some((username, password)) = &self.credentials {
+            let encoded = base64::engine::general_purpose::STANDARD.encode(format!("{}:{}", username, password));
+            builder = builder.header(AUTHORIZATION, format!("Basic {}", encoded));
+        }
+
+        let request = match form {
+            Some(form) => form.set_body_convert::<Body, multipart::Body>(builder),
+            None => builder.body(Body::empty()),
+        }?;
+        Ok(request)
+    }
+
+    fn get_header(res: &Response<Body>, key: HeaderName) -> Option<&HeaderValue> {
+        res.headers().get(key)
+    }
+
+    async fn request_raw<Req>(&self, req: Req, form: Option<multipart::Form<'static>>) -> Result<(StatusCode, Bytes), BackendError>
+    where
+        Req: ApiRequest,
+    {
+        let request = self.build_base_request(req, form)?;
+        let response = self.send(request).await?;
+        let status = response.status();
+        let body = hyper::body::to_bytes(response.into_body()).await?;
+        Ok((status, body))
+    }
+
+    fn response_to_byte_stream(res: Response<Body>) -> BoxStream<Bytes, BackendError> {
+        Box::new(res.into_body().err_into())
+    }
+
+    fn request_stream<Res, F>(&self, req: Request<Body>, process: F) -> BoxStream<Res, BackendError>
+    where
+        F: 'static + Send + Fn(Response<Body>) -> BoxStream<Res, BackendError>,
+    {
+        let stream = self.send(req)
+            .err_into()
+            .map_ok(move |response| match response.status() {
+                StatusCode::OK => process(response).right_stream(),
+                // Read the whole error body so the API error can be built from it
+                _ => hyper::body::to_bytes(response.into_body())
+                    .boxed()
+                    .map(|body| match body {
+                        Ok(body) => Err(Self::process_error_from_body(body)),
+                        Err(e) => Err(e.into()),
+                    })
+                    .into_stream()
+                    .left_stream(),
+            })
+            .try_flatten_stream();
+
+        Box::new(stream)
+    }
+
+    fn with_credentials<U, P>(self, username: U, password: P) -> Self
+    where
+        U: Into<String>,
+        P: Into<String>,
+    {
+        Self { credentials: Some((username.into(), password.into())), ..self }
+    }
+}
+
+impl Service<Uri> for CountingConnector {
+    type Response = <HttpConnector as Service<Uri>>::Response;
+    type Error = <HttpConnector as Service<Uri>>::Error;
+    type Future = Pin<Box<dyn Future<Output = Result<Self::Response, Self::Error>> + Send>>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, uri: Uri) -> Self::Future {
+        let connecting = self.inner.call(uri);
+        let counters = self.counters.clone();
+        Box::pin(async move {
+            let stream = connecting.await?;
+            counters.connections_opened.fetch_add(1, Ordering::Relaxed);
+            Ok(stream)
+        })
+    }
+}
@@ -0,0 +1,184 @@
+//! Client wrapper that re-creates its connection after the node drops it
+
+use std::future::Future;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicU64, Ordering};
+use tokio::sync::RwLock;
+use crate::client::{ContentMetadata, IpfsClient, StorageResult};
+use crate::error::IpfsError;
+
+/// Creates a client for an endpoint
+pub type Connector<C> = Box<dyn Fn(&str) -> Result<C, IpfsError> + Send + Sync>;
+
+/// Wrapper that reconnects and retries once when a call fails with a connection error
+pub struct Reconnecting<C> {
+    endpoint: String,
+    connector: Connector<C>,
+    client: RwLock<Arc<C>>,
+    reconnections: AtomicU64,
+}
+
+/// IPFS client that survives node restarts
+pub type ReconnectingIpfsClient = Reconnecting<IpfsClient>;
+
+impl<C> Reconnecting<C> {
+    /// Connect to an endpoint using a custom connector
+    pub fn with_connector(endpoint: &str, connector: Connector<C>) -> Result<Self, IpfsError> {
+        let client = connector(endpoint)?;
+
+        Ok(Self {
+            endpoint: endpoint.to_string(),
+            connector,
+            client: RwLock::new(Arc::new(client)),
+            reconnections: AtomicU64::new(0),
+        })
+    }
+
+    /// Get the endpoint clients are created for
+    pub fn endpoint(&self) -> &str {
+        &self.endpoint
+    }
+
+    /// Number of times the underlying client has been re-created
+    pub fn reconnection_count(&self) -> u64 {
+        self.reconnections.load(Ordering::Relaxed)
+    }
+
+    /// Get the current underlying client
+    pub async fn client(&self) -> Arc<C> {
+        self.client.read().await.clone()
+    }
+
+    /// Replace the underlying client with a fresh one
+    pub async fn reconnect(&self) -> Result<(), IpfsError> {
+        let client = (self.connector)(&self.endpoint)?;
+        *self.client.write().await = Arc::new(client);
+        self.reconnections.fetch_add(1, Ordering::Relaxed);
+        Ok(())
+    }
+
+    /// Run an operation, reconnecting and retrying once if it fails to reach the node
+    pub async fn call<T, F, Fut>(&self, operation: F) -> Result<T, IpfsError>
+    where
+        F: Fn(Arc<C>) -> Fut,
+        Fut: Future<Output = Result<T, IpfsError>>,
+    {
+        match operation(self.client().await).await {
+            Err(e) if e.is_connection_error() => {
+                self.reconnect().await?;
+                operation(self.client().await).await
+            }
+            result => result,
+        }
+    }
+}
+
+impl Reconnecting<IpfsClient> {
+    /// Connect to an IPFS node
+    pub fn new(endpoint: &str) -> Result<Self, IpfsError> {
+        Self::with_connector(endpoint, Box::new(IpfsClient::new))
+    }
+
+    /// Test connection to the IPFS node
+    pub async fn test_connection(&self) -> Result<bool, IpfsError> {
+        self.call(|client| async move { client.test_connection().await }).await
+    }
+
+    /// Store arbitrary content with metadata
+    pub async fn store_content(&self, content: &[u8], metadata: ContentMetadata) -> Result<StorageResult, IpfsError> {
+        self.call(|client| {
+            let metadata = metadata.clone();
+            async move { client.store_content(content, metadata).await }
+        }).await
+    }
+
+    /// Retrieve content by hash
+    pub async fn get_content(&self, hash: &str) -> Result<Vec<u8>, IpfsError> {
+        self.call(|client| async move { client.get_content(hash).await }).await
+    }
+
+    /// Pin content to ensure it stays available
+    pub async fn pin_content(&self, hash: &str) -> Result<(), IpfsError> {
+        self.call(|client| async move { client.pin_content(hash).await }).await
+    }
+
+    /// Unpin content
+    pub async fn unpin_content(&self, hash: &str) -> Result<(), IpfsError> {
+        self.call(|client| async move { client.unpin_content(hash).await }).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::AtomicUsize;
+
+    /// Client whose connection dies once the node restarts
+    struct MockClient {
+        generation: usize,
+        restarted_before: usize,
+        calls: Arc<AtomicUsize>,
+    }
+
+    impl MockClient {
+        async fn get_content(&self) -> Result<Vec<u8>, IpfsError> {
+            self.calls.fetch_add(1, Ordering::SeqCst);
+            if self.generation < self.restarted_before {
+                return Err(IpfsError::ConnectionError("connection closed before message completed".to_string()));
+            }
+            Ok(format!("generation {}", self.generation).into_bytes())
+        }
+    }
+
+    /// Wrapper whose first `restarted_before` clients see a dropped connection
+    fn mock(restarted_before: usize) -> (Reconnecting<MockClient>, Arc<AtomicUsize>) {
+        let calls = Arc::new(AtomicUsize::new(0));
+        let generation = AtomicUsize::new(0);
+        let connector_calls = calls.clone();
+        let client = Reconnecting::with_connector("http://127.0.0.1:5001", Box::new(move |_| {
+            Ok(MockClient {
+                generation: generation.fetch_add(1, Ordering::SeqCst),
+                restarted_before,
+                calls: connector_calls.clone(),
+            })
+        })).unwrap();
+        (client, calls)
+    }
+
+    #[tokio::test]
+    async fn dropped_connection_recovers_with_a_fresh_client() {
+        let (client, calls) = mock(1);
+
+        let content = client.call(|c| async move { c.get_content().await }).await.unwrap();
+
+        assert_eq!(content, b"generation 1");
+        assert_eq!(client.reconnection_count(), 1);
+        assert_eq!(calls.load(Ordering::SeqCst), 2);
+
+        // The fresh client is kept for later calls
+        client.call(|c| async move { c.get_content().await }).await.unwrap();
+        assert_eq!(client.reconnection_count(), 1);
+    }
+
+    #[tokio::test]
+    async fn retries_only_once() {
+        let (client, calls) = mock(usize::MAX);
+
+        let result = client.call(|c| async move { c.get_content().await }).await;
+
+        assert!(matches!(result, Err(IpfsError::ConnectionError(_))));
+        assert_eq!(client.reconnection_count(), 1);
+        assert_eq!(calls.load(Ordering::SeqCst), 2);
+    }
+
+    #[tokio::test]
+    async fn other_errors_are_not_retried() {
+        let (client, _) = mock(0);
+
+        let result: Result<(), _> = client.call(|_| async { Err(IpfsError::StorageError("not found".to_string())) }).await;
+
+        assert!(matches!(result, Err(IpfsError::StorageError(_))));
+        assert_eq!(client.reconnection_count(), 0);
+        assert_eq!(client.endpoint(), "http://127.0.0.1:5001");
+    }
+}
@@ -2,16 +2,31 @@
 
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
+use std::sync::Arc;
 use chrono::{DateTime, Utc};
 use crate::client::{IpfsClient, ContentType};
+use crate::compression::Compression;
+use crate::envelope::ContentEnvelope;
 use crate::error::IpfsError;
-use identity_core::{DidDocument, VerifiableCredential, VerifiablePresentation};
+use identity_core::{system_clock, Clock, DidDocument, VerifiableCredential, VerifiablePresentation};
 
 /// Retrieval manager for fetching and caching content
 pub struct RetrievalManager {
     client: IpfsClient,
     cache: HashMap<String, CachedContent>,
     cache_ttl: chrono::Duration,
+    content_type_ttls: HashMap<ContentType, chrono::Duration>,
+    staleness_policy: StalenessPolicy,
+    clock: Arc<dyn Clock>,
+}
+
+/// How to treat expired cache entries when the backend cannot be reached
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct StalenessPolicy {
+    /// Serve expired cache entries, flagged as stale, when fetching fails
+    pub serve_stale_on_error: bool,
+    /// Maximum time past expiry a stale entry may still be served; unbounded if `None`
+    pub max_stale: Option<chrono::Duration>,
 }
 
 /// Cached content with metadata
@@ -45,6 +60,9 @@ pub struct BatchRetrievalResult {
     pub failed: HashMap<String, String>, // hash -> error message
     pub cache_hits: usize,
     pub cache_misses: usize,
+    /// Hashes served from expired cache entries after a backend failure
+    #[serde(default)]
+    pub stale: Vec<String>,
 }
 
 /// Content verification result
@@ -54,27 +72,64 @@ pub struct VerificationResult {
     pub is_valid: bool,
     pub content_type: Option<ContentType>,
     pub size: u64,
+    /// Hex SHA-256 of the content as stored, computed while it was retrieved
+    #[serde(default)]
+    pub sha256: String,
     pub errors: Vec<String>,
 }
 
 impl RetrievalManager {
     /// Create a new retrieval manager
     pub fn new(client: IpfsClient) -> Self {
+        Self::new_with_clock(client, system_clock())
+    }
+
+    /// Create a retrieval manager whose cache TTL is measured by the given clock
+    pub fn new_with_clock(client: IpfsClient, clock: Arc<dyn Clock>) -> Self {
         Self {
             client,
             cache: HashMap::new(),
             cache_ttl: chrono::Duration::hours(1), // 1 hour default TTL
+            content_type_ttls: default_content_type_ttls(),
+            staleness_policy: StalenessPolicy::default(),
+            clock,
         }
     }
 
-    /// Set cache TTL
+    /// Set the policy for serving expired cache entries on backend failure
+    pub fn set_staleness_policy(&mut self, policy: StalenessPolicy) {
+        self.staleness_policy = policy;
+    }
+
+    /// Set the cache TTL for content types without their own TTL
     pub fn set_cache_ttl(&mut self, ttl: chrono::Duration) {
         self.cache_ttl = ttl;
     }
 
-    /// Retrieve and parse a DID document
-    pub async fn get_did_document(&mut self, hash: &str, options: RetrievalOptions) -> Result<DidDocument, IpfsError> {
+    /// Set the cache TTL for one content type, overriding the global TTL
+    pub fn set_content_type_ttl(&mut self, content_type: ContentType, ttl: chrono::Duration) {
+        self.content_type_ttls.insert(content_type, ttl);
+    }
+
+    /// Remove a content type's TTL, reverting it to the global TTL
+    pub fn remove_content_type_ttl(&mut self, content_type: &ContentType) {
+        self.content_type_ttls.remove(content_type);
+    }
+
+    /// TTL applied to cached content of a type
+    pub fn cache_ttl_for(&self, content_type: &ContentType) -> chrono::Duration {
+        self.content_type_ttls.get(content_type).copied().unwrap_or(self.cache_ttl)
+    }
+
+    /// Retrieve and parse a DID document stored with the given compression
+    pub async fn get_did_document(
+        &mut self,
+        hash: &str,
+        compression: Compression,
+        options: RetrievalOptions,
+    ) -> Result<DidDocument, IpfsError> {
         let content = self.get_content_with_cache(hash, &options).await?;
+        let content = compression.decompress(&content, self.client.max_content_size())?;
 
         serde_json::from_slice(&content)
             .map_err(|e| IpfsError::StorageError(format!("Failed to parse DID document: {}", e)))
@@ -96,12 +151,23 @@ impl RetrievalManager {
             .map_err(|e| IpfsError::StorageError(format!("Failed to parse presentation: {}", e)))
     }
 
-    /// Retrieve raw content as JSON
+    /// Retrieve raw content as JSON, unwrapping it from its envelope if present
     pub async fn get_json(&mut self, hash: &str, options: RetrievalOptions) -> Result<serde_json::Value, IpfsError> {
+        self.get_typed_json(hash, options).await.map(|(_, data)| data)
+    }
+
+    /// Retrieve JSON together with its content type, taken from the envelope or sniffed for legacy content
+    pub async fn get_typed_json(&mut self, hash: &str, options: RetrievalOptions) -> Result<(ContentType, serde_json::Value), IpfsError> {
         let content = self.get_content_with_cache(hash, &options).await?;
 
-        serde_json::from_slice(&content)
-            .map_err(|e| IpfsError::StorageError(format!("Failed to parse JSON: {}", e)))
+        if let Some(envelope) = ContentEnvelope::from_bytes(&content) {
+            return Ok((envelope.content_type, envelope.data));
+        }
+
+        let data = serde_json::from_slice(&content)
+            .map_err(|e| IpfsError::StorageError(format!("Failed to parse JSON: {}", e)))?;
+        let content_type = self.detect_content_type(&content).unwrap_or(ContentType::Metadata);
+        Ok((content_type, data))
     }
 
     /// Retrieve raw content
@@ -115,6 +181,7 @@ impl RetrievalManager {
         let mut failed = HashMap::new();
         let mut cache_hits = 0;
         let mut cache_misses = 0;
+        let mut stale = Vec::new();
 
         for hash in batch.hashes {
             // Check cache first if enabled
@@ -149,7 +216,22 @@ impl RetrievalManager {
                     }
                 }
                 Err(e) => {
-                    failed.insert(hash, e.to_string());
+                    let stale_value = if batch.options.use_cache {
+                        self.get_stale_from_cache(&hash)
+                            .and_then(|cached| serde_json::from_slice(&cached.data).ok())
+                    } else {
+                        None
+                    };
+
+                    match stale_value {
+                        Some(value) => {
+                            successful.insert(hash.clone(), value);
+                            stale.push(hash);
+                        }
+                        None => {
+                            failed.insert(hash, e.to_string());
+                        }
+                    }
                 }
             }
         }
@@ -159,12 +241,13 @@ impl RetrievalManager {
             failed,
             cache_hits,
             cache_misses,
+            stale,
         }
     }
 
     /// Verify content integrity and structure
     pub async fn verify_content(&self, hash: &str, expected_type: Option<ContentType>) -> Result<VerificationResult, IpfsError> {
-        let content = self.client.get_content(hash).await?;
+        let (content, digest) = self.client.get_content_with_digest(hash).await?;
         let mut errors = Vec::new();
         let mut is_valid = true;
 
@@ -174,8 +257,12 @@ impl RetrievalManager {
             is_valid = false;
         }
 
-        // Try to determine content type
+        // Try to determine content type, validating the enveloped data rather than the envelope
         let detected_type = self.detect_content_type(&content);
+        let content = match ContentEnvelope::from_bytes(&content) {
+            Some(envelope) => serde_json::to_vec(&envelope.data)?,
+            None => content,
+        };
 
         // Verify expected type matches detected type
         if let (Some(expected), Some(detected)) = (&expected_type, &detected_type) {
@@ -221,6 +308,7 @@ impl RetrievalManager {
             is_valid,
             content_type: detected_type,
             size: content.len() as u64,
+            sha256: digest.sha256,
             errors,
         })
     }
@@ -262,7 +350,7 @@ impl RetrievalManager {
             self.cache.insert(hash.to_string(), CachedContent {
                 data: content.clone(),
                 content_type,
-                cached_at: Utc::now(),
+                cached_at: self.clock.now(),
                 access_count: 1,
             });
         }
@@ -272,21 +360,43 @@ impl RetrievalManager {
 
     /// Get content from cache if available and not expired
     fn get_from_cache(&mut self, hash: &str) -> Option<CachedContent> {
+        let ttl = self.cache.get(hash).map(|cached| self.cache_ttl_for(&cached.content_type))?;
         if let Some(cached) = self.cache.get_mut(hash) {
             // Check if cache entry is still valid
-            if Utc::now() - cached.cached_at < self.cache_ttl {
+            if self.clock.now() - cached.cached_at < ttl {
                 cached.access_count += 1;
                 return Some(cached.clone());
-            } else {
-                // Remove expired entry
+            } else if !self.staleness_policy.serve_stale_on_error {
+                // Remove expired entry; it is kept as a fallback when stale content may be served
                 self.cache.remove(hash);
             }
         }
         None
     }
 
-    /// Detect content type from content
+    /// Get an expired cache entry if the staleness policy allows serving it
+    fn get_stale_from_cache(&mut self, hash: &str) -> Option<CachedContent> {
+        if !self.staleness_policy.serve_stale_on_error {
+            return None;
+        }
+
+        let ttl = self.cache.get(hash).map(|cached| self.cache_ttl_for(&cached.content_type))?;
+        let cached = self.cache.get_mut(hash)?;
+        let staleness = self.clock.now() - cached.cached_at - ttl;
+        if self.staleness_policy.max_stale.is_some_and(|max_stale| staleness > max_stale) {
+            return None;
+        }
+
+        cached.access_count += 1;
+        Some(cached.clone())
+    }
+
+    /// Detect content type from its envelope, falling back to sniffing the content
     fn detect_content_type(&self, content: &[u8]) -> Option<ContentType> {
+        if let Some(envelope) = ContentEnvelope::from_bytes(content) {
+            return Some(envelope.content_type);
+        }
+
         // Try to parse as JSON first
         if let Ok(json) = serde_json::from_slice::<serde_json::Value>(content) {
             if let Some(obj) = json.as_object() {
@@ -299,6 +409,11 @@ impl RetrievalManager {
                     }
                 }
 
+                // Status list credentials change whenever a credential is revoked
+                if obj.get("type").is_some_and(is_status_list_type) {
+                    return Some(ContentType::RevocationList);
+                }
+
                 // Check for verifiable credential
                 if obj.contains_key("@context") && obj.contains_key("credentialSubject") {
                     return Some(ContentType::VerifiableCredential);
@@ -318,6 +433,26 @@ impl RetrievalManager {
     }
 }
 
+/// Content-addressed credentials and schemas never change, while status lists change on revocation
+fn default_content_type_ttls() -> HashMap<ContentType, chrono::Duration> {
+    HashMap::from([
+        (ContentType::DidDocument, chrono::Duration::days(1)),
+        (ContentType::VerifiableCredential, chrono::Duration::days(30)),
+        (ContentType::Schema, chrono::Duration::days(30)),
+        (ContentType::RevocationList, chrono::Duration::minutes(5)),
+    ])
+}
+
+/// Whether a credential `type` value names a status list credential
+fn is_status_list_type(types: &serde_json::Value) -> bool {
+    const STATUS_LIST_TYPES: [&str; 3] = ["StatusList2021Credential", "BitstringStatusListCredential", "RevocationList2020Credential"];
+    match types {
+        serde_json::Value::String(t) => STATUS_LIST_TYPES.contains(&t.as_str()),
+        serde_json::Value::Array(items) => items.iter().filter_map(|t| t.as_str()).any(|t| STATUS_LIST_TYPES.contains(&t)),
+        _ => false,
+    }
+}
+
 /// Cache statistics
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct CacheStatistics {
@@ -337,6 +472,22 @@ impl Default for RetrievalOptions {
     }
 }
 
+impl StalenessPolicy {
+    /// Serve stale entries on backend failure, however old
+    pub fn serve_stale() -> Self {
+        Self {
+            serve_stale_on_error: true,
+            max_stale: None,
+        }
+    }
+
+    /// Limit how long past expiry stale entries may be served
+    pub fn with_max_stale(mut self, max_stale: chrono::Duration) -> Self {
+        self.max_stale = Some(max_stale);
+        self
+    }
+}
+
 impl BatchRetrieval {
     /// Create a new batch retrieval
     pub fn new(hashes: Vec<String>) -> Self {
@@ -364,3 +515,197 @@ impl BatchRetrieval {
         self
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use identity_core::MockClock;
+
+    /// Manager whose cache already holds `content` under `hash`, so no node is contacted
+    fn primed(hash: &str, content: Vec<u8>) -> RetrievalManager {
+        let mut manager = RetrievalManager::new(IpfsClient::new_local().unwrap());
+        let content_type = manager.detect_content_type(&content).unwrap_or(ContentType::Metadata);
+        manager.cache.insert(hash.to_string(), CachedContent {
+            data: content,
+            content_type,
+            cached_at: manager.clock.now(),
+            access_count: 0,
+        });
+        manager
+    }
+
+    #[tokio::test]
+    async fn stored_schema_is_retrieved_as_a_schema() {
+        let schema = serde_json::json!({
+            "@context": ["https://www.w3.org/2018/credentials/v1"],
+            "id": "did:example:schema",
+            "type": "object",
+        });
+        let content = ContentEnvelope::new(ContentType::Schema, schema.clone()).to_bytes().unwrap();
+        let mut manager = primed("QmSchema", content);
+
+        let (content_type, data) = manager.get_typed_json("QmSchema", RetrievalOptions::default()).await.unwrap();
+
+        // Sniffing the bare schema would have called it a DID document
+        assert_eq!(content_type, ContentType::Schema);
+        assert_eq!(data, schema);
+        assert_eq!(manager.get_json("QmSchema", RetrievalOptions::default()).await.unwrap(), schema);
+    }
+
+    #[tokio::test]
+    async fn legacy_content_falls_back_to_sniffing() {
+        let credential = serde_json::json!({
+            "@context": ["https://www.w3.org/2018/credentials/v1"],
+            "credentialSubject": { "id": "did:example:alice" },
+        });
+        let mut manager = primed("QmLegacy", serde_json::to_vec(&credential).unwrap());
+
+        let (content_type, data) = manager.get_typed_json("QmLegacy", RetrievalOptions::default()).await.unwrap();
+
+        assert_eq!(content_type, ContentType::VerifiableCredential);
+        assert_eq!(data, credential);
+    }
+
+    /// Manager backed by a closed port, holding `QmCached` cached at the clock's current time
+    async fn offline(policy: StalenessPolicy) -> (RetrievalManager, MockClock) {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let endpoint = format!("http://{}", listener.local_addr().unwrap());
+        drop(listener);
+
+        let clock = MockClock::default();
+        let mut manager = RetrievalManager::new_with_clock(IpfsClient::new(&endpoint).unwrap(), Arc::new(clock.clone()));
+        manager.set_staleness_policy(policy);
+        manager.cache.insert("QmCached".to_string(), CachedContent {
+            data: serde_json::to_vec(&serde_json::json!({ "name": "cached" })).unwrap(),
+            content_type: ContentType::Metadata,
+            cached_at: clock.now(),
+            access_count: 0,
+        });
+        (manager, clock)
+    }
+
+    fn batch() -> BatchRetrieval {
+        BatchRetrieval::new(vec!["QmCached".to_string(), "QmMissing".to_string()])
+    }
+
+    #[tokio::test]
+    async fn stale_entry_is_served_and_flagged_when_the_backend_fails() {
+        let (mut manager, clock) = offline(StalenessPolicy::serve_stale()).await;
+        clock.advance(chrono::Duration::days(2));
+
+        let result = manager.execute_batch_retrieval(batch()).await;
+
+        assert_eq!(result.successful["QmCached"], serde_json::json!({ "name": "cached" }));
+        assert_eq!(result.stale, vec!["QmCached"]);
+        assert!(result.failed.contains_key("QmMissing"));
+        assert_eq!((result.cache_hits, result.cache_misses), (0, 2));
+    }
+
+    #[tokio::test]
+    async fn expired_entry_fails_under_the_default_policy() {
+        let (mut manager, clock) = offline(StalenessPolicy::default()).await;
+        clock.advance(chrono::Duration::days(2));
+
+        let result = manager.execute_batch_retrieval(batch()).await;
+
+        assert!(result.successful.is_empty());
+        assert!(result.stale.is_empty());
+        assert!(result.failed.contains_key("QmCached"));
+        assert!(!manager.cache.contains_key("QmCached"));
+    }
+
+    #[tokio::test]
+    async fn entries_past_max_stale_are_not_served() {
+        let policy = StalenessPolicy::serve_stale().with_max_stale(chrono::Duration::hours(6));
+        let (mut manager, clock) = offline(policy).await;
+
+        clock.advance(chrono::Duration::hours(4));
+        let result = manager.execute_batch_retrieval(batch()).await;
+        assert_eq!(result.stale, vec!["QmCached"]);
+
+        clock.advance(chrono::Duration::days(1));
+        let result = manager.execute_batch_retrieval(batch()).await;
+        assert!(result.stale.is_empty());
+        assert!(result.failed.contains_key("QmCached"));
+    }
+
+    #[tokio::test]
+    async fn fresh_entries_are_cache_hits_not_stale() {
+        let (mut manager, _) = offline(StalenessPolicy::serve_stale()).await;
+
+        let result = manager.execute_batch_retrieval(batch()).await;
+
+        assert!(result.successful.contains_key("QmCached"));
+        assert!(result.stale.is_empty());
+        assert_eq!((result.cache_hits, result.cache_misses), (1, 1));
+    }
+
+    #[test]
+    fn cache_entries_expire_when_the_clock_passes_the_ttl() {
+        let clock = MockClock::default();
+        let mut manager = RetrievalManager::new_with_clock(IpfsClient::new_local().unwrap(), Arc::new(clock.clone()));
+        manager.set_cache_ttl(chrono::Duration::minutes(10));
+        manager.cache.insert("QmEntry".to_string(), CachedContent {
+            data: b"{}".to_vec(),
+            content_type: ContentType::Metadata,
+            cached_at: clock.now(),
+            access_count: 0,
+        });
+
+        clock.advance(chrono::Duration::minutes(9));
+        assert!(manager.get_from_cache("QmEntry").is_some());
+
+        clock.advance(chrono::Duration::minutes(1));
+        assert!(manager.get_from_cache("QmEntry").is_none());
+        assert!(!manager.cache.contains_key("QmEntry"));
+    }
+
+    fn cache_entry(manager: &mut RetrievalManager, hash: &str, content_type: ContentType) {
+        let cached_at = manager.clock.now();
+        manager.cache.insert(hash.to_string(), CachedContent { data: b"{}".to_vec(), content_type, cached_at, access_count: 0 });
+    }
+
+    #[test]
+    fn revocation_lists_expire_sooner_than_credentials() {
+        let clock = MockClock::default();
+        let mut manager = RetrievalManager::new_with_clock(IpfsClient::new_local().unwrap(), Arc::new(clock.clone()));
+        cache_entry(&mut manager, "QmCredential", ContentType::VerifiableCredential);
+        cache_entry(&mut manager, "QmStatusList", ContentType::RevocationList);
+        cache_entry(&mut manager, "QmMetadata", ContentType::Metadata);
+
+        clock.advance(chrono::Duration::minutes(30));
+        assert!(manager.get_from_cache("QmStatusList").is_none());
+        assert!(manager.get_from_cache("QmCredential").is_some());
+        assert!(manager.get_from_cache("QmMetadata").is_some());
+
+        // Types without their own TTL follow the global one
+        clock.advance(chrono::Duration::minutes(30));
+        assert!(manager.get_from_cache("QmMetadata").is_none());
+        assert!(manager.get_from_cache("QmCredential").is_some());
+    }
+
+    #[test]
+    fn content_type_ttls_can_be_overridden_and_removed() {
+        let mut manager = RetrievalManager::new(IpfsClient::new_local().unwrap());
+        manager.set_cache_ttl(chrono::Duration::hours(2));
+
+        assert_eq!(manager.cache_ttl_for(&ContentType::RevocationList), chrono::Duration::minutes(5));
+        manager.set_content_type_ttl(ContentType::RevocationList, chrono::Duration::seconds(30));
+        assert_eq!(manager.cache_ttl_for(&ContentType::RevocationList), chrono::Duration::seconds(30));
+        manager.remove_content_type_ttl(&ContentType::RevocationList);
+        assert_eq!(manager.cache_ttl_for(&ContentType::RevocationList), chrono::Duration::hours(2));
+    }
+
+    #[test]
+    fn status_list_credentials_are_detected_as_revocation_lists() {
+        let manager = RetrievalManager::new(IpfsClient::new_local().unwrap());
+        let status_list = serde_json::json!({
+            "@context": ["https://www.w3.org/2018/credentials/v1"],
+            "type": ["VerifiableCredential", "StatusList2021Credential"],
+            "credentialSubject": { "id": "https://example.com/status/1#list", "encodedList": "H4sI" },
+        });
+
+        let detected = manager.detect_content_type(&serde_json::to_vec(&status_list).unwrap());
+        assert_eq!(detected, Some(ContentType::RevocationList));
+    }
+}
@@ -0,0 +1,128 @@
+//! Tamper-evident snapshots of a node's pin set
+
+use serde::{Deserialize, Serialize};
+use chrono::{DateTime, Utc};
+use identity_core::{MerkleProof, MerkleTree};
+use crate::client::IpfsClient;
+use crate::error::IpfsError;
+
+/// Sorted pin set committed to by a Merkle root at a point in time
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct PinSnapshot {
+    pub pins: Vec<String>,
+    /// Hex-encoded Merkle root over the sorted pins
+    pub merkle_root: String,
+    pub timestamp: DateTime<Utc>,
+}
+
+impl PinSnapshot {
+    /// Build a snapshot from a pin list, sorting and deduplicating it
+    pub fn from_pins(mut pins: Vec<String>, timestamp: DateTime<Utc>) -> Self {
+        pins.sort();
+        pins.dedup();
+        let merkle_root = MerkleTree::new(&pins).root_hex();
+
+        Self {
+            pins,
+            merkle_root,
+            timestamp,
+        }
+    }
+
+    /// Check the pin list still matches the recorded root
+    pub fn is_intact(&self) -> bool {
+        self.pins.windows(2).all(|pair| pair[0] < pair[1])
+            && MerkleTree::new(&self.pins).root_hex() == self.merkle_root
+    }
+
+    /// Inclusion proof for a CID, verifiable against the root alone
+    pub fn membership_proof(&self, cid: &str) -> Option<MerkleProof> {
+        let index = self.pins.binary_search_by(|pin| pin.as_str().cmp(cid)).ok()?;
+        MerkleTree::new(&self.pins).proof(index)
+    }
+}
+
+impl IpfsClient {
+    /// Snapshot the node's current pin set
+    pub async fn snapshot_pins(&self) -> Result<PinSnapshot, IpfsError> {
+        let pins = self.list_pinned().await?;
+        Ok(PinSnapshot::from_pins(pins, Utc::now()))
+    }
+}
+
+/// Check that a snapshot is untampered and includes a CID
+pub fn verify_pin_snapshot(snapshot: &PinSnapshot, cid: &str) -> bool {
+    snapshot.is_intact() && snapshot.pins.binary_search_by(|pin| pin.as_str().cmp(cid)).is_ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use tokio::net::TcpListener;
+
+    /// Serve a single `pin/ls` response listing `pins`
+    async fn mock_node(pins: &[&str]) -> String {
+        let keys: serde_json::Map<String, serde_json::Value> = pins.iter()
+            .map(|pin| (pin.to_string(), serde_json::json!({ "Type": "recursive" })))
+            .collect();
+        let body = serde_json::to_vec(&serde_json::json!({ "Keys": keys })).unwrap();
+
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let address = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            let (mut socket, _) = listener.accept().await.unwrap();
+            let mut buffer = [0u8; 4096];
+            let _ = socket.read(&mut buffer).await;
+            let header = format!(
+                "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n",
+                body.len()
+            );
+            let _ = socket.write_all(header.as_bytes()).await;
+            let _ = socket.write_all(&body).await;
+        });
+        format!("http://{}", address)
+    }
+
+    #[tokio::test]
+    async fn snapshot_of_mock_pin_list_proves_membership() {
+        let client = IpfsClient::new(&mock_node(&["QmCharlie", "QmAlpha", "QmBravo"]).await).unwrap();
+
+        let snapshot = client.snapshot_pins().await.unwrap();
+
+        assert_eq!(snapshot.pins, vec!["QmAlpha", "QmBravo", "QmCharlie"]);
+        assert!(snapshot.is_intact());
+        assert!(verify_pin_snapshot(&snapshot, "QmBravo"));
+        assert!(!verify_pin_snapshot(&snapshot, "QmDelta"));
+    }
+
+    #[test]
+    fn membership_proofs_verify_against_the_root_alone() {
+        let snapshot = PinSnapshot::from_pins(
+            vec!["QmBravo".to_string(), "QmAlpha".to_string(), "QmBravo".to_string(), "QmCharlie".to_string()],
+            Utc::now(),
+        );
+        let mut root = [0u8; 32];
+        root.copy_from_slice(&hex::decode(&snapshot.merkle_root).unwrap());
+
+        assert_eq!(snapshot.pins.len(), 3);
+        let proof = snapshot.membership_proof("QmCharlie").unwrap();
+        assert!(identity_core::verify_merkle_proof(&root, b"QmCharlie", &proof));
+        assert!(!identity_core::verify_merkle_proof(&root, b"QmDelta", &proof));
+        assert!(snapshot.membership_proof("QmDelta").is_none());
+    }
+
+    #[test]
+    fn tampered_snapshots_are_not_intact() {
+        let snapshot = PinSnapshot::from_pins(vec!["QmAlpha".to_string(), "QmBravo".to_string()], Utc::now());
+
+        let mut added = snapshot.clone();
+        added.pins.push("QmMallory".to_string());
+        assert!(!added.is_intact());
+        assert!(!verify_pin_snapshot(&added, "QmMallory"));
+
+        let mut reordered = snapshot;
+        reordered.pins.reverse();
+        assert!(!reordered.is_intact());
+    }
+}
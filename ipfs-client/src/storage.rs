@@ -4,6 +4,8 @@ use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use chrono::{DateTime, Utc};
 use crate::client::{IpfsClient, ContentType, StorageResult, ContentMetadata};
+use crate::compression::Compression;
+use crate::envelope::ContentEnvelope;
 use crate::error::IpfsError;
 use identity_core::{DidDocument, VerifiableCredential, VerifiablePresentation};
 
@@ -26,6 +28,7 @@ pub enum StorageOperation {
     StoreDid {
         did_doc: DidDocument,
         tags: Vec<String>,
+        compress: bool,
     },
     StoreCredential {
         credential: VerifiableCredential,
@@ -62,6 +65,23 @@ pub struct SearchCriteria {
     pub max_size: Option<u64>,
 }
 
+/// Composable search expression over content metadata
+#[derive(Debug, Clone, PartialEq)]
+pub enum Filter {
+    /// Every sub-filter matches (an empty list matches everything)
+    And(Vec<Filter>),
+    /// At least one sub-filter matches
+    Or(Vec<Filter>),
+    Not(Box<Filter>),
+    ContentType(ContentType),
+    Tag(String),
+    CreatedAfter(DateTime<Utc>),
+    CreatedBefore(DateTime<Utc>),
+    MinSize(u64),
+    MaxSize(u64),
+    Encrypted,
+}
+
 impl StorageManager {
     /// Create a new storage manager
     pub fn new(client: IpfsClient) -> Self {
@@ -78,8 +98,8 @@ impl StorageManager {
         operation: StorageOperation,
     ) -> Result<StorageResult, IpfsError> {
         let result = match operation {
-            StorageOperation::StoreDid { did_doc, tags } => {
-                let mut result = self.client.store_did_document(&did_doc).await?;
+            StorageOperation::StoreDid { did_doc, tags, compress } => {
+                let mut result = self.client.store_did_document(&did_doc, compress).await?;
                 result.metadata.tags.extend(tags);
                 result
             }
@@ -94,8 +114,7 @@ impl StorageManager {
                 result
             }
             StorageOperation::StoreJson { data, content_type, tags } => {
-                let content = serde_json::to_vec(&data)
-                    .map_err(|e| IpfsError::StorageError(format!("Serialization failed: {}", e)))?;
+                let content = ContentEnvelope::new(content_type.clone(), data).to_bytes()?;
 
                 let metadata = ContentMetadata {
                     content_type,
@@ -104,6 +123,7 @@ impl StorageManager {
                     created_at: Utc::now(),
                     tags,
                     encryption: None,
+                    compression: Compression::None,
                 };
 
                 self.client.store_content(&content, metadata).await?
@@ -150,6 +170,14 @@ impl StorageManager {
             .collect()
     }
 
+    /// Search for content matching a filter expression
+    pub fn search_expr(&self, filter: Filter) -> Vec<&ContentMetadata> {
+        self.content_index
+            .values()
+            .filter(|&metadata| filter.matches(metadata))
+            .collect()
+    }
+
     /// Find content by tags
     pub fn find_by_tags(&self, tags: &[String]) -> Vec<&ContentMetadata> {
         let mut results = Vec::new();
@@ -169,6 +197,18 @@ impl StorageManager {
         results
     }
 
+    /// Retrieve an indexed DID document, decompressing it according to its metadata
+    pub async fn get_did_document(&self, hash: &str) -> Result<DidDocument, IpfsError> {
+        let metadata = self.content_index.get(hash)
+            .ok_or_else(|| IpfsError::NotFound(format!("No indexed content for {}", hash)))?;
+
+        let content = self.client.get_content(hash).await?;
+        let content = metadata.compression.decompress(&content, self.client.max_content_size())?;
+
+        serde_json::from_slice(&content)
+            .map_err(|e| IpfsError::StorageError(format!("Failed to deserialize DID document: {}", e)))
+    }
+
     /// Get content metadata by hash
     pub fn get_metadata(&self, hash: &str) -> Option<&ContentMetadata> {
         self.content_index.get(hash)
@@ -208,7 +248,7 @@ impl StorageManager {
         for tag in &result.metadata.tags {
             self.tags_index
                 .entry(tag.clone())
-                .or_insert_with(Vec::new)
+                .or_default()
                 .push(hash.clone());
         }
     }
@@ -280,7 +320,13 @@ impl BatchOperation {
 
     /// Add a DID document to the batch
     pub fn add_did_document(mut self, did_doc: DidDocument, tags: Vec<String>) -> Self {
-        self.operations.push(StorageOperation::StoreDid { did_doc, tags });
+        self.operations.push(StorageOperation::StoreDid { did_doc, tags, compress: false });
+        self
+    }
+
+    /// Add a gzip-compressed DID document to the batch
+    pub fn add_compressed_did_document(mut self, did_doc: DidDocument, tags: Vec<String>) -> Self {
+        self.operations.push(StorageOperation::StoreDid { did_doc, tags, compress: true });
         self
     }
 
@@ -359,8 +405,171 @@ impl SearchCriteria {
     }
 }
 
+impl Filter {
+    /// Combine with another filter, requiring both to match
+    pub fn and(self, other: Filter) -> Self {
+        match self {
+            Filter::And(mut filters) => {
+                filters.push(other);
+                Filter::And(filters)
+            }
+            filter => Filter::And(vec![filter, other]),
+        }
+    }
+
+    /// Combine with another filter, requiring either to match
+    pub fn or(self, other: Filter) -> Self {
+        match self {
+            Filter::Or(mut filters) => {
+                filters.push(other);
+                Filter::Or(filters)
+            }
+            filter => Filter::Or(vec![filter, other]),
+        }
+    }
+
+    /// Check whether metadata satisfies the expression
+    pub fn matches(&self, metadata: &ContentMetadata) -> bool {
+        match self {
+            Filter::And(filters) => filters.iter().all(|filter| filter.matches(metadata)),
+            Filter::Or(filters) => filters.iter().any(|filter| filter.matches(metadata)),
+            Filter::Not(filter) => !filter.matches(metadata),
+            Filter::ContentType(content_type) => metadata.content_type == *content_type,
+            Filter::Tag(tag) => metadata.tags.contains(tag),
+            Filter::CreatedAfter(after) => metadata.created_at > *after,
+            Filter::CreatedBefore(before) => metadata.created_at < *before,
+            Filter::MinSize(min_size) => metadata.size >= *min_size,
+            Filter::MaxSize(max_size) => metadata.size <= *max_size,
+            Filter::Encrypted => metadata.encryption.is_some(),
+        }
+    }
+}
+
+impl std::ops::Not for Filter {
+    type Output = Filter;
+
+    fn not(self) -> Self::Output {
+        Filter::Not(Box::new(self))
+    }
+}
+
+impl From<SearchCriteria> for Filter {
+    fn from(criteria: SearchCriteria) -> Self {
+        let mut filters: Vec<Filter> = criteria.tags.into_iter().map(Filter::Tag).collect();
+        filters.extend(criteria.content_type.map(Filter::ContentType));
+        filters.extend(criteria.created_after.map(Filter::CreatedAfter));
+        filters.extend(criteria.created_before.map(Filter::CreatedBefore));
+        filters.extend(criteria.min_size.map(Filter::MinSize));
+        filters.extend(criteria.max_size.map(Filter::MaxSize));
+        Filter::And(filters)
+    }
+}
+
 impl Default for SearchCriteria {
     fn default() -> Self {
         Self::new()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::client::EncryptionInfo;
+    use chrono::Duration;
+
+    /// Manager whose index is seeded directly, so no node is contacted
+    fn indexed(entries: Vec<ContentMetadata>) -> StorageManager {
+        let mut manager = StorageManager::new(IpfsClient::new_local().unwrap());
+        for metadata in entries {
+            manager.update_indexes(&StorageResult { hash: metadata.hash.clone(), metadata });
+        }
+        manager
+    }
+
+    fn entry(hash: &str, content_type: ContentType, tags: &[&str], age_days: i64) -> ContentMetadata {
+        ContentMetadata {
+            content_type,
+            hash: hash.to_string(),
+            size: 100,
+            created_at: Utc::now() - Duration::days(age_days),
+            tags: tags.iter().map(|tag| tag.to_string()).collect(),
+            encryption: None,
+            compression: Compression::None,
+        }
+    }
+
+    fn hashes(results: Vec<&ContentMetadata>) -> Vec<String> {
+        let mut hashes: Vec<String> = results.into_iter().map(|m| m.hash.clone()).collect();
+        hashes.sort();
+        hashes
+    }
+
+    fn sample() -> StorageManager {
+        indexed(vec![
+            entry("kyc-fresh", ContentType::VerifiableCredential, &["kyc"], 1),
+            entry("aml-fresh", ContentType::VerifiableCredential, &["aml"], 1),
+            entry("kyc-stale", ContentType::VerifiableCredential, &["kyc"], 400),
+            entry("other-fresh", ContentType::VerifiableCredential, &["other"], 1),
+            entry("kyc-did", ContentType::DidDocument, &["kyc"], 1),
+        ])
+    }
+
+    #[test]
+    fn nested_expression_selects_the_expected_subset() {
+        let manager = sample();
+        let cutoff = Utc::now() - Duration::days(365);
+
+        let filter = Filter::ContentType(ContentType::VerifiableCredential)
+            .and(Filter::Tag("kyc".to_string()).or(Filter::Tag("aml".to_string())))
+            .and(!Filter::CreatedBefore(cutoff));
+
+        assert_eq!(hashes(manager.search_expr(filter)), vec!["aml-fresh", "kyc-fresh"]);
+    }
+
+    #[test]
+    fn empty_groups_and_negation() {
+        let manager = sample();
+
+        assert_eq!(manager.search_expr(Filter::And(vec![])).len(), 5);
+        assert!(manager.search_expr(Filter::Or(vec![])).is_empty());
+        assert_eq!(
+            hashes(manager.search_expr(!Filter::Tag("kyc".to_string()))),
+            vec!["aml-fresh", "other-fresh"],
+        );
+        assert!(manager.search_expr(Filter::Encrypted).is_empty());
+    }
+
+    #[test]
+    fn size_and_encryption_leaves() {
+        let mut large = entry("large", ContentType::Metadata, &[], 1);
+        large.size = 10_000;
+        let mut encrypted = entry("encrypted", ContentType::Metadata, &[], 1);
+        encrypted.encryption = Some(EncryptionInfo {
+            algorithm: "aes-256-gcm".to_string(),
+            key_id: "key-1".to_string(),
+            nonce: None,
+        });
+        let manager = indexed(vec![large, encrypted, entry("small", ContentType::Metadata, &[], 1)]);
+
+        assert_eq!(hashes(manager.search_expr(Filter::MinSize(1_000))), vec!["large"]);
+        assert_eq!(hashes(manager.search_expr(Filter::MaxSize(1_000))), vec!["encrypted", "small"]);
+        assert_eq!(
+            hashes(manager.search_expr(Filter::Encrypted.or(Filter::MinSize(1_000)))),
+            vec!["encrypted", "large"],
+        );
+    }
+
+    #[test]
+    fn criteria_conversion_matches_search() {
+        let manager = sample();
+        let criteria = SearchCriteria::new()
+            .with_content_type(ContentType::VerifiableCredential)
+            .with_tags(vec!["kyc".to_string()]);
+
+        assert_eq!(
+            hashes(manager.search_expr(criteria.clone().into())),
+            hashes(manager.search(criteria)),
+        );
+        assert_eq!(hashes(manager.search_expr(Filter::from(SearchCriteria::new()))).len(), 5);
+    }
+}
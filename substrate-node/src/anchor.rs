@@ -0,0 +1,207 @@
+//! Periodic anchors committing to DID and credential registry state for light clients
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use crate::credential_registry::CredentialRegistry;
+use crate::did_registry::{DidRegistry, DidStatus};
+use crate::store::RegistryStore;
+use identity_core::{verify_merkle_proof, MerkleProof, MerkleTree};
+
+/// Merkle roots of registry state, anchored on-chain as one record
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct AnchorRecord {
+    /// Hex root over active DIDs and their document hashes
+    pub did_root: String,
+    /// Hex root over registered credentials and their content hashes
+    pub credential_root: String,
+    /// Hex root over revoked credential ids
+    pub revocation_root: String,
+    /// Sum of both registries' event sequences, so later state anchors with a higher sequence
+    pub sequence: u64,
+    pub timestamp: DateTime<Utc>,
+}
+
+/// Fact a light client can check against an anchor
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub enum AnchorMember {
+    /// An active DID published with a document hash
+    Did { did: String, document_hash: String },
+    /// A credential registered with a content hash
+    Credential { credential_id: String, credential_hash: String },
+    /// A revoked credential
+    Revocation { credential_id: String },
+}
+
+impl AnchorMember {
+    /// Leaf bytes of the member in its tree
+    pub fn leaf(&self) -> Vec<u8> {
+        // Ids and hashes cannot contain NUL, so the separator keeps the encoding unambiguous
+        match self {
+            AnchorMember::Did { did, document_hash } => [did.as_bytes(), &[0], document_hash.as_bytes()].concat(),
+            AnchorMember::Credential { credential_id, credential_hash } => {
+                [credential_id.as_bytes(), &[0], credential_hash.as_bytes()].concat()
+            }
+            AnchorMember::Revocation { credential_id } => credential_id.as_bytes().to_vec(),
+        }
+    }
+}
+
+impl AnchorRecord {
+    /// Check a member's inclusion proof against the root of its kind
+    pub fn verify_membership(&self, member: &AnchorMember, proof: &MerkleProof) -> bool {
+        let root = match member {
+            AnchorMember::Did { .. } => &self.did_root,
+            AnchorMember::Credential { .. } => &self.credential_root,
+            AnchorMember::Revocation { .. } => &self.revocation_root,
+        };
+        let root: [u8; 32] = match hex::decode(root).ok().and_then(|bytes| bytes.try_into().ok()) {
+            Some(root) => root,
+            None => return false,
+        };
+        verify_merkle_proof(&root, &member.leaf(), proof)
+    }
+}
+
+/// Build an anchor over the current state of both registries
+pub fn build_anchor<D: RegistryStore, C: RegistryStore>(
+    did_registry: &DidRegistry<D>,
+    credential_registry: &CredentialRegistry<C>,
+) -> AnchorRecord {
+    let [did_members, credential_members, revocation_members] = anchor_members(did_registry, credential_registry);
+    AnchorRecord {
+        did_root: tree(&did_members).root_hex(),
+        credential_root: tree(&credential_members).root_hex(),
+        revocation_root: tree(&revocation_members).root_hex(),
+        sequence: did_registry.latest_event_sequence() + credential_registry.latest_event_sequence(),
+        timestamp: Utc::now(),
+    }
+}
+
+/// Inclusion proof of a member in an anchor built from the registries' current state
+pub fn anchor_membership_proof<D: RegistryStore, C: RegistryStore>(
+    did_registry: &DidRegistry<D>,
+    credential_registry: &CredentialRegistry<C>,
+    member: &AnchorMember,
+) -> Option<MerkleProof> {
+    let [did_members, credential_members, revocation_members] = anchor_members(did_registry, credential_registry);
+    let members = match member {
+        AnchorMember::Did { .. } => did_members,
+        AnchorMember::Credential { .. } => credential_members,
+        AnchorMember::Revocation { .. } => revocation_members,
+    };
+    let index = members.iter().position(|candidate| candidate == member)?;
+    tree(&members).proof(index)
+}
+
+/// Members of the DID, credential and revocation trees, each sorted so roots do not depend on map order
+fn anchor_members<D: RegistryStore, C: RegistryStore>(
+    did_registry: &DidRegistry<D>,
+    credential_registry: &CredentialRegistry<C>,
+) -> [Vec<AnchorMember>; 3] {
+    let mut dids: Vec<_> = did_registry.entries()
+        .filter(|entry| entry.status == DidStatus::Active)
+        .map(|entry| (entry.did.clone(), entry.document_hash.clone()))
+        .collect();
+    dids.sort();
+
+    let mut credentials: Vec<_> = credential_registry.entries()
+        .map(|entry| (entry.credential_id.clone(), entry.credential_hash.clone()))
+        .collect();
+    credentials.sort();
+
+    let mut revocations: Vec<_> = credential_registry.revocations()
+        .map(|revocation| revocation.credential_id.clone())
+        .collect();
+    revocations.sort();
+
+    [
+        dids.into_iter().map(|(did, document_hash)| AnchorMember::Did { did, document_hash }).collect(),
+        credentials.into_iter()
+            .map(|(credential_id, credential_hash)| AnchorMember::Credential { credential_id, credential_hash })
+            .collect(),
+        revocations.into_iter().map(|credential_id| AnchorMember::Revocation { credential_id }).collect(),
+    ]
+}
+
+fn tree(members: &[AnchorMember]) -> MerkleTree {
+    MerkleTree::new(&members.iter().map(AnchorMember::leaf).collect::<Vec<_>>())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn registries() -> (DidRegistry, CredentialRegistry) {
+        let mut dids = DidRegistry::new();
+        for name in ["alice", "bob", "carol"] {
+            let did = format!("did:example:{}", name);
+            dids.register_did(did.clone(), format!("Qm{}", name), did.clone(), vec![format!("{}#key-1", did)]).unwrap();
+        }
+
+        let mut credentials = CredentialRegistry::new();
+        for id in ["urn:uuid:1", "urn:uuid:2", "urn:uuid:3"] {
+            credentials.register_credential(
+                id.to_string(),
+                format!("Qm{}", id),
+                "did:example:alice".to_string(),
+                None,
+                None,
+                None,
+                0,
+            ).unwrap();
+        }
+        credentials.revoke_credential("urn:uuid:2", "did:example:alice".to_string(), "superseded".to_string()).unwrap();
+        (dids, credentials)
+    }
+
+    fn did(name: &str) -> AnchorMember {
+        AnchorMember::Did { did: format!("did:example:{}", name), document_hash: format!("Qm{}", name) }
+    }
+
+    #[test]
+    fn did_and_credential_membership_verify_against_the_anchor() {
+        let (dids, credentials) = registries();
+        let anchor = build_anchor(&dids, &credentials);
+        let credential = AnchorMember::Credential {
+            credential_id: "urn:uuid:3".to_string(),
+            credential_hash: "Qmurn:uuid:3".to_string(),
+        };
+        let revocation = AnchorMember::Revocation { credential_id: "urn:uuid:2".to_string() };
+
+        for member in [did("bob"), credential.clone(), revocation] {
+            let proof = anchor_membership_proof(&dids, &credentials, &member).unwrap();
+            assert!(anchor.verify_membership(&member, &proof), "{:?}", member);
+        }
+
+        // A proof only verifies for its own member and root
+        let proof = anchor_membership_proof(&dids, &credentials, &credential).unwrap();
+        let altered = AnchorMember::Credential {
+            credential_id: "urn:uuid:3".to_string(),
+            credential_hash: "QmForged".to_string(),
+        };
+        assert!(!anchor.verify_membership(&altered, &proof));
+        assert!(!anchor.verify_membership(&AnchorMember::Revocation { credential_id: "urn:uuid:3".to_string() }, &proof));
+        assert!(anchor_membership_proof(&dids, &credentials, &did("mallory")).is_none());
+    }
+
+    #[test]
+    fn anchor_tracks_registry_changes() {
+        let (mut dids, credentials) = registries();
+        let before = build_anchor(&dids, &credentials);
+        assert_eq!(build_anchor(&dids, &credentials).did_root, before.did_root);
+
+        dids.deactivate_did("did:example:carol", &["did:example:carol"]).unwrap();
+        let after = build_anchor(&dids, &credentials);
+
+        assert_ne!(after.did_root, before.did_root);
+        assert_eq!(after.credential_root, before.credential_root);
+        assert_eq!(after.revocation_root, before.revocation_root);
+        assert!(after.sequence > before.sequence);
+        assert!(anchor_membership_proof(&dids, &credentials, &did("carol")).is_none());
+
+        // Proofs from the old state do not verify against the new anchor
+        let (old_dids, old_credentials) = registries();
+        let stale = anchor_membership_proof(&old_dids, &old_credentials, &did("alice")).unwrap();
+        assert!(!after.verify_membership(&did("alice"), &stale));
+    }
+}
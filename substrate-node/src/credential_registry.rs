@@ -2,13 +2,19 @@
 
 use serde::{Deserialize, Serialize};
 use chrono::{DateTime, Utc};
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
+use crate::events::{event_key, EventFilter, EventLog, PendingEvent, RegistryEvent, RegistryEventType};
+use crate::store::{encode_record, load_records, MemoryStore, RegistryStore, WriteBatch};
+use identity_core::{hash_code_of, DomainEvent, EventBus, HashCode};
 
 /// Credential registry entry stored on-chain
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct CredentialRegistryEntry {
     pub credential_id: String,
     pub credential_hash: String, // IPFS hash
+    /// Hash function the credential hash was computed with
+    #[serde(default)]
+    pub hash_code: HashCode,
     pub issuer_did: String,
     pub subject_did: Option<String>,
     pub schema_id: Option<String>,
@@ -31,6 +37,18 @@ pub enum CredentialStatus {
     Suspended,    // Temporarily suspended
 }
 
+/// Parameters for registering a credential
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CredentialRegistration {
+    pub credential_id: String,
+    pub credential_hash: String,
+    pub issuer_did: String,
+    pub subject_did: Option<String>,
+    pub schema_id: Option<String>,
+    pub expires_at: Option<DateTime<Utc>>,
+    pub required_attestations: u32,
+}
+
 /// Revocation entry
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct RevocationEntry {
@@ -41,24 +59,76 @@ pub struct RevocationEntry {
     pub revocation_list_hash: Option<String>,
 }
 
-/// Credential registry for managing credentials on-chain
-pub struct CredentialRegistry {
+/// Store key prefix of credential entries
+const CREDENTIAL_PREFIX: &str = "credential/";
+
+/// Store key prefix of revocation entries
+const REVOCATION_PREFIX: &str = "revocation/";
+
+/// Store key prefix of schema hashes
+const SCHEMA_PREFIX: &str = "schema/";
+
+/// Store key prefix of registry events
+const EVENT_PREFIX: &str = "credential_event/";
+
+/// Credential registry for managing credentials on-chain, persisted to a pluggable store
+pub struct CredentialRegistry<S: RegistryStore = MemoryStore> {
+    store: S,
     entries: HashMap<String, CredentialRegistryEntry>,
     revocations: HashMap<String, RevocationEntry>,
     schema_registry: HashMap<String, String>, // schema_id -> schema_hash
+    expirations: BTreeMap<DateTime<Utc>, Vec<String>>, // expires_at -> credential_ids not yet swept
+    events: EventLog,
+    event_bus: Option<EventBus>,
 }
 
 impl CredentialRegistry {
-    /// Create a new credential registry
+    /// Create a new in-memory credential registry
     pub fn new() -> Self {
+        Self::empty(MemoryStore::new())
+    }
+}
+
+impl<S: RegistryStore> CredentialRegistry<S> {
+    /// Open a registry over a store, loading the credentials, revocations, schemas and events it already holds
+    pub fn with_store(store: S) -> Result<Self, String> {
+        let mut registry = Self::empty(store);
+        registry.entries = load_records(&registry.store, CREDENTIAL_PREFIX)?.into_iter().collect();
+        registry.revocations = load_records(&registry.store, REVOCATION_PREFIX)?.into_iter().collect();
+        registry.schema_registry = load_records(&registry.store, SCHEMA_PREFIX)?.into_iter().collect();
+        registry.events = EventLog::from_events(
+            load_records(&registry.store, EVENT_PREFIX)?.into_iter().map(|(_, event)| event).collect(),
+        )?;
+
+        let unswept: Vec<(String, DateTime<Utc>)> = registry.entries.values()
+            .filter(|entry| !matches!(entry.status, CredentialStatus::Expired | CredentialStatus::Revoked))
+            .filter_map(|entry| entry.expires_at.map(|expires_at| (entry.credential_id.clone(), expires_at)))
+            .collect();
+        for (credential_id, expires_at) in unswept {
+            registry.index_expiration(&credential_id, expires_at);
+        }
+        Ok(registry)
+    }
+
+    fn empty(store: S) -> Self {
         Self {
+            store,
             entries: HashMap::new(),
             revocations: HashMap::new(),
             schema_registry: HashMap::new(),
+            expirations: BTreeMap::new(),
+            events: EventLog::new(),
+            event_bus: None,
         }
     }
 
+    /// Publish `CredentialRevoked` events to a bus
+    pub fn set_event_bus(&mut self, event_bus: EventBus) {
+        self.event_bus = Some(event_bus);
+    }
+
     /// Register a new credential
+    #[allow(clippy::too_many_arguments)]
     pub fn register_credential(
         &mut self,
         credential_id: String,
@@ -73,33 +143,160 @@ impl CredentialRegistry {
             return Err("Credential already exists".to_string());
         }
 
-        let entry = CredentialRegistryEntry {
+        let entry = Self::new_entry(CredentialRegistration {
             credential_id: credential_id.clone(),
             credential_hash,
             issuer_did,
             subject_did,
             schema_id,
-            issued_at: Utc::now(),
             expires_at,
-            status: if required_attestations > 0 {
+            required_attestations,
+        });
+
+        self.insert_entry(entry)
+    }
+
+    /// Register a batch of credentials atomically.
+    ///
+    /// Every registration is validated before any is committed, and the entries are written
+    /// to the store with their events in a single batch. On failure nothing is registered and the index and
+    /// reason of the first invalid entry is returned; a store failure is reported at index 0.
+    pub fn register_batch(&mut self, registrations: Vec<CredentialRegistration>) -> Result<(), (usize, String)> {
+        let mut seen = std::collections::HashSet::new();
+
+        for (index, registration) in registrations.iter().enumerate() {
+            if registration.credential_id.is_empty() {
+                return Err((index, "Credential ID must not be empty".to_string()));
+            }
+
+            if self.entries.contains_key(&registration.credential_id) {
+                return Err((index, "Credential already exists".to_string()));
+            }
+
+            if !seen.insert(registration.credential_id.as_str()) {
+                return Err((index, "Duplicate credential ID in batch".to_string()));
+            }
+
+            identity_core::utils::parse_did(&registration.issuer_did)
+                .map_err(|e| (index, format!("Invalid issuer: {}", e)))?;
+        }
+
+        let entries: Vec<CredentialRegistryEntry> = registrations.into_iter().map(Self::new_entry).collect();
+        let mut batch = WriteBatch::new();
+        for (index, entry) in entries.iter().enumerate() {
+            batch.put(credential_key(&entry.credential_id), encode_record(entry).map_err(|e| (index, e))?);
+        }
+        let events = entries.iter()
+            .map(|entry| credential_event(RegistryEventType::CredentialRegistered, entry))
+            .collect();
+        self.commit(batch, events).map_err(|e| (0, format!("Failed to commit batch: {}", e)))?;
+
+        for entry in entries {
+            self.cache_new_entry(entry);
+        }
+        Ok(())
+    }
+
+    /// Store an entry with its registration event and index its expiration
+    fn insert_entry(&mut self, entry: CredentialRegistryEntry) -> Result<(), String> {
+        let mut batch = WriteBatch::new();
+        batch.put(credential_key(&entry.credential_id), encode_record(&entry)?);
+        self.commit(batch, vec![credential_event(RegistryEventType::CredentialRegistered, &entry)])?;
+        self.cache_new_entry(entry);
+        Ok(())
+    }
+
+    /// Cache a newly stored entry and index its expiration
+    fn cache_new_entry(&mut self, entry: CredentialRegistryEntry) {
+        if let Some(expires_at) = entry.expires_at {
+            self.index_expiration(&entry.credential_id, expires_at);
+        }
+        self.entries.insert(entry.credential_id.clone(), entry);
+    }
+
+    /// Write an entry and the event describing its change through to the store, then cache it
+    fn save_entry(&mut self, entry: CredentialRegistryEntry, event_type: RegistryEventType) -> Result<(), String> {
+        let mut batch = WriteBatch::new();
+        batch.put(credential_key(&entry.credential_id), encode_record(&entry)?);
+        self.commit(batch, vec![credential_event(event_type, &entry)])?;
+        self.entries.insert(entry.credential_id.clone(), entry);
+        Ok(())
+    }
+
+    /// Write a batch together with the events it causes, then append the events to the log
+    fn commit(&mut self, mut batch: WriteBatch, events: Vec<PendingEvent>) -> Result<(), String> {
+        let events = self.events.prepare(events);
+        for event in &events {
+            batch.put(event_key(EVENT_PREFIX, event.sequence), encode_record(event)?);
+        }
+        self.store.write_batch(batch)?;
+        self.events.extend(events);
+        Ok(())
+    }
+
+    /// Get a copy of an entry to modify and save
+    fn entry_for_update(&self, credential_id: &str) -> Result<CredentialRegistryEntry, String> {
+        self.entries.get(credential_id)
+            .cloned()
+            .ok_or_else(|| "Credential not found".to_string())
+    }
+
+    /// The backing store
+    pub fn store(&self) -> &S {
+        &self.store
+    }
+
+    /// Events matching a filter, for incremental sync by indexers
+    pub fn query_events(&self, filter: &EventFilter) -> Vec<RegistryEvent> {
+        self.events.query(filter)
+    }
+
+    /// Sequence id of the most recent event
+    pub fn latest_event_sequence(&self) -> u64 {
+        self.events.latest_sequence()
+    }
+
+    /// Add a credential to the expiration index
+    fn index_expiration(&mut self, credential_id: &str, expires_at: DateTime<Utc>) {
+        self.expirations.entry(expires_at).or_default().push(credential_id.to_string());
+    }
+
+    /// Remove a credential from the expiration index
+    fn unindex_expiration(&mut self, credential_id: &str, expires_at: DateTime<Utc>) {
+        if let Some(ids) = self.expirations.get_mut(&expires_at) {
+            ids.retain(|id| id != credential_id);
+            if ids.is_empty() {
+                self.expirations.remove(&expires_at);
+            }
+        }
+    }
+
+    /// Build a fresh registry entry
+    fn new_entry(registration: CredentialRegistration) -> CredentialRegistryEntry {
+        CredentialRegistryEntry {
+            credential_id: registration.credential_id,
+            hash_code: hash_code_of(&registration.credential_hash),
+            credential_hash: registration.credential_hash,
+            issuer_did: registration.issuer_did,
+            subject_did: registration.subject_did,
+            schema_id: registration.schema_id,
+            issued_at: Utc::now(),
+            expires_at: registration.expires_at,
+            status: if registration.required_attestations > 0 {
                 CredentialStatus::Pending
             } else {
                 CredentialStatus::Active
             },
             revocation_reason: None,
             attestation_count: 0,
-            required_attestations,
+            required_attestations: registration.required_attestations,
             metadata: HashMap::new(),
-        };
-
-        self.entries.insert(credential_id, entry);
-        Ok(())
+        }
     }
 
     /// Add attestation to a credential
     pub fn add_attestation(&mut self, credential_id: &str) -> Result<(), String> {
-        let entry = self.entries.get_mut(credential_id)
-            .ok_or("Credential not found")?;
+        let mut entry = self.entry_for_update(credential_id)?;
 
         if entry.status != CredentialStatus::Pending {
             return Err("Credential is not pending attestation".to_string());
@@ -112,6 +309,7 @@ impl CredentialRegistry {
             entry.status = CredentialStatus::Active;
         }
 
+        self.save_entry(entry, RegistryEventType::CredentialAttested)?;
         Ok(())
     }
 
@@ -122,13 +320,14 @@ impl CredentialRegistry {
         revoked_by: String,
         reason: String,
     ) -> Result<(), String> {
-        let entry = self.entries.get_mut(credential_id)
-            .ok_or("Credential not found")?;
+        let mut entry = self.entry_for_update(credential_id)?;
 
         if entry.status == CredentialStatus::Revoked {
             return Err("Credential already revoked".to_string());
         }
 
+        let previous = entry.status.clone();
+        let expires_at = entry.expires_at;
         entry.status = CredentialStatus::Revoked;
         entry.revocation_reason = Some(reason.clone());
 
@@ -136,14 +335,103 @@ impl CredentialRegistry {
             credential_id: credential_id.to_string(),
             revoked_at: Utc::now(),
             revoked_by,
-            reason,
+            reason: reason.clone(),
             revocation_list_hash: None,
         };
+        // The revocation record and the revoked entry are written together or not at all
+        let mut batch = WriteBatch::new();
+        batch.put(credential_key(credential_id), encode_record(&entry)?);
+        batch.put(revocation_key(credential_id), encode_record(&revocation)?);
+        self.commit(batch, vec![credential_event(RegistryEventType::CredentialRevoked, &entry)])?;
+        self.entries.insert(credential_id.to_string(), entry);
+
+        if previous != CredentialStatus::Expired {
+            if let Some(expires_at) = expires_at {
+                self.unindex_expiration(credential_id, expires_at);
+            }
+        }
 
         self.revocations.insert(credential_id.to_string(), revocation);
+
+        if let Some(event_bus) = &self.event_bus {
+            event_bus.publish(DomainEvent::CredentialRevoked {
+                credential_id: credential_id.to_string(),
+                reason,
+            });
+        }
         Ok(())
     }
 
+    /// Reinstate a revoked credential, restoring its attestation-based status
+    pub fn reinstate_credential(&mut self, credential_id: &str) -> Result<(), String> {
+        let mut entry = self.entry_for_update(credential_id)?;
+
+        if entry.status != CredentialStatus::Revoked {
+            return Err("Credential is not revoked".to_string());
+        }
+
+        entry.revocation_reason = None;
+        entry.status = match entry.expires_at {
+            Some(expires_at) if Utc::now() > expires_at => CredentialStatus::Expired,
+            _ if entry.attestation_count >= entry.required_attestations => CredentialStatus::Active,
+            _ => CredentialStatus::Pending,
+        };
+
+        let reindex = match entry.expires_at {
+            Some(expires_at) if entry.status != CredentialStatus::Expired => Some(expires_at),
+            _ => None,
+        };
+        let mut batch = WriteBatch::new();
+        batch.put(credential_key(credential_id), encode_record(&entry)?);
+        batch.delete(revocation_key(credential_id));
+        self.commit(batch, vec![credential_event(RegistryEventType::CredentialReinstated, &entry)])?;
+        self.entries.insert(credential_id.to_string(), entry);
+
+        if let Some(expires_at) = reindex {
+            self.index_expiration(credential_id, expires_at);
+        }
+
+        self.revocations.remove(credential_id);
+
+        if let Some(event_bus) = &self.event_bus {
+            event_bus.publish(DomainEvent::CredentialReinstated {
+                credential_id: credential_id.to_string(),
+            });
+        }
+        Ok(())
+    }
+
+    /// Mark every credential whose expiration is before `now` as expired.
+    ///
+    /// Only entries in the expiration index are visited, so the cost is proportional
+    /// to the number of newly expired credentials. Returns the IDs that were expired;
+    /// a credential whose update cannot be persisted stays indexed for the next sweep.
+    pub fn sweep_expired(&mut self, now: DateTime<Utc>) -> Vec<String> {
+        let pending = self.expirations.split_off(&now);
+        let due = std::mem::replace(&mut self.expirations, pending);
+
+        let mut expired = Vec::new();
+        for (expires_at, credential_ids) in due {
+            for credential_id in credential_ids {
+                let Some(mut entry) = self.entries.get(&credential_id).cloned() else {
+                    continue;
+                };
+                entry.status = CredentialStatus::Expired;
+                match self.save_entry(entry, RegistryEventType::CredentialExpired) {
+                    Ok(()) => expired.push(credential_id),
+                    Err(_) => self.index_expiration(&credential_id, expires_at),
+                }
+            }
+        }
+
+        expired
+    }
+
+    /// Number of credentials awaiting expiration in the index
+    pub fn pending_expirations(&self) -> usize {
+        self.expirations.values().map(Vec::len).sum()
+    }
+
     /// Check credential status
     pub fn get_credential_status(&self, credential_id: &str) -> Option<&CredentialStatus> {
         self.entries.get(credential_id).map(|entry| {
@@ -164,10 +452,17 @@ impl CredentialRegistry {
 
     /// Check if credential is valid (active and not expired)
     pub fn is_valid(&self, credential_id: &str) -> bool {
-        match self.get_credential_status(credential_id) {
-            Some(CredentialStatus::Active) => true,
-            _ => false,
-        }
+        matches!(self.get_credential_status(credential_id), Some(CredentialStatus::Active))
+    }
+
+    /// All registered credentials, in no particular order
+    pub fn entries(&self) -> impl Iterator<Item = &CredentialRegistryEntry> {
+        self.entries.values()
+    }
+
+    /// All revocations, in no particular order
+    pub fn revocations(&self) -> impl Iterator<Item = &RevocationEntry> {
+        self.revocations.values()
     }
 
     /// List credentials by issuer
@@ -180,7 +475,7 @@ impl CredentialRegistry {
     /// List credentials by subject
     pub fn list_credentials_by_subject(&self, subject_did: &str) -> Vec<&CredentialRegistryEntry> {
         self.entries.values()
-            .filter(|entry| entry.subject_did.as_ref().map(|s| s.as_str()) == Some(subject_did))
+            .filter(|entry| entry.subject_did.as_deref() == Some(subject_did))
             .collect()
     }
 
@@ -190,6 +485,9 @@ impl CredentialRegistry {
             return Err("Schema already exists".to_string());
         }
 
+        let mut batch = WriteBatch::new();
+        batch.put(format!("{}{}", SCHEMA_PREFIX, schema_id), encode_record(&schema_hash)?);
+        self.commit(batch, vec![(RegistryEventType::SchemaRegistered, None, None)])?;
         self.schema_registry.insert(schema_id, schema_hash);
         Ok(())
     }
@@ -205,8 +503,291 @@ impl CredentialRegistry {
     }
 }
 
+/// Event concerning a credential, tagged with its issuer
+fn credential_event(event_type: RegistryEventType, entry: &CredentialRegistryEntry) -> PendingEvent {
+    (event_type, Some(entry.issuer_did.clone()), Some(entry.credential_id.clone()))
+}
+
+/// Store key of a credential entry
+fn credential_key(credential_id: &str) -> String {
+    format!("{}{}", CREDENTIAL_PREFIX, credential_id)
+}
+
+/// Store key of a credential's revocation record
+fn revocation_key(credential_id: &str) -> String {
+    format!("{}{}", REVOCATION_PREFIX, credential_id)
+}
+
 impl Default for CredentialRegistry {
     fn default() -> Self {
         Self::new()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::store::FailingStore;
+
+    fn registration(id: &str) -> CredentialRegistration {
+        CredentialRegistration {
+            credential_id: id.to_string(),
+            credential_hash: format!("Qm{}", id),
+            issuer_did: "did:example:issuer".to_string(),
+            subject_did: Some("did:example:alice".to_string()),
+            schema_id: None,
+            expires_at: None,
+            required_attestations: 0,
+        }
+    }
+
+    #[test]
+    fn valid_batch_commits_every_entry() {
+        let mut registry = CredentialRegistry::new();
+
+        registry.register_batch(vec![registration("a"), registration("b"), registration("c")]).unwrap();
+
+        assert_eq!(registry.entries().count(), 3);
+        assert!(registry.is_valid("b"));
+        assert_eq!(registry.latest_event_sequence(), 3);
+    }
+
+    #[test]
+    fn batch_with_duplicate_id_commits_nothing() {
+        let mut registry = CredentialRegistry::new();
+
+        let error = registry.register_batch(vec![registration("a"), registration("b"), registration("a")]).unwrap_err();
+
+        assert_eq!(error.0, 2);
+        assert_eq!(registry.entries().count(), 0);
+        assert!(registry.store().iter(CREDENTIAL_PREFIX).unwrap().is_empty());
+    }
+
+    #[test]
+    fn batch_reports_invalid_issuer_index() {
+        let mut registry = CredentialRegistry::new();
+        let mut invalid = registration("b");
+        invalid.issuer_did = "issuer".to_string();
+
+        let error = registry.register_batch(vec![registration("a"), invalid]).unwrap_err();
+
+        assert_eq!(error.0, 1);
+        assert_eq!(registry.entries().count(), 0);
+    }
+
+    #[test]
+    fn store_failure_mid_batch_commits_nothing() {
+        let store = FailingStore { fail_key: Some(credential_key("b")), ..Default::default() };
+        let mut registry = CredentialRegistry::with_store(store).unwrap();
+
+        assert!(registry.register_batch(vec![registration("a"), registration("b"), registration("c")]).is_err());
+
+        assert_eq!(registry.entries().count(), 0);
+        assert!(registry.store().iter(CREDENTIAL_PREFIX).unwrap().is_empty());
+        assert_eq!(registry.latest_event_sequence(), 0);
+    }
+
+    #[test]
+    fn failed_revocation_leaves_no_revocation_record() {
+        let mut registry = CredentialRegistry::with_store(FailingStore::default()).unwrap();
+        registry.register_batch(vec![registration("a")]).unwrap();
+        registry.store.fail_key = Some(credential_key("a"));
+
+        assert!(registry.revoke_credential("a", "did:example:issuer".to_string(), "compromised".to_string()).is_err());
+
+        assert!(registry.store().iter(REVOCATION_PREFIX).unwrap().is_empty());
+        assert!(registry.get_revocation_info("a").is_none());
+        assert!(registry.is_valid("a"));
+    }
+
+    #[test]
+    fn events_survive_reopening_the_store() {
+        let mut registry = CredentialRegistry::new();
+        registry.register_batch(vec![registration("a"), registration("b")]).unwrap();
+        registry.revoke_credential("a", "did:example:issuer".to_string(), "compromised".to_string()).unwrap();
+        registry.register_schema("schema-1".to_string(), "QmSchema".to_string()).unwrap();
+
+        let mut reopened = CredentialRegistry::with_store(registry.store().clone()).unwrap();
+        assert_eq!(reopened.latest_event_sequence(), 4);
+        let revoked = reopened.query_events(&EventFilter::new().with_event_type(RegistryEventType::CredentialRevoked));
+        assert_eq!(revoked.len(), 1);
+        assert_eq!(revoked[0].credential_id.as_deref(), Some("a"));
+        assert_eq!(revoked[0].did.as_deref(), Some("did:example:issuer"));
+
+        reopened.reinstate_credential("a").unwrap();
+        let since = reopened.query_events(&EventFilter::new().with_since(4));
+        assert_eq!(since.len(), 1);
+        assert_eq!(since[0].sequence, 5);
+        assert_eq!(since[0].event_type, RegistryEventType::CredentialReinstated);
+    }
+
+    fn expiring(id: &str, expires_at: DateTime<Utc>) -> CredentialRegistration {
+        CredentialRegistration { expires_at: Some(expires_at), ..registration(id) }
+    }
+
+    #[test]
+    fn sweep_touches_only_expired_entries() {
+        let now = Utc::now();
+        let mut registry = CredentialRegistry::new();
+        registry.register_batch(vec![
+            expiring("past", now - chrono::Duration::hours(1)),
+            expiring("future", now + chrono::Duration::hours(1)),
+            registration("forever"),
+        ]).unwrap();
+        assert_eq!(registry.pending_expirations(), 2);
+
+        assert_eq!(registry.sweep_expired(now), vec!["past"]);
+
+        assert_eq!(registry.get_credential("past").unwrap().status, CredentialStatus::Expired);
+        assert_eq!(registry.get_credential("future").unwrap().status, CredentialStatus::Active);
+        assert_eq!(registry.pending_expirations(), 1);
+        let expired = registry.query_events(&EventFilter::new().with_event_type(RegistryEventType::CredentialExpired));
+        assert_eq!(expired.len(), 1);
+        assert!(registry.sweep_expired(now).is_empty());
+    }
+
+    #[test]
+    fn index_follows_revocation_and_reinstatement() {
+        let now = Utc::now();
+        let mut registry = CredentialRegistry::new();
+        registry.register_batch(vec![
+            expiring("a", now + chrono::Duration::hours(1)),
+            expiring("b", now - chrono::Duration::hours(1)),
+        ]).unwrap();
+
+        registry.revoke_credential("a", "did:example:issuer".to_string(), "compromised".to_string()).unwrap();
+        registry.revoke_credential("b", "did:example:issuer".to_string(), "compromised".to_string()).unwrap();
+        assert_eq!(registry.pending_expirations(), 0);
+        assert!(registry.sweep_expired(now + chrono::Duration::hours(2)).is_empty());
+
+        registry.reinstate_credential("a").unwrap();
+        registry.reinstate_credential("b").unwrap();
+        // b lapsed while revoked, so it comes back expired and stays out of the index
+        assert_eq!(registry.get_credential("b").unwrap().status, CredentialStatus::Expired);
+        assert_eq!(registry.pending_expirations(), 1);
+        assert_eq!(registry.sweep_expired(now + chrono::Duration::hours(2)), vec!["a"]);
+        assert_eq!(registry.pending_expirations(), 0);
+    }
+
+    #[test]
+    fn index_is_rebuilt_when_reopening_the_store() {
+        let now = Utc::now();
+        let mut registry = CredentialRegistry::new();
+        registry.register_batch(vec![
+            expiring("a", now - chrono::Duration::hours(1)),
+            expiring("b", now + chrono::Duration::hours(1)),
+            expiring("c", now + chrono::Duration::hours(2)),
+        ]).unwrap();
+        registry.sweep_expired(now);
+        registry.revoke_credential("c", "did:example:issuer".to_string(), "compromised".to_string()).unwrap();
+
+        let mut reopened = CredentialRegistry::with_store(registry.store().clone()).unwrap();
+        assert_eq!(reopened.pending_expirations(), 1);
+        assert_eq!(reopened.sweep_expired(now + chrono::Duration::hours(3)), vec!["b"]);
+    }
+
+    #[test]
+    fn revocations_are_announced_on_the_event_bus() {
+        let bus = EventBus::default();
+        let mut events = bus.subscribe();
+        let mut registry = CredentialRegistry::new();
+        registry.set_event_bus(bus);
+        registry.register_batch(vec![registration("a")]).unwrap();
+
+        registry.revoke_credential("a", "did:example:issuer".to_string(), "compromised".to_string()).unwrap();
+        match events.try_recv().unwrap() {
+            DomainEvent::CredentialRevoked { credential_id, reason } => {
+                assert_eq!(credential_id, "a");
+                assert_eq!(reason, "compromised");
+            }
+            other => panic!("unexpected event {:?}", other),
+        }
+
+        assert!(registry.revoke_credential("a", "did:example:issuer".to_string(), "again".to_string()).is_err());
+        assert!(events.try_recv().is_err());
+    }
+
+    #[tokio::test]
+    async fn revoking_invalidates_subscribed_caches() {
+        use identity_core::{generate_ed25519_keypair, KeyType, RevocationCache, VerifiableCredential, VerificationCache, VerificationOptions};
+        use std::sync::{Arc, Mutex};
+
+        let keypair = generate_ed25519_keypair().unwrap();
+        let mut credential = VerifiableCredential::new("did:example:issuer".to_string(), None, BTreeMap::new());
+        credential.sign(&keypair, "did:example:issuer#key-1".to_string()).unwrap();
+        let verification_cache = Arc::new(Mutex::new(VerificationCache::new(chrono::Duration::minutes(5))));
+        verification_cache.lock().unwrap()
+            .verify(&credential, &keypair.public_key, &KeyType::Ed25519, &VerificationOptions::default()).unwrap();
+        let revocation_cache = Arc::new(Mutex::new(RevocationCache::new(chrono::Duration::minutes(5))));
+        revocation_cache.lock().unwrap().insert(credential.id.clone(), false, None);
+
+        let bus = EventBus::default();
+        let tasks = [bus.attach(verification_cache.clone()), bus.attach(revocation_cache.clone())];
+        let mut registry = CredentialRegistry::new();
+        registry.set_event_bus(bus);
+        registry.register_batch(vec![registration(&credential.id)]).unwrap();
+        registry.revoke_credential(&credential.id, "did:example:issuer".to_string(), "compromised".to_string()).unwrap();
+        drop(registry);
+        for task in tasks {
+            task.await.unwrap();
+        }
+
+        assert!(verification_cache.lock().unwrap().is_empty());
+        let revocations = revocation_cache.lock().unwrap();
+        assert!(revocations.is_revoked(&credential.id));
+        assert_eq!(revocations.get(&credential.id).unwrap().reason.as_deref(), Some("compromised"));
+    }
+
+    #[test]
+    fn entries_record_the_hash_function_of_their_credential() {
+        let mut registry = CredentialRegistry::new();
+        let mut blake3 = registration("b");
+        blake3.credential_hash = "bafkr4igxjga67jykbseaxdmmdgc5a5o3zp3htom2l6mrjznk7fvyggu6eq".to_string();
+        registry.register_batch(vec![registration("a"), blake3]).unwrap();
+
+        assert_eq!(registry.get_credential("a").unwrap().hash_code, HashCode::Sha2_256);
+        assert_eq!(registry.get_credential("b").unwrap().hash_code, HashCode::Blake3_256);
+    }
+
+    /// Register, attest and revoke credentials, then check the state survives reopening the store
+    fn credential_lifecycle<S: RegistryStore + Clone>(store: S) {
+        let mut registry = CredentialRegistry::with_store(store).unwrap();
+        registry.register_batch(vec![registration("a"), registration("b")]).unwrap();
+        registry.register_credential(
+            "c".to_string(),
+            "QmC".to_string(),
+            "did:example:issuer".to_string(),
+            None,
+            None,
+            None,
+            1,
+        ).unwrap();
+        registry.add_attestation("c").unwrap();
+        registry.revoke_credential("a", "did:example:issuer".to_string(), "compromised".to_string()).unwrap();
+        registry.store().flush().unwrap();
+
+        let reopened = CredentialRegistry::with_store(registry.store().clone()).unwrap();
+        assert!(!reopened.is_valid("a"));
+        assert!(reopened.is_valid("b"));
+        assert_eq!(reopened.get_revocation_info("a").unwrap().reason, "compromised");
+        assert_eq!(reopened.get_credential("c").unwrap().credential_hash, "QmC");
+        assert_eq!(reopened.list_credentials_by_issuer("did:example:issuer").len(), 3);
+    }
+
+    #[test]
+    fn lifecycle_against_the_memory_store() {
+        credential_lifecycle(MemoryStore::new());
+    }
+
+    #[cfg(feature = "sled")]
+    #[test]
+    fn lifecycle_against_a_sled_store() {
+        let path = std::env::temp_dir().join(format!("credential-registry-{}", uuid::Uuid::new_v4()));
+        credential_lifecycle(crate::store::SledStore::open(&path).unwrap());
+
+        let reopened = CredentialRegistry::with_store(crate::store::SledStore::open(&path).unwrap()).unwrap();
+        assert!(!reopened.is_valid("a"));
+        drop(reopened);
+        std::fs::remove_dir_all(&path).unwrap();
+    }
+}
@@ -1,15 +1,26 @@
 //! DID registry for Substrate runtime
 
-use serde::{Deserialize, Serialize};
+use serde::{Deserialize, Deserializer, Serialize};
 use chrono::{DateTime, Utc};
 use std::collections::HashMap;
+use crate::events::{event_key, EventFilter, EventLog, RegistryEvent, RegistryEventType};
+use crate::store::{encode_record, load_records, MemoryStore, RegistryStore, WriteBatch};
+use identity_core::utils::parse_did;
+use identity_core::{hash_code_of, verify_merkle_proof, DomainEvent, EventBus, HashCode, MerkleProof, MerkleTree};
 
 /// DID registry entry stored on-chain
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct DidRegistryEntry {
     pub did: String,
     pub document_hash: String, // IPFS hash
-    pub controller: String,
+    /// Hash function the document hash was computed with
+    #[serde(default)]
+    pub hash_code: HashCode,
+    /// Entries stored before multi-controller support have a single `controller` string
+    #[serde(alias = "controller", deserialize_with = "one_or_many")]
+    pub controllers: Vec<String>,
+    #[serde(default = "single_controller_threshold")]
+    pub threshold: usize, // number of controllers required to authorize changes
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
     pub status: DidStatus,
@@ -17,6 +28,38 @@ pub struct DidRegistryEntry {
     pub metadata: HashMap<String, String>,
 }
 
+impl DidRegistryEntry {
+    /// Check whether the given authorizers include enough distinct controllers
+    pub fn is_authorized(&self, authorizers: &[&str]) -> bool {
+        let mut approved: Vec<&str> = Vec::new();
+        for authorizer in authorizers {
+            if self.controllers.iter().any(|c| c == authorizer) && !approved.contains(authorizer) {
+                approved.push(authorizer);
+            }
+        }
+        approved.len() >= self.threshold
+    }
+}
+
+/// Read a list of controllers, or a single legacy controller string
+fn one_or_many<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Vec<String>, D::Error> {
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum OneOrMany {
+        One(String),
+        Many(Vec<String>),
+    }
+
+    Ok(match OneOrMany::deserialize(deserializer)? {
+        OneOrMany::One(controller) => vec![controller],
+        OneOrMany::Many(controllers) => controllers,
+    })
+}
+
+fn single_controller_threshold() -> usize {
+    1
+}
+
 /// Status of a DID
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub enum DidStatus {
@@ -25,83 +68,336 @@ pub enum DidStatus {
     Revoked,
 }
 
-/// DID registry for managing DIDs on-chain
-pub struct DidRegistry {
+/// DID to register as part of a batch
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DidBatchEntry {
+    pub did: String,
+    pub document_hash: String,
+    pub controller: String,
+    pub verification_methods: Vec<String>,
+}
+
+/// Store key prefix of DID entries
+const DID_PREFIX: &str = "did/";
+
+/// Store key prefix of batch leaves
+const BATCH_PREFIX: &str = "batch/";
+
+/// Store key prefix of registry events
+const EVENT_PREFIX: &str = "did_event/";
+
+/// DID registry for managing DIDs on-chain, persisted to a pluggable store
+pub struct DidRegistry<S: RegistryStore = MemoryStore> {
+    store: S,
     entries: HashMap<String, DidRegistryEntry>,
+    events: EventLog,
+    event_bus: Option<EventBus>,
+    batches: HashMap<String, Vec<(String, String)>>, // Merkle root -> (did, document hash) leaves in order
 }
 
 impl DidRegistry {
-    /// Create a new DID registry
+    /// Create a new in-memory DID registry
     pub fn new() -> Self {
+        Self::empty(MemoryStore::new())
+    }
+}
+
+impl<S: RegistryStore> DidRegistry<S> {
+    /// Open a registry over a store, loading the DIDs, batches and events it already holds
+    pub fn with_store(store: S) -> Result<Self, String> {
+        let entries = load_records(&store, DID_PREFIX)?.into_iter().collect();
+        let batches = load_records(&store, BATCH_PREFIX)?.into_iter().collect();
+        let events = EventLog::from_events(
+            load_records(&store, EVENT_PREFIX)?.into_iter().map(|(_, event)| event).collect(),
+        )?;
+        Ok(Self { entries, batches, events, ..Self::empty(store) })
+    }
+
+    fn empty(store: S) -> Self {
         Self {
+            store,
             entries: HashMap::new(),
+            events: EventLog::new(),
+            event_bus: None,
+            batches: HashMap::new(),
         }
     }
 
-    /// Register a new DID
+    /// The backing store
+    pub fn store(&self) -> &S {
+        &self.store
+    }
+
+    /// Write an entry and the event describing its change through to the store, then cache it
+    fn save_entry(&mut self, entry: DidRegistryEntry, event_type: RegistryEventType) -> Result<(), String> {
+        let mut batch = WriteBatch::new();
+        batch.put(did_key(&entry.did), encode_record(&entry)?);
+        self.commit(batch, vec![(event_type, entry.did.clone())])?;
+        self.entries.insert(entry.did.clone(), entry);
+        Ok(())
+    }
+
+    /// Write a batch together with the events it causes, then append the events to the log
+    fn commit(&mut self, mut batch: WriteBatch, events: Vec<(RegistryEventType, String)>) -> Result<(), String> {
+        let events = self.events.prepare(
+            events.into_iter().map(|(event_type, did)| (event_type, Some(did), None)).collect(),
+        );
+        for event in &events {
+            batch.put(event_key(EVENT_PREFIX, event.sequence), encode_record(event)?);
+        }
+        self.store.write_batch(batch)?;
+        self.events.extend(events);
+        Ok(())
+    }
+
+    /// Get a copy of an entry to modify and save
+    fn entry_for_update(&self, did: &str, authorizers: &[&str]) -> Result<DidRegistryEntry, String> {
+        let entry = self.entries.get(did)
+            .ok_or("DID not found")?;
+
+        if !entry.is_authorized(authorizers) {
+            return Err("Unauthorized: controller threshold not met".to_string());
+        }
+        Ok(entry.clone())
+    }
+
+    /// Publish `DidRegistered` events to a bus
+    pub fn set_event_bus(&mut self, event_bus: EventBus) {
+        self.event_bus = Some(event_bus);
+    }
+
+    /// Register a new DID with a single controller
     pub fn register_did(
         &mut self,
         did: String,
         document_hash: String,
         controller: String,
         verification_methods: Vec<String>,
+    ) -> Result<(), String> {
+        self.register_did_with_controllers(did, document_hash, vec![controller], 1, verification_methods)
+    }
+
+    /// Register a new DID controlled by a set of controllers with a k-of-n threshold
+    pub fn register_did_with_controllers(
+        &mut self,
+        did: String,
+        document_hash: String,
+        controllers: Vec<String>,
+        threshold: usize,
+        verification_methods: Vec<String>,
     ) -> Result<(), String> {
         if self.entries.contains_key(&did) {
             return Err("DID already exists".to_string());
         }
 
-        let entry = DidRegistryEntry {
-            did: did.clone(),
+        let entry = Self::new_entry(did.clone(), document_hash, controllers, threshold, verification_methods)?;
+        self.save_entry(entry, RegistryEventType::DidRegistered)?;
+        self.announce(&did);
+        Ok(())
+    }
+
+    /// Build a fresh entry, deduplicating its controllers
+    fn new_entry(
+        did: String,
+        document_hash: String,
+        controllers: Vec<String>,
+        threshold: usize,
+        verification_methods: Vec<String>,
+    ) -> Result<DidRegistryEntry, String> {
+        let mut unique_controllers: Vec<String> = Vec::new();
+        for controller in controllers {
+            if !unique_controllers.contains(&controller) {
+                unique_controllers.push(controller);
+            }
+        }
+
+        if threshold == 0 || threshold > unique_controllers.len() {
+            return Err("Threshold must be between 1 and the number of controllers".to_string());
+        }
+
+        Ok(DidRegistryEntry {
+            did,
+            hash_code: hash_code_of(&document_hash),
             document_hash,
-            controller,
+            controllers: unique_controllers,
+            threshold,
             created_at: Utc::now(),
             updated_at: Utc::now(),
             status: DidStatus::Active,
             verification_methods,
             metadata: HashMap::new(),
-        };
+        })
+    }
 
-        self.entries.insert(did, entry);
-        Ok(())
+    /// Publish the registration of a stored DID to the event bus
+    fn announce(&self, did: &str) {
+        if let (Some(event_bus), Some(entry)) = (&self.event_bus, self.entries.get(did)) {
+            event_bus.publish(DomainEvent::DidRegistered {
+                did: did.to_string(),
+                document_hash: entry.document_hash.clone(),
+            });
+        }
+    }
+
+    /// Register many DIDs at once, returning the hex Merkle root over their DID and hash pairs.
+    ///
+    /// The batch is rejected as a whole if any DID is malformed, duplicated or already
+    /// registered, and its entries, leaves and events are written to the store in a single batch.
+    pub fn register_batch(&mut self, entries: Vec<DidBatchEntry>) -> Result<String, String> {
+        if entries.is_empty() {
+            return Err("Batch is empty".to_string());
+        }
+
+        for entry in &entries {
+            parse_did(&entry.did).map_err(|e| format!("Invalid DID in batch: {}", e))?;
+        }
+
+        let mut dids: Vec<&str> = entries.iter().map(|entry| entry.did.as_str()).collect();
+        dids.sort();
+        if let Some(pair) = dids.windows(2).find(|pair| pair[0] == pair[1]) {
+            return Err(format!("Duplicate DID in batch: {}", pair[0]));
+        }
+        if let Some(did) = dids.iter().find(|did| self.entries.contains_key(**did)) {
+            return Err(format!("DID already exists: {}", did));
+        }
+
+        // Leaves are sorted by DID so the root does not depend on submission order
+        let mut leaves: Vec<(String, String)> = entries.iter()
+            .map(|entry| (entry.did.clone(), entry.document_hash.clone()))
+            .collect();
+        leaves.sort();
+
+        let entries = entries.into_iter()
+            .map(|entry| Self::new_entry(entry.did, entry.document_hash, vec![entry.controller], 1, entry.verification_methods))
+            .collect::<Result<Vec<_>, _>>()?;
+        let root = MerkleTree::new(&Self::batch_leaves(&leaves)).root_hex();
+
+        let mut batch = WriteBatch::new();
+        for entry in &entries {
+            batch.put(did_key(&entry.did), encode_record(entry)?);
+        }
+        batch.put(format!("{}{}", BATCH_PREFIX, root), encode_record(&leaves)?);
+        let events = entries.iter().map(|entry| (RegistryEventType::DidRegistered, entry.did.clone())).collect();
+        self.commit(batch, events)?;
+
+        for entry in entries {
+            let did = entry.did.clone();
+            self.entries.insert(did.clone(), entry);
+            self.announce(&did);
+        }
+        self.batches.insert(root.clone(), leaves);
+        Ok(root)
     }
 
-    /// Update DID document hash
+    /// Proof that a DID was registered in the batch with the given root
+    pub fn batch_membership_proof(&self, root: &str, did: &str) -> Option<MerkleProof> {
+        let leaves = self.batches.get(root)?;
+        let index = leaves.binary_search_by(|(leaf_did, _)| leaf_did.as_str().cmp(did)).ok()?;
+        MerkleTree::new(&Self::batch_leaves(leaves)).proof(index)
+    }
+
+    fn batch_leaves(leaves: &[(String, String)]) -> Vec<Vec<u8>> {
+        leaves.iter().map(|(did, hash)| did_batch_leaf(did, hash)).collect()
+    }
+
+    /// Update DID document hash, authorized by a set of controllers meeting the threshold
     pub fn update_did_document(
         &mut self,
         did: &str,
         new_document_hash: String,
-        controller: &str,
+        authorizers: &[&str],
     ) -> Result<(), String> {
-        let entry = self.entries.get_mut(did)
-            .ok_or("DID not found")?;
-
-        if entry.controller != controller {
-            return Err("Unauthorized: not the controller".to_string());
-        }
+        let mut entry = self.entry_for_update(did, authorizers)?;
 
         if entry.status != DidStatus::Active {
             return Err("DID is not active".to_string());
         }
 
+        entry.hash_code = hash_code_of(&new_document_hash);
         entry.document_hash = new_document_hash;
         entry.updated_at = Utc::now();
+        self.save_entry(entry, RegistryEventType::DidUpdated)?;
         Ok(())
     }
 
-    /// Deactivate a DID
-    pub fn deactivate_did(&mut self, did: &str, controller: &str) -> Result<(), String> {
-        let entry = self.entries.get_mut(did)
-            .ok_or("DID not found")?;
+    /// Deactivate a DID, authorized by a set of controllers meeting the threshold
+    pub fn deactivate_did(&mut self, did: &str, authorizers: &[&str]) -> Result<(), String> {
+        let mut entry = self.entry_for_update(did, authorizers)?;
+
+        entry.status = DidStatus::Deactivated;
+        entry.updated_at = Utc::now();
+        self.save_entry(entry, RegistryEventType::DidDeactivated)?;
+        Ok(())
+    }
+
+    /// Set a metadata entry on a DID, authorized by a set of controllers meeting the threshold
+    pub fn set_metadata(
+        &mut self,
+        did: &str,
+        key: String,
+        value: String,
+        authorizers: &[&str],
+    ) -> Result<(), String> {
+        let mut entry = self.entry_for_update(did, authorizers)?;
+
+        entry.metadata.insert(key, value);
+        entry.updated_at = Utc::now();
+        self.save_entry(entry, RegistryEventType::DidUpdated)?;
+        Ok(())
+    }
+
+    /// Delegate control of a DID to an additional controller
+    pub fn add_controller(
+        &mut self,
+        did: &str,
+        new_controller: String,
+        authorizers: &[&str],
+    ) -> Result<(), String> {
+        let mut entry = self.entry_for_update(did, authorizers)?;
 
-        if entry.controller != controller {
-            return Err("Unauthorized: not the controller".to_string());
+        if entry.controllers.contains(&new_controller) {
+            return Err("Controller already exists".to_string());
         }
 
-        entry.status = DidStatus::Deactivated;
+        entry.controllers.push(new_controller);
         entry.updated_at = Utc::now();
+        self.save_entry(entry, RegistryEventType::ControllerAdded)?;
         Ok(())
     }
 
+    /// Remove a controller, keeping the threshold achievable by the remaining set
+    pub fn remove_controller(
+        &mut self,
+        did: &str,
+        controller: &str,
+        authorizers: &[&str],
+    ) -> Result<(), String> {
+        let mut entry = self.entry_for_update(did, authorizers)?;
+
+        if !entry.controllers.iter().any(|c| c == controller) {
+            return Err("Controller not found".to_string());
+        }
+
+        if entry.controllers.len() - 1 < entry.threshold {
+            return Err("Removing controller would make threshold unreachable".to_string());
+        }
+
+        entry.controllers.retain(|c| c != controller);
+        entry.updated_at = Utc::now();
+        self.save_entry(entry, RegistryEventType::ControllerRemoved)?;
+        Ok(())
+    }
+
+    /// Events matching a filter, for incremental sync by indexers
+    pub fn query_events(&self, filter: &EventFilter) -> Vec<RegistryEvent> {
+        self.events.query(filter)
+    }
+
+    /// Sequence id of the most recent event
+    pub fn latest_event_sequence(&self) -> u64 {
+        self.events.latest_sequence()
+    }
+
     /// Get DID entry
     pub fn get_did(&self, did: &str) -> Option<&DidRegistryEntry> {
         self.entries.get(did)
@@ -114,10 +410,15 @@ impl DidRegistry {
             .unwrap_or(false)
     }
 
+    /// All registered DIDs, in no particular order
+    pub fn entries(&self) -> impl Iterator<Item = &DidRegistryEntry> {
+        self.entries.values()
+    }
+
     /// List all DIDs for a controller
     pub fn list_dids_by_controller(&self, controller: &str) -> Vec<&DidRegistryEntry> {
         self.entries.values()
-            .filter(|entry| entry.controller == controller)
+            .filter(|entry| entry.controllers.iter().any(|c| c == controller))
             .collect()
     }
 }
@@ -127,3 +428,298 @@ impl Default for DidRegistry {
         Self::new()
     }
 }
+
+/// Store key of a DID entry
+fn did_key(did: &str) -> String {
+    format!("{}{}", DID_PREFIX, did)
+}
+
+/// Leaf bytes committing to a DID and its document hash
+pub fn did_batch_leaf(did: &str, document_hash: &str) -> Vec<u8> {
+    // Batches only hold DIDs that pass `parse_did`, which rejects NUL, so the separator is unambiguous
+    [did.as_bytes(), &[0], document_hash.as_bytes()].concat()
+}
+
+/// Check that a DID was registered with a document hash in the batch with the given hex root
+pub fn verify_did_membership(root: &str, did: &str, document_hash: &str, proof: &MerkleProof) -> bool {
+    if parse_did(did).is_err() {
+        return false;
+    }
+    let root: [u8; 32] = match hex::decode(root).ok().and_then(|bytes| bytes.try_into().ok()) {
+        Some(root) => root,
+        None => return false,
+    };
+    verify_merkle_proof(&root, &did_batch_leaf(did, document_hash), proof)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::store::FailingStore;
+
+    fn batch_entry(did: &str) -> DidBatchEntry {
+        DidBatchEntry {
+            did: did.to_string(),
+            document_hash: format!("Qm{}", did.len()),
+            controller: "did:example:controller".to_string(),
+            verification_methods: vec![format!("{}#key-1", did)],
+        }
+    }
+
+    fn stored_dids<S: RegistryStore>(registry: &DidRegistry<S>) -> usize {
+        registry.store().iter(DID_PREFIX).unwrap().len()
+    }
+
+    #[test]
+    fn batch_members_verify_against_the_root() {
+        let mut registry = DidRegistry::new();
+        let entries = vec![batch_entry("did:example:bob"), batch_entry("did:example:alice"), batch_entry("did:example:carol")];
+
+        let root = registry.register_batch(entries).unwrap();
+
+        assert_eq!(stored_dids(&registry), 3);
+        assert!(registry.is_active("did:example:alice"));
+        let proof = registry.batch_membership_proof(&root, "did:example:bob").unwrap();
+        assert!(verify_did_membership(&root, "did:example:bob", "Qm15", &proof));
+        assert!(!verify_did_membership(&root, "did:example:bob", "Qm16", &proof));
+        assert!(!verify_did_membership(&root, "did:example:alice", "Qm17", &proof));
+    }
+
+    #[test]
+    fn batch_with_invalid_did_commits_nothing() {
+        let mut registry = DidRegistry::new();
+        let entries = vec![batch_entry("did:example:alice"), batch_entry("did:Bad:bob")];
+
+        let err = registry.register_batch(entries).unwrap_err();
+
+        assert!(err.starts_with("Invalid DID in batch"), "{}", err);
+        assert_eq!(stored_dids(&registry), 0);
+        assert!(registry.get_did("did:example:alice").is_none());
+        assert_eq!(registry.latest_event_sequence(), 0);
+    }
+
+    #[test]
+    fn batch_with_duplicate_did_commits_nothing() {
+        let mut registry = DidRegistry::new();
+        let entries = vec![batch_entry("did:example:alice"), batch_entry("did:example:bob"), batch_entry("did:example:alice")];
+
+        assert!(registry.register_batch(entries).unwrap_err().starts_with("Duplicate DID"));
+        assert_eq!(stored_dids(&registry), 0);
+        assert!(registry.get_did("did:example:bob").is_none());
+    }
+
+    #[test]
+    fn batch_with_registered_did_commits_nothing() {
+        let mut registry = DidRegistry::new();
+        registry.register_did(
+            "did:example:bob".to_string(), "QmBob".to_string(), "did:example:controller".to_string(), vec![],
+        ).unwrap();
+
+        let err = registry.register_batch(vec![batch_entry("did:example:alice"), batch_entry("did:example:bob")]).unwrap_err();
+
+        assert!(err.starts_with("DID already exists"));
+        assert_eq!(stored_dids(&registry), 1);
+        assert!(registry.get_did("did:example:alice").is_none());
+    }
+
+    #[test]
+    fn store_failure_mid_batch_commits_nothing() {
+        let store = FailingStore { fail_key: Some(did_key("did:example:bob")), ..Default::default() };
+        let mut registry = DidRegistry::with_store(store).unwrap();
+        let entries = vec![batch_entry("did:example:alice"), batch_entry("did:example:bob"), batch_entry("did:example:carol")];
+
+        assert!(registry.register_batch(entries).is_err());
+        assert_eq!(stored_dids(&registry), 0);
+        assert!(registry.store().iter(BATCH_PREFIX).unwrap().is_empty());
+        assert!(registry.get_did("did:example:alice").is_none());
+        assert_eq!(registry.latest_event_sequence(), 0);
+    }
+
+    #[test]
+    fn batches_survive_reopening_the_store() {
+        let mut registry = DidRegistry::new();
+        let root = registry.register_batch(vec![batch_entry("did:example:alice"), batch_entry("did:example:bob")]).unwrap();
+
+        let reopened = DidRegistry::with_store(registry.store().clone()).unwrap();
+
+        assert!(reopened.is_active("did:example:bob"));
+        let proof = reopened.batch_membership_proof(&root, "did:example:alice").unwrap();
+        assert!(verify_did_membership(&root, "did:example:alice", "Qm17", &proof));
+    }
+
+    #[test]
+    fn events_survive_reopening_the_store() {
+        let mut registry = DidRegistry::new();
+        registry.register_batch(vec![batch_entry("did:example:alice"), batch_entry("did:example:bob")]).unwrap();
+        registry.update_did_document("did:example:alice", "QmNew".to_string(), &["did:example:controller"]).unwrap();
+
+        let mut reopened = DidRegistry::with_store(registry.store().clone()).unwrap();
+        assert_eq!(reopened.latest_event_sequence(), 3);
+        let updates = reopened.query_events(&EventFilter::new().with_event_type(RegistryEventType::DidUpdated));
+        assert_eq!(updates.len(), 1);
+        assert_eq!(updates[0].did.as_deref(), Some("did:example:alice"));
+
+        reopened.deactivate_did("did:example:bob", &["did:example:controller"]).unwrap();
+        let since = reopened.query_events(&EventFilter::new().with_since(3));
+        assert_eq!(since.len(), 1);
+        assert_eq!(since[0].sequence, 4);
+        assert_eq!(since[0].event_type, RegistryEventType::DidDeactivated);
+    }
+
+    #[test]
+    fn failed_write_records_no_event() {
+        let store = FailingStore { fail_key: Some(did_key("did:example:bob")), ..Default::default() };
+        let mut registry = DidRegistry::with_store(store).unwrap();
+
+        let result = registry.register_did(
+            "did:example:bob".to_string(), "QmBob".to_string(), "did:example:controller".to_string(), vec![],
+        );
+
+        assert!(result.is_err());
+        assert_eq!(registry.latest_event_sequence(), 0);
+        assert!(registry.store().iter(EVENT_PREFIX).unwrap().is_empty());
+    }
+
+    fn multi_controller_registry() -> DidRegistry {
+        let mut registry = DidRegistry::new();
+        registry.register_did_with_controllers(
+            "did:example:org".to_string(),
+            "QmOrg".to_string(),
+            vec!["did:example:a".to_string(), "did:example:b".to_string(), "did:example:c".to_string()],
+            2,
+            vec![],
+        ).unwrap();
+        registry
+    }
+
+    #[test]
+    fn multi_controller_update_succeeds_at_threshold() {
+        let mut registry = multi_controller_registry();
+
+        registry.update_did_document("did:example:org", "QmNew".to_string(), &["did:example:a", "did:example:c"]).unwrap();
+
+        assert_eq!(registry.get_did("did:example:org").unwrap().document_hash, "QmNew");
+    }
+
+    #[test]
+    fn multi_controller_update_fails_below_threshold() {
+        let mut registry = multi_controller_registry();
+
+        for authorizers in [&["did:example:a"][..], &["did:example:a", "did:example:a"], &["did:example:a", "did:example:stranger"]] {
+            assert!(registry.update_did_document("did:example:org", "QmNew".to_string(), authorizers).is_err());
+            assert!(registry.deactivate_did("did:example:org", authorizers).is_err());
+        }
+        assert!(registry.is_active("did:example:org"));
+        assert_eq!(registry.get_did("did:example:org").unwrap().document_hash, "QmOrg");
+    }
+
+    #[test]
+    fn threshold_must_be_reachable() {
+        let mut registry = DidRegistry::new();
+        let controllers = vec!["did:example:a".to_string(), "did:example:a".to_string()];
+
+        assert!(registry.register_did_with_controllers("did:example:org".to_string(), "QmOrg".to_string(), controllers, 2, vec![]).is_err());
+
+        let mut registry = multi_controller_registry();
+        registry.remove_controller("did:example:org", "did:example:c", &["did:example:a", "did:example:b"]).unwrap();
+        assert!(registry.remove_controller("did:example:org", "did:example:b", &["did:example:a", "did:example:b"]).is_err());
+    }
+
+    #[test]
+    fn legacy_single_controller_entries_still_load() {
+        let legacy = serde_json::json!({
+            "did": "did:example:old",
+            "document_hash": "QmOld",
+            "controller": "did:example:owner",
+            "created_at": "2024-01-01T00:00:00Z",
+            "updated_at": "2024-01-01T00:00:00Z",
+            "status": "Active",
+            "verification_methods": ["did:example:old#key-1"],
+            "metadata": {}
+        });
+        let mut store = MemoryStore::new();
+        store.put(&did_key("did:example:old"), serde_json::to_vec(&legacy).unwrap()).unwrap();
+
+        let mut registry = DidRegistry::with_store(store).unwrap();
+        let entry = registry.get_did("did:example:old").unwrap();
+        assert_eq!(entry.controllers, vec!["did:example:owner"]);
+        assert_eq!(entry.threshold, 1);
+
+        registry.update_did_document("did:example:old", "QmNew".to_string(), &["did:example:owner"]).unwrap();
+        let reopened = DidRegistry::with_store(registry.store().clone()).unwrap();
+        assert_eq!(reopened.get_did("did:example:old").unwrap().document_hash, "QmNew");
+    }
+
+    #[test]
+    fn registrations_are_announced_on_the_event_bus() {
+        let bus = EventBus::default();
+        let mut events = bus.subscribe();
+        let mut registry = DidRegistry::new();
+        registry.set_event_bus(bus);
+
+        let entry = batch_entry("did:example:alice");
+        registry.register_did(entry.did.clone(), entry.document_hash.clone(), entry.controller, entry.verification_methods).unwrap();
+        assert!(registry.register_did("did:example:alice".to_string(), "Qm0".to_string(), "did:example:controller".to_string(), Vec::new()).is_err());
+
+        match events.try_recv().unwrap() {
+            DomainEvent::DidRegistered { did, document_hash } => {
+                assert_eq!(did, entry.did);
+                assert_eq!(document_hash, entry.document_hash);
+            }
+            other => panic!("unexpected event {:?}", other),
+        }
+        assert!(events.try_recv().is_err());
+    }
+
+    #[test]
+    fn entries_record_the_hash_function_of_their_document() {
+        let mut registry = DidRegistry::new();
+        let blake3_cid = "bafkr4igxjga67jykbseaxdmmdgc5a5o3zp3htom2l6mrjznk7fvyggu6eq";
+        registry.register_did("did:example:alice".to_string(), blake3_cid.to_string(), "did:example:alice".to_string(), Vec::new()).unwrap();
+        assert_eq!(registry.get_did("did:example:alice").unwrap().hash_code, HashCode::Blake3_256);
+
+        let sha256_cid = "bafkreifzjut3te2nhyekklss27nh3k72ysco7y32koao5eei66wof36n5e";
+        registry.update_did_document("did:example:alice", sha256_cid.to_string(), &["did:example:alice"]).unwrap();
+        assert_eq!(registry.get_did("did:example:alice").unwrap().hash_code, HashCode::Sha2_256);
+    }
+
+    /// Register, update and deactivate a DID, then check the state survives reopening the store
+    fn did_lifecycle<S: RegistryStore + Clone>(store: S) {
+        let mut registry = DidRegistry::with_store(store).unwrap();
+        registry.register_did(
+            "did:example:alice".to_string(),
+            "QmFirst".to_string(),
+            "did:example:controller".to_string(),
+            vec!["did:example:alice#key-1".to_string()],
+        ).unwrap();
+        registry.register_batch(vec![batch_entry("did:example:bob")]).unwrap();
+        registry.update_did_document("did:example:alice", "QmSecond".to_string(), &["did:example:controller"]).unwrap();
+        registry.deactivate_did("did:example:bob", &["did:example:controller"]).unwrap();
+        registry.store().flush().unwrap();
+
+        let reopened = DidRegistry::with_store(registry.store().clone()).unwrap();
+        assert_eq!(reopened.get_did("did:example:alice").unwrap().document_hash, "QmSecond");
+        assert!(reopened.is_active("did:example:alice"));
+        assert!(!reopened.is_active("did:example:bob"));
+        assert_eq!(reopened.list_dids_by_controller("did:example:controller").len(), 2);
+        assert_eq!(reopened.latest_event_sequence(), 4);
+    }
+
+    #[test]
+    fn lifecycle_against_the_memory_store() {
+        did_lifecycle(MemoryStore::new());
+    }
+
+    #[cfg(feature = "sled")]
+    #[test]
+    fn lifecycle_against_a_sled_store() {
+        let path = std::env::temp_dir().join(format!("did-registry-{}", uuid::Uuid::new_v4()));
+        did_lifecycle(crate::store::SledStore::open(&path).unwrap());
+
+        // A fresh handle on the same database sees everything written
+        let reopened = DidRegistry::with_store(crate::store::SledStore::open(&path).unwrap()).unwrap();
+        assert_eq!(reopened.get_did("did:example:alice").unwrap().document_hash, "QmSecond");
+        drop(reopened);
+        std::fs::remove_dir_all(&path).unwrap();
+    }
+}
@@ -0,0 +1,197 @@
+//! Structured registry events for indexers
+
+use serde::{Deserialize, Serialize};
+use chrono::{DateTime, Utc};
+
+/// Kind of state change recorded by a registry
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Hash)]
+pub enum RegistryEventType {
+    DidRegistered,
+    DidUpdated,
+    DidDeactivated,
+    ControllerAdded,
+    ControllerRemoved,
+    CredentialRegistered,
+    CredentialAttested,
+    CredentialRevoked,
+    CredentialReinstated,
+    CredentialExpired,
+    SchemaRegistered,
+}
+
+/// Event emitted by a registry, ordered by a monotonic sequence id
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RegistryEvent {
+    pub sequence: u64,
+    pub event_type: RegistryEventType,
+    pub did: Option<String>,
+    pub credential_id: Option<String>,
+    pub timestamp: DateTime<Utc>,
+}
+
+/// Filter for pulling a subset of registry events
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct EventFilter {
+    /// Only events with a sequence id greater than this
+    pub since: Option<u64>,
+    /// Only events of these types; empty matches every type
+    pub event_types: Vec<RegistryEventType>,
+    pub did: Option<String>,
+    pub credential_id: Option<String>,
+}
+
+impl EventFilter {
+    /// Create a filter matching every event
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Only match events after the given sequence id
+    pub fn with_since(mut self, sequence: u64) -> Self {
+        self.since = Some(sequence);
+        self
+    }
+
+    /// Add an event type to match
+    pub fn with_event_type(mut self, event_type: RegistryEventType) -> Self {
+        self.event_types.push(event_type);
+        self
+    }
+
+    /// Only match events concerning a DID
+    pub fn with_did(mut self, did: impl Into<String>) -> Self {
+        self.did = Some(did.into());
+        self
+    }
+
+    /// Only match events concerning a credential
+    pub fn with_credential_id(mut self, credential_id: impl Into<String>) -> Self {
+        self.credential_id = Some(credential_id.into());
+        self
+    }
+
+    /// Check whether an event passes the filter
+    pub fn matches(&self, event: &RegistryEvent) -> bool {
+        self.since.is_none_or(|since| event.sequence > since)
+            && (self.event_types.is_empty() || self.event_types.contains(&event.event_type))
+            && (self.did.is_none() || event.did == self.did)
+            && (self.credential_id.is_none() || event.credential_id == self.credential_id)
+    }
+}
+
+/// Event type and the DID and credential an event concerns, before it is numbered
+pub type PendingEvent = (RegistryEventType, Option<String>, Option<String>);
+
+/// Append-only event log with monotonic sequence ids starting at 1
+#[derive(Debug, Clone, Default)]
+pub struct EventLog {
+    events: Vec<RegistryEvent>,
+}
+
+impl EventLog {
+    /// Create an empty event log
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Rebuild a log from persisted events, which must be numbered contiguously from 1
+    pub fn from_events(events: Vec<RegistryEvent>) -> Result<Self, String> {
+        for (index, event) in events.iter().enumerate() {
+            if event.sequence != index as u64 + 1 {
+                return Err(format!("Event log has a gap: expected sequence {}, found {}", index + 1, event.sequence));
+            }
+        }
+        Ok(Self { events })
+    }
+
+    /// Append an event and return its sequence id
+    pub fn record(
+        &mut self,
+        event_type: RegistryEventType,
+        did: Option<String>,
+        credential_id: Option<String>,
+    ) -> u64 {
+        let events = self.prepare(vec![(event_type, did, credential_id)]);
+        self.extend(events);
+        self.latest_sequence()
+    }
+
+    /// Number the next events without appending them, so they can be persisted first
+    pub fn prepare(&self, pending: Vec<PendingEvent>) -> Vec<RegistryEvent> {
+        let timestamp = Utc::now();
+        pending.into_iter()
+            .zip(self.latest_sequence() + 1..)
+            .map(|((event_type, did, credential_id), sequence)| RegistryEvent {
+                sequence,
+                event_type,
+                did,
+                credential_id,
+                timestamp,
+            })
+            .collect()
+    }
+
+    /// Append events numbered by `prepare`
+    pub fn extend(&mut self, events: Vec<RegistryEvent>) {
+        debug_assert!(events.first().is_none_or(|event| event.sequence == self.latest_sequence() + 1));
+        self.events.extend(events);
+    }
+
+    /// Sequence id of the most recent event, or 0 if none
+    pub fn latest_sequence(&self) -> u64 {
+        self.events.last().map(|event| event.sequence).unwrap_or(0)
+    }
+
+    /// Events matching a filter, in sequence order
+    pub fn query(&self, filter: &EventFilter) -> Vec<RegistryEvent> {
+        // Sequence ids are contiguous, so `since` maps directly to an index
+        let start = filter.since.map(|since| since.min(self.events.len() as u64) as usize).unwrap_or(0);
+        self.events[start..].iter()
+            .filter(|event| filter.matches(event))
+            .cloned()
+            .collect()
+    }
+}
+
+/// Store key of a persisted event; the padded sequence id keeps keys in sequence order
+pub(crate) fn event_key(prefix: &str, sequence: u64) -> String {
+    format!("{}{:020}", prefix, sequence)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn prepared_events_continue_the_sequence() {
+        let mut log = EventLog::new();
+        log.record(RegistryEventType::SchemaRegistered, None, None);
+
+        let events = log.prepare(vec![
+            (RegistryEventType::DidRegistered, Some("did:example:alice".to_string()), None),
+            (RegistryEventType::DidRegistered, Some("did:example:bob".to_string()), None),
+        ]);
+        assert_eq!(log.latest_sequence(), 1);
+
+        log.extend(events);
+        assert_eq!(log.latest_sequence(), 3);
+        let filter = EventFilter::new().with_since(1).with_did("did:example:bob");
+        assert_eq!(log.query(&filter).iter().map(|event| event.sequence).collect::<Vec<_>>(), vec![3]);
+    }
+
+    #[test]
+    fn restoring_a_log_with_a_gap_fails() {
+        let mut log = EventLog::new();
+        log.record(RegistryEventType::SchemaRegistered, None, None);
+        log.record(RegistryEventType::SchemaRegistered, None, None);
+        let mut events = log.query(&EventFilter::new());
+        events.remove(0);
+
+        assert!(EventLog::from_events(events).is_err());
+    }
+
+    #[test]
+    fn event_keys_sort_by_sequence() {
+        assert!(event_key("did_event/", 9) < event_key("did_event/", 10));
+    }
+}
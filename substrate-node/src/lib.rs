@@ -6,7 +6,17 @@
 pub mod did_registry;
 pub mod credential_registry;
 pub mod verification;
+pub mod events;
+#[cfg(feature = "ipfs")]
+pub mod resolver;
+pub mod store;
+pub mod anchor;
 
 pub use did_registry::*;
 pub use credential_registry::*;
 pub use verification::*;
+pub use events::*;
+#[cfg(feature = "ipfs")]
+pub use resolver::*;
+pub use store::*;
+pub use anchor::*;
@@ -0,0 +1,142 @@
+//! DID resolution from the on-chain registry and IPFS
+
+use async_trait::async_trait;
+use std::sync::{Arc, RwLock};
+use identity_core::{DidDocument, DidResolver, IdentityError};
+use ipfs_client::{Compression, IpfsClient, IpfsError, COMPRESSION_METADATA_KEY};
+use crate::did_registry::{DidRegistry, DidStatus};
+use crate::store::{MemoryStore, RegistryStore};
+
+/// Resolver reading a DID's document hash from the registry and the document from IPFS.
+///
+/// Documents stored compressed must have their compression recorded in the entry's metadata
+/// under `COMPRESSION_METADATA_KEY`; entries without it are read as uncompressed.
+pub struct RegistryResolver<S: RegistryStore = MemoryStore> {
+    registry: Arc<RwLock<DidRegistry<S>>>,
+    ipfs: Arc<IpfsClient>,
+}
+
+impl<S: RegistryStore> RegistryResolver<S> {
+    /// Create a resolver over a shared registry and IPFS client
+    pub fn new(registry: Arc<RwLock<DidRegistry<S>>>, ipfs: Arc<IpfsClient>) -> Self {
+        Self { registry, ipfs }
+    }
+}
+
+#[async_trait]
+impl<S: RegistryStore> DidResolver for RegistryResolver<S> {
+    async fn resolve(&self, did: &str) -> Result<DidDocument, IdentityError> {
+        let (document_hash, compression, controllers) = {
+            let registry = self.registry.read()
+                .map_err(|_| IdentityError::StorageError("DID registry lock poisoned".to_string()))?;
+            let entry = registry.get_did(did)
+                .ok_or_else(|| IdentityError::NotFound(format!("DID not registered: {}", did)))?;
+            if entry.status != DidStatus::Active {
+                return Err(IdentityError::InvalidDid(format!("DID is not active: {}", did)));
+            }
+            let compression = Compression::from_name(entry.metadata.get(COMPRESSION_METADATA_KEY).map(String::as_str))
+                .map_err(|e| IdentityError::StorageError(e.to_string()))?;
+            (entry.document_hash.clone(), compression, entry.controllers.clone())
+        };
+
+        // The IPFS backend's response streams are not `Send`, so drive the fetch off this task
+        let ipfs = self.ipfs.clone();
+        let handle = tokio::runtime::Handle::current();
+        let document = tokio::task::spawn_blocking(move || handle.block_on(ipfs.get_did_document(&document_hash, compression)))
+            .await
+            .map_err(|e| IdentityError::NetworkError(format!("DID document fetch failed: {}", e)))?
+            .map_err(|e| match e {
+                IpfsError::NotFound(message) => IdentityError::NotFound(message),
+                e => IdentityError::NetworkError(e.to_string()),
+            })?;
+
+        // A swapped or tampered document must not be served for this DID
+        document.check_integrity(did, &controllers)?;
+        Ok(document)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use identity_core::utils::create_did_document_with_id;
+    use identity_core::KeyType;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use tokio::net::TcpListener;
+
+    /// IPFS node answering every request with `document` as the content
+    async fn mock_ipfs(document: &DidDocument) -> Arc<IpfsClient> {
+        let body = serde_json::to_vec(document).unwrap();
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let address = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            while let Ok((mut socket, _)) = listener.accept().await {
+                let mut buffer = [0u8; 4096];
+                let _ = socket.read(&mut buffer).await;
+                let header = format!(
+                    "HTTP/1.1 200 OK\r\nContent-Type: text/plain\r\nContent-Length: {}\r\nConnection: close\r\n\r\n",
+                    body.len()
+                );
+                let _ = socket.write_all(header.as_bytes()).await;
+                let _ = socket.write_all(&body).await;
+            }
+        });
+        Arc::new(IpfsClient::new(&format!("http://{}", address)).unwrap())
+    }
+
+    /// Resolver whose registry maps `did:example:alice` to whatever the node serves
+    async fn resolver(served: &DidDocument) -> RegistryResolver {
+        let mut registry = DidRegistry::new();
+        registry.register_did_with_controllers(
+            "did:example:alice".to_string(),
+            "QmAlice".to_string(),
+            vec!["did:example:alice".to_string(), "did:example:guardian".to_string()],
+            1,
+            vec!["did:example:alice#key-1".to_string()],
+        ).unwrap();
+        RegistryResolver::new(Arc::new(RwLock::new(registry)), mock_ipfs(served).await)
+    }
+
+    fn document(did: &str) -> DidDocument {
+        create_did_document_with_id(did.to_string(), KeyType::Ed25519).unwrap().0
+    }
+
+    #[tokio::test]
+    async fn consistent_document_resolves() {
+        let mut served = document("did:example:alice");
+        served.verification_method.as_mut().unwrap()[0].controller = "did:example:guardian".to_string();
+
+        let resolved = resolver(&served).await.resolve("did:example:alice").await.unwrap();
+
+        assert_eq!(resolved, served);
+    }
+
+    #[tokio::test]
+    async fn swapped_document_is_rejected() {
+        let resolver = resolver(&document("did:example:mallory")).await;
+
+        let error = resolver.resolve("did:example:alice").await.unwrap_err();
+
+        assert!(matches!(error, IdentityError::VerificationError(_)), "{:?}", error);
+    }
+
+    #[tokio::test]
+    async fn foreign_controlled_or_foreign_method_is_rejected() {
+        let mut foreign_controller = document("did:example:alice");
+        foreign_controller.verification_method.as_mut().unwrap()[0].controller = "did:example:mallory".to_string();
+        let error = resolver(&foreign_controller).await.resolve("did:example:alice").await.unwrap_err();
+        assert!(matches!(error, IdentityError::VerificationError(_)), "{:?}", error);
+
+        let mut foreign_method = document("did:example:alice");
+        foreign_method.verification_method.as_mut().unwrap()[0].id = "did:example:mallory#key-1".to_string();
+        let error = resolver(&foreign_method).await.resolve("did:example:alice").await.unwrap_err();
+        assert!(matches!(error, IdentityError::VerificationError(_)), "{:?}", error);
+    }
+
+    #[tokio::test]
+    async fn unregistered_did_is_not_found() {
+        let resolver = resolver(&document("did:example:alice")).await;
+
+        assert!(matches!(resolver.resolve("did:example:bob").await, Err(IdentityError::NotFound(_))));
+    }
+}
@@ -0,0 +1,368 @@
+//! Pluggable key-value persistence for the registries
+
+use std::collections::BTreeMap;
+
+/// Key-value storage backing a registry; keys are namespaced by a `kind/` prefix
+pub trait RegistryStore: Send + Sync {
+    /// Get the value stored under a key
+    fn get(&self, key: &str) -> Result<Option<Vec<u8>>, String>;
+
+    /// Store a value, replacing any previous one
+    fn put(&mut self, key: &str, value: Vec<u8>) -> Result<(), String>;
+
+    /// Remove a key; removing a missing key is not an error
+    fn delete(&mut self, key: &str) -> Result<(), String>;
+
+    /// Entries whose key starts with `prefix`, in key order
+    fn iter(&self, prefix: &str) -> Result<Vec<(String, Vec<u8>)>, String>;
+
+    /// Apply every write in a batch, or none of them.
+    ///
+    /// The default applies the writes in order and restores the previous values if one fails;
+    /// stores with native atomic batches override it.
+    fn write_batch(&mut self, batch: WriteBatch) -> Result<(), String> {
+        let mut applied: Vec<(String, Option<Vec<u8>>)> = Vec::new();
+        for write in batch.writes {
+            let key = write.key().to_string();
+            let result = self.get(&key).and_then(|previous| {
+                match write {
+                    BatchWrite::Put(key, value) => self.put(&key, value)?,
+                    BatchWrite::Delete(key) => self.delete(&key)?,
+                }
+                Ok(previous)
+            });
+
+            match result {
+                Ok(previous) => applied.push((key, previous)),
+                Err(e) => {
+                    for (key, previous) in applied.into_iter().rev() {
+                        let restored = match previous {
+                            Some(value) => self.put(&key, value),
+                            None => self.delete(&key),
+                        };
+                        restored.map_err(|rollback| format!("{}; rolling back {} failed: {}", e, key, rollback))?;
+                    }
+                    return Err(e);
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Make every write so far durable
+    fn flush(&self) -> Result<(), String> {
+        Ok(())
+    }
+}
+
+/// Writes a registry commits to its store together
+#[derive(Debug, Clone, Default)]
+pub struct WriteBatch {
+    writes: Vec<BatchWrite>,
+}
+
+/// One write of a batch
+#[derive(Debug, Clone, PartialEq)]
+pub enum BatchWrite {
+    Put(String, Vec<u8>),
+    Delete(String),
+}
+
+impl WriteBatch {
+    /// Create an empty batch
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Store a value under a key
+    pub fn put(&mut self, key: impl Into<String>, value: Vec<u8>) {
+        self.writes.push(BatchWrite::Put(key.into(), value));
+    }
+
+    /// Remove a key
+    pub fn delete(&mut self, key: impl Into<String>) {
+        self.writes.push(BatchWrite::Delete(key.into()));
+    }
+
+    /// Writes in the order they were added
+    pub fn writes(&self) -> &[BatchWrite] {
+        &self.writes
+    }
+
+    /// Whether the batch has no writes
+    pub fn is_empty(&self) -> bool {
+        self.writes.is_empty()
+    }
+}
+
+impl BatchWrite {
+    /// Key the write targets
+    pub fn key(&self) -> &str {
+        match self {
+            BatchWrite::Put(key, _) | BatchWrite::Delete(key) => key,
+        }
+    }
+}
+
+/// Store that keeps everything in memory; the default for registries
+#[derive(Debug, Clone, Default)]
+pub struct MemoryStore {
+    entries: BTreeMap<String, Vec<u8>>,
+}
+
+impl MemoryStore {
+    /// Create an empty store
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl RegistryStore for MemoryStore {
+    fn get(&self, key: &str) -> Result<Option<Vec<u8>>, String> {
+        Ok(self.entries.get(key).cloned())
+    }
+
+    fn put(&mut self, key: &str, value: Vec<u8>) -> Result<(), String> {
+        self.entries.insert(key.to_string(), value);
+        Ok(())
+    }
+
+    fn delete(&mut self, key: &str) -> Result<(), String> {
+        self.entries.remove(key);
+        Ok(())
+    }
+
+    fn iter(&self, prefix: &str) -> Result<Vec<(String, Vec<u8>)>, String> {
+        Ok(self.entries.range(prefix.to_string()..)
+            .take_while(|(key, _)| key.starts_with(prefix))
+            .map(|(key, value)| (key.clone(), value.clone()))
+            .collect())
+    }
+}
+
+/// Store backed by an embedded sled database
+#[cfg(feature = "sled")]
+#[derive(Debug, Clone)]
+pub struct SledStore {
+    tree: sled::Tree,
+}
+
+#[cfg(feature = "sled")]
+impl SledStore {
+    /// Open or create a database at a path
+    pub fn open(path: impl AsRef<std::path::Path>) -> Result<Self, String> {
+        let db = sled::open(path).map_err(|e| format!("Failed to open sled store: {}", e))?;
+        Ok(Self::from_tree((*db).clone()))
+    }
+
+    /// Use an existing tree, e.g. one of several registries sharing a database
+    pub fn from_tree(tree: sled::Tree) -> Self {
+        Self { tree }
+    }
+}
+
+#[cfg(feature = "sled")]
+impl RegistryStore for SledStore {
+    fn get(&self, key: &str) -> Result<Option<Vec<u8>>, String> {
+        self.tree.get(key)
+            .map(|value| value.map(|value| value.to_vec()))
+            .map_err(|e| format!("sled get failed: {}", e))
+    }
+
+    fn put(&mut self, key: &str, value: Vec<u8>) -> Result<(), String> {
+        self.tree.insert(key, value)
+            .map(|_| ())
+            .map_err(|e| format!("sled put failed: {}", e))
+    }
+
+    fn delete(&mut self, key: &str) -> Result<(), String> {
+        self.tree.remove(key)
+            .map(|_| ())
+            .map_err(|e| format!("sled delete failed: {}", e))
+    }
+
+    fn iter(&self, prefix: &str) -> Result<Vec<(String, Vec<u8>)>, String> {
+        self.tree.scan_prefix(prefix)
+            .map(|item| {
+                let (key, value) = item.map_err(|e| format!("sled scan failed: {}", e))?;
+                Ok((String::from_utf8_lossy(&key).into_owned(), value.to_vec()))
+            })
+            .collect()
+    }
+
+    fn write_batch(&mut self, batch: WriteBatch) -> Result<(), String> {
+        let mut sled_batch = sled::Batch::default();
+        for write in batch.writes {
+            match write {
+                BatchWrite::Put(key, value) => sled_batch.insert(key.as_bytes(), value),
+                BatchWrite::Delete(key) => sled_batch.remove(key.as_bytes()),
+            }
+        }
+        self.tree.apply_batch(sled_batch)
+            .map_err(|e| format!("sled batch failed: {}", e))
+    }
+
+    fn flush(&self) -> Result<(), String> {
+        self.tree.flush()
+            .map(|_| ())
+            .map_err(|e| format!("sled flush failed: {}", e))
+    }
+}
+
+/// Store backed by a RocksDB database
+#[cfg(feature = "rocksdb")]
+pub struct RocksDbStore {
+    db: rocksdb::DB,
+}
+
+#[cfg(feature = "rocksdb")]
+impl RocksDbStore {
+    /// Open or create a database at a path
+    pub fn open(path: impl AsRef<std::path::Path>) -> Result<Self, String> {
+        let mut options = rocksdb::Options::default();
+        options.create_if_missing(true);
+        let db = rocksdb::DB::open(&options, path).map_err(|e| format!("Failed to open RocksDB store: {}", e))?;
+        Ok(Self { db })
+    }
+}
+
+#[cfg(feature = "rocksdb")]
+impl RegistryStore for RocksDbStore {
+    fn get(&self, key: &str) -> Result<Option<Vec<u8>>, String> {
+        self.db.get(key).map_err(|e| format!("RocksDB get failed: {}", e))
+    }
+
+    fn put(&mut self, key: &str, value: Vec<u8>) -> Result<(), String> {
+        self.db.put(key, value).map_err(|e| format!("RocksDB put failed: {}", e))
+    }
+
+    fn delete(&mut self, key: &str) -> Result<(), String> {
+        self.db.delete(key).map_err(|e| format!("RocksDB delete failed: {}", e))
+    }
+
+    fn iter(&self, prefix: &str) -> Result<Vec<(String, Vec<u8>)>, String> {
+        let mode = rocksdb::IteratorMode::From(prefix.as_bytes(), rocksdb::Direction::Forward);
+        let mut entries = Vec::new();
+        for item in self.db.iterator(mode) {
+            let (key, value) = item.map_err(|e| format!("RocksDB scan failed: {}", e))?;
+            if !key.starts_with(prefix.as_bytes()) {
+                break;
+            }
+            entries.push((String::from_utf8_lossy(&key).into_owned(), value.to_vec()));
+        }
+        Ok(entries)
+    }
+
+    fn write_batch(&mut self, batch: WriteBatch) -> Result<(), String> {
+        let mut rocks_batch = rocksdb::WriteBatch::default();
+        for write in batch.writes {
+            match write {
+                BatchWrite::Put(key, value) => rocks_batch.put(key, value),
+                BatchWrite::Delete(key) => rocks_batch.delete(key),
+            }
+        }
+        self.db.write(rocks_batch)
+            .map_err(|e| format!("RocksDB batch failed: {}", e))
+    }
+
+    fn flush(&self) -> Result<(), String> {
+        self.db.flush().map_err(|e| format!("RocksDB flush failed: {}", e))
+    }
+}
+
+/// Serialize a registry record for storage
+pub(crate) fn encode_record<T: serde::Serialize>(record: &T) -> Result<Vec<u8>, String> {
+    serde_json::to_vec(record).map_err(|e| format!("Failed to encode registry record: {}", e))
+}
+
+/// Load every record under a prefix, keyed by the part of the key after the prefix
+pub(crate) fn load_records<S, T>(store: &S, prefix: &str) -> Result<Vec<(String, T)>, String>
+where
+    S: RegistryStore + ?Sized,
+    T: serde::de::DeserializeOwned,
+{
+    store.iter(prefix)?
+        .into_iter()
+        .map(|(key, value)| {
+            let record = serde_json::from_slice(&value)
+                .map_err(|e| format!("Corrupt registry record {}: {}", key, e))?;
+            Ok((key[prefix.len()..].to_string(), record))
+        })
+        .collect()
+}
+
+/// Store that fails writes to one key, for exercising partial-failure paths
+#[cfg(test)]
+#[derive(Debug, Clone, Default)]
+pub(crate) struct FailingStore {
+    pub inner: MemoryStore,
+    pub fail_key: Option<String>,
+}
+
+#[cfg(test)]
+impl RegistryStore for FailingStore {
+    fn get(&self, key: &str) -> Result<Option<Vec<u8>>, String> {
+        self.inner.get(key)
+    }
+
+    fn put(&mut self, key: &str, value: Vec<u8>) -> Result<(), String> {
+        if self.fail_key.as_deref() == Some(key) {
+            return Err(format!("write to {} failed", key));
+        }
+        self.inner.put(key, value)
+    }
+
+    fn delete(&mut self, key: &str) -> Result<(), String> {
+        self.inner.delete(key)
+    }
+
+    fn iter(&self, prefix: &str) -> Result<Vec<(String, Vec<u8>)>, String> {
+        self.inner.iter(prefix)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn write_batch_applies_puts_and_deletes() {
+        let mut store = MemoryStore::new();
+        store.put("a", b"old".to_vec()).unwrap();
+
+        let mut batch = WriteBatch::new();
+        batch.delete("a");
+        batch.put("b", b"new".to_vec());
+        store.write_batch(batch).unwrap();
+
+        assert_eq!(store.get("a").unwrap(), None);
+        assert_eq!(store.get("b").unwrap(), Some(b"new".to_vec()));
+    }
+
+    #[test]
+    fn failed_write_batch_restores_earlier_writes() {
+        let mut store = FailingStore { fail_key: Some("c".to_string()), ..Default::default() };
+        store.put("a", b"old".to_vec()).unwrap();
+
+        let mut batch = WriteBatch::new();
+        batch.put("a", b"new".to_vec());
+        batch.put("b", b"new".to_vec());
+        batch.put("c", b"new".to_vec());
+
+        assert!(store.write_batch(batch).is_err());
+        assert_eq!(store.get("a").unwrap(), Some(b"old".to_vec()));
+        assert_eq!(store.get("b").unwrap(), None);
+        assert_eq!(store.get("c").unwrap(), None);
+    }
+
+    #[test]
+    fn iter_returns_only_prefixed_keys_in_order() {
+        let mut store = MemoryStore::new();
+        store.put("did/b", vec![2]).unwrap();
+        store.put("did/a", vec![1]).unwrap();
+        store.put("credential/a", vec![3]).unwrap();
+
+        let keys: Vec<String> = store.iter("did/").unwrap().into_iter().map(|(key, _)| key).collect();
+        assert_eq!(keys, vec!["did/a", "did/b"]);
+    }
+}
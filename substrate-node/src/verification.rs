@@ -1,5 +1,123 @@
 //! Verification logic for Substrate
 
+use bls12_381::hash_to_curve::{ExpandMsgXmd, HashToCurve};
+use bls12_381::{multi_miller_loop, G1Affine, G2Affine, G2Prepared, G2Projective, Gt};
+
+/// Prefix of the attestors' hash-to-curve domain separation tag; the scheme id completes it
+const THRESHOLD_DST_PREFIX: &[u8] = b"DIMS_THRESHOLD_BLS12381G2_XMD:SHA-256_SSWU_RO_";
+
 pub fn verify_credential_hash(stored_hash: &[u8], provided_hash: &[u8]) -> bool {
     stored_hash == provided_hash
 }
+
+/// Verify the threshold signature proving an attestation, not just a hash match.
+///
+/// `signature_bytes` is the combined signature as a compressed G2 point and `public_key_bytes`
+/// the scheme's compressed G1 public key. Malformed input and identity points verify as false.
+pub fn verify_attestation_signature(
+    message: &[u8],
+    signature_bytes: &[u8],
+    public_key_bytes: &[u8],
+    scheme_id: &str,
+) -> bool {
+    let (Ok(signature), Ok(public_key)) = (<[u8; 96]>::try_from(signature_bytes), <[u8; 48]>::try_from(public_key_bytes)) else {
+        return false;
+    };
+    let (Some(signature), Some(public_key)) = (
+        Option::<G2Affine>::from(G2Affine::from_compressed(&signature)),
+        Option::<G1Affine>::from(G1Affine::from_compressed(&public_key)),
+    ) else {
+        return false;
+    };
+    // The identity key and signature satisfy the pairing equation for every message
+    if bool::from(signature.is_identity()) || bool::from(public_key.is_identity()) {
+        return false;
+    }
+
+    let dst = [THRESHOLD_DST_PREFIX, scheme_id.as_bytes()].concat();
+    let message_hash = <G2Projective as HashToCurve<ExpandMsgXmd<sha2_v09::Sha256>>>::hash_to_curve(message, &dst);
+
+    // e(pk, H(m)) * e(-g1, sig) == 1
+    multi_miller_loop(&[
+        (&public_key, &G2Prepared::from(G2Affine::from(message_hash))),
+        (&-G1Affine::generator(), &G2Prepared::from(signature)),
+    ])
+    .final_exponentiation()
+        == Gt::identity()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use attestors::ThresholdScheme;
+
+    /// Compressed threshold signature over `message` by parties 1 and 2 of a 2-of-3 scheme
+    fn sign(scheme: &ThresholdScheme, message: &[u8]) -> (Vec<u8>, Vec<u8>) {
+        let (shares, public_key) = scheme.generate_key_shares().unwrap();
+        let partials: Vec<_> = shares[..2]
+            .iter()
+            .map(|share| scheme.partial_sign(message, share).unwrap())
+            .collect();
+        let signature = scheme.combine_signatures(&partials).unwrap();
+        (signature.signature, public_key.public_key)
+    }
+
+    #[test]
+    fn genuine_threshold_signature_verifies() {
+        let scheme = ThresholdScheme::new(2, 3).unwrap();
+        let (signature, public_key) = sign(&scheme, b"credential digest");
+
+        assert!(verify_attestation_signature(
+            b"credential digest",
+            &signature,
+            &public_key,
+            &scheme.scheme_id
+        ));
+        assert!(!verify_attestation_signature(
+            b"other digest",
+            &signature,
+            &public_key,
+            &scheme.scheme_id
+        ));
+        assert!(!verify_attestation_signature(
+            b"credential digest",
+            &signature,
+            &public_key,
+            "other-scheme"
+        ));
+    }
+
+    #[test]
+    fn forged_or_malformed_signatures_are_rejected() {
+        let scheme = ThresholdScheme::new(2, 3).unwrap();
+        let (_, public_key) = sign(&scheme, b"credential digest");
+        // Signed by a different set of key shares than the registered public key
+        let (forged, _) = sign(&scheme, b"credential digest");
+        let identity = G2Affine::identity().to_compressed();
+
+        assert!(!verify_attestation_signature(
+            b"credential digest",
+            &forged,
+            &public_key,
+            &scheme.scheme_id
+        ));
+        assert!(!verify_attestation_signature(
+            b"credential digest",
+            b"not a point",
+            &public_key,
+            &scheme.scheme_id
+        ));
+        assert!(!verify_attestation_signature(
+            b"credential digest",
+            &identity,
+            &G1Affine::identity().to_compressed(),
+            &scheme.scheme_id
+        ));
+        assert!(!verify_attestation_signature(
+            b"credential digest",
+            &forged,
+            &[0u8; 48],
+            &scheme.scheme_id
+        ));
+    }
+}